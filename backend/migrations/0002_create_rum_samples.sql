@@ -0,0 +1,10 @@
+-- Real user monitoring samples. Deliberately no IP, user agent or user id.
+CREATE TABLE rum_samples (
+    id BIGSERIAL PRIMARY KEY,
+    metric VARCHAR(64) NOT NULL,
+    value DOUBLE PRECISION NOT NULL,
+    path VARCHAR(200) NOT NULL,
+    recorded_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX rum_samples_metric_recorded_at_idx ON rum_samples (metric, recorded_at);
@@ -0,0 +1,4 @@
+-- Trigram similarity for duplicate detection on event titles.
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+
+CREATE INDEX events_title_trgm_idx ON events USING gin (title gin_trgm_ops);
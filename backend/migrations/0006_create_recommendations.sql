@@ -0,0 +1,23 @@
+-- Per-user read history; one row per user and event, refreshed on each view.
+CREATE TABLE event_reads (
+    user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    event_id UUID NOT NULL REFERENCES events (id) ON DELETE CASCADE,
+    viewed_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, event_id)
+);
+
+CREATE TABLE favorites (
+    user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    event_id UUID NOT NULL REFERENCES events (id) ON DELETE CASCADE,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, event_id)
+);
+
+-- Rebuilt by the nightly recommendations job.
+CREATE TABLE recommendations (
+    user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    event_id UUID NOT NULL REFERENCES events (id) ON DELETE CASCADE,
+    score DOUBLE PRECISION NOT NULL,
+    computed_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, event_id)
+);
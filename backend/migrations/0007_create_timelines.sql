@@ -0,0 +1,14 @@
+CREATE TABLE timelines (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    owner_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    title VARCHAR(255) NOT NULL,
+    description TEXT,
+    -- Set when the timeline is frozen; archived timelines are read-only.
+    archived_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+ALTER TABLE events ADD COLUMN timeline_id UUID REFERENCES timelines (id) ON DELETE CASCADE;
+
+CREATE INDEX events_timeline_id_idx ON events (timeline_id);
@@ -0,0 +1,30 @@
+CREATE TABLE email_templates (
+    key VARCHAR(50) NOT NULL,
+    locale VARCHAR(10) NOT NULL,
+    subject VARCHAR(255) NOT NULL,
+    -- Plain text with {{variable}} placeholders.
+    body TEXT NOT NULL,
+    updated_by UUID REFERENCES users (id) ON DELETE SET NULL,
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (key, locale)
+);
+
+INSERT INTO email_templates (key, locale, subject, body) VALUES
+(
+    'verification',
+    'en',
+    'Confirm your Timeline Explorer account',
+    E'Hi {{username}},\n\nPlease confirm your email address by opening the link below:\n\n{{verify_url}}\n\nIf you did not sign up, you can ignore this message.'
+),
+(
+    'digest',
+    'en',
+    'Your Timeline Explorer digest',
+    E'Hi {{username}},\n\nHere is what happened on your timelines:\n\n{{events}}\n\nTo stop receiving these emails: {{unsubscribe_url}}'
+),
+(
+    'approval',
+    'en',
+    'Your event "{{event_title}}" was {{status}}',
+    E'Hi {{username}},\n\nYour submission "{{event_title}}" was {{status}}.\n\n{{event_url}}'
+);
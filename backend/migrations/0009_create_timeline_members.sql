@@ -0,0 +1,13 @@
+ALTER TABLE timelines ADD COLUMN is_private BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE TABLE timeline_members (
+    timeline_id UUID NOT NULL REFERENCES timelines (id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    -- 'viewer' or 'editor'; owners are not listed here.
+    role VARCHAR(20) NOT NULL CHECK (role IN ('viewer', 'editor')),
+    invited_by UUID REFERENCES users (id) ON DELETE SET NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (timeline_id, user_id)
+);
+
+CREATE INDEX timeline_members_user_id_idx ON timeline_members (user_id);
@@ -0,0 +1,6 @@
+-- 'draft' events are only visible to people who can edit them; 'archived'
+-- events are kept but hidden like drafts. Existing events stay published.
+ALTER TABLE events ADD COLUMN status VARCHAR(20) NOT NULL DEFAULT 'published'
+    CHECK (status IN ('draft', 'published', 'archived'));
+
+CREATE INDEX events_status_idx ON events (status);
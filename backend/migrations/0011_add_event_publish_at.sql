@@ -0,0 +1,4 @@
+-- When set on a draft, the scheduler publishes the event at this time (UTC).
+ALTER TABLE events ADD COLUMN publish_at TIMESTAMP;
+
+CREATE INDEX events_publish_at_idx ON events (publish_at) WHERE status = 'draft';
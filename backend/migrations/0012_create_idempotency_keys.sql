@@ -0,0 +1,17 @@
+-- Responses to POST requests sent with an Idempotency-Key header, replayed
+-- when the client retries with the same key. Kept for 24 hours.
+CREATE TABLE idempotency_keys (
+    -- The caller's user id; the nil UUID for anonymous requests.
+    user_id UUID NOT NULL,
+    key VARCHAR(255) NOT NULL,
+    -- SHA-256 of the method, path, query and body, to spot reused keys.
+    request_hash CHAR(64) NOT NULL,
+    -- NULL while the first request is still being handled.
+    status SMALLINT,
+    content_type VARCHAR(255),
+    body BYTEA,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, key)
+);
+
+CREATE INDEX idempotency_keys_created_at_idx ON idempotency_keys (created_at);
@@ -0,0 +1,12 @@
+-- Detail page views, written in batches by the event_views job.
+ALTER TABLE events ADD COLUMN views BIGINT NOT NULL DEFAULT 0;
+
+-- Views per event and day, for trending over a recent window.
+CREATE TABLE event_views (
+    event_id UUID NOT NULL REFERENCES events (id) ON DELETE CASCADE,
+    day DATE NOT NULL,
+    views BIGINT NOT NULL,
+    PRIMARY KEY (event_id, day)
+);
+
+CREATE INDEX event_views_day_idx ON event_views (day);
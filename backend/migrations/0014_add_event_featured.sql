@@ -0,0 +1,5 @@
+-- Events picked by an admin for the Home page carousel.
+ALTER TABLE events ADD COLUMN featured BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE INDEX events_featured_idx ON events (start_date) WHERE featured;
+CREATE INDEX events_created_at_idx ON events (created_at);
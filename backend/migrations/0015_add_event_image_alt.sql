@@ -0,0 +1,2 @@
+-- Text alternative for the event's image, read out by screen readers.
+ALTER TABLE events ADD COLUMN image_alt VARCHAR(255);
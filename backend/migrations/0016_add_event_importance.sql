@@ -0,0 +1,4 @@
+-- How significant the event is, from 1 (minor) to 5 (major). Zoomed-out
+-- timelines show only the more important events.
+ALTER TABLE events ADD COLUMN importance SMALLINT NOT NULL DEFAULT 3
+    CHECK (importance BETWEEN 1 AND 5);
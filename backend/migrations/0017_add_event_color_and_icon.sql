@@ -0,0 +1,5 @@
+-- Display overrides chosen by editors: a `#rrggbb` colour replacing the
+-- category colour, and the name of an icon from `appearance::ICONS`.
+ALTER TABLE events
+    ADD COLUMN color VARCHAR(7) CHECK (color ~ '^#[0-9a-fA-F]{6}$'),
+    ADD COLUMN icon VARCHAR(32);
@@ -0,0 +1,22 @@
+-- People events are about, shared across timelines.
+CREATE TABLE people (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name VARCHAR(255) NOT NULL,
+    birth_date DATE,
+    death_date DATE,
+    bio TEXT,
+    image_url VARCHAR(512),
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    CHECK (death_date IS NULL OR birth_date IS NULL OR death_date >= birth_date)
+);
+
+CREATE INDEX people_name_idx ON people (lower(name));
+
+CREATE TABLE event_people (
+    event_id UUID NOT NULL REFERENCES events (id) ON DELETE CASCADE,
+    person_id UUID NOT NULL REFERENCES people (id) ON DELETE CASCADE,
+    PRIMARY KEY (event_id, person_id)
+);
+
+CREATE INDEX event_people_person_id_idx ON event_people (person_id);
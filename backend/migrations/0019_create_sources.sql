@@ -0,0 +1,20 @@
+-- Works events are cited from. A source can back several events.
+CREATE TABLE sources (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    title VARCHAR(255) NOT NULL,
+    url VARCHAR(2048),
+    author VARCHAR(255),
+    -- Full reference as it should be cited, e.g. with publisher and pages.
+    citation TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+-- Footnotes are numbered in the order sources were attached.
+CREATE TABLE event_sources (
+    event_id UUID NOT NULL REFERENCES events (id) ON DELETE CASCADE,
+    source_id UUID NOT NULL REFERENCES sources (id) ON DELETE CASCADE,
+    attached_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (event_id, source_id)
+);
+
+CREATE INDEX event_sources_source_id_idx ON event_sources (source_id);
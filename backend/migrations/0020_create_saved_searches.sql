@@ -0,0 +1,24 @@
+-- Event filters users keep under a name, optionally emailing them when
+-- new events match.
+CREATE TABLE saved_searches (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    name VARCHAR(100) NOT NULL,
+    search VARCHAR(255),
+    categories VARCHAR(100)[] NOT NULL DEFAULT '{}',
+    start_date TIMESTAMP,
+    end_date TIMESTAMP,
+    notify BOOLEAN NOT NULL DEFAULT FALSE,
+    -- Events created after this are new to the next alert.
+    last_notified_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    UNIQUE (user_id, name)
+);
+
+INSERT INTO email_templates (key, locale, subject, body) VALUES
+(
+    'saved_search',
+    'en',
+    'New events for "{{search_name}}"',
+    E'Hi {{username}},\n\nNew events match your saved search "{{search_name}}":\n\n{{events}}\n\nSee them all: {{search_url}}'
+);
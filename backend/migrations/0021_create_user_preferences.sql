@@ -0,0 +1,11 @@
+-- Settings that follow a user across devices. Users without a row get the
+-- defaults below.
+CREATE TABLE user_preferences (
+    user_id UUID PRIMARY KEY REFERENCES users (id) ON DELETE CASCADE,
+    default_timeline_id UUID REFERENCES timelines (id) ON DELETE SET NULL,
+    theme VARCHAR(20) NOT NULL DEFAULT 'system',
+    locale VARCHAR(10) NOT NULL DEFAULT 'en',
+    items_per_page SMALLINT NOT NULL DEFAULT 20,
+    default_sort VARCHAR(20) NOT NULL DEFAULT 'newest',
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,23 @@
+-- Accounts at OAuth providers that users log in with. Users who only
+-- ever log in that way have no password.
+ALTER TABLE users ALTER COLUMN password_hash DROP NOT NULL;
+
+CREATE TABLE user_identities (
+    provider VARCHAR(20) NOT NULL,
+    -- The provider's stable id for the account; emails can change.
+    subject VARCHAR(255) NOT NULL,
+    user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (provider, subject)
+);
+
+CREATE INDEX user_identities_user_id_idx ON user_identities (user_id);
+
+-- Logins in progress: the CSRF state sent to the provider and the PKCE
+-- verifier that goes with it. Each is used once.
+CREATE TABLE oauth_states (
+    state VARCHAR(255) PRIMARY KEY,
+    provider VARCHAR(20) NOT NULL,
+    pkce_verifier VARCHAR(255) NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
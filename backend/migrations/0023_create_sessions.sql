@@ -0,0 +1,19 @@
+-- One row per logged-in device. Access tokens are short-lived JWTs naming
+-- their session; the refresh token renews them and changes on every use.
+CREATE TABLE sessions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    -- SHA-256 of the refresh token; the token itself is never stored.
+    refresh_token_hash CHAR(64) NOT NULL UNIQUE,
+    -- The token it replaced, to spot a stolen one being used again.
+    previous_token_hash CHAR(64),
+    user_agent VARCHAR(255),
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    last_seen_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    rotated_at TIMESTAMP,
+    expires_at TIMESTAMP NOT NULL,
+    revoked_at TIMESTAMP
+);
+
+CREATE INDEX sessions_user_id_idx ON sessions (user_id);
+CREATE INDEX sessions_previous_token_hash_idx ON sessions (previous_token_hash);
@@ -0,0 +1,25 @@
+-- Groups sharing one instance. Timelines may belong to an organization;
+-- their events, and so their categories, belong to it with them.
+CREATE TABLE organizations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name VARCHAR(100) NOT NULL,
+    -- Short unique handle, e.g. for URLs.
+    slug VARCHAR(50) NOT NULL UNIQUE,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+-- Owners and admins run the organization and own all of its timelines;
+-- members can see all of them, private ones included.
+CREATE TABLE organization_members (
+    organization_id UUID NOT NULL REFERENCES organizations (id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    role VARCHAR(20) NOT NULL CHECK (role IN ('owner', 'admin', 'member')),
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (organization_id, user_id)
+);
+
+CREATE INDEX organization_members_user_id_idx ON organization_members (user_id);
+
+ALTER TABLE timelines ADD COLUMN organization_id UUID REFERENCES organizations (id) ON DELETE CASCADE;
+
+CREATE INDEX timelines_organization_id_idx ON timelines (organization_id);
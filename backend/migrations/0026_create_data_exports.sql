@@ -0,0 +1,18 @@
+-- Archives of everything a user has stored, built in the background by the
+-- `data_exports` job and downloadable until they expire.
+CREATE TABLE data_exports (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    status VARCHAR(20) NOT NULL DEFAULT 'queued' CHECK (status IN ('queued', 'running', 'ready', 'failed')),
+    -- Secret part of the download link. Kept in the clear, unlike refresh
+    -- tokens, because the owner is shown the link again each time they look.
+    token CHAR(64) NOT NULL UNIQUE,
+    archive BYTEA,
+    error TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMP,
+    expires_at TIMESTAMP
+);
+
+CREATE INDEX data_exports_user_id_idx ON data_exports (user_id);
+CREATE INDEX data_exports_status_idx ON data_exports (status);
@@ -0,0 +1,11 @@
+-- Set on accounts anonymized on their owner's request. They stay so that
+-- their timelines keep an owner, but can no longer log in.
+ALTER TABLE users ADD COLUMN deleted_at TIMESTAMP;
+
+INSERT INTO email_templates (key, locale, subject, body) VALUES
+(
+    'account_deleted',
+    'en',
+    'Your Timeline Explorer account has been deleted',
+    E'Hi {{username}},\n\nAs you asked, your Timeline Explorer account has been deleted along with your personal data. You will not get any more emails from us.\n\nIf you did not ask for this, please reply to this email.'
+);
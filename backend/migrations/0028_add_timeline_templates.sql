@@ -0,0 +1,6 @@
+-- Templates are offered in the gallery for others to copy; copies
+-- remember where they came from.
+ALTER TABLE timelines ADD COLUMN is_template BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE timelines ADD COLUMN cloned_from UUID REFERENCES timelines (id) ON DELETE SET NULL;
+
+CREATE INDEX timelines_is_template_idx ON timelines (is_template) WHERE is_template;
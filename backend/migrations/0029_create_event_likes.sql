@@ -0,0 +1,16 @@
+-- Likes on public events, kept as a total on the event like views.
+ALTER TABLE events ADD COLUMN likes BIGINT NOT NULL DEFAULT 0;
+
+-- Who liked what: a signed-in user, or an anonymous visitor known only by
+-- a hash of the id their browser made up.
+CREATE TABLE event_likes (
+    event_id UUID NOT NULL REFERENCES events (id) ON DELETE CASCADE,
+    user_id UUID REFERENCES users (id) ON DELETE CASCADE,
+    fingerprint CHAR(64),
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    CHECK ((user_id IS NULL) <> (fingerprint IS NULL))
+);
+
+CREATE UNIQUE INDEX event_likes_user_idx ON event_likes (event_id, user_id) WHERE user_id IS NOT NULL;
+CREATE UNIQUE INDEX event_likes_fingerprint_idx ON event_likes (event_id, fingerprint) WHERE fingerprint IS NOT NULL;
+CREATE INDEX event_likes_created_at_idx ON event_likes (created_at);
@@ -0,0 +1,20 @@
+-- Banned users cannot log in or renew a session.
+ALTER TABLE users ADD COLUMN banned_at TIMESTAMP;
+
+-- Reports of public events, worked through by admins in the moderation
+-- queue. Each user has at most one open report per event.
+CREATE TABLE event_reports (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    event_id UUID NOT NULL REFERENCES events (id) ON DELETE CASCADE,
+    reporter_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    reason VARCHAR(20) NOT NULL CHECK (reason IN ('spam', 'abuse', 'inappropriate', 'copyright', 'other')),
+    details TEXT,
+    -- What the admin did: 'dismissed', 'unpublished', 'deleted' or 'banned'.
+    resolution VARCHAR(20),
+    resolved_by UUID REFERENCES users (id) ON DELETE SET NULL,
+    resolved_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX event_reports_open_idx ON event_reports (event_id, reporter_id) WHERE resolved_at IS NULL;
+CREATE INDEX event_reports_unresolved_idx ON event_reports (created_at) WHERE resolved_at IS NULL;
@@ -0,0 +1,13 @@
+-- A video or audio player shown on an event's page. `url` is the
+-- normalized link the editor gave; the rest comes from the provider's
+-- oEmbed endpoint when it is set.
+CREATE TABLE event_embeds (
+    event_id UUID PRIMARY KEY REFERENCES events (id) ON DELETE CASCADE,
+    url VARCHAR(512) NOT NULL,
+    provider VARCHAR(20) NOT NULL CHECK (provider IN ('youtube', 'vimeo', 'soundcloud')),
+    player_url VARCHAR(512) NOT NULL,
+    title VARCHAR(255),
+    author_name VARCHAR(255),
+    thumbnail_url VARCHAR(512),
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
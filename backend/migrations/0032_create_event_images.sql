@@ -0,0 +1,17 @@
+-- Image galleries on events, shown in `position` order. `events.image_url`
+-- stays the image cards and previews use.
+CREATE TABLE event_images (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    event_id UUID NOT NULL REFERENCES events (id) ON DELETE CASCADE,
+    url VARCHAR(512) NOT NULL,
+    alt VARCHAR(255),
+    caption VARCHAR(500),
+    position INTEGER NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX event_images_event_id_idx ON event_images (event_id, position);
+
+-- Existing images start their event's gallery.
+INSERT INTO event_images (event_id, url, alt, position)
+SELECT id, image_url, image_alt, 0 FROM events WHERE image_url IS NOT NULL;
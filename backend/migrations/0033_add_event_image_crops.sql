@@ -0,0 +1,4 @@
+-- Part of a gallery image shown when it is the event's cover, as fractions
+-- of its size: [x, y, width, height] from the top left. NULL shows all of it.
+ALTER TABLE event_images ADD COLUMN crop DOUBLE PRECISION[]
+    CHECK (crop IS NULL OR array_length(crop, 1) = 4);
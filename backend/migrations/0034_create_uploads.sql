@@ -0,0 +1,17 @@
+-- Images uploaded rather than linked, served from /uploads/:id. Each counts
+-- against the storage quota of exactly one owner: a user or, for uploads
+-- made for an organization, the organization.
+CREATE TABLE uploads (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID REFERENCES users (id) ON DELETE CASCADE,
+    organization_id UUID REFERENCES organizations (id) ON DELETE CASCADE,
+    uploaded_by UUID REFERENCES users (id) ON DELETE SET NULL,
+    content_type VARCHAR(100) NOT NULL,
+    size BIGINT NOT NULL,
+    body BYTEA NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    CHECK ((user_id IS NULL) <> (organization_id IS NULL))
+);
+
+CREATE INDEX uploads_user_id_idx ON uploads (user_id);
+CREATE INDEX uploads_organization_id_idx ON uploads (organization_id);
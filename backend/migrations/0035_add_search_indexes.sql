@@ -0,0 +1,8 @@
+-- Full-text indexes for /api/search. The expressions must match the
+-- queries' exactly to be used.
+CREATE INDEX events_search_idx ON events
+    USING gin (to_tsvector('simple', title || ' ' || COALESCE(description, '')));
+CREATE INDEX timelines_search_idx ON timelines
+    USING gin (to_tsvector('simple', title || ' ' || COALESCE(description, '')));
+CREATE INDEX people_search_idx ON people
+    USING gin (to_tsvector('simple', name || ' ' || COALESCE(bio, '')));
@@ -0,0 +1,9 @@
+-- Vocabulary for "Did you mean…?" suggestions, rebuilt periodically from
+-- public titles, names and tags (search::refresh_words).
+CREATE TABLE search_words (
+    word TEXT PRIMARY KEY
+);
+
+CREATE INDEX search_words_trgm_idx ON search_words USING gin (word gin_trgm_ops);
+CREATE INDEX timelines_title_trgm_idx ON timelines USING gin (title gin_trgm_ops);
+CREATE INDEX people_name_trgm_idx ON people USING gin (name gin_trgm_ops);
@@ -0,0 +1,3 @@
+-- "On this day" looks events up by month and day across years.
+CREATE INDEX events_month_day_idx ON events
+    (EXTRACT(MONTH FROM start_date), EXTRACT(DAY FROM start_date));
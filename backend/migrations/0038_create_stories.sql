@@ -0,0 +1,24 @@
+-- Stories: named, narrated walks through a timeline's events, shown in
+-- `position` order.
+CREATE TABLE stories (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    timeline_id UUID NOT NULL REFERENCES timelines (id) ON DELETE CASCADE,
+    title VARCHAR(255) NOT NULL,
+    description TEXT,
+    created_by UUID REFERENCES users (id) ON DELETE SET NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX stories_timeline_id_idx ON stories (timeline_id);
+
+CREATE TABLE story_steps (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    story_id UUID NOT NULL REFERENCES stories (id) ON DELETE CASCADE,
+    position INTEGER NOT NULL,
+    event_id UUID NOT NULL REFERENCES events (id) ON DELETE CASCADE,
+    narration TEXT,
+    UNIQUE (story_id, position)
+);
+
+CREATE INDEX story_steps_event_id_idx ON story_steps (event_id);
@@ -0,0 +1,14 @@
+-- Named eras on a timeline, drawn as bands behind its events.
+CREATE TABLE periods (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    timeline_id UUID NOT NULL REFERENCES timelines (id) ON DELETE CASCADE,
+    name VARCHAR(255) NOT NULL,
+    start_date DATE NOT NULL,
+    end_date DATE NOT NULL,
+    color VARCHAR(7) NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    CHECK (end_date >= start_date)
+);
+
+CREATE INDEX periods_timeline_id_idx ON periods (timeline_id, start_date);
@@ -0,0 +1,5 @@
+-- Events dated only roughly ("between 1430 and 1440") keep the earliest and
+-- latest their start could be; `start_date` is the best guess between them.
+ALTER TABLE events
+    ADD COLUMN start_date_min TIMESTAMP CHECK (start_date_min <= start_date),
+    ADD COLUMN start_date_max TIMESTAMP CHECK (start_date_max >= start_date);
@@ -0,0 +1,8 @@
+-- Feature flags admins have flipped; flags without a row are at their
+-- default, and `FEATURE_FLAGS` overrides both.
+CREATE TABLE feature_flags (
+    name VARCHAR(64) PRIMARY KEY,
+    enabled BOOLEAN NOT NULL,
+    updated_by UUID REFERENCES users (id) ON DELETE SET NULL,
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
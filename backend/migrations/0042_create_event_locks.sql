@@ -0,0 +1,8 @@
+-- Who has an event's edit controls open. A lock lapses at `expires_at`
+-- unless its holder's heartbeats keep pushing that back.
+CREATE TABLE event_locks (
+    event_id UUID PRIMARY KEY REFERENCES events (id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    acquired_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMP NOT NULL
+);
@@ -0,0 +1,16 @@
+-- Things a user should hear about, shown under the header's bell. `kind`
+-- says what happened; so far only `mention`, when `actor_id` named the
+-- user in `comment_id`.
+CREATE TABLE notifications (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    kind VARCHAR(20) NOT NULL,
+    actor_id UUID REFERENCES users (id) ON DELETE CASCADE,
+    event_id UUID REFERENCES events (id) ON DELETE CASCADE,
+    comment_id UUID REFERENCES comments (id) ON DELETE CASCADE,
+    read_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX notifications_user_id_idx ON notifications (user_id, created_at DESC);
+CREATE INDEX notifications_unread_idx ON notifications (user_id) WHERE read_at IS NULL;
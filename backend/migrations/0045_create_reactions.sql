@@ -0,0 +1,14 @@
+-- Emoji reactions from signed-in users, each on either an event or a
+-- comment. A user gives each emoji at most once per thing.
+CREATE TABLE reactions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    event_id UUID REFERENCES events (id) ON DELETE CASCADE,
+    comment_id UUID REFERENCES comments (id) ON DELETE CASCADE,
+    user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    emoji VARCHAR(16) NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    CHECK ((event_id IS NULL) <> (comment_id IS NULL))
+);
+
+CREATE UNIQUE INDEX reactions_event_idx ON reactions (event_id, user_id, emoji) WHERE event_id IS NOT NULL;
+CREATE UNIQUE INDEX reactions_comment_idx ON reactions (comment_id, user_id, emoji) WHERE comment_id IS NOT NULL;
@@ -0,0 +1,7 @@
+-- Replies. `depth` is 0 for a top-level comment and one more than its
+-- parent's for a reply, so the API can cap nesting without walking up.
+ALTER TABLE comments
+    ADD COLUMN parent_comment_id UUID REFERENCES comments (id) ON DELETE CASCADE,
+    ADD COLUMN depth SMALLINT NOT NULL DEFAULT 0;
+
+CREATE INDEX comments_parent_comment_id_idx ON comments (parent_comment_id, created_at);
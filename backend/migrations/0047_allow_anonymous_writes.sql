@@ -0,0 +1,9 @@
+-- Signed-out visitors can comment on and report public events when the
+-- instance allows it. A guest's comment has the name they gave instead of
+-- an author; a guest's report has no reporter.
+ALTER TABLE comments
+    ALTER COLUMN author_id DROP NOT NULL,
+    ADD COLUMN guest_name VARCHAR(50),
+    ADD CONSTRAINT comments_author_check CHECK ((author_id IS NULL) <> (guest_name IS NULL));
+
+ALTER TABLE event_reports ALTER COLUMN reporter_id DROP NOT NULL;
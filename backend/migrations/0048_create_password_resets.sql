@@ -0,0 +1,22 @@
+-- Links that let a user choose a new password after an admin forces a
+-- reset. Only the token's hash is kept, like refresh tokens; a link works
+-- once, until it expires.
+CREATE TABLE password_resets (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id UUID NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    token_hash CHAR(64) NOT NULL UNIQUE,
+    created_by UUID REFERENCES users (id) ON DELETE SET NULL,
+    expires_at TIMESTAMP NOT NULL,
+    used_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX password_resets_user_id_idx ON password_resets (user_id);
+
+INSERT INTO email_templates (key, locale, subject, body) VALUES
+(
+    'password_reset',
+    'en',
+    'Choose a new Timeline Explorer password',
+    E'Hi {{username}},\n\nAn administrator has reset your Timeline Explorer password and logged you out everywhere. Choose a new password by opening the link below within a day:\n\n{{reset_url}}\n\nIf the link has expired, ask an administrator to send another.'
+);
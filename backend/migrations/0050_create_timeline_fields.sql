@@ -0,0 +1,18 @@
+-- Metadata a timeline's events carry beyond the built-in columns, such as
+-- "casualties" or "dynasty". Values live in `events.custom_fields`, keyed by
+-- the field's name and checked against its kind when they are written.
+CREATE TABLE timeline_fields (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    timeline_id UUID NOT NULL REFERENCES timelines (id) ON DELETE CASCADE,
+    name VARCHAR(50) NOT NULL,
+    kind VARCHAR(10) NOT NULL CHECK (kind IN ('text', 'number', 'date', 'boolean')),
+    required BOOLEAN NOT NULL DEFAULT FALSE,
+    position INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    UNIQUE (timeline_id, name)
+);
+
+ALTER TABLE events ADD COLUMN custom_fields JSONB NOT NULL DEFAULT '{}';
+
+CREATE INDEX events_custom_fields_idx ON events USING GIN (custom_fields);
@@ -0,0 +1,58 @@
+-- Messages for systems outside the database, written in the same transaction
+-- as the change they describe so that neither can happen without the other.
+-- The outbox relay job sends them and stamps `delivered_at`; a failed send is
+-- retried after `next_attempt_at`, so delivery is at least once.
+CREATE TABLE outbox (
+    id BIGSERIAL PRIMARY KEY,
+    -- Where the message goes: 'bus' for the event stream.
+    destination VARCHAR(20) NOT NULL,
+    topic VARCHAR(100) NOT NULL,
+    -- Messages with the same key are delivered in the order they were written.
+    key VARCHAR(100) NOT NULL,
+    payload JSONB NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    next_attempt_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    delivered_at TIMESTAMP,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX outbox_pending_idx ON outbox (id) WHERE delivered_at IS NULL;
+
+-- Events are written from too many places (editing, batches, merges,
+-- templates, imports, scheduled publishing) for each to remember the outbox,
+-- so a trigger records every change instead. Updates that only move the
+-- view or like counters aren't changes anyone downstream cares about.
+CREATE FUNCTION capture_event_change() RETURNS trigger AS $$
+DECLARE
+    row_data JSONB;
+    change TEXT;
+BEGIN
+    IF TG_OP = 'DELETE' THEN
+        row_data := to_jsonb(OLD);
+        change := 'event.deleted';
+    ELSIF TG_OP = 'INSERT' THEN
+        row_data := to_jsonb(NEW);
+        change := 'event.created';
+    ELSE
+        row_data := to_jsonb(NEW);
+        change := 'event.updated';
+        IF row_data - 'views' - 'likes' - 'updated_at' = to_jsonb(OLD) - 'views' - 'likes' - 'updated_at' THEN
+            RETURN NULL;
+        END IF;
+    END IF;
+
+    INSERT INTO outbox (destination, topic, key, payload)
+    VALUES ('bus', change, row_data ->> 'id', jsonb_build_object(
+        'type', change,
+        'id', row_data -> 'id',
+        'occurred_at', NOW(),
+        'event', row_data
+    ));
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER events_capture_changes
+    AFTER INSERT OR UPDATE OR DELETE ON events
+    FOR EACH ROW EXECUTE FUNCTION capture_event_change();
@@ -0,0 +1,4 @@
+-- Webhooks and emails now go through the outbox too, queued by the
+-- transaction whose change they announce instead of sent after it commits.
+ALTER TABLE outbox ADD CONSTRAINT outbox_destination_check
+    CHECK (destination IN ('bus', 'webhook', 'mail'));
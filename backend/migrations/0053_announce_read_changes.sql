@@ -0,0 +1,38 @@
+-- Public reads are cached (see `cache`), so every instance needs to hear
+-- when an event or timeline changes, whichever code path changed it. Like
+-- the outbox trigger, updates that only move the view or like counters are
+-- left out; the cached copies may lag on those until they expire.
+CREATE FUNCTION announce_event_change() RETURNS trigger AS $$
+BEGIN
+    IF TG_OP = 'UPDATE' AND to_jsonb(NEW) - 'views' - 'likes' - 'updated_at' = to_jsonb(OLD) - 'views' - 'likes' - 'updated_at' THEN
+        RETURN NULL;
+    END IF;
+    PERFORM pg_notify('read_changes', json_build_object(
+        'event_id', CASE WHEN TG_OP = 'DELETE' THEN OLD.id ELSE NEW.id END,
+        'timeline_ids', array_remove(ARRAY[
+            CASE WHEN TG_OP = 'INSERT' THEN NULL ELSE OLD.timeline_id END,
+            CASE WHEN TG_OP = 'DELETE' THEN NULL ELSE NEW.timeline_id END
+        ], NULL)
+    )::text);
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER events_announce_changes
+    AFTER INSERT OR UPDATE OR DELETE ON events
+    FOR EACH ROW EXECUTE FUNCTION announce_event_change();
+
+-- A timeline going private, archived or away changes what its events show.
+CREATE FUNCTION announce_timeline_change() RETURNS trigger AS $$
+BEGIN
+    PERFORM pg_notify('read_changes', json_build_object(
+        'event_id', NULL,
+        'timeline_ids', ARRAY[OLD.id]
+    )::text);
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER timelines_announce_changes
+    AFTER UPDATE OR DELETE ON timelines
+    FOR EACH ROW EXECUTE FUNCTION announce_timeline_change();
@@ -0,0 +1,21 @@
+-- Indexes for the listing filters, which otherwise scan every event. Each
+-- expression must match the query's exactly to be used; see `push_filter`.
+-- `cargo bench-queries` seeds a scratch database and prints the plans.
+
+-- A timeline's events, in order, in one index scan. Covers lookups by
+-- timeline alone, so the old single-column index goes.
+CREATE INDEX events_timeline_start_date_idx ON events (timeline_id, start_date);
+DROP INDEX events_timeline_id_idx;
+
+-- The default listing: published events, newest first.
+CREATE INDEX events_published_start_date_idx ON events (start_date) WHERE status = 'published';
+
+CREATE INDEX events_category_idx ON events (lower(COALESCE(category, 'Uncategorized')));
+
+-- Substring search on titles and descriptions. Both sides of the OR need
+-- an index for either to be used.
+CREATE INDEX events_title_lower_trgm_idx ON events USING gin (lower(title) gin_trgm_ops);
+CREATE INDEX events_description_lower_trgm_idx ON events USING gin (lower(description) gin_trgm_ops);
+
+-- No deleted_at partial index: events have no deleted_at column, as they
+-- are deleted outright rather than soft-deleted.
@@ -0,0 +1,26 @@
+-- Tags, people and likes are served with their event, so cached copies of
+-- it and its timeline's lists (see `cache`) go stale when they change.
+CREATE FUNCTION announce_event_link_change() RETURNS trigger AS $$
+DECLARE
+    changed UUID := CASE WHEN TG_OP = 'DELETE' THEN OLD.event_id ELSE NEW.event_id END;
+BEGIN
+    PERFORM pg_notify('read_changes', json_build_object(
+        'event_id', changed,
+        -- Nothing, once the event itself is gone; its own trigger said so.
+        'timeline_ids', array_remove(ARRAY[(SELECT timeline_id FROM events WHERE id = changed)], NULL)
+    )::text);
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER event_tags_announce_changes
+    AFTER INSERT OR UPDATE OR DELETE ON event_tags
+    FOR EACH ROW EXECUTE FUNCTION announce_event_link_change();
+
+CREATE TRIGGER event_people_announce_changes
+    AFTER INSERT OR UPDATE OR DELETE ON event_people
+    FOR EACH ROW EXECUTE FUNCTION announce_event_link_change();
+
+CREATE TRIGGER event_likes_announce_changes
+    AFTER INSERT OR UPDATE OR DELETE ON event_likes
+    FOR EACH ROW EXECUTE FUNCTION announce_event_link_change();
@@ -0,0 +1,5 @@
+-- The relay no longer holds row locks while it sends. It stamps the rows it
+-- takes with `claimed_until` and commits straight away; other instances skip
+-- claimed rows until then, so a relay that dies mid-batch only delays its
+-- messages until the claim runs out.
+ALTER TABLE outbox ADD COLUMN claimed_until TIMESTAMP;
@@ -0,0 +1,65 @@
+-- The SQLite event store the repository tests use (see `db::sqlite`): the
+-- events table as the Postgres migrations leave it, plus the columns of
+-- the tables its visibility and tag filters read. `timeline_id` has no
+-- foreign key here.
+
+CREATE TABLE events (
+    id BLOB PRIMARY KEY,
+    title VARCHAR(255) NOT NULL,
+    description TEXT,
+    start_date DATETIME NOT NULL,
+    end_date DATETIME,
+    start_date_min DATETIME CHECK (start_date_min <= start_date),
+    start_date_max DATETIME CHECK (start_date_max >= start_date),
+    location VARCHAR(255),
+    image_url VARCHAR(512),
+    image_alt VARCHAR(255),
+    category VARCHAR(100),
+    importance SMALLINT NOT NULL DEFAULT 3 CHECK (importance BETWEEN 1 AND 5),
+    color VARCHAR(7),
+    icon VARCHAR(32),
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    timeline_id BLOB,
+    status VARCHAR(20) NOT NULL DEFAULT 'published' CHECK (status IN ('draft', 'published', 'archived')),
+    publish_at DATETIME,
+    views BIGINT NOT NULL DEFAULT 0,
+    likes BIGINT NOT NULL DEFAULT 0,
+    featured BOOLEAN NOT NULL DEFAULT FALSE,
+    custom_fields TEXT NOT NULL DEFAULT '{}'
+);
+
+CREATE INDEX events_timeline_start_date_idx ON events (timeline_id, start_date);
+CREATE INDEX events_status_start_date_idx ON events (status, start_date);
+
+CREATE TABLE timelines (
+    id BLOB PRIMARY KEY,
+    owner_id BLOB NOT NULL,
+    is_private BOOLEAN NOT NULL DEFAULT FALSE,
+    organization_id BLOB
+);
+
+CREATE TABLE timeline_members (
+    timeline_id BLOB NOT NULL REFERENCES timelines (id) ON DELETE CASCADE,
+    user_id BLOB NOT NULL,
+    role VARCHAR(20) NOT NULL CHECK (role IN ('viewer', 'editor')),
+    PRIMARY KEY (timeline_id, user_id)
+);
+
+CREATE TABLE organization_members (
+    organization_id BLOB NOT NULL,
+    user_id BLOB NOT NULL,
+    role VARCHAR(20) NOT NULL CHECK (role IN ('owner', 'admin', 'member')),
+    PRIMARY KEY (organization_id, user_id)
+);
+
+CREATE TABLE tags (
+    id INTEGER PRIMARY KEY,
+    name VARCHAR(50) NOT NULL UNIQUE
+);
+
+CREATE TABLE event_tags (
+    event_id BLOB NOT NULL REFERENCES events (id) ON DELETE CASCADE,
+    tag_id INTEGER NOT NULL REFERENCES tags (id) ON DELETE CASCADE,
+    PRIMARY KEY (event_id, tag_id)
+);
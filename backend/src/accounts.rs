@@ -0,0 +1,221 @@
+//! `DELETE /api/me`: users closing their own account.
+
+use std::collections::HashMap;
+
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Deserialize;
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use crate::{
+    auth::{self, AuthUser},
+    config::AccountDeletion,
+    email_templates, preferences, AppState,
+};
+
+/// Personal data removed whatever `ACCOUNT_DELETION` says, as
+/// `(table, user column)`.
+const PERSONAL_DATA: &[(&str, &str)] = &[
+    ("favorites", "user_id"),
+    ("event_reads", "user_id"),
+    ("recommendations", "user_id"),
+    ("user_preferences", "user_id"),
+    ("saved_searches", "user_id"),
+    ("sessions", "user_id"),
+    ("password_resets", "user_id"),
+    ("user_identities", "user_id"),
+    ("data_exports", "user_id"),
+    ("notifications", "user_id"),
+    ("reactions", "user_id"),
+    ("timeline_members", "user_id"),
+    ("organization_members", "user_id"),
+];
+
+#[derive(Deserialize)]
+pub struct DeleteAccount {
+    /// Required for accounts with a password.
+    password: Option<String>,
+    /// Accounts that only log in through OAuth confirm with their username
+    /// instead.
+    username: Option<String>,
+}
+
+/// Whether `payload` proves the caller is the account's owner.
+fn confirmed(payload: &DeleteAccount, username: &str, password_hash: Option<&str>) -> bool {
+    match password_hash {
+        Some(hash) => payload.password.as_deref().is_some_and(|password| auth::verify_password(password, hash)),
+        None => payload.username.as_deref().map(str::trim) == Some(username),
+    }
+}
+
+/// Placeholder name for an anonymized account; unique like the id it comes from.
+fn anonymous_username(id: Uuid) -> String {
+    format!("deleted-{}", id.simple())
+}
+
+/// Fails if the user is the last owner of an organization others still
+/// belong to; they hand it over or delete it first.
+async fn ensure_no_orphaned_organizations(conn: &mut PgConnection, user: Uuid) -> Result<(), Response> {
+    let orphaned = sqlx::query_scalar::<_, String>(
+        r#"
+        SELECT o.name FROM organizations o
+        JOIN organization_members m ON m.organization_id = o.id AND m.user_id = $1 AND m.role = 'owner'
+        WHERE NOT EXISTS (
+            SELECT 1 FROM organization_members other
+            WHERE other.organization_id = o.id AND other.user_id <> $1 AND other.role = 'owner'
+        )
+        AND EXISTS (
+            SELECT 1 FROM organization_members other WHERE other.organization_id = o.id AND other.user_id <> $1
+        )
+        LIMIT 1
+        "#,
+    )
+    .bind(user)
+    .fetch_optional(&mut *conn)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
+    match orphaned {
+        Some(name) => Err((
+            StatusCode::CONFLICT,
+            format!("make someone else an owner of {} before deleting your account", name),
+        )
+            .into_response()),
+        None => Ok(()),
+    }
+}
+
+/// Deletes the caller's account after they confirm it. Their personal data
+/// always goes; their timelines and events are deleted or kept under an
+/// anonymous name, as `ACCOUNT_DELETION` says. A confirmation email goes
+/// to the address the account had.
+pub async fn delete_account(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Json(payload): Json<DeleteAccount>,
+) -> Result<StatusCode, Response> {
+    let pool = state.db.writer();
+    let internal = |_| StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    let (username, email, password_hash, role) = sqlx::query_as::<_, (String, String, Option<String>, String)>(
+        "SELECT username, email, password_hash, role FROM users WHERE id = $1 AND deleted_at IS NULL",
+    )
+    .bind(user.id)
+    .fetch_optional(pool)
+    .await
+    .map_err(internal)?
+    .ok_or_else(|| StatusCode::UNAUTHORIZED.into_response())?;
+    if !confirmed(&payload, &username, password_hash.as_deref()) {
+        return Err((StatusCode::FORBIDDEN, "confirm with your password").into_response());
+    }
+    let locale = preferences::find(pool, user.id).await.map_err(internal)?.locale;
+
+    let mut tx = pool.begin().await.map_err(internal)?;
+    if role == "admin" {
+        let others = sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM users WHERE role = 'admin' AND id <> $1 AND deleted_at IS NULL",
+        )
+        .bind(user.id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(internal)?;
+        if others == 0 {
+            return Err((StatusCode::CONFLICT, "the last admin cannot delete their account").into_response());
+        }
+    }
+    ensure_no_orphaned_organizations(&mut tx, user.id).await?;
+
+    // Likes go in both modes, and come off the events' totals.
+    sqlx::query(
+        r#"
+        WITH removed AS (DELETE FROM event_likes WHERE user_id = $1 RETURNING event_id)
+        UPDATE events e SET likes = e.likes - 1 FROM removed WHERE e.id = removed.event_id
+        "#,
+    )
+    .bind(user.id)
+    .execute(&mut *tx)
+    .await
+    .map_err(internal)?;
+
+    match state.config.account_deletion {
+        AccountDeletion::Delete => {
+            // Organizations nobody else belongs to would be left unmanaged.
+            sqlx::query(
+                r#"
+                DELETE FROM organizations o
+                WHERE EXISTS (SELECT 1 FROM organization_members m WHERE m.organization_id = o.id AND m.user_id = $1)
+                AND NOT EXISTS (
+                    SELECT 1 FROM organization_members m WHERE m.organization_id = o.id AND m.user_id <> $1
+                )
+                "#,
+            )
+            .bind(user.id)
+            .execute(&mut *tx)
+            .await
+            .map_err(internal)?;
+            // Everything else hangs off the user row.
+            sqlx::query("DELETE FROM users WHERE id = $1")
+                .bind(user.id)
+                .execute(&mut *tx)
+                .await
+                .map_err(internal)?;
+        }
+        AccountDeletion::Anonymize => {
+            for (table, column) in PERSONAL_DATA {
+                sqlx::query(&format!("DELETE FROM {} WHERE {} = $1", table, column))
+                    .bind(user.id)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(internal)?;
+            }
+            sqlx::query(
+                r#"
+                UPDATE users SET
+                    username = $2,
+                    email = $2 || '@invalid',
+                    password_hash = NULL,
+                    role = 'user',
+                    deleted_at = NOW()
+                WHERE id = $1
+                "#,
+            )
+            .bind(user.id)
+            .bind(anonymous_username(user.id))
+            .execute(&mut *tx)
+            .await
+            .map_err(internal)?;
+        }
+    }
+    // A template that doesn't render shouldn't keep the account; the email
+    // is skipped instead.
+    let values = HashMap::from([("username".to_string(), username)]);
+    email_templates::queue(&state, &mut tx, "account_deleted", &locale, &email, &values).await.ok();
+    tx.commit().await.map_err(internal)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn passwordless_accounts_confirm_with_their_username() {
+        let by_name = DeleteAccount { password: None, username: Some(" ada ".to_string()) };
+        assert!(confirmed(&by_name, "ada", None));
+        assert!(!confirmed(&by_name, "grace", None));
+
+        let hash = auth::hash_password("correct horse").unwrap();
+        assert!(!confirmed(&by_name, "ada", Some(&hash)));
+        let by_password = DeleteAccount { password: Some("correct horse".to_string()), username: None };
+        assert!(confirmed(&by_password, "ada", Some(&hash)));
+    }
+
+    #[test]
+    fn anonymous_names_fit_the_username_column() {
+        assert!(anonymous_username(Uuid::new_v4()).len() <= 50);
+    }
+}
@@ -0,0 +1,116 @@
+use axum::{
+    extract::Query,
+    http::StatusCode,
+    routing::get,
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{db::Reader, AppState};
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/analytics/tag-cooccurrence", get(tag_cooccurrence))
+        .route("/api/analytics/decades", get(decade_categories))
+}
+
+#[derive(Deserialize)]
+struct CooccurrenceQuery {
+    /// Number of most-used tags in the matrix.
+    limit: Option<i64>,
+}
+
+/// Symmetric matrix; `matrix[i][j]` is the number of events tagged with both
+/// `tags[i]` and `tags[j]`, and the diagonal holds each tag's own count.
+#[derive(Serialize)]
+struct Cooccurrence {
+    tags: Vec<String>,
+    matrix: Vec<Vec<i64>>,
+}
+
+#[derive(Deserialize)]
+struct DecadeQuery {
+    from: Option<i32>,
+    to: Option<i32>,
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+struct DecadeCategory {
+    /// First year of the decade, e.g. `1910`.
+    decade: i32,
+    category: Option<String>,
+    count: i64,
+}
+
+async fn tag_cooccurrence(
+    Reader(pool): Reader,
+    Query(query): Query<CooccurrenceQuery>,
+) -> Result<Json<Cooccurrence>, StatusCode> {
+    let limit = query.limit.unwrap_or(20).clamp(2, 50);
+
+    let tags = sqlx::query_scalar::<_, String>(
+        r#"
+        SELECT t.name FROM tags t
+        JOIN event_tags et ON et.tag_id = t.id
+        GROUP BY t.name
+        ORDER BY COUNT(*) DESC, t.name
+        LIMIT $1
+        "#,
+    )
+    .bind(limit)
+    .fetch_all(&pool)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let pairs = sqlx::query_as::<_, (String, String, i64)>(
+        r#"
+        SELECT a.name, b.name, COUNT(*)
+        FROM event_tags x
+        JOIN event_tags y ON y.event_id = x.event_id
+        JOIN tags a ON a.id = x.tag_id
+        JOIN tags b ON b.id = y.tag_id
+        WHERE a.name = ANY($1) AND b.name = ANY($1)
+        GROUP BY a.name, b.name
+        "#,
+    )
+    .bind(&tags)
+    .fetch_all(&pool)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let mut matrix = vec![vec![0; tags.len()]; tags.len()];
+    for (a, b, count) in pairs {
+        let i = tags.iter().position(|tag| *tag == a);
+        let j = tags.iter().position(|tag| *tag == b);
+        if let (Some(i), Some(j)) = (i, j) {
+            matrix[i][j] = count;
+        }
+    }
+
+    Ok(Json(Cooccurrence { tags, matrix }))
+}
+
+async fn decade_categories(
+    Reader(pool): Reader,
+    Query(query): Query<DecadeQuery>,
+) -> Result<Json<Vec<DecadeCategory>>, StatusCode> {
+    let rows = sqlx::query_as::<_, DecadeCategory>(
+        r#"
+        SELECT (floor(extract(year FROM start_date) / 10) * 10)::int AS decade,
+               category,
+               COUNT(*) AS count
+        FROM events
+        WHERE ($1::int IS NULL OR extract(year FROM start_date) >= $1)
+          AND ($2::int IS NULL OR extract(year FROM start_date) <= $2)
+        GROUP BY 1, 2
+        ORDER BY 1, 2
+        "#,
+    )
+    .bind(query.from)
+    .bind(query.to)
+    .fetch_all(&pool)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(rows))
+}
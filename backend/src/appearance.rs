@@ -0,0 +1,51 @@
+//! Per-event display overrides: a colour replacing the category colour and
+//! an icon shown beside the title.
+
+use validator::ValidationError;
+
+/// Icon names events may use. The frontend maps each to a glyph, so new
+/// names must be added there too.
+pub const ICONS: [&str; 12] = [
+    "star", "flag", "crown", "swords", "book", "flask", "building", "music", "palette", "globe", "heart", "rocket",
+];
+
+/// Accepts `#rrggbb` colours.
+pub fn valid_color(color: &str) -> Result<(), ValidationError> {
+    let digits = color.strip_prefix('#').unwrap_or_default();
+    if digits.len() == 6 && digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        Ok(())
+    } else {
+        Err(ValidationError::new("color"))
+    }
+}
+
+/// Accepts the names in `ICONS`.
+pub fn valid_icon(icon: &str) -> Result<(), ValidationError> {
+    if ICONS.contains(&icon) {
+        Ok(())
+    } else {
+        Err(ValidationError::new("icon"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn colors_are_six_hex_digits() {
+        assert!(valid_color("#3b82f6").is_ok());
+        assert!(valid_color("#3B82F6").is_ok());
+        assert!(valid_color("3b82f6").is_err());
+        assert!(valid_color("#fff").is_err());
+        assert!(valid_color("#3b82fg").is_err());
+        assert!(valid_color("red").is_err());
+    }
+
+    #[test]
+    fn icons_come_from_the_set() {
+        assert!(valid_icon("crown").is_ok());
+        assert!(valid_icon("Crown").is_err());
+        assert!(valid_icon("").is_err());
+    }
+}
@@ -0,0 +1,107 @@
+//! The audit log: what admins did, kept so it can be looked back on, and
+//! `GET /api/admin/audit` to do so.
+
+use axum::{extract::Query, http::StatusCode, routing::get, Json, Router};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+use crate::{auth::AuthUser, db::Reader, AppState, PaginatedResponse};
+
+pub fn routes() -> Router<AppState> {
+    Router::new().route("/api/admin/audit", get(list_entries))
+}
+
+/// Adds an entry. `action` is a dotted name, e.g. `impersonation.started`.
+pub async fn record(
+    conn: &mut PgConnection,
+    actor: Option<Uuid>,
+    action: &str,
+    target: Option<Uuid>,
+    details: serde_json::Value,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("INSERT INTO audit_log (actor_id, action, target_user_id, details) VALUES ($1, $2, $3, $4)")
+        .bind(actor)
+        .bind(action)
+        .bind(target)
+        .bind(details)
+        .execute(conn)
+        .await
+        .map(|_| ())
+}
+
+#[derive(Deserialize)]
+struct AuditQuery {
+    page: Option<i32>,
+    limit: Option<i32>,
+    /// Entries whose action is this or starts with it and a dot, so
+    /// `impersonation` covers all of an impersonation's entries.
+    action: Option<String>,
+    /// Entries where this user acted or was acted on.
+    user_id: Option<Uuid>,
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+struct Entry {
+    id: Uuid,
+    actor_id: Option<Uuid>,
+    actor: Option<String>,
+    action: String,
+    target_user_id: Option<Uuid>,
+    target: Option<String>,
+    details: serde_json::Value,
+    created_at: NaiveDateTime,
+}
+
+const FILTERS: &str = r#"
+    WHERE ($1::text IS NULL OR a.action = $1 OR a.action LIKE $1 || '.%')
+    AND ($2::uuid IS NULL OR a.actor_id = $2 OR a.target_user_id = $2)
+"#;
+
+/// A page of entries, newest first. Admins only.
+async fn list_entries(
+    Reader(pool): Reader,
+    user: AuthUser,
+    Query(query): Query<AuditQuery>,
+) -> Result<Json<PaginatedResponse<Entry>>, StatusCode> {
+    user.require_admin()?;
+    let page = query.page.unwrap_or(1).max(1);
+    let limit = query.limit.unwrap_or(50).clamp(1, 200);
+    let action = query.action.filter(|action| !action.is_empty());
+
+    let total = sqlx::query_scalar::<_, i64>(&format!("SELECT COUNT(*) FROM audit_log a {}", FILTERS))
+        .bind(&action)
+        .bind(query.user_id)
+        .fetch_one(&pool)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let entries = sqlx::query_as::<_, Entry>(&format!(
+        r#"
+        SELECT a.id, a.actor_id, actor.username AS actor, a.action, a.target_user_id, target.username AS target,
+            a.details, a.created_at
+        FROM audit_log a
+        LEFT JOIN users actor ON actor.id = a.actor_id
+        LEFT JOIN users target ON target.id = a.target_user_id
+        {}
+        ORDER BY a.created_at DESC
+        LIMIT $3 OFFSET $4
+        "#,
+        FILTERS
+    ))
+    .bind(&action)
+    .bind(query.user_id)
+    .bind(limit as i64)
+    .bind(((page - 1) * limit) as i64)
+    .fetch_all(&pool)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(PaginatedResponse {
+        data: entries,
+        total,
+        page,
+        limit,
+        pages: (total as f64 / limit as f64).ceil() as i32,
+    }))
+}
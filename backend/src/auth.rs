@@ -0,0 +1,319 @@
+use std::sync::Arc;
+
+use argon2::{
+    password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2,
+};
+use axum::{
+    async_trait,
+    extract::{FromRef, FromRequestParts, State},
+    http::{header, request::Parts, HeaderMap, StatusCode},
+    routing::{get, post},
+    Json, Router,
+};
+use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    accounts,
+    config::{Config, RegistrationMode},
+    db::Reader,
+    flags::Flag,
+    invites, sessions, AppState,
+};
+
+/// Access tokens are short-lived, so a revoked session's token stops
+/// working soon; the session's refresh token gets new ones.
+pub const ACCESS_TOKEN_TTL_SECONDS: i64 = 15 * 60;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/auth/register", post(register))
+        .route("/api/auth/login", post(login))
+        .route("/api/auth/registration", get(registration))
+        .route("/api/me", get(me).delete(accounts::delete_account))
+}
+
+#[derive(Serialize, Deserialize)]
+struct Claims {
+    sub: Uuid,
+    role: String,
+    exp: i64,
+    /// The session the token was issued for.
+    #[serde(default)]
+    sid: Option<Uuid>,
+    /// The impersonation the token was issued for, if an admin is using
+    /// the app as `sub`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    imp: Option<Uuid>,
+}
+
+/// The caller identified by a valid `Authorization: Bearer` token.
+///
+/// Use `Option<AuthUser>` for routes that also serve anonymous visitors.
+#[derive(Clone, Debug)]
+pub struct AuthUser {
+    pub id: Uuid,
+    pub role: String,
+    /// Missing from tokens issued before sessions existed.
+    pub session_id: Option<Uuid>,
+    /// Set when an admin is impersonating the user; see `impersonation`.
+    pub impersonation: Option<Uuid>,
+}
+
+impl AuthUser {
+    pub fn is_admin(&self) -> bool {
+        self.role == "admin"
+    }
+
+    /// `403 Forbidden` unless the caller is an admin.
+    pub fn require_admin(&self) -> Result<(), StatusCode> {
+        if self.is_admin() {
+            Ok(())
+        } else {
+            Err(StatusCode::FORBIDDEN)
+        }
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for AuthUser
+where
+    Arc<Config>: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = StatusCode;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let config = Arc::<Config>::from_ref(state);
+        let token = parts
+            .headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or(StatusCode::UNAUTHORIZED)?;
+        verify_token(&config, token)
+    }
+}
+
+/// The user a session token was issued to, for callers that can't send
+/// it as a header, such as WebSockets.
+pub fn verify_token(config: &Config, token: &str) -> Result<AuthUser, StatusCode> {
+    let claims = decode::<Claims>(
+        token,
+        &DecodingKey::from_secret(config.jwt_secret.as_bytes()),
+        &Validation::default(),
+    )
+    .map_err(|_| StatusCode::UNAUTHORIZED)?
+    .claims;
+
+    Ok(AuthUser {
+        id: claims.sub,
+        role: claims.role,
+        session_id: claims.sid,
+        impersonation: claims.imp,
+    })
+}
+
+/// `verify_token` for WebSocket handshakes. The impersonation guard only
+/// sees HTTP requests, so impersonation tokens are refused here rather
+/// than let onto a socket unaudited.
+pub fn verify_socket_token(config: &Config, token: &str) -> Result<AuthUser, StatusCode> {
+    let user = verify_token(config, token)?;
+    if user.impersonation.is_some() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    Ok(user)
+}
+
+#[derive(Deserialize)]
+struct RegisterRequest {
+    username: String,
+    email: String,
+    password: String,
+    /// Required when registration is invite-only.
+    invite_code: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct LoginRequest {
+    username: String,
+    password: String,
+}
+
+/// An access token plus the refresh token that renews it.
+#[derive(Serialize)]
+pub struct TokenResponse {
+    pub token: String,
+    pub refresh_token: String,
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+struct Me {
+    id: Uuid,
+    username: String,
+    email: String,
+    role: String,
+    /// False for accounts that only log in through OAuth.
+    has_password: bool,
+}
+
+pub fn issue_token(config: &Config, id: Uuid, role: &str, session: Uuid) -> Result<String, StatusCode> {
+    sign(
+        config,
+        &Claims {
+            sub: id,
+            role: role.to_string(),
+            exp: chrono::Utc::now().timestamp() + ACCESS_TOKEN_TTL_SECONDS,
+            sid: Some(session),
+            imp: None,
+        },
+    )
+}
+
+/// A token for an admin to use the app as `id` until `expires_at`. It
+/// belongs to no session, so it can't be refreshed.
+pub fn issue_impersonation_token(
+    config: &Config,
+    id: Uuid,
+    role: &str,
+    impersonation: Uuid,
+    expires_at: chrono::NaiveDateTime,
+) -> Result<String, StatusCode> {
+    sign(
+        config,
+        &Claims {
+            sub: id,
+            role: role.to_string(),
+            exp: expires_at.and_utc().timestamp(),
+            sid: None,
+            imp: Some(impersonation),
+        },
+    )
+}
+
+fn sign(config: &Config, claims: &Claims) -> Result<String, StatusCode> {
+    encode(
+        &Header::default(),
+        claims,
+        &EncodingKey::from_secret(config.jwt_secret.as_bytes()),
+    )
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// Starts a session on the requesting device and issues its tokens.
+pub async fn start_session(
+    state: &AppState,
+    id: Uuid,
+    role: &str,
+    headers: &HeaderMap,
+) -> Result<TokenResponse, StatusCode> {
+    let (session, refresh_token) = sessions::create(state.db.writer(), id, sessions::user_agent(headers))
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(TokenResponse {
+        token: issue_token(&state.config, id, role, session)?,
+        refresh_token,
+    })
+}
+
+pub fn hash_password(password: &str) -> Result<String, StatusCode> {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+pub fn verify_password(password: &str, hash: &str) -> bool {
+    PasswordHash::new(hash)
+        .map(|hash| Argon2::default().verify_password(password.as_bytes(), &hash).is_ok())
+        .unwrap_or(false)
+}
+
+async fn register(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<RegisterRequest>,
+) -> Result<Json<TokenResponse>, StatusCode> {
+    let username = payload.username.trim();
+    if username.len() < 3 || username.len() > 50 || !payload.email.contains('@') || payload.password.len() < 8 {
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+    let email = payload.email.trim();
+    let password_hash = hash_password(&payload.password)?;
+
+    let mut tx = state.db.writer().begin().await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    match (registration_mode(&state), payload.invite_code.as_deref()) {
+        (RegistrationMode::Open, _) => {}
+        (RegistrationMode::InviteOnly, Some(code)) => invites::redeem(&mut tx, code, email).await?,
+        (RegistrationMode::InviteOnly, None) | (RegistrationMode::Closed, _) => return Err(StatusCode::FORBIDDEN),
+    }
+    let id = sqlx::query_scalar::<_, Uuid>(
+        "INSERT INTO users (username, email, password_hash) VALUES ($1, $2, $3) RETURNING id",
+    )
+    .bind(username)
+    .bind(email)
+    .bind(password_hash)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(|err| match err {
+        sqlx::Error::Database(db) if db.is_unique_violation() => StatusCode::CONFLICT,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    })?;
+    tx.commit().await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    start_session(&state, id, "user", &headers).await.map(Json)
+}
+
+async fn login(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<LoginRequest>,
+) -> Result<Json<TokenResponse>, StatusCode> {
+    let user = sqlx::query_as::<_, (Uuid, Option<String>, String, bool)>(
+        "SELECT id, password_hash, role, banned_at IS NOT NULL FROM users WHERE username = $1",
+    )
+    .bind(payload.username.trim())
+    .fetch_optional(state.db.reader())
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    match user {
+        // Users who signed up through an OAuth provider have no password.
+        Some((id, Some(hash), role, banned)) if verify_password(&payload.password, &hash) => match banned {
+            true => Err(StatusCode::FORBIDDEN),
+            false => start_session(&state, id, &role, &headers).await.map(Json),
+        },
+        _ => Err(StatusCode::UNAUTHORIZED),
+    }
+}
+
+/// `REGISTRATION_MODE`, except that open sign-up needs an invite while
+/// the `public_registration` flag is off.
+pub fn registration_mode(state: &AppState) -> RegistrationMode {
+    match state.config.registration {
+        RegistrationMode::Open if !state.flags.enabled(Flag::PublicRegistration) => RegistrationMode::InviteOnly,
+        mode => mode,
+    }
+}
+
+/// How sign-up works here, so clients know whether to ask for an invite
+/// code or offer sign-up at all.
+async fn registration(State(state): State<AppState>) -> Json<serde_json::Value> {
+    Json(serde_json::json!({ "mode": registration_mode(&state).name() }))
+}
+
+async fn me(Reader(pool): Reader, user: AuthUser) -> Result<Json<Me>, StatusCode> {
+    sqlx::query_as::<_, Me>(
+        "SELECT id, username, email, role, password_hash IS NOT NULL AS has_password \
+         FROM users WHERE id = $1 AND deleted_at IS NULL",
+    )
+    .bind(user.id)
+    .fetch_optional(&pool)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+    .map(Json)
+    .ok_or(StatusCode::UNAUTHORIZED)
+}
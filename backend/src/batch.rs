@@ -0,0 +1,180 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::post,
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    auth::AuthUser,
+    db::events::{Change, Events, Outcome},
+    new_event, timelines, validation_error, AppState, CreateParams, Event, EventCreate, EventPatch,
+};
+
+/// Most operations accepted in one request.
+const MAX_OPERATIONS: usize = 100;
+
+pub fn routes() -> Router<AppState> {
+    Router::new().route("/api/events/batch", post(run_batch))
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+enum Operation {
+    Create { event: EventCreate },
+    Update { id: Uuid, changes: EventPatch },
+    Delete { id: Uuid },
+}
+
+#[derive(Deserialize)]
+struct BatchRequest {
+    operations: Vec<Operation>,
+    /// All operations succeed or none are kept. Defaults to true; with
+    /// false each operation stands on its own.
+    atomic: Option<bool>,
+}
+
+/// `results[i]` describes `operations[i]`.
+#[derive(Serialize)]
+struct BatchResponse {
+    /// Whether any changes were kept.
+    committed: bool,
+    results: Vec<OperationResult>,
+}
+
+#[derive(Serialize)]
+struct OperationResult {
+    /// The HTTP status the operation would have had on its own; 424 when it
+    /// was not applied because another operation in an atomic batch failed.
+    status: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    event: Option<Event>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<Value>,
+}
+
+impl OperationResult {
+    fn ok(status: StatusCode, event: Option<Event>) -> Self {
+        Self { status: status.as_u16(), event, error: None }
+    }
+
+    fn error(status: StatusCode, error: impl Into<Value>) -> Self {
+        Self { status: status.as_u16(), event: None, error: Some(error.into()) }
+    }
+
+    /// Carries over the status and body of a check that failed.
+    async fn rejected(response: Response) -> Self {
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap_or_default();
+        let error = serde_json::from_slice(&body)
+            .unwrap_or_else(|_| Value::String(String::from_utf8_lossy(&body).into_owned()));
+        Self { status: status.as_u16(), event: None, error: Some(error) }
+    }
+
+    fn not_applied() -> Self {
+        Self::error(StatusCode::FAILED_DEPENDENCY, "not applied because another operation failed")
+    }
+}
+
+/// Validates an operation and checks the caller may perform it.
+async fn prepare(
+    pool: &PgPool,
+    events: &Events,
+    user: Option<&AuthUser>,
+    operation: Operation,
+    force: bool,
+) -> Result<Change, Response> {
+    match operation {
+        Operation::Create { event } => new_event(pool, events, user, event, force).await.map(Change::Create),
+        Operation::Update { id, mut changes } => {
+            changes.sanitize();
+            changes.validate_patch().map_err(validation_error)?;
+            timelines::ensure_event_writable(pool, events, user, id).await?;
+            changes.check_bounds(events, id).await?;
+            changes.resolve_custom_fields(pool, events, id).await?;
+            Ok(Change::Update(id, changes))
+        }
+        Operation::Delete { id } => {
+            timelines::ensure_event_writable(pool, events, user, id).await?;
+            Ok(Change::Delete(id))
+        }
+    }
+}
+
+fn from_outcome(outcome: Outcome, created: bool) -> OperationResult {
+    match outcome {
+        Outcome::Saved(event) if created => OperationResult::ok(StatusCode::CREATED, Some(*event)),
+        Outcome::Saved(event) => OperationResult::ok(StatusCode::OK, Some(*event)),
+        Outcome::Deleted => OperationResult::ok(StatusCode::NO_CONTENT, None),
+        Outcome::NotFound => OperationResult::error(StatusCode::NOT_FOUND, "event not found"),
+        Outcome::Failed(error) => {
+            tracing::error!(%error, "batch operation failed");
+            OperationResult::error(StatusCode::INTERNAL_SERVER_ERROR, "database error")
+        }
+        Outcome::RolledBack => OperationResult::not_applied(),
+    }
+}
+
+/// Creates, updates and deletes events in one request. Every operation is
+/// checked first; an atomic batch with a rejected operation changes nothing.
+/// The rest run in a single transaction, see `EventRepository::batch`.
+async fn run_batch(
+    State(pool): State<PgPool>,
+    State(events): State<Events>,
+    user: Option<AuthUser>,
+    Query(params): Query<CreateParams>,
+    Json(request): Json<BatchRequest>,
+) -> Result<Json<BatchResponse>, Response> {
+    if request.operations.len() > MAX_OPERATIONS {
+        let error = format!("at most {} operations per batch", MAX_OPERATIONS);
+        return Err((StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({ "error": error }))).into_response());
+    }
+    let atomic = request.atomic.unwrap_or(true);
+    let force = params.force.unwrap_or(false);
+
+    let mut results = Vec::with_capacity(request.operations.len());
+    let mut changes = Vec::new();
+    for operation in request.operations {
+        match prepare(&pool, &events, user.as_ref(), operation, force).await {
+            Ok(change) => {
+                // Filled in once the change has run.
+                results.push(None);
+                changes.push(change);
+            }
+            Err(response) => results.push(Some(OperationResult::rejected(response).await)),
+        }
+    }
+
+    if atomic && results.iter().any(Option::is_some) {
+        let results = results
+            .into_iter()
+            .map(|result| result.unwrap_or_else(OperationResult::not_applied))
+            .collect();
+        return Ok(Json(BatchResponse { committed: false, results }));
+    }
+
+    let created: Vec<bool> = changes.iter().map(|change| matches!(change, Change::Create(_))).collect();
+    let outcomes = events
+        .batch(changes, atomic)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
+    let committed = outcomes.iter().any(Outcome::applied);
+
+    let mut outcomes = outcomes.into_iter().zip(created);
+    let results = results
+        .into_iter()
+        .map(|result| {
+            result.unwrap_or_else(|| {
+                let (outcome, created) = outcomes.next().expect("one outcome per change");
+                from_outcome(outcome, created)
+            })
+        })
+        .collect();
+
+    Ok(Json(BatchResponse { committed, results }))
+}
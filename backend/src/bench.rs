@@ -0,0 +1,209 @@
+//! `cargo bench-queries`: seeds a scratch database with a million events
+//! and times the listing and search queries against it, printing each
+//! one's plan so that a filter missing its index shows up as a sequential
+//! scan. Runs against `BENCH_DATABASE_URL` only, never `DATABASE_URL`.
+
+use std::env;
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+use sqlx::{postgres::PgPoolOptions, PgPool, Postgres, QueryBuilder};
+
+use crate::db::{
+    events::{push_filter, EventFilter, EventRepository, EventSort, PgEvents},
+    Db,
+};
+
+/// Marks the seeded events, so a second run reuses them.
+const SEEDED: &str = "Seeded for benchmarks";
+
+fn env_number(name: &str, default: i64) -> i64 {
+    env::var(name)
+        .map(|value| value.parse().unwrap_or_else(|_| panic!("{} must be a number, got {:?}", name, value)))
+        .unwrap_or(default)
+}
+
+pub async fn run() {
+    let url = env::var("BENCH_DATABASE_URL").expect("BENCH_DATABASE_URL must point at a scratch database");
+    let events = env_number("BENCH_EVENTS", 1_000_000);
+    let runs = env_number("BENCH_RUNS", 20).max(1) as usize;
+
+    let pool = PgPoolOptions::new()
+        .max_connections(4)
+        .connect(&url)
+        .await
+        .expect("could not connect to BENCH_DATABASE_URL");
+    sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+    seed(&pool, events).await.expect("could not seed the benchmark events");
+
+    let store = PgEvents::new(Db::single(pool.clone()));
+    println!("{:<24} {:>9} {:>9} {:>9}  plan", "query", "p50 ms", "p95 ms", "max ms");
+    for (name, filter) in cases() {
+        let mut timings = Vec::with_capacity(runs);
+        for _ in 0..runs {
+            let started = Instant::now();
+            store.list(&filter).await.unwrap();
+            timings.push(started.elapsed());
+        }
+        let plan = explain(&pool, &filter).await.unwrap();
+        report(name, &mut timings, &plan);
+    }
+
+    let mut timings = Vec::with_capacity(runs);
+    for _ in 0..runs {
+        let started = Instant::now();
+        store.facets(&cases()[0].1).await.unwrap();
+        timings.push(started.elapsed());
+    }
+    report("facets", &mut timings, &[]);
+}
+
+/// Inserts events up to `count` across a hundred timelines, a tenth of
+/// them private, with the triggers off so the outbox isn't flooded.
+async fn seed(pool: &PgPool, count: i64) -> Result<(), sqlx::Error> {
+    let seeded = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM events WHERE description = $1")
+        .bind(SEEDED)
+        .fetch_one(pool)
+        .await?;
+    if seeded >= count {
+        return Ok(());
+    }
+
+    println!("seeding {} events", count - seeded);
+    let mut tx = pool.begin().await?;
+    sqlx::query(
+        r#"
+        INSERT INTO users (username, email, password_hash) VALUES ('bench', 'bench@example.invalid', NULL)
+        ON CONFLICT (username) DO NOTHING
+        "#,
+    )
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query(
+        r#"
+        INSERT INTO timelines (owner_id, title, is_private)
+        SELECT u.id, 'Bench ' || n, n % 10 = 0
+        FROM users u, generate_series(1, 100) n
+        WHERE u.username = 'bench'
+            AND NOT EXISTS (SELECT 1 FROM timelines WHERE owner_id = u.id)
+        "#,
+    )
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query("ALTER TABLE events DISABLE TRIGGER USER").execute(&mut *tx).await?;
+    sqlx::query(
+        r#"
+        INSERT INTO events (title, description, start_date, category, importance, status, timeline_id)
+        SELECT
+            'Event ' || n || ' ' || (ARRAY['battle', 'treaty', 'launch', 'election', 'discovery'])[1 + n % 5],
+            $2,
+            TIMESTAMP '1000-01-01' + n * INTERVAL '7 hours',
+            (ARRAY['War', 'Politics', 'Science', 'Art', NULL])[1 + n % 7 % 5],
+            1 + n % 5,
+            CASE WHEN n % 20 = 0 THEN 'draft' ELSE 'published' END,
+            CASE WHEN n % 3 = 0 THEN NULL ELSE t.ids[1 + n % 100] END
+        FROM generate_series(($1 + 1)::int, $3::int) n,
+            (SELECT array_agg(id) AS ids FROM timelines WHERE title LIKE 'Bench %') t
+        "#,
+    )
+    .bind(seeded)
+    .bind(SEEDED)
+    .bind(count)
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query("ALTER TABLE events ENABLE TRIGGER USER").execute(&mut *tx).await?;
+    tx.commit().await?;
+    sqlx::query("ANALYZE events").execute(pool).await?;
+    Ok(())
+}
+
+/// The listings the app runs most, as signed-out visitors see them.
+fn cases() -> Vec<(&'static str, EventFilter)> {
+    let listing = EventFilter {
+        status: "published".to_string(),
+        sort: EventSort::Newest,
+        limit: 20,
+        ..EventFilter::default()
+    };
+    vec![
+        ("newest", listing.clone()),
+        ("oldest, page 50", EventFilter { sort: EventSort::Oldest, offset: 980, ..listing.clone() }),
+        ("category", EventFilter { categories: vec!["science".to_string()], ..listing.clone() }),
+        ("search", EventFilter { search: Some("treaty".to_string()), ..listing.clone() }),
+        (
+            "date window",
+            EventFilter {
+                from: chrono::NaiveDate::from_ymd_opt(1500, 1, 1).and_then(|day| day.and_hms_opt(0, 0, 0)),
+                until: chrono::NaiveDate::from_ymd_opt(1501, 1, 1).and_then(|day| day.and_hms_opt(0, 0, 0)),
+                ..listing
+            },
+        ),
+    ]
+}
+
+/// The plan of the page query `list` runs for `filter`.
+async fn explain(pool: &PgPool, filter: &EventFilter) -> Result<Vec<String>, sqlx::Error> {
+    let mut query = QueryBuilder::<Postgres>::new("EXPLAIN (FORMAT JSON) SELECT e.* FROM events e");
+    push_filter(&mut query, filter);
+    query
+        .push(filter.sort.order_by())
+        .push(" LIMIT ")
+        .push_bind(filter.limit)
+        .push(" OFFSET ")
+        .push_bind(filter.offset);
+    let plan = query.build_query_scalar::<Value>().fetch_one(pool).await?;
+    Ok(plan_nodes(&plan[0]["Plan"]))
+}
+
+/// The scans in a JSON plan, such as `Index Scan on events_category_idx`.
+fn plan_nodes(plan: &Value) -> Vec<String> {
+    let mut nodes = Vec::new();
+    let kind = plan["Node Type"].as_str().unwrap_or_default();
+    if kind.contains("Scan") {
+        match plan["Index Name"].as_str().or(plan["Relation Name"].as_str()) {
+            Some(target) => nodes.push(format!("{} on {}", kind, target)),
+            None => nodes.push(kind.to_string()),
+        }
+    }
+    for child in plan["Plans"].as_array().into_iter().flatten() {
+        nodes.extend(plan_nodes(child));
+    }
+    nodes
+}
+
+fn report(name: &str, timings: &mut [Duration], plan: &[String]) {
+    timings.sort();
+    let at = |fraction: f64| {
+        let index = ((timings.len() as f64 * fraction).ceil() as usize).clamp(1, timings.len()) - 1;
+        timings[index].as_secs_f64() * 1000.0
+    };
+    println!(
+        "{:<24} {:>9.2} {:>9.2} {:>9.2}  {}",
+        name,
+        at(0.5),
+        at(0.95),
+        at(1.0),
+        plan.join(", ")
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plans_list_their_scans() {
+        let plan = serde_json::json!({
+            "Node Type": "Limit",
+            "Plans": [{
+                "Node Type": "Bitmap Heap Scan",
+                "Relation Name": "events",
+                "Plans": [{ "Node Type": "Bitmap Index Scan", "Index Name": "events_category_idx" }],
+            }],
+        });
+        assert_eq!(
+            plan_nodes(&plan),
+            vec!["Bitmap Heap Scan on events", "Bitmap Index Scan on events_category_idx"]
+        );
+    }
+}
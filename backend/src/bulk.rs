@@ -0,0 +1,233 @@
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    routing::post,
+    Json, Router,
+};
+use serde::Deserialize;
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::{
+    auth::AuthUser,
+    db::events::Events,
+    export::{self, Format},
+    sanitize, tags, timelines, AppState, Event,
+};
+
+/// Most events one bulk action may cover.
+const MAX_EVENTS: usize = 500;
+const MAX_CATEGORY_LENGTH: usize = 100;
+
+pub fn routes() -> Router<AppState> {
+    Router::new().route("/api/events/bulk", post(run_bulk))
+}
+
+/// What to do with every selected event.
+#[derive(Deserialize, Debug, PartialEq)]
+#[serde(tag = "action", rename_all = "snake_case")]
+enum BulkAction {
+    Delete,
+    Publish,
+    /// `null` clears the category.
+    SetCategory { category: Option<String> },
+    /// Adds to the events' tags, keeping the ones they have.
+    AddTags { tags: Vec<String> },
+    /// Downloads the events as JSON.
+    Export,
+}
+
+#[derive(Deserialize)]
+struct BulkRequest {
+    event_ids: Vec<Uuid>,
+    #[serde(flatten)]
+    action: BulkAction,
+}
+
+/// Applies one action to a selection of events in a single transaction:
+/// if any event is missing or the caller may not change it, nothing
+/// changes. Exporting only needs the events to be visible.
+///
+/// Changes return the affected events; deletes return `204 No Content`.
+async fn run_bulk(
+    State(pool): State<PgPool>,
+    State(events): State<Events>,
+    user: Option<AuthUser>,
+    Json(request): Json<BulkRequest>,
+) -> Result<Response, Response> {
+    let mut ids = request.event_ids;
+    ids.sort();
+    ids.dedup();
+    if ids.is_empty() || ids.len() > MAX_EVENTS {
+        let message = format!("select between 1 and {} events", MAX_EVENTS);
+        return Err((StatusCode::UNPROCESSABLE_ENTITY, message).into_response());
+    }
+    for id in &ids {
+        match request.action {
+            BulkAction::Export => timelines::ensure_event_visible(&pool, &events, user.as_ref(), *id).await?,
+            _ => timelines::ensure_event_writable(&pool, &events, user.as_ref(), *id).await?,
+        }
+    }
+    let error = |_| StatusCode::INTERNAL_SERVER_ERROR.into_response();
+
+    let mut tx = pool.begin().await.map_err(error)?;
+    let response = match request.action {
+        BulkAction::Delete => {
+            let deleted = sqlx::query("DELETE FROM events WHERE id = ANY($1)")
+                .bind(&ids)
+                .execute(&mut *tx)
+                .await
+                .map_err(error)?
+                .rows_affected();
+            if deleted < ids.len() as u64 {
+                return Err(StatusCode::NOT_FOUND.into_response());
+            }
+            StatusCode::NO_CONTENT.into_response()
+        }
+        BulkAction::Publish => {
+            let events = update(
+                &mut tx,
+                "UPDATE events SET status = 'published', publish_at = NULL, updated_at = NOW() \
+                 WHERE id = ANY($1) RETURNING *",
+                &ids,
+            )
+            .await?;
+            Json(events).into_response()
+        }
+        BulkAction::SetCategory { mut category } => {
+            sanitize::optional(&mut category);
+            if category.as_ref().is_some_and(|category| category.chars().count() > MAX_CATEGORY_LENGTH) {
+                return Err(StatusCode::UNPROCESSABLE_ENTITY.into_response());
+            }
+            let events = sqlx::query_as::<_, Event>(
+                "UPDATE events SET category = $2, updated_at = NOW() WHERE id = ANY($1) RETURNING *",
+            )
+            .bind(&ids)
+            .bind(category)
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(error)?;
+            if events.len() < ids.len() {
+                return Err(StatusCode::NOT_FOUND.into_response());
+            }
+            Json(events).into_response()
+        }
+        BulkAction::AddTags { tags: names } => {
+            let names = tags::normalize(names).map_err(IntoResponse::into_response)?;
+            add_tags(&mut tx, &ids, &names).await?;
+            let events = update(&mut tx, "UPDATE events SET updated_at = NOW() WHERE id = ANY($1) RETURNING *", &ids)
+                .await?;
+            Json(events).into_response()
+        }
+        BulkAction::Export => {
+            // Streamed like the timeline exports, so the rows are read from
+            // the pool once the (empty) transaction is over.
+            let (sender, body) = export::channel();
+            let pool = pool.clone();
+            tokio::spawn(async move {
+                let rows = sqlx::query_as::<_, Event>("SELECT * FROM events WHERE id = ANY($1) ORDER BY start_date")
+                    .bind(ids)
+                    .fetch(&pool);
+                export::pump(rows, Format::Json, sender).await;
+            });
+            (
+                [
+                    (header::CONTENT_TYPE, Format::Json.content_type()),
+                    (header::CONTENT_DISPOSITION, "attachment; filename=\"events.json\""),
+                ],
+                body,
+            )
+                .into_response()
+        }
+    };
+    tx.commit().await.map_err(error)?;
+
+    Ok(response)
+}
+
+/// Runs an `UPDATE ... RETURNING *` over `ids`, failing with 404 unless
+/// every event was there.
+async fn update(tx: &mut Transaction<'_, Postgres>, query: &str, ids: &[Uuid]) -> Result<Vec<Event>, Response> {
+    let events = sqlx::query_as::<_, Event>(query)
+        .bind(ids)
+        .fetch_all(&mut **tx)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
+    if events.len() < ids.len() {
+        return Err(StatusCode::NOT_FOUND.into_response());
+    }
+    Ok(events)
+}
+
+/// Tags every event with `names`, creating unknown tags. Fails if an event
+/// would end up with more than `tags::MAX_TAGS_PER_EVENT`.
+async fn add_tags(tx: &mut Transaction<'_, Postgres>, ids: &[Uuid], names: &[String]) -> Result<(), Response> {
+    let error = |_| StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    sqlx::query("INSERT INTO tags (name) SELECT UNNEST($1::varchar[]) ON CONFLICT (name) DO NOTHING")
+        .bind(names)
+        .execute(&mut **tx)
+        .await
+        .map_err(error)?;
+    sqlx::query(
+        r#"
+        INSERT INTO event_tags (event_id, tag_id)
+        SELECT e.id, t.id FROM events e, tags t
+        WHERE e.id = ANY($1) AND t.name = ANY($2)
+        ON CONFLICT DO NOTHING
+        "#,
+    )
+    .bind(ids)
+    .bind(names)
+    .execute(&mut **tx)
+    .await
+    .map_err(error)?;
+
+    let over_limit = sqlx::query_scalar::<_, bool>(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM event_tags WHERE event_id = ANY($1)
+            GROUP BY event_id HAVING COUNT(*) > $2
+        )
+        "#,
+    )
+    .bind(ids)
+    .bind(tags::MAX_TAGS_PER_EVENT as i64)
+    .fetch_one(&mut **tx)
+    .await
+    .map_err(error)?;
+    if over_limit {
+        let message = format!("events can have at most {} tags", tags::MAX_TAGS_PER_EVENT);
+        return Err((StatusCode::UNPROCESSABLE_ENTITY, message).into_response());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(json: serde_json::Value) -> BulkAction {
+        serde_json::from_value::<BulkRequest>(json).unwrap().action
+    }
+
+    #[test]
+    fn reads_the_action_beside_the_selection() {
+        let id = Uuid::new_v4();
+        assert_eq!(parse(serde_json::json!({ "event_ids": [id], "action": "publish" })), BulkAction::Publish);
+        assert_eq!(
+            parse(serde_json::json!({ "event_ids": [id], "action": "set_category", "category": null })),
+            BulkAction::SetCategory { category: None }
+        );
+        assert_eq!(
+            parse(serde_json::json!({ "event_ids": [id], "action": "add_tags", "tags": ["war"] })),
+            BulkAction::AddTags { tags: vec!["war".to_string()] }
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_actions() {
+        let request = serde_json::json!({ "event_ids": [], "action": "archive" });
+        assert!(serde_json::from_value::<BulkRequest>(request).is_err());
+    }
+}
@@ -0,0 +1,163 @@
+//! The event stream: every change to an event, published to a message
+//! broker for systems that index, search or analyze timelines. Changes
+//! reach the broker through the outbox (see `outbox`), so a change is never
+//! published without being committed and is published at least once.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Arc;
+
+use axum::async_trait;
+use tokio::sync::Mutex;
+
+use crate::config::{self, BusKind};
+
+/// Why the broker didn't take a message; the relay retries it later.
+#[derive(Debug)]
+pub struct BusError(pub String);
+
+impl fmt::Display for BusError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// A broker that event changes are published to.
+#[async_trait]
+pub trait EventBus: Send + Sync {
+    /// Publishes `payload`, a change of type `kind` such as
+    /// `event.updated`, returning once the broker has stored it. Changes
+    /// with the same `key` must reach consumers in the order published.
+    async fn publish(&self, kind: &str, key: &str, payload: &[u8]) -> Result<(), BusError>;
+}
+
+pub type Bus = Arc<dyn EventBus>;
+
+/// Drops every change; used when no broker is configured.
+pub struct NoopBus;
+
+#[async_trait]
+impl EventBus for NoopBus {
+    async fn publish(&self, _kind: &str, _key: &str, _payload: &[u8]) -> Result<(), BusError> {
+        Ok(())
+    }
+}
+
+/// Publishes to NATS JetStream on `<topic>.<kind>`, e.g.
+/// `timeline.events.event.updated`, so consumers can subscribe to only the
+/// changes they want. The stream must already exist.
+pub struct NatsBus {
+    jetstream: async_nats::jetstream::Context,
+    prefix: String,
+}
+
+/// The subject a change is published on.
+fn subject(prefix: &str, kind: &str) -> String {
+    format!("{}.{}", prefix, kind)
+}
+
+#[async_trait]
+impl EventBus for NatsBus {
+    async fn publish(&self, kind: &str, key: &str, payload: &[u8]) -> Result<(), BusError> {
+        let mut headers = async_nats::HeaderMap::new();
+        headers.insert("Event-Id", key);
+        self.jetstream
+            .publish_with_headers(subject(&self.prefix, kind), headers, payload.to_vec().into())
+            .await
+            .map_err(|err| BusError(err.to_string()))?
+            .await
+            .map(|_| ())
+            .map_err(|err| BusError(err.to_string()))
+    }
+}
+
+/// Publishes to one Kafka topic, partition 0, so every change keeps its
+/// order; the change type goes in the `type` header and the event id is
+/// the record key.
+pub struct KafkaBus {
+    client: rskafka::client::Client,
+    topic: String,
+    partition: Mutex<Option<Arc<rskafka::client::partition::PartitionClient>>>,
+}
+
+impl KafkaBus {
+    /// The partition client, connected on first use and kept.
+    async fn partition(&self) -> Result<Arc<rskafka::client::partition::PartitionClient>, BusError> {
+        let mut partition = self.partition.lock().await;
+        if let Some(client) = partition.as_ref() {
+            return Ok(client.clone());
+        }
+        let client = self
+            .client
+            .partition_client(
+                self.topic.clone(),
+                0,
+                rskafka::client::partition::UnknownTopicHandling::Retry,
+            )
+            .await
+            .map(Arc::new)
+            .map_err(|err| BusError(err.to_string()))?;
+        *partition = Some(client.clone());
+        Ok(client)
+    }
+}
+
+#[async_trait]
+impl EventBus for KafkaBus {
+    async fn publish(&self, kind: &str, key: &str, payload: &[u8]) -> Result<(), BusError> {
+        let record = rskafka::record::Record {
+            key: Some(key.as_bytes().to_vec()),
+            value: Some(payload.to_vec()),
+            headers: BTreeMap::from([("type".to_string(), kind.as_bytes().to_vec())]),
+            timestamp: chrono::Utc::now(),
+        };
+        self.partition()
+            .await?
+            .produce(vec![record], rskafka::client::partition::Compression::NoCompression)
+            .await
+            .map(|_| ())
+            .map_err(|err| BusError(err.to_string()))
+    }
+}
+
+/// Connects to the configured broker, or returns the no-op bus when there
+/// is none.
+pub async fn connect(config: Option<&config::EventBus>) -> Result<Bus, BusError> {
+    let Some(config) = config else {
+        tracing::info!("EVENT_BUS not set; event changes are not published");
+        return Ok(Arc::new(NoopBus));
+    };
+    match config.kind {
+        BusKind::Nats => {
+            let client = async_nats::connect(&config.url)
+                .await
+                .map_err(|err| BusError(err.to_string()))?;
+            Ok(Arc::new(NatsBus {
+                jetstream: async_nats::jetstream::new(client),
+                prefix: config.topic.clone(),
+            }))
+        }
+        BusKind::Kafka => {
+            let brokers = config.url.split(',').map(|broker| broker.trim().to_string()).collect();
+            let client = rskafka::client::ClientBuilder::new(brokers)
+                .build()
+                .await
+                .map_err(|err| BusError(err.to_string()))?;
+            Ok(Arc::new(KafkaBus {
+                client,
+                topic: config.topic.clone(),
+                partition: Mutex::new(None),
+            }))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nats_subjects_end_with_the_change() {
+        assert_eq!(subject("timeline.events", "event.deleted"), "timeline.events.event.deleted");
+    }
+}
@@ -0,0 +1,446 @@
+//! Cache for the hot public reads: event lists and details as signed-out
+//! visitors see them, which is what popular public timelines get hammered
+//! with. Responses are kept as the JSON sent, in Redis or in memory as
+//! `Config::cache` says, and nothing is cached without one.
+//!
+//! Entries are never looked up for deletion. Each key includes the current
+//! generation of the scopes it depends on, such as `timeline:<id>`, and a
+//! change moves those scopes to a new generation, so the old entries are
+//! simply never read again and expire. Changes are announced through
+//! Postgres `NOTIFY` by triggers, so every instance hears about every
+//! change, whichever code path made it.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::{
+    async_trait,
+    http::header,
+    response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+use sqlx::{postgres::PgListener, PgPool};
+use uuid::Uuid;
+
+use crate::config::{self, CacheKind};
+
+/// The Postgres channel changes to events, their tags, people and likes,
+/// and timelines are announced on.
+const CHANNEL: &str = "read_changes";
+/// How long a scope keeps its generation without changing. Longer than
+/// any entry lives; losing one only costs misses.
+const GENERATION_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+#[derive(Debug)]
+pub struct CacheError(String);
+
+impl fmt::Display for CacheError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Somewhere to keep bytes for a while.
+#[async_trait]
+pub trait Cache: Send + Sync {
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, CacheError>;
+    async fn set(&self, key: &str, value: Vec<u8>, ttl: Duration) -> Result<(), CacheError>;
+}
+
+/// A cache local to the process, for single instances. Holds at most
+/// `max_entries`; when full, the entry closest to expiring goes first.
+pub struct MemoryCache {
+    entries: Mutex<HashMap<String, (Instant, Vec<u8>)>>,
+    max_entries: usize,
+}
+
+impl MemoryCache {
+    pub fn new(max_entries: usize) -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            max_entries: max_entries.max(1),
+        }
+    }
+}
+
+#[async_trait]
+impl Cache for MemoryCache {
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, CacheError> {
+        let entries = self.entries.lock().map_err(|err| CacheError(err.to_string()))?;
+        Ok(entries
+            .get(key)
+            .filter(|(expires_at, _)| *expires_at > Instant::now())
+            .map(|(_, value)| value.clone()))
+    }
+
+    async fn set(&self, key: &str, value: Vec<u8>, ttl: Duration) -> Result<(), CacheError> {
+        let mut entries = self.entries.lock().map_err(|err| CacheError(err.to_string()))?;
+        let now = Instant::now();
+        if entries.len() >= self.max_entries && !entries.contains_key(key) {
+            entries.retain(|_, (expires_at, _)| *expires_at > now);
+        }
+        if entries.len() >= self.max_entries && !entries.contains_key(key) {
+            let soonest = entries
+                .iter()
+                .min_by_key(|(_, (expires_at, _))| *expires_at)
+                .map(|(key, _)| key.clone());
+            if let Some(soonest) = soonest {
+                entries.remove(&soonest);
+            }
+        }
+        entries.insert(key.to_string(), (now + ttl, value));
+        Ok(())
+    }
+}
+
+/// A Redis server shared by every instance.
+pub struct RedisCache {
+    connection: redis::aio::ConnectionManager,
+}
+
+impl RedisCache {
+    pub async fn connect(url: &str) -> Result<Self, CacheError> {
+        let client = redis::Client::open(url).map_err(|err| CacheError(err.to_string()))?;
+        let connection = client
+            .get_connection_manager()
+            .await
+            .map_err(|err| CacheError(err.to_string()))?;
+        Ok(Self { connection })
+    }
+}
+
+#[async_trait]
+impl Cache for RedisCache {
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, CacheError> {
+        redis::cmd("GET")
+            .arg(key)
+            .query_async(&mut self.connection.clone())
+            .await
+            .map_err(|err| CacheError(err.to_string()))
+    }
+
+    async fn set(&self, key: &str, value: Vec<u8>, ttl: Duration) -> Result<(), CacheError> {
+        redis::cmd("SET")
+            .arg(key)
+            .arg(value)
+            .arg("EX")
+            .arg(ttl.as_secs().max(1))
+            .query_async(&mut self.connection.clone())
+            .await
+            .map_err(|err| CacheError(err.to_string()))
+    }
+}
+
+/// Which kind of read a lookup is for; each has its own TTL and counters.
+#[derive(Clone, Copy)]
+pub enum Read {
+    List,
+    Detail,
+}
+
+#[derive(Default)]
+struct Counters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl Counters {
+    fn stats(&self) -> ReadStats {
+        let hits = self.hits.load(Ordering::Relaxed);
+        let misses = self.misses.load(Ordering::Relaxed);
+        ReadStats {
+            hits,
+            misses,
+            hit_ratio: (hits + misses > 0).then(|| hits as f64 / (hits + misses) as f64),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct ReadStats {
+    hits: u64,
+    misses: u64,
+    hit_ratio: Option<f64>,
+}
+
+/// Hits and misses since the process started, for the admin system info.
+#[derive(Serialize)]
+pub struct Stats {
+    backend: Option<&'static str>,
+    list: ReadStats,
+    detail: ReadStats,
+}
+
+/// The read cache handlers use, shared through `AppState`.
+#[derive(Clone)]
+pub struct ReadCache {
+    store: Option<Arc<dyn Cache>>,
+    backend: Option<&'static str>,
+    list_ttl: Duration,
+    detail_ttl: Duration,
+    lists: Arc<Counters>,
+    details: Arc<Counters>,
+}
+
+impl ReadCache {
+    fn new(store: Option<Arc<dyn Cache>>, config: Option<&config::Cache>) -> Self {
+        Self {
+            store,
+            backend: config.map(|config| config.kind.name()),
+            list_ttl: config.map_or(Duration::ZERO, |config| config.list_ttl),
+            detail_ttl: config.map_or(Duration::ZERO, |config| config.detail_ttl),
+            lists: Arc::default(),
+            details: Arc::default(),
+        }
+    }
+
+    /// Connects to the configured cache; without one every read loads.
+    pub async fn connect(config: Option<&config::Cache>) -> Result<Self, CacheError> {
+        let store: Option<Arc<dyn Cache>> = match config {
+            None => None,
+            Some(cache) => match (cache.kind, &cache.url) {
+                (CacheKind::Memory, _) => Some(Arc::new(MemoryCache::new(cache.max_entries))),
+                (CacheKind::Redis, Some(url)) => Some(Arc::new(RedisCache::connect(url).await?)),
+                (CacheKind::Redis, None) => return Err(CacheError("CACHE_URL must be set for redis".to_string())),
+            },
+        };
+        Ok(Self::new(store, config))
+    }
+
+    pub fn stats(&self) -> Stats {
+        Stats {
+            backend: self.backend,
+            list: self.lists.stats(),
+            detail: self.details.stats(),
+        }
+    }
+
+    /// The generation `scope` is on, starting one when it has none.
+    async fn generation(&self, store: &dyn Cache, scope: &str) -> Result<String, CacheError> {
+        let key = format!("generation:{}", scope);
+        if let Some(generation) = store.get(&key).await? {
+            return Ok(String::from_utf8_lossy(&generation).into_owned());
+        }
+        let generation = Uuid::new_v4().simple().to_string();
+        store.set(&key, generation.clone().into_bytes(), GENERATION_TTL).await?;
+        Ok(generation)
+    }
+
+    /// Moves `scope` to a new generation, retiring the entries under it.
+    async fn invalidate(&self, store: &dyn Cache, scope: &str) -> Result<(), CacheError> {
+        let key = format!("generation:{}", scope);
+        store
+            .set(&key, Uuid::new_v4().simple().to_string().into_bytes(), GENERATION_TTL)
+            .await
+    }
+
+    /// The key for `name`, valid until any of `scopes` changes.
+    async fn key<S: AsRef<str>>(&self, store: &dyn Cache, scopes: &[S], name: &str) -> Result<String, CacheError> {
+        let mut key = String::new();
+        for scope in scopes {
+            key.push_str(scope.as_ref());
+            key.push('@');
+            key.push_str(&self.generation(store, scope.as_ref()).await?);
+            key.push(':');
+        }
+        key.push_str(name);
+        Ok(key)
+    }
+
+    /// The JSON response for `name`, from the cache while none of `scopes`
+    /// has changed, otherwise from `load` and kept. Failures are never
+    /// kept, and a cache that isn't answering is treated as a miss.
+    pub async fn read<S, T, F, Fut>(&self, read: Read, scopes: &[S], name: &str, load: F) -> Result<Response, Response>
+    where
+        S: AsRef<str>,
+        T: Serialize,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, Response>>,
+    {
+        let Some(store) = self.store.as_deref() else {
+            return load().await.map(|value| json_response(serde_json::to_vec(&value).unwrap_or_default()));
+        };
+        let (counters, ttl) = match read {
+            Read::List => (&self.lists, self.list_ttl),
+            Read::Detail => (&self.details, self.detail_ttl),
+        };
+
+        let key = match self.key(store, scopes, name).await {
+            Ok(key) => match store.get(&key).await {
+                Ok(Some(body)) => {
+                    counters.hits.fetch_add(1, Ordering::Relaxed);
+                    return Ok(json_response(body));
+                }
+                Ok(None) => Some(key),
+                Err(err) => {
+                    tracing::warn!(error = %err, "cache read failed");
+                    None
+                }
+            },
+            Err(err) => {
+                tracing::warn!(error = %err, "cache read failed");
+                None
+            }
+        };
+        counters.misses.fetch_add(1, Ordering::Relaxed);
+
+        let body = serde_json::to_vec(&load().await?).unwrap_or_default();
+        if let Some(key) = key {
+            if let Err(err) = store.set(&key, body.clone(), ttl).await {
+                tracing::warn!(error = %err, "cache write failed");
+            }
+        }
+        Ok(json_response(body))
+    }
+
+    /// Retires what `change` makes stale.
+    async fn apply(&self, store: &dyn Cache, change: &Change) -> Result<(), CacheError> {
+        let mut scopes = vec!["events".to_string()];
+        match change.event_id {
+            Some(event_id) => scopes.push(format!("event:{}", event_id)),
+            // A timeline changed, and with it possibly any of its events.
+            None => scopes.push("timelines".to_string()),
+        }
+        scopes.extend(change.timeline_ids.iter().map(|id| format!("timeline:{}", id)));
+        for scope in scopes {
+            self.invalidate(store, &scope).await?;
+        }
+        Ok(())
+    }
+
+    /// Listens for changes for as long as the process runs, coming back
+    /// after losing the connection. Nothing to do without a cache.
+    pub fn listen(&self, pool: PgPool) {
+        if self.store.is_none() {
+            return;
+        }
+        let cache = self.clone();
+        tokio::spawn(async move {
+            loop {
+                if let Err(err) = cache.follow(&pool).await {
+                    tracing::warn!(error = %err, "lost the read changes channel");
+                }
+                tokio::time::sleep(Duration::from_secs(5)).await;
+            }
+        });
+    }
+
+    async fn follow(&self, pool: &PgPool) -> Result<(), sqlx::Error> {
+        let Some(store) = self.store.as_deref() else {
+            return Ok(());
+        };
+        let mut listener = PgListener::connect_with(pool).await?;
+        listener.listen(CHANNEL).await?;
+        loop {
+            let message = listener.recv().await?;
+            match serde_json::from_str::<Change>(message.payload()) {
+                Ok(change) => {
+                    if let Err(err) = self.apply(store, &change).await {
+                        tracing::warn!(error = %err, "failed to invalidate cached reads");
+                    }
+                }
+                Err(err) => tracing::warn!(error = %err, "unreadable read change"),
+            }
+        }
+    }
+}
+
+/// What the triggers announce: an event and the timelines it was or is on,
+/// or just a timeline.
+#[derive(Deserialize)]
+struct Change {
+    event_id: Option<Uuid>,
+    #[serde(default)]
+    timeline_ids: Vec<Uuid>,
+}
+
+fn json_response(body: Vec<u8>) -> Response {
+    ([(header::CONTENT_TYPE, "application/json")], body).into_response()
+}
+
+/// Scopes a public event detail depends on.
+pub fn event_scopes(id: Uuid) -> [String; 2] {
+    [format!("event:{}", id), "timelines".to_string()]
+}
+
+/// Scope of the public events on a timeline.
+pub fn timeline_scope(id: Uuid) -> String {
+    format!("timeline:{}", id)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+
+    use super::*;
+
+    fn memory() -> ReadCache {
+        ReadCache {
+            store: Some(Arc::new(MemoryCache::new(100))),
+            backend: Some("memory"),
+            list_ttl: Duration::from_secs(30),
+            detail_ttl: Duration::from_secs(300),
+            lists: Arc::default(),
+            details: Arc::default(),
+        }
+    }
+
+    async fn body(response: Response) -> String {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn reads_are_kept_until_their_scope_changes() {
+        let cache = memory();
+        let timeline = Uuid::new_v4();
+        let scope = timeline_scope(timeline);
+        let scopes = [scope];
+        let read = |title: &'static str| {
+            cache.read(Read::List, &scopes, "page=1", move || async move {
+                Ok::<_, Response>(serde_json::json!([{ "title": title }]))
+            })
+        };
+
+        assert_eq!(body(read("Apollo 11").await.unwrap()).await, r#"[{"title":"Apollo 11"}]"#);
+        assert_eq!(body(read("Apollo 12").await.unwrap()).await, r#"[{"title":"Apollo 11"}]"#);
+
+        let store = cache.store.clone().unwrap();
+        let change = Change { event_id: Some(Uuid::new_v4()), timeline_ids: vec![timeline] };
+        cache.apply(store.as_ref(), &change).await.unwrap();
+        assert_eq!(body(read("Apollo 12").await.unwrap()).await, r#"[{"title":"Apollo 12"}]"#);
+
+        let stats = cache.stats();
+        assert_eq!((stats.list.hits, stats.list.misses), (1, 2));
+    }
+
+    #[tokio::test]
+    async fn failures_are_not_kept() {
+        let cache = memory();
+        let missing = cache
+            .read(Read::Detail, &["event:1"], "", || async { Err::<(), _>(StatusCode::NOT_FOUND.into_response()) })
+            .await
+            .unwrap_err();
+        assert_eq!(missing.status(), StatusCode::NOT_FOUND);
+        let found = cache
+            .read(Read::Detail, &["event:1"], "", || async { Ok::<_, Response>("found") })
+            .await
+            .unwrap();
+        assert_eq!(body(found).await, r#""found""#);
+    }
+
+    #[tokio::test]
+    async fn a_full_memory_cache_drops_what_expires_first() {
+        let store = MemoryCache::new(2);
+        store.set("a", b"1".to_vec(), Duration::from_secs(10)).await.unwrap();
+        store.set("b", b"2".to_vec(), Duration::from_secs(60)).await.unwrap();
+        store.set("c", b"3".to_vec(), Duration::from_secs(60)).await.unwrap();
+        assert_eq!(store.get("a").await.unwrap(), None);
+        assert_eq!(store.get("b").await.unwrap(), Some(b"2".to_vec()));
+        assert_eq!(store.get("c").await.unwrap(), Some(b"3".to_vec()));
+    }
+}
@@ -0,0 +1,73 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    routing::get,
+    Json, Router,
+};
+use serde::Deserialize;
+
+use crate::{
+    auth::AuthUser,
+    db::events::{CategoryCount, EventFilter, Events},
+    AppState,
+};
+
+pub fn routes() -> Router<AppState> {
+    Router::new().route("/api/categories/counts", get(get_counts))
+}
+
+/// Query string of `GET /api/categories/counts`; the same window and search
+/// as `GET /api/events`, so the counts match the list beside them.
+#[derive(Deserialize)]
+struct CountsQuery {
+    search: Option<String>,
+    start_date: Option<chrono::NaiveDateTime>,
+    end_date: Option<chrono::NaiveDateTime>,
+    organization_id: Option<uuid::Uuid>,
+}
+
+/// Reads a comma-separated `categories` parameter, e.g. `Politics,Science`.
+pub fn parse(list: Option<&str>) -> Vec<String> {
+    list.unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Published events per category, most first. Events without a category
+/// are counted under `db::events::UNCATEGORIZED`.
+async fn get_counts(
+    State(events): State<Events>,
+    user: Option<AuthUser>,
+    Query(query): Query<CountsQuery>,
+) -> Result<Json<Vec<CategoryCount>>, StatusCode> {
+    let filter = EventFilter {
+        search: query.search.filter(|search| !search.is_empty()),
+        start_date: query.start_date,
+        end_date: query.end_date,
+        status: "published".to_string(),
+        editor: user.as_ref().map(|user| (user.id, user.is_admin())),
+        organization_id: query.organization_id,
+        ..EventFilter::default()
+    };
+    let counts = events
+        .category_counts(&filter)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(counts))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_a_comma_separated_list() {
+        assert_eq!(parse(Some(" Politics,,Science ")), ["Politics", "Science"]);
+        assert!(parse(Some("")).is_empty());
+        assert!(parse(None).is_empty());
+    }
+}
@@ -0,0 +1,382 @@
+//! Comments under events, from signed-in users who can see the event.
+//! Comments can be replied to, up to `MAX_DEPTH` levels deep. An
+//! `@username` in a comment notifies that user, and a reply the author of
+//! the comment it answers, if they can see the event too. Where the
+//! instance allows it, signed-out visitors comment on public events under
+//! a name they give, past the checks in `spam`.
+
+use std::collections::HashMap;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{delete, get},
+    Json, Router,
+};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    auth::AuthUser,
+    db::{events::Events, Reader},
+    notifications, reactions, sanitize,
+    spam::{self, ClientAddress},
+    timelines, validation_error, AppState,
+};
+
+/// Most users one comment notifies, so a comment can't page everyone.
+const MAX_MENTIONS: usize = 10;
+/// Deepest a reply may sit; top-level comments are at depth 0.
+const MAX_DEPTH: i16 = 4;
+
+/// Comments with their author's name; the caller is bound as `$1`.
+const SELECT: &str = r#"
+    SELECT c.id, c.event_id, c.parent_comment_id, c.depth, c.author_id,
+        COALESCE(u.username, c.guest_name) AS author, c.author_id IS NULL AS guest, c.body, c.created_at,
+        COALESCE(c.author_id = $1, FALSE) AS mine
+    FROM comments c LEFT JOIN users u ON u.id = c.author_id
+"#;
+
+/// The event bound as `$2`'s comments in reading order: each thread
+/// after its parent, replies oldest first. The walk starts from the
+/// top-level comments and follows the parent index down.
+const THREADED: &str = r#"
+    WITH RECURSIVE thread AS (
+        SELECT c.id, ARRAY[TO_CHAR(c.created_at, 'YYYYMMDDHH24MISSUS') || c.id::text] AS path
+        FROM comments c
+        WHERE c.event_id = $2 AND c.parent_comment_id IS NULL
+        UNION ALL
+        SELECT c.id, t.path || (TO_CHAR(c.created_at, 'YYYYMMDDHH24MISSUS') || c.id::text)
+        FROM comments c JOIN thread t ON c.parent_comment_id = t.id
+    )
+"#;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/events/:id/comments", get(list_comments).post(create_comment))
+        .route("/api/comments/:id", delete(delete_comment))
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+struct Comment {
+    id: Uuid,
+    event_id: Uuid,
+    /// The comment this replies to.
+    parent_comment_id: Option<Uuid>,
+    depth: i16,
+    author_id: Option<Uuid>,
+    author: String,
+    /// Written signed out, under a name the visitor gave.
+    guest: bool,
+    body: String,
+    created_at: NaiveDateTime,
+    /// Whether the caller wrote it.
+    mine: bool,
+    #[sqlx(skip)]
+    reactions: Vec<reactions::Count>,
+    /// Answers to it, when listed as threads.
+    #[sqlx(skip)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    replies: Option<Vec<Comment>>,
+}
+
+#[derive(Deserialize, Validate)]
+struct NewComment {
+    #[validate(length(min = 1, max = 5000))]
+    body: String,
+    /// Makes it a reply to this comment on the same event.
+    parent_comment_id: Option<Uuid>,
+    /// What a signed-out visitor goes by.
+    #[validate(length(min = 1, max = 50))]
+    name: Option<String>,
+    #[serde(flatten)]
+    proof: spam::Proof,
+}
+
+#[derive(Deserialize)]
+struct ListComments {
+    /// Nest replies under what they answer instead of listing every
+    /// comment oldest first.
+    #[serde(default)]
+    threaded: bool,
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '.')
+}
+
+/// The usernames `@mentioned` in `body`, each once, in order. A mention
+/// starts a word, so email addresses don't count, and a trailing full stop
+/// ends the sentence rather than the name.
+fn mentions(body: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    let mut previous = ' ';
+    for (at, c) in body.char_indices() {
+        if c == '@' && !is_name_char(previous) {
+            let rest = &body[at + 1..];
+            let end = rest.find(|c: char| !is_name_char(c)).unwrap_or(rest.len());
+            let name = rest[..end].trim_end_matches('.');
+            if !name.is_empty() && !names.iter().any(|known| known == name) {
+                names.push(name.to_string());
+            }
+        }
+        previous = c;
+    }
+    names
+}
+
+async fn find(pool: &PgPool, id: Uuid, user: Option<Uuid>) -> Result<Comment, sqlx::Error> {
+    sqlx::query_as::<_, Comment>(&format!("{} WHERE c.id = $2", SELECT))
+        .bind(user)
+        .bind(id)
+        .fetch_one(pool)
+        .await
+}
+
+/// Nests `comments` under their parents, keeping each level's order.
+fn thread(comments: Vec<Comment>) -> Vec<Comment> {
+    fn replies_to(parent: Option<Uuid>, children: &mut HashMap<Option<Uuid>, Vec<Comment>>) -> Vec<Comment> {
+        let mut level = children.remove(&parent).unwrap_or_default();
+        for comment in &mut level {
+            comment.replies = Some(replies_to(Some(comment.id), children));
+        }
+        level
+    }
+
+    let mut children: HashMap<Option<Uuid>, Vec<Comment>> = HashMap::new();
+    for comment in comments {
+        children.entry(comment.parent_comment_id).or_default().push(comment);
+    }
+    replies_to(None, &mut children)
+}
+
+/// The event's comments, oldest first, or as threads with `?threaded=true`.
+async fn list_comments(
+    Reader(pool): Reader,
+    State(events): State<Events>,
+    user: Option<AuthUser>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<ListComments>,
+) -> Result<Json<Vec<Comment>>, Response> {
+    timelines::ensure_event_visible(&pool, &events, user.as_ref(), id).await?;
+    let internal = |_| StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    let user = user.map(|user| user.id);
+    let query = match params.threaded {
+        true => format!("{} {} JOIN thread t ON t.id = c.id ORDER BY t.path", THREADED, SELECT),
+        false => format!("{} WHERE c.event_id = $2 ORDER BY c.created_at", SELECT),
+    };
+    let mut comments = sqlx::query_as::<_, Comment>(&query)
+        .bind(user)
+        .bind(id)
+        .fetch_all(&pool)
+        .await
+        .map_err(internal)?;
+
+    let ids: Vec<Uuid> = comments.iter().map(|comment| comment.id).collect();
+    let mut counts = reactions::for_comments(&pool, &ids, user).await.map_err(internal)?;
+    for comment in &mut comments {
+        comment.reactions = counts.remove(&comment.id).unwrap_or_default();
+    }
+
+    Ok(Json(if params.threaded { thread(comments) } else { comments }))
+}
+
+fn unprocessable(message: &str) -> Response {
+    (StatusCode::UNPROCESSABLE_ENTITY, Json(serde_json::json!({ "error": message }))).into_response()
+}
+
+/// Comments on the event, or replies to one of its comments, and notifies
+/// whom it concerns; names of users who can't see the event are left as
+/// plain text.
+async fn create_comment(
+    State(pool): State<PgPool>,
+    State(state): State<AppState>,
+    user: Option<AuthUser>,
+    ClientAddress(address): ClientAddress,
+    Path(id): Path<Uuid>,
+    Json(mut payload): Json<NewComment>,
+) -> Result<(StatusCode, Json<Comment>), Response> {
+    payload.body = sanitize::text(&payload.body).trim().to_string();
+    payload.name = payload.name.map(|name| sanitize::text(&name).trim().to_string());
+    payload.validate().map_err(validation_error)?;
+    let guest_name = match &user {
+        Some(_) => None,
+        None => {
+            let name = payload.name.clone().ok_or_else(|| unprocessable("Give a name to comment under."))?;
+            state.spam.check(&state.config, address, &payload.proof).await?;
+            Some(name)
+        }
+    };
+    timelines::ensure_event_visible(&pool, &state.events, user.as_ref(), id).await?;
+    let internal = |_| StatusCode::INTERNAL_SERVER_ERROR.into_response();
+
+    // The parent's author and depth.
+    let parent = match payload.parent_comment_id {
+        Some(parent_id) => {
+            let parent = sqlx::query_as::<_, (Uuid, Option<Uuid>, i16)>(
+                "SELECT event_id, author_id, depth FROM comments WHERE id = $1",
+            )
+            .bind(parent_id)
+            .fetch_optional(&pool)
+            .await
+            .map_err(internal)?;
+            match parent {
+                Some((event_id, _, _)) if event_id != id => {
+                    return Err(unprocessable("Replies must be on the same event."))
+                }
+                Some((_, _, depth)) if depth >= MAX_DEPTH => {
+                    return Err(unprocessable("This thread is too deep to reply to."))
+                }
+                Some((_, author, depth)) => Some((author, depth)),
+                None => return Err(unprocessable("The comment replied to no longer exists.")),
+            }
+        }
+        None => None,
+    };
+
+    let comment_id = sqlx::query_scalar::<_, Uuid>(
+        "INSERT INTO comments (event_id, author_id, guest_name, body, parent_comment_id, depth) \
+         VALUES ($1, $2, $3, $4, $5, $6) RETURNING id",
+    )
+    .bind(id)
+    .bind(user.as_ref().map(|user| user.id))
+    .bind(&guest_name)
+    .bind(&payload.body)
+    .bind(payload.parent_comment_id)
+    .bind(parent.map_or(0, |(_, depth)| depth + 1))
+    .fetch_one(&pool)
+    .await
+    .map_err(internal)?;
+
+    // Guests can't page people by name.
+    let mut names = if user.is_some() { mentions(&payload.body) } else { Vec::new() };
+    names.truncate(MAX_MENTIONS);
+    let replied_to = parent.and_then(|(author, _)| author);
+    let author = user.as_ref().map(|user| user.id);
+    if !names.is_empty() || replied_to.is_some() {
+        // The comment is saved either way; a lost notification is only logged.
+        if let Err(err) = notify(&pool, &state.events, author, id, comment_id, &names, replied_to).await {
+            tracing::warn!(comment = %comment_id, error = %err, "failed to notify about a comment");
+        }
+    }
+
+    let comment = find(&pool, comment_id, author).await.map_err(internal)?;
+    Ok((StatusCode::CREATED, Json(comment)))
+}
+
+/// Tells the users `names` mentions, and the author of the comment replied
+/// to, about a new comment. Someone both mentioned and replied to hears of
+/// the mention.
+async fn notify(
+    pool: &PgPool,
+    events: &Events,
+    author: Option<Uuid>,
+    event_id: Uuid,
+    comment_id: Uuid,
+    names: &[String],
+    replied_to: Option<Uuid>,
+) -> Result<(), sqlx::Error> {
+    let candidates = sqlx::query_as::<_, (Uuid, String, String)>(
+        "SELECT id, role, username FROM users \
+         WHERE (username = ANY($1) OR id = $3) AND id IS DISTINCT FROM $2 AND deleted_at IS NULL",
+    )
+    .bind(names)
+    .bind(author)
+    .bind(replied_to)
+    .fetch_all(pool)
+    .await?;
+
+    // Naming someone must not show them an event they can't see.
+    let mut mentioned = Vec::new();
+    let mut replied = Vec::new();
+    for (id, role, username) in candidates {
+        let candidate = AuthUser { id, role, session_id: None, impersonation: None };
+        if timelines::ensure_event_visible(pool, events, Some(&candidate), event_id).await.is_err() {
+            continue;
+        }
+        if names.contains(&username) {
+            mentioned.push(id);
+        } else {
+            replied.push(id);
+        }
+    }
+    for (kind, users) in [(notifications::Kind::Mention, mentioned), (notifications::Kind::Reply, replied)] {
+        if !users.is_empty() {
+            notifications::comment(pool, kind, &users, author, event_id, comment_id).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Deletes a comment and the replies under it; its author and admins may.
+async fn delete_comment(State(pool): State<PgPool>, user: AuthUser, Path(id): Path<Uuid>) -> StatusCode {
+    let author = match sqlx::query_scalar::<_, Option<Uuid>>("SELECT author_id FROM comments WHERE id = $1")
+        .bind(id)
+        .fetch_optional(&pool)
+        .await
+    {
+        Ok(Some(author)) => author,
+        Ok(None) => return StatusCode::NOT_FOUND,
+        Err(_) => return StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    if author != Some(user.id) && !user.is_admin() {
+        return StatusCode::FORBIDDEN;
+    }
+
+    match sqlx::query("DELETE FROM comments WHERE id = $1").bind(id).execute(&pool).await {
+        Ok(_) => StatusCode::NO_CONTENT,
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_mentions() {
+        let body = "Thanks @ada and @grace_h. Ask @ada, not ada@example.com or a lone @ (or @Lin-Wei).";
+        assert_eq!(mentions(body), ["ada", "grace_h", "Lin-Wei"]);
+        assert!(mentions("@").is_empty());
+        assert_eq!(mentions("@ada"), ["ada"]);
+    }
+
+    fn comment(id: u128, parent: Option<u128>) -> Comment {
+        Comment {
+            id: Uuid::from_u128(id),
+            event_id: Uuid::nil(),
+            parent_comment_id: parent.map(Uuid::from_u128),
+            depth: 0,
+            author_id: Some(Uuid::nil()),
+            author: "ada".to_string(),
+            guest: false,
+            body: String::new(),
+            created_at: NaiveDateTime::default(),
+            mine: false,
+            reactions: Vec::new(),
+            replies: None,
+        }
+    }
+
+    #[test]
+    fn threads_replies_under_their_parents_in_order() {
+        let threads = thread(vec![
+            comment(1, None),
+            comment(2, Some(1)),
+            comment(3, Some(2)),
+            comment(4, Some(1)),
+            comment(5, None),
+        ]);
+        let ids = |comments: &[Comment]| comments.iter().map(|comment| comment.id.as_u128()).collect::<Vec<_>>();
+
+        assert_eq!(ids(&threads), [1, 5]);
+        let replies = threads[0].replies.as_deref().unwrap();
+        assert_eq!(ids(replies), [2, 4]);
+        assert_eq!(ids(replies[0].replies.as_deref().unwrap()), [3]);
+        assert!(threads[1].replies.as_deref().unwrap().is_empty());
+    }
+}
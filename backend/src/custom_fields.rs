@@ -0,0 +1,532 @@
+//! Custom fields: metadata a timeline's events carry beyond the built-in
+//! columns, such as "casualties" or "dynasty". Editors define the fields on
+//! the timeline; events keep their values in `custom_fields`, a JSON object
+//! keyed by field name, which is checked against the definitions whenever
+//! it is written. `GET /api/timelines/:id/events` filters on them.
+
+use std::collections::{BTreeMap, HashMap};
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, put},
+    Json, Router,
+};
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sqlx::PgPool;
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+use crate::{
+    auth::AuthUser,
+    db::{events::Events, Reader},
+    sanitize, timelines, validation_error, AppState,
+};
+
+/// What a field holds. Dates are `YYYY-MM-DD` strings.
+pub const KINDS: [&str; 4] = ["text", "number", "date", "boolean"];
+
+/// Longest text value, in characters.
+const MAX_TEXT: usize = 1_000;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/timelines/:id/fields", get(list_fields).post(create_field))
+        .route("/api/fields/:id", put(update_field).delete(delete_field))
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+pub struct Field {
+    id: Uuid,
+    timeline_id: Uuid,
+    name: String,
+    /// One of `KINDS`; fixed once the field exists.
+    kind: String,
+    /// Events can't be saved without a value.
+    required: bool,
+    /// Order of the field in forms.
+    position: i32,
+    created_at: NaiveDateTime,
+    updated_at: NaiveDateTime,
+}
+
+#[derive(Deserialize, Validate)]
+struct FieldCreate {
+    #[validate(custom(function = "valid_name"))]
+    name: String,
+    #[validate(custom(function = "valid_kind"))]
+    kind: String,
+    #[serde(default)]
+    required: bool,
+    /// Defaults to after the existing fields.
+    position: Option<i32>,
+}
+
+/// Body of `PUT /api/fields/:id`. Renaming moves the values on the
+/// timeline's events to the new name.
+#[derive(Deserialize, Validate)]
+struct FieldUpdate {
+    #[validate(custom(function = "valid_name"))]
+    name: String,
+    required: bool,
+    position: i32,
+}
+
+/// Names are keys in `custom_fields` and in filter parameters, so they are
+/// lower-case letters, digits and underscores, starting with a letter.
+fn valid_name(name: &str) -> Result<(), ValidationError> {
+    let mut chars = name.chars();
+    let starts_with_letter = chars.next().is_some_and(|first| first.is_ascii_lowercase());
+    let rest_valid = chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    if !starts_with_letter || !rest_valid || name.len() > 50 {
+        return Err(ValidationError::new("field_name"));
+    }
+    Ok(())
+}
+
+fn valid_kind(kind: &str) -> Result<(), ValidationError> {
+    if !KINDS.contains(&kind) {
+        return Err(ValidationError::new("field_kind"));
+    }
+    Ok(())
+}
+
+/// "Battle Deaths" becomes `battle_deaths`.
+fn normalize_name(name: &str) -> String {
+    name.trim().to_lowercase().split_whitespace().collect::<Vec<_>>().join("_")
+}
+
+/// 409 for a name the timeline already has.
+fn taken(error: sqlx::Error) -> Response {
+    match error {
+        sqlx::Error::Database(error) if error.is_unique_violation() => (
+            StatusCode::CONFLICT,
+            Json(serde_json::json!({ "error": "the timeline already has a field with this name" })),
+        )
+            .into_response(),
+        _ => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+/// The fields defined on a timeline, in form order. Events outside any
+/// timeline have none.
+pub async fn definitions(pool: &PgPool, timeline_id: Option<Uuid>) -> Result<Vec<Field>, Response> {
+    let Some(timeline_id) = timeline_id else {
+        return Ok(Vec::new());
+    };
+    sqlx::query_as::<_, Field>("SELECT * FROM timeline_fields WHERE timeline_id = $1 ORDER BY position, name")
+        .bind(timeline_id)
+        .fetch_all(pool)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())
+}
+
+/// The timeline a field is on, and its current name.
+async fn field_of(pool: &PgPool, id: Uuid) -> Result<(Uuid, String), Response> {
+    sqlx::query_as::<_, (Uuid, String)>("SELECT timeline_id, name FROM timeline_fields WHERE id = $1")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?
+        .ok_or_else(|| StatusCode::NOT_FOUND.into_response())
+}
+
+async fn list_fields(
+    Reader(pool): Reader,
+    user: Option<AuthUser>,
+    Path(timeline_id): Path<Uuid>,
+) -> Result<Json<Vec<Field>>, Response> {
+    timelines::find_visible(&pool, timeline_id, user.as_ref()).await?;
+    definitions(&pool, Some(timeline_id)).await.map(Json)
+}
+
+async fn create_field(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Path(timeline_id): Path<Uuid>,
+    Json(mut payload): Json<FieldCreate>,
+) -> Result<(StatusCode, Json<Field>), Response> {
+    payload.name = normalize_name(&payload.name);
+    payload.validate().map_err(validation_error)?;
+    timelines::ensure_timeline_writable(&pool, Some(&user), Some(timeline_id)).await?;
+
+    let field = sqlx::query_as::<_, Field>(
+        r#"
+        INSERT INTO timeline_fields (timeline_id, name, kind, required, position)
+        VALUES ($1, $2, $3, $4, COALESCE($5, (SELECT COUNT(*) FROM timeline_fields WHERE timeline_id = $1)::int))
+        RETURNING *
+        "#,
+    )
+    .bind(timeline_id)
+    .bind(&payload.name)
+    .bind(&payload.kind)
+    .bind(payload.required)
+    .bind(payload.position)
+    .fetch_one(&pool)
+    .await
+    .map_err(taken)?;
+
+    Ok((StatusCode::CREATED, Json(field)))
+}
+
+async fn update_field(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(mut payload): Json<FieldUpdate>,
+) -> Result<Json<Field>, Response> {
+    payload.name = normalize_name(&payload.name);
+    payload.validate().map_err(validation_error)?;
+    let (timeline_id, name) = field_of(&pool, id).await?;
+    timelines::ensure_timeline_writable(&pool, Some(&user), Some(timeline_id)).await?;
+    let internal = |_| StatusCode::INTERNAL_SERVER_ERROR.into_response();
+
+    let mut tx = pool.begin().await.map_err(internal)?;
+    let field = sqlx::query_as::<_, Field>(
+        r#"
+        UPDATE timeline_fields SET name = $2, required = $3, position = $4, updated_at = NOW()
+        WHERE id = $1
+        RETURNING *
+        "#,
+    )
+    .bind(id)
+    .bind(&payload.name)
+    .bind(payload.required)
+    .bind(payload.position)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(taken)?;
+    if payload.name != name {
+        sqlx::query(
+            r#"
+            UPDATE events SET custom_fields = (custom_fields - $2) || jsonb_build_object($3::text, custom_fields -> $2)
+            WHERE timeline_id = $1 AND custom_fields ? $2
+            "#,
+        )
+        .bind(timeline_id)
+        .bind(&name)
+        .bind(&payload.name)
+        .execute(&mut *tx)
+        .await
+        .map_err(internal)?;
+    }
+    tx.commit().await.map_err(internal)?;
+
+    Ok(Json(field))
+}
+
+/// Removes the field and its values from the timeline's events.
+async fn delete_field(State(pool): State<PgPool>, user: AuthUser, Path(id): Path<Uuid>) -> Result<StatusCode, Response> {
+    let (timeline_id, name) = field_of(&pool, id).await?;
+    timelines::ensure_timeline_writable(&pool, Some(&user), Some(timeline_id)).await?;
+    let internal = |_| StatusCode::INTERNAL_SERVER_ERROR.into_response();
+
+    let mut tx = pool.begin().await.map_err(internal)?;
+    sqlx::query("UPDATE events SET custom_fields = custom_fields - $2 WHERE timeline_id = $1 AND custom_fields ? $2")
+        .bind(timeline_id)
+        .bind(&name)
+        .execute(&mut *tx)
+        .await
+        .map_err(internal)?;
+    sqlx::query("DELETE FROM timeline_fields WHERE id = $1")
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(internal)?;
+    tx.commit().await.map_err(internal)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// What's wrong with each invalid value, by field name.
+pub type Problems = BTreeMap<String, &'static str>;
+
+/// 422 in the shape of `validation_error`, with the problems under
+/// `custom_fields`.
+pub fn invalid(problems: Problems) -> Response {
+    (
+        StatusCode::UNPROCESSABLE_ENTITY,
+        Json(serde_json::json!({
+            "error": "invalid input",
+            "fields": { "custom_fields": problems },
+        })),
+    )
+        .into_response()
+}
+
+/// Checks `values` against the definitions and returns them cleaned up:
+/// text sanitized, and `null`s and empty text dropped as unset.
+fn check(definitions: &[Field], values: Map<String, Value>) -> Result<Map<String, Value>, Problems> {
+    let mut problems = Problems::new();
+    let mut checked = Map::new();
+    for (name, value) in values {
+        let Some(field) = definitions.iter().find(|field| field.name == name) else {
+            problems.insert(name, "not a field of this timeline");
+            continue;
+        };
+        let value = match (field.kind.as_str(), value) {
+            (_, Value::Null) => continue,
+            ("text", Value::String(text)) => {
+                let text = sanitize::text(&text);
+                if text.is_empty() {
+                    continue;
+                }
+                if text.chars().count() > MAX_TEXT {
+                    problems.insert(name, "too long");
+                    continue;
+                }
+                Value::String(text)
+            }
+            ("number", Value::Number(number)) if number.as_f64().is_some_and(f64::is_finite) => Value::Number(number),
+            ("date", Value::String(date)) if NaiveDate::parse_from_str(&date, "%Y-%m-%d").is_ok() => Value::String(date),
+            ("boolean", Value::Bool(flag)) => Value::Bool(flag),
+            (kind, _) => {
+                problems.insert(name, expected(kind));
+                continue;
+            }
+        };
+        checked.insert(name, value);
+    }
+    for field in definitions.iter().filter(|field| field.required) {
+        if !checked.contains_key(&field.name) && !problems.contains_key(&field.name) {
+            problems.insert(field.name.clone(), "required");
+        }
+    }
+    if problems.is_empty() {
+        Ok(checked)
+    } else {
+        Err(problems)
+    }
+}
+
+fn expected(kind: &str) -> &'static str {
+    match kind {
+        "number" => "expected a number",
+        "date" => "expected a YYYY-MM-DD date",
+        "boolean" => "expected true or false",
+        _ => "expected text",
+    }
+}
+
+fn object(values: Value) -> Result<Map<String, Value>, (StatusCode, &'static str)> {
+    match values {
+        Value::Object(values) => Ok(values),
+        _ => Err((StatusCode::UNPROCESSABLE_ENTITY, "custom_fields must be an object")),
+    }
+}
+
+/// The custom field values for a new event on `timeline_id`, checked.
+pub async fn validate(pool: &PgPool, timeline_id: Option<Uuid>, values: Value) -> Result<Value, Response> {
+    let definitions = definitions(pool, timeline_id).await?;
+    let values = object(values).map_err(IntoResponse::into_response)?;
+    check(&definitions, values).map(Value::Object).map_err(invalid)
+}
+
+/// The event's custom field values once `values` are applied, checked. A
+/// replacement drops the values it leaves out; otherwise `values` is a
+/// merge patch, keeping values it leaves out and removing those it sets to
+/// `null`.
+pub async fn resolve(
+    pool: &PgPool,
+    events: &Events,
+    event_id: Uuid,
+    values: Value,
+    replace: bool,
+) -> Result<Value, Response> {
+    let event = events
+        .find(event_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?
+        .ok_or_else(|| StatusCode::NOT_FOUND.into_response())?;
+
+    let mut merged = match (replace, event.custom_fields) {
+        (false, Value::Object(current)) => current,
+        _ => Map::new(),
+    };
+    for (name, value) in object(values).map_err(IntoResponse::into_response)? {
+        merged.insert(name, value);
+    }
+    let definitions = definitions(pool, event.timeline_id).await?;
+    check(&definitions, merged).map(Value::Object).map_err(invalid)
+}
+
+/// What a filter parameter compares.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Bound {
+    Equal,
+    Min,
+    Max,
+}
+
+impl Bound {
+    pub fn operator(self) -> &'static str {
+        match self {
+            Bound::Equal => " = ",
+            Bound::Min => " >= ",
+            Bound::Max => " <= ",
+        }
+    }
+}
+
+/// One `field.*` parameter, read against the field's kind. Each names the
+/// field it compares.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FieldFilter {
+    /// Text equal to this, ignoring case.
+    Text(String, String),
+    Boolean(String, bool),
+    Number(String, Bound, f64),
+    Date(String, Bound, NaiveDate),
+}
+
+/// Reads `field.<name>`, `field.<name>.min` or `field.<name>.max`.
+fn filter_key(key: &str) -> Option<(&str, Bound)> {
+    let rest = key.strip_prefix("field.")?;
+    Some(match rest.rsplit_once('.') {
+        Some((name, "min")) => (name, Bound::Min),
+        Some((name, "max")) => (name, Bound::Max),
+        _ => (rest, Bound::Equal),
+    })
+}
+
+/// The filters the `field.*` parameters ask for: text matches ignoring
+/// case, booleans and numbers exactly, and numbers and dates also take
+/// `.min` and `.max` bounds. Other parameters are ignored.
+pub fn filters(definitions: &[Field], params: &HashMap<String, String>) -> Result<Vec<FieldFilter>, Problems> {
+    let mut problems = Problems::new();
+    let mut filters = Vec::new();
+    for (key, value) in params {
+        let Some((name, bound)) = filter_key(key) else {
+            continue;
+        };
+        let Some(field) = definitions.iter().find(|field| field.name == name) else {
+            problems.insert(name.to_string(), "not a field of this timeline");
+            continue;
+        };
+        let name = name.to_string();
+        match (field.kind.as_str(), bound) {
+            ("text", Bound::Equal) => filters.push(FieldFilter::Text(name, value.clone())),
+            ("boolean", Bound::Equal) => match value.parse::<bool>() {
+                Ok(flag) => filters.push(FieldFilter::Boolean(name, flag)),
+                Err(_) => {
+                    problems.insert(name, expected("boolean"));
+                }
+            },
+            ("number", _) => match value.parse::<f64>() {
+                Ok(number) if number.is_finite() => filters.push(FieldFilter::Number(name, bound, number)),
+                _ => {
+                    problems.insert(name, expected("number"));
+                }
+            },
+            ("date", _) => match NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+                Ok(date) => filters.push(FieldFilter::Date(name, bound, date)),
+                Err(_) => {
+                    problems.insert(name, expected("date"));
+                }
+            },
+            _ => {
+                problems.insert(name, "only numbers and dates take a range");
+            }
+        }
+    }
+    if problems.is_empty() {
+        Ok(filters)
+    } else {
+        Err(problems)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::QueryBuilder;
+
+    use super::*;
+    use crate::db::events::push_field_filters;
+
+    fn field(name: &str, kind: &str, required: bool) -> Field {
+        let now = chrono::Utc::now().naive_utc();
+        Field {
+            id: Uuid::new_v4(),
+            timeline_id: Uuid::nil(),
+            name: name.to_string(),
+            kind: kind.to_string(),
+            required,
+            position: 0,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    fn values(json: Value) -> Map<String, Value> {
+        json.as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn values_must_match_their_field() {
+        let fields = [
+            field("casualties", "number", false),
+            field("dynasty", "text", true),
+            field("signed", "date", false),
+            field("decisive", "boolean", false),
+        ];
+        let checked = check(
+            &fields,
+            values(serde_json::json!({
+                "casualties": 5000,
+                "dynasty": "Tudor",
+                "signed": "1485-08-22",
+                "decisive": null,
+            })),
+        )
+        .unwrap();
+        assert_eq!(Value::Object(checked), serde_json::json!({
+            "casualties": 5000,
+            "dynasty": "Tudor",
+            "signed": "1485-08-22",
+        }));
+
+        let problems = check(
+            &fields,
+            values(serde_json::json!({
+                "casualties": "many",
+                "signed": "22 August 1485",
+                "decisive": "yes",
+                "pope": "Innocent VIII",
+            })),
+        )
+        .unwrap_err();
+        assert_eq!(problems["casualties"], "expected a number");
+        assert_eq!(problems["signed"], "expected a YYYY-MM-DD date");
+        assert_eq!(problems["decisive"], "expected true or false");
+        assert_eq!(problems["pope"], "not a field of this timeline");
+        assert_eq!(problems["dynasty"], "required");
+    }
+
+    #[test]
+    fn names_are_normalized_keys() {
+        assert_eq!(normalize_name("  Battle Deaths "), "battle_deaths");
+        assert!(valid_name("battle_deaths").is_ok());
+        assert!(valid_name("1st_wave").is_err());
+        assert!(valid_name("deaths'); --").is_err());
+        assert_eq!(filter_key("field.casualties.min"), Some(("casualties", Bound::Min)));
+        assert_eq!(filter_key("field.dynasty"), Some(("dynasty", Bound::Equal)));
+        assert_eq!(filter_key("min_importance"), None);
+    }
+
+    #[test]
+    fn filters_bind_names_and_values() {
+        let fields = [field("casualties", "number", false), field("dynasty", "text", false)];
+        let params = HashMap::from([("field.casualties.min".to_string(), "1000".to_string())]);
+        let mut builder = QueryBuilder::new("SELECT * FROM events WHERE TRUE");
+        push_field_filters(&mut builder, &filters(&fields, &params).unwrap());
+        assert_eq!(
+            builder.sql(),
+            "SELECT * FROM events WHERE TRUE AND (custom_fields ->> $1)::float8 >= $2"
+        );
+
+        let params = HashMap::from([("field.dynasty.max".to_string(), "Tudor".to_string())]);
+        assert!(filters(&fields, &params).is_err());
+    }
+}
@@ -0,0 +1,391 @@
+//! `POST /api/me/export`: a zip of everything a user has stored here, as
+//! JSON plus the images of their events. Archives are built by the
+//! `data_exports` job and downloaded through a link that expires.
+
+use std::io::{self, Cursor, Write};
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use axum::{
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use chrono::NaiveDateTime;
+use serde::Serialize;
+use sqlx::PgPool;
+use uuid::Uuid;
+use zip::{write::FileOptions, ZipWriter};
+
+use crate::{auth::AuthUser, config::Config, db::Reader, export, images::ImageProxy, AppState};
+
+/// How long a finished archive can be downloaded.
+const EXPORT_TTL_DAYS: i32 = 7;
+/// Most event images one archive includes.
+const MAX_IMAGES: usize = 500;
+/// Bytes of an archive read from the database per chunk of a download.
+const DOWNLOAD_CHUNK_BYTES: i32 = 1024 * 1024;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/me/export", get(latest_export).post(request_export))
+        .route("/api/exports/:token", get(download))
+}
+
+/// The JSON files of an archive and the rows each holds, for the user
+/// bound as `$1`.
+const SECTIONS: &[(&str, &str)] = &[
+    ("profile.json", "SELECT id, username, email, role, created_at FROM users WHERE id = $1"),
+    (
+        "preferences.json",
+        "SELECT default_timeline_id, theme, locale, items_per_page, default_sort, updated_at \
+         FROM user_preferences WHERE user_id = $1",
+    ),
+    ("timelines.json", "SELECT * FROM timelines WHERE owner_id = $1 ORDER BY created_at"),
+    (
+        "events.json",
+        "SELECT e.* FROM events e JOIN timelines t ON t.id = e.timeline_id \
+         WHERE t.owner_id = $1 ORDER BY e.start_date",
+    ),
+    (
+        "comments.json",
+        "SELECT c.event_id, e.title, c.body, c.created_at FROM comments c JOIN events e ON e.id = c.event_id \
+         WHERE c.author_id = $1 ORDER BY c.created_at",
+    ),
+    (
+        "favorites.json",
+        "SELECT f.event_id, e.title, f.created_at FROM favorites f JOIN events e ON e.id = f.event_id \
+         WHERE f.user_id = $1 ORDER BY f.created_at",
+    ),
+    (
+        "likes.json",
+        "SELECT l.event_id, e.title, l.created_at FROM event_likes l JOIN events e ON e.id = l.event_id \
+         WHERE l.user_id = $1 ORDER BY l.created_at",
+    ),
+    (
+        "reactions.json",
+        "SELECT r.event_id, r.comment_id, r.emoji, r.created_at FROM reactions r \
+         WHERE r.user_id = $1 ORDER BY r.created_at",
+    ),
+    (
+        "reading_history.json",
+        "SELECT r.event_id, e.title, r.viewed_at FROM event_reads r JOIN events e ON e.id = r.event_id \
+         WHERE r.user_id = $1 ORDER BY r.viewed_at",
+    ),
+    (
+        "saved_searches.json",
+        "SELECT name, search, categories, start_date, end_date, notify, created_at \
+         FROM saved_searches WHERE user_id = $1 ORDER BY created_at",
+    ),
+    (
+        "timeline_memberships.json",
+        "SELECT m.timeline_id, t.title, m.role, m.created_at FROM timeline_members m \
+         JOIN timelines t ON t.id = m.timeline_id WHERE m.user_id = $1 ORDER BY m.created_at",
+    ),
+    (
+        "organizations.json",
+        "SELECT o.id, o.name, o.slug, m.role, m.created_at FROM organization_members m \
+         JOIN organizations o ON o.id = m.organization_id WHERE m.user_id = $1 ORDER BY m.created_at",
+    ),
+    (
+        "linked_accounts.json",
+        "SELECT provider, created_at FROM user_identities WHERE user_id = $1 ORDER BY created_at",
+    ),
+    (
+        "sessions.json",
+        "SELECT user_agent, created_at, last_seen_at, expires_at, revoked_at \
+         FROM sessions WHERE user_id = $1 ORDER BY created_at",
+    ),
+];
+
+#[derive(Serialize, sqlx::FromRow)]
+struct DataExport {
+    id: Uuid,
+    /// `queued`, `running`, `ready` or `failed`.
+    status: String,
+    #[serde(skip)]
+    token: String,
+    created_at: NaiveDateTime,
+    completed_at: Option<NaiveDateTime>,
+    expires_at: Option<NaiveDateTime>,
+}
+
+/// What the owner sees of an export: its progress and, once ready, the
+/// link to download it.
+#[derive(Serialize)]
+struct ExportView {
+    #[serde(flatten)]
+    export: DataExport,
+    download_url: Option<String>,
+}
+
+impl From<DataExport> for ExportView {
+    fn from(export: DataExport) -> Self {
+        let expired = export.expires_at.is_some_and(|at| at < chrono::Utc::now().naive_utc());
+        let download_url = (export.status == "ready" && !expired).then(|| format!("/api/exports/{}", export.token));
+        Self { export, download_url }
+    }
+}
+
+fn new_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+const EXPORT_COLUMNS: &str = "id, status, token, created_at, completed_at, expires_at";
+
+/// The caller's most recent export, or `null` if they never asked for one.
+async fn latest_export(Reader(pool): Reader, user: AuthUser) -> Result<Json<Option<ExportView>>, StatusCode> {
+    let export = sqlx::query_as::<_, DataExport>(&format!(
+        "SELECT {} FROM data_exports WHERE user_id = $1 ORDER BY created_at DESC LIMIT 1",
+        EXPORT_COLUMNS
+    ))
+    .bind(user.id)
+    .fetch_optional(&pool)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(export.map(ExportView::from)))
+}
+
+/// Queues an export. While one is still queued or running, that one is
+/// returned instead of starting another.
+async fn request_export(State(pool): State<PgPool>, user: AuthUser) -> Result<(StatusCode, Json<ExportView>), StatusCode> {
+    let pending = sqlx::query_as::<_, DataExport>(&format!(
+        "SELECT {} FROM data_exports WHERE user_id = $1 AND status IN ('queued', 'running')",
+        EXPORT_COLUMNS
+    ))
+    .bind(user.id)
+    .fetch_optional(&pool)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if let Some(export) = pending {
+        return Ok((StatusCode::ACCEPTED, Json(export.into())));
+    }
+
+    let export = sqlx::query_as::<_, DataExport>(&format!(
+        "INSERT INTO data_exports (user_id, token) VALUES ($1, $2) RETURNING {}",
+        EXPORT_COLUMNS
+    ))
+    .bind(user.id)
+    .bind(new_token())
+    .fetch_one(&pool)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok((StatusCode::ACCEPTED, Json(export.into())))
+}
+
+/// Serves a finished archive. The token is the only credential, so the
+/// link works from a plain `<a href>`; it stops working when it expires.
+/// The archive is streamed a chunk at a time rather than loaded whole.
+async fn download(Reader(pool): Reader, Path(token): Path<String>) -> Result<Response, StatusCode> {
+    let (id, size, created_at, expired) = sqlx::query_as::<_, (Uuid, Option<i32>, NaiveDateTime, bool)>(
+        "SELECT id, octet_length(archive), created_at, COALESCE(expires_at < NOW(), FALSE) \
+         FROM data_exports WHERE token = $1 AND status = 'ready'",
+    )
+    .bind(token)
+    .fetch_optional(&pool)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+    .ok_or(StatusCode::NOT_FOUND)?;
+    let size = match size {
+        Some(size) if !expired => size,
+        _ => return Err(StatusCode::GONE),
+    };
+
+    let (sender, body) = export::channel();
+    tokio::spawn(async move {
+        for offset in (0..size).step_by(DOWNLOAD_CHUNK_BYTES as usize) {
+            let chunk = sqlx::query_scalar::<_, Vec<u8>>(
+                "SELECT substring(archive FROM $2 FOR $3) FROM data_exports WHERE id = $1",
+            )
+            .bind(id)
+            .bind(offset + 1)
+            .bind(DOWNLOAD_CHUNK_BYTES)
+            .fetch_one(&pool)
+            .await
+            .map(Into::into)
+            .map_err(io::Error::other);
+            let failed = chunk.is_err();
+            if sender.send(chunk).await.is_err() || failed {
+                return;
+            }
+        }
+    });
+
+    let filename = format!("timeline-export-{}.zip", created_at.format("%Y-%m-%d"));
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/zip".to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename)),
+            (header::CACHE_CONTROL, "private, no-store".to_string()),
+            (header::CONTENT_LENGTH, size.to_string()),
+        ],
+        body,
+    )
+        .into_response())
+}
+
+/// File extension for an image's content type.
+fn extension(content_type: &str) -> &str {
+    match content_type {
+        "image/jpeg" => "jpg",
+        "image/png" => "png",
+        "image/gif" => "gif",
+        "image/webp" => "webp",
+        "image/avif" => "avif",
+        _ => "img",
+    }
+}
+
+/// Zips `files` (name and contents) in memory.
+fn archive(files: &[(String, Vec<u8>)]) -> zip::result::ZipResult<Vec<u8>> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    for (name, contents) in files {
+        zip.start_file(name.as_str(), options)?;
+        zip.write_all(contents)?;
+    }
+    Ok(zip.finish()?.into_inner())
+}
+
+/// The files of `user`'s archive. Images that cannot be fetched are left
+/// out and listed in the README.
+async fn collect(pool: &PgPool, proxy: &ImageProxy, config: &Config, user: Uuid) -> Result<Vec<(String, Vec<u8>)>, sqlx::Error> {
+    let mut files = Vec::new();
+    for (name, query) in SECTIONS {
+        let json = sqlx::query_scalar::<_, String>(&format!(
+            "SELECT jsonb_pretty(COALESCE(jsonb_agg(r), '[]')) FROM ({}) r",
+            query
+        ))
+        .bind(user)
+        .fetch_one(pool)
+        .await?;
+        files.push((name.to_string(), json.into_bytes()));
+    }
+
+    let images = sqlx::query_as::<_, (Uuid, String)>(
+        r#"
+        SELECT e.id, e.image_url FROM events e JOIN timelines t ON t.id = e.timeline_id
+        WHERE t.owner_id = $1 AND e.image_url IS NOT NULL
+        ORDER BY e.start_date
+        "#,
+    )
+    .bind(user)
+    .fetch_all(pool)
+    .await?;
+    let mut missing = Vec::new();
+    for (index, (event, url)) in images.into_iter().enumerate() {
+        if index >= MAX_IMAGES {
+            missing.push(url);
+            continue;
+        }
+        match proxy.fetch(config, &url).await {
+            Ok((content_type, body)) => {
+                files.push((format!("media/{}.{}", event, extension(&content_type)), body.to_vec()));
+            }
+            Err(_) => missing.push(url),
+        }
+    }
+
+    let mut readme = String::from(
+        "Your data from Timeline Explorer. Each JSON file holds a list of records; \
+         media/ holds the images of your events, named by event id.\n",
+    );
+    if !missing.is_empty() {
+        readme.push_str("\nThese images could not be included:\n");
+        for url in missing {
+            readme.push_str(&format!("{}\n", url));
+        }
+    }
+    files.insert(0, ("README.txt".to_string(), readme.into_bytes()));
+    Ok(files)
+}
+
+/// Builds queued archives one at a time; `retention` deletes them once
+/// they expire.
+pub async fn process(pool: &PgPool, proxy: &ImageProxy, config: &Config) -> Result<u64, sqlx::Error> {
+    let mut built = 0;
+    loop {
+        let claimed = sqlx::query_as::<_, (Uuid, Uuid)>(
+            r#"
+            UPDATE data_exports SET status = 'running'
+            WHERE id = (
+                SELECT id FROM data_exports WHERE status = 'queued'
+                ORDER BY created_at LIMIT 1 FOR UPDATE SKIP LOCKED
+            )
+            RETURNING id, user_id
+            "#,
+        )
+        .fetch_optional(pool)
+        .await?;
+        let Some((id, user)) = claimed else {
+            break;
+        };
+
+        let result = match collect(pool, proxy, config, user).await {
+            Ok(files) => archive(&files).map_err(|err| err.to_string()),
+            Err(err) => Err(err.to_string()),
+        };
+        match result {
+            Ok(zip) => {
+                sqlx::query(
+                    r#"
+                    UPDATE data_exports SET
+                        status = 'ready',
+                        archive = $2,
+                        completed_at = NOW(),
+                        expires_at = NOW() + make_interval(days => $3)
+                    WHERE id = $1
+                    "#,
+                )
+                .bind(id)
+                .bind(zip)
+                .bind(EXPORT_TTL_DAYS)
+                .execute(pool)
+                .await?;
+                built += 1;
+            }
+            Err(error) => {
+                tracing::error!(export = %id, error, "failed to build data export");
+                sqlx::query("UPDATE data_exports SET status = 'failed', error = $2, completed_at = NOW() WHERE id = $1")
+                    .bind(id)
+                    .bind(error)
+                    .execute(pool)
+                    .await?;
+            }
+        }
+    }
+    Ok(built)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    #[test]
+    fn archives_files_readably() {
+        let files = vec![
+            ("README.txt".to_string(), b"hello".to_vec()),
+            ("media/a.png".to_string(), vec![1, 2, 3]),
+        ];
+        let zip = archive(&files).unwrap();
+
+        let mut reader = zip::ZipArchive::new(Cursor::new(zip)).unwrap();
+        assert_eq!(reader.len(), 2);
+        let mut readme = String::new();
+        reader.by_name("README.txt").unwrap().read_to_string(&mut readme).unwrap();
+        assert_eq!(readme, "hello");
+    }
+
+    #[test]
+    fn names_images_by_content_type() {
+        assert_eq!(extension("image/jpeg"), "jpg");
+        assert_eq!(extension("image/webp"), "webp");
+        assert_eq!(extension("image/x-unknown"), "img");
+    }
+}
@@ -0,0 +1,1963 @@
+use std::sync::Arc;
+
+use axum::async_trait;
+use chrono::NaiveDateTime;
+use serde::Deserialize;
+use sqlx::{
+    postgres::PgArguments, query::QueryAs, Acquire, Database, Encode, PgConnection, PgPool, Postgres, QueryBuilder,
+    Type,
+};
+use uuid::Uuid;
+
+use super::Db;
+use crate::{custom_fields::FieldFilter, outbox, timelines, Event, EventPatch};
+
+/// The event store handlers use, shared through `AppState`.
+pub(crate) type Events = Arc<dyn EventRepository>;
+
+/// Category name that filters and counts use for events without one.
+pub const UNCATEGORIZED: &str = "Uncategorized";
+
+/// Order of a listing, as named in `preferences::SORTS`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum EventSort {
+    /// By start date, latest first.
+    #[default]
+    Newest,
+    Oldest,
+    /// Most liked first, then newest.
+    Likes,
+}
+
+impl EventSort {
+    pub fn parse(value: &str) -> Option<EventSort> {
+        match value {
+            "newest" => Some(EventSort::Newest),
+            "oldest" => Some(EventSort::Oldest),
+            "likes" => Some(EventSort::Likes),
+            _ => None,
+        }
+    }
+
+    pub fn order_by(self) -> &'static str {
+        match self {
+            EventSort::Newest => " ORDER BY e.start_date DESC",
+            EventSort::Oldest => " ORDER BY e.start_date",
+            EventSort::Likes => " ORDER BY e.likes DESC, e.start_date DESC",
+        }
+    }
+}
+
+/// Which events a listing returns.
+#[derive(Clone, Debug, Default)]
+pub struct EventFilter {
+    /// Case-insensitive substring of the title or description.
+    pub search: Option<String>,
+    pub start_date: Option<NaiveDateTime>,
+    pub end_date: Option<NaiveDateTime>,
+    /// Only events under way at some point in `[from, until)`. Unlike the
+    /// start date bounds above, an event that began earlier but ends inside
+    /// the window matches.
+    pub from: Option<NaiveDateTime>,
+    pub until: Option<NaiveDateTime>,
+    /// Only events in one of these categories, compared case-insensitively;
+    /// `UNCATEGORIZED` matches events without one. Empty means any.
+    pub categories: Vec<String>,
+    /// Only events with at least this importance.
+    pub min_importance: Option<i16>,
+    /// Only events with this tag, compared case-insensitively.
+    pub tag: Option<String>,
+    /// Published listings are public. Any other status is limited to the
+    /// events `editor` may edit, and returns nothing without one.
+    pub status: String,
+    /// The signed-in caller as `(user id, is admin)`.
+    pub editor: Option<(Uuid, bool)>,
+    /// Only events on this organization's timelines. Its members also see
+    /// the published events of its private timelines.
+    pub organization_id: Option<Uuid>,
+    pub sort: EventSort,
+    pub limit: i64,
+    pub offset: i64,
+}
+
+/// One page of a listing plus the total number of matches.
+pub(crate) struct EventPage {
+    pub events: Vec<Event>,
+    pub total: i64,
+}
+
+/// Number of events in one category.
+#[derive(Debug, PartialEq, serde::Serialize, sqlx::FromRow)]
+pub struct CategoryCount {
+    pub category: String,
+    pub count: i64,
+}
+
+/// Number of events starting in one decade, named by its first year. Years
+/// are astronomical: the decade `-10` runs from 11 BCE to 2 BCE.
+#[derive(Debug, PartialEq, serde::Serialize, sqlx::FromRow)]
+pub struct DecadeCount {
+    pub decade: i32,
+    pub count: i64,
+}
+
+/// Number of events with one tag.
+#[derive(Debug, PartialEq, serde::Serialize, sqlx::FromRow)]
+pub struct TagCount {
+    pub tag: String,
+    pub count: i64,
+}
+
+/// Counts for faceted navigation of a listing.
+#[derive(Debug, PartialEq, serde::Serialize)]
+pub struct Facets {
+    /// As `category_counts` gives them.
+    pub categories: Vec<CategoryCount>,
+    /// Earliest first.
+    pub decades: Vec<DecadeCount>,
+    /// The `MAX_TAG_FACETS` most used, most first.
+    pub tags: Vec<TagCount>,
+}
+
+/// Tags listed in `Facets`; the long tail is left out.
+pub const MAX_TAG_FACETS: i64 = 20;
+
+/// How far apart two start dates may be for the events to count as the same.
+pub const DATE_TOLERANCE_DAYS: i32 = 7;
+/// Minimum `pg_trgm` similarity between titles, so "Battle of Hastings" and
+/// "The Battle of Hastings" match but unrelated battles on the same day don't.
+const TITLE_SIMILARITY: f32 = 0.6;
+/// Most likely duplicates `similar` returns.
+pub const MAX_CANDIDATES: i64 = 5;
+
+/// Which of a timeline's events `on_timeline` returns.
+pub(crate) struct TimelineFilter {
+    /// Drafts and archived events too, for those who may edit them.
+    pub unpublished: bool,
+    pub min_importance: i16,
+    pub fields: Vec<FieldFilter>,
+}
+
+/// Which event a merged field is taken from.
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Side {
+    Keep,
+    Other,
+}
+
+impl Side {
+    pub(super) fn as_str(self) -> &'static str {
+        match self {
+            Side::Keep => "keep",
+            Side::Other => "other",
+        }
+    }
+}
+
+/// Per-field choices for a merge. Fields left out keep the surviving
+/// event's value unless it is empty, in which case the other value is used.
+#[derive(Deserialize, Default)]
+pub(crate) struct MergeChoices {
+    pub title: Option<Side>,
+    pub description: Option<Side>,
+    pub start_date: Option<Side>,
+    pub end_date: Option<Side>,
+    pub location: Option<Side>,
+    pub image_url: Option<Side>,
+    pub category: Option<Side>,
+}
+
+/// One write in a batch.
+pub(crate) enum Change {
+    Create(Event),
+    Update(Uuid, EventPatch),
+    Delete(Uuid),
+}
+
+/// What became of a `Change` in a batch.
+pub(crate) enum Outcome {
+    /// The created or updated event.
+    Saved(Box<Event>),
+    Deleted,
+    NotFound,
+    Failed(sqlx::Error),
+    /// Not applied because an atomic batch failed.
+    RolledBack,
+}
+
+impl Outcome {
+    pub fn applied(&self) -> bool {
+        matches!(self, Outcome::Saved(_) | Outcome::Deleted)
+    }
+}
+
+/// Marks the applied outcomes of a failed atomic batch as rolled back.
+pub(super) fn roll_back(outcomes: &mut [Outcome]) {
+    for outcome in outcomes.iter_mut().filter(|outcome| outcome.applied()) {
+        *outcome = Outcome::RolledBack;
+    }
+}
+
+/// Persistence for events. Permission checks stay with the callers; the
+/// repository only applies the visibility rules a listing filter implies.
+#[async_trait]
+pub(crate) trait EventRepository: Send + Sync {
+    async fn list(&self, filter: &EventFilter) -> Result<EventPage, sqlx::Error>;
+    /// Events per category among those `filter` matches, most first. The
+    /// filter's own categories and paging are ignored, so that counts for
+    /// unselected categories are still shown.
+    async fn category_counts(&self, filter: &EventFilter) -> Result<Vec<CategoryCount>, sqlx::Error>;
+    /// Category, decade and tag counts among the events `filter` matches,
+    /// paging aside. Categories are counted as in `category_counts`.
+    async fn facets(&self, filter: &EventFilter) -> Result<Facets, sqlx::Error>;
+    async fn find(&self, id: Uuid) -> Result<Option<Event>, sqlx::Error>;
+    async fn create(&self, event: Event) -> Result<Event, sqlx::Error>;
+    /// Applies the fields set in `changes`; `None` when the event is missing.
+    async fn update(&self, id: Uuid, changes: &EventPatch) -> Result<Option<Event>, sqlx::Error>;
+    async fn delete(&self, id: Uuid) -> Result<bool, sqlx::Error>;
+    /// Publishing clears any schedule.
+    async fn set_status(&self, id: Uuid, status: &str) -> Result<Option<Event>, sqlx::Error>;
+    /// Sets `publish_at` on a draft; `None` if the event is missing or not a draft.
+    async fn schedule(&self, id: Uuid, publish_at: Option<NaiveDateTime>) -> Result<Option<Event>, sqlx::Error>;
+    /// Scheduled drafts the user may edit, soonest first.
+    async fn scheduled(&self, user: Uuid, admin: bool) -> Result<Vec<Event>, sqlx::Error>;
+    /// Publishes drafts whose `publish_at` has passed and returns them. The
+    /// Postgres store queues an `event.published` webhook for each in the
+    /// same transaction.
+    async fn publish_due(&self) -> Result<Vec<Event>, sqlx::Error>;
+    /// Applies `changes` in order, returning one outcome each. When `atomic`,
+    /// the first change that is not applied stops the batch and undoes the
+    /// rest; otherwise every change is attempted and the applied ones kept.
+    async fn batch(&self, changes: Vec<Change>, atomic: bool) -> Result<Vec<Outcome>, sqlx::Error>;
+    /// A timeline's events that `filter` matches, earliest first. Whether
+    /// the caller may see the timeline is theirs to check.
+    async fn on_timeline(&self, timeline_id: Uuid, filter: &TimelineFilter) -> Result<Vec<Event>, sqlx::Error>;
+    /// Moves the events, given without repeats, onto the timeline; `None`,
+    /// with nothing moved, when any of them is missing.
+    async fn move_to(&self, ids: &[Uuid], timeline_id: Uuid) -> Result<Option<Vec<Event>>, sqlx::Error>;
+    /// Public events that look like `title` on `start_date`: a similar
+    /// title starting within `DATE_TOLERANCE_DAYS`, most similar first, at
+    /// most `MAX_CANDIDATES` of them.
+    async fn similar(
+        &self,
+        title: &str,
+        start_date: NaiveDateTime,
+        exclude: Option<Uuid>,
+    ) -> Result<Vec<Event>, sqlx::Error>;
+    /// Copies an event, with what hangs off it, as a new draft on
+    /// `timeline_id`. Views, featuring and any schedule are not copied, and
+    /// custom fields only within the same timeline. `None` when missing.
+    async fn duplicate(&self, id: Uuid, timeline_id: Option<Uuid>) -> Result<Option<Event>, sqlx::Error>;
+    /// Folds `other` into `id` as `choices` say and deletes `other`, moving
+    /// what hangs off it to the survivor. `None` when either is missing.
+    async fn merge(&self, id: Uuid, other: Uuid, choices: &MergeChoices) -> Result<Option<Event>, sqlx::Error>;
+}
+
+/// The Postgres store. Reads go to a replica when one is configured.
+pub struct PgEvents {
+    db: Db,
+}
+
+impl PgEvents {
+    pub fn new(db: Db) -> Self {
+        Self { db }
+    }
+
+    fn writer(&self) -> &PgPool {
+        self.db.writer()
+    }
+}
+
+fn insert_query(event: Event) -> QueryAs<'static, Postgres, Event, PgArguments> {
+    sqlx::query_as::<_, Event>(
+        r#"
+        INSERT INTO events (id, title, description, start_date, end_date, location, image_url, image_alt, category, importance, color, icon, created_at, updated_at, timeline_id, status, publish_at, start_date_min, start_date_max, custom_fields)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20)
+        RETURNING *
+        "#,
+    )
+    .bind(event.id)
+    .bind(event.title)
+    .bind(event.description)
+    .bind(event.start_date)
+    .bind(event.end_date)
+    .bind(event.location)
+    .bind(event.image_url)
+    .bind(event.image_alt)
+    .bind(event.category)
+    .bind(event.importance)
+    .bind(event.color)
+    .bind(event.icon)
+    .bind(event.created_at)
+    .bind(event.updated_at)
+    .bind(event.timeline_id)
+    .bind(event.status)
+    .bind(event.publish_at)
+    .bind(event.start_date_min)
+    .bind(event.start_date_max)
+    .bind(event.custom_fields)
+}
+
+/// `UPDATE events ... RETURNING *` setting the fields present in `changes`.
+/// Placeholders are numbered in the order the fields are pushed.
+fn update_query(id: Uuid, changes: &EventPatch) -> QueryBuilder<'static, Postgres> {
+    let mut query = QueryBuilder::new("UPDATE events SET updated_at = NOW()");
+    push_changes(&mut query, changes);
+    query.push(" WHERE id = ").push_bind(id).push(" RETURNING *");
+    query
+}
+
+/// Appends `, column = value` for each field present in `changes`.
+pub(super) fn push_changes<DB>(query: &mut QueryBuilder<'static, DB>, changes: &EventPatch)
+where
+    DB: Database,
+    String: Encode<'static, DB> + Type<DB>,
+    Option<String>: Encode<'static, DB> + Type<DB>,
+    NaiveDateTime: Encode<'static, DB> + Type<DB>,
+    Option<NaiveDateTime>: Encode<'static, DB> + Type<DB>,
+    i16: Encode<'static, DB> + Type<DB>,
+    serde_json::Value: Encode<'static, DB> + Type<DB>,
+{
+    if let Some(Some(title)) = &changes.title {
+        query.push(", title = ").push_bind(title.clone());
+    }
+    if let Some(description) = &changes.description {
+        query.push(", description = ").push_bind(description.clone());
+    }
+    if let Some(Some(start_date)) = changes.start_date {
+        query.push(", start_date = ").push_bind(start_date);
+    }
+    if let Some(end_date) = changes.end_date {
+        query.push(", end_date = ").push_bind(end_date);
+    }
+    if let Some(location) = &changes.location {
+        query.push(", location = ").push_bind(location.clone());
+    }
+    if let Some(image_url) = &changes.image_url {
+        query.push(", image_url = ").push_bind(image_url.clone());
+    }
+    if let Some(image_alt) = &changes.image_alt {
+        query.push(", image_alt = ").push_bind(image_alt.clone());
+    }
+    if let Some(category) = &changes.category {
+        query.push(", category = ").push_bind(category.clone());
+    }
+    if let Some(importance) = changes.importance {
+        query.push(", importance = ").push_bind(importance);
+    }
+    if let Some(color) = &changes.color {
+        query.push(", color = ").push_bind(color.clone());
+    }
+    if let Some(icon) = &changes.icon {
+        query.push(", icon = ").push_bind(icon.clone());
+    }
+    if let Some(start_date_min) = changes.start_date_min {
+        query.push(", start_date_min = ").push_bind(start_date_min);
+    }
+    if let Some(start_date_max) = changes.start_date_max {
+        query.push(", start_date_max = ").push_bind(start_date_max);
+    }
+    if let Some(custom_fields) = &changes.custom_fields {
+        query.push(", custom_fields = ").push_bind(custom_fields.clone());
+    }
+}
+
+/// Runs one change of a batch on `conn`.
+async fn apply(conn: &mut PgConnection, change: Change) -> Result<Outcome, sqlx::Error> {
+    let outcome = match change {
+        Change::Create(event) => Outcome::Saved(Box::new(insert_query(event).fetch_one(&mut *conn).await?)),
+        Change::Update(id, changes) => {
+            let mut query = update_query(id, &changes);
+            let event = query.build_query_as::<Event>().fetch_optional(&mut *conn).await?;
+            event.map_or(Outcome::NotFound, |event| Outcome::Saved(Box::new(event)))
+        }
+        Change::Delete(id) => {
+            let result = sqlx::query("DELETE FROM events WHERE id = $1")
+                .bind(id)
+                .execute(&mut *conn)
+                .await?;
+            if result.rows_affected() > 0 {
+                Outcome::Deleted
+            } else {
+                Outcome::NotFound
+            }
+        }
+    };
+    Ok(outcome)
+}
+
+/// Appends `WHERE ...` for `filter` to a query over `events e`. Shared by
+/// the Postgres and SQLite stores, so it sticks to SQL both understand.
+pub fn push_filter<'a, DB>(builder: &mut QueryBuilder<'a, DB>, filter: &EventFilter)
+where
+    DB: Database,
+    String: Encode<'a, DB> + Type<DB>,
+    NaiveDateTime: Encode<'a, DB> + Type<DB>,
+    i16: Encode<'a, DB> + Type<DB>,
+    Uuid: Encode<'a, DB> + Type<DB>,
+    bool: Encode<'a, DB> + Type<DB>,
+{
+    builder.push(" WHERE ");
+    match filter.editor {
+        Some((user, admin)) if filter.status != "published" => {
+            builder.push("e.status = ").push_bind(filter.status.clone()).push(" AND ");
+            timelines::push_editable_event(builder, user, admin);
+        }
+        _ if filter.status != "published" => {
+            builder.push("FALSE");
+        }
+        Some((user, _)) if filter.organization_id.is_some() => {
+            builder.push("(").push(timelines::PUBLIC_EVENT).push(" OR (e.status = 'published' AND ");
+            timelines::push_organization_event(builder, user);
+            builder.push("))");
+        }
+        _ => {
+            builder.push(timelines::PUBLIC_EVENT);
+        }
+    }
+    if let Some(organization) = filter.organization_id {
+        builder
+            .push(" AND e.timeline_id IN (SELECT id FROM timelines WHERE organization_id = ")
+            .push_bind(organization)
+            .push(")");
+    }
+
+    // Lower-cased rather than ILIKE, and the category default inlined
+    // rather than bound, to match the indexes on those expressions.
+    if let Some(search) = &filter.search {
+        let pattern = format!("%{}%", search.to_lowercase());
+        builder
+            .push(" AND (lower(e.title) LIKE ")
+            .push_bind(pattern.clone())
+            .push(" OR lower(e.description) LIKE ")
+            .push_bind(pattern)
+            .push(")");
+    }
+    if let Some(start_date) = filter.start_date {
+        builder.push(" AND e.start_date >= ").push_bind(start_date);
+    }
+    if let Some(end_date) = filter.end_date {
+        builder.push(" AND e.start_date <= ").push_bind(end_date);
+    }
+    if let Some(from) = filter.from {
+        builder.push(" AND COALESCE(e.end_date, e.start_date) >= ").push_bind(from);
+    }
+    if let Some(until) = filter.until {
+        builder.push(" AND e.start_date < ").push_bind(until);
+    }
+    if !filter.categories.is_empty() {
+        builder.push(format!(" AND lower(COALESCE(e.category, '{}')) IN (", UNCATEGORIZED));
+        let mut categories = builder.separated(", ");
+        for name in &filter.categories {
+            categories.push_bind(name.to_lowercase());
+        }
+        builder.push(")");
+    }
+    if let Some(min_importance) = filter.min_importance {
+        builder.push(" AND e.importance >= ").push_bind(min_importance);
+    }
+    if let Some(tag) = &filter.tag {
+        builder
+            .push(" AND EXISTS (SELECT 1 FROM event_tags et JOIN tags g ON g.id = et.tag_id WHERE et.event_id = e.id AND lower(g.name) = lower(")
+            .push_bind(tag.clone())
+            .push("))");
+    }
+}
+
+/// Appends ` AND ...` for each custom field filter to a query over events.
+pub fn push_field_filters(builder: &mut QueryBuilder<'_, Postgres>, filters: &[FieldFilter]) {
+    for filter in filters {
+        match filter {
+            FieldFilter::Text(name, text) => {
+                builder.push(" AND lower(custom_fields ->> ").push_bind(name.clone());
+                builder.push(") = lower(").push_bind(text.clone()).push(")");
+            }
+            FieldFilter::Boolean(name, flag) => {
+                builder.push(" AND custom_fields -> ").push_bind(name.clone());
+                builder.push(" = to_jsonb(").push_bind(*flag).push(")");
+            }
+            FieldFilter::Number(name, bound, number) => {
+                builder.push(" AND (custom_fields ->> ").push_bind(name.clone());
+                builder.push(")::float8").push(bound.operator()).push_bind(*number);
+            }
+            FieldFilter::Date(name, bound, date) => {
+                builder.push(" AND (custom_fields ->> ").push_bind(name.clone());
+                builder.push(")::date").push(bound.operator()).push_bind(*date);
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl EventRepository for PgEvents {
+    async fn list(&self, filter: &EventFilter) -> Result<EventPage, sqlx::Error> {
+        let mut query = QueryBuilder::new("SELECT e.* FROM events e");
+        push_filter(&mut query, filter);
+        query
+            .push(filter.sort.order_by())
+            .push(" LIMIT ")
+            .push_bind(filter.limit)
+            .push(" OFFSET ")
+            .push_bind(filter.offset);
+        let events = query.build_query_as::<Event>().fetch_all(self.db.reader()).await?;
+
+        let mut count = QueryBuilder::new("SELECT COUNT(*) FROM events e");
+        push_filter(&mut count, filter);
+        let total = count.build_query_scalar::<i64>().fetch_one(self.db.reader()).await?;
+
+        Ok(EventPage { events, total })
+    }
+
+    async fn category_counts(&self, filter: &EventFilter) -> Result<Vec<CategoryCount>, sqlx::Error> {
+        let filter = EventFilter { categories: Vec::new(), ..filter.clone() };
+        let mut query = QueryBuilder::new("SELECT COALESCE(e.category, ");
+        query.push_bind(UNCATEGORIZED).push(") AS category, COUNT(*) AS count FROM events e");
+        push_filter(&mut query, &filter);
+        query.push(" GROUP BY 1 ORDER BY 2 DESC, 1");
+        query.build_query_as::<CategoryCount>().fetch_all(self.db.reader()).await
+    }
+
+    /// The three groupings run concurrently, each on its own connection.
+    async fn facets(&self, filter: &EventFilter) -> Result<Facets, sqlx::Error> {
+        // Postgres has no year 0, so its BCE years are shifted by one to
+        // make them astronomical, as `DecadeCount` and the frontend expect.
+        let mut decades = QueryBuilder::new(
+            "SELECT (FLOOR((EXTRACT(YEAR FROM e.start_date) + CASE WHEN e.start_date < '0001-01-01' THEN 1 ELSE 0 END) / 10) * 10)::INT AS decade, \
+             COUNT(*) AS count FROM events e",
+        );
+        push_filter(&mut decades, filter);
+        decades.push(" GROUP BY 1 ORDER BY 1");
+        let mut tags = QueryBuilder::new(
+            "SELECT g.name AS tag, COUNT(*) AS count FROM events e \
+             JOIN event_tags et ON et.event_id = e.id JOIN tags g ON g.id = et.tag_id",
+        );
+        push_filter(&mut tags, filter);
+        tags.push(" GROUP BY 1 ORDER BY 2 DESC, 1 LIMIT ").push_bind(MAX_TAG_FACETS);
+
+        let (categories, decades, tags) = tokio::try_join!(
+            self.category_counts(filter),
+            decades.build_query_as::<DecadeCount>().fetch_all(self.db.reader()),
+            tags.build_query_as::<TagCount>().fetch_all(self.db.reader()),
+        )?;
+        Ok(Facets { categories, decades, tags })
+    }
+
+    async fn find(&self, id: Uuid) -> Result<Option<Event>, sqlx::Error> {
+        sqlx::query_as::<_, Event>("SELECT * FROM events WHERE id = $1")
+            .bind(id)
+            .fetch_optional(self.db.reader())
+            .await
+    }
+
+    async fn create(&self, event: Event) -> Result<Event, sqlx::Error> {
+        insert_query(event).fetch_one(self.writer()).await
+    }
+
+    async fn update(&self, id: Uuid, changes: &EventPatch) -> Result<Option<Event>, sqlx::Error> {
+        update_query(id, changes)
+            .build_query_as::<Event>()
+            .fetch_optional(self.writer())
+            .await
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM events WHERE id = $1")
+            .bind(id)
+            .execute(self.writer())
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn set_status(&self, id: Uuid, status: &str) -> Result<Option<Event>, sqlx::Error> {
+        sqlx::query_as::<_, Event>(
+            r#"
+            UPDATE events
+            SET status = $2,
+                publish_at = CASE WHEN $2 = 'published' THEN NULL ELSE publish_at END,
+                updated_at = NOW()
+            WHERE id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .bind(status)
+        .fetch_optional(self.writer())
+        .await
+    }
+
+    async fn schedule(&self, id: Uuid, publish_at: Option<NaiveDateTime>) -> Result<Option<Event>, sqlx::Error> {
+        sqlx::query_as::<_, Event>(
+            "UPDATE events SET publish_at = $2, updated_at = NOW() WHERE id = $1 AND status = 'draft' RETURNING *",
+        )
+        .bind(id)
+        .bind(publish_at)
+        .fetch_optional(self.writer())
+        .await
+    }
+
+    async fn scheduled(&self, user: Uuid, admin: bool) -> Result<Vec<Event>, sqlx::Error> {
+        let mut query =
+            QueryBuilder::new("SELECT e.* FROM events e WHERE e.status = 'draft' AND e.publish_at IS NOT NULL AND ");
+        timelines::push_editable_event(&mut query, user, admin);
+        query.push(" ORDER BY e.publish_at");
+        query.build_query_as::<Event>().fetch_all(self.db.reader()).await
+    }
+
+    async fn publish_due(&self) -> Result<Vec<Event>, sqlx::Error> {
+        let mut transaction = self.writer().begin().await?;
+        let published = sqlx::query_as::<_, Event>(
+            r#"
+            UPDATE events SET status = 'published', updated_at = NOW()
+            WHERE status = 'draft' AND publish_at <= NOW() AT TIME ZONE 'UTC'
+            RETURNING *
+            "#,
+        )
+        .fetch_all(&mut *transaction)
+        .await?;
+        for event in &published {
+            outbox::queue_webhook(&mut transaction, "event.published", &event.id.to_string(), event).await?;
+        }
+        transaction.commit().await?;
+        Ok(published)
+    }
+
+    /// Runs the batch in one transaction with a savepoint per change, so a
+    /// failed change can be undone without aborting the others.
+    async fn batch(&self, changes: Vec<Change>, atomic: bool) -> Result<Vec<Outcome>, sqlx::Error> {
+        let mut transaction = self.writer().begin().await?;
+        let mut outcomes = Vec::with_capacity(changes.len());
+        let mut failed = false;
+        for change in changes {
+            if failed {
+                outcomes.push(Outcome::RolledBack);
+                continue;
+            }
+            let mut savepoint = transaction.begin().await?;
+            let outcome = apply(&mut savepoint, change).await.unwrap_or_else(Outcome::Failed);
+            if outcome.applied() {
+                savepoint.commit().await?;
+            } else {
+                savepoint.rollback().await?;
+                failed = atomic;
+            }
+            outcomes.push(outcome);
+        }
+
+        if failed {
+            transaction.rollback().await?;
+            roll_back(&mut outcomes);
+        } else {
+            transaction.commit().await?;
+        }
+        Ok(outcomes)
+    }
+
+    async fn on_timeline(&self, timeline_id: Uuid, filter: &TimelineFilter) -> Result<Vec<Event>, sqlx::Error> {
+        let mut query = QueryBuilder::<Postgres>::new("SELECT * FROM events WHERE timeline_id = ");
+        query.push_bind(timeline_id);
+        query.push(" AND (").push_bind(filter.unpublished).push(" OR status = 'published')");
+        query.push(" AND importance >= ").push_bind(filter.min_importance);
+        push_field_filters(&mut query, &filter.fields);
+        query.push(" ORDER BY start_date");
+        query.build_query_as::<Event>().fetch_all(self.db.reader()).await
+    }
+
+    async fn move_to(&self, ids: &[Uuid], timeline_id: Uuid) -> Result<Option<Vec<Event>>, sqlx::Error> {
+        let mut transaction = self.writer().begin().await?;
+        let moved = sqlx::query_as::<_, Event>(
+            r#"
+            UPDATE events SET timeline_id = $1, updated_at = NOW()
+            WHERE id = ANY($2)
+            RETURNING *
+            "#,
+        )
+        .bind(timeline_id)
+        .bind(ids)
+        .fetch_all(&mut *transaction)
+        .await?;
+        // Dropping the transaction undoes the moves.
+        if moved.len() < ids.len() {
+            return Ok(None);
+        }
+        transaction.commit().await?;
+        Ok(Some(moved))
+    }
+
+    /// Private timelines are never suggested.
+    async fn similar(
+        &self,
+        title: &str,
+        start_date: NaiveDateTime,
+        exclude: Option<Uuid>,
+    ) -> Result<Vec<Event>, sqlx::Error> {
+        let query = format!(
+            r#"
+            SELECT * FROM events e
+            WHERE (lower(title) = lower($1) OR similarity(title, $1) >= $2)
+              AND start_date BETWEEN $3 - make_interval(days => $4) AND $3 + make_interval(days => $4)
+              AND ($5::uuid IS NULL OR id <> $5)
+              AND {}
+            ORDER BY similarity(title, $1) DESC, abs(extract(epoch FROM start_date - $3))
+            LIMIT $6
+            "#,
+            timelines::PUBLIC_EVENT
+        );
+        sqlx::query_as::<_, Event>(&query)
+            .bind(title)
+            .bind(TITLE_SIMILARITY)
+            .bind(start_date)
+            .bind(DATE_TOLERANCE_DAYS)
+            .bind(exclude)
+            .bind(MAX_CANDIDATES)
+            .fetch_all(self.db.reader())
+            .await
+    }
+
+    /// Copies its tags, people, sources, embeds and gallery images too.
+    async fn duplicate(&self, id: Uuid, timeline_id: Option<Uuid>) -> Result<Option<Event>, sqlx::Error> {
+        let mut transaction = self.writer().begin().await?;
+        let copy = sqlx::query_as::<_, Event>(
+            r#"
+            INSERT INTO events (id, title, description, start_date, end_date, start_date_min, start_date_max,
+                location, image_url, image_alt, category, importance, color, icon, created_at, updated_at,
+                timeline_id, status, custom_fields)
+            SELECT $2, title, description, start_date, end_date, start_date_min, start_date_max,
+                location, image_url, image_alt, category, importance, color, icon, NOW(), NOW(), $3, 'draft',
+                -- Custom fields belong to the timeline, so they only come along within it.
+                CASE WHEN timeline_id IS NOT DISTINCT FROM $3 THEN custom_fields ELSE '{}' END
+            FROM events WHERE id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .bind(Uuid::new_v4())
+        .bind(timeline_id)
+        .fetch_optional(&mut *transaction)
+        .await?;
+        let Some(copy) = copy else {
+            return Ok(None);
+        };
+
+        for query in [
+            "INSERT INTO event_tags (event_id, tag_id) SELECT $1, tag_id FROM event_tags WHERE event_id = $2",
+            "INSERT INTO event_people (event_id, person_id) SELECT $1, person_id FROM event_people WHERE event_id = $2",
+            // Keeps the footnote order.
+            "INSERT INTO event_sources (event_id, source_id, attached_at) \
+             SELECT $1, source_id, attached_at FROM event_sources WHERE event_id = $2",
+            "INSERT INTO event_embeds (event_id, url, provider, player_url, title, author_name, thumbnail_url) \
+             SELECT $1, url, provider, player_url, title, author_name, thumbnail_url FROM event_embeds WHERE event_id = $2",
+            "INSERT INTO event_images (event_id, url, alt, caption, position, crop) \
+             SELECT $1, url, alt, caption, position, crop FROM event_images WHERE event_id = $2",
+        ] {
+            sqlx::query(query).bind(copy.id).bind(id).execute(&mut *transaction).await?;
+        }
+        transaction.commit().await?;
+        Ok(Some(copy))
+    }
+
+    /// Tags, people, sources, gallery images, likes and reports from both
+    /// events are kept on the survivor, which keeps its own embed if it has
+    /// one, and the stories, reactions, comments and notifications of the
+    /// other move to it.
+    async fn merge(&self, id: Uuid, other: Uuid, choices: &MergeChoices) -> Result<Option<Event>, sqlx::Error> {
+        let side = |choice: Option<Side>| choice.map(Side::as_str);
+        let mut transaction = self.writer().begin().await?;
+        let merged = sqlx::query_as::<_, Event>(
+            r#"
+            UPDATE events AS e SET
+                title = CASE $3 WHEN 'other' THEN o.title ELSE e.title END,
+                description = CASE $4 WHEN 'other' THEN o.description WHEN 'keep' THEN e.description
+                    ELSE COALESCE(e.description, o.description) END,
+                start_date = CASE $5 WHEN 'other' THEN o.start_date ELSE e.start_date END,
+                -- The bounds belong to whichever start date is kept.
+                start_date_min = CASE $5 WHEN 'other' THEN o.start_date_min ELSE e.start_date_min END,
+                start_date_max = CASE $5 WHEN 'other' THEN o.start_date_max ELSE e.start_date_max END,
+                end_date = CASE $6 WHEN 'other' THEN o.end_date WHEN 'keep' THEN e.end_date
+                    ELSE COALESCE(e.end_date, o.end_date) END,
+                location = CASE $7 WHEN 'other' THEN o.location WHEN 'keep' THEN e.location
+                    ELSE COALESCE(e.location, o.location) END,
+                image_url = CASE $8 WHEN 'other' THEN o.image_url WHEN 'keep' THEN e.image_url
+                    ELSE COALESCE(e.image_url, o.image_url) END,
+                -- The description goes wherever its image does.
+                image_alt = CASE $8 WHEN 'other' THEN o.image_alt WHEN 'keep' THEN e.image_alt
+                    ELSE CASE WHEN e.image_url IS NULL THEN o.image_alt ELSE e.image_alt END END,
+                category = CASE $9 WHEN 'other' THEN o.category WHEN 'keep' THEN e.category
+                    ELSE COALESCE(e.category, o.category) END,
+                -- Either event may be the one a zoomed-out timeline should show.
+                importance = GREATEST(e.importance, o.importance),
+                color = COALESCE(e.color, o.color),
+                icon = COALESCE(e.icon, o.icon),
+                -- Values the kept event lacks come from the other, if it shares the timeline.
+                custom_fields = CASE WHEN e.timeline_id IS NOT DISTINCT FROM o.timeline_id
+                    THEN o.custom_fields || e.custom_fields ELSE e.custom_fields END,
+                updated_at = NOW()
+            FROM events AS o
+            WHERE e.id = $1 AND o.id = $2
+            RETURNING e.*
+            "#,
+        )
+        .bind(id)
+        .bind(other)
+        .bind(side(choices.title))
+        .bind(side(choices.description))
+        .bind(side(choices.start_date))
+        .bind(side(choices.end_date))
+        .bind(side(choices.location))
+        .bind(side(choices.image_url))
+        .bind(side(choices.category))
+        .fetch_optional(&mut *transaction)
+        .await?;
+        if merged.is_none() {
+            return Ok(None);
+        }
+
+        // Everything hanging off the merged-away event is copied to the
+        // survivor before the delete cascades; what the survivor already has
+        // wins.
+        for query in [
+            "INSERT INTO event_tags (event_id, tag_id) SELECT $1, tag_id FROM event_tags WHERE event_id = $2 \
+             ON CONFLICT DO NOTHING",
+            "INSERT INTO event_people (event_id, person_id) SELECT $1, person_id FROM event_people WHERE event_id = $2 \
+             ON CONFLICT DO NOTHING",
+            "INSERT INTO event_sources (event_id, source_id, attached_at) \
+             SELECT $1, source_id, attached_at FROM event_sources WHERE event_id = $2 ON CONFLICT DO NOTHING",
+            "INSERT INTO event_embeds (event_id, url, provider, player_url, title, author_name, thumbnail_url) \
+             SELECT $1, url, provider, player_url, title, author_name, thumbnail_url \
+             FROM event_embeds WHERE event_id = $2 ON CONFLICT DO NOTHING",
+            // The other gallery follows the survivor's.
+            "INSERT INTO event_images (event_id, url, alt, caption, position, crop, created_at) \
+             SELECT $1, url, alt, caption, \
+                 position + (SELECT COALESCE(MAX(position) + 1, 0) FROM event_images WHERE event_id = $1), \
+                 crop, created_at \
+             FROM event_images WHERE event_id = $2 ON CONFLICT DO NOTHING",
+            // Someone who liked both events has liked the survivor once.
+            "INSERT INTO event_likes (event_id, user_id, fingerprint, created_at) \
+             SELECT $1, user_id, fingerprint, created_at FROM event_likes WHERE event_id = $2 ON CONFLICT DO NOTHING",
+            "INSERT INTO event_reports \
+                 (event_id, reporter_id, reason, details, resolution, resolved_by, resolved_at, created_at) \
+             SELECT $1, reporter_id, reason, details, resolution, resolved_by, resolved_at, created_at \
+             FROM event_reports WHERE event_id = $2 ON CONFLICT DO NOTHING",
+        ] {
+            sqlx::query(query).bind(id).bind(other).execute(&mut *transaction).await?;
+        }
+
+        // Stories that visit the merged-away event visit the survivor instead.
+        sqlx::query("UPDATE story_steps SET event_id = $1 WHERE event_id = $2")
+            .bind(id)
+            .bind(other)
+            .execute(&mut *transaction)
+            .await?;
+
+        // Reactions too, except where the same person already gave the same
+        // emoji on the survivor; the delete takes those.
+        sqlx::query(
+            r#"
+            UPDATE reactions r SET event_id = $1
+            WHERE r.event_id = $2 AND NOT EXISTS (
+                SELECT 1 FROM reactions s WHERE s.event_id = $1 AND s.user_id = r.user_id AND s.emoji = r.emoji
+            )
+            "#,
+        )
+        .bind(id)
+        .bind(other)
+        .execute(&mut *transaction)
+        .await?;
+
+        // So does the discussion, and what people were told about it.
+        for table in ["comments", "notifications"] {
+            sqlx::query(&format!("UPDATE {} SET event_id = $1 WHERE event_id = $2", table))
+                .bind(id)
+                .bind(other)
+                .execute(&mut *transaction)
+                .await?;
+        }
+
+        sqlx::query("DELETE FROM events WHERE id = $1")
+            .bind(other)
+            .execute(&mut *transaction)
+            .await?;
+        let merged = sqlx::query_as::<_, Event>(
+            "UPDATE events SET likes = (SELECT COUNT(*) FROM event_likes WHERE event_id = $1) \
+             WHERE id = $1 RETURNING *",
+        )
+        .bind(id)
+        .fetch_one(&mut *transaction)
+        .await?;
+        transaction.commit().await?;
+        Ok(Some(merged))
+    }
+}
+
+/// A store kept in memory, for tests. It has no timelines table, so every
+/// timeline counts as public, only admins can edit timeline events, and no
+/// event belongs to an organization. Nor has it tags, so filtering by one
+/// finds nothing, or anything else hanging off events, so copies and merges
+/// only touch the events themselves. Duplicates need the same title, case
+/// aside, as there is no trigram similarity.
+#[cfg(test)]
+#[derive(Default)]
+pub struct MemoryEvents {
+    events: std::sync::Mutex<Vec<Event>>,
+}
+
+#[cfg(test)]
+impl MemoryEvents {
+    fn matches(event: &Event, filter: &EventFilter) -> bool {
+        let visible = match filter.editor {
+            Some((_, admin)) if filter.status != "published" => {
+                event.status == filter.status && (event.timeline_id.is_none() || admin)
+            }
+            _ => filter.status == "published" && event.status == "published",
+        };
+        let contains = |text: &str, term: &str| text.to_lowercase().contains(&term.to_lowercase());
+        let searched = filter.search.as_deref().is_none_or(|term| {
+            contains(&event.title, term) || event.description.as_deref().is_some_and(|text| contains(text, term))
+        });
+        let category = event.category.as_deref().unwrap_or(UNCATEGORIZED);
+        let categorized = filter.categories.is_empty()
+            || filter.categories.iter().any(|name| name.eq_ignore_ascii_case(category));
+        visible
+            && searched
+            && categorized
+            && filter.organization_id.is_none()
+            && filter.tag.is_none()
+            && filter.min_importance.is_none_or(|min| event.importance >= min)
+            && filter.start_date.is_none_or(|start| event.start_date >= start)
+            && filter.end_date.is_none_or(|end| event.start_date <= end)
+            && filter.from.is_none_or(|from| event.end_date.unwrap_or(event.start_date) >= from)
+            && filter.until.is_none_or(|until| event.start_date < until)
+    }
+
+    fn field_matches(values: &serde_json::Value, filter: &FieldFilter) -> bool {
+        use crate::custom_fields::Bound;
+        use std::cmp::Ordering;
+
+        let within = |ordering: Option<Ordering>, bound: Bound| {
+            matches!(
+                (ordering, bound),
+                (Some(Ordering::Equal), _) | (Some(Ordering::Greater), Bound::Min) | (Some(Ordering::Less), Bound::Max)
+            )
+        };
+        match filter {
+            FieldFilter::Text(name, text) => {
+                values[name.as_str()].as_str().is_some_and(|value| value.to_lowercase() == text.to_lowercase())
+            }
+            FieldFilter::Boolean(name, flag) => values[name.as_str()].as_bool() == Some(*flag),
+            FieldFilter::Number(name, bound, number) => {
+                within(values[name.as_str()].as_f64().and_then(|value| value.partial_cmp(number)), *bound)
+            }
+            FieldFilter::Date(name, bound, date) => {
+                let value = values[name.as_str()].as_str();
+                let value = value.and_then(|value| chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d").ok());
+                within(value.map(|value| value.cmp(date)), *bound)
+            }
+        }
+    }
+
+    fn modify(&self, id: Uuid, change: impl FnOnce(&mut Event) -> bool) -> Option<Event> {
+        let mut events = self.events.lock().unwrap();
+        let event = events.iter_mut().find(|event| event.id == id)?;
+        if !change(event) {
+            return None;
+        }
+        event.updated_at = chrono::Utc::now().naive_utc();
+        Some(event.clone())
+    }
+}
+
+#[cfg(test)]
+#[async_trait]
+impl EventRepository for MemoryEvents {
+    async fn list(&self, filter: &EventFilter) -> Result<EventPage, sqlx::Error> {
+        let mut matching: Vec<Event> = self
+            .events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|event| Self::matches(event, filter))
+            .cloned()
+            .collect();
+        matching.sort_by_key(|event| std::cmp::Reverse(event.start_date));
+        match filter.sort {
+            EventSort::Newest => {}
+            EventSort::Oldest => matching.reverse(),
+            EventSort::Likes => matching.sort_by_key(|event| std::cmp::Reverse(event.likes)),
+        }
+        let total = matching.len() as i64;
+        let events = matching
+            .into_iter()
+            .skip(filter.offset.max(0) as usize)
+            .take(filter.limit.max(0) as usize)
+            .collect();
+        Ok(EventPage { events, total })
+    }
+
+    async fn category_counts(&self, filter: &EventFilter) -> Result<Vec<CategoryCount>, sqlx::Error> {
+        let filter = EventFilter { categories: Vec::new(), ..filter.clone() };
+        let mut counts: Vec<CategoryCount> = Vec::new();
+        for event in self.events.lock().unwrap().iter().filter(|event| Self::matches(event, &filter)) {
+            let category = event.category.as_deref().unwrap_or(UNCATEGORIZED);
+            match counts.iter_mut().find(|count| count.category == category) {
+                Some(count) => count.count += 1,
+                None => counts.push(CategoryCount { category: category.to_string(), count: 1 }),
+            }
+        }
+        counts.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.category.cmp(&b.category)));
+        Ok(counts)
+    }
+
+    /// Without a tags table, no tags are counted.
+    async fn facets(&self, filter: &EventFilter) -> Result<Facets, sqlx::Error> {
+        use chrono::Datelike;
+
+        let mut decades: Vec<DecadeCount> = Vec::new();
+        for event in self.events.lock().unwrap().iter().filter(|event| Self::matches(event, filter)) {
+            let decade = event.start_date.year().div_euclid(10) * 10;
+            match decades.iter_mut().find(|count| count.decade == decade) {
+                Some(count) => count.count += 1,
+                None => decades.push(DecadeCount { decade, count: 1 }),
+            }
+        }
+        decades.sort_by_key(|count| count.decade);
+        Ok(Facets { categories: self.category_counts(filter).await?, decades, tags: Vec::new() })
+    }
+
+    async fn find(&self, id: Uuid) -> Result<Option<Event>, sqlx::Error> {
+        Ok(self.events.lock().unwrap().iter().find(|event| event.id == id).cloned())
+    }
+
+    async fn create(&self, event: Event) -> Result<Event, sqlx::Error> {
+        self.events.lock().unwrap().push(event.clone());
+        Ok(event)
+    }
+
+    async fn update(&self, id: Uuid, changes: &EventPatch) -> Result<Option<Event>, sqlx::Error> {
+        Ok(self.modify(id, |event| {
+            if let Some(Some(title)) = &changes.title {
+                event.title = title.clone();
+            }
+            if let Some(description) = &changes.description {
+                event.description = description.clone();
+            }
+            if let Some(Some(start_date)) = changes.start_date {
+                event.start_date = start_date;
+            }
+            if let Some(end_date) = changes.end_date {
+                event.end_date = end_date;
+            }
+            if let Some(location) = &changes.location {
+                event.location = location.clone();
+            }
+            if let Some(image_url) = &changes.image_url {
+                event.image_url = image_url.clone();
+            }
+            if let Some(image_alt) = &changes.image_alt {
+                event.image_alt = image_alt.clone();
+            }
+            if let Some(category) = &changes.category {
+                event.category = category.clone();
+            }
+            if let Some(importance) = changes.importance {
+                event.importance = importance;
+            }
+            if let Some(color) = &changes.color {
+                event.color = color.clone();
+            }
+            if let Some(icon) = &changes.icon {
+                event.icon = icon.clone();
+            }
+            if let Some(start_date_min) = changes.start_date_min {
+                event.start_date_min = start_date_min;
+            }
+            if let Some(start_date_max) = changes.start_date_max {
+                event.start_date_max = start_date_max;
+            }
+            if let Some(custom_fields) = &changes.custom_fields {
+                event.custom_fields = custom_fields.clone();
+            }
+            true
+        }))
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<bool, sqlx::Error> {
+        let mut events = self.events.lock().unwrap();
+        let before = events.len();
+        events.retain(|event| event.id != id);
+        Ok(events.len() < before)
+    }
+
+    async fn set_status(&self, id: Uuid, status: &str) -> Result<Option<Event>, sqlx::Error> {
+        Ok(self.modify(id, |event| {
+            event.status = status.to_string();
+            if status == "published" {
+                event.publish_at = None;
+            }
+            true
+        }))
+    }
+
+    async fn schedule(&self, id: Uuid, publish_at: Option<NaiveDateTime>) -> Result<Option<Event>, sqlx::Error> {
+        Ok(self.modify(id, |event| {
+            if event.status != "draft" {
+                return false;
+            }
+            event.publish_at = publish_at;
+            true
+        }))
+    }
+
+    async fn scheduled(&self, _user: Uuid, admin: bool) -> Result<Vec<Event>, sqlx::Error> {
+        let mut events: Vec<Event> = self
+            .events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|event| event.status == "draft" && event.publish_at.is_some())
+            .filter(|event| event.timeline_id.is_none() || admin)
+            .cloned()
+            .collect();
+        events.sort_by_key(|event| event.publish_at);
+        Ok(events)
+    }
+
+    async fn publish_due(&self) -> Result<Vec<Event>, sqlx::Error> {
+        let now = chrono::Utc::now().naive_utc();
+        let mut published = Vec::new();
+        for event in self.events.lock().unwrap().iter_mut() {
+            if event.status == "draft" && event.publish_at.is_some_and(|at| at <= now) {
+                event.status = "published".to_string();
+                event.updated_at = now;
+                published.push(event.clone());
+            }
+        }
+        Ok(published)
+    }
+
+    async fn batch(&self, changes: Vec<Change>, atomic: bool) -> Result<Vec<Outcome>, sqlx::Error> {
+        let before = self.events.lock().unwrap().clone();
+        let mut outcomes = Vec::with_capacity(changes.len());
+        let mut failed = false;
+        for change in changes {
+            if failed {
+                outcomes.push(Outcome::RolledBack);
+                continue;
+            }
+            let outcome = match change {
+                Change::Create(event) => Outcome::Saved(Box::new(self.create(event).await?)),
+                Change::Update(id, changes) => self
+                    .update(id, &changes)
+                    .await?
+                    .map_or(Outcome::NotFound, |event| Outcome::Saved(Box::new(event))),
+                Change::Delete(id) => match self.delete(id).await? {
+                    true => Outcome::Deleted,
+                    false => Outcome::NotFound,
+                },
+            };
+            failed = atomic && !outcome.applied();
+            outcomes.push(outcome);
+        }
+
+        if failed {
+            *self.events.lock().unwrap() = before;
+            roll_back(&mut outcomes);
+        }
+        Ok(outcomes)
+    }
+
+    async fn on_timeline(&self, timeline_id: Uuid, filter: &TimelineFilter) -> Result<Vec<Event>, sqlx::Error> {
+        let mut events: Vec<Event> = self
+            .events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|event| event.timeline_id == Some(timeline_id))
+            .filter(|event| filter.unpublished || event.status == "published")
+            .filter(|event| event.importance >= filter.min_importance)
+            .filter(|event| filter.fields.iter().all(|field| Self::field_matches(&event.custom_fields, field)))
+            .cloned()
+            .collect();
+        events.sort_by_key(|event| event.start_date);
+        Ok(events)
+    }
+
+    async fn move_to(&self, ids: &[Uuid], timeline_id: Uuid) -> Result<Option<Vec<Event>>, sqlx::Error> {
+        let mut events = self.events.lock().unwrap();
+        if !ids.iter().all(|id| events.iter().any(|event| event.id == *id)) {
+            return Ok(None);
+        }
+        let now = chrono::Utc::now().naive_utc();
+        let mut moved = Vec::with_capacity(ids.len());
+        for event in events.iter_mut().filter(|event| ids.contains(&event.id)) {
+            event.timeline_id = Some(timeline_id);
+            event.updated_at = now;
+            moved.push(event.clone());
+        }
+        Ok(Some(moved))
+    }
+
+    async fn similar(
+        &self,
+        title: &str,
+        start_date: NaiveDateTime,
+        exclude: Option<Uuid>,
+    ) -> Result<Vec<Event>, sqlx::Error> {
+        let distance = |event: &Event| (event.start_date - start_date).num_seconds().abs();
+        let tolerance = chrono::Duration::days(DATE_TOLERANCE_DAYS.into()).num_seconds();
+        let mut events: Vec<Event> = self
+            .events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|event| event.status == "published" && Some(event.id) != exclude)
+            .filter(|event| event.title.to_lowercase() == title.to_lowercase() && distance(event) <= tolerance)
+            .cloned()
+            .collect();
+        events.sort_by_key(distance);
+        events.truncate(MAX_CANDIDATES as usize);
+        Ok(events)
+    }
+
+    async fn duplicate(&self, id: Uuid, timeline_id: Option<Uuid>) -> Result<Option<Event>, sqlx::Error> {
+        let Some(original) = self.find(id).await? else {
+            return Ok(None);
+        };
+        let now = chrono::Utc::now().naive_utc();
+        let custom_fields = match original.timeline_id == timeline_id {
+            true => original.custom_fields.clone(),
+            false => serde_json::json!({}),
+        };
+        let copy = Event {
+            id: Uuid::new_v4(),
+            created_at: now,
+            updated_at: now,
+            timeline_id,
+            status: "draft".to_string(),
+            publish_at: None,
+            views: 0,
+            likes: 0,
+            featured: false,
+            custom_fields,
+            ..original
+        };
+        self.create(copy).await.map(Some)
+    }
+
+    async fn merge(&self, id: Uuid, other: Uuid, choices: &MergeChoices) -> Result<Option<Event>, sqlx::Error> {
+        fn pick<T: Clone>(side: Option<Side>, kept: &Option<T>, other: &Option<T>) -> Option<T> {
+            match side {
+                Some(Side::Other) => other.clone(),
+                Some(Side::Keep) => kept.clone(),
+                None => kept.clone().or_else(|| other.clone()),
+            }
+        }
+
+        let mut events = self.events.lock().unwrap();
+        let Some(other) = events.iter().find(|event| event.id == other).cloned() else {
+            return Ok(None);
+        };
+        let Some(kept) = events.iter_mut().find(|event| event.id == id) else {
+            return Ok(None);
+        };
+        if matches!(choices.title, Some(Side::Other)) {
+            kept.title = other.title.clone();
+        }
+        if matches!(choices.start_date, Some(Side::Other)) {
+            kept.start_date = other.start_date;
+            kept.start_date_min = other.start_date_min;
+            kept.start_date_max = other.start_date_max;
+        }
+        kept.description = pick(choices.description, &kept.description, &other.description);
+        kept.end_date = pick(choices.end_date, &kept.end_date, &other.end_date);
+        kept.location = pick(choices.location, &kept.location, &other.location);
+        kept.image_alt = match (choices.image_url, &kept.image_url) {
+            (Some(Side::Other), _) | (None, None) => other.image_alt.clone(),
+            _ => kept.image_alt.clone(),
+        };
+        kept.image_url = pick(choices.image_url, &kept.image_url, &other.image_url);
+        kept.category = pick(choices.category, &kept.category, &other.category);
+        kept.importance = kept.importance.max(other.importance);
+        kept.color = pick(None, &kept.color, &other.color);
+        kept.icon = pick(None, &kept.icon, &other.icon);
+        if let (true, Some(values)) = (kept.timeline_id == other.timeline_id, other.custom_fields.as_object()) {
+            let mut merged = values.clone();
+            if let Some(own) = kept.custom_fields.as_object() {
+                merged.extend(own.clone());
+            }
+            kept.custom_fields = serde_json::Value::Object(merged);
+        }
+        kept.updated_at = chrono::Utc::now().naive_utc();
+        let merged = kept.clone();
+        events.retain(|event| event.id != other.id);
+        Ok(Some(merged))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(title: &str, day: u32, status: &str) -> Event {
+        let date = chrono::NaiveDate::from_ymd_opt(2024, 1, day).unwrap().and_hms_opt(0, 0, 0).unwrap();
+        Event {
+            id: Uuid::new_v4(),
+            title: title.to_string(),
+            description: None,
+            start_date: date,
+            end_date: None,
+            start_date_min: None,
+            start_date_max: None,
+            location: None,
+            image_url: None,
+            image_alt: None,
+            category: None,
+            importance: 3,
+            color: None,
+            icon: None,
+            created_at: date,
+            updated_at: date,
+            timeline_id: None,
+            status: status.to_string(),
+            publish_at: None,
+            views: 0,
+            likes: 0,
+            featured: false,
+            custom_fields: serde_json::json!({}),
+        }
+    }
+
+    /// Each store holding `events`: the in-memory one and SQLite.
+    async fn stores(events: Vec<Event>) -> [Events; 2] {
+        let memory: Events = Arc::new(MemoryEvents::default());
+        let sqlite: Events = Arc::new(crate::db::sqlite::SqliteEvents::connect("sqlite::memory:").await.unwrap());
+        for store in [&memory, &sqlite] {
+            for event in &events {
+                store.create(event.clone()).await.unwrap();
+            }
+        }
+        [memory, sqlite]
+    }
+
+    fn published() -> EventFilter {
+        EventFilter {
+            status: "published".to_string(),
+            limit: 20,
+            ..EventFilter::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn lists_published_events_newest_first() {
+        for store in stores(vec![
+            event("First", 1, "published"),
+            event("Draft", 2, "draft"),
+            event("Second", 3, "published"),
+        ])
+        .await
+        {
+
+            let page = store.list(&published()).await.unwrap();
+            let titles: Vec<_> = page.events.iter().map(|event| event.title.as_str()).collect();
+            assert_eq!(titles, ["Second", "First"]);
+            assert_eq!(page.total, 2);
+        }
+    }
+
+    #[tokio::test]
+    async fn lists_oldest_first_on_request() {
+        for store in stores(vec![event("First", 1, "published"), event("Second", 2, "published")]).await {
+            let filter = EventFilter { sort: EventSort::Oldest, ..published() };
+
+            let page = store.list(&filter).await.unwrap();
+            let titles: Vec<_> = page.events.iter().map(|event| event.title.as_str()).collect();
+            assert_eq!(titles, ["First", "Second"]);
+        }
+    }
+
+    #[tokio::test]
+    async fn lists_most_liked_first_on_request() {
+        let liked = Event { likes: 3, ..event("Liked", 1, "published") };
+        for store in stores(vec![liked, event("Newer", 2, "published")]).await {
+            let filter = EventFilter { sort: EventSort::Likes, ..published() };
+
+            let page = store.list(&filter).await.unwrap();
+            let titles: Vec<_> = page.events.iter().map(|event| event.title.as_str()).collect();
+            assert_eq!(titles, ["Liked", "Newer"]);
+        }
+    }
+
+    #[tokio::test]
+    async fn drafts_need_an_editor() {
+        for store in stores(vec![event("Draft", 1, "draft")]).await {
+            let drafts = EventFilter {
+                status: "draft".to_string(),
+                ..published()
+            };
+
+            assert_eq!(store.list(&drafts).await.unwrap().total, 0);
+            let editor = EventFilter {
+                editor: Some((Uuid::new_v4(), false)),
+                ..drafts
+            };
+            assert_eq!(store.list(&editor).await.unwrap().total, 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn searches_case_insensitively_and_pages() {
+        for store in stores((1..=5).map(|day| event(&format!("Battle {}", day), day, "published")).collect()).await {
+            let filter = EventFilter {
+                search: Some("battle".to_string()),
+                limit: 2,
+                offset: 2,
+                ..published()
+            };
+
+            let page = store.list(&filter).await.unwrap();
+            let titles: Vec<_> = page.events.iter().map(|event| event.title.as_str()).collect();
+            assert_eq!(titles, ["Battle 3", "Battle 2"]);
+            assert_eq!(page.total, 5);
+        }
+    }
+
+    fn categorized(title: &str, day: u32, category: Option<&str>) -> Event {
+        Event { category: category.map(str::to_string), ..event(title, day, "published") }
+    }
+
+    #[tokio::test]
+    async fn filters_by_category_including_uncategorized() {
+        for store in stores(vec![
+            categorized("Treaty", 1, Some("Politics")),
+            categorized("Eclipse", 2, Some("Science")),
+            categorized("Festival", 3, None),
+        ])
+        .await
+        {
+            let filter = EventFilter {
+                categories: vec!["politics".to_string(), UNCATEGORIZED.to_string()],
+                ..published()
+            };
+
+            let page = store.list(&filter).await.unwrap();
+            let titles: Vec<_> = page.events.iter().map(|event| event.title.as_str()).collect();
+            assert_eq!(titles, ["Festival", "Treaty"]);
+        }
+    }
+
+    #[tokio::test]
+    async fn counts_categories_in_the_date_window_ignoring_the_selection() {
+        for store in stores(vec![
+            categorized("Treaty", 1, Some("Politics")),
+            categorized("Election", 2, Some("Politics")),
+            categorized("Eclipse", 3, Some("Science")),
+            categorized("Festival", 4, None),
+            categorized("Coronation", 20, Some("Politics")),
+        ])
+        .await
+        {
+            let day = |day| chrono::NaiveDate::from_ymd_opt(2024, 1, day).unwrap().and_hms_opt(0, 0, 0).unwrap();
+            let filter = EventFilter {
+                start_date: Some(day(1)),
+                end_date: Some(day(10)),
+                categories: vec!["Science".to_string()],
+                ..published()
+            };
+
+            let counts = store.category_counts(&filter).await.unwrap();
+            let counts: Vec<_> = counts.iter().map(|count| (count.category.as_str(), count.count)).collect();
+            assert_eq!(counts, [("Politics", 2), ("Science", 1), (UNCATEGORIZED, 1)]);
+        }
+    }
+
+    #[tokio::test]
+    async fn windows_include_events_still_under_way() {
+        let day = |day| chrono::NaiveDate::from_ymd_opt(2024, 1, day).unwrap().and_hms_opt(0, 0, 0).unwrap();
+        for store in stores(vec![
+            event("Before", 1, "published"),
+            Event { end_date: Some(day(12)), ..event("Siege", 2, "published") },
+            event("Inside", 15, "published"),
+            event("After", 20, "published"),
+        ])
+        .await
+        {
+            let filter = EventFilter { from: Some(day(10)), until: Some(day(20)), sort: EventSort::Oldest, ..published() };
+
+            let page = store.list(&filter).await.unwrap();
+            let titles: Vec<_> = page.events.iter().map(|event| event.title.as_str()).collect();
+            assert_eq!(titles, ["Siege", "Inside"]);
+        }
+    }
+
+    #[tokio::test]
+    async fn facets_count_decades_of_the_filtered_events() {
+        let on = |title: &str, year: i32, category: Option<&str>| {
+            let date = chrono::NaiveDate::from_ymd_opt(year, 6, 1).unwrap().and_hms_opt(0, 0, 0).unwrap();
+            Event { start_date: date, ..categorized(title, 1, category) }
+        };
+        for store in stores(vec![
+            on("Moon landing", 1969, Some("Science")),
+            on("Woodstock", 1969, Some("Culture")),
+            on("Berlin Wall falls", 1989, Some("Politics")),
+            on("Caesar crosses the Rubicon", -49, Some("Politics")),
+        ])
+        .await
+        {
+            let filter = EventFilter { categories: vec!["Politics".to_string()], ..published() };
+
+            let facets = store.facets(&filter).await.unwrap();
+            let decades: Vec<_> = facets.decades.iter().map(|count| (count.decade, count.count)).collect();
+            assert_eq!(decades, [(-50, 1), (1980, 1)]);
+            assert_eq!(facets.categories.len(), 3);
+        }
+    }
+
+    #[tokio::test]
+    async fn filters_by_minimum_importance() {
+        for store in stores(vec![
+            Event { importance: 5, ..event("Revolution", 1, "published") },
+            event("Election", 2, "published"),
+            Event { importance: 1, ..event("Parade", 3, "published") },
+        ])
+        .await
+        {
+            let filter = EventFilter { min_importance: Some(3), ..published() };
+
+            let page = store.list(&filter).await.unwrap();
+            let titles: Vec<_> = page.events.iter().map(|event| event.title.as_str()).collect();
+            assert_eq!(titles, ["Election", "Revolution"]);
+        }
+    }
+
+    /// A field's value in an update: left out, set to null, or set.
+    #[derive(Clone, Copy)]
+    enum FieldChange {
+        Absent,
+        Null,
+        Set,
+    }
+
+    fn nullable<T>(change: FieldChange, value: T) -> Option<Option<T>> {
+        match change {
+            FieldChange::Absent => None,
+            FieldChange::Null => Some(None),
+            FieldChange::Set => Some(Some(value)),
+        }
+    }
+
+    /// Every combination of the thirteen fields: title, start date and
+    /// importance are absent or set, the nullable fields absent, null or set.
+    fn combinations() -> Vec<[FieldChange; 13]> {
+        let mut all = vec![[FieldChange::Absent; 13]];
+        for field in 0..13 {
+            let options: &[FieldChange] = if field == 0 || field == 2 || field == 8 {
+                &[FieldChange::Absent, FieldChange::Set]
+            } else {
+                &[FieldChange::Absent, FieldChange::Null, FieldChange::Set]
+            };
+            all = all
+                .into_iter()
+                .flat_map(|combination| {
+                    options.iter().map(move |&option| {
+                        let mut combination = combination;
+                        combination[field] = option;
+                        combination
+                    })
+                })
+                .collect();
+        }
+        all
+    }
+
+    #[test]
+    fn update_query_numbers_placeholders_for_every_field_combination() {
+        const COLUMNS: [&str; 13] = [
+            "title",
+            "description",
+            "start_date",
+            "end_date",
+            "location",
+            "image_url",
+            "image_alt",
+            "category",
+            "importance",
+            "color",
+            "icon",
+            "start_date_min",
+            "start_date_max",
+        ];
+        let date = chrono::NaiveDate::from_ymd_opt(2024, 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap();
+        let text = |value: &str| value.to_string();
+
+        let combinations = combinations();
+        assert_eq!(combinations.len(), 2 * 2 * 3 * 3 * 3 * 3 * 3 * 3 * 2 * 3 * 3 * 3 * 3);
+        for combination in combinations {
+            let changes = EventPatch {
+                title: matches!(combination[0], FieldChange::Set).then(|| Some(text("Title"))),
+                description: nullable(combination[1], text("Description")),
+                start_date: matches!(combination[2], FieldChange::Set).then_some(Some(date)),
+                end_date: nullable(combination[3], date),
+                location: nullable(combination[4], text("Location")),
+                image_url: nullable(combination[5], text("/image.png")),
+                image_alt: nullable(combination[6], text("A painting")),
+                category: nullable(combination[7], text("Category")),
+                importance: matches!(combination[8], FieldChange::Set).then_some(5),
+                color: nullable(combination[9], text("#3b82f6")),
+                icon: nullable(combination[10], text("crown")),
+                start_date_min: nullable(combination[11], date),
+                start_date_max: nullable(combination[12], date),
+                ..EventPatch::default()
+            };
+
+            let mut expected = String::from("UPDATE events SET updated_at = NOW()");
+            let mut placeholder = 0;
+            for (column, change) in COLUMNS.iter().zip(combination) {
+                if !matches!(change, FieldChange::Absent) {
+                    placeholder += 1;
+                    expected += &format!(", {} = ${}", column, placeholder);
+                }
+            }
+            expected += &format!(" WHERE id = ${} RETURNING *", placeholder + 1);
+
+            assert_eq!(update_query(Uuid::nil(), &changes).sql(), expected);
+        }
+    }
+
+    #[test]
+    fn update_distinguishes_absent_from_null() {
+        let changes: EventPatch = serde_json::from_str(r#"{"end_date": null, "location": "Paris"}"#).unwrap();
+        assert_eq!(changes.end_date, Some(None));
+        assert_eq!(changes.location, Some(Some("Paris".to_string())));
+        assert_eq!(changes.description, None);
+        assert_eq!(changes.category, None);
+    }
+
+    #[test]
+    fn update_rejects_null_required_fields() {
+        let changes: EventPatch = serde_json::from_str(r#"{"title": null, "start_date": null}"#).unwrap();
+        let errors = changes.validate_patch().unwrap_err();
+        assert!(errors.field_errors().contains_key("title"));
+        assert!(errors.field_errors().contains_key("start_date"));
+
+        let changes: EventPatch = serde_json::from_str(r#"{"end_date": null}"#).unwrap();
+        assert!(changes.validate_patch().is_ok());
+    }
+
+    #[test]
+    fn update_bounds_are_checked_against_the_stored_event() {
+        let stored = event("Uncertain", 10, "published");
+        let at = |day: u32| chrono::NaiveDate::from_ymd_opt(2024, 1, day).unwrap().and_hms_opt(0, 0, 0).unwrap();
+
+        let after_start = EventPatch {
+            start_date_min: Some(Some(at(20))),
+            ..EventPatch::default()
+        };
+        assert!(crate::patch_bounds(&after_start, &stored).is_err());
+
+        let before_start = EventPatch {
+            start_date_min: Some(Some(at(5))),
+            ..EventPatch::default()
+        };
+        assert!(crate::patch_bounds(&before_start, &stored).is_ok());
+
+        let mut bounded = stored.clone();
+        bounded.start_date_max = Some(at(15));
+        let past_max = EventPatch {
+            start_date: Some(Some(at(20))),
+            ..EventPatch::default()
+        };
+        assert!(crate::patch_bounds(&past_max, &bounded).is_err());
+        let cleared = EventPatch {
+            start_date: Some(Some(at(20))),
+            start_date_max: Some(None),
+            ..EventPatch::default()
+        };
+        assert!(crate::patch_bounds(&cleared, &bounded).is_ok());
+    }
+
+    #[tokio::test]
+    async fn null_clears_a_field() {
+        let mut located = event("Located", 1, "published");
+        located.location = Some("Paris".to_string());
+        located.category = Some("Politics".to_string());
+        let id = located.id;
+        for store in stores(vec![located]).await {
+
+            let changes = EventPatch {
+                location: Some(None),
+                ..EventPatch::default()
+            };
+            let event = store.update(id, &changes).await.unwrap().unwrap();
+            assert_eq!(event.location, None);
+            assert_eq!(event.category.as_deref(), Some("Politics"));
+        }
+    }
+
+    #[tokio::test]
+    async fn replacing_clears_omitted_fields() {
+        let mut located = event("Located", 1, "published");
+        located.location = Some("Paris".to_string());
+        let id = located.id;
+        for store in stores(vec![located]).await {
+
+            let replacement: crate::EventUpdate =
+                serde_json::from_str(r#"{"title": "Moved", "start_date": "2024-02-01T00:00:00"}"#).unwrap();
+            let event = store.update(id, &replacement.into()).await.unwrap().unwrap();
+            assert_eq!(event.title, "Moved");
+            assert_eq!(event.location, None);
+        }
+    }
+
+    #[tokio::test]
+    async fn atomic_batch_rolls_back_on_failure() {
+        let kept = event("Kept", 1, "published");
+        let id = kept.id;
+        for store in stores(vec![kept]).await {
+
+            let outcomes = store
+                .batch(
+                    vec![
+                        Change::Create(event("New", 2, "published")),
+                        Change::Delete(Uuid::new_v4()),
+                        Change::Delete(id),
+                    ],
+                    true,
+                )
+                .await
+                .unwrap();
+            assert!(matches!(
+                outcomes[..],
+                [Outcome::RolledBack, Outcome::NotFound, Outcome::RolledBack]
+            ));
+            let page = store.list(&published()).await.unwrap();
+            let titles: Vec<_> = page.events.iter().map(|event| event.title.as_str()).collect();
+            assert_eq!(titles, ["Kept"]);
+        }
+    }
+
+    #[tokio::test]
+    async fn partial_batch_keeps_applied_changes() {
+        let removed = event("Removed", 1, "published");
+        let id = removed.id;
+        for store in stores(vec![removed]).await {
+
+            let outcomes = store
+                .batch(
+                    vec![
+                        Change::Update(Uuid::new_v4(), EventPatch::default()),
+                        Change::Create(event("New", 2, "published")),
+                        Change::Delete(id),
+                    ],
+                    false,
+                )
+                .await
+                .unwrap();
+            assert!(matches!(
+                outcomes[..],
+                [Outcome::NotFound, Outcome::Saved(_), Outcome::Deleted]
+            ));
+            let page = store.list(&published()).await.unwrap();
+            let titles: Vec<_> = page.events.iter().map(|event| event.title.as_str()).collect();
+            assert_eq!(titles, ["New"]);
+        }
+    }
+
+    #[tokio::test]
+    async fn publishing_clears_the_schedule() {
+        let draft = event("Draft", 1, "draft");
+        let id = draft.id;
+        for store in stores(vec![draft]).await {
+
+            let at = chrono::Utc::now().naive_utc();
+            assert!(store.schedule(id, Some(at)).await.unwrap().is_some());
+            let event = store.set_status(id, "published").await.unwrap().unwrap();
+            assert_eq!(event.publish_at, None);
+            assert!(store.schedule(id, Some(at)).await.unwrap().is_none());
+        }
+    }
+
+    #[tokio::test]
+    async fn timeline_listings_filter_on_custom_fields() {
+        use crate::custom_fields::Bound;
+
+        let timeline = Uuid::new_v4();
+        let on_timeline = |title: &str, day: u32, status: &str, values: serde_json::Value| Event {
+            timeline_id: Some(timeline),
+            custom_fields: values,
+            ..event(title, day, status)
+        };
+        for store in stores(vec![
+            on_timeline("Hastings", 3, "published", serde_json::json!({ "casualties": 10000, "dynasty": "Norman" })),
+            on_timeline("Bosworth", 2, "published", serde_json::json!({ "casualties": 1000, "dynasty": "Tudor" })),
+            on_timeline("Draft", 1, "draft", serde_json::json!({ "casualties": 50000 })),
+            event("Elsewhere", 1, "published"),
+        ])
+        .await
+        {
+            let titles = |events: Vec<Event>| events.into_iter().map(|event| event.title).collect::<Vec<_>>();
+            let everything = TimelineFilter { unpublished: true, min_importance: 1, fields: Vec::new() };
+            let listed = store.on_timeline(timeline, &everything).await.unwrap();
+            assert_eq!(titles(listed), ["Draft", "Bosworth", "Hastings"]);
+
+            let costly = TimelineFilter {
+                unpublished: false,
+                min_importance: 1,
+                fields: vec![FieldFilter::Number("casualties".to_string(), Bound::Min, 5000.0)],
+            };
+            assert_eq!(titles(store.on_timeline(timeline, &costly).await.unwrap()), ["Hastings"]);
+
+            let tudor = TimelineFilter {
+                fields: vec![FieldFilter::Text("dynasty".to_string(), "tudor".to_string())],
+                ..everything
+            };
+            assert_eq!(titles(store.on_timeline(timeline, &tudor).await.unwrap()), ["Bosworth"]);
+        }
+    }
+
+    #[tokio::test]
+    async fn moves_all_of_the_events_or_none() {
+        let (first, second) = (event("First", 1, "published"), event("Second", 2, "published"));
+        let ids = [first.id, second.id];
+        for store in stores(vec![first.clone(), second.clone()]).await {
+            let timeline = Uuid::new_v4();
+
+            assert!(store.move_to(&[ids[0], Uuid::new_v4()], timeline).await.unwrap().is_none());
+            assert_eq!(store.find(ids[0]).await.unwrap().unwrap().timeline_id, None);
+            let moved = store.move_to(&ids, timeline).await.unwrap().unwrap();
+            assert_eq!(moved.len(), 2);
+            assert!(moved.iter().all(|event| event.timeline_id == Some(timeline)));
+        }
+    }
+
+    #[tokio::test]
+    async fn finds_and_copies_duplicates() {
+        let original = Event {
+            likes: 4,
+            custom_fields: serde_json::json!({ "dynasty": "Tudor" }),
+            ..event("Battle of Bosworth", 10, "published")
+        };
+        for store in stores(vec![
+            original.clone(),
+            event("Battle of Bosworth", 30, "published"),
+            event("Coronation", 11, "published"),
+        ])
+        .await
+        {
+            let near = original.start_date + chrono::Duration::days(2);
+            let similar = store.similar("battle of bosworth", near, None).await.unwrap();
+            assert_eq!(similar.iter().map(|event| event.id).collect::<Vec<_>>(), [original.id]);
+            assert!(store.similar("Battle of Bosworth", near, Some(original.id)).await.unwrap().is_empty());
+
+            let timeline = Uuid::new_v4();
+            let copy = store.duplicate(original.id, Some(timeline)).await.unwrap().unwrap();
+            assert_ne!(copy.id, original.id);
+            assert_eq!((copy.status.as_str(), copy.likes, copy.timeline_id), ("draft", 0, Some(timeline)));
+            assert_eq!(copy.custom_fields, serde_json::json!({}));
+            let copy = store.duplicate(original.id, None).await.unwrap().unwrap();
+            assert_eq!(copy.custom_fields, original.custom_fields);
+            assert!(store.duplicate(Uuid::new_v4(), None).await.unwrap().is_none());
+        }
+    }
+
+    #[tokio::test]
+    async fn merging_picks_fields_and_removes_the_other() {
+        let kept = Event {
+            location: Some("Hastings".to_string()),
+            importance: 2,
+            custom_fields: serde_json::json!({ "dynasty": "Norman" }),
+            ..event("Battle of Hastings", 14, "published")
+        };
+        let other = Event {
+            description: Some("Harold falls.".to_string()),
+            location: Some("Senlac Hill".to_string()),
+            importance: 5,
+            custom_fields: serde_json::json!({ "casualties": 10000, "dynasty": "Saxon" }),
+            ..event("The Battle of Hastings", 15, "published")
+        };
+        for store in stores(vec![kept.clone(), other.clone()]).await {
+            let choices = MergeChoices { title: Some(Side::Other), ..MergeChoices::default() };
+
+            let merged = store.merge(kept.id, other.id, &choices).await.unwrap().unwrap();
+            assert_eq!(merged.title, "The Battle of Hastings");
+            assert_eq!(merged.start_date, kept.start_date);
+            assert_eq!(merged.description.as_deref(), Some("Harold falls."));
+            assert_eq!(merged.location.as_deref(), Some("Hastings"));
+            assert_eq!(merged.importance, 5);
+            assert_eq!(merged.custom_fields, serde_json::json!({ "casualties": 10000, "dynasty": "Norman" }));
+            assert!(store.find(other.id).await.unwrap().is_none());
+            assert!(store.merge(kept.id, other.id, &choices).await.unwrap().is_none());
+        }
+    }
+
+    /// What hangs off events lives only in Postgres, so this runs against a
+    /// scratch database: `TEST_DATABASE_URL=postgres://... cargo test -- --ignored`.
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn merging_keeps_images_people_and_likes() {
+        let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL must point at a scratch database");
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        let store = PgEvents::new(Db::single(pool.clone()));
+        let kept = store.create(event("Battle of Hastings", 14, "published")).await.unwrap();
+        let other = store.create(event("The Battle of Hastings", 15, "published")).await.unwrap();
+
+        let (both, either) = (Uuid::new_v4(), Uuid::new_v4());
+        for user in [both, either] {
+            sqlx::query("INSERT INTO users (id, username, email, password_hash) VALUES ($1, $2, $3, '')")
+                .bind(user)
+                .bind(user.to_string())
+                .bind(format!("{}@example.com", user))
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+        for (event, user) in [(kept.id, both), (other.id, both), (other.id, either)] {
+            sqlx::query("INSERT INTO event_likes (event_id, user_id) VALUES ($1, $2)")
+                .bind(event)
+                .bind(user)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+        sqlx::query("UPDATE events SET likes = CASE WHEN id = $1 THEN 1 ELSE 2 END WHERE id IN ($1, $2)")
+            .bind(kept.id)
+            .bind(other.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        for (event, url) in [(kept.id, "/uploads/kept.jpg"), (other.id, "/uploads/other.jpg")] {
+            sqlx::query("INSERT INTO event_images (event_id, url, position) VALUES ($1, $2, 0)")
+                .bind(event)
+                .bind(url)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+        let harold = sqlx::query_scalar::<_, Uuid>("INSERT INTO people (name) VALUES ('Harold Godwinson') RETURNING id")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO event_people (event_id, person_id) VALUES ($1, $2)")
+            .bind(other.id)
+            .bind(harold)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let merged = store.merge(kept.id, other.id, &MergeChoices::default()).await.unwrap().unwrap();
+        assert_eq!(merged.likes, 2);
+        let gallery =
+            sqlx::query_scalar::<_, String>("SELECT url FROM event_images WHERE event_id = $1 ORDER BY position")
+                .bind(kept.id)
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(gallery, ["/uploads/kept.jpg", "/uploads/other.jpg"]);
+        let people = sqlx::query_scalar::<_, Uuid>("SELECT person_id FROM event_people WHERE event_id = $1")
+            .bind(kept.id)
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(people, [harold]);
+        assert!(store.find(other.id).await.unwrap().is_none());
+    }
+}
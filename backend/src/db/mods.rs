@@ -1,8 +1,177 @@
-use sqlx::PgPool;
-use std::env;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+use std::time::Duration;
 
-pub async fn init_db() -> PgPool {
-    let database_url = env::var("DATABASE_URL")
-        .expect("DATABASE_URL must be set");
-    PgPool::connect(&database_url).await.unwrap()
+use axum::{
+    async_trait,
+    extract::{FromRef, FromRequestParts},
+    http::request::Parts,
+};
+use serde::Serialize;
+use sqlx::{postgres::PgPoolOptions, PgPool};
+
+use crate::config::Config;
+
+pub mod events;
+#[cfg(test)]
+pub mod sqlite;
+
+/// The primary database plus any read replicas.
+///
+/// Writes, and reads that must see a write made in the same request, go to
+/// `writer`. Other reads use `reader`, which rotates through the replicas
+/// and falls back to the primary when none are configured.
+#[derive(Clone)]
+pub struct Db {
+    writer: PgPool,
+    readers: Arc<[PgPool]>,
+    next: Arc<AtomicUsize>,
+}
+
+impl Db {
+    /// A primary without replicas.
+    pub fn single(writer: PgPool) -> Self {
+        Self {
+            writer,
+            readers: Arc::new([]),
+            next: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    pub fn writer(&self) -> &PgPool {
+        &self.writer
+    }
+
+    pub fn reader(&self) -> &PgPool {
+        if self.readers.is_empty() {
+            return &self.writer;
+        }
+        let index = self.next.fetch_add(1, Ordering::Relaxed) % self.readers.len();
+        &self.readers[index]
+    }
+
+    pub fn replicas(&self) -> &[PgPool] {
+        &self.readers
+    }
+}
+
+/// Extracts a pool for read-only handlers: `Reader(pool): Reader`.
+/// `State<PgPool>` remains the primary.
+pub struct Reader(pub PgPool);
+
+#[async_trait]
+impl<S> FromRequestParts<S> for Reader
+where
+    Db: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(_parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Reader(Db::from_ref(state).reader().clone()))
+    }
+}
+
+/// First wait between connection attempts; doubles after each failure.
+const INITIAL_BACKOFF: Duration = Duration::from_millis(500);
+/// Longest wait between connection attempts.
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+fn pool_options(config: &Config) -> PgPoolOptions {
+    PgPoolOptions::new()
+        .max_connections(config.db_max_connections)
+        .min_connections(config.db_min_connections)
+        .acquire_timeout(config.db_acquire_timeout)
+}
+
+/// Connects to `DATABASE_URL` and every `DATABASE_REPLICA_URLS` entry.
+pub async fn connect(config: &Config) -> Db {
+    let writer = connect_pool(config, "primary", &config.database_url).await;
+    let mut readers = Vec::new();
+    for url in &config.database_replica_urls {
+        readers.push(connect_pool(config, "replica", url).await);
+    }
+
+    Db {
+        writer,
+        readers: readers.into(),
+        next: Arc::new(AtomicUsize::new(0)),
+    }
+}
+
+/// Connects one pool, retrying with exponential backoff so the server can
+/// start before Postgres is ready (e.g. under docker compose). Panics once
+/// `DB_CONNECT_ATTEMPTS` attempts have failed.
+async fn connect_pool(config: &Config, role: &str, url: &str) -> PgPool {
+    let mut delay = INITIAL_BACKOFF;
+    let mut attempt = 0;
+    loop {
+        attempt += 1;
+        match pool_options(config).connect(url).await {
+            Ok(pool) => {
+                tracing::info!(
+                    role,
+                    attempt,
+                    max_connections = config.db_max_connections,
+                    min_connections = config.db_min_connections,
+                    "connected to database"
+                );
+                return pool;
+            }
+            Err(err) if attempt < config.db_connect_attempts => {
+                tracing::warn!(
+                    role,
+                    attempt,
+                    max_attempts = config.db_connect_attempts,
+                    retry_in_ms = delay.as_millis() as u64,
+                    error = %err,
+                    "database not reachable; retrying"
+                );
+                tokio::time::sleep(delay).await;
+                delay = (delay * 2).min(MAX_BACKOFF);
+            }
+            Err(err) => panic!("could not connect to the {} database after {} attempts: {}", role, attempt, err),
+        }
+    }
+}
+
+/// Connection pool usage, for the admin system info.
+#[derive(Serialize)]
+pub struct PoolStats {
+    pub size: u32,
+    pub idle: usize,
+    pub in_use: usize,
+    pub max_connections: u32,
+    pub min_connections: u32,
+    pub acquire_timeout_ms: u64,
+}
+
+pub fn stats(pool: &PgPool) -> PoolStats {
+    let size = pool.size();
+    let idle = pool.num_idle();
+    let options = pool.options();
+    PoolStats {
+        size,
+        idle,
+        in_use: (size as usize).saturating_sub(idle),
+        max_connections: options.get_max_connections(),
+        min_connections: options.get_min_connections(),
+        acquire_timeout_ms: options.get_acquire_timeout().as_millis() as u64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    /// The SQLite migrations run whenever a test opens an `SqliteEvents`;
+    /// this runs the Postgres ones, against a scratch database:
+    /// `TEST_DATABASE_URL=postgres://... cargo test -- --ignored`.
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn postgres_migrations_apply() {
+        let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL must point at a scratch database");
+        let pool = sqlx::PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+    }
 }
@@ -0,0 +1,560 @@
+//! An event store in SQLite, for tests: the repository tests run against
+//! it as well as `MemoryEvents`, so the shared filter SQL is exercised
+//! without a Postgres server to hand. `sqlite::memory:` keeps everything in
+//! memory. It migrates itself from `migrations/sqlite`, which holds the
+//! events table and the columns of the tables its filters read.
+//!
+//! The server always keeps events in Postgres: tags, comments, likes and
+//! the other tables hanging off events live there and refer to them. This
+//! store's own `timelines` table stays empty unless a test fills it; events
+//! on a timeline are then hidden from public listings and only admins may
+//! edit them. Dates are stored as text, so those before 1 CE sort among
+//! themselves by their text rather than their date. Published drafts queue
+//! no webhook, there being no outbox here.
+
+use std::str::FromStr;
+
+use axum::async_trait;
+use chrono::NaiveDateTime;
+use sqlx::{
+    query::QueryAs,
+    sqlite::{SqliteArguments, SqliteConnectOptions, SqlitePoolOptions},
+    Acquire, QueryBuilder, Sqlite, SqliteConnection, SqlitePool,
+};
+use uuid::Uuid;
+
+use super::events::{
+    push_changes, push_filter, roll_back, CategoryCount, Change, DecadeCount, EventFilter, EventPage, EventRepository,
+    Facets, MergeChoices, Outcome, Side, TagCount, TimelineFilter, DATE_TOLERANCE_DAYS, MAX_CANDIDATES,
+    MAX_TAG_FACETS, UNCATEGORIZED,
+};
+use crate::{custom_fields::FieldFilter, timelines, Event, EventPatch};
+
+/// The year of `e.start_date`, read from its text since SQLite's date
+/// functions stop at year 0.
+const YEAR: &str = "CAST(substr(e.start_date, 1, instr(substr(e.start_date, 2), '-')) AS INTEGER)";
+
+pub struct SqliteEvents {
+    pool: SqlitePool,
+}
+
+impl SqliteEvents {
+    /// Opens the database at `url`, creating it if need be, and brings its
+    /// schema up to date.
+    pub async fn connect(url: &str) -> Result<Self, sqlx::Error> {
+        let options = SqliteConnectOptions::from_str(url)?.create_if_missing(true);
+        // Each connection to `sqlite::memory:` opens a database of its own,
+        // so the pool holds one connection and never closes it. SQLite
+        // writes one transaction at a time anyway.
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .idle_timeout(None)
+            .max_lifetime(None)
+            .connect_with(options)
+            .await?;
+        sqlx::migrate!("./migrations/sqlite").run(&pool).await?;
+        Ok(Self { pool })
+    }
+}
+
+fn now() -> NaiveDateTime {
+    chrono::Utc::now().naive_utc()
+}
+
+fn insert_query(event: Event) -> QueryAs<'static, Sqlite, Event, SqliteArguments<'static>> {
+    sqlx::query_as::<_, Event>(
+        r#"
+        INSERT INTO events (id, title, description, start_date, end_date, location, image_url, image_alt, category, importance, color, icon, created_at, updated_at, timeline_id, status, publish_at, start_date_min, start_date_max, custom_fields, views, likes, featured)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21, $22, $23)
+        RETURNING *
+        "#,
+    )
+    .bind(event.id)
+    .bind(event.title)
+    .bind(event.description)
+    .bind(event.start_date)
+    .bind(event.end_date)
+    .bind(event.location)
+    .bind(event.image_url)
+    .bind(event.image_alt)
+    .bind(event.category)
+    .bind(event.importance)
+    .bind(event.color)
+    .bind(event.icon)
+    .bind(event.created_at)
+    .bind(event.updated_at)
+    .bind(event.timeline_id)
+    .bind(event.status)
+    .bind(event.publish_at)
+    .bind(event.start_date_min)
+    .bind(event.start_date_max)
+    .bind(event.custom_fields)
+    .bind(event.views)
+    .bind(event.likes)
+    .bind(event.featured)
+}
+
+/// `UPDATE events ... RETURNING *` setting the fields present in `changes`.
+fn update_query(id: Uuid, changes: &EventPatch) -> QueryBuilder<'static, Sqlite> {
+    let mut query = QueryBuilder::new("UPDATE events SET updated_at = ");
+    query.push_bind(now());
+    push_changes(&mut query, changes);
+    query.push(" WHERE id = ").push_bind(id).push(" RETURNING *");
+    query
+}
+
+/// Appends ` AND ...` for each custom field filter, as
+/// `db::events::push_field_filters` does for Postgres. Field names are
+/// plain identifiers, so they can extend a JSON path.
+fn push_field_filters(builder: &mut QueryBuilder<'_, Sqlite>, filters: &[FieldFilter]) {
+    for filter in filters {
+        match filter {
+            FieldFilter::Text(name, text) => {
+                builder.push(" AND lower(json_extract(custom_fields, '$.' || ").push_bind(name.clone());
+                builder.push(")) = lower(").push_bind(text.clone()).push(")");
+            }
+            // `json_extract` reads JSON booleans as 1 and 0.
+            FieldFilter::Boolean(name, flag) => {
+                builder.push(" AND json_extract(custom_fields, '$.' || ").push_bind(name.clone());
+                builder.push(") = ").push_bind(*flag);
+            }
+            FieldFilter::Number(name, bound, number) => {
+                builder.push(" AND CAST(json_extract(custom_fields, '$.' || ").push_bind(name.clone());
+                builder.push(") AS REAL)").push(bound.operator()).push_bind(*number);
+            }
+            FieldFilter::Date(name, bound, date) => {
+                builder.push(" AND json_extract(custom_fields, '$.' || ").push_bind(name.clone());
+                builder.push(")").push(bound.operator()).push_bind(*date);
+            }
+        }
+    }
+}
+
+/// Runs one change of a batch on `conn`.
+async fn apply(conn: &mut SqliteConnection, change: Change) -> Result<Outcome, sqlx::Error> {
+    let outcome = match change {
+        Change::Create(event) => Outcome::Saved(Box::new(insert_query(event).fetch_one(&mut *conn).await?)),
+        Change::Update(id, changes) => {
+            let mut query = update_query(id, &changes);
+            let event = query.build_query_as::<Event>().fetch_optional(&mut *conn).await?;
+            event.map_or(Outcome::NotFound, |event| Outcome::Saved(Box::new(event)))
+        }
+        Change::Delete(id) => {
+            let result = sqlx::query("DELETE FROM events WHERE id = $1")
+                .bind(id)
+                .execute(&mut *conn)
+                .await?;
+            if result.rows_affected() > 0 {
+                Outcome::Deleted
+            } else {
+                Outcome::NotFound
+            }
+        }
+    };
+    Ok(outcome)
+}
+
+#[async_trait]
+impl EventRepository for SqliteEvents {
+    async fn list(&self, filter: &EventFilter) -> Result<EventPage, sqlx::Error> {
+        let mut query = QueryBuilder::new("SELECT e.* FROM events e");
+        push_filter(&mut query, filter);
+        query
+            .push(filter.sort.order_by())
+            .push(" LIMIT ")
+            .push_bind(filter.limit)
+            .push(" OFFSET ")
+            .push_bind(filter.offset);
+        let events = query.build_query_as::<Event>().fetch_all(&self.pool).await?;
+
+        let mut count = QueryBuilder::new("SELECT COUNT(*) FROM events e");
+        push_filter(&mut count, filter);
+        let total = count.build_query_scalar::<i64>().fetch_one(&self.pool).await?;
+
+        Ok(EventPage { events, total })
+    }
+
+    async fn category_counts(&self, filter: &EventFilter) -> Result<Vec<CategoryCount>, sqlx::Error> {
+        let filter = EventFilter { categories: Vec::new(), ..filter.clone() };
+        let mut query = QueryBuilder::new("SELECT COALESCE(e.category, ");
+        query.push_bind(UNCATEGORIZED).push(") AS category, COUNT(*) AS count FROM events e");
+        push_filter(&mut query, &filter);
+        query.push(" GROUP BY 1 ORDER BY 2 DESC, 1");
+        query.build_query_as::<CategoryCount>().fetch_all(&self.pool).await
+    }
+
+    async fn facets(&self, filter: &EventFilter) -> Result<Facets, sqlx::Error> {
+        // `%` truncates towards zero, so BCE years need the extra `+ 10`
+        // to round down.
+        let mut decades = QueryBuilder::new(format!(
+            "SELECT {year} - (({year} % 10) + 10) % 10 AS decade, COUNT(*) AS count FROM events e",
+            year = YEAR
+        ));
+        push_filter(&mut decades, filter);
+        decades.push(" GROUP BY 1 ORDER BY 1");
+        let decades = decades.build_query_as::<DecadeCount>().fetch_all(&self.pool).await?;
+
+        let mut tags = QueryBuilder::new(
+            "SELECT g.name AS tag, COUNT(*) AS count FROM events e \
+             JOIN event_tags et ON et.event_id = e.id JOIN tags g ON g.id = et.tag_id",
+        );
+        push_filter(&mut tags, filter);
+        tags.push(" GROUP BY 1 ORDER BY 2 DESC, 1 LIMIT ").push_bind(MAX_TAG_FACETS);
+        let tags = tags.build_query_as::<TagCount>().fetch_all(&self.pool).await?;
+
+        Ok(Facets { categories: self.category_counts(filter).await?, decades, tags })
+    }
+
+    async fn find(&self, id: Uuid) -> Result<Option<Event>, sqlx::Error> {
+        sqlx::query_as::<_, Event>("SELECT * FROM events WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await
+    }
+
+    async fn create(&self, event: Event) -> Result<Event, sqlx::Error> {
+        insert_query(event).fetch_one(&self.pool).await
+    }
+
+    async fn update(&self, id: Uuid, changes: &EventPatch) -> Result<Option<Event>, sqlx::Error> {
+        update_query(id, changes)
+            .build_query_as::<Event>()
+            .fetch_optional(&self.pool)
+            .await
+    }
+
+    async fn delete(&self, id: Uuid) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM events WHERE id = $1")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn set_status(&self, id: Uuid, status: &str) -> Result<Option<Event>, sqlx::Error> {
+        sqlx::query_as::<_, Event>(
+            r#"
+            UPDATE events
+            SET status = $2,
+                publish_at = CASE WHEN $2 = 'published' THEN NULL ELSE publish_at END,
+                updated_at = $3
+            WHERE id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .bind(status)
+        .bind(now())
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    async fn schedule(&self, id: Uuid, publish_at: Option<NaiveDateTime>) -> Result<Option<Event>, sqlx::Error> {
+        sqlx::query_as::<_, Event>(
+            "UPDATE events SET publish_at = $2, updated_at = $3 WHERE id = $1 AND status = 'draft' RETURNING *",
+        )
+        .bind(id)
+        .bind(publish_at)
+        .bind(now())
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    async fn scheduled(&self, user: Uuid, admin: bool) -> Result<Vec<Event>, sqlx::Error> {
+        let mut query =
+            QueryBuilder::new("SELECT e.* FROM events e WHERE e.status = 'draft' AND e.publish_at IS NOT NULL AND ");
+        timelines::push_editable_event(&mut query, user, admin);
+        query.push(" ORDER BY e.publish_at");
+        query.build_query_as::<Event>().fetch_all(&self.pool).await
+    }
+
+    async fn publish_due(&self) -> Result<Vec<Event>, sqlx::Error> {
+        let now = now();
+        sqlx::query_as::<_, Event>(
+            r#"
+            UPDATE events SET status = 'published', updated_at = $1
+            WHERE status = 'draft' AND publish_at <= $1
+            RETURNING *
+            "#,
+        )
+        .bind(now)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// As in Postgres: one transaction with a savepoint per change.
+    async fn batch(&self, changes: Vec<Change>, atomic: bool) -> Result<Vec<Outcome>, sqlx::Error> {
+        let mut transaction = self.pool.begin().await?;
+        let mut outcomes = Vec::with_capacity(changes.len());
+        let mut failed = false;
+        for change in changes {
+            if failed {
+                outcomes.push(Outcome::RolledBack);
+                continue;
+            }
+            let mut savepoint = transaction.begin().await?;
+            let outcome = apply(&mut savepoint, change).await.unwrap_or_else(Outcome::Failed);
+            if outcome.applied() {
+                savepoint.commit().await?;
+            } else {
+                savepoint.rollback().await?;
+                failed = atomic;
+            }
+            outcomes.push(outcome);
+        }
+
+        if failed {
+            transaction.rollback().await?;
+            roll_back(&mut outcomes);
+        } else {
+            transaction.commit().await?;
+        }
+        Ok(outcomes)
+    }
+
+    async fn on_timeline(&self, timeline_id: Uuid, filter: &TimelineFilter) -> Result<Vec<Event>, sqlx::Error> {
+        let mut query = QueryBuilder::<Sqlite>::new("SELECT * FROM events WHERE timeline_id = ");
+        query.push_bind(timeline_id);
+        query.push(" AND (").push_bind(filter.unpublished).push(" OR status = 'published')");
+        query.push(" AND importance >= ").push_bind(filter.min_importance);
+        push_field_filters(&mut query, &filter.fields);
+        query.push(" ORDER BY start_date");
+        query.build_query_as::<Event>().fetch_all(&self.pool).await
+    }
+
+    async fn move_to(&self, ids: &[Uuid], timeline_id: Uuid) -> Result<Option<Vec<Event>>, sqlx::Error> {
+        let mut transaction = self.pool.begin().await?;
+        let mut query = QueryBuilder::<Sqlite>::new("UPDATE events SET timeline_id = ");
+        query.push_bind(timeline_id).push(", updated_at = ").push_bind(now()).push(" WHERE id IN (");
+        let mut separated = query.separated(", ");
+        for id in ids {
+            separated.push_bind(*id);
+        }
+        query.push(") RETURNING *");
+        let moved = query.build_query_as::<Event>().fetch_all(&mut *transaction).await?;
+        // Dropping the transaction undoes the moves.
+        if moved.len() < ids.len() {
+            return Ok(None);
+        }
+        transaction.commit().await?;
+        Ok(Some(moved))
+    }
+
+    /// Without `pg_trgm`, titles must match but for case.
+    async fn similar(
+        &self,
+        title: &str,
+        start_date: NaiveDateTime,
+        exclude: Option<Uuid>,
+    ) -> Result<Vec<Event>, sqlx::Error> {
+        let query = format!(
+            r#"
+            SELECT * FROM events e
+            WHERE lower(title) = lower($1)
+              AND abs(julianday(start_date) - julianday($2)) <= $3
+              AND ($4 IS NULL OR id <> $4)
+              AND {}
+            ORDER BY abs(julianday(start_date) - julianday($2))
+            LIMIT $5
+            "#,
+            timelines::PUBLIC_EVENT
+        );
+        sqlx::query_as::<_, Event>(&query)
+            .bind(title)
+            .bind(start_date)
+            .bind(DATE_TOLERANCE_DAYS)
+            .bind(exclude)
+            .bind(MAX_CANDIDATES)
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    /// Copies the tags, the only table hanging off events kept here.
+    async fn duplicate(&self, id: Uuid, timeline_id: Option<Uuid>) -> Result<Option<Event>, sqlx::Error> {
+        let mut transaction = self.pool.begin().await?;
+        let copy = sqlx::query_as::<_, Event>(
+            r#"
+            INSERT INTO events (id, title, description, start_date, end_date, start_date_min, start_date_max,
+                location, image_url, image_alt, category, importance, color, icon, created_at, updated_at,
+                timeline_id, status, custom_fields)
+            SELECT $2, title, description, start_date, end_date, start_date_min, start_date_max,
+                location, image_url, image_alt, category, importance, color, icon, $4, $4, $3, 'draft',
+                CASE WHEN timeline_id IS $3 THEN custom_fields ELSE '{}' END
+            FROM events WHERE id = $1
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .bind(Uuid::new_v4())
+        .bind(timeline_id)
+        .bind(now())
+        .fetch_optional(&mut *transaction)
+        .await?;
+        let Some(copy) = copy else {
+            return Ok(None);
+        };
+        sqlx::query("INSERT INTO event_tags (event_id, tag_id) SELECT $1, tag_id FROM event_tags WHERE event_id = $2")
+            .bind(copy.id)
+            .bind(id)
+            .execute(&mut *transaction)
+            .await?;
+        transaction.commit().await?;
+        Ok(Some(copy))
+    }
+
+    /// The Postgres merge in SQLite's dialect; tags are the only table
+    /// hanging off events to move.
+    async fn merge(&self, id: Uuid, other: Uuid, choices: &MergeChoices) -> Result<Option<Event>, sqlx::Error> {
+        let side = |choice: Option<Side>| choice.map(Side::as_str);
+        let mut transaction = self.pool.begin().await?;
+        let merged = sqlx::query_as::<_, Event>(
+            r#"
+            UPDATE events AS e SET
+                title = CASE $3 WHEN 'other' THEN o.title ELSE e.title END,
+                description = CASE $4 WHEN 'other' THEN o.description WHEN 'keep' THEN e.description
+                    ELSE COALESCE(e.description, o.description) END,
+                start_date = CASE $5 WHEN 'other' THEN o.start_date ELSE e.start_date END,
+                start_date_min = CASE $5 WHEN 'other' THEN o.start_date_min ELSE e.start_date_min END,
+                start_date_max = CASE $5 WHEN 'other' THEN o.start_date_max ELSE e.start_date_max END,
+                end_date = CASE $6 WHEN 'other' THEN o.end_date WHEN 'keep' THEN e.end_date
+                    ELSE COALESCE(e.end_date, o.end_date) END,
+                location = CASE $7 WHEN 'other' THEN o.location WHEN 'keep' THEN e.location
+                    ELSE COALESCE(e.location, o.location) END,
+                image_url = CASE $8 WHEN 'other' THEN o.image_url WHEN 'keep' THEN e.image_url
+                    ELSE COALESCE(e.image_url, o.image_url) END,
+                image_alt = CASE $8 WHEN 'other' THEN o.image_alt WHEN 'keep' THEN e.image_alt
+                    ELSE CASE WHEN e.image_url IS NULL THEN o.image_alt ELSE e.image_alt END END,
+                category = CASE $9 WHEN 'other' THEN o.category WHEN 'keep' THEN e.category
+                    ELSE COALESCE(e.category, o.category) END,
+                importance = MAX(e.importance, o.importance),
+                color = COALESCE(e.color, o.color),
+                icon = COALESCE(e.icon, o.icon),
+                custom_fields = CASE WHEN e.timeline_id IS o.timeline_id
+                    THEN json_patch(o.custom_fields, e.custom_fields) ELSE e.custom_fields END,
+                updated_at = $10
+            FROM events AS o
+            WHERE e.id = $1 AND o.id = $2
+            RETURNING *
+            "#,
+        )
+        .bind(id)
+        .bind(other)
+        .bind(side(choices.title))
+        .bind(side(choices.description))
+        .bind(side(choices.start_date))
+        .bind(side(choices.end_date))
+        .bind(side(choices.location))
+        .bind(side(choices.image_url))
+        .bind(side(choices.category))
+        .bind(now())
+        .fetch_optional(&mut *transaction)
+        .await?;
+        let Some(merged) = merged else {
+            return Ok(None);
+        };
+        sqlx::query(
+            "INSERT OR IGNORE INTO event_tags (event_id, tag_id) SELECT $1, tag_id FROM event_tags WHERE event_id = $2",
+        )
+        .bind(id)
+        .bind(other)
+        .execute(&mut *transaction)
+        .await?;
+        sqlx::query("DELETE FROM events WHERE id = $1")
+            .bind(other)
+            .execute(&mut *transaction)
+            .await?;
+        transaction.commit().await?;
+        Ok(Some(merged))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(title: &str, timeline_id: Option<Uuid>) -> Event {
+        let date = chrono::NaiveDate::from_ymd_opt(2024, 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap();
+        Event {
+            id: Uuid::new_v4(),
+            title: title.to_string(),
+            description: None,
+            start_date: date,
+            end_date: None,
+            start_date_min: None,
+            start_date_max: None,
+            location: None,
+            image_url: None,
+            image_alt: None,
+            category: None,
+            importance: 3,
+            color: None,
+            icon: None,
+            created_at: date,
+            updated_at: date,
+            timeline_id,
+            status: "published".to_string(),
+            publish_at: None,
+            views: 0,
+            likes: 0,
+            featured: false,
+            custom_fields: serde_json::json!({ "source": "chronicle" }),
+        }
+    }
+
+    fn published() -> EventFilter {
+        EventFilter {
+            status: "published".to_string(),
+            limit: 20,
+            ..EventFilter::default()
+        }
+    }
+
+    async fn titles(store: &SqliteEvents, filter: &EventFilter) -> Vec<String> {
+        let page = store.list(filter).await.unwrap();
+        let mut titles: Vec<_> = page.events.into_iter().map(|event| event.title).collect();
+        titles.sort();
+        titles
+    }
+
+    #[tokio::test]
+    async fn hides_the_events_of_private_timelines() {
+        let store = SqliteEvents::connect("sqlite::memory:").await.unwrap();
+        let (public, private) = (Uuid::new_v4(), Uuid::new_v4());
+        for (id, is_private) in [(public, false), (private, true)] {
+            sqlx::query("INSERT INTO timelines (id, owner_id, is_private) VALUES ($1, $2, $3)")
+                .bind(id)
+                .bind(Uuid::new_v4())
+                .bind(is_private)
+                .execute(&store.pool)
+                .await
+                .unwrap();
+        }
+        for event in [event("Loose", None), event("Public", Some(public)), event("Private", Some(private))] {
+            store.create(event).await.unwrap();
+        }
+
+        assert_eq!(titles(&store, &published()).await, ["Loose", "Public"]);
+    }
+
+    #[tokio::test]
+    async fn filters_and_counts_tags() {
+        let store = SqliteEvents::connect("sqlite::memory:").await.unwrap();
+        let tagged = store.create(event("Tagged", None)).await.unwrap();
+        store.create(event("Untagged", None)).await.unwrap();
+        sqlx::query("INSERT INTO tags (id, name) VALUES (1, 'Treaties')")
+            .execute(&store.pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO event_tags (event_id, tag_id) VALUES ($1, 1)")
+            .bind(tagged.id)
+            .execute(&store.pool)
+            .await
+            .unwrap();
+
+        let filter = EventFilter { tag: Some("treaties".to_string()), ..published() };
+        assert_eq!(titles(&store, &filter).await, ["Tagged"]);
+        let facets = store.facets(&published()).await.unwrap();
+        assert_eq!(facets.tags, [TagCount { tag: "Treaties".to_string(), count: 1 }]);
+        assert_eq!(tagged.custom_fields, serde_json::json!({ "source": "chronicle" }));
+    }
+}
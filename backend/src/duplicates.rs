@@ -0,0 +1,148 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    auth::AuthUser,
+    db::{
+        events::{Events, MergeChoices},
+        Reader,
+    },
+    timelines, AppState, Event,
+};
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/events/:id/duplicates", get(get_duplicates))
+        .route("/api/events/:id/merge/:other_id", post(merge_events))
+        .route("/api/events/:id/duplicate", post(duplicate_event))
+}
+
+/// Returned with `409 Conflict` when a new event looks like an existing one.
+#[derive(Serialize)]
+pub struct DuplicateConflict {
+    error: &'static str,
+    candidates: Vec<Event>,
+}
+
+impl IntoResponse for DuplicateConflict {
+    fn into_response(self) -> Response {
+        (StatusCode::CONFLICT, Json(self)).into_response()
+    }
+}
+
+/// Rejects `title`/`start_date` with a conflict listing the likely duplicates.
+pub async fn check(
+    events: &Events,
+    title: &str,
+    start_date: NaiveDateTime,
+) -> Result<(), Response> {
+    let candidates = events
+        .similar(title, start_date, None)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
+
+    if candidates.is_empty() {
+        Ok(())
+    } else {
+        Err(DuplicateConflict {
+            error: "event looks like a duplicate; resend with ?force=true to create it anyway",
+            candidates,
+        }
+        .into_response())
+    }
+}
+
+/// Likely duplicates of an existing event.
+async fn get_duplicates(
+    Reader(pool): Reader,
+    State(events): State<Events>,
+    user: Option<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<Event>>, Response> {
+    timelines::ensure_event_visible(&pool, &events, user.as_ref(), id).await?;
+    let error = |_| StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    let event = events
+        .find(id)
+        .await
+        .map_err(error)?
+        .ok_or_else(|| StatusCode::NOT_FOUND.into_response())?;
+
+    let candidates = events
+        .similar(&event.title, event.start_date, Some(id))
+        .await
+        .map_err(error)?;
+
+    Ok(Json(candidates))
+}
+
+#[derive(Deserialize, Default)]
+struct DuplicateTarget {
+    /// Timeline the copy goes on; the original's when unset.
+    timeline_id: Option<Uuid>,
+}
+
+/// Copies an event, with its tags, people and sources, as a new draft for
+/// the caller to edit. Views, featuring and any schedule are not copied.
+async fn duplicate_event(
+    State(pool): State<PgPool>,
+    State(events): State<Events>,
+    user: Option<AuthUser>,
+    Path(id): Path<Uuid>,
+    target: Option<Json<DuplicateTarget>>,
+) -> Result<Json<Event>, Response> {
+    timelines::ensure_event_visible(&pool, &events, user.as_ref(), id).await?;
+    let error = |_| StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    let target = target.map(|Json(target)| target).unwrap_or_default();
+    let timeline_id = match target.timeline_id {
+        Some(timeline_id) => Some(timeline_id),
+        None => events
+            .find(id)
+            .await
+            .map_err(error)?
+            .ok_or_else(|| StatusCode::NOT_FOUND.into_response())?
+            .timeline_id,
+    };
+    timelines::ensure_timeline_writable(&pool, user.as_ref(), timeline_id).await?;
+
+    events
+        .duplicate(id, timeline_id)
+        .await
+        .map_err(error)?
+        .ok_or_else(|| StatusCode::NOT_FOUND.into_response())
+        .map(Json)
+}
+
+/// Folds `other_id` into `id` and deletes `other_id`.
+///
+/// Field values are picked per `MergeChoices`; tags from both events are
+/// kept on the survivor.
+async fn merge_events(
+    State(pool): State<PgPool>,
+    State(events): State<Events>,
+    user: Option<AuthUser>,
+    Path((id, other_id)): Path<(Uuid, Uuid)>,
+    choices: Option<Json<MergeChoices>>,
+) -> Result<Json<Event>, Response> {
+    if id == other_id {
+        return Err(StatusCode::BAD_REQUEST.into_response());
+    }
+    timelines::ensure_event_writable(&pool, &events, user.as_ref(), id).await?;
+    timelines::ensure_event_writable(&pool, &events, user.as_ref(), other_id).await?;
+    let choices = choices.map(|Json(choices)| choices).unwrap_or_default();
+
+    events
+        .merge(id, other_id, &choices)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?
+        .ok_or_else(|| StatusCode::NOT_FOUND.into_response())
+        .map(Json)
+}
@@ -0,0 +1,358 @@
+use std::collections::HashMap;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post, put},
+    Json, Router,
+};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::{auth::AuthUser, db::Reader, mailer, outbox, AppState};
+
+/// Locale every template must exist in; others fall back to it.
+pub const DEFAULT_LOCALE: &str = "en";
+
+/// The emails the app sends and the placeholders each may use.
+const KINDS: &[(&str, &[&str])] = &[
+    ("verification", &["username", "verify_url"]),
+    ("digest", &["username", "events", "unsubscribe_url"]),
+    ("approval", &["username", "event_title", "status", "event_url"]),
+    ("saved_search", &["username", "search_name", "events", "search_url"]),
+    ("account_deleted", &["username"]),
+    ("password_reset", &["username", "reset_url"]),
+];
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/admin/email-templates", get(list_templates))
+        .route(
+            "/api/admin/email-templates/:key/:locale",
+            put(save_template).delete(delete_template),
+        )
+        .route("/api/admin/email-templates/:key/:locale/preview", post(preview_template))
+        .route("/api/admin/email-templates/:key/:locale/test", post(test_send))
+}
+
+#[derive(Serialize, Clone, sqlx::FromRow)]
+pub struct EmailTemplate {
+    pub key: String,
+    pub locale: String,
+    pub subject: String,
+    pub body: String,
+    pub updated_by: Option<Uuid>,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Serialize)]
+struct TemplateKind {
+    key: &'static str,
+    variables: &'static [&'static str],
+    variants: Vec<EmailTemplate>,
+}
+
+#[derive(Deserialize)]
+struct TemplateSave {
+    subject: String,
+    body: String,
+}
+
+#[derive(Deserialize)]
+struct PreviewRequest {
+    /// Unsaved drafts; the stored template is used for whichever is missing.
+    subject: Option<String>,
+    body: Option<String>,
+    /// Values for placeholders; sample values fill in the rest.
+    #[serde(default)]
+    variables: HashMap<String, String>,
+}
+
+#[derive(Deserialize)]
+struct TestSendRequest {
+    to: String,
+    #[serde(default)]
+    variables: HashMap<String, String>,
+}
+
+#[derive(Serialize)]
+pub struct Rendered {
+    pub subject: String,
+    pub body: String,
+}
+
+fn invalid(message: impl Into<String>) -> Response {
+    (
+        StatusCode::UNPROCESSABLE_ENTITY,
+        Json(serde_json::json!({ "error": message.into() })),
+    )
+        .into_response()
+}
+
+fn variables_for(key: &str) -> Option<&'static [&'static str]> {
+    KINDS.iter().find(|(kind, _)| *kind == key).map(|(_, variables)| *variables)
+}
+
+/// Names of the `{{placeholder}}`s in `template`, in order of appearance.
+fn placeholders(template: &str) -> Result<Vec<&str>, String> {
+    let mut names = Vec::new();
+    let mut rest = template;
+    while let Some(open) = rest.find("{{") {
+        let after = &rest[open + 2..];
+        let close = after.find("}}").ok_or("unterminated {{ placeholder")?;
+        names.push(after[..close].trim());
+        rest = &after[close + 2..];
+    }
+    Ok(names)
+}
+
+/// Substitutes `{{name}}` placeholders; every placeholder needs a value.
+pub fn render(template: &str, values: &HashMap<String, String>) -> Result<String, String> {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find("{{") {
+        out.push_str(&rest[..open]);
+        let after = &rest[open + 2..];
+        let close = after.find("}}").ok_or("unterminated {{ placeholder")?;
+        let name = after[..close].trim();
+        let value = values.get(name).ok_or_else(|| format!("no value for {{{{{}}}}}", name))?;
+        out.push_str(value);
+        rest = &after[close + 2..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Stand-in values so templates can be previewed without real data.
+fn sample_values(key: &str, overrides: HashMap<String, String>) -> HashMap<String, String> {
+    let mut values: HashMap<String, String> = variables_for(key)
+        .unwrap_or_default()
+        .iter()
+        .map(|name| {
+            let sample = match *name {
+                "username" => "ada".to_string(),
+                "events" => "- 1969-07-20 Apollo 11 lands on the Moon\n- 1989-11-09 The Berlin Wall falls".to_string(),
+                "status" => "approved".to_string(),
+                "event_title" => "Apollo 11 lands on the Moon".to_string(),
+                url if url.ends_with("_url") => format!("https://example.com/{}", url.trim_end_matches("_url")),
+                other => format!("[{}]", other),
+            };
+            (name.to_string(), sample)
+        })
+        .collect();
+    values.extend(overrides);
+    values
+}
+
+pub fn valid_locale(locale: &str) -> bool {
+    (2..=10).contains(&locale.len()) && locale.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+/// The template for `locale`, or the default locale's when there is no
+/// variant for it.
+pub async fn find(pool: &PgPool, key: &str, locale: &str) -> Result<Option<EmailTemplate>, sqlx::Error> {
+    sqlx::query_as::<_, EmailTemplate>(
+        r#"
+        SELECT * FROM email_templates
+        WHERE key = $1 AND locale IN ($2, $3)
+        ORDER BY locale = $2 DESC
+        LIMIT 1
+        "#,
+    )
+    .bind(key)
+    .bind(locale)
+    .bind(DEFAULT_LOCALE)
+    .fetch_optional(pool)
+    .await
+}
+
+/// The subject and body of the `key` email in `locale`.
+async fn render_email(
+    state: &AppState,
+    key: &str,
+    locale: &str,
+    values: &HashMap<String, String>,
+) -> Result<(String, String), Response> {
+    let template = find(state.db.reader(), key, locale)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?
+        .ok_or_else(|| StatusCode::NOT_FOUND.into_response())?;
+    let subject = render(&template.subject, values).map_err(invalid)?;
+    let body = render(&template.body, values).map_err(invalid)?;
+    Ok((subject, body))
+}
+
+/// Renders the `key` email in the recipient's locale and queues it in the
+/// outbox with `conn`, so it goes out once that transaction commits.
+pub async fn queue(
+    state: &AppState,
+    conn: &mut PgConnection,
+    key: &str,
+    locale: &str,
+    to: &str,
+    values: &HashMap<String, String>,
+) -> Result<(), Response> {
+    let (subject, body) = render_email(state, key, locale, values).await?;
+    outbox::queue_mail(conn, to, &subject, &body)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())
+}
+
+/// Renders and sends the `key` email in the recipient's locale straight
+/// away, reporting a failed send as 502.
+async fn send(
+    state: &AppState,
+    key: &str,
+    locale: &str,
+    to: &str,
+    values: &HashMap<String, String>,
+) -> Result<(), Response> {
+    let (subject, body) = render_email(state, key, locale, values).await?;
+    mailer::send(&state.config, to, &subject, &body).await.map_err(|err| {
+        tracing::warn!(?err, key, "failed to send email");
+        StatusCode::BAD_GATEWAY.into_response()
+    })
+}
+
+async fn list_templates(Reader(pool): Reader, user: AuthUser) -> Result<Json<Vec<TemplateKind>>, StatusCode> {
+    user.require_admin()?;
+
+    let templates = sqlx::query_as::<_, EmailTemplate>("SELECT * FROM email_templates ORDER BY key, locale")
+        .fetch_all(&pool)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let kinds = KINDS
+        .iter()
+        .map(|&(key, variables)| TemplateKind {
+            key,
+            variables,
+            variants: templates.iter().filter(|t| t.key == key).cloned().collect(),
+        })
+        .collect();
+
+    Ok(Json(kinds))
+}
+
+async fn save_template(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Path((key, locale)): Path<(String, String)>,
+    Json(payload): Json<TemplateSave>,
+) -> Result<Json<EmailTemplate>, Response> {
+    user.require_admin().map_err(IntoResponse::into_response)?;
+    let variables = variables_for(&key).ok_or_else(|| StatusCode::NOT_FOUND.into_response())?;
+    if !valid_locale(&locale) {
+        return Err(invalid("locale must be a language tag such as en or pt-BR"));
+    }
+    if payload.subject.trim().is_empty() || payload.body.trim().is_empty() {
+        return Err(invalid("subject and body are required"));
+    }
+    for text in [&payload.subject, &payload.body] {
+        for name in placeholders(text).map_err(invalid)? {
+            if !variables.contains(&name) {
+                return Err(invalid(format!(
+                    "unknown placeholder {{{{{}}}}}; available: {}",
+                    name,
+                    variables.join(", ")
+                )));
+            }
+        }
+    }
+
+    let template = sqlx::query_as::<_, EmailTemplate>(
+        r#"
+        INSERT INTO email_templates (key, locale, subject, body, updated_by)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (key, locale) DO UPDATE SET
+            subject = EXCLUDED.subject,
+            body = EXCLUDED.body,
+            updated_by = EXCLUDED.updated_by,
+            updated_at = NOW()
+        RETURNING *
+        "#,
+    )
+    .bind(&key)
+    .bind(&locale)
+    .bind(payload.subject.trim())
+    .bind(&payload.body)
+    .bind(user.id)
+    .fetch_one(&pool)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
+
+    Ok(Json(template))
+}
+
+async fn delete_template(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Path((key, locale)): Path<(String, String)>,
+) -> Result<StatusCode, Response> {
+    user.require_admin().map_err(IntoResponse::into_response)?;
+    if locale == DEFAULT_LOCALE {
+        return Err(invalid("the default locale is the fallback and cannot be removed"));
+    }
+
+    let deleted = sqlx::query("DELETE FROM email_templates WHERE key = $1 AND locale = $2")
+        .bind(&key)
+        .bind(&locale)
+        .execute(&pool)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?
+        .rows_affected();
+
+    if deleted == 0 {
+        return Err(StatusCode::NOT_FOUND.into_response());
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn preview_template(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Path((key, locale)): Path<(String, String)>,
+    Json(payload): Json<PreviewRequest>,
+) -> Result<Json<Rendered>, Response> {
+    user.require_admin().map_err(IntoResponse::into_response)?;
+    variables_for(&key).ok_or_else(|| StatusCode::NOT_FOUND.into_response())?;
+
+    let stored = find(&pool, &key, &locale)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
+    let subject = payload
+        .subject
+        .or_else(|| stored.as_ref().map(|t| t.subject.clone()))
+        .unwrap_or_default();
+    let body = payload
+        .body
+        .or_else(|| stored.map(|t| t.body))
+        .unwrap_or_default();
+
+    let values = sample_values(&key, payload.variables);
+    Ok(Json(Rendered {
+        subject: render(&subject, &values).map_err(invalid)?,
+        body: render(&body, &values).map_err(invalid)?,
+    }))
+}
+
+async fn test_send(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path((key, locale)): Path<(String, String)>,
+    Json(payload): Json<TestSendRequest>,
+) -> Result<StatusCode, Response> {
+    user.require_admin().map_err(IntoResponse::into_response)?;
+    variables_for(&key).ok_or_else(|| StatusCode::NOT_FOUND.into_response())?;
+    if !payload.to.contains('@') {
+        return Err(invalid("a recipient address is required"));
+    }
+
+    let values = sample_values(&key, payload.variables);
+    send(&state, &key, &locale, payload.to.trim(), &values).await?;
+
+    Ok(StatusCode::ACCEPTED)
+}
@@ -0,0 +1,270 @@
+//! Video and audio players on event pages. Only links to the providers
+//! below are accepted; each is normalized, looked up through the
+//! provider's oEmbed endpoint, and played through a player URL built here
+//! rather than HTML taken from the provider.
+
+use std::time::Duration;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use chrono::NaiveDateTime;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{auth::AuthUser, db::{events::Events, Reader}, images, sanitize, timelines, AppState};
+
+const LOOKUP_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub fn routes() -> Router<AppState> {
+    Router::new().route("/api/events/:id/embed", get(get_embed).put(set_embed).delete(remove_embed))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Provider {
+    YouTube,
+    Vimeo,
+    SoundCloud,
+}
+
+impl Provider {
+    fn name(self) -> &'static str {
+        match self {
+            Provider::YouTube => "youtube",
+            Provider::Vimeo => "vimeo",
+            Provider::SoundCloud => "soundcloud",
+        }
+    }
+
+    fn oembed_endpoint(self) -> &'static str {
+        match self {
+            Provider::YouTube => "https://www.youtube.com/oembed",
+            Provider::Vimeo => "https://vimeo.com/api/oembed.json",
+            Provider::SoundCloud => "https://soundcloud.com/oembed",
+        }
+    }
+}
+
+/// A link recognized as one of the providers'.
+#[derive(Debug, PartialEq)]
+struct Normalized {
+    provider: Provider,
+    /// The canonical form of the link.
+    url: String,
+    player_url: String,
+}
+
+fn youtube_id(id: &str) -> Option<&str> {
+    let valid = id.len() == 11 && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    valid.then_some(id)
+}
+
+/// Recognizes watch, short, embed and `youtu.be` links to YouTube videos,
+/// Vimeo videos and SoundCloud tracks; anything else is `None`.
+fn normalize(link: &str) -> Option<Normalized> {
+    let url = Url::parse(link.trim()).ok()?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return None;
+    }
+    let host = url.host_str()?.to_ascii_lowercase();
+    let host = host.strip_prefix("www.").or_else(|| host.strip_prefix("m.")).unwrap_or(&host).to_string();
+    let segments: Vec<&str> = url.path_segments()?.filter(|segment| !segment.is_empty()).collect();
+
+    let (provider, id) = match (host.as_str(), segments.as_slice()) {
+        ("youtube.com", ["watch"]) => {
+            let id = url.query_pairs().find(|(key, _)| key == "v")?.1.into_owned();
+            (Provider::YouTube, youtube_id(&id)?.to_string())
+        }
+        ("youtube.com", ["shorts" | "embed", id]) | ("youtu.be", [id]) => (Provider::YouTube, youtube_id(id)?.to_string()),
+        ("vimeo.com", [id]) | ("player.vimeo.com", ["video", id]) if id.chars().all(|c| c.is_ascii_digit()) => {
+            (Provider::Vimeo, id.to_string())
+        }
+        ("soundcloud.com", [artist, track]) => (Provider::SoundCloud, format!("{}/{}", artist, track)),
+        _ => return None,
+    };
+
+    let (url, player_url) = match provider {
+        Provider::YouTube => (
+            format!("https://www.youtube.com/watch?v={}", id),
+            format!("https://www.youtube-nocookie.com/embed/{}", id),
+        ),
+        Provider::Vimeo => (format!("https://vimeo.com/{}", id), format!("https://player.vimeo.com/video/{}", id)),
+        Provider::SoundCloud => {
+            let url = format!("https://soundcloud.com/{}", id);
+            let player = Url::parse_with_params("https://w.soundcloud.com/player/", &[("url", url.as_str())]).ok()?;
+            (url, player.to_string())
+        }
+    };
+    Some(Normalized { provider, url, player_url })
+}
+
+/// The parts of an oEmbed response kept.
+#[derive(Deserialize)]
+struct OEmbed {
+    title: Option<String>,
+    author_name: Option<String>,
+    thumbnail_url: Option<String>,
+}
+
+/// Asks the provider about the link; `None` when it does not know it, as
+/// for private or deleted videos.
+async fn look_up(link: &Normalized) -> Result<Option<OEmbed>, reqwest::Error> {
+    let client = reqwest::Client::builder().timeout(LOOKUP_TIMEOUT).build()?;
+    let response = client
+        .get(link.provider.oembed_endpoint())
+        .query(&[("url", link.url.as_str()), ("format", "json")])
+        .send()
+        .await?;
+    if response.status().is_client_error() {
+        return Ok(None);
+    }
+    response.error_for_status()?.json().await.map(Some)
+}
+
+/// Provider text, made safe and cut to fit its column.
+fn clean(value: Option<String>, max: usize) -> Option<String> {
+    let text = sanitize::text(&value?);
+    Some(text.chars().take(max).collect()).filter(|text: &String| !text.is_empty())
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+struct Embed {
+    url: String,
+    provider: String,
+    player_url: String,
+    title: Option<String>,
+    author_name: Option<String>,
+    thumbnail_url: Option<String>,
+    created_at: NaiveDateTime,
+}
+
+/// The event's player, or `null` when it has none.
+async fn get_embed(
+    Reader(pool): Reader,
+    State(events): State<Events>,
+    user: Option<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Option<Embed>>, Response> {
+    timelines::ensure_event_visible(&pool, &events, user.as_ref(), id).await?;
+    let embed = sqlx::query_as::<_, Embed>("SELECT * FROM event_embeds WHERE event_id = $1")
+        .bind(id)
+        .fetch_optional(&pool)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
+
+    Ok(Json(embed))
+}
+
+#[derive(Deserialize)]
+struct EmbedRequest {
+    url: String,
+}
+
+/// Sets or replaces the event's player.
+async fn set_embed(
+    State(pool): State<PgPool>,
+    State(events): State<Events>,
+    user: Option<AuthUser>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<EmbedRequest>,
+) -> Result<Json<Embed>, Response> {
+    timelines::ensure_event_writable(&pool, &events, user.as_ref(), id).await?;
+    let link = normalize(&payload.url).ok_or_else(|| {
+        (StatusCode::UNPROCESSABLE_ENTITY, "only YouTube, Vimeo and SoundCloud links can be embedded").into_response()
+    })?;
+    let found = look_up(&link)
+        .await
+        .map_err(|_| (StatusCode::BAD_GATEWAY, "could not reach the provider; try again later").into_response())?
+        .ok_or_else(|| (StatusCode::UNPROCESSABLE_ENTITY, "the provider does not know that link").into_response())?;
+    // Thumbnails are shown through the image proxy, so the same rules apply.
+    let thumbnail_url = found.thumbnail_url.filter(|url| url.len() <= 512 && images::valid_url(url).is_ok());
+
+    let embed = sqlx::query_as::<_, Embed>(
+        r#"
+        INSERT INTO event_embeds (event_id, url, provider, player_url, title, author_name, thumbnail_url)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        ON CONFLICT (event_id) DO UPDATE SET
+            url = EXCLUDED.url,
+            provider = EXCLUDED.provider,
+            player_url = EXCLUDED.player_url,
+            title = EXCLUDED.title,
+            author_name = EXCLUDED.author_name,
+            thumbnail_url = EXCLUDED.thumbnail_url,
+            created_at = NOW()
+        RETURNING *
+        "#,
+    )
+    .bind(id)
+    .bind(&link.url)
+    .bind(link.provider.name())
+    .bind(&link.player_url)
+    .bind(clean(found.title, 255))
+    .bind(clean(found.author_name, 255))
+    .bind(thumbnail_url)
+    .fetch_one(&pool)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
+
+    Ok(Json(embed))
+}
+
+async fn remove_embed(
+    State(pool): State<PgPool>,
+    State(events): State<Events>,
+    user: Option<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, Response> {
+    timelines::ensure_event_writable(&pool, &events, user.as_ref(), id).await?;
+    sqlx::query("DELETE FROM event_embeds WHERE event_id = $1")
+        .bind(id)
+        .execute(&pool)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_youtube_links() {
+        for link in [
+            "https://www.youtube.com/watch?v=dQw4w9WgXcQ&t=42s",
+            "https://m.youtube.com/watch?v=dQw4w9WgXcQ",
+            "https://youtu.be/dQw4w9WgXcQ",
+            "https://www.youtube.com/shorts/dQw4w9WgXcQ",
+            "https://www.youtube.com/embed/dQw4w9WgXcQ",
+        ] {
+            let embed = normalize(link).unwrap();
+            assert_eq!(embed.provider, Provider::YouTube);
+            assert_eq!(embed.url, "https://www.youtube.com/watch?v=dQw4w9WgXcQ");
+            assert_eq!(embed.player_url, "https://www.youtube-nocookie.com/embed/dQw4w9WgXcQ");
+        }
+    }
+
+    #[test]
+    fn normalizes_vimeo_and_soundcloud_links() {
+        let vimeo = normalize("https://player.vimeo.com/video/76979871").unwrap();
+        assert_eq!(vimeo.url, "https://vimeo.com/76979871");
+
+        let track = normalize("https://soundcloud.com/nasa/apollo-11-liftoff").unwrap();
+        assert_eq!(track.provider, Provider::SoundCloud);
+        assert!(track.player_url.starts_with("https://w.soundcloud.com/player/?url=https%3A%2F%2Fsoundcloud.com"));
+    }
+
+    #[test]
+    fn refuses_other_links() {
+        assert!(normalize("https://example.com/watch?v=dQw4w9WgXcQ").is_none());
+        assert!(normalize("https://www.youtube.com/watch?v=short").is_none());
+        assert!(normalize("https://vimeo.com/channels/staffpicks").is_none());
+        assert!(normalize("javascript:alert(1)").is_none());
+    }
+}
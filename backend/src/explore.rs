@@ -0,0 +1,126 @@
+//! `GET /api/timelines/public`: the public timelines shown on the Explore
+//! page, with what a card needs to tempt a visitor in.
+
+use axum::{extract::Query, http::StatusCode, routing::get, Json, Router};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{db::Reader, AppState, PaginatedResponse};
+
+pub fn routes() -> Router<AppState> {
+    Router::new().route("/api/timelines/public", get(list_public))
+}
+
+/// Public timelines with at least one published event, and their
+/// published events' totals as `s`. Binds the search pattern as `$1`.
+const PUBLIC_TIMELINES: &str = r#"
+    FROM timelines t
+    JOIN users u ON u.id = t.owner_id
+    JOIN LATERAL (
+        SELECT COUNT(*) AS event_count,
+            MIN(e.start_date) AS starts_at,
+            MAX(COALESCE(e.end_date, e.start_date)) AS ends_at,
+            COALESCE(SUM(e.views), 0)::BIGINT AS views
+        FROM events e WHERE e.timeline_id = t.id AND e.status = 'published'
+    ) s ON s.event_count > 0
+    WHERE NOT t.is_private
+    AND ($1::text IS NULL OR t.title ILIKE $1 OR t.description ILIKE $1)
+"#;
+
+/// `ORDER BY` for each accepted `sort`.
+fn order_by(sort: &str) -> Option<&'static str> {
+    match sort {
+        "popular" => Some("s.views DESC, s.event_count DESC, t.updated_at DESC"),
+        "recent" => Some("t.updated_at DESC"),
+        _ => None,
+    }
+}
+
+#[derive(Deserialize)]
+struct ExploreQuery {
+    page: Option<i32>,
+    limit: Option<i32>,
+    /// Case-insensitive substring of the title or description.
+    search: Option<String>,
+    /// `popular` (the default) or `recent`.
+    sort: Option<String>,
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+struct PublicTimeline {
+    id: Uuid,
+    title: String,
+    description: Option<String>,
+    owner: String,
+    /// The most important published event with an image, whose thumbnail
+    /// is the timeline's cover.
+    cover_event_id: Option<Uuid>,
+    event_count: i64,
+    /// Span of the published events.
+    starts_at: NaiveDateTime,
+    ends_at: NaiveDateTime,
+    updated_at: NaiveDateTime,
+}
+
+async fn list_public(
+    Reader(pool): Reader,
+    Query(query): Query<ExploreQuery>,
+) -> Result<Json<PaginatedResponse<PublicTimeline>>, StatusCode> {
+    let page = query.page.unwrap_or(1).max(1);
+    let limit = query.limit.unwrap_or(12).clamp(1, 48);
+    let order = order_by(query.sort.as_deref().unwrap_or("popular")).ok_or(StatusCode::UNPROCESSABLE_ENTITY)?;
+    let pattern = query
+        .search
+        .map(|search| search.trim().to_string())
+        .filter(|search| !search.is_empty())
+        .map(|search| format!("%{}%", search));
+
+    let total = sqlx::query_scalar::<_, i64>(&format!("SELECT COUNT(*) {}", PUBLIC_TIMELINES))
+        .bind(&pattern)
+        .fetch_one(&pool)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let timelines = sqlx::query_as::<_, PublicTimeline>(&format!(
+        r#"
+        SELECT t.id, t.title, t.description, u.username AS owner, s.event_count, s.starts_at, s.ends_at,
+            t.updated_at,
+            (
+                SELECT e.id FROM events e
+                WHERE e.timeline_id = t.id AND e.status = 'published' AND e.image_url IS NOT NULL
+                ORDER BY e.importance DESC, e.start_date
+                LIMIT 1
+            ) AS cover_event_id
+        {}
+        ORDER BY {}
+        LIMIT $2 OFFSET $3
+        "#,
+        PUBLIC_TIMELINES, order
+    ))
+    .bind(&pattern)
+    .bind(limit as i64)
+    .bind(((page - 1) * limit) as i64)
+    .fetch_all(&pool)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(PaginatedResponse {
+        data: timelines,
+        total,
+        page,
+        limit,
+        pages: (total as f64 / limit as f64).ceil() as i32,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_known_sorts_are_accepted() {
+        assert!(order_by("popular").is_some());
+        assert!(order_by("recent").is_some());
+        assert!(order_by("t.id; DROP TABLE timelines").is_none());
+    }
+}
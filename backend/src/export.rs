@@ -0,0 +1,800 @@
+use std::io;
+
+use axum::{
+    body::{Body, Bytes},
+    extract::{Path, Query},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use chrono::{Datelike, NaiveDate, NaiveDateTime};
+use futures::{stream, Stream, StreamExt};
+use printpdf::{BuiltinFont, IndirectFontRef, Mm, PdfDocument, PdfDocumentReference, PdfLayerReference};
+use serde::Deserialize;
+use sqlx::PgPool;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+use crate::{auth::AuthUser, db::Reader, timelines, AppState, Event};
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/timelines/:id/export.pdf", get(export_pdf))
+        .route("/api/timelines/:id/export.csv", get(export_csv))
+        .route("/api/timelines/:id/export.json", get(export_json))
+        .route("/api/timelines/:id/render.svg", get(render_svg))
+}
+
+#[derive(Deserialize)]
+struct RenderParams {
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    width: Option<u32>,
+}
+
+/// A4 portrait.
+const PAGE_WIDTH: f64 = 210.0;
+const PAGE_HEIGHT: f64 = 297.0;
+const MARGIN: f64 = 20.0;
+const PT_TO_MM: f64 = 0.3528;
+
+/// Bytes of encoded rows sent to the client as one chunk.
+const CHUNK_BYTES: usize = 64 * 1024;
+/// Chunks encoded ahead of a slow client. When they're all waiting, the
+/// export stops reading rows until the client catches up.
+const CHUNKS_AHEAD: usize = 4;
+/// Rows read ahead of the PDF layout.
+const ROWS_AHEAD: usize = 256;
+
+const CSV_COLUMNS: [&str; 9] = [
+    "id",
+    "title",
+    "description",
+    "start_date",
+    "end_date",
+    "location",
+    "category",
+    "importance",
+    "image_url",
+];
+
+/// Published events of a timeline; exports never include drafts.
+const PUBLISHED_EVENTS: &str =
+    "SELECT * FROM events WHERE timeline_id = $1 AND status = 'published' ORDER BY start_date";
+const PUBLISHED_COUNT: &str = "SELECT COUNT(*) FROM events WHERE timeline_id = $1 AND status = 'published'";
+
+async fn timeline_events(pool: &PgPool, id: Uuid) -> Result<Vec<Event>, Response> {
+    sqlx::query_as::<_, Event>(PUBLISHED_EVENTS)
+        .bind(id)
+        .fetch_all(pool)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())
+}
+
+async fn export_pdf(
+    Reader(pool): Reader,
+    user: Option<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Response, Response> {
+    let timeline = timelines::find_visible(&pool, id, user.as_ref()).await?;
+    let count = sqlx::query_scalar::<_, i64>(PUBLISHED_COUNT)
+        .bind(id)
+        .fetch_one(&pool)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
+    let filename = format!("{}.pdf", slug(&timeline.title));
+
+    let (rows_sender, rows) = mpsc::channel(ROWS_AHEAD);
+    tokio::spawn(async move {
+        let mut cursor = sqlx::query_as::<_, Event>(PUBLISHED_EVENTS).bind(id).fetch(&pool);
+        while let Some(row) = cursor.next().await {
+            if rows_sender.send(row).await.is_err() {
+                return;
+            }
+        }
+    });
+    let (sender, body) = channel();
+    tokio::task::spawn_blocking(move || stream_pdf(&timeline, count as usize, rows, sender));
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/pdf".to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename)),
+        ],
+        body,
+    )
+        .into_response())
+}
+
+/// Lays out the events as `rows` delivers them and sends the finished PDF
+/// down `sender` in chunks. printpdf documents can't cross threads, so
+/// this runs on a blocking thread; the rows are read ahead of it by at most
+/// `ROWS_AHEAD`, but the document itself is only complete at the end. As in
+/// `pump`, a failed query or layout aborts the body.
+fn stream_pdf(
+    timeline: &timelines::Timeline,
+    count: usize,
+    mut rows: mpsc::Receiver<Result<Event, sqlx::Error>>,
+    sender: mpsc::Sender<Chunk>,
+) {
+    let mut failed = None;
+    let events = std::iter::from_fn(|| match rows.blocking_recv()? {
+        Ok(event) => Some(event),
+        Err(err) => {
+            failed = Some(err.to_string());
+            None
+        }
+    });
+    let pdf = render_pdf(timeline, count, events).map_err(|err| err.to_string());
+    let pdf = match (pdf, failed) {
+        (Ok(pdf), None) => Bytes::from(pdf),
+        (Err(err), _) | (_, Some(err)) => {
+            tracing::error!(error = %err, "failed to render timeline pdf");
+            let _ = sender.blocking_send(Err(io::Error::other(err)));
+            return;
+        }
+    };
+    for start in (0..pdf.len()).step_by(CHUNK_BYTES) {
+        let chunk = pdf.slice(start..pdf.len().min(start + CHUNK_BYTES));
+        if sender.blocking_send(Ok(chunk)).is_err() {
+            return;
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Format {
+    Csv,
+    Json,
+}
+
+impl Format {
+    pub(crate) fn content_type(self) -> &'static str {
+        match self {
+            Format::Csv => "text/csv; charset=utf-8",
+            Format::Json => "application/json",
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Format::Csv => "csv",
+            Format::Json => "json",
+        }
+    }
+
+    fn start(self, out: &mut Vec<u8>) {
+        match self {
+            Format::Csv => {
+                out.extend_from_slice(CSV_COLUMNS.join(",").as_bytes());
+                out.extend_from_slice(b"\r\n");
+            }
+            Format::Json => out.push(b'['),
+        }
+    }
+
+    /// Appends the `index`th event.
+    fn row(self, index: usize, event: &Event, out: &mut Vec<u8>) -> io::Result<()> {
+        match self {
+            Format::Csv => {
+                let fields = [
+                    event.id.to_string(),
+                    event.title.clone(),
+                    event.description.clone().unwrap_or_default(),
+                    csv_date(Some(event.start_date)),
+                    csv_date(event.end_date),
+                    event.location.clone().unwrap_or_default(),
+                    event.category.clone().unwrap_or_default(),
+                    event.importance.to_string(),
+                    event.image_url.clone().unwrap_or_default(),
+                ];
+                for (column, field) in fields.iter().enumerate() {
+                    if column > 0 {
+                        out.push(b',');
+                    }
+                    csv_field(out, field);
+                }
+                out.extend_from_slice(b"\r\n");
+            }
+            Format::Json => {
+                if index > 0 {
+                    out.push(b',');
+                }
+                out.push(b'\n');
+                serde_json::to_writer(&mut *out, event)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn end(self, out: &mut Vec<u8>) {
+        if self == Format::Json {
+            out.extend_from_slice(b"\n]\n");
+        }
+    }
+}
+
+fn csv_date(at: Option<NaiveDateTime>) -> String {
+    at.map(|at| at.format("%Y-%m-%dT%H:%M:%S").to_string()).unwrap_or_default()
+}
+
+/// Writes `value`, quoted as RFC 4180 asks when it holds a comma, quote or
+/// line break.
+fn csv_field(out: &mut Vec<u8>, value: &str) {
+    if value.contains([',', '"', '\n', '\r']) {
+        out.push(b'"');
+        out.extend_from_slice(value.replace('"', "\"\"").as_bytes());
+        out.push(b'"');
+    } else {
+        out.extend_from_slice(value.as_bytes());
+    }
+}
+
+pub(crate) type Chunk = io::Result<Bytes>;
+
+/// A response body fed through a channel holding at most `CHUNKS_AHEAD`
+/// chunks.
+pub(crate) fn channel() -> (mpsc::Sender<Chunk>, Body) {
+    let (sender, receiver) = mpsc::channel(CHUNKS_AHEAD);
+    let chunks = stream::unfold(receiver, |mut receiver| async move {
+        receiver.recv().await.map(|chunk| (chunk, receiver))
+    });
+    (sender, Body::from_stream(chunks))
+}
+
+/// Encodes `rows` into chunks of about `CHUNK_BYTES` and sends them down
+/// `sender`. The next row is only read once the chunk before it is queued,
+/// so a slow client holds the query back instead of rows piling up in
+/// memory, and a client that hangs up ends it. A failed query aborts the
+/// body, so the download is cut short rather than ending as a valid file
+/// with rows missing.
+pub(crate) async fn pump<S>(mut rows: S, format: Format, sender: mpsc::Sender<Chunk>)
+where
+    S: Stream<Item = Result<Event, sqlx::Error>> + Unpin,
+{
+    let mut buffer = Vec::with_capacity(CHUNK_BYTES);
+    format.start(&mut buffer);
+    let mut index = 0;
+    while let Some(row) = rows.next().await {
+        let written = row
+            .map_err(io::Error::other)
+            .and_then(|event| format.row(index, &event, &mut buffer));
+        if let Err(err) = written {
+            tracing::error!(error = %err, "failed to stream export");
+            let _ = sender.send(Err(err)).await;
+            return;
+        }
+        index += 1;
+        if buffer.len() >= CHUNK_BYTES {
+            let chunk = std::mem::replace(&mut buffer, Vec::with_capacity(CHUNK_BYTES));
+            if sender.send(Ok(chunk.into())).await.is_err() {
+                return;
+            }
+        }
+    }
+    format.end(&mut buffer);
+    let _ = sender.send(Ok(buffer.into())).await;
+}
+
+async fn export_rows(pool: PgPool, user: Option<AuthUser>, id: Uuid, format: Format) -> Result<Response, Response> {
+    let timeline = timelines::find_visible(&pool, id, user.as_ref()).await?;
+
+    let (sender, body) = channel();
+    tokio::spawn(async move {
+        let rows = sqlx::query_as::<_, Event>(PUBLISHED_EVENTS).bind(id).fetch(&pool);
+        pump(rows, format, sender).await;
+    });
+
+    let filename = format!("{}.{}", slug(&timeline.title), format.extension());
+    Ok((
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename)),
+        ],
+        body,
+    )
+        .into_response())
+}
+
+/// The timeline's published events as CSV, streamed as they are read.
+async fn export_csv(Reader(pool): Reader, user: Option<AuthUser>, Path(id): Path<Uuid>) -> Result<Response, Response> {
+    export_rows(pool, user, id, Format::Csv).await
+}
+
+/// The timeline's published events as a JSON array, streamed as they are
+/// read.
+async fn export_json(Reader(pool): Reader, user: Option<AuthUser>, Path(id): Path<Uuid>) -> Result<Response, Response> {
+    export_rows(pool, user, id, Format::Json).await
+}
+
+/// Lowercase ASCII file name for a title.
+fn slug(title: &str) -> String {
+    let slug: String = title
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+        .collect();
+    let slug = slug.split('-').filter(|part| !part.is_empty()).collect::<Vec<_>>().join("-");
+    if slug.is_empty() { "timeline".to_string() } else { slug }
+}
+
+/// Greedy word wrap using an average Helvetica glyph width of half the
+/// font size, which is close enough for body text.
+fn wrap(text: &str, size_pt: f64, width_mm: f64) -> Vec<String> {
+    let max_chars = (width_mm / (size_pt * PT_TO_MM * 0.5)).floor().max(1.0) as usize;
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > max_chars {
+                lines.push(std::mem::take(&mut line));
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(word);
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+/// Writes lines top to bottom, starting a new page when one fills up.
+struct Writer {
+    doc: PdfDocumentReference,
+    layer: PdfLayerReference,
+    regular: IndirectFontRef,
+    bold: IndirectFontRef,
+    y: f64,
+    pages: usize,
+}
+
+impl Writer {
+    fn line(&mut self, text: &str, size_pt: f64, bold: bool) {
+        let height = size_pt * PT_TO_MM * 1.4;
+        if self.y - height < MARGIN {
+            self.new_page();
+        }
+        self.y -= height;
+        let font = if bold { &self.bold } else { &self.regular };
+        self.layer.use_text(text, size_pt as f32, Mm(MARGIN as f32), Mm(self.y as f32), font);
+    }
+
+    fn paragraph(&mut self, text: &str, size_pt: f64) {
+        for line in wrap(text, size_pt, PAGE_WIDTH - 2.0 * MARGIN) {
+            self.line(&line, size_pt, false);
+        }
+    }
+
+    fn space(&mut self, mm: f64) {
+        self.y -= mm;
+    }
+
+    fn new_page(&mut self) {
+        self.footer();
+        let (page, layer) = self.doc.add_page(Mm(PAGE_WIDTH as f32), Mm(PAGE_HEIGHT as f32), "Events");
+        self.layer = self.doc.get_page(page).get_layer(layer);
+        self.y = PAGE_HEIGHT - MARGIN;
+        self.pages += 1;
+    }
+
+    fn footer(&self) {
+        self.layer.use_text(
+            format!("Page {}", self.pages),
+            8.0,
+            Mm((PAGE_WIDTH - MARGIN - 12.0) as f32),
+            Mm((MARGIN / 2.0) as f32),
+            &self.regular,
+        );
+    }
+}
+
+/// The timeline as a PDF with one entry per event; `count` is the number
+/// of `events`, printed before they are.
+fn render_pdf(
+    timeline: &timelines::Timeline,
+    count: usize,
+    events: impl Iterator<Item = Event>,
+) -> Result<Vec<u8>, printpdf::Error> {
+    let (doc, page, layer) = PdfDocument::new(&timeline.title, Mm(PAGE_WIDTH as f32), Mm(PAGE_HEIGHT as f32), "Events");
+    let regular = doc.add_builtin_font(BuiltinFont::Helvetica)?;
+    let bold = doc.add_builtin_font(BuiltinFont::HelveticaBold)?;
+    let layer = doc.get_page(page).get_layer(layer);
+
+    let mut writer = Writer { doc, layer, regular, bold, y: PAGE_HEIGHT - MARGIN, pages: 1 };
+
+    writer.line(&timeline.title, 20.0, true);
+    if let Some(description) = &timeline.description {
+        writer.space(2.0);
+        writer.paragraph(description, 11.0);
+    }
+    writer.line(
+        &format!("{} events, exported {}", count, chrono::Utc::now().format("%Y-%m-%d")),
+        9.0,
+        false,
+    );
+    writer.space(6.0);
+
+    for event in events {
+        let mut when = event.start_date.format("%Y-%m-%d").to_string();
+        if let Some(end) = event.end_date {
+            when += &format!(" - {}", end.format("%Y-%m-%d"));
+        }
+        if let Some(category) = &event.category {
+            when += &format!("  |  {}", category);
+        }
+        writer.line(&when, 9.0, true);
+        writer.line(&event.title, 13.0, true);
+        if let Some(description) = &event.description {
+            writer.paragraph(description, 10.0);
+        }
+        if let Some(location) = &event.location {
+            writer.line(&format!("Location: {}", location), 9.0, false);
+        }
+        writer.space(5.0);
+    }
+
+    writer.footer();
+    writer.doc.save_to_bytes()
+}
+
+/// Category colours, shared with the frontend's `appearance` module so
+/// exports look like the app. An event's own colour overrides them.
+const PALETTE: [&str; 8] = ["#3b82f6", "#ef4444", "#10b981", "#f59e0b", "#8b5cf6", "#ec4899", "#14b8a6", "#f97316"];
+const UNCATEGORIZED_COLOR: &str = "#6b7280";
+
+fn category_color(category: Option<&str>) -> &'static str {
+    match category {
+        Some(category) => {
+            let hash = category.bytes().fold(0u32, |hash, b| hash.wrapping_mul(31).wrapping_add(b as u32));
+            PALETTE[hash as usize % PALETTE.len()]
+        }
+        None => UNCATEGORIZED_COLOR,
+    }
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn days(at: NaiveDateTime) -> f64 {
+    at.and_utc().timestamp() as f64 / 86_400.0
+}
+
+fn date_days(date: NaiveDate) -> f64 {
+    days(date.and_hms_opt(0, 0, 0).unwrap_or_default())
+}
+
+const SVG_LANE_HEIGHT: f64 = 28.0;
+const SVG_AXIS_HEIGHT: f64 = 48.0;
+const SVG_MAX_LABEL: f64 = 200.0;
+
+/// Year ticks at a round step giving roughly one label per 120px, or
+/// month ticks for spans under two years.
+fn svg_ticks(from: f64, to: f64, width: f64) -> Vec<(f64, String)> {
+    let start = chrono::DateTime::from_timestamp((from * 86_400.0) as i64, 0).map(|at| at.date_naive());
+    let Some(start) = start else {
+        return Vec::new();
+    };
+    let span_years = (to - from) / 365.25;
+    let mut ticks = Vec::new();
+
+    if span_years < 2.0 {
+        let mut date = NaiveDate::from_ymd_opt(start.year(), start.month(), 1);
+        while let Some(current) = date {
+            let at = date_days(current);
+            if at > to {
+                break;
+            }
+            if at >= from {
+                ticks.push((at, current.format("%Y-%m").to_string()));
+            }
+            date = current.checked_add_months(chrono::Months::new(1));
+        }
+        return ticks;
+    }
+
+    let wanted = span_years / (width / 120.0).max(1.0);
+    let step = [1, 2, 5, 10, 20, 50, 100, 200, 500, 1000, 2000, 5000]
+        .into_iter()
+        .find(|step| *step as f64 >= wanted)
+        .unwrap_or(10_000);
+    let mut year = start.year().div_euclid(step) * step;
+    while let Some(current) = NaiveDate::from_ymd_opt(year, 1, 1) {
+        let at = date_days(current);
+        if at > to {
+            break;
+        }
+        if at >= from {
+            ticks.push((at, if year <= 0 { format!("{} BCE", 1 - year) } else { year.to_string() }));
+        }
+        year += step;
+    }
+    ticks
+}
+
+/// Draws the events between `from` and `to` as an SVG the same way the
+/// timeline view does: markers or range bars in first-fit lanes, coloured
+/// by category, above a labelled axis.
+fn render_events_svg(title: &str, events: &[Event], from: f64, to: f64, width: f64) -> String {
+    let x = |day: f64| (day - from) / (to - from) * width;
+
+    let mut lane_ends: Vec<f64> = Vec::new();
+    let mut placed = Vec::new();
+    for event in events {
+        let start = x(days(event.start_date));
+        let bar = event.end_date.map_or(10.0, |end| (x(days(end)) - start).max(10.0));
+        let label = (event.title.chars().count() as f64 * 7.0 + 8.0).min(SVG_MAX_LABEL);
+        let end = start + bar + label;
+        if end < 0.0 || start > width {
+            continue;
+        }
+        let lane = match lane_ends.iter().position(|&lane_end| lane_end + 8.0 <= start) {
+            Some(lane) => lane,
+            None => {
+                lane_ends.push(f64::NEG_INFINITY);
+                lane_ends.len() - 1
+            }
+        };
+        lane_ends[lane] = end;
+        placed.push((event, start, bar, lane));
+    }
+
+    let height = 32.0 + lane_ends.len().max(1) as f64 * SVG_LANE_HEIGHT + SVG_AXIS_HEIGHT;
+    let axis_y = height - SVG_AXIS_HEIGHT + 8.0;
+    let mut svg = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width:.0}" height="{height:.0}" viewBox="0 0 {width:.0} {height:.0}" font-family="Segoe UI, Helvetica, Arial, sans-serif">"#
+    );
+    svg += r##"<rect width="100%" height="100%" fill="#ffffff"/>"##;
+    svg += &format!(
+        r##"<text x="12" y="22" font-size="16" font-weight="bold" fill="#111827">{}</text>"##,
+        escape_xml(title)
+    );
+
+    for (event, start, bar, lane) in placed {
+        let y = 32.0 + lane as f64 * SVG_LANE_HEIGHT + 8.0;
+        let color = event.color.as_deref().unwrap_or_else(|| category_color(event.category.as_deref()));
+        svg += &format!(
+            r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="10" rx="5" fill="{}"/>"#,
+            start, y, bar, color
+        );
+        let mut label = event.title.clone();
+        let max_chars = (SVG_MAX_LABEL / 7.0) as usize;
+        if label.chars().count() > max_chars {
+            label = label.chars().take(max_chars - 1).collect::<String>() + "…";
+        }
+        svg += &format!(
+            r##"<text x="{:.1}" y="{:.1}" font-size="12" fill="#1f2937">{}</text>"##,
+            start + bar + 4.0,
+            y + 9.0,
+            escape_xml(&label)
+        );
+    }
+
+    svg += &format!(r##"<rect x="0" y="{:.1}" width="{:.0}" height="3" fill="#3b82f6"/>"##, axis_y, width);
+    for (at, label) in svg_ticks(from, to, width) {
+        let tick_x = x(at);
+        svg += &format!(
+            r##"<line x1="{tick_x:.1}" y1="{:.1}" x2="{tick_x:.1}" y2="{:.1}" stroke="#9ca3af"/>"##,
+            axis_y,
+            axis_y + 12.0
+        );
+        svg += &format!(
+            r##"<text x="{:.1}" y="{:.1}" font-size="11" fill="#4b5563">{}</text>"##,
+            tick_x + 3.0,
+            axis_y + 24.0,
+            escape_xml(&label)
+        );
+    }
+
+    svg += "</svg>";
+    svg
+}
+
+async fn render_svg(
+    Reader(pool): Reader,
+    user: Option<AuthUser>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<RenderParams>,
+) -> Result<Response, Response> {
+    let timeline = timelines::find_visible(&pool, id, user.as_ref()).await?;
+    let events = timeline_events(&pool, id).await?;
+
+    let width = params.width.unwrap_or(1200).clamp(200, 4000) as f64;
+    let first = events.iter().map(|event| days(event.start_date)).fold(f64::INFINITY, f64::min);
+    let last = events
+        .iter()
+        .map(|event| days(event.end_date.unwrap_or(event.start_date)))
+        .fold(f64::NEG_INFINITY, f64::max);
+    let (first, last) = if first.is_finite() { (first, last) } else { (0.0, 365.0) };
+    let margin = ((last - first) * 0.05).max(30.0);
+
+    let from = params.from.map(date_days).unwrap_or(first - margin);
+    let to = params.to.map(date_days).unwrap_or(last + margin);
+    if to <= from {
+        return Err((StatusCode::BAD_REQUEST, "`to` must be after `from`").into_response());
+    }
+
+    let svg = render_events_svg(&timeline.title, &events, from, to, width);
+    Ok(([(header::CONTENT_TYPE, "image/svg+xml")], svg).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use super::*;
+
+    fn event(n: usize) -> Event {
+        let at = NaiveDate::from_ymd_opt(2000, 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap();
+        Event {
+            id: Uuid::from_u128(n as u128),
+            title: format!("Event {:06}", n),
+            description: Some("Signed, sealed, \"delivered\"".to_string()),
+            start_date: at,
+            end_date: None,
+            start_date_min: None,
+            start_date_max: None,
+            location: Some("Paris".to_string()),
+            image_url: None,
+            image_alt: None,
+            category: Some("Politics".to_string()),
+            importance: 3,
+            color: None,
+            icon: None,
+            created_at: at,
+            updated_at: at,
+            timeline_id: None,
+            status: "published".to_string(),
+            publish_at: None,
+            views: 0,
+            likes: 0,
+            featured: false,
+            custom_fields: serde_json::json!({}),
+        }
+    }
+
+    fn timeline() -> timelines::Timeline {
+        let at = NaiveDate::from_ymd_opt(2000, 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap();
+        timelines::Timeline {
+            id: Uuid::nil(),
+            owner_id: Uuid::nil(),
+            title: "Treaties".to_string(),
+            description: None,
+            is_private: false,
+            organization_id: None,
+            is_template: false,
+            cloned_from: None,
+            archived_at: None,
+            created_at: at,
+            updated_at: at,
+        }
+    }
+
+    /// The body of a PDF export of `rows`.
+    async fn pdf(rows: Vec<Result<Event, sqlx::Error>>) -> Result<Bytes, axum::Error> {
+        let (rows_sender, receiver) = mpsc::channel(ROWS_AHEAD);
+        let (sender, body) = channel();
+        let count = rows.len();
+        tokio::task::spawn_blocking(move || stream_pdf(&timeline(), count, receiver, sender));
+        for row in rows {
+            rows_sender.send(row).await.unwrap();
+        }
+        drop(rows_sender);
+        axum::body::to_bytes(body, usize::MAX).await
+    }
+
+    #[tokio::test]
+    async fn streams_pdfs_as_rows_arrive() {
+        let pdf = pdf((0..3).map(|n| Ok(event(n))).collect()).await.unwrap();
+        assert!(pdf.starts_with(b"%PDF"));
+    }
+
+    #[tokio::test]
+    async fn aborts_the_pdf_when_the_query_fails() {
+        assert!(pdf(vec![Ok(event(0)), Err(sqlx::Error::RowNotFound)]).await.is_err());
+    }
+
+    async fn collect(format: Format, count: usize) -> String {
+        let (sender, body) = channel();
+        tokio::spawn(pump(stream::iter((0..count).map(|n| Ok(event(n)))), format, sender));
+        let bytes = axum::body::to_bytes(body, usize::MAX).await.unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn quotes_csv_fields_that_need_it() {
+        let csv = collect(Format::Csv, 1).await;
+        assert_eq!(
+            csv,
+            "id,title,description,start_date,end_date,location,category,importance,image_url\r\n\
+             00000000-0000-0000-0000-000000000000,Event 000000,\"Signed, sealed, \"\"delivered\"\"\",\
+             2000-01-01T00:00:00,,Paris,Politics,3,\r\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn streams_json_as_one_array() {
+        let json: Vec<serde_json::Value> = serde_json::from_str(&collect(Format::Json, 3).await).unwrap();
+        assert_eq!(json.len(), 3);
+        assert_eq!(json[2]["title"], "Event 000002");
+        assert!(serde_json::from_str::<Vec<serde_json::Value>>(&collect(Format::Json, 0).await).unwrap().is_empty());
+    }
+
+    /// Exports 500k rows to a client reading them one chunk at a time, and
+    /// checks that the rows read but not yet sent never outgrow the chunks
+    /// the channel holds.
+    #[tokio::test]
+    async fn exports_500k_rows_within_a_memory_budget() {
+        const ROWS: usize = 500_000;
+        let mut row = Vec::new();
+        Format::Csv.row(0, &event(0), &mut row).unwrap();
+        // The chunks queued, the one being sent and the one being filled.
+        let budget = (CHUNKS_AHEAD + 2) * (CHUNK_BYTES + row.len());
+
+        let read = Arc::new(AtomicUsize::new(0));
+        let rows = {
+            let read = read.clone();
+            stream::iter(0..ROWS).map(move |n| {
+                read.fetch_add(1, Ordering::SeqCst);
+                Ok(event(n))
+            })
+        };
+        let (sender, body) = channel();
+        let export = tokio::spawn(pump(rows, Format::Csv, sender));
+
+        // A client that hasn't read anything yet holds the export back.
+        for _ in 0..10 {
+            tokio::task::yield_now().await;
+        }
+        assert!(read.load(Ordering::SeqCst) * row.len() <= budget);
+
+        let mut chunks = body.into_data_stream();
+        let mut received = 0;
+        let mut lines = 0;
+        while let Some(chunk) = chunks.next().await {
+            let chunk = chunk.unwrap();
+            assert!(chunk.len() < CHUNK_BYTES + row.len());
+            received += chunk.len();
+            lines += chunk.iter().filter(|&&byte| byte == b'\n').count();
+            let in_flight = (read.load(Ordering::SeqCst) * row.len()).saturating_sub(received);
+            assert!(in_flight <= budget, "{} bytes read ahead of the client", in_flight);
+        }
+
+        export.await.unwrap();
+        assert_eq!(read.load(Ordering::SeqCst), ROWS);
+        assert_eq!(lines, ROWS + 1);
+    }
+
+    #[tokio::test]
+    async fn stops_reading_when_the_client_hangs_up() {
+        let read = Arc::new(AtomicUsize::new(0));
+        let rows = {
+            let read = read.clone();
+            stream::iter(0..100_000).map(move |n| {
+                read.fetch_add(1, Ordering::SeqCst);
+                Ok(event(n))
+            })
+        };
+        let (sender, body) = channel();
+        let export = tokio::spawn(pump(rows, Format::Json, sender));
+        drop(body);
+
+        export.await.unwrap();
+        assert!(read.load(Ordering::SeqCst) < 100_000);
+    }
+
+    #[tokio::test]
+    async fn aborts_the_body_when_the_query_fails() {
+        let rows = stream::iter(vec![Ok(event(0)), Err(sqlx::Error::RowNotFound)]);
+        let (sender, body) = channel();
+        tokio::spawn(pump(rows, Format::Csv, sender));
+        assert!(axum::body::to_bytes(body, usize::MAX).await.is_err());
+    }
+}
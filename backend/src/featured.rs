@@ -0,0 +1,174 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    routing::{get, put},
+    Json, Router,
+};
+use chrono::{Datelike, NaiveDate, Utc};
+use serde::Deserialize;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{auth::AuthUser, db::Reader, timelines, AppState, Event};
+
+/// Lists for the Home page: featured events, the latest additions, a
+/// random event and events that happened on this day in history.
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/events/featured", get(get_featured))
+        .route("/api/events/recent", get(get_recent))
+        .route("/api/events/random", get(get_random))
+        .route("/api/events/on-this-day", get(get_on_this_day))
+        .route("/api/events/:id/featured", put(feature).delete(unfeature))
+}
+
+#[derive(Deserialize)]
+struct LimitQuery {
+    limit: Option<i64>,
+}
+
+/// Public featured events in date order.
+async fn get_featured(
+    Reader(pool): Reader,
+    Query(query): Query<LimitQuery>,
+) -> Result<Json<Vec<Event>>, StatusCode> {
+    let limit = query.limit.unwrap_or(10).clamp(1, 50);
+    let sql = format!(
+        "SELECT e.* FROM events e WHERE e.featured AND {} ORDER BY e.start_date LIMIT $1",
+        timelines::PUBLIC_EVENT
+    );
+    let events = sqlx::query_as::<_, Event>(&sql)
+        .bind(limit)
+        .fetch_all(&pool)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(events))
+}
+
+/// Public events, most recently added first.
+async fn get_recent(
+    Reader(pool): Reader,
+    Query(query): Query<LimitQuery>,
+) -> Result<Json<Vec<Event>>, StatusCode> {
+    let limit = query.limit.unwrap_or(5).clamp(1, 50);
+    let sql = format!(
+        "SELECT e.* FROM events e WHERE {} ORDER BY e.created_at DESC LIMIT $1",
+        timelines::PUBLIC_EVENT
+    );
+    let events = sqlx::query_as::<_, Event>(&sql)
+        .bind(limit)
+        .fetch_all(&pool)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(events))
+}
+
+/// One public event picked at random; 404 when there are none.
+async fn get_random(Reader(pool): Reader) -> Result<Json<Event>, StatusCode> {
+    let sql = format!("SELECT e.* FROM events e WHERE {} ORDER BY random() LIMIT 1", timelines::PUBLIC_EVENT);
+    sqlx::query_as::<_, Event>(&sql)
+        .fetch_optional(&pool)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .map(Json)
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+#[derive(Deserialize)]
+struct OnThisDayQuery {
+    /// Defaults to today's, in UTC.
+    month: Option<u32>,
+    day: Option<u32>,
+    limit: Option<i64>,
+    /// A random selection instead of the most important.
+    #[serde(default)]
+    shuffle: bool,
+}
+
+/// Whether `month` and `day` name a day of some year, February 29 included.
+fn valid_day(month: u32, day: u32) -> bool {
+    NaiveDate::from_ymd_opt(2000, month, day).is_some()
+}
+
+/// Public events that started on the given month and day in any year, the
+/// most important first and then in date order.
+async fn get_on_this_day(
+    Reader(pool): Reader,
+    Query(query): Query<OnThisDayQuery>,
+) -> Result<Json<Vec<Event>>, StatusCode> {
+    let today = Utc::now().date_naive();
+    let month = query.month.unwrap_or(today.month());
+    let day = query.day.unwrap_or(today.day());
+    if !valid_day(month, day) {
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+    let limit = query.limit.unwrap_or(5).clamp(1, 50);
+    let order = match query.shuffle {
+        true => "random()",
+        false => "e.importance DESC, e.start_date",
+    };
+    let sql = format!(
+        "SELECT e.* FROM events e \
+         WHERE EXTRACT(MONTH FROM e.start_date) = $1 AND EXTRACT(DAY FROM e.start_date) = $2 AND {} \
+         ORDER BY {} LIMIT $3",
+        timelines::PUBLIC_EVENT,
+        order
+    );
+    let events = sqlx::query_as::<_, Event>(&sql)
+        .bind(month as i32)
+        .bind(day as i32)
+        .bind(limit)
+        .fetch_all(&pool)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(events))
+}
+
+async fn set_featured(pool: &PgPool, user: &AuthUser, id: Uuid, featured: bool) -> Result<StatusCode, StatusCode> {
+    user.require_admin()?;
+    let result = sqlx::query("UPDATE events SET featured = $2, updated_at = NOW() WHERE id = $1")
+        .bind(id)
+        .bind(featured)
+        .execute(pool)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    match result.rows_affected() {
+        0 => Err(StatusCode::NOT_FOUND),
+        _ => Ok(StatusCode::NO_CONTENT),
+    }
+}
+
+/// Admins pick the featured events.
+async fn feature(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    set_featured(&pool, &user, id, true).await
+}
+
+async fn unfeature(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    set_featured(&pool, &user, id, false).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leap_days_are_valid_days() {
+        assert!(valid_day(2, 29));
+        assert!(valid_day(12, 31));
+        assert!(!valid_day(2, 30));
+        assert!(!valid_day(13, 1));
+        assert!(!valid_day(1, 0));
+    }
+}
@@ -0,0 +1,226 @@
+//! Features operators can switch off without redeploying. A flag's state
+//! comes from `FEATURE_FLAGS` if the configuration pins it, else from the
+//! `feature_flags` table, else from its default. Admins flip stored flags
+//! at runtime; every instance picks the change up within a minute.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, RwLock};
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, put},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+
+use crate::{auth::AuthUser, config::Config, AppState};
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/flags", get(get_flags))
+        .route("/api/admin/flags", get(list_flags))
+        .route("/api/admin/flags/:name", put(set_flag))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Flag {
+    /// Sign-ups without an invite, when `REGISTRATION_MODE` is `open`.
+    PublicRegistration,
+    /// Notifications to `WEBHOOK_URL`.
+    Webhooks,
+}
+
+impl Flag {
+    pub const ALL: &'static [Flag] = &[Flag::PublicRegistration, Flag::Webhooks];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|flag| flag.name() == name)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Flag::PublicRegistration => "public_registration",
+            Flag::Webhooks => "webhooks",
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            Flag::PublicRegistration => "Anyone may sign up; when off, open registration needs an invite.",
+            Flag::Webhooks => "Notable changes are POSTed to the configured webhook URL.",
+        }
+    }
+
+    /// State before anyone sets it. Every flag so far is a feature that
+    /// existed before flags did, so they start on.
+    fn default_enabled(self) -> bool {
+        true
+    }
+}
+
+/// Parses `FEATURE_FLAGS`, e.g. `webhooks=off,public_registration=on`.
+pub fn parse_overrides(value: &str) -> Result<Vec<(Flag, bool)>, String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, state) = pair.split_once('=').ok_or_else(|| format!("expected name=on|off, got {:?}", pair))?;
+            let flag = Flag::parse(name.trim()).ok_or_else(|| format!("unknown flag {:?}", name.trim()))?;
+            match state.trim() {
+                "on" | "true" | "1" => Ok((flag, true)),
+                "off" | "false" | "0" => Ok((flag, false)),
+                other => Err(format!("{} must be on or off, got {:?}", flag.name(), other)),
+            }
+        })
+        .collect()
+}
+
+/// Where a flag's current state comes from.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum Source {
+    Default,
+    Database,
+    /// Pinned by `FEATURE_FLAGS`; admins can't change it.
+    Config,
+}
+
+/// The flags' states, shared through `AppState`. Handlers ask
+/// `flags.enabled(Flag::Webhooks)`; the `feature_flags` job keeps the
+/// stored states in step with the table.
+#[derive(Clone, Default)]
+pub struct Flags {
+    stored: Arc<RwLock<HashMap<Flag, bool>>>,
+    pinned: Arc<HashMap<Flag, bool>>,
+}
+
+impl Flags {
+    pub fn new(config: &Config) -> Self {
+        Self { stored: Arc::default(), pinned: Arc::new(config.feature_flags.iter().copied().collect()) }
+    }
+
+    pub fn enabled(&self, flag: Flag) -> bool {
+        self.state(flag).0
+    }
+
+    fn state(&self, flag: Flag) -> (bool, Source) {
+        if let Some(&enabled) = self.pinned.get(&flag) {
+            return (enabled, Source::Config);
+        }
+        let stored = self.stored.read().ok().and_then(|stored| stored.get(&flag).copied());
+        match stored {
+            Some(enabled) => (enabled, Source::Database),
+            None => (flag.default_enabled(), Source::Default),
+        }
+    }
+
+    fn store(&self, flag: Flag, enabled: bool) {
+        if let Ok(mut stored) = self.stored.write() {
+            stored.insert(flag, enabled);
+        }
+    }
+
+    /// Rereads the table, so flags another instance flipped take effect
+    /// here too. Rows for flags this build doesn't know are ignored.
+    pub async fn reload(&self, pool: &PgPool) -> Result<u64, sqlx::Error> {
+        let rows = sqlx::query_as::<_, (String, bool)>("SELECT name, enabled FROM feature_flags")
+            .fetch_all(pool)
+            .await?;
+        let stored: HashMap<Flag, bool> =
+            rows.iter().filter_map(|(name, enabled)| Some((Flag::parse(name)?, *enabled))).collect();
+        if let Ok(mut current) = self.stored.write() {
+            *current = stored;
+        }
+        Ok(rows.len() as u64)
+    }
+}
+
+/// Every flag's state by name, for the frontend to hide what's off.
+async fn get_flags(State(flags): State<Flags>) -> Json<BTreeMap<&'static str, bool>> {
+    Json(Flag::ALL.iter().map(|flag| (flag.name(), flags.enabled(*flag))).collect())
+}
+
+#[derive(Serialize)]
+struct FlagState {
+    name: &'static str,
+    description: &'static str,
+    enabled: bool,
+    source: Source,
+}
+
+fn flag_state(flags: &Flags, flag: Flag) -> FlagState {
+    let (enabled, source) = flags.state(flag);
+    FlagState { name: flag.name(), description: flag.description(), enabled, source }
+}
+
+async fn list_flags(State(flags): State<Flags>, user: AuthUser) -> Result<Json<Vec<FlagState>>, StatusCode> {
+    user.require_admin()?;
+    Ok(Json(Flag::ALL.iter().map(|flag| flag_state(&flags, *flag)).collect()))
+}
+
+#[derive(Deserialize)]
+struct SetFlag {
+    enabled: bool,
+}
+
+async fn set_flag(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(name): Path<String>,
+    Json(payload): Json<SetFlag>,
+) -> Result<Json<FlagState>, Response> {
+    user.require_admin().map_err(IntoResponse::into_response)?;
+    let flag = Flag::parse(&name).ok_or_else(|| StatusCode::NOT_FOUND.into_response())?;
+    if state.flags.state(flag).1 == Source::Config {
+        return Err((StatusCode::CONFLICT, "this flag is set by FEATURE_FLAGS").into_response());
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO feature_flags (name, enabled, updated_by) VALUES ($1, $2, $3)
+        ON CONFLICT (name) DO UPDATE SET enabled = $2, updated_by = $3, updated_at = NOW()
+        "#,
+    )
+    .bind(flag.name())
+    .bind(payload.enabled)
+    .bind(user.id)
+    .execute(state.db.writer())
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
+    state.flags.store(flag, payload.enabled);
+    tracing::info!(flag = flag.name(), enabled = payload.enabled, admin = %user.id, "feature flag changed");
+
+    Ok(Json(flag_state(&state.flags, flag)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_overrides() {
+        let overrides = parse_overrides("webhooks=off, public_registration=on,").unwrap();
+        assert_eq!(overrides, [(Flag::Webhooks, false), (Flag::PublicRegistration, true)]);
+        assert!(parse_overrides("comments=off").is_err());
+        assert!(parse_overrides("webhooks").is_err());
+        assert!(parse_overrides("webhooks=maybe").is_err());
+    }
+
+    #[test]
+    fn pinned_flags_win_over_stored_ones() {
+        let flags = Flags { pinned: Arc::new(HashMap::from([(Flag::Webhooks, false)])), ..Flags::default() };
+        flags.store(Flag::Webhooks, true);
+        flags.store(Flag::PublicRegistration, false);
+        assert_eq!(flags.state(Flag::Webhooks), (false, Source::Config));
+        assert_eq!(flags.state(Flag::PublicRegistration), (false, Source::Database));
+    }
+
+    #[test]
+    fn flags_start_at_their_default() {
+        assert_eq!(Flags::default().state(Flag::Webhooks), (true, Source::Default));
+    }
+}
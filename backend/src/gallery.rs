@@ -0,0 +1,396 @@
+//! Image galleries on events: `/api/events/:id/images`. The event's own
+//! `image_url` remains the one image cards and previews show; adding a
+//! first image to an event without one sets it, and removing that image
+//! hands the role to the gallery's first. Editors can also choose it, the
+//! cover, themselves, with a crop `/api/events/:id/thumbnail` applies.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Redirect, Response},
+    routing::{get, patch, put},
+    Json, Router,
+};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    auth::AuthUser,
+    config::Config,
+    db::{events::Events, Reader},
+    images::{self, ImageProxy},
+    sanitize, timelines, validation_error, AppState,
+};
+
+/// Most images one event's gallery holds.
+const MAX_IMAGES: i64 = 50;
+/// Width of thumbnails asked for without one; cards are about this wide.
+const DEFAULT_THUMBNAIL_WIDTH: u32 = 480;
+/// Browsers recheck thumbnails this often, in seconds, so a new cover or
+/// crop shows up soon after it is saved.
+const THUMBNAIL_MAX_AGE: u32 = 300;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/events/:id/images", get(list_images).post(add_image))
+        .route("/api/events/:id/images/order", put(reorder_images))
+        .route("/api/events/:id/cover", put(set_cover))
+        .route("/api/events/:id/thumbnail", get(thumbnail))
+        .route(
+            "/api/events/:id/images/:image_id",
+            patch(update_image).delete(remove_image),
+        )
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+struct EventImage {
+    id: Uuid,
+    url: String,
+    /// Describes the image for screen readers.
+    alt: Option<String>,
+    caption: Option<String>,
+    position: i32,
+    /// `[x, y, width, height]` shown when it is the cover; see `images::valid_crop`.
+    crop: Option<Vec<f64>>,
+    created_at: NaiveDateTime,
+}
+
+#[derive(Deserialize, Validate)]
+struct ImageInput {
+    #[validate(length(min = 1, max = 512), custom(function = "images::valid_url"))]
+    url: String,
+    #[validate(length(max = 255))]
+    alt: Option<String>,
+    #[validate(length(max = 500))]
+    caption: Option<String>,
+}
+
+/// Body of the PATCH; omitted fields are left alone and empty ones cleared.
+#[derive(Deserialize, Validate)]
+struct ImageChanges {
+    #[validate(length(max = 255))]
+    alt: Option<String>,
+    #[validate(length(max = 500))]
+    caption: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Cover {
+    image_id: Uuid,
+    /// `None` shows the whole image.
+    crop: Option<Vec<f64>>,
+}
+
+#[derive(Deserialize)]
+struct ThumbnailQuery {
+    width: Option<u32>,
+}
+
+#[derive(Deserialize)]
+struct Order {
+    /// Every image of the event, in the new order.
+    ids: Vec<Uuid>,
+}
+
+/// Sanitized, with blank text as `None`.
+fn text(value: Option<String>) -> Option<String> {
+    value.map(|value| sanitize::text(&value)).filter(|value| !value.trim().is_empty())
+}
+
+/// Whether `ids` is exactly the event's images, each once.
+fn same_images(ids: &[Uuid], current: &[Uuid]) -> bool {
+    let mut ids = ids.to_vec();
+    let mut current = current.to_vec();
+    ids.sort();
+    current.sort();
+    ids == current
+}
+
+async fn gallery(pool: &PgPool, event_id: Uuid) -> Result<Vec<EventImage>, sqlx::Error> {
+    sqlx::query_as::<_, EventImage>("SELECT * FROM event_images WHERE event_id = $1 ORDER BY position, created_at")
+        .bind(event_id)
+        .fetch_all(pool)
+        .await
+}
+
+async fn list_images(
+    Reader(pool): Reader,
+    State(events): State<Events>,
+    user: Option<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<EventImage>>, Response> {
+    timelines::ensure_event_visible(&pool, &events, user.as_ref(), id).await?;
+    gallery(&pool, id)
+        .await
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())
+}
+
+/// Adds an image at the end of the gallery.
+async fn add_image(
+    State(pool): State<PgPool>,
+    State(events): State<Events>,
+    user: Option<AuthUser>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<ImageInput>,
+) -> Result<Json<Vec<EventImage>>, Response> {
+    payload.validate().map_err(validation_error)?;
+    timelines::ensure_event_writable(&pool, &events, user.as_ref(), id).await?;
+    let internal = |_| StatusCode::INTERNAL_SERVER_ERROR.into_response();
+
+    let mut tx = pool.begin().await.map_err(internal)?;
+    // Locks the event so concurrent additions get distinct positions.
+    let count = sqlx::query_scalar::<_, i64>(
+        "SELECT (SELECT COUNT(*) FROM event_images WHERE event_id = e.id) FROM events e WHERE e.id = $1 FOR UPDATE",
+    )
+    .bind(id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(internal)?
+    .ok_or_else(|| StatusCode::NOT_FOUND.into_response())?;
+    if count >= MAX_IMAGES {
+        return Err((StatusCode::CONFLICT, format!("an event can have at most {} images", MAX_IMAGES)).into_response());
+    }
+    let alt = text(payload.alt);
+    sqlx::query("INSERT INTO event_images (event_id, url, alt, caption, position) VALUES ($1, $2, $3, $4, $5)")
+        .bind(id)
+        .bind(&payload.url)
+        .bind(&alt)
+        .bind(text(payload.caption))
+        .bind(count as i32)
+        .execute(&mut *tx)
+        .await
+        .map_err(internal)?;
+    sqlx::query(
+        "UPDATE events SET image_url = $2, image_alt = $3, updated_at = NOW() WHERE id = $1 AND image_url IS NULL",
+    )
+    .bind(id)
+    .bind(&payload.url)
+    .bind(&alt)
+    .execute(&mut *tx)
+    .await
+    .map_err(internal)?;
+    tx.commit().await.map_err(internal)?;
+
+    gallery(&pool, id).await.map(Json).map_err(internal)
+}
+
+async fn reorder_images(
+    State(pool): State<PgPool>,
+    State(events): State<Events>,
+    user: Option<AuthUser>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<Order>,
+) -> Result<Json<Vec<EventImage>>, Response> {
+    timelines::ensure_event_writable(&pool, &events, user.as_ref(), id).await?;
+    let internal = |_| StatusCode::INTERNAL_SERVER_ERROR.into_response();
+
+    let mut tx = pool.begin().await.map_err(internal)?;
+    let current = sqlx::query_scalar::<_, Uuid>("SELECT id FROM event_images WHERE event_id = $1 FOR UPDATE")
+        .bind(id)
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(internal)?;
+    if !same_images(&payload.ids, &current) {
+        return Err((StatusCode::UNPROCESSABLE_ENTITY, "list every image of the event once").into_response());
+    }
+    sqlx::query(
+        r#"
+        UPDATE event_images i SET position = o.position - 1
+        FROM UNNEST($2::uuid[]) WITH ORDINALITY AS o(id, position)
+        WHERE i.id = o.id AND i.event_id = $1
+        "#,
+    )
+    .bind(id)
+    .bind(&payload.ids)
+    .execute(&mut *tx)
+    .await
+    .map_err(internal)?;
+    tx.commit().await.map_err(internal)?;
+
+    gallery(&pool, id).await.map(Json).map_err(internal)
+}
+
+async fn update_image(
+    State(pool): State<PgPool>,
+    State(events): State<Events>,
+    user: Option<AuthUser>,
+    Path((id, image_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<ImageChanges>,
+) -> Result<Json<Vec<EventImage>>, Response> {
+    payload.validate().map_err(validation_error)?;
+    timelines::ensure_event_writable(&pool, &events, user.as_ref(), id).await?;
+    let internal = |_| StatusCode::INTERNAL_SERVER_ERROR.into_response();
+
+    let mut tx = pool.begin().await.map_err(internal)?;
+    let image = sqlx::query_as::<_, EventImage>(
+        r#"
+        UPDATE event_images SET
+            alt = CASE WHEN $3 THEN $4 ELSE alt END,
+            caption = CASE WHEN $5 THEN $6 ELSE caption END
+        WHERE id = $1 AND event_id = $2
+        RETURNING *
+        "#,
+    )
+    .bind(image_id)
+    .bind(id)
+    .bind(payload.alt.is_some())
+    .bind(text(payload.alt))
+    .bind(payload.caption.is_some())
+    .bind(text(payload.caption))
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(internal)?
+    .ok_or_else(|| StatusCode::NOT_FOUND.into_response())?;
+    // Cards describe the event's image the same way.
+    sqlx::query("UPDATE events SET image_alt = $3, updated_at = NOW() WHERE id = $1 AND image_url = $2")
+        .bind(id)
+        .bind(&image.url)
+        .bind(&image.alt)
+        .execute(&mut *tx)
+        .await
+        .map_err(internal)?;
+    tx.commit().await.map_err(internal)?;
+
+    gallery(&pool, id).await.map(Json).map_err(internal)
+}
+
+/// Makes the image the event's cover, framed by `crop`.
+async fn set_cover(
+    State(pool): State<PgPool>,
+    State(events): State<Events>,
+    user: Option<AuthUser>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<Cover>,
+) -> Result<Json<Vec<EventImage>>, Response> {
+    if let Some(crop) = &payload.crop {
+        images::valid_crop(crop)
+            .map_err(|_| (StatusCode::UNPROCESSABLE_ENTITY, "the crop must lie inside the image").into_response())?;
+    }
+    timelines::ensure_event_writable(&pool, &events, user.as_ref(), id).await?;
+    let internal = |_| StatusCode::INTERNAL_SERVER_ERROR.into_response();
+
+    let mut tx = pool.begin().await.map_err(internal)?;
+    let image = sqlx::query_as::<_, EventImage>(
+        "UPDATE event_images SET crop = $3 WHERE id = $1 AND event_id = $2 RETURNING *",
+    )
+    .bind(payload.image_id)
+    .bind(id)
+    .bind(&payload.crop)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(internal)?
+    .ok_or_else(|| StatusCode::NOT_FOUND.into_response())?;
+    sqlx::query("UPDATE events SET image_url = $2, image_alt = $3, updated_at = NOW() WHERE id = $1")
+        .bind(id)
+        .bind(&image.url)
+        .bind(&image.alt)
+        .execute(&mut *tx)
+        .await
+        .map_err(internal)?;
+    tx.commit().await.map_err(internal)?;
+
+    gallery(&pool, id).await.map(Json).map_err(internal)
+}
+
+/// The event's image as cards show it: cut to the cover's crop and scaled
+/// to `width`. Images on this site are not processed and are redirected to.
+async fn thumbnail(
+    Reader(pool): Reader,
+    State(events): State<Events>,
+    State(proxy): State<ImageProxy>,
+    State(config): State<Arc<Config>>,
+    user: Option<AuthUser>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<ThumbnailQuery>,
+) -> Result<Response, Response> {
+    timelines::ensure_event_visible(&pool, &events, user.as_ref(), id).await?;
+    let (url, crop) = sqlx::query_as::<_, (Option<String>, Option<Vec<f64>>)>(
+        r#"
+        SELECT e.image_url, (
+            SELECT i.crop FROM event_images i
+            WHERE i.event_id = e.id AND i.url = e.image_url
+            ORDER BY i.position
+            LIMIT 1
+        )
+        FROM events e WHERE e.id = $1
+        "#,
+    )
+    .bind(id)
+    .fetch_optional(&pool)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?
+    .ok_or_else(|| StatusCode::NOT_FOUND.into_response())?;
+    let url = url.ok_or_else(|| StatusCode::NOT_FOUND.into_response())?;
+    if url.starts_with('/') {
+        return Ok(Redirect::temporary(&url).into_response());
+    }
+
+    let width = query.width.unwrap_or(DEFAULT_THUMBNAIL_WIDTH);
+    let (content_type, body) = proxy.thumbnail(&config, &url, crop, width).await?;
+    Ok(images::image_response(content_type, body, THUMBNAIL_MAX_AGE))
+}
+
+/// Removes the image and closes the gap it leaves in the order.
+async fn remove_image(
+    State(pool): State<PgPool>,
+    State(events): State<Events>,
+    user: Option<AuthUser>,
+    Path((id, image_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<Vec<EventImage>>, Response> {
+    timelines::ensure_event_writable(&pool, &events, user.as_ref(), id).await?;
+    let internal = |_| StatusCode::INTERNAL_SERVER_ERROR.into_response();
+
+    let mut tx = pool.begin().await.map_err(internal)?;
+    let (url, position) = sqlx::query_as::<_, (String, i32)>(
+        "DELETE FROM event_images WHERE id = $1 AND event_id = $2 RETURNING url, position",
+    )
+    .bind(image_id)
+    .bind(id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(internal)?
+    .ok_or_else(|| StatusCode::NOT_FOUND.into_response())?;
+    sqlx::query("UPDATE event_images SET position = position - 1 WHERE event_id = $1 AND position > $2")
+        .bind(id)
+        .bind(position)
+        .execute(&mut *tx)
+        .await
+        .map_err(internal)?;
+    sqlx::query(
+        r#"
+        UPDATE events SET
+            image_url = (SELECT url FROM event_images WHERE event_id = $1 ORDER BY position LIMIT 1),
+            image_alt = (SELECT alt FROM event_images WHERE event_id = $1 ORDER BY position LIMIT 1),
+            updated_at = NOW()
+        WHERE id = $1 AND image_url = $2
+        "#,
+    )
+    .bind(id)
+    .bind(&url)
+    .execute(&mut *tx)
+    .await
+    .map_err(internal)?;
+    tx.commit().await.map_err(internal)?;
+
+    gallery(&pool, id).await.map(Json).map_err(internal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reordering_must_list_every_image_once() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        assert!(same_images(&[b, a], &[a, b]));
+        assert!(!same_images(&[a], &[a, b]));
+        assert!(!same_images(&[a, a], &[a, b]));
+        assert!(!same_images(&[a, b, Uuid::new_v4()], &[a, b]));
+    }
+}
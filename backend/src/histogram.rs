@@ -0,0 +1,234 @@
+use axum::{
+    extract::Query,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+use sqlx::QueryBuilder;
+use uuid::Uuid;
+
+use crate::{
+    auth::AuthUser,
+    db::Reader,
+    timelines::{self, Access},
+    AppState,
+};
+
+/// Most buckets one response may hold; wider ranges need a coarser
+/// granularity.
+const MAX_BUCKETS: i64 = 5_000;
+
+pub fn routes() -> Router<AppState> {
+    Router::new().route("/api/events/histogram", get(get_histogram))
+}
+
+/// Length of the periods events are counted in.
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum Granularity {
+    Year,
+    Decade,
+    Century,
+}
+
+impl Granularity {
+    fn years(self) -> i64 {
+        match self {
+            Granularity::Year => 1,
+            Granularity::Decade => 10,
+            Granularity::Century => 100,
+        }
+    }
+
+    /// First year of the period `year` falls in.
+    fn start(self, year: i64) -> i64 {
+        year.div_euclid(self.years()) * self.years()
+    }
+}
+
+#[derive(Deserialize)]
+struct HistogramQuery {
+    granularity: Granularity,
+    /// First and last start dates counted, inclusive. Without them the
+    /// buckets run from the first event to the last.
+    from: Option<NaiveDate>,
+    to: Option<NaiveDate>,
+    /// Count one timeline's events rather than all public ones.
+    timeline_id: Option<Uuid>,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+struct Bucket {
+    /// First year of the period, e.g. `1910` for the 1910s. Years are
+    /// astronomical, as in the frontend: `0` is 1 BCE, `-9` is 10 BCE.
+    start: i64,
+    count: i64,
+}
+
+/// Number of events starting in each period, earliest first, with a zero
+/// for every empty period in the range.
+///
+/// Postgres truncates the dates to years; the periods are formed here,
+/// because `date_trunc`'s centuries start at year 1 and its BCE years have
+/// no year 0, neither of which lines up with the frontend's calendar.
+async fn get_histogram(
+    Reader(pool): Reader,
+    user: Option<AuthUser>,
+    Query(query): Query<HistogramQuery>,
+) -> Result<Json<Vec<Bucket>>, Response> {
+    let range = match (query.from, query.to) {
+        (Some(from), Some(to)) if to < from => {
+            return Err((StatusCode::BAD_REQUEST, "`to` must not be before `from`").into_response());
+        }
+        (Some(from), Some(to)) => Some((from.year() as i64, to.year() as i64)),
+        _ => None,
+    };
+    if let Some((first, last)) = range {
+        check_size(first, last, query.granularity).map_err(IntoResponse::into_response)?;
+    }
+
+    let mut builder = QueryBuilder::new(
+        "SELECT extract(year FROM date_trunc('year', e.start_date))::int AS year, COUNT(*) FROM events e WHERE ",
+    );
+    match query.timeline_id {
+        Some(id) => {
+            let timeline = timelines::find_visible(&pool, id, user.as_ref()).await?;
+            // Drafts count for the people who can see them on the timeline.
+            let editor = timelines::access(&pool, &timeline, user.as_ref()).await? >= Access::Edit;
+            builder
+                .push("e.timeline_id = ")
+                .push_bind(id)
+                .push(" AND (")
+                .push_bind(editor)
+                .push(" OR e.status = 'published')");
+        }
+        None => {
+            builder.push(timelines::PUBLIC_EVENT);
+        }
+    }
+    if let Some(from) = query.from {
+        builder.push(" AND e.start_date >= ").push_bind(from.and_hms_opt(0, 0, 0));
+    }
+    if let Some(to) = query.to.and_then(|to| to.succ_opt()) {
+        builder.push(" AND e.start_date < ").push_bind(to.and_hms_opt(0, 0, 0));
+    }
+    builder.push(" GROUP BY 1");
+
+    let years: Vec<(i64, i64)> = builder
+        .build_query_as::<(i32, i64)>()
+        .fetch_all(&pool)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?
+        .into_iter()
+        .map(|(year, count)| (astronomical(year), count))
+        .collect();
+
+    // Only one end given: the other comes from the events.
+    let range = range.or_else(|| {
+        let first = query.from.map(|from| from.year() as i64).or(years.iter().map(|(year, _)| *year).min())?;
+        let last = query.to.map(|to| to.year() as i64).or(years.iter().map(|(year, _)| *year).max())?;
+        Some((first, last))
+    });
+    let Some((first, last)) = range else {
+        return Ok(Json(Vec::new()));
+    };
+    check_size(first, last, query.granularity).map_err(IntoResponse::into_response)?;
+
+    Ok(Json(buckets(&years, query.granularity, first, last)))
+}
+
+/// Postgres numbers BCE years from -1 with no year 0; astronomical years
+/// call 1 BCE year 0.
+fn astronomical(year: i32) -> i64 {
+    if year < 0 {
+        year as i64 + 1
+    } else {
+        year as i64
+    }
+}
+
+fn check_size(first: i64, last: i64, granularity: Granularity) -> Result<(), (StatusCode, &'static str)> {
+    let count = (granularity.start(last) - granularity.start(first)) / granularity.years() + 1;
+    if count > MAX_BUCKETS {
+        return Err((StatusCode::UNPROCESSABLE_ENTITY, "range too wide for this granularity"));
+    }
+    Ok(())
+}
+
+/// Sums per-year counts into periods, one bucket per period from the one
+/// holding `first` to the one holding `last`.
+fn buckets(years: &[(i64, i64)], granularity: Granularity, first: i64, last: i64) -> Vec<Bucket> {
+    let step = granularity.years();
+    let first = granularity.start(first);
+    let last = granularity.start(last);
+    let mut buckets: Vec<Bucket> = (0..=(last - first) / step)
+        .map(|index| Bucket { start: first + index * step, count: 0 })
+        .collect();
+    for (year, count) in years {
+        let index = (granularity.start(*year) - first) / step;
+        if let Some(bucket) = usize::try_from(index).ok().and_then(|index| buckets.get_mut(index)) {
+            bucket.count += count;
+        }
+    }
+    buckets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn counts(buckets: &[Bucket]) -> Vec<(i64, i64)> {
+        buckets.iter().map(|bucket| (bucket.start, bucket.count)).collect()
+    }
+
+    #[test]
+    fn fills_empty_periods_with_zero() {
+        let years = [(1912, 2), (1915, 1), (1941, 4)];
+        let buckets = buckets(&years, Granularity::Decade, 1912, 1941);
+        assert_eq!(counts(&buckets), [(1910, 3), (1920, 0), (1930, 0), (1940, 4)]);
+    }
+
+    #[test]
+    fn a_range_without_events_is_all_zeros() {
+        let buckets = buckets(&[], Granularity::Century, 1801, 2024);
+        assert_eq!(counts(&buckets), [(1800, 0), (1900, 0), (2000, 0)]);
+    }
+
+    #[test]
+    fn years_outside_the_range_are_left_out() {
+        let years = [(1899, 1), (1900, 2), (1902, 3)];
+        let buckets = buckets(&years, Granularity::Year, 1900, 1901);
+        assert_eq!(counts(&buckets), [(1900, 2), (1901, 0)]);
+    }
+
+    #[test]
+    fn bce_years_become_astronomical() {
+        assert_eq!(astronomical(-1), 0);
+        assert_eq!(astronomical(-44), -43);
+        assert_eq!(astronomical(1), 1);
+    }
+
+    #[test]
+    fn bce_periods_continue_across_the_era_boundary() {
+        // 44 BCE, 5 BCE, 1 BCE and 1 CE as Postgres reports them.
+        let years: Vec<(i64, i64)> = [(-44, 1), (-5, 1), (-1, 1), (1, 1)]
+            .into_iter()
+            .map(|(year, count)| (astronomical(year), count))
+            .collect();
+
+        let decades = buckets(&years, Granularity::Decade, -43, 1);
+        assert_eq!(counts(&decades), [(-50, 1), (-40, 0), (-30, 0), (-20, 0), (-10, 1), (0, 2)]);
+
+        let centuries = buckets(&years, Granularity::Century, -43, 1);
+        assert_eq!(counts(&centuries), [(-100, 2), (0, 2)]);
+    }
+
+    #[test]
+    fn limits_the_number_of_buckets() {
+        assert!(check_size(-10_000, 2024, Granularity::Century).is_ok());
+        assert!(check_size(-10_000, 2024, Granularity::Year).is_err());
+    }
+}
@@ -0,0 +1,184 @@
+use axum::{
+    extract::{Path, State},
+    response::Html,
+    routing::get,
+    Router,
+};
+use serde::Serialize;
+
+use crate::{db::events::EventFilter, images, sanitize, timelines, AppState, Event, PaginatedResponse};
+
+/// Size of the first page embedded into `/events`, matching the API default.
+const FIRST_PAGE_LIMIT: i32 = 20;
+
+/// HTML routes that embed the data their page would otherwise fetch on load.
+///
+/// The payload is written into a `<script type="application/json">` tag that
+/// the frontend reads on first render before revalidating in the background.
+/// Event pages also get Open Graph tags so shared links show a preview.
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/events", get(events_page))
+        .route("/events/:id", get(event_page))
+}
+
+#[derive(Serialize)]
+struct InitialData<'a, T> {
+    path: &'a str,
+    data: T,
+}
+
+async fn events_page(State(state): State<AppState>) -> Html<String> {
+    let filter = EventFilter {
+        status: "published".to_string(),
+        limit: FIRST_PAGE_LIMIT as i64,
+        ..EventFilter::default()
+    };
+    // Without data the page still works; it just fetches on load as before.
+    let Ok(page) = state.events.list(&filter).await else {
+        return Html(state.index_html.to_string());
+    };
+    let (data, total) = (page.events, page.total);
+
+    Html(inject(
+        &state.index_html,
+        "/events",
+        &PaginatedResponse {
+            data,
+            total,
+            page: 1,
+            limit: FIRST_PAGE_LIMIT,
+            pages: (total as f64 / FIRST_PAGE_LIMIT as f64).ceil() as i32,
+        },
+    ))
+}
+
+async fn event_page(State(state): State<AppState>, Path(id): Path<uuid::Uuid>) -> Html<String> {
+    // Only public events are embedded; the session token lives in
+    // localStorage, so this request cannot tell who is asking.
+    let visible = timelines::ensure_event_visible(state.db.reader(), &state.events, None, id).await;
+    let event = state.events.find(id).await;
+
+    match (visible, event) {
+        (Ok(()), Ok(Some(event))) => {
+            let path = format!("/events/{}", id);
+            let html = with_meta(&state.index_html, &event_meta(&state.config.public_url, &path, &event));
+            Html(inject(&html, &path, &event))
+        }
+        _ => Html(state.index_html.to_string()),
+    }
+}
+
+/// Longest `og:description`; previews truncate around here anyway.
+const DESCRIPTION_LIMIT: usize = 200;
+
+struct Meta {
+    title: String,
+    description: String,
+    url: String,
+    image: String,
+    image_alt: String,
+}
+
+/// Preview tags for the event. Its text goes through the sanitizer again,
+/// for rows stored before text was cleaned on write.
+fn event_meta(public_url: &str, path: &str, event: &Event) -> Meta {
+    let title = sanitize::text(&event.title);
+    let mut summary = event.start_date.format("%B %-d, %Y").to_string();
+    if let Some(location) = &event.location {
+        summary = format!("{} · {}", summary, sanitize::text(location));
+    }
+    let description = match event.description.as_deref().map(sanitize::text) {
+        Some(description) if !description.trim().is_empty() => {
+            let text: String = description.split_whitespace().collect::<Vec<_>>().join(" ");
+            if text.chars().count() > DESCRIPTION_LIMIT {
+                let cut: String = text.chars().take(DESCRIPTION_LIMIT - 1).collect();
+                format!("{} — {}…", summary, cut.trim_end())
+            } else {
+                format!("{} — {}", summary, text)
+            }
+        }
+        _ => summary,
+    };
+    let image = match &event.image_url {
+        Some(url) if images::valid_url(url).is_err() => None,
+        Some(url) if url.starts_with('/') => Some(format!("{}{}", public_url, url)),
+        Some(url) => Some(url.clone()),
+        None => None,
+    };
+    let image_alt = match &image {
+        Some(_) => event.image_alt.as_deref().map_or_else(|| title.clone(), sanitize::text),
+        None => "Timeline Explorer".to_string(),
+    };
+    let image = image.unwrap_or_else(|| format!("{}/icons/icon-512.png", public_url));
+
+    Meta {
+        title,
+        description,
+        url: format!("{}{}", public_url, path),
+        image,
+        image_alt,
+    }
+}
+
+fn escape_attr(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Sets the page `<title>` and adds Open Graph / Twitter card tags before
+/// `</head>`.
+fn with_meta(html: &str, meta: &Meta) -> String {
+    let title = escape_attr(&meta.title);
+    let description = escape_attr(&meta.description);
+    let tags = [
+        ("og:type", "article".to_string()),
+        ("og:site_name", "Timeline Explorer".to_string()),
+        ("og:title", title.clone()),
+        ("og:description", description.clone()),
+        ("og:url", escape_attr(&meta.url)),
+        ("og:image", escape_attr(&meta.image)),
+        ("og:image:alt", escape_attr(&meta.image_alt)),
+        ("twitter:card", "summary_large_image".to_string()),
+    ]
+    .iter()
+    .map(|(property, content)| format!(r#"<meta property="{}" content="{}">"#, property, content))
+    .chain(std::iter::once(format!(r#"<meta name="description" content="{}">"#, description)))
+    .collect::<Vec<_>>()
+    .join("\n    ");
+
+    let html = match (html.find("<title>"), html.find("</title>")) {
+        (Some(start), Some(end)) if start < end => format!(
+            "{}<title>{} – Timeline Explorer</title>{}",
+            &html[..start],
+            title,
+            &html[end + "</title>".len()..]
+        ),
+        _ => html.to_string(),
+    };
+    match html.find("</head>") {
+        Some(index) => format!("{}    {}\n{}", &html[..index], tags, &html[index..]),
+        None => html,
+    }
+}
+
+/// Embeds `data` into `html` just before `</body>`.
+///
+/// `<` is escaped so event text can never close the script element early.
+pub fn inject<T: Serialize>(html: &str, path: &str, data: &T) -> String {
+    let json = match serde_json::to_string(&InitialData { path, data }) {
+        Ok(json) => json.replace('<', "\\u003c"),
+        Err(_) => return html.to_string(),
+    };
+    let script = format!(
+        r#"<script id="initial-data" type="application/json">{}</script>"#,
+        json
+    );
+
+    match html.rfind("</body>") {
+        Some(index) => format!("{}{}\n{}", &html[..index], script, &html[index..]),
+        None => format!("{}{}", html, script),
+    }
+}
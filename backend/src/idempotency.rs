@@ -0,0 +1,252 @@
+use std::sync::Arc;
+
+use axum::{
+    body::{Body, HttpBody},
+    extract::{Request, State},
+    http::{header, HeaderValue, Method, StatusCode, Uri},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{auth::AuthUser, config::Config, layers::BODY_LIMIT};
+
+const KEY_HEADER: &str = "idempotency-key";
+/// Set on responses served from the keys table.
+const REPLAYED_HEADER: &str = "idempotent-replayed";
+const MAX_KEY_LENGTH: usize = 255;
+/// Routes whose responses carry session or impersonation tokens, which
+/// must not be written to the keys table.
+const SECRET_PREFIXES: &[&str] = &["/api/auth/", "/api/admin/impersonate/"];
+
+#[derive(sqlx::FromRow)]
+struct Stored {
+    request_hash: String,
+    status: Option<i16>,
+    content_type: Option<String>,
+    body: Option<Vec<u8>>,
+}
+
+/// Makes POST requests carrying an `Idempotency-Key` header safe to retry.
+///
+/// The first request with a key runs as usual and its response is stored
+/// for 24 hours. A retry with the same key and the same request gets the
+/// stored response back instead of running again; reusing the key for a
+/// different request is a 422, and retrying while the first request is
+/// still running a 409. Keys are scoped to the caller, so anonymous
+/// requests, which could not be told apart, ignore them. Server errors are
+/// not stored, so those requests can be retried for real, and nor are
+/// downloads or streamed responses. Responses that hand out tokens are
+/// never stored: the key is ignored on those routes.
+pub async fn replay(
+    State(pool): State<PgPool>,
+    State(config): State<Arc<Config>>,
+    user: Option<AuthUser>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if request.method() != Method::POST || hands_out_tokens(request.uri().path()) {
+        return next.run(request).await;
+    }
+    let Some(user) = user else {
+        return next.run(request).await;
+    };
+    let Some(key) = request.headers().get(KEY_HEADER) else {
+        return next.run(request).await;
+    };
+    let Some(key) = key
+        .to_str()
+        .ok()
+        .filter(|key| !key.is_empty() && key.len() <= MAX_KEY_LENGTH)
+        .map(str::to_owned)
+    else {
+        return error(StatusCode::BAD_REQUEST, "Idempotency-Key must be 1 to 255 visible ASCII characters");
+    };
+    let user_id = user.id;
+
+    let (parts, body) = request.into_parts();
+    // Uploads may be larger than other bodies; the route's own limit still
+    // applies once the body is passed on.
+    let Ok(body) = axum::body::to_bytes(body, BODY_LIMIT.max(config.max_upload_bytes)).await else {
+        return error(StatusCode::PAYLOAD_TOO_LARGE, "request body is too large for this endpoint");
+    };
+    let hash = request_hash(&parts.method, &parts.uri, &body);
+
+    match claim(&pool, user_id, &key, &hash).await {
+        Ok(None) => {}
+        Ok(Some(stored)) => return replayed(stored, &hash),
+        Err(error) => {
+            tracing::error!(%error, "could not claim idempotency key");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    }
+
+    let response = next.run(Request::from_parts(parts, Body::from(body))).await;
+    save(&pool, user_id, &key, response).await
+}
+
+fn hands_out_tokens(path: &str) -> bool {
+    SECRET_PREFIXES.iter().any(|prefix| path.starts_with(prefix))
+}
+
+fn error(status: StatusCode, message: &str) -> Response {
+    (status, Json(serde_json::json!({ "error": message }))).into_response()
+}
+
+/// Identifies a request by its method, path, query and body.
+fn request_hash(method: &Method, uri: &Uri, body: &[u8]) -> String {
+    let target = uri.path_and_query().map_or(uri.path(), |target| target.as_str());
+    let mut hasher = Sha256::new();
+    hasher.update(method.as_str());
+    hasher.update(b" ");
+    hasher.update(target);
+    hasher.update(b"\n");
+    hasher.update(body);
+    format!("{:x}", hasher.finalize())
+}
+
+/// Records the key as in progress, taking over a row that has expired.
+/// Returns the existing row when the key is still in use.
+async fn claim(pool: &PgPool, user_id: Uuid, key: &str, hash: &str) -> Result<Option<Stored>, sqlx::Error> {
+    let claimed = sqlx::query(
+        r#"
+        INSERT INTO idempotency_keys (user_id, key, request_hash)
+        VALUES ($1, $2, $3)
+        ON CONFLICT (user_id, key) DO UPDATE
+        SET request_hash = EXCLUDED.request_hash,
+            status = NULL,
+            content_type = NULL,
+            body = NULL,
+            created_at = NOW()
+        WHERE idempotency_keys.created_at < NOW() - INTERVAL '24 hours'
+        "#,
+    )
+    .bind(user_id)
+    .bind(key)
+    .bind(hash)
+    .execute(pool)
+    .await?
+    .rows_affected()
+        > 0;
+    if claimed {
+        return Ok(None);
+    }
+
+    let stored = sqlx::query_as::<_, Stored>(
+        "SELECT request_hash, status, content_type, body FROM idempotency_keys WHERE user_id = $1 AND key = $2",
+    )
+    .bind(user_id)
+    .bind(key)
+    .fetch_optional(pool)
+    .await?;
+    // Released by a failed first request in the meantime; ask for a retry
+    // rather than racing for the key again.
+    Ok(Some(stored.unwrap_or(Stored {
+        request_hash: hash.to_string(),
+        status: None,
+        content_type: None,
+        body: None,
+    })))
+}
+
+fn replayed(stored: Stored, hash: &str) -> Response {
+    if stored.request_hash != hash {
+        return error(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "Idempotency-Key was already used for a different request",
+        );
+    }
+    let Some(status) = stored.status.and_then(|status| StatusCode::from_u16(status as u16).ok()) else {
+        return error(StatusCode::CONFLICT, "a request with this Idempotency-Key is still in progress");
+    };
+
+    let mut response = (status, stored.body.unwrap_or_default()).into_response();
+    let headers = response.headers_mut();
+    match stored.content_type.and_then(|value| HeaderValue::from_str(&value).ok()) {
+        Some(content_type) => headers.insert(header::CONTENT_TYPE, content_type),
+        None => headers.remove(header::CONTENT_TYPE),
+    };
+    headers.insert(REPLAYED_HEADER, HeaderValue::from_static("true"));
+    response
+}
+
+/// Stores the response for replay, or releases the key after a server
+/// error or for a response that is not worth keeping in memory: a
+/// download, or a body streamed without a known length.
+async fn save(pool: &PgPool, user_id: Uuid, key: &str, response: Response) -> Response {
+    let streamed =
+        response.headers().contains_key(header::CONTENT_DISPOSITION) || response.body().size_hint().exact().is_none();
+    if response.status().is_server_error() || streamed {
+        release(pool, user_id, key).await;
+        return response;
+    }
+    let (parts, body) = response.into_parts();
+    let Ok(body) = axum::body::to_bytes(body, usize::MAX).await else {
+        release(pool, user_id, key).await;
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+
+    let content_type = parts.headers.get(header::CONTENT_TYPE).and_then(|value| value.to_str().ok());
+    let saved = sqlx::query(
+        "UPDATE idempotency_keys SET status = $3, content_type = $4, body = $5 WHERE user_id = $1 AND key = $2",
+    )
+    .bind(user_id)
+    .bind(key)
+    .bind(parts.status.as_u16() as i16)
+    .bind(content_type)
+    .bind(body.as_ref())
+    .execute(pool)
+    .await;
+    if let Err(error) = saved {
+        tracing::error!(%error, "could not store idempotent response");
+    }
+
+    Response::from_parts(parts, Body::from(body))
+}
+
+async fn release(pool: &PgPool, user_id: Uuid, key: &str) {
+    let released = sqlx::query("DELETE FROM idempotency_keys WHERE user_id = $1 AND key = $2")
+        .bind(user_id)
+        .bind(key)
+        .execute(pool)
+        .await;
+    if let Err(error) = released {
+        tracing::error!(%error, "could not release idempotency key");
+    }
+}
+
+/// Deletes keys older than 24 hours.
+pub async fn purge(pool: &PgPool) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM idempotency_keys WHERE created_at < NOW() - INTERVAL '24 hours'")
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn request_hash_covers_target_and_body() {
+        let hash = |uri: &str, body: &str| request_hash(&Method::POST, &uri.parse().unwrap(), body.as_bytes());
+
+        assert_eq!(hash("/api/events", "{}"), hash("/api/events", "{}"));
+        assert_ne!(hash("/api/events", "{}"), hash("/api/events", r#"{"title":"x"}"#));
+        assert_ne!(hash("/api/events", "{}"), hash("/api/events?force=true", "{}"));
+        assert_ne!(hash("/api/events", "{}"), hash("/api/events/batch", "{}"));
+    }
+
+    #[test]
+    fn token_responses_are_not_stored() {
+        assert!(hands_out_tokens("/api/auth/login"));
+        assert!(hands_out_tokens("/api/auth/refresh"));
+        assert!(hands_out_tokens("/api/auth/oauth/github/callback"));
+        assert!(hands_out_tokens("/api/admin/impersonate/8c1f0e34-5a7b-4d2e-9f6a-3b1c2d4e5f60"));
+        assert!(!hands_out_tokens("/api/events"));
+        assert!(!hands_out_tokens("/api/admin/users/8c1f0e34-5a7b-4d2e-9f6a-3b1c2d4e5f60/disable"));
+    }
+}
@@ -0,0 +1,182 @@
+//! Admins using the app as another user, to see what they reported the way
+//! they see it. `POST /api/admin/impersonate/:user_id` hands the admin a
+//! short-lived token for the user; `guard` keeps that token to reading,
+//! writes every request made with it to the audit log, and stops it
+//! working once the impersonation is ended.
+
+use axum::{
+    extract::{Path, Request, State},
+    http::{Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    routing::post,
+    Json, Router,
+};
+use chrono::NaiveDateTime;
+use serde::Serialize;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    audit,
+    auth::{self, AuthUser},
+    AppState,
+};
+
+/// How long an impersonation lasts unless ended first.
+const TTL_MINUTES: i32 = 30;
+/// The one write an impersonation token may make.
+const END_PATH: &str = "/api/impersonation/end";
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/admin/impersonate/:user_id", post(start))
+        .route(END_PATH, post(end))
+}
+
+fn refuse(status: StatusCode, message: &str) -> Response {
+    (status, Json(serde_json::json!({ "error": message }))).into_response()
+}
+
+#[derive(Serialize)]
+struct Started {
+    /// Sent as the bearer token in place of the admin's own.
+    token: String,
+    user_id: Uuid,
+    username: String,
+    expires_at: NaiveDateTime,
+}
+
+/// Starts impersonating another user. Admins can't be impersonated, so the
+/// token never carries more rights than an ordinary user has.
+async fn start(
+    State(state): State<AppState>,
+    admin: AuthUser,
+    Path(user_id): Path<Uuid>,
+) -> Result<Json<Started>, Response> {
+    admin.require_admin().map_err(IntoResponse::into_response)?;
+    if admin.id == user_id {
+        return Err(refuse(StatusCode::CONFLICT, "You can't impersonate yourself."));
+    }
+    let internal = |_| StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    let mut tx = state.db.writer().begin().await.map_err(internal)?;
+    let (username, role) = sqlx::query_as::<_, (String, String)>(
+        "SELECT username, role FROM users WHERE id = $1 AND deleted_at IS NULL",
+    )
+    .bind(user_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(internal)?
+    .ok_or_else(|| StatusCode::NOT_FOUND.into_response())?;
+    if role == "admin" {
+        return Err(refuse(StatusCode::CONFLICT, "Admins can't be impersonated."));
+    }
+
+    let (id, expires_at) = sqlx::query_as::<_, (Uuid, NaiveDateTime)>(
+        r#"
+        INSERT INTO impersonations (admin_id, user_id, expires_at)
+        VALUES ($1, $2, NOW() + make_interval(mins => $3))
+        RETURNING id, expires_at
+        "#,
+    )
+    .bind(admin.id)
+    .bind(user_id)
+    .bind(TTL_MINUTES)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(internal)?;
+    let details = serde_json::json!({ "impersonation": id, "expires_at": expires_at });
+    audit::record(&mut tx, Some(admin.id), "impersonation.started", Some(user_id), details)
+        .await
+        .map_err(internal)?;
+    tx.commit().await.map_err(internal)?;
+
+    let token = auth::issue_impersonation_token(&state.config, user_id, &role, id, expires_at)
+        .map_err(IntoResponse::into_response)?;
+    Ok(Json(Started { token, user_id, username, expires_at }))
+}
+
+/// Ends the impersonation the caller's token was issued for; the token
+/// stops working straight away.
+async fn end(State(pool): State<PgPool>, user: AuthUser) -> Result<StatusCode, StatusCode> {
+    let impersonation = user.impersonation.ok_or(StatusCode::BAD_REQUEST)?;
+    let internal = |_| StatusCode::INTERNAL_SERVER_ERROR;
+    let mut tx = pool.begin().await.map_err(internal)?;
+    let admin = sqlx::query_scalar::<_, Uuid>(
+        "UPDATE impersonations SET ended_at = NOW() WHERE id = $1 AND ended_at IS NULL RETURNING admin_id",
+    )
+    .bind(impersonation)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(internal)?
+    .ok_or(StatusCode::UNAUTHORIZED)?;
+    let details = serde_json::json!({ "impersonation": impersonation });
+    audit::record(&mut tx, Some(admin), "impersonation.ended", Some(user.id), details)
+        .await
+        .map_err(internal)?;
+    tx.commit().await.map_err(internal)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Whether an impersonation token may make this request: it may read
+/// anything its user can, and end itself.
+fn allowed(method: &Method, path: &str) -> bool {
+    matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS) || (*method == Method::POST && path == END_PATH)
+}
+
+/// Lets requests made with an impersonation token through only while the
+/// impersonation lasts and only to read, recording each in the audit log
+/// under the admin behind it. Other requests pass untouched.
+pub async fn guard(State(pool): State<PgPool>, user: Option<AuthUser>, request: Request, next: Next) -> Response {
+    let Some((user_id, impersonation)) = user.and_then(|user| Some((user.id, user.impersonation?))) else {
+        return next.run(request).await;
+    };
+    let internal = |error: sqlx::Error| {
+        tracing::error!(%error, "could not check impersonation");
+        StatusCode::INTERNAL_SERVER_ERROR.into_response()
+    };
+    // Owned copies: the request body isn't `Sync`, so `request` can't be
+    // borrowed across the awaits below.
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    let checked = async {
+        let mut conn = pool.acquire().await?;
+        let admin = sqlx::query_scalar::<_, Uuid>(
+            "SELECT admin_id FROM impersonations WHERE id = $1 AND ended_at IS NULL AND expires_at > NOW()",
+        )
+        .bind(impersonation)
+        .fetch_optional(&mut *conn)
+        .await?;
+        let Some(admin) = admin else {
+            return Ok(None);
+        };
+        let permitted = allowed(&method, &path);
+        let details = serde_json::json!({
+            "impersonation": impersonation,
+            "method": method.as_str(),
+            "path": path,
+            "allowed": permitted,
+        });
+        audit::record(&mut conn, Some(admin), "impersonation.request", Some(user_id), details).await?;
+        Ok::<_, sqlx::Error>(Some(permitted))
+    };
+    match checked.await {
+        Ok(Some(true)) => next.run(request).await,
+        Ok(Some(false)) => refuse(StatusCode::FORBIDDEN, "Impersonation is read-only."),
+        Ok(None) => refuse(StatusCode::UNAUTHORIZED, "The impersonation has ended."),
+        Err(error) => internal(error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn impersonation_only_reads_and_ends_itself() {
+        assert!(allowed(&Method::GET, "/api/timelines"));
+        assert!(allowed(&Method::POST, END_PATH));
+        assert!(!allowed(&Method::POST, "/api/events"));
+        assert!(!allowed(&Method::DELETE, "/api/me"));
+    }
+}
@@ -0,0 +1,171 @@
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{delete, get},
+    Json, Router,
+};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{auth::AuthUser, db::Reader, validation_error, AppState};
+
+/// Letters and digits that can't be mistaken for one another when read
+/// out or typed from paper.
+const CODE_ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+const CODE_LENGTH: usize = 12;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/invites", get(list_invites).post(create_invite))
+        .route("/api/invites/:id", delete(delete_invite))
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+struct Invite {
+    id: Uuid,
+    code: String,
+    created_by: Option<Uuid>,
+    email: Option<String>,
+    max_uses: i32,
+    uses: i32,
+    expires_at: Option<NaiveDateTime>,
+    created_at: NaiveDateTime,
+}
+
+#[derive(Deserialize, Validate)]
+struct NewInvite {
+    /// Restrict the code to one address.
+    #[validate(email, length(max = 255))]
+    email: Option<String>,
+    #[serde(default = "one")]
+    #[validate(range(min = 1, max = 1000))]
+    max_uses: i32,
+    /// Days until the code stops working; never when unset.
+    #[validate(range(min = 1, max = 365))]
+    expires_in_days: Option<i32>,
+}
+
+fn one() -> i32 {
+    1
+}
+
+fn new_code() -> String {
+    let mut bytes = [0u8; CODE_LENGTH];
+    OsRng.fill_bytes(&mut bytes);
+    // 256 is a multiple of the alphabet's 32 letters, so each is as likely.
+    bytes.iter().map(|byte| CODE_ALPHABET[*byte as usize % CODE_ALPHABET.len()] as char).collect()
+}
+
+/// Codes are shown in capitals but accepted in any case and with the
+/// dashes or spaces people add when copying them.
+fn normalize(code: &str) -> String {
+    code.chars().filter(|c| c.is_ascii_alphanumeric()).map(|c| c.to_ascii_uppercase()).collect()
+}
+
+/// Every invite, newest first. Admins only.
+async fn list_invites(Reader(pool): Reader, user: AuthUser) -> Result<Json<Vec<Invite>>, StatusCode> {
+    user.require_admin()?;
+
+    sqlx::query_as::<_, Invite>("SELECT * FROM invites ORDER BY created_at DESC")
+        .fetch_all(&pool)
+        .await
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+async fn create_invite(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Json(payload): Json<NewInvite>,
+) -> Result<Json<Invite>, Response> {
+    user.require_admin().map_err(IntoResponse::into_response)?;
+    payload.validate().map_err(validation_error)?;
+
+    sqlx::query_as::<_, Invite>(
+        r#"
+        INSERT INTO invites (code, created_by, email, max_uses, expires_at)
+        VALUES ($1, $2, $3, $4, NOW() + make_interval(days => $5))
+        RETURNING *
+        "#,
+    )
+    .bind(new_code())
+    .bind(user.id)
+    .bind(payload.email.as_deref().map(str::trim))
+    .bind(payload.max_uses)
+    .bind(payload.expires_in_days)
+    .fetch_one(&pool)
+    .await
+    .map(Json)
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())
+}
+
+/// Withdraws an invite; accounts already registered with it stay.
+async fn delete_invite(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    user.require_admin()?;
+
+    let deleted = sqlx::query("DELETE FROM invites WHERE id = $1")
+        .bind(id)
+        .execute(&pool)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .rows_affected();
+
+    match deleted {
+        0 => Err(StatusCode::NOT_FOUND),
+        _ => Ok(StatusCode::NO_CONTENT),
+    }
+}
+
+/// Uses up one registration on `code` for `email`. Run it in the same
+/// transaction as the account's insert, so a failed registration doesn't
+/// spend the invite. `403 Forbidden` when the code is unknown, used up,
+/// expired or for another address.
+pub async fn redeem(conn: &mut PgConnection, code: &str, email: &str) -> Result<(), StatusCode> {
+    let redeemed = sqlx::query(
+        r#"
+        UPDATE invites SET uses = uses + 1
+        WHERE code = $1
+            AND uses < max_uses
+            AND (expires_at IS NULL OR expires_at > NOW())
+            AND (email IS NULL OR lower(email) = lower($2))
+        "#,
+    )
+    .bind(normalize(code))
+    .bind(email)
+    .execute(conn)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+    .rows_affected();
+
+    match redeemed {
+        0 => Err(StatusCode::FORBIDDEN),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_use_the_unambiguous_alphabet() {
+        let code = new_code();
+        assert_eq!(code.len(), CODE_LENGTH);
+        assert!(code.bytes().all(|c| CODE_ALPHABET.contains(&c)));
+        assert_ne!(code, new_code());
+    }
+
+    #[test]
+    fn typed_codes_are_normalized() {
+        assert_eq!(normalize(" abcd-efgh-jk23 "), "ABCDEFGHJK23");
+    }
+}
@@ -0,0 +1,164 @@
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::NaiveDateTime;
+use serde::Serialize;
+use sqlx::PgPool;
+
+use crate::{
+    bus::Bus,
+    config::Config,
+    data_exports,
+    db::events::Events,
+    flags::Flags,
+    idempotency,
+    images::ImageProxy,
+    outbox,
+    publishing, recommendations, retention, saved_searches, search,
+    views::{self, ViewCounter},
+};
+
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+const HOUR: Duration = Duration::from_secs(60 * 60);
+const MINUTE: Duration = Duration::from_secs(60);
+/// The outbox is relayed often so changes reach the broker promptly.
+const OUTBOX: Duration = Duration::from_secs(5);
+
+/// Outcome of each job's latest run, for the admin system info.
+static STATUS: Mutex<Vec<JobStatus>> = Mutex::new(Vec::new());
+
+#[derive(Serialize, Clone)]
+pub struct JobStatus {
+    pub name: &'static str,
+    pub period_seconds: u64,
+    pub last_run: Option<NaiveDateTime>,
+    pub last_error: Option<String>,
+    pub last_rows: Option<u64>,
+}
+
+pub fn status() -> Vec<JobStatus> {
+    STATUS.lock().map(|status| status.clone()).unwrap_or_default()
+}
+
+fn report(name: &'static str, update: impl FnOnce(&mut JobStatus)) {
+    if let Ok(mut status) = STATUS.lock() {
+        if let Some(job) = status.iter_mut().find(|job| job.name == name) {
+            update(job);
+        }
+    }
+}
+
+/// Starts the background jobs. Each runs once at startup and then on its
+/// own period for the life of the process.
+pub fn spawn(
+    pool: PgPool,
+    events: Events,
+    counter: ViewCounter,
+    proxy: ImageProxy,
+    flags: Flags,
+    bus: Bus,
+    config: Arc<Config>,
+) {
+    let keys_pool = pool.clone();
+    let flags_pool = pool.clone();
+    let views_pool = pool.clone();
+    let alerts_pool = pool.clone();
+    let retention_pool = pool.clone();
+    let exports_pool = pool.clone();
+    let words_pool = pool.clone();
+    let outbox_pool = pool.clone();
+    let alerts_config = config.clone();
+    let exports_config = config.clone();
+    let retention_config = config.clone();
+    let outbox_flags = flags.clone();
+    tokio::spawn(every(DAY, "recommendations", move || {
+        let pool = pool.clone();
+        async move { recommendations::refresh(&pool).await }
+    }));
+    tokio::spawn(every(HOUR, "search_words", move || {
+        let pool = words_pool.clone();
+        async move { search::refresh_words(&pool).await }
+    }));
+    tokio::spawn(every(DAY, "retention", move || {
+        let pool = retention_pool.clone();
+        let config = retention_config.clone();
+        async move { retention::purge(&pool, &config.retention).await }
+    }));
+    tokio::spawn(every(HOUR, "idempotency_keys", move || {
+        let pool = keys_pool.clone();
+        async move { idempotency::purge(&pool).await }
+    }));
+    tokio::spawn(every(MINUTE, "feature_flags", move || {
+        let pool = flags_pool.clone();
+        let flags = flags.clone();
+        async move { flags.reload(&pool).await }
+    }));
+    tokio::spawn(every(MINUTE, "scheduled_publishing", move || {
+        let events = events.clone();
+        async move { publishing::publish_due(events.as_ref()).await }
+    }));
+    tokio::spawn(every(MINUTE, "event_views", move || {
+        let pool = views_pool.clone();
+        let counter = counter.clone();
+        async move { views::flush(&pool, &counter).await }
+    }));
+    tokio::spawn(every(HOUR, "saved_search_alerts", move || {
+        let pool = alerts_pool.clone();
+        let config = alerts_config.clone();
+        async move { saved_searches::send_alerts(&pool, &config).await }
+    }));
+    tokio::spawn(every(MINUTE, "data_exports", move || {
+        let pool = exports_pool.clone();
+        let proxy = proxy.clone();
+        let config = exports_config.clone();
+        async move { data_exports::process(&pool, &proxy, &config).await }
+    }));
+    tokio::spawn(every(OUTBOX, "outbox", move || {
+        let pool = outbox_pool.clone();
+        let bus = bus.clone();
+        let config = config.clone();
+        let flags = outbox_flags.clone();
+        async move { outbox::relay(&pool, &bus, &config, &flags).await }
+    }));
+}
+
+async fn every<F, Fut>(period: Duration, name: &'static str, job: F)
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<u64, sqlx::Error>>,
+{
+    if let Ok(mut status) = STATUS.lock() {
+        status.push(JobStatus {
+            name,
+            period_seconds: period.as_secs(),
+            last_run: None,
+            last_error: None,
+            last_rows: None,
+        });
+    }
+
+    let mut interval = tokio::time::interval(period);
+    loop {
+        interval.tick().await;
+        let result = job().await;
+        let now = chrono::Utc::now().naive_utc();
+        match result {
+            Ok(rows) => {
+                tracing::info!(job = name, rows, "job finished");
+                report(name, |job| {
+                    job.last_run = Some(now);
+                    job.last_error = None;
+                    job.last_rows = Some(rows);
+                });
+            }
+            Err(err) => {
+                tracing::error!(job = name, error = %err, "job failed");
+                report(name, |job| {
+                    job.last_run = Some(now);
+                    job.last_error = Some(err.to_string());
+                });
+            }
+        }
+    }
+}
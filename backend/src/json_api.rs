@@ -0,0 +1,375 @@
+//! JSON:API (https://jsonapi.org) responses for clients that ask for them
+//! with `Accept: application/vnd.api+json`. The handlers know nothing of
+//! it: `negotiate` maps their plain JSON into JSON:API documents on the way
+//! out, so every list and detail endpoint speaks both.
+//!
+//! Objects with an `id` become resources typed after the collection in the
+//! path, with `*_id` fields as relationships; paginated lists get `first`,
+//! `prev`, `next` and `last` links. Bodies that aren't resources go under
+//! `meta`, and errors into `errors`.
+
+use axum::{
+    body::Body,
+    extract::Request,
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde_json::{json, Map, Value};
+
+pub const MEDIA_TYPE: &str = "application/vnd.api+json";
+
+/// Path segments that scope a listing rather than name what it lists.
+const SCOPES: [&str; 2] = ["admin", "me"];
+
+/// Collections with a `GET /api/<type>/:id` of their own, which resources
+/// link to.
+const DETAIL_ROUTES: [&str; 3] = ["events", "timelines", "people"];
+
+/// Whether the client asked for JSON:API, and whether it can take it: the
+/// spec has servers refuse with 406 when every JSON:API entry in `Accept`
+/// carries media type parameters.
+fn wants(headers: &HeaderMap) -> Option<bool> {
+    let accept = headers.get(header::ACCEPT)?.to_str().ok()?;
+    let entries: Vec<&str> = accept
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| entry.split(';').next().map(str::trim) == Some(MEDIA_TYPE))
+        .collect();
+    if entries.is_empty() {
+        return None;
+    }
+    Some(entries.iter().any(|entry| !entry.contains(';')))
+}
+
+/// Answers in JSON:API when the client asks for it; other requests pass
+/// untouched. Responses say they vary by `Accept`, so caches keep the two
+/// apart.
+pub async fn negotiate(request: Request, next: Next) -> Response {
+    let is_api = request.uri().path().starts_with("/api/");
+    let wanted = if is_api { wants(request.headers()) } else { None };
+    let path = request.uri().path().to_string();
+    let query = request.uri().query().unwrap_or_default().to_string();
+
+    let mut response = match wanted {
+        Some(false) => document(StatusCode::NOT_ACCEPTABLE, error_document(StatusCode::NOT_ACCEPTABLE, None)),
+        Some(true) => {
+            let response = next.run(request).await;
+            map_response(response, &path, &query).await
+        }
+        None => next.run(request).await,
+    };
+    if is_api {
+        response.headers_mut().append(header::VARY, HeaderValue::from_static("Accept"));
+    }
+    response
+}
+
+fn document(status: StatusCode, body: Value) -> Response {
+    let mut response = (status, body.to_string()).into_response();
+    response
+        .headers_mut()
+        .insert(header::CONTENT_TYPE, HeaderValue::from_static(MEDIA_TYPE));
+    response
+}
+
+/// Rewrites a JSON response, or an error without a body, as a JSON:API
+/// document. Anything else (files, downloads, empty successes) is left as
+/// it is; downloads may be streamed and too large to hold here.
+async fn map_response(response: Response, path: &str, query: &str) -> Response {
+    let status = response.status();
+    let is_download = response
+        .headers()
+        .get(header::CONTENT_DISPOSITION)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("attachment"));
+    if is_download {
+        return response;
+    }
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"));
+    if !(is_json || status.is_client_error() || status.is_server_error()) {
+        return response;
+    }
+
+    let (parts, body) = response.into_parts();
+    let Ok(bytes) = axum::body::to_bytes(body, usize::MAX).await else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    let body = match serde_json::from_slice::<Value>(&bytes) {
+        Ok(body) if is_json => Some(body),
+        _ if status.is_success() => return Response::from_parts(parts, Body::from(bytes)),
+        _ => None,
+    };
+
+    let mapped = if status.is_success() {
+        success_document(body.unwrap_or(Value::Null), path, query)
+    } else {
+        error_document(status, body)
+    };
+    let mut response = document(status, mapped);
+    for (name, value) in &parts.headers {
+        if name != header::CONTENT_TYPE && name != header::CONTENT_LENGTH {
+            response.headers_mut().append(name, value.clone());
+        }
+    }
+    response
+}
+
+/// The resource type for a path: the last segment that isn't an id, or
+/// the collection a sub-listing such as `events/featured` belongs to.
+fn resource_type(path: &str) -> String {
+    let segments: Vec<&str> = path
+        .trim_start_matches("/api/")
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .collect();
+    let scoped = match segments.split_first() {
+        Some((first, rest)) if SCOPES.contains(first) && !rest.is_empty() => rest,
+        _ => &segments[..],
+    };
+    let mut kind = None;
+    let mut after_id = true;
+    for segment in scoped {
+        if is_id(segment) {
+            after_id = true;
+            continue;
+        }
+        if after_id {
+            kind = Some(*segment);
+        }
+        after_id = false;
+    }
+    kind.unwrap_or("resources").to_string()
+}
+
+fn is_id(segment: &str) -> bool {
+    uuid::Uuid::parse_str(segment).is_ok() || segment.chars().all(|c| c.is_ascii_digit())
+}
+
+/// The collection a `<name>_id` field points into.
+fn related_type(name: &str) -> String {
+    match name {
+        "owner" | "user" | "actor" | "author" | "admin" | "target_user" | "created_by" => "users".to_string(),
+        "person" => "people".to_string(),
+        "category" => "categories".to_string(),
+        _ => format!("{}s", name),
+    }
+}
+
+/// An object with an `id` as a resource object; `None` for anything else.
+fn resource(value: Value, kind: &str) -> Option<Value> {
+    let Value::Object(mut attributes) = value else {
+        return None;
+    };
+    let id = match attributes.remove("id")? {
+        Value::String(id) => id,
+        Value::Number(id) => id.to_string(),
+        _ => return None,
+    };
+
+    let mut relationships = Map::new();
+    let keys: Vec<String> = attributes.keys().filter(|key| key.ends_with("_id")).cloned().collect();
+    for key in keys {
+        let related = match &attributes[&key] {
+            Value::String(related) => json!({ "type": related_type(&key[..key.len() - 3]), "id": related }),
+            Value::Null => Value::Null,
+            _ => continue,
+        };
+        attributes.remove(&key);
+        relationships.insert(key[..key.len() - 3].to_string(), json!({ "data": related }));
+    }
+
+    let mut resource = json!({ "type": kind, "id": id, "attributes": attributes });
+    if !relationships.is_empty() {
+        resource["relationships"] = Value::Object(relationships);
+    }
+    if DETAIL_ROUTES.contains(&kind) {
+        resource["links"] = json!({ "self": format!("/api/{}/{}", kind, resource["id"].as_str().unwrap_or_default()) });
+    }
+    Some(resource)
+}
+
+/// Every item as a resource, or `None` unless all of them are.
+fn resources(items: Vec<Value>, kind: &str) -> Option<Vec<Value>> {
+    items.into_iter().map(|item| resource(item, kind)).collect()
+}
+
+/// `path?query` with `page` set to `page`, keeping the other parameters.
+fn page_link(path: &str, query: &str, page: i64) -> String {
+    let mut params: Vec<String> = query
+        .split('&')
+        .filter(|param| !param.is_empty() && param.split('=').next() != Some("page"))
+        .map(str::to_string)
+        .collect();
+    params.push(format!("page={}", page));
+    format!("{}?{}", path, params.join("&"))
+}
+
+fn self_link(path: &str, query: &str) -> String {
+    if query.is_empty() {
+        path.to_string()
+    } else {
+        format!("{}?{}", path, query)
+    }
+}
+
+fn success_document(body: Value, path: &str, query: &str) -> Value {
+    let kind = resource_type(path);
+    let mut links = json!({ "self": self_link(path, query) });
+
+    match body {
+        // A page: `data` plus its counts, and perhaps facets.
+        Value::Object(mut page) if page.get("data").is_some_and(Value::is_array) => {
+            let Some(Value::Array(items)) = page.remove("data") else {
+                unreachable!("checked above");
+            };
+            let current = page.get("page").and_then(Value::as_i64);
+            let last = page.get("pages").and_then(Value::as_i64);
+            if let (Some(current), Some(last)) = (current, last) {
+                let last = last.max(1);
+                links["first"] = json!(page_link(path, query, 1));
+                links["last"] = json!(page_link(path, query, last));
+                links["prev"] = if current > 1 { json!(page_link(path, query, current - 1)) } else { Value::Null };
+                links["next"] = if current < last { json!(page_link(path, query, current + 1)) } else { Value::Null };
+            }
+            match resources(items.clone(), &kind) {
+                Some(data) => json!({ "data": data, "meta": page, "links": links }),
+                None => {
+                    page.insert("data".to_string(), Value::Array(items));
+                    json!({ "meta": page, "links": links })
+                }
+            }
+        }
+        Value::Array(items) => match resources(items.clone(), &kind) {
+            Some(data) => json!({ "data": data, "links": links }),
+            None => json!({ "meta": { "data": items }, "links": links }),
+        },
+        Value::Object(object) if object.contains_key("id") => match resource(Value::Object(object.clone()), &kind) {
+            Some(data) => json!({ "data": data, "links": links }),
+            None => json!({ "meta": object, "links": links }),
+        },
+        Value::Null => json!({ "data": null, "links": links }),
+        other => json!({ "meta": other, "links": links }),
+    }
+}
+
+/// `{"errors": [..]}` from the API's `{"error": .., "fields": ..}` bodies,
+/// one error per invalid field, or from the status alone.
+fn error_document(status: StatusCode, body: Option<Value>) -> Value {
+    let reason = status.canonical_reason().unwrap_or("Error");
+    let mut error = json!({ "status": status.as_str(), "title": reason });
+    let mut fields = Map::new();
+    match body {
+        Some(Value::Object(mut body)) => {
+            if let Some(detail) = body.remove("error") {
+                error["detail"] = detail;
+            }
+            if let Some(Value::Object(invalid)) = body.remove("fields") {
+                fields = invalid;
+            }
+        }
+        Some(Value::String(detail)) => error["detail"] = json!(detail),
+        _ => {}
+    }
+    if fields.is_empty() {
+        return json!({ "errors": [error] });
+    }
+    let errors: Vec<Value> = fields
+        .into_iter()
+        .map(|(field, problems)| {
+            let mut error = error.clone();
+            error["source"] = json!({ "pointer": format!("/data/attributes/{}", field) });
+            error["meta"] = json!({ "problems": problems });
+            error
+        })
+        .collect();
+    json!({ "errors": errors })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{middleware, routing::get, Json, Router};
+    use tower::ServiceExt;
+
+    #[test]
+    fn types_come_from_the_collection_in_the_path() {
+        let id = uuid::Uuid::new_v4();
+        assert_eq!(resource_type("/api/events"), "events");
+        assert_eq!(resource_type(&format!("/api/events/{}", id)), "events");
+        assert_eq!(resource_type(&format!("/api/timelines/{}/events", id)), "events");
+        assert_eq!(resource_type("/api/events/featured"), "events");
+        assert_eq!(resource_type("/api/admin/users"), "users");
+        assert_eq!(resource_type("/api/me/notifications"), "notifications");
+    }
+
+    #[test]
+    fn ids_become_relationships() {
+        let event = json!({ "id": "e1", "title": "Moon landing", "timeline_id": "t1", "owner_id": null });
+        let resource = resource(event, "events").unwrap();
+        assert_eq!(resource["attributes"], json!({ "title": "Moon landing" }));
+        assert_eq!(resource["relationships"]["timeline"]["data"], json!({ "type": "timelines", "id": "t1" }));
+        assert_eq!(resource["relationships"]["owner"]["data"], Value::Null);
+        assert_eq!(resource["links"]["self"], "/api/events/e1");
+    }
+
+    #[test]
+    fn pages_link_to_their_neighbours() {
+        let page = json!({ "data": [{ "id": "e1" }], "total": 45, "page": 2, "limit": 20, "pages": 3 });
+        let document = success_document(page, "/api/events", "search=moon&page=2");
+        assert_eq!(document["links"]["prev"], "/api/events?search=moon&page=1");
+        assert_eq!(document["links"]["next"], "/api/events?search=moon&page=3");
+        assert_eq!(document["links"]["last"], "/api/events?search=moon&page=3");
+        assert_eq!(document["meta"]["total"], 45);
+        assert_eq!(document["data"][0]["type"], "events");
+    }
+
+    fn app() -> Router {
+        Router::new()
+            .route("/api/events/:id", get(|| async { Json(json!({ "id": "e1", "title": "Moon landing" })) }))
+            .route(
+                "/api/events",
+                get(|| async { (StatusCode::UNPROCESSABLE_ENTITY, Json(json!({ "error": "Unknown sort order" }))) }),
+            )
+            .layer(middleware::from_fn(negotiate))
+    }
+
+    async fn get_with(uri: &str, accept: &str) -> Response {
+        let request = Request::builder().uri(uri).header(header::ACCEPT, accept).body(Body::empty()).unwrap();
+        app().oneshot(request).await.unwrap()
+    }
+
+    async fn json_body(response: Response) -> Value {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[tokio::test]
+    async fn only_answers_in_json_api_when_asked() {
+        let plain = get_with("/api/events/e1", "application/json").await;
+        assert_eq!(plain.headers()[header::CONTENT_TYPE], "application/json");
+        assert_eq!(json_body(plain).await["title"], "Moon landing");
+
+        let wrapped = get_with("/api/events/e1", MEDIA_TYPE).await;
+        assert_eq!(wrapped.headers()[header::CONTENT_TYPE], MEDIA_TYPE);
+        assert_eq!(wrapped.headers()[header::VARY], "Accept");
+        let document = json_body(wrapped).await;
+        assert_eq!(document["data"]["attributes"]["title"], "Moon landing");
+    }
+
+    #[tokio::test]
+    async fn errors_and_parameters_follow_the_spec() {
+        let failed = get_with("/api/events", MEDIA_TYPE).await;
+        assert_eq!(failed.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let document = json_body(failed).await;
+        assert_eq!(document["errors"][0]["status"], "422");
+        assert_eq!(document["errors"][0]["detail"], "Unknown sort order");
+
+        let refused = get_with("/api/events/e1", "application/vnd.api+json; ext=bulk").await;
+        assert_eq!(refused.status(), StatusCode::NOT_ACCEPTABLE);
+    }
+}
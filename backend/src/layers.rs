@@ -0,0 +1,160 @@
+use axum::{
+    extract::{DefaultBodyLimit, Request},
+    http::{header, HeaderValue, Method, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    Json, Router,
+};
+use tower_http::compression::CompressionLayer;
+
+/// Largest request body accepted by default. Routes that need more (or
+/// less) set their own `DefaultBodyLimit`, which takes precedence.
+pub const BODY_LIMIT: usize = 64 * 1024;
+
+/// Adds the layers shared by every route: the default body size limit,
+/// `Cache-Control` defaults for the API and, when enabled, gzip/brotli
+/// compression.
+pub fn apply(router: Router, compression: bool) -> Router {
+    let router = router
+        .layer(middleware::from_fn(payload_too_large))
+        .layer(DefaultBodyLimit::max(BODY_LIMIT))
+        .layer(middleware::from_fn(api_cache_control));
+
+    if compression {
+        router.layer(CompressionLayer::new())
+    } else {
+        router
+    }
+}
+
+/// API responses are always revalidated; mutations are never stored.
+///
+/// Handlers that set their own `Cache-Control` are left alone, as are
+/// non-API routes, which `static_files` already covers.
+async fn api_cache_control(request: Request, next: Next) -> Response {
+    let is_api = request.uri().path().starts_with("/api/");
+    let is_read = matches!(*request.method(), Method::GET | Method::HEAD);
+    let mut response = next.run(request).await;
+
+    if is_api && !response.headers().contains_key(header::CACHE_CONTROL) {
+        let value = if is_read { "no-cache" } else { "no-store" };
+        response
+            .headers_mut()
+            .insert(header::CACHE_CONTROL, HeaderValue::from_static(value));
+    }
+
+    response
+}
+
+/// Replaces the extractors' plain-text 413 rejection with a JSON error in
+/// the same shape as the API's other errors.
+async fn payload_too_large(request: Request, next: Next) -> Response {
+    let response = next.run(request).await;
+    if response.status() != StatusCode::PAYLOAD_TOO_LARGE {
+        return response;
+    }
+
+    (
+        StatusCode::PAYLOAD_TOO_LARGE,
+        Json(serde_json::json!({
+            "error": "request body is too large for this endpoint"
+        })),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        body::Body,
+        routing::{get, post},
+    };
+    use tower::ServiceExt;
+
+    fn app(compression: bool) -> Router {
+        let router = Router::new()
+            .route("/api/events", get(|| async { "event ".repeat(200) }).post(|| async { "ok" }))
+            .route("/api/echo", post(|body: String| async move { body }))
+            .route(
+                "/api/small",
+                post(|body: String| async move { body }).layer(DefaultBodyLimit::max(16)),
+            )
+            .route("/assets/app.js", get(|| async { "console.log(1);".repeat(50) }));
+        apply(router, compression)
+    }
+
+    async fn post_body(uri: &str, len: usize) -> Response {
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri(uri)
+            .body(Body::from("x".repeat(len)))
+            .unwrap();
+        app(false).oneshot(request).await.unwrap()
+    }
+
+    async fn send(app: Router, method: Method, uri: &str, encoding: &str) -> Response {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(header::ACCEPT_ENCODING, encoding)
+            .body(Body::empty())
+            .unwrap();
+        app.oneshot(request).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn compresses_with_gzip() {
+        let response = send(app(true), Method::GET, "/api/events", "gzip").await;
+        assert_eq!(response.headers()[header::CONTENT_ENCODING], "gzip");
+    }
+
+    #[tokio::test]
+    async fn prefers_brotli_when_accepted() {
+        let response = send(app(true), Method::GET, "/api/events", "gzip, br").await;
+        assert_eq!(response.headers()[header::CONTENT_ENCODING], "br");
+    }
+
+    #[tokio::test]
+    async fn leaves_responses_alone_when_disabled() {
+        let response = send(app(false), Method::GET, "/api/events", "gzip, br").await;
+        assert!(!response.headers().contains_key(header::CONTENT_ENCODING));
+    }
+
+    #[tokio::test]
+    async fn api_reads_are_revalidated() {
+        let response = send(app(true), Method::GET, "/api/events", "identity").await;
+        assert_eq!(response.headers()[header::CACHE_CONTROL], "no-cache");
+    }
+
+    #[tokio::test]
+    async fn api_writes_are_not_stored() {
+        let response = send(app(true), Method::POST, "/api/events", "identity").await;
+        assert_eq!(response.headers()[header::CACHE_CONTROL], "no-store");
+    }
+
+    #[tokio::test]
+    async fn non_api_routes_are_untouched() {
+        let response = send(app(true), Method::GET, "/assets/app.js", "identity").await;
+        assert!(!response.headers().contains_key(header::CACHE_CONTROL));
+    }
+
+    #[tokio::test]
+    async fn accepts_bodies_within_the_limit() {
+        let response = post_body("/api/echo", BODY_LIMIT).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn rejects_oversized_bodies_with_json() {
+        let response = post_body("/api/echo", BODY_LIMIT + 1).await;
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], "application/json");
+    }
+
+    #[tokio::test]
+    async fn route_limits_override_the_default() {
+        let response = post_body("/api/small", 17).await;
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+}
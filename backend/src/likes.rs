@@ -0,0 +1,166 @@
+//! Likes on public events, from signed-in users or anonymous visitors.
+//! Anonymous visitors send a random id their browser keeps in
+//! `X-Visitor-Id`; only its hash is stored.
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    routing::get,
+    Json, Router,
+};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{auth::AuthUser, db::Reader, timelines, AppState};
+
+/// Header carrying an anonymous visitor's id.
+const VISITOR_HEADER: &str = "x-visitor-id";
+
+pub fn routes() -> Router<AppState> {
+    Router::new().route("/api/events/:id/like", get(get_like).post(toggle_like))
+}
+
+/// Whoever is liking: a user, or a visitor's fingerprint.
+enum Liker {
+    User(Uuid),
+    Visitor(String),
+}
+
+/// The caller as a liker. Visitor ids must look like the ones the frontend
+/// makes, so that a missing or junk header is not one shared identity.
+fn liker(user: Option<&AuthUser>, headers: &HeaderMap) -> Option<Liker> {
+    if let Some(user) = user {
+        return Some(Liker::User(user.id));
+    }
+    let visitor = headers.get(VISITOR_HEADER)?.to_str().ok()?;
+    let valid = (16..=128).contains(&visitor.len()) && visitor.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+    valid.then(|| Liker::Visitor(format!("{:x}", Sha256::digest(visitor.as_bytes()))))
+}
+
+impl Liker {
+    fn user(&self) -> Option<Uuid> {
+        match self {
+            Liker::User(id) => Some(*id),
+            Liker::Visitor(_) => None,
+        }
+    }
+
+    fn fingerprint(&self) -> Option<&str> {
+        match self {
+            Liker::User(_) => None,
+            Liker::Visitor(fingerprint) => Some(fingerprint),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct LikeState {
+    /// Whether the caller likes the event.
+    liked: bool,
+    likes: i64,
+}
+
+/// The public event's like total, or 404.
+async fn public_likes(pool: &PgPool, id: Uuid) -> Result<i64, StatusCode> {
+    sqlx::query_scalar::<_, i64>(&format!("SELECT e.likes FROM events e WHERE e.id = $1 AND {}", timelines::PUBLIC_EVENT))
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+async fn get_like(
+    Reader(pool): Reader,
+    user: Option<AuthUser>,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+) -> Result<Json<LikeState>, StatusCode> {
+    let likes = public_likes(&pool, id).await?;
+    let liked = match liker(user.as_ref(), &headers) {
+        Some(liker) => sqlx::query_scalar::<_, bool>(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM event_likes
+                WHERE event_id = $1 AND (user_id = $2 OR fingerprint = $3)
+            )
+            "#,
+        )
+        .bind(id)
+        .bind(liker.user())
+        .bind(liker.fingerprint())
+        .fetch_one(&pool)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?,
+        None => false,
+    };
+
+    Ok(Json(LikeState { liked, likes }))
+}
+
+/// Likes the event, or takes the like back if the caller already gave one.
+async fn toggle_like(
+    State(pool): State<PgPool>,
+    user: Option<AuthUser>,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+) -> Result<Json<LikeState>, StatusCode> {
+    let liker = liker(user.as_ref(), &headers).ok_or(StatusCode::BAD_REQUEST)?;
+    public_likes(&pool, id).await?;
+
+    let internal = |_| StatusCode::INTERNAL_SERVER_ERROR;
+    let mut tx = pool.begin().await.map_err(internal)?;
+    let removed = sqlx::query("DELETE FROM event_likes WHERE event_id = $1 AND (user_id = $2 OR fingerprint = $3)")
+        .bind(id)
+        .bind(liker.user())
+        .bind(liker.fingerprint())
+        .execute(&mut *tx)
+        .await
+        .map_err(internal)?
+        .rows_affected();
+    let change: i64 = if removed > 0 {
+        -(removed as i64)
+    } else {
+        // A concurrent like from the same caller makes this a no-op.
+        sqlx::query(
+            "INSERT INTO event_likes (event_id, user_id, fingerprint) VALUES ($1, $2, $3) ON CONFLICT DO NOTHING",
+        )
+        .bind(id)
+        .bind(liker.user())
+        .bind(liker.fingerprint())
+        .execute(&mut *tx)
+        .await
+        .map_err(internal)?
+        .rows_affected() as i64
+    };
+    let likes = sqlx::query_scalar::<_, i64>("UPDATE events SET likes = likes + $2 WHERE id = $1 RETURNING likes")
+        .bind(id)
+        .bind(change)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(internal)?;
+    tx.commit().await.map_err(internal)?;
+
+    Ok(Json(LikeState { liked: removed == 0, likes }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn visitors_need_a_plausible_id() {
+        let mut headers = HeaderMap::new();
+        assert!(liker(None, &headers).is_none());
+
+        headers.insert(VISITOR_HEADER, "abc".parse().unwrap());
+        assert!(liker(None, &headers).is_none());
+
+        headers.insert(VISITOR_HEADER, "3f2a9c0d4b1e4f6a8c7d5e9b0a1f2c3d".parse().unwrap());
+        let fingerprint = liker(None, &headers).unwrap().fingerprint().map(str::to_string).unwrap();
+        assert_eq!(fingerprint.len(), 64);
+        assert_ne!(fingerprint, "3f2a9c0d4b1e4f6a8c7d5e9b0a1f2c3d");
+    }
+}
@@ -0,0 +1,242 @@
+//! Soft locks on an event while someone has its edit controls open, so a
+//! second editor sees who else is there instead of overwriting them. A
+//! lock lapses `LOCK_TTL_SECONDS` after its last renewal; the holder's
+//! page renews it with heartbeats over a WebSocket and releases it when
+//! the socket closes. The server closes the socket once the token it was
+//! opened with expires or its session is revoked; the page then renews
+//! over HTTP with a fresh token. Nothing stops a save without the lock.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{
+        ws::{close_code, CloseFrame, Message, WebSocket, WebSocketUpgrade},
+        Path, Query, State,
+    },
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{auth, auth::AuthUser, config::Config, db::events::Events, sessions, timelines, AppState};
+
+/// How long a lock outlives its last heartbeat.
+const LOCK_TTL_SECONDS: i32 = 30;
+/// What the holder's page sends to renew its lock.
+const HEARTBEAT: &str = "heartbeat";
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/events/:id/lock", post(acquire).delete(release))
+        .route("/api/events/:id/lock/ws", get(heartbeats))
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+struct Lock {
+    user_id: Uuid,
+    username: String,
+    acquired_at: NaiveDateTime,
+    expires_at: NaiveDateTime,
+    /// Whether the caller holds it.
+    mine: bool,
+}
+
+/// The event's lock as `user` sees it, unless there is none or it lapsed.
+async fn current(pool: &PgPool, event_id: Uuid, user: Uuid) -> Result<Option<Lock>, sqlx::Error> {
+    sqlx::query_as::<_, Lock>(
+        r#"
+        SELECT l.user_id, u.username, l.acquired_at, l.expires_at, l.user_id = $2 AS mine
+        FROM event_locks l JOIN users u ON u.id = l.user_id
+        WHERE l.event_id = $1 AND l.expires_at > NOW()
+        "#,
+    )
+    .bind(event_id)
+    .bind(user)
+    .fetch_optional(pool)
+    .await
+}
+
+#[derive(Deserialize)]
+struct AcquireLock {
+    /// Take the lock even though someone else holds it.
+    #[serde(default)]
+    takeover: bool,
+}
+
+/// Takes the event's lock, or renews it for its holder. 409 with the
+/// holder's lock when someone else has it, unless taking it over.
+async fn acquire(
+    State(pool): State<PgPool>,
+    State(events): State<Events>,
+    user: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<AcquireLock>,
+) -> Result<Json<Lock>, Response> {
+    timelines::ensure_event_writable(&pool, &events, Some(&user), id).await?;
+    let internal = |_| StatusCode::INTERNAL_SERVER_ERROR.into_response();
+
+    sqlx::query(
+        r#"
+        INSERT INTO event_locks (event_id, user_id, expires_at)
+        VALUES ($1, $2, NOW() + $3 * INTERVAL '1 second')
+        ON CONFLICT (event_id) DO UPDATE SET
+            user_id = EXCLUDED.user_id,
+            expires_at = EXCLUDED.expires_at,
+            acquired_at = CASE WHEN event_locks.user_id = EXCLUDED.user_id
+                THEN event_locks.acquired_at ELSE NOW() END
+        WHERE event_locks.user_id = EXCLUDED.user_id OR event_locks.expires_at <= NOW() OR $4
+        "#,
+    )
+    .bind(id)
+    .bind(user.id)
+    .bind(LOCK_TTL_SECONDS)
+    .bind(payload.takeover)
+    .execute(&pool)
+    .await
+    .map_err(internal)?;
+
+    match current(&pool, id, user.id).await.map_err(internal)? {
+        Some(lock) if lock.mine => Ok(Json(lock)),
+        Some(lock) => Err((
+            StatusCode::CONFLICT,
+            Json(serde_json::json!({ "error": format!("{} is editing this event.", lock.username), "lock": lock })),
+        )
+            .into_response()),
+        // Lapsed between the two statements; the caller just tries again.
+        None => Err(StatusCode::CONFLICT.into_response()),
+    }
+}
+
+/// Gives up the caller's lock; someone else's is left alone.
+async fn release(State(pool): State<PgPool>, user: AuthUser, Path(id): Path<Uuid>) -> StatusCode {
+    match drop_lock(&pool, id, user.id).await {
+        Ok(_) => StatusCode::NO_CONTENT,
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+async fn drop_lock(pool: &PgPool, event_id: Uuid, user: Uuid) -> Result<u64, sqlx::Error> {
+    sqlx::query("DELETE FROM event_locks WHERE event_id = $1 AND user_id = $2")
+        .bind(event_id)
+        .bind(user)
+        .execute(pool)
+        .await
+        .map(|done| done.rows_affected())
+}
+
+#[derive(Deserialize)]
+struct SocketParams {
+    /// The session token, which browsers can't send as a header here.
+    token: String,
+}
+
+/// The holder's heartbeat channel. Each `heartbeat` renews the lock if
+/// the caller still holds it and is answered with the lock as it now is
+/// (`null` once it lapsed), so a page that was taken over finds out.
+/// Closing the socket releases the lock it was opened for, but not one
+/// the same user has taken back since.
+async fn heartbeats(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<SocketParams>,
+    upgrade: WebSocketUpgrade,
+) -> Result<Response, Response> {
+    let user = auth::verify_socket_token(&state.config, &params.token).map_err(IntoResponse::into_response)?;
+    let pool = state.db.writer().clone();
+    timelines::ensure_event_writable(&pool, &state.events, Some(&user), id).await?;
+    let held = current(&pool, id, user.id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?
+        .filter(|lock| lock.mine)
+        .map(|lock| lock.acquired_at);
+    let config = state.config.clone();
+    Ok(upgrade.on_upgrade(move |socket| serve(socket, pool, config, params.token, id, user, held)))
+}
+
+/// Whether the token a socket was opened with still stands: unexpired,
+/// and its session neither revoked nor expired.
+async fn signed_in(pool: &PgPool, config: &Config, token: &str) -> Result<bool, sqlx::Error> {
+    let Ok(user) = auth::verify_socket_token(config, token) else {
+        return Ok(false);
+    };
+    match user.session_id {
+        Some(session) => sessions::is_active(pool, session).await,
+        None => Ok(true),
+    }
+}
+
+/// `held` is when the lock this socket keeps alive was acquired.
+async fn serve(
+    mut socket: WebSocket,
+    pool: PgPool,
+    config: Arc<Config>,
+    token: String,
+    event_id: Uuid,
+    user: AuthUser,
+    held: Option<NaiveDateTime>,
+) {
+    let user = user.id;
+    while let Some(Ok(message)) = socket.recv().await {
+        match message {
+            Message::Text(text) if text == HEARTBEAT => {
+                match signed_in(&pool, &config, &token).await {
+                    Ok(true) => {}
+                    Ok(false) => {
+                        let frame = CloseFrame { code: close_code::POLICY, reason: "The session has ended.".into() };
+                        socket.send(Message::Close(Some(frame))).await.ok();
+                        break;
+                    }
+                    Err(err) => {
+                        tracing::warn!(%event_id, error = %err, "failed to check an edit lock's session");
+                        continue;
+                    }
+                }
+                let lock = match renew(&pool, event_id, user).await {
+                    Ok(()) => current(&pool, event_id, user).await,
+                    Err(err) => Err(err),
+                };
+                let Ok(lock) = lock else {
+                    tracing::warn!(%event_id, "failed to renew an edit lock");
+                    continue;
+                };
+                let reply = serde_json::to_string(&lock).unwrap_or_default();
+                if socket.send(Message::Text(reply)).await.is_err() {
+                    break;
+                }
+            }
+            Message::Close(_) => break,
+            _ => {}
+        }
+    }
+    let Some(acquired_at) = held else {
+        return;
+    };
+    let released = sqlx::query("DELETE FROM event_locks WHERE event_id = $1 AND user_id = $2 AND acquired_at = $3")
+        .bind(event_id)
+        .bind(user)
+        .bind(acquired_at)
+        .execute(&pool)
+        .await;
+    if let Err(err) = released {
+        tracing::warn!(%event_id, error = %err, "failed to release an edit lock");
+    }
+}
+
+/// Pushes the lock's expiry back, if `user` still holds it.
+async fn renew(pool: &PgPool, event_id: Uuid, user: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE event_locks SET expires_at = NOW() + $3 * INTERVAL '1 second' \
+         WHERE event_id = $1 AND user_id = $2",
+    )
+    .bind(event_id)
+    .bind(user)
+    .bind(LOCK_TTL_SECONDS)
+    .execute(pool)
+    .await
+    .map(|_| ())
+}
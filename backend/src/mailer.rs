@@ -0,0 +1,47 @@
+use lettre::{
+    message::Mailbox, AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+
+use crate::config::Config;
+
+#[derive(Debug)]
+pub enum MailError {
+    /// The recipient or sender address could not be parsed.
+    Address,
+    Transport(String),
+}
+
+impl std::fmt::Display for MailError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MailError::Address => f.write_str("invalid email address"),
+            MailError::Transport(message) => f.write_str(message),
+        }
+    }
+}
+
+/// Sends a plain-text email through `SMTP_URL`. Without one configured the
+/// message is only logged, which is what development setups want.
+pub async fn send(config: &Config, to: &str, subject: &str, body: &str) -> Result<(), MailError> {
+    let Some(smtp_url) = &config.smtp_url else {
+        tracing::info!(to, subject, body, "SMTP_URL not set; email not sent");
+        return Ok(());
+    };
+
+    let from: Mailbox = config.mail_from.parse().map_err(|_| MailError::Address)?;
+    let to: Mailbox = to.parse().map_err(|_| MailError::Address)?;
+    let message = Message::builder()
+        .from(from)
+        .to(to)
+        .subject(subject)
+        .body(body.to_string())
+        .map_err(|err| MailError::Transport(err.to_string()))?;
+
+    AsyncSmtpTransport::<Tokio1Executor>::from_url(smtp_url)
+        .map_err(|err| MailError::Transport(err.to_string()))?
+        .build()
+        .send(message)
+        .await
+        .map(|_| ())
+        .map_err(|err| MailError::Transport(err.to_string()))
+}
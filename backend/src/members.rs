@@ -0,0 +1,172 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, put},
+    Json, Router,
+};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{auth::AuthUser, db::Reader, timelines, AppState};
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/timelines/:id/members", get(list_members).post(invite_member))
+        .route(
+            "/api/timelines/:id/members/:user_id",
+            put(update_member).delete(remove_member),
+        )
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+struct Member {
+    user_id: Uuid,
+    username: String,
+    role: String,
+    created_at: NaiveDateTime,
+}
+
+#[derive(Deserialize)]
+struct Invite {
+    /// Username or email address of an existing user.
+    user: String,
+    role: String,
+}
+
+#[derive(Deserialize)]
+struct RoleUpdate {
+    role: String,
+}
+
+fn valid_role(role: &str) -> Result<(), (StatusCode, &'static str)> {
+    match role {
+        "viewer" | "editor" => Ok(()),
+        _ => Err((StatusCode::UNPROCESSABLE_ENTITY, "role must be viewer or editor")),
+    }
+}
+
+async fn member(pool: &PgPool, timeline_id: Uuid, user_id: Uuid) -> Result<Member, Response> {
+    sqlx::query_as::<_, Member>(
+        r#"
+        SELECT m.user_id, u.username, m.role, m.created_at
+        FROM timeline_members m JOIN users u ON u.id = m.user_id
+        WHERE m.timeline_id = $1 AND m.user_id = $2
+        "#,
+    )
+    .bind(timeline_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?
+    .ok_or_else(|| StatusCode::NOT_FOUND.into_response())
+}
+
+/// Members of a timeline; visible to anyone who can see the timeline.
+async fn list_members(
+    Reader(pool): Reader,
+    user: Option<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<Member>>, Response> {
+    timelines::find_visible(&pool, id, user.as_ref()).await?;
+
+    let members = sqlx::query_as::<_, Member>(
+        r#"
+        SELECT m.user_id, u.username, m.role, m.created_at
+        FROM timeline_members m JOIN users u ON u.id = m.user_id
+        WHERE m.timeline_id = $1
+        ORDER BY u.username
+        "#,
+    )
+    .bind(id)
+    .fetch_all(&pool)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
+
+    Ok(Json(members))
+}
+
+/// Adds an existing user, found by username or email, as a member.
+/// Re-inviting a member changes their role.
+async fn invite_member(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<Invite>,
+) -> Result<Json<Member>, Response> {
+    let timeline = timelines::find_owned(&pool, id, &user).await?;
+    valid_role(&payload.role).map_err(IntoResponse::into_response)?;
+
+    let invitee = sqlx::query_scalar::<_, Uuid>("SELECT id FROM users WHERE username = $1 OR lower(email) = lower($1)")
+        .bind(payload.user.trim())
+        .fetch_optional(&pool)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "no user with that username or email").into_response())?;
+    if invitee == timeline.owner_id {
+        return Err((StatusCode::UNPROCESSABLE_ENTITY, "the owner is already a member").into_response());
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO timeline_members (timeline_id, user_id, role, invited_by)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (timeline_id, user_id) DO UPDATE SET role = EXCLUDED.role
+        "#,
+    )
+    .bind(id)
+    .bind(invitee)
+    .bind(&payload.role)
+    .bind(user.id)
+    .execute(&pool)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
+
+    member(&pool, id, invitee).await.map(Json)
+}
+
+async fn update_member(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Path((id, user_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<RoleUpdate>,
+) -> Result<Json<Member>, Response> {
+    timelines::find_owned(&pool, id, &user).await?;
+    valid_role(&payload.role).map_err(IntoResponse::into_response)?;
+
+    let updated = sqlx::query("UPDATE timeline_members SET role = $3 WHERE timeline_id = $1 AND user_id = $2")
+        .bind(id)
+        .bind(user_id)
+        .bind(&payload.role)
+        .execute(&pool)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?
+        .rows_affected();
+    if updated == 0 {
+        return Err(StatusCode::NOT_FOUND.into_response());
+    }
+
+    member(&pool, id, user_id).await.map(Json)
+}
+
+/// Owners remove members; members may also remove themselves.
+async fn remove_member(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Path((id, user_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, Response> {
+    if user.id != user_id {
+        timelines::find_owned(&pool, id, &user).await?;
+    }
+
+    sqlx::query("DELETE FROM timeline_members WHERE timeline_id = $1 AND user_id = $2")
+        .bind(id)
+        .bind(user_id)
+        .execute(&pool)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,278 @@
+//! Reports of public events and the admins' moderation queue. Where the
+//! instance allows it, signed-out visitors report too, past the checks in
+//! `spam`.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+use crate::{
+    auth::AuthUser,
+    config::Config,
+    db::{events::Events, Reader},
+    spam::{self, ClientAddress},
+    timelines, validation_error, AppState,
+};
+
+/// Values of `event_reports.reason`.
+pub const REASONS: [&str; 5] = ["spam", "abuse", "inappropriate", "copyright", "other"];
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/events/:id/report", post(report_event))
+        .route("/api/admin/reports", get(list_reports))
+        .route("/api/admin/reports/:event_id", post(resolve_reports))
+}
+
+fn valid_reason(reason: &str) -> Result<(), ValidationError> {
+    if REASONS.contains(&reason) {
+        Ok(())
+    } else {
+        Err(ValidationError::new("reason"))
+    }
+}
+
+#[derive(Deserialize, Validate)]
+struct ReportRequest {
+    #[validate(custom(function = "valid_reason"))]
+    reason: String,
+    #[validate(length(max = 2000))]
+    details: Option<String>,
+    #[serde(flatten)]
+    proof: spam::Proof,
+}
+
+/// Reports a public event. Reporting it again while the first report is
+/// open changes nothing; guests' reports are all kept.
+async fn report_event(
+    State(pool): State<PgPool>,
+    State(config): State<Arc<Config>>,
+    State(guard): State<spam::Guard>,
+    user: Option<AuthUser>,
+    ClientAddress(address): ClientAddress,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<ReportRequest>,
+) -> Result<StatusCode, Response> {
+    payload.validate().map_err(validation_error)?;
+    if user.is_none() {
+        guard.check(&config, address, &payload.proof).await?;
+    }
+    let internal = |_| StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    let public = sqlx::query_scalar::<_, bool>(&format!(
+        "SELECT EXISTS (SELECT 1 FROM events e WHERE e.id = $1 AND {})",
+        timelines::PUBLIC_EVENT
+    ))
+    .bind(id)
+    .fetch_one(&pool)
+    .await
+    .map_err(internal)?;
+    if !public {
+        return Err(StatusCode::NOT_FOUND.into_response());
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO event_reports (event_id, reporter_id, reason, details)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (event_id, reporter_id) WHERE resolved_at IS NULL DO NOTHING
+        "#,
+    )
+    .bind(id)
+    .bind(user.map(|user| user.id))
+    .bind(&payload.reason)
+    .bind(payload.details.as_deref().map(str::trim).filter(|details| !details.is_empty()))
+    .execute(&pool)
+    .await
+    .map_err(internal)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// An event in the queue with its open reports rolled up.
+#[derive(Serialize, sqlx::FromRow)]
+struct ReportedEvent {
+    event_id: Uuid,
+    title: String,
+    timeline_id: Option<Uuid>,
+    /// The owner of the event's timeline, whom "ban" applies to.
+    author_id: Option<Uuid>,
+    author: Option<String>,
+    reports: i64,
+    /// Distinct reasons given, most common first.
+    reasons: Vec<String>,
+    /// What reporters wrote, newest first.
+    details: Vec<String>,
+    first_reported_at: NaiveDateTime,
+}
+
+/// Events with open reports, most reported first.
+async fn list_reports(Reader(pool): Reader, user: AuthUser) -> Result<Json<Vec<ReportedEvent>>, StatusCode> {
+    user.require_admin()?;
+    let reported = sqlx::query_as::<_, ReportedEvent>(
+        r#"
+        SELECT e.id AS event_id, e.title, e.timeline_id, t.owner_id AS author_id, u.username AS author,
+            COUNT(*) AS reports,
+            ARRAY(
+                SELECT reason FROM event_reports o WHERE o.event_id = e.id AND o.resolved_at IS NULL
+                GROUP BY reason ORDER BY COUNT(*) DESC, reason
+            ) AS reasons,
+            ARRAY_REMOVE(ARRAY_AGG(r.details ORDER BY r.created_at DESC), NULL) AS details,
+            MIN(r.created_at) AS first_reported_at
+        FROM event_reports r
+        JOIN events e ON e.id = r.event_id
+        LEFT JOIN timelines t ON t.id = e.timeline_id
+        LEFT JOIN users u ON u.id = t.owner_id
+        WHERE r.resolved_at IS NULL
+        GROUP BY e.id, t.owner_id, u.username
+        ORDER BY reports DESC, first_reported_at
+        "#,
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(reported))
+}
+
+/// What an admin does about a reported event.
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum Action {
+    /// Nothing was wrong.
+    Dismiss,
+    /// Back to draft, out of public view.
+    Unpublish,
+    Delete,
+    /// Unpublish, and ban the owner of the event's timeline.
+    Ban,
+}
+
+impl Action {
+    /// Recorded as the reports' `resolution`.
+    fn resolution(self) -> &'static str {
+        match self {
+            Action::Dismiss => "dismissed",
+            Action::Unpublish => "unpublished",
+            Action::Delete => "deleted",
+            Action::Ban => "banned",
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct Resolution {
+    action: Action,
+}
+
+/// Whether `user` has been banned.
+pub async fn is_banned(pool: &PgPool, user: Uuid) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar::<_, bool>("SELECT banned_at IS NOT NULL FROM users WHERE id = $1")
+        .bind(user)
+        .fetch_one(pool)
+        .await
+}
+
+/// Bans `user`: they can no longer log in, and their sessions end so their
+/// current access token is the last they get.
+pub async fn ban(pool: &PgPool, user: Uuid) -> Result<(), Response> {
+    let internal = |_| StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    let mut tx = pool.begin().await.map_err(internal)?;
+    let banned = sqlx::query("UPDATE users SET banned_at = NOW() WHERE id = $1 AND role <> 'admin'")
+        .bind(user)
+        .execute(&mut *tx)
+        .await
+        .map_err(internal)?
+        .rows_affected();
+    if banned == 0 {
+        return Err((StatusCode::CONFLICT, "admins cannot be banned").into_response());
+    }
+    sqlx::query("UPDATE sessions SET revoked_at = NOW() WHERE user_id = $1 AND revoked_at IS NULL")
+        .bind(user)
+        .execute(&mut *tx)
+        .await
+        .map_err(internal)?;
+    tx.commit().await.map_err(internal)
+}
+
+/// Closes the event's open reports with `action`. Deleting the event
+/// deletes its reports with it.
+async fn resolve_reports(
+    State(pool): State<PgPool>,
+    State(events): State<Events>,
+    user: AuthUser,
+    Path(event_id): Path<Uuid>,
+    Json(payload): Json<Resolution>,
+) -> Result<StatusCode, Response> {
+    user.require_admin().map_err(IntoResponse::into_response)?;
+    let internal = |_| StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    let author = sqlx::query_scalar::<_, Option<Uuid>>(
+        "SELECT t.owner_id FROM events e LEFT JOIN timelines t ON t.id = e.timeline_id WHERE e.id = $1",
+    )
+    .bind(event_id)
+    .fetch_optional(&pool)
+    .await
+    .map_err(internal)?
+    .ok_or_else(|| StatusCode::NOT_FOUND.into_response())?;
+
+    match payload.action {
+        Action::Dismiss => {}
+        Action::Unpublish => {
+            events.set_status(event_id, "draft").await.map_err(internal)?;
+        }
+        Action::Delete => {
+            events.delete(event_id).await.map_err(internal)?;
+            return Ok(StatusCode::NO_CONTENT);
+        }
+        Action::Ban => {
+            let author = author
+                .ok_or_else(|| (StatusCode::UNPROCESSABLE_ENTITY, "the event is not on anyone's timeline").into_response())?;
+            ban(&pool, author).await?;
+            events.set_status(event_id, "draft").await.map_err(internal)?;
+        }
+    }
+
+    sqlx::query(
+        r#"
+        UPDATE event_reports SET resolution = $2, resolved_by = $3, resolved_at = NOW()
+        WHERE event_id = $1 AND resolved_at IS NULL
+        "#,
+    )
+    .bind(event_id)
+    .bind(payload.action.resolution())
+    .bind(user.id)
+    .execute(&pool)
+    .await
+    .map_err(internal)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn actions_parse_from_lowercase_names() {
+        let resolution: Resolution = serde_json::from_str(r#"{"action": "ban"}"#).unwrap();
+        assert_eq!(resolution.action, Action::Ban);
+        assert_eq!(resolution.action.resolution(), "banned");
+        assert!(serde_json::from_str::<Resolution>(r#"{"action": "shrug"}"#).is_err());
+    }
+
+    #[test]
+    fn reasons_must_be_known() {
+        assert!(valid_reason("spam").is_ok());
+        assert!(valid_reason("boring").is_err());
+    }
+}
@@ -0,0 +1,264 @@
+//! In-app notifications: things a user should hear about, listed under
+//! the header's bell. Any instance may write one, so new notifications are
+//! announced through Postgres `NOTIFY`; every instance's `Notifier` relays
+//! them to the WebSockets its users have open.
+
+use std::time::Duration;
+
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        Query, State,
+    },
+    http::StatusCode,
+    response::Response,
+    routing::{get, post},
+    Json, Router,
+};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::{postgres::PgListener, PgPool};
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+use crate::{auth, auth::AuthUser, db::Reader, AppState};
+
+/// The Postgres channel new notifications are announced on.
+const CHANNEL: &str = "notifications";
+/// How many the bell lists, newest first.
+const LIMIT: i64 = 50;
+
+/// Notifications with what they refer to, joined in; `n` is the
+/// notifications row.
+const SELECT: &str = r#"
+    SELECT n.user_id, n.id, n.kind, a.username AS actor, n.event_id, e.title AS event_title, n.comment_id,
+        LEFT(c.body, 140) AS excerpt, n.read_at IS NOT NULL AS read, n.created_at
+    FROM notifications n
+    LEFT JOIN users a ON a.id = n.actor_id
+    LEFT JOIN events e ON e.id = n.event_id
+    LEFT JOIN comments c ON c.id = n.comment_id
+"#;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/me/notifications", get(list_notifications))
+        .route("/api/me/notifications/read", post(mark_read))
+        .route("/api/me/notifications/ws", get(push))
+}
+
+#[derive(Serialize, Deserialize, Clone, sqlx::FromRow)]
+struct Notification {
+    id: Uuid,
+    /// One of `Kind`'s names.
+    kind: String,
+    actor: Option<String>,
+    event_id: Option<Uuid>,
+    event_title: Option<String>,
+    comment_id: Option<Uuid>,
+    /// The start of the comment.
+    excerpt: Option<String>,
+    read: bool,
+    created_at: NaiveDateTime,
+}
+
+/// A new notification on its way to `user_id`'s open pages.
+#[derive(Serialize, Deserialize, Clone, sqlx::FromRow)]
+struct Announcement {
+    user_id: Uuid,
+    #[sqlx(flatten)]
+    notification: Notification,
+}
+
+/// What a notification about a comment says happened.
+#[derive(Clone, Copy)]
+pub enum Kind {
+    /// The actor named the user in the comment.
+    Mention,
+    /// The comment answers one of the user's.
+    Reply,
+}
+
+impl Kind {
+    fn name(self) -> &'static str {
+        match self {
+            Kind::Mention => "mention",
+            Kind::Reply => "reply",
+        }
+    }
+}
+
+/// Tells `users` about `actor`'s comment on an event; a guest's comment
+/// has no actor.
+pub async fn comment(
+    pool: &PgPool,
+    kind: Kind,
+    users: &[Uuid],
+    actor: Option<Uuid>,
+    event_id: Uuid,
+    comment_id: Uuid,
+) -> Result<u64, sqlx::Error> {
+    let ids = sqlx::query_scalar::<_, Uuid>(
+        r#"
+        INSERT INTO notifications (user_id, kind, actor_id, event_id, comment_id)
+        SELECT user_id, $5, $2, $3, $4 FROM UNNEST($1::uuid[]) AS user_id
+        RETURNING id
+        "#,
+    )
+    .bind(users)
+    .bind(actor)
+    .bind(event_id)
+    .bind(comment_id)
+    .bind(kind.name())
+    .fetch_all(pool)
+    .await?;
+    announce(pool, &ids).await?;
+    Ok(ids.len() as u64)
+}
+
+/// Announces the new notifications `ids` to every instance.
+async fn announce(pool: &PgPool, ids: &[Uuid]) -> Result<(), sqlx::Error> {
+    let announcements = sqlx::query_as::<_, Announcement>(&format!("{} WHERE n.id = ANY($1)", SELECT))
+        .bind(ids)
+        .fetch_all(pool)
+        .await?;
+    for announcement in announcements {
+        let payload = serde_json::to_string(&announcement).unwrap_or_default();
+        sqlx::query("SELECT pg_notify($1, $2)").bind(CHANNEL).bind(payload).execute(pool).await?;
+    }
+    Ok(())
+}
+
+/// Relays announced notifications to the WebSockets open on this
+/// instance. Shared through `AppState`.
+#[derive(Clone)]
+pub struct Notifier {
+    sender: broadcast::Sender<Announcement>,
+}
+
+impl Default for Notifier {
+    fn default() -> Self {
+        Self { sender: broadcast::channel(256).0 }
+    }
+}
+
+impl Notifier {
+    /// Listens for announcements for as long as the process runs, coming
+    /// back after losing the connection.
+    pub fn listen(&self, pool: PgPool) {
+        let sender = self.sender.clone();
+        tokio::spawn(async move {
+            loop {
+                if let Err(err) = relay(&pool, &sender).await {
+                    tracing::warn!(error = %err, "lost the notifications channel");
+                }
+                tokio::time::sleep(Duration::from_secs(5)).await;
+            }
+        });
+    }
+}
+
+async fn relay(pool: &PgPool, sender: &broadcast::Sender<Announcement>) -> Result<(), sqlx::Error> {
+    let mut listener = PgListener::connect_with(pool).await?;
+    listener.listen(CHANNEL).await?;
+    loop {
+        let message = listener.recv().await?;
+        match serde_json::from_str::<Announcement>(message.payload()) {
+            // Nobody listening on this instance is fine.
+            Ok(announcement) => {
+                sender.send(announcement).ok();
+            }
+            Err(err) => tracing::warn!(error = %err, "unreadable notification announcement"),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Inbox {
+    unread: i64,
+    /// The latest `LIMIT`, read or not.
+    notifications: Vec<Notification>,
+}
+
+async fn list_notifications(Reader(pool): Reader, user: AuthUser) -> Result<Json<Inbox>, StatusCode> {
+    let internal = |_| StatusCode::INTERNAL_SERVER_ERROR;
+    let notifications =
+        sqlx::query_as::<_, Notification>(&format!("{} WHERE n.user_id = $1 ORDER BY n.created_at DESC LIMIT $2", SELECT))
+            .bind(user.id)
+            .bind(LIMIT)
+            .fetch_all(&pool)
+            .await
+            .map_err(internal)?;
+    let unread =
+        sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM notifications WHERE user_id = $1 AND read_at IS NULL")
+            .bind(user.id)
+            .fetch_one(&pool)
+            .await
+            .map_err(internal)?;
+
+    Ok(Json(Inbox { unread, notifications }))
+}
+
+#[derive(Deserialize)]
+struct MarkRead {
+    /// Every unread notification when unset.
+    ids: Option<Vec<Uuid>>,
+}
+
+async fn mark_read(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Json(payload): Json<MarkRead>,
+) -> Result<StatusCode, StatusCode> {
+    sqlx::query(
+        "UPDATE notifications SET read_at = NOW() \
+         WHERE user_id = $1 AND read_at IS NULL AND ($2::uuid[] IS NULL OR id = ANY($2))",
+    )
+    .bind(user.id)
+    .bind(payload.ids)
+    .execute(&pool)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Deserialize)]
+struct SocketParams {
+    /// The session token, which browsers can't send as a header here.
+    token: String,
+}
+
+/// Sends the caller each new notification of theirs as it arrives. The
+/// socket only listens; pages load the list itself over HTTP.
+async fn push(
+    State(state): State<AppState>,
+    Query(params): Query<SocketParams>,
+    upgrade: WebSocketUpgrade,
+) -> Result<Response, StatusCode> {
+    let user = auth::verify_socket_token(&state.config, &params.token)?;
+    let receiver = state.notifier.sender.subscribe();
+    Ok(upgrade.on_upgrade(move |socket| serve(socket, receiver, user.id)))
+}
+
+async fn serve(mut socket: WebSocket, mut receiver: broadcast::Receiver<Announcement>, user: Uuid) {
+    loop {
+        tokio::select! {
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+            announced = receiver.recv() => match announced {
+                Ok(announcement) if announcement.user_id == user => {
+                    let message = serde_json::to_string(&announcement.notification).unwrap_or_default();
+                    if socket.send(Message::Text(message)).await.is_err() {
+                        break;
+                    }
+                }
+                Ok(_) => {}
+                // Missed ones still show when the list is next loaded.
+                Err(broadcast::error::RecvError::Lagged(_)) => {}
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+        }
+    }
+}
@@ -0,0 +1,411 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::Redirect,
+    routing::get,
+    Json, Router,
+};
+use oauth2::{
+    basic::BasicClient, reqwest::async_http_client, AuthUrl, AuthorizationCode, ClientId, ClientSecret, CsrfToken,
+    PkceCodeChallenge, PkceCodeVerifier, RedirectUrl, Scope, TokenResponse, TokenUrl,
+};
+use serde::Deserialize;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    auth,
+    config::{Config, OAuthCredentials, RegistrationMode},
+    moderation, AppState,
+};
+
+/// How long a login may take at the provider before its state expires.
+const STATE_TTL_MINUTES: i32 = 10;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/auth/oauth/providers", get(list_providers))
+        .route("/api/auth/oauth/:provider/start", get(start))
+        .route("/api/auth/oauth/:provider/callback", get(callback))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Provider {
+    Google,
+    GitHub,
+}
+
+impl Provider {
+    const ALL: [Provider; 2] = [Provider::Google, Provider::GitHub];
+
+    fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|provider| provider.name() == name)
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Provider::Google => "google",
+            Provider::GitHub => "github",
+        }
+    }
+
+    fn credentials(self, config: &Config) -> Option<&OAuthCredentials> {
+        match self {
+            Provider::Google => config.google_oauth.as_ref(),
+            Provider::GitHub => config.github_oauth.as_ref(),
+        }
+    }
+
+    fn endpoints(self) -> (&'static str, &'static str) {
+        match self {
+            Provider::Google => (
+                "https://accounts.google.com/o/oauth2/v2/auth",
+                "https://oauth2.googleapis.com/token",
+            ),
+            Provider::GitHub => (
+                "https://github.com/login/oauth/authorize",
+                "https://github.com/login/oauth/access_token",
+            ),
+        }
+    }
+
+    /// Enough to learn who the user is and their verified email.
+    fn scopes(self) -> &'static [&'static str] {
+        match self {
+            Provider::Google => &["openid", "email", "profile"],
+            Provider::GitHub => &["read:user", "user:email"],
+        }
+    }
+
+    fn client(self, config: &Config) -> Option<BasicClient> {
+        let credentials = self.credentials(config)?;
+        let (auth_url, token_url) = self.endpoints();
+        let redirect = format!("{}/api/auth/oauth/{}/callback", config.public_url, self.name());
+        Some(
+            BasicClient::new(
+                ClientId::new(credentials.client_id.clone()),
+                Some(ClientSecret::new(credentials.client_secret.clone())),
+                AuthUrl::new(auth_url.to_string()).ok()?,
+                Some(TokenUrl::new(token_url.to_string()).ok()?),
+            )
+            .set_redirect_uri(RedirectUrl::new(redirect).ok()?),
+        )
+    }
+}
+
+/// Unknown providers and ones without credentials are both not found.
+fn configured(config: &Config, name: &str) -> Result<(Provider, BasicClient), StatusCode> {
+    let provider = Provider::parse(name).ok_or(StatusCode::NOT_FOUND)?;
+    let client = provider.client(config).ok_or(StatusCode::NOT_FOUND)?;
+    Ok((provider, client))
+}
+
+/// Names of the providers users can log in with, for the login page.
+async fn list_providers(State(state): State<AppState>) -> Json<Vec<&'static str>> {
+    Json(
+        Provider::ALL
+            .into_iter()
+            .filter(|provider| provider.credentials(&state.config).is_some())
+            .map(Provider::name)
+            .collect(),
+    )
+}
+
+/// Sends the browser to the provider, remembering the CSRF state and PKCE
+/// verifier for the callback.
+async fn start(State(state): State<AppState>, Path(name): Path<String>) -> Result<Redirect, StatusCode> {
+    let (provider, client) = configured(&state.config, &name)?;
+    let (challenge, verifier) = PkceCodeChallenge::new_random_sha256();
+    let mut request = client.authorize_url(CsrfToken::new_random).set_pkce_challenge(challenge);
+    for scope in provider.scopes() {
+        request = request.add_scope(Scope::new(scope.to_string()));
+    }
+    let (url, csrf) = request.url();
+
+    let pool = state.db.writer();
+    sqlx::query("DELETE FROM oauth_states WHERE created_at < NOW() - make_interval(mins => $1)")
+        .bind(STATE_TTL_MINUTES)
+        .execute(pool)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    sqlx::query("INSERT INTO oauth_states (state, provider, pkce_verifier) VALUES ($1, $2, $3)")
+        .bind(csrf.secret())
+        .bind(provider.name())
+        .bind(verifier.secret())
+        .execute(pool)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Redirect::to(url.as_str()))
+}
+
+#[derive(Deserialize)]
+struct CallbackQuery {
+    code: Option<String>,
+    state: Option<String>,
+    /// Set instead of `code` when the user declined.
+    error: Option<String>,
+}
+
+/// Who the provider says the user is.
+#[derive(Debug, PartialEq)]
+struct Identity {
+    subject: String,
+    /// Only ever a verified address.
+    email: String,
+    /// Starting point for a new user's username.
+    name: String,
+}
+
+/// Finishes a login started by `start` and hands the session's tokens to
+/// the login page in the URL fragment, which never reaches a server. Failures
+/// go back there too, as `#error=`.
+async fn callback(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(name): Path<String>,
+    Query(query): Query<CallbackQuery>,
+) -> Result<Redirect, StatusCode> {
+    let (provider, client) = configured(&state.config, &name)?;
+    let login_page = |fragment: String| Redirect::to(&format!("{}/login#{}", state.config.public_url, fragment));
+    let failed = |message: &str| login_page(format!("error={}", message.replace(' ', "%20")));
+
+    let (Some(code), Some(csrf)) = (query.code, query.state) else {
+        return Ok(failed(match query.error.as_deref() {
+            Some("access_denied") => "Login was cancelled",
+            _ => "Login failed",
+        }));
+    };
+
+    let pool = state.db.writer();
+    let verifier = sqlx::query_scalar::<_, String>(
+        r#"
+        DELETE FROM oauth_states
+        WHERE state = $1 AND provider = $2 AND created_at >= NOW() - make_interval(mins => $3)
+        RETURNING pkce_verifier
+        "#,
+    )
+    .bind(&csrf)
+    .bind(provider.name())
+    .bind(STATE_TTL_MINUTES)
+    .fetch_optional(pool)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let Some(verifier) = verifier else {
+        return Ok(failed("Login expired, please try again"));
+    };
+
+    let token = match client
+        .exchange_code(AuthorizationCode::new(code))
+        .set_pkce_verifier(PkceCodeVerifier::new(verifier))
+        .request_async(async_http_client)
+        .await
+    {
+        Ok(token) => token,
+        Err(err) => {
+            tracing::warn!(provider = provider.name(), ?err, "OAuth code exchange failed");
+            return Ok(failed("Login failed"));
+        }
+    };
+    let identity = match fetch_identity(provider, token.access_token().secret()).await {
+        Ok(Some(identity)) => identity,
+        Ok(None) => return Ok(failed("Your account needs a verified email address")),
+        Err(err) => {
+            tracing::warn!(provider = provider.name(), ?err, "failed to fetch OAuth user");
+            return Ok(failed("Login failed"));
+        }
+    };
+
+    let allow_signup = auth::registration_mode(&state) == RegistrationMode::Open;
+    let (id, role) = match sign_in(pool, provider, &identity, allow_signup).await {
+        Ok(Some(user)) => user,
+        Ok(None) => return Ok(failed("Sign-ups need an invite; register with your code first")),
+        Err(err) => {
+            tracing::warn!(provider = provider.name(), ?err, "failed to link OAuth identity");
+            return Ok(failed("Login failed"));
+        }
+    };
+    if moderation::is_banned(pool, id).await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)? {
+        return Ok(failed("This account has been suspended"));
+    }
+    let tokens = auth::start_session(&state, id, &role, &headers).await?;
+    Ok(login_page(format!("token={}&refresh_token={}", tokens.token, tokens.refresh_token)))
+}
+
+#[derive(Deserialize)]
+struct GoogleUser {
+    sub: String,
+    email: Option<String>,
+    #[serde(default)]
+    email_verified: bool,
+    name: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct GitHubUser {
+    id: i64,
+    login: String,
+}
+
+#[derive(Deserialize)]
+struct GitHubEmail {
+    email: String,
+    primary: bool,
+    verified: bool,
+}
+
+/// The user behind `access_token`, or `None` when they have no verified
+/// email to link or sign up with.
+async fn fetch_identity(provider: Provider, access_token: &str) -> Result<Option<Identity>, reqwest::Error> {
+    // GitHub refuses requests without a user agent.
+    let client = reqwest::Client::builder().user_agent("timeline-explorer").build()?;
+    match provider {
+        Provider::Google => {
+            let user: GoogleUser = client
+                .get("https://openidconnect.googleapis.com/v1/userinfo")
+                .bearer_auth(access_token)
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+            Ok(user.email.filter(|_| user.email_verified).map(|email| Identity {
+                name: user.name.unwrap_or_else(|| email.split('@').next().unwrap_or_default().to_string()),
+                subject: user.sub,
+                email,
+            }))
+        }
+        Provider::GitHub => {
+            let user: GitHubUser = client
+                .get("https://api.github.com/user")
+                .bearer_auth(access_token)
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+            let emails: Vec<GitHubEmail> = client
+                .get("https://api.github.com/user/emails")
+                .bearer_auth(access_token)
+                .send()
+                .await?
+                .error_for_status()?
+                .json()
+                .await?;
+            let email = emails
+                .into_iter()
+                .filter(|email| email.verified)
+                .max_by_key(|email| email.primary)
+                .map(|email| email.email);
+            Ok(email.map(|email| Identity { subject: user.id.to_string(), email, name: user.login }))
+        }
+    }
+}
+
+/// The user for `identity`: the one already linked to it, else the one
+/// with the same email, else a new one if `allow_signup`. Returns their id
+/// and role.
+async fn sign_in(
+    pool: &PgPool,
+    provider: Provider,
+    identity: &Identity,
+    allow_signup: bool,
+) -> Result<Option<(Uuid, String)>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let linked = sqlx::query_as::<_, (Uuid, String)>(
+        r#"
+        SELECT u.id, u.role FROM user_identities i JOIN users u ON u.id = i.user_id
+        WHERE i.provider = $1 AND i.subject = $2
+        "#,
+    )
+    .bind(provider.name())
+    .bind(&identity.subject)
+    .fetch_optional(&mut *tx)
+    .await?;
+    if let Some(user) = linked {
+        return Ok(Some(user));
+    }
+
+    let existing = sqlx::query_as::<_, (Uuid, String)>("SELECT id, role FROM users WHERE lower(email) = lower($1)")
+        .bind(&identity.email)
+        .fetch_optional(&mut *tx)
+        .await?;
+    let user = match existing {
+        Some(user) => user,
+        None if !allow_signup => return Ok(None),
+        None => {
+            let username = available_username(&mut tx, &identity.name).await?;
+            sqlx::query_as::<_, (Uuid, String)>(
+                "INSERT INTO users (username, email) VALUES ($1, $2) RETURNING id, role",
+            )
+            .bind(username)
+            .bind(&identity.email)
+            .fetch_one(&mut *tx)
+            .await?
+        }
+    };
+
+    sqlx::query("INSERT INTO user_identities (provider, subject, user_id) VALUES ($1, $2, $3)")
+        .bind(provider.name())
+        .bind(&identity.subject)
+        .bind(user.0)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(Some(user))
+}
+
+/// `name` made into a username, with a number added if it is taken.
+async fn available_username(tx: &mut sqlx::PgConnection, name: &str) -> Result<String, sqlx::Error> {
+    let base = username_base(name);
+    for attempt in 0..20 {
+        let candidate = match attempt {
+            0 => base.clone(),
+            _ => format!("{}{}", base, attempt + 1),
+        };
+        let taken = sqlx::query_scalar::<_, bool>("SELECT EXISTS (SELECT 1 FROM users WHERE lower(username) = lower($1))")
+            .bind(&candidate)
+            .fetch_one(&mut *tx)
+            .await?;
+        if !taken {
+            return Ok(candidate);
+        }
+    }
+    Ok(format!("{}-{}", base, &Uuid::new_v4().simple().to_string()[..8]))
+}
+
+/// Letters, digits, `-` and `_` from `name`, lowercased, at least 3 and at
+/// most 40 characters.
+fn username_base(name: &str) -> String {
+    let mut base: String = name
+        .chars()
+        .map(|c| if c.is_whitespace() { '_' } else { c.to_ascii_lowercase() })
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+        .take(40)
+        .collect();
+    while base.len() < 3 {
+        base.push('_');
+    }
+    base
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_known_providers_parse() {
+        assert_eq!(Provider::parse("google"), Some(Provider::Google));
+        assert_eq!(Provider::parse("github"), Some(Provider::GitHub));
+        assert_eq!(Provider::parse("GitHub"), None);
+    }
+
+    #[test]
+    fn usernames_come_from_display_names() {
+        assert_eq!(username_base("Ada Lovelace"), "ada_lovelace");
+        assert_eq!(username_base("octo-cat"), "octo-cat");
+        assert_eq!(username_base("Zoë"), "zo_");
+        assert_eq!(username_base(&"x".repeat(60)).len(), 40);
+    }
+}
@@ -0,0 +1,420 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, put},
+    Json, Router,
+};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+use crate::{auth::AuthUser, db::Reader, sanitize, validation_error, AppState};
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/organizations", get(list_organizations).post(create_organization))
+        .route(
+            "/api/organizations/:id",
+            get(get_organization).put(update_organization).delete(delete_organization),
+        )
+        .route("/api/organizations/:id/members", get(list_members).post(add_member))
+        .route(
+            "/api/organizations/:id/members/:user_id",
+            put(update_member).delete(remove_member),
+        )
+}
+
+/// A member's standing in an organization, from least to most.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    Member,
+    Admin,
+    Owner,
+}
+
+impl Role {
+    fn name(self) -> &'static str {
+        match self {
+            Role::Member => "member",
+            Role::Admin => "admin",
+            Role::Owner => "owner",
+        }
+    }
+
+    fn parse(name: &str) -> Option<Self> {
+        [Role::Member, Role::Admin, Role::Owner].into_iter().find(|role| role.name() == name)
+    }
+}
+
+/// `user`'s role in `organization`, if they belong to it.
+pub async fn role(pool: &PgPool, organization: Uuid, user: Uuid) -> Result<Option<Role>, Response> {
+    let role = sqlx::query_scalar::<_, String>(
+        "SELECT role FROM organization_members WHERE organization_id = $1 AND user_id = $2",
+    )
+    .bind(organization)
+    .bind(user)
+    .fetch_optional(pool)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
+    Ok(role.as_deref().and_then(Role::parse))
+}
+
+/// Fails unless `user` has at least `needed` in the organization. Outsiders
+/// get 404, so organizations' existence isn't revealed; instance admins
+/// count as owners everywhere.
+pub async fn ensure_role(pool: &PgPool, organization: Uuid, user: &AuthUser, needed: Role) -> Result<Role, Response> {
+    if user.is_admin() {
+        return Ok(Role::Owner);
+    }
+    match role(pool, organization, user.id).await? {
+        None => Err(StatusCode::NOT_FOUND.into_response()),
+        Some(role) if role < needed => Err(StatusCode::FORBIDDEN.into_response()),
+        Some(role) => Ok(role),
+    }
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+struct Organization {
+    id: Uuid,
+    name: String,
+    slug: String,
+    created_at: NaiveDateTime,
+    /// The caller's role; `None` for instance admins who aren't members.
+    role: Option<String>,
+}
+
+#[derive(Deserialize, Validate)]
+struct OrganizationInput {
+    #[validate(length(min = 1, max = 100))]
+    name: String,
+    #[validate(length(min = 2, max = 50), custom(function = "valid_slug"))]
+    slug: String,
+}
+
+fn valid_slug(slug: &str) -> Result<(), ValidationError> {
+    if slug.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-') {
+        Ok(())
+    } else {
+        Err(ValidationError::new("slug"))
+    }
+}
+
+fn save_error(error: sqlx::Error) -> Response {
+    match error {
+        sqlx::Error::Database(error) if error.is_unique_violation() => {
+            (StatusCode::CONFLICT, "that slug is taken").into_response()
+        }
+        _ => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+async fn find(pool: &PgPool, id: Uuid, user: &AuthUser) -> Result<Organization, Response> {
+    sqlx::query_as::<_, Organization>(
+        r#"
+        SELECT o.*, m.role FROM organizations o
+        LEFT JOIN organization_members m ON m.organization_id = o.id AND m.user_id = $2
+        WHERE o.id = $1
+        "#,
+    )
+    .bind(id)
+    .bind(user.id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?
+    .ok_or_else(|| StatusCode::NOT_FOUND.into_response())
+}
+
+/// Organizations the caller belongs to, by name.
+async fn list_organizations(Reader(pool): Reader, user: AuthUser) -> Result<Json<Vec<Organization>>, StatusCode> {
+    sqlx::query_as::<_, Organization>(
+        r#"
+        SELECT o.*, m.role FROM organizations o
+        JOIN organization_members m ON m.organization_id = o.id
+        WHERE m.user_id = $1
+        ORDER BY lower(o.name)
+        "#,
+    )
+    .bind(user.id)
+    .fetch_all(&pool)
+    .await
+    .map(Json)
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// Creates an organization with the caller as its owner.
+async fn create_organization(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Json(mut payload): Json<OrganizationInput>,
+) -> Result<Json<Organization>, Response> {
+    payload.name = sanitize::text(&payload.name).trim().to_string();
+    payload.validate().map_err(validation_error)?;
+
+    let mut tx = pool.begin().await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
+    let id = sqlx::query_scalar::<_, Uuid>("INSERT INTO organizations (name, slug) VALUES ($1, $2) RETURNING id")
+        .bind(&payload.name)
+        .bind(&payload.slug)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(save_error)?;
+    sqlx::query("INSERT INTO organization_members (organization_id, user_id, role) VALUES ($1, $2, 'owner')")
+        .bind(id)
+        .bind(user.id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
+    tx.commit().await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
+
+    find(&pool, id, &user).await.map(Json)
+}
+
+async fn get_organization(
+    Reader(pool): Reader,
+    user: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Organization>, Response> {
+    ensure_role(&pool, id, &user, Role::Member).await?;
+    find(&pool, id, &user).await.map(Json)
+}
+
+async fn update_organization(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(mut payload): Json<OrganizationInput>,
+) -> Result<Json<Organization>, Response> {
+    ensure_role(&pool, id, &user, Role::Admin).await?;
+    payload.name = sanitize::text(&payload.name).trim().to_string();
+    payload.validate().map_err(validation_error)?;
+
+    sqlx::query("UPDATE organizations SET name = $2, slug = $3 WHERE id = $1")
+        .bind(id)
+        .bind(&payload.name)
+        .bind(&payload.slug)
+        .execute(&pool)
+        .await
+        .map_err(save_error)?;
+
+    find(&pool, id, &user).await.map(Json)
+}
+
+/// Deletes the organization with all of its timelines and their events.
+/// Owners only.
+async fn delete_organization(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, Response> {
+    ensure_role(&pool, id, &user, Role::Owner).await?;
+
+    sqlx::query("DELETE FROM organizations WHERE id = $1")
+        .bind(id)
+        .execute(&pool)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+struct Member {
+    user_id: Uuid,
+    username: String,
+    role: String,
+    created_at: NaiveDateTime,
+}
+
+#[derive(Deserialize)]
+struct NewMember {
+    /// Username or email address of an existing user.
+    user: String,
+    role: Role,
+}
+
+#[derive(Deserialize)]
+struct RoleUpdate {
+    role: Role,
+}
+
+/// Only owners make or unmake owners.
+fn ensure_can_grant(caller: Role, role: Role) -> Result<(), (StatusCode, &'static str)> {
+    if role == Role::Owner && caller != Role::Owner {
+        return Err((StatusCode::FORBIDDEN, "only owners can manage owners"));
+    }
+    Ok(())
+}
+
+async fn member(pool: &PgPool, organization: Uuid, user: Uuid) -> Result<Member, Response> {
+    sqlx::query_as::<_, Member>(
+        r#"
+        SELECT m.user_id, u.username, m.role, m.created_at
+        FROM organization_members m JOIN users u ON u.id = m.user_id
+        WHERE m.organization_id = $1 AND m.user_id = $2
+        "#,
+    )
+    .bind(organization)
+    .bind(user)
+    .fetch_optional(pool)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?
+    .ok_or_else(|| StatusCode::NOT_FOUND.into_response())
+}
+
+/// Fails if removing or demoting `user` would leave no owner.
+async fn ensure_other_owner(pool: &PgPool, organization: Uuid, user: Uuid) -> Result<(), Response> {
+    let others = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM organization_members WHERE organization_id = $1 AND role = 'owner' AND user_id <> $2",
+    )
+    .bind(organization)
+    .bind(user)
+    .fetch_one(pool)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
+    if others == 0 {
+        return Err((StatusCode::CONFLICT, "an organization needs at least one owner").into_response());
+    }
+    Ok(())
+}
+
+async fn list_members(
+    Reader(pool): Reader,
+    user: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<Member>>, Response> {
+    ensure_role(&pool, id, &user, Role::Member).await?;
+
+    sqlx::query_as::<_, Member>(
+        r#"
+        SELECT m.user_id, u.username, m.role, m.created_at
+        FROM organization_members m JOIN users u ON u.id = m.user_id
+        WHERE m.organization_id = $1
+        ORDER BY u.username
+        "#,
+    )
+    .bind(id)
+    .fetch_all(&pool)
+    .await
+    .map(Json)
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())
+}
+
+/// Adds an existing user, found by username or email. Adding a member
+/// again changes their role.
+async fn add_member(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<NewMember>,
+) -> Result<Json<Member>, Response> {
+    let caller = ensure_role(&pool, id, &user, Role::Admin).await?;
+    ensure_can_grant(caller, payload.role).map_err(IntoResponse::into_response)?;
+
+    let invitee = sqlx::query_scalar::<_, Uuid>("SELECT id FROM users WHERE username = $1 OR lower(email) = lower($1)")
+        .bind(payload.user.trim())
+        .fetch_optional(&pool)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "no user with that username or email").into_response())?;
+    if let Some(current) = role(&pool, id, invitee).await? {
+        ensure_can_grant(caller, current).map_err(IntoResponse::into_response)?;
+        if current == Role::Owner && payload.role != Role::Owner {
+            ensure_other_owner(&pool, id, invitee).await?;
+        }
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO organization_members (organization_id, user_id, role) VALUES ($1, $2, $3)
+        ON CONFLICT (organization_id, user_id) DO UPDATE SET role = EXCLUDED.role
+        "#,
+    )
+    .bind(id)
+    .bind(invitee)
+    .bind(payload.role.name())
+    .execute(&pool)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
+
+    member(&pool, id, invitee).await.map(Json)
+}
+
+async fn update_member(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Path((id, user_id)): Path<(Uuid, Uuid)>,
+    Json(payload): Json<RoleUpdate>,
+) -> Result<Json<Member>, Response> {
+    let caller = ensure_role(&pool, id, &user, Role::Admin).await?;
+    let current = role(&pool, id, user_id).await?.ok_or_else(|| StatusCode::NOT_FOUND.into_response())?;
+    ensure_can_grant(caller, current).map_err(IntoResponse::into_response)?;
+    ensure_can_grant(caller, payload.role).map_err(IntoResponse::into_response)?;
+    if current == Role::Owner && payload.role != Role::Owner {
+        ensure_other_owner(&pool, id, user_id).await?;
+    }
+
+    sqlx::query("UPDATE organization_members SET role = $3 WHERE organization_id = $1 AND user_id = $2")
+        .bind(id)
+        .bind(user_id)
+        .bind(payload.role.name())
+        .execute(&pool)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
+
+    member(&pool, id, user_id).await.map(Json)
+}
+
+/// Admins remove members; anyone may leave. The last owner can do neither.
+async fn remove_member(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Path((id, user_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, Response> {
+    let current = role(&pool, id, user_id).await?;
+    if user.id != user_id {
+        let caller = ensure_role(&pool, id, &user, Role::Admin).await?;
+        if let Some(current) = current {
+            ensure_can_grant(caller, current).map_err(IntoResponse::into_response)?;
+        }
+    }
+    if current == Some(Role::Owner) {
+        ensure_other_owner(&pool, id, user_id).await?;
+    }
+
+    sqlx::query("DELETE FROM organization_members WHERE organization_id = $1 AND user_id = $2")
+        .bind(id)
+        .bind(user_id)
+        .execute(&pool)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roles_are_ordered_by_power() {
+        assert!(Role::Member < Role::Admin && Role::Admin < Role::Owner);
+        assert_eq!(Role::parse("admin"), Some(Role::Admin));
+        assert_eq!(Role::parse("editor"), None);
+    }
+
+    #[test]
+    fn only_owners_manage_owners() {
+        assert!(ensure_can_grant(Role::Owner, Role::Owner).is_ok());
+        assert!(ensure_can_grant(Role::Admin, Role::Owner).is_err());
+        assert!(ensure_can_grant(Role::Admin, Role::Member).is_ok());
+    }
+
+    #[test]
+    fn slugs_are_lowercase_words() {
+        assert!(valid_slug("history-club-42").is_ok());
+        assert!(valid_slug("History Club").is_err());
+    }
+}
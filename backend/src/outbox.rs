@@ -0,0 +1,276 @@
+//! The outbox: side effects such as webhooks, emails and event stream
+//! messages are queued in the `outbox` table by the transaction whose
+//! change they announce, so they go out if and only if it commits. The
+//! relay job claims a batch, sends it outside any transaction and then
+//! records which went. A message is marked delivered only once its
+//! destination has it, so a crash between the two sends it again;
+//! recipients see every message at least once.
+
+use std::collections::HashSet;
+use std::future::Future;
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{PgConnection, PgPool};
+
+use crate::{bus::Bus, config::Config, flags::Flags, mailer, webhooks};
+
+/// Messages claimed per run.
+const BATCH: i64 = 500;
+
+/// How long a claimed batch is kept from other relays, in seconds. Long
+/// enough to send a whole batch; a relay that dies leaves its claim to run
+/// out.
+const CLAIM: i32 = 15 * 60;
+
+/// Longest wait between attempts at a message, in seconds.
+const MAX_BACKOFF: i32 = 60 * 60;
+
+#[derive(sqlx::FromRow)]
+struct Message {
+    id: i64,
+    destination: String,
+    topic: String,
+    key: String,
+    payload: Value,
+    attempts: i32,
+    next_attempt_at: NaiveDateTime,
+}
+
+/// The payload of a `mail` message.
+#[derive(Serialize, Deserialize)]
+struct Mail {
+    to: String,
+    subject: String,
+    body: String,
+}
+
+async fn queue(
+    conn: &mut PgConnection,
+    destination: &str,
+    topic: &str,
+    key: &str,
+    payload: Value,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("INSERT INTO outbox (destination, topic, key, payload) VALUES ($1, $2, $3, $4)")
+        .bind(destination)
+        .bind(topic)
+        .bind(key)
+        .bind(payload)
+        .execute(conn)
+        .await
+        .map(|_| ())
+}
+
+/// Queues a `kind` webhook carrying `data`, sent through
+/// `webhooks::notify`. Webhooks with the same `key` go in order.
+pub async fn queue_webhook<T: Serialize>(
+    conn: &mut PgConnection,
+    kind: &str,
+    key: &str,
+    data: &T,
+) -> Result<(), sqlx::Error> {
+    let data = serde_json::to_value(data).map_err(|err| sqlx::Error::Protocol(err.to_string()))?;
+    queue(conn, "webhook", kind, key, data).await
+}
+
+/// Queues a plain-text email, sent through `mailer::send`.
+pub async fn queue_mail(conn: &mut PgConnection, to: &str, subject: &str, body: &str) -> Result<(), sqlx::Error> {
+    let mail = Mail {
+        to: to.to_string(),
+        subject: subject.to_string(),
+        body: body.to_string(),
+    };
+    let payload = serde_json::to_value(mail).map_err(|err| sqlx::Error::Protocol(err.to_string()))?;
+    queue(conn, "mail", "email", to, payload).await
+}
+
+/// Seconds until the next attempt after `attempts` failures: doubling from
+/// two seconds up to an hour.
+fn backoff(attempts: i32) -> i32 {
+    2_i32.saturating_pow(attempts.clamp(1, 12) as u32).min(MAX_BACKOFF)
+}
+
+async fn send(bus: &Bus, config: &Config, flags: &Flags, message: &Message) -> Result<(), String> {
+    match message.destination.as_str() {
+        "bus" => {
+            let payload = serde_json::to_vec(&message.payload).map_err(|err| err.to_string())?;
+            bus.publish(&message.topic, &message.key, &payload)
+                .await
+                .map_err(|err| err.to_string())
+        }
+        "webhook" => webhooks::notify(config, flags, &message.topic, &message.payload)
+            .await
+            .map_err(|err| err.to_string()),
+        "mail" => {
+            let mail = Mail::deserialize(&message.payload).map_err(|err| err.to_string())?;
+            mailer::send(config, &mail.to, &mail.subject, &mail.body)
+                .await
+                .map_err(|err| err.to_string())
+        }
+        other => Err(format!("unknown destination {:?}", other)),
+    }
+}
+
+/// Sends `messages` in order and returns what became of each one that was
+/// tried. Once a key has a message waiting or failing, its later messages
+/// stay put so they can't overtake it.
+async fn deliver<'a, F, Fut>(messages: &'a [Message], now: NaiveDateTime, send: F) -> Vec<(&'a Message, Result<(), String>)>
+where
+    F: Fn(&'a Message) -> Fut,
+    Fut: Future<Output = Result<(), String>>,
+{
+    let mut blocked = HashSet::new();
+    let mut outcomes = Vec::new();
+    for message in messages {
+        let key = (message.destination.as_str(), message.key.as_str());
+        if blocked.contains(&key) || message.next_attempt_at > now {
+            blocked.insert(key);
+            continue;
+        }
+        let outcome = send(message).await;
+        if outcome.is_err() {
+            blocked.insert(key);
+        }
+        outcomes.push((message, outcome));
+    }
+    outcomes
+}
+
+/// Delivers what is waiting in the outbox. The batch is claimed in one
+/// statement, so no transaction stays open while messages are sent; other
+/// instances skip claimed rows, and rows whose key has an earlier message
+/// claimed elsewhere, until the claim runs out. Returns the messages
+/// delivered.
+pub async fn relay(pool: &PgPool, bus: &Bus, config: &Config, flags: &Flags) -> Result<u64, sqlx::Error> {
+    let mut messages = sqlx::query_as::<_, Message>(
+        r#"
+        UPDATE outbox SET claimed_until = NOW() + make_interval(secs => $2)
+        WHERE id IN (
+            SELECT id FROM outbox
+            WHERE delivered_at IS NULL
+              AND (claimed_until IS NULL OR claimed_until < NOW())
+              AND NOT EXISTS (
+                  SELECT 1 FROM outbox earlier
+                  WHERE earlier.destination = outbox.destination
+                    AND earlier.key = outbox.key
+                    AND earlier.id < outbox.id
+                    AND earlier.delivered_at IS NULL
+                    AND earlier.claimed_until >= NOW()
+              )
+            ORDER BY id
+            LIMIT $1
+            FOR UPDATE SKIP LOCKED
+        )
+        RETURNING id, destination, topic, key, payload, attempts, next_attempt_at
+        "#,
+    )
+    .bind(BATCH)
+    .bind(CLAIM as f64)
+    .fetch_all(pool)
+    .await?;
+    if messages.is_empty() {
+        return Ok(0);
+    }
+    messages.sort_by_key(|message| message.id);
+
+    let now = chrono::Utc::now().naive_utc();
+    let outcomes = deliver(&messages, now, |message| send(bus, config, flags, message)).await;
+
+    let mut tx = pool.begin().await?;
+    let mut delivered = 0;
+    for (message, outcome) in outcomes {
+        match outcome {
+            Ok(()) => {
+                sqlx::query(
+                    "UPDATE outbox SET delivered_at = NOW(), attempts = attempts + 1, last_error = NULL WHERE id = $1",
+                )
+                .bind(message.id)
+                .execute(&mut *tx)
+                .await?;
+                delivered += 1;
+            }
+            Err(error) => {
+                let attempts = message.attempts + 1;
+                tracing::warn!(id = message.id, attempts, error, "outbox message not delivered");
+                sqlx::query(
+                    r#"
+                    UPDATE outbox SET attempts = $2, last_error = $3, next_attempt_at = NOW() + make_interval(secs => $4)
+                    WHERE id = $1
+                    "#,
+                )
+                .bind(message.id)
+                .bind(attempts)
+                .bind(&error)
+                .bind(backoff(attempts) as f64)
+                .execute(&mut *tx)
+                .await?;
+            }
+        }
+    }
+    let claimed: Vec<i64> = messages.iter().map(|message| message.id).collect();
+    sqlx::query("UPDATE outbox SET claimed_until = NULL WHERE id = ANY($1)")
+        .bind(&claimed)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+    Ok(delivered)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    fn message(id: i64, key: &str, next_attempt_at: NaiveDateTime) -> Message {
+        Message {
+            id,
+            destination: "bus".to_string(),
+            topic: "event.updated".to_string(),
+            key: key.to_string(),
+            payload: serde_json::json!({ "id": key }),
+            attempts: 0,
+            next_attempt_at,
+        }
+    }
+
+    #[tokio::test]
+    async fn later_messages_wait_behind_a_failed_one() {
+        let now = chrono::Utc::now().naive_utc();
+        let later = now + chrono::Duration::minutes(5);
+        let messages = [
+            message(1, "a", now),
+            message(2, "b", now),
+            message(3, "c", later),
+            message(4, "a", now),
+            message(5, "b", now),
+            message(6, "c", now),
+        ];
+        let sent = Mutex::new(Vec::new());
+
+        let outcomes = deliver(&messages, now, |message| {
+            let outcome = match message.key.as_str() {
+                "b" => Err("broker unavailable".to_string()),
+                _ => {
+                    sent.lock().unwrap().push(message.id);
+                    Ok(())
+                }
+            };
+            async move { outcome }
+        })
+        .await;
+
+        let tried: Vec<_> = outcomes.iter().map(|(message, outcome)| (message.id, outcome.is_ok())).collect();
+        assert_eq!(tried, vec![(1, true), (2, false), (4, true)]);
+        assert_eq!(*sent.lock().unwrap(), vec![1, 4]);
+    }
+
+    #[test]
+    fn retries_back_off_to_an_hour() {
+        assert_eq!(backoff(1), 2);
+        assert_eq!(backoff(5), 32);
+        assert_eq!(backoff(40), MAX_BACKOFF);
+    }
+}
@@ -0,0 +1,309 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+use crate::{
+    auth::AuthUser,
+    db::{events::Events, Reader},
+    images, sanitize, timelines, validation_error, AppState, Event,
+};
+
+const MAX_PEOPLE_PER_EVENT: usize = 50;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/people", get(list_people).post(create_person))
+        .route(
+            "/api/people/:id",
+            get(get_person).put(update_person).delete(delete_person),
+        )
+        .route("/api/people/:id/events", get(get_person_events))
+        .route("/api/events/:id/people", get(get_event_people).put(set_event_people))
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+struct Person {
+    id: Uuid,
+    name: String,
+    birth_date: Option<NaiveDate>,
+    death_date: Option<NaiveDate>,
+    bio: Option<String>,
+    image_url: Option<String>,
+    created_at: NaiveDateTime,
+    updated_at: NaiveDateTime,
+}
+
+/// Body of `POST /api/people` and `PUT /api/people/:id`; a replacement
+/// clears omitted fields.
+#[derive(Deserialize, Validate)]
+#[validate(schema(function = "lifespan"))]
+struct PersonInput {
+    #[validate(length(min = 1, max = 255))]
+    name: String,
+    birth_date: Option<NaiveDate>,
+    death_date: Option<NaiveDate>,
+    #[validate(length(max = 10_000))]
+    bio: Option<String>,
+    #[validate(length(max = 512), custom(function = "images::valid_url"))]
+    image_url: Option<String>,
+}
+
+impl PersonInput {
+    fn sanitize(&mut self) {
+        self.name = sanitize::text(&self.name);
+        sanitize::optional(&mut self.bio);
+    }
+}
+
+/// Nobody dies before they are born.
+fn lifespan(person: &PersonInput) -> Result<(), ValidationError> {
+    match (person.birth_date, person.death_date) {
+        (Some(birth), Some(death)) if death < birth => Err(ValidationError::new("death_before_birth")),
+        _ => Ok(()),
+    }
+}
+
+#[derive(Deserialize)]
+struct ListQuery {
+    /// Case-insensitive substring of the name.
+    search: Option<String>,
+}
+
+async fn list_people(Reader(pool): Reader, Query(query): Query<ListQuery>) -> Result<Json<Vec<Person>>, StatusCode> {
+    let pattern = format!("%{}%", query.search.unwrap_or_default());
+    let people = sqlx::query_as::<_, Person>("SELECT * FROM people WHERE name ILIKE $1 ORDER BY lower(name)")
+        .bind(pattern)
+        .fetch_all(&pool)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(people))
+}
+
+async fn get_person(Reader(pool): Reader, Path(id): Path<Uuid>) -> Result<Json<Person>, StatusCode> {
+    sqlx::query_as::<_, Person>("SELECT * FROM people WHERE id = $1")
+        .bind(id)
+        .fetch_optional(&pool)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .map(Json)
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+/// Anyone signed in may add people, as with loose events.
+async fn create_person(
+    State(pool): State<PgPool>,
+    _user: AuthUser,
+    Json(mut payload): Json<PersonInput>,
+) -> Result<Json<Person>, Response> {
+    payload.sanitize();
+    payload.validate().map_err(validation_error)?;
+
+    let person = sqlx::query_as::<_, Person>(
+        r#"
+        INSERT INTO people (name, birth_date, death_date, bio, image_url)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING *
+        "#,
+    )
+    .bind(payload.name.trim())
+    .bind(payload.birth_date)
+    .bind(payload.death_date)
+    .bind(payload.bio)
+    .bind(payload.image_url)
+    .fetch_one(&pool)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
+
+    Ok(Json(person))
+}
+
+async fn update_person(
+    State(pool): State<PgPool>,
+    _user: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(mut payload): Json<PersonInput>,
+) -> Result<Json<Person>, Response> {
+    payload.sanitize();
+    payload.validate().map_err(validation_error)?;
+
+    let person = sqlx::query_as::<_, Person>(
+        r#"
+        UPDATE people SET
+            name = $2,
+            birth_date = $3,
+            death_date = $4,
+            bio = $5,
+            image_url = $6,
+            updated_at = NOW()
+        WHERE id = $1
+        RETURNING *
+        "#,
+    )
+    .bind(id)
+    .bind(payload.name.trim())
+    .bind(payload.birth_date)
+    .bind(payload.death_date)
+    .bind(payload.bio)
+    .bind(payload.image_url)
+    .fetch_optional(&pool)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?
+    .ok_or_else(|| StatusCode::NOT_FOUND.into_response())?;
+
+    Ok(Json(person))
+}
+
+/// People are shared by every timeline, so only admins remove them.
+async fn delete_person(State(pool): State<PgPool>, user: AuthUser, Path(id): Path<Uuid>) -> Result<StatusCode, StatusCode> {
+    user.require_admin()?;
+
+    let result = sqlx::query("DELETE FROM people WHERE id = $1")
+        .bind(id)
+        .execute(&pool)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if result.rows_affected() == 0 {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// The person's public events, earliest first: their personal timeline.
+async fn get_person_events(Reader(pool): Reader, Path(id): Path<Uuid>) -> Result<Json<Vec<Event>>, StatusCode> {
+    let events = sqlx::query_as::<_, Event>(&format!(
+        r#"
+        SELECT e.* FROM events e
+        JOIN event_people ep ON ep.event_id = e.id
+        WHERE ep.person_id = $1 AND {}
+        ORDER BY e.start_date
+        "#,
+        timelines::PUBLIC_EVENT
+    ))
+    .bind(id)
+    .fetch_all(&pool)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(events))
+}
+
+async fn get_event_people(
+    Reader(pool): Reader,
+    State(events): State<Events>,
+    user: Option<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<Person>>, Response> {
+    timelines::ensure_event_visible(&pool, &events, user.as_ref(), id).await?;
+    let people = event_people(&pool, id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
+
+    Ok(Json(people))
+}
+
+/// Replaces the people linked to the event with the given ids.
+async fn set_event_people(
+    State(pool): State<PgPool>,
+    State(events): State<Events>,
+    user: Option<AuthUser>,
+    Path(id): Path<Uuid>,
+    Json(mut ids): Json<Vec<Uuid>>,
+) -> Result<Json<Vec<Person>>, Response> {
+    ids.sort();
+    ids.dedup();
+    if ids.len() > MAX_PEOPLE_PER_EVENT {
+        return Err(StatusCode::UNPROCESSABLE_ENTITY.into_response());
+    }
+    timelines::ensure_event_writable(&pool, &events, user.as_ref(), id).await?;
+    let error = |_| StatusCode::INTERNAL_SERVER_ERROR.into_response();
+
+    let mut tx = pool.begin().await.map_err(error)?;
+
+    let exists = sqlx::query_scalar::<_, bool>("SELECT EXISTS (SELECT 1 FROM events WHERE id = $1)")
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(error)?;
+    if !exists {
+        return Err(StatusCode::NOT_FOUND.into_response());
+    }
+    let known = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM people WHERE id = ANY($1)")
+        .bind(&ids)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(error)?;
+    if known != ids.len() as i64 {
+        return Err((StatusCode::UNPROCESSABLE_ENTITY, "unknown person").into_response());
+    }
+
+    sqlx::query("DELETE FROM event_people WHERE event_id = $1")
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(error)?;
+    sqlx::query("INSERT INTO event_people (event_id, person_id) SELECT $1, UNNEST($2::uuid[])")
+        .bind(id)
+        .bind(&ids)
+        .execute(&mut *tx)
+        .await
+        .map_err(error)?;
+
+    tx.commit().await.map_err(error)?;
+
+    let people = event_people(&pool, id).await.map_err(error)?;
+    Ok(Json(people))
+}
+
+async fn event_people(pool: &PgPool, event_id: Uuid) -> Result<Vec<Person>, sqlx::Error> {
+    sqlx::query_as::<_, Person>(
+        r#"
+        SELECT p.* FROM people p
+        JOIN event_people ep ON ep.person_id = p.id
+        WHERE ep.event_id = $1
+        ORDER BY lower(p.name)
+        "#,
+    )
+    .bind(event_id)
+    .fetch_all(pool)
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn person(birth: Option<(i32, u32, u32)>, death: Option<(i32, u32, u32)>) -> PersonInput {
+        let date = |(year, month, day)| NaiveDate::from_ymd_opt(year, month, day).unwrap();
+        PersonInput {
+            name: "Ada Lovelace".to_string(),
+            birth_date: birth.map(date),
+            death_date: death.map(date),
+            bio: None,
+            image_url: None,
+        }
+    }
+
+    #[test]
+    fn death_must_not_precede_birth() {
+        assert!(person(Some((1815, 12, 10)), Some((1852, 11, 27))).validate().is_ok());
+        assert!(person(Some((1852, 11, 27)), Some((1815, 12, 10))).validate().is_err());
+    }
+
+    #[test]
+    fn either_date_may_be_unknown() {
+        assert!(person(None, Some((1852, 11, 27))).validate().is_ok());
+        assert!(person(Some((1815, 12, 10)), None).validate().is_ok());
+        assert!(person(None, None).validate().is_ok());
+    }
+}
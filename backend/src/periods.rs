@@ -0,0 +1,176 @@
+//! Periods: named date ranges on a timeline, such as "Renaissance", that
+//! the timeline draws as bands behind its events. They are read with the
+//! timeline and written by its editors.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, put},
+    Json, Router,
+};
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+use crate::{auth::AuthUser, db::Reader, sanitize, timelines, validation_error, AppState};
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/timelines/:id/periods", get(list_periods).post(create_period))
+        .route("/api/periods/:id", put(update_period).delete(delete_period))
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+struct Period {
+    id: Uuid,
+    timeline_id: Uuid,
+    name: String,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    color: String,
+    created_at: NaiveDateTime,
+    updated_at: NaiveDateTime,
+}
+
+/// Body of both POST and PUT.
+#[derive(Deserialize, Validate)]
+#[validate(schema(function = "range"))]
+struct PeriodInput {
+    #[validate(length(min = 1, max = 255))]
+    name: String,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    #[validate(custom(function = "crate::appearance::valid_color"))]
+    color: String,
+}
+
+/// A period may last a single day but cannot end before it starts.
+fn range(period: &PeriodInput) -> Result<(), ValidationError> {
+    if period.end_date < period.start_date {
+        return Err(ValidationError::new("end_before_start"));
+    }
+    Ok(())
+}
+
+/// The timeline a period is on.
+async fn timeline_of(pool: &PgPool, id: Uuid) -> Result<Uuid, Response> {
+    sqlx::query_scalar::<_, Uuid>("SELECT timeline_id FROM periods WHERE id = $1")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?
+        .ok_or_else(|| StatusCode::NOT_FOUND.into_response())
+}
+
+/// The timeline's periods, earliest first.
+async fn list_periods(
+    Reader(pool): Reader,
+    user: Option<AuthUser>,
+    Path(timeline_id): Path<Uuid>,
+) -> Result<Json<Vec<Period>>, Response> {
+    timelines::find_visible(&pool, timeline_id, user.as_ref()).await?;
+    let periods = sqlx::query_as::<_, Period>(
+        "SELECT * FROM periods WHERE timeline_id = $1 ORDER BY start_date, end_date DESC",
+    )
+    .bind(timeline_id)
+    .fetch_all(&pool)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
+
+    Ok(Json(periods))
+}
+
+async fn create_period(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Path(timeline_id): Path<Uuid>,
+    Json(mut payload): Json<PeriodInput>,
+) -> Result<(StatusCode, Json<Period>), Response> {
+    payload.name = sanitize::text(&payload.name);
+    payload.validate().map_err(validation_error)?;
+    timelines::ensure_timeline_writable(&pool, Some(&user), Some(timeline_id)).await?;
+
+    let period = sqlx::query_as::<_, Period>(
+        r#"
+        INSERT INTO periods (timeline_id, name, start_date, end_date, color)
+        VALUES ($1, $2, $3, $4, lower($5))
+        RETURNING *
+        "#,
+    )
+    .bind(timeline_id)
+    .bind(payload.name.trim())
+    .bind(payload.start_date)
+    .bind(payload.end_date)
+    .bind(&payload.color)
+    .fetch_one(&pool)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
+
+    Ok((StatusCode::CREATED, Json(period)))
+}
+
+async fn update_period(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(mut payload): Json<PeriodInput>,
+) -> Result<Json<Period>, Response> {
+    payload.name = sanitize::text(&payload.name);
+    payload.validate().map_err(validation_error)?;
+    let timeline_id = timeline_of(&pool, id).await?;
+    timelines::ensure_timeline_writable(&pool, Some(&user), Some(timeline_id)).await?;
+
+    let period = sqlx::query_as::<_, Period>(
+        r#"
+        UPDATE periods SET name = $2, start_date = $3, end_date = $4, color = lower($5), updated_at = NOW()
+        WHERE id = $1
+        RETURNING *
+        "#,
+    )
+    .bind(id)
+    .bind(payload.name.trim())
+    .bind(payload.start_date)
+    .bind(payload.end_date)
+    .bind(&payload.color)
+    .fetch_one(&pool)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
+
+    Ok(Json(period))
+}
+
+async fn delete_period(State(pool): State<PgPool>, user: AuthUser, Path(id): Path<Uuid>) -> Result<StatusCode, Response> {
+    let timeline_id = timeline_of(&pool, id).await?;
+    timelines::ensure_timeline_writable(&pool, Some(&user), Some(timeline_id)).await?;
+    sqlx::query("DELETE FROM periods WHERE id = $1")
+        .bind(id)
+        .execute(&pool)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn period(start: &str, end: &str, color: &str) -> PeriodInput {
+        PeriodInput {
+            name: "Renaissance".to_string(),
+            start_date: start.parse().unwrap(),
+            end_date: end.parse().unwrap(),
+            color: color.to_string(),
+        }
+    }
+
+    #[test]
+    fn periods_need_an_ordered_range_and_a_hex_color() {
+        assert!(period("1300-01-01", "1600-12-31", "#F59E0B").validate().is_ok());
+        assert!(period("1500-06-01", "1500-06-01", "#f59e0b").validate().is_ok());
+        assert!(period("1600-01-01", "1300-01-01", "#f59e0b").validate().is_err());
+        assert!(period("1300-01-01", "1600-12-31", "orange").validate().is_err());
+    }
+}
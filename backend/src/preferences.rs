@@ -0,0 +1,153 @@
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+use crate::{auth::AuthUser, db::Reader, email_templates, timelines, validation_error, AppState};
+
+/// Themes the frontend offers; `system` follows the browser.
+pub const THEMES: &[&str] = &["system", "light", "dark"];
+/// Orders `GET /api/events` accepts as `sort`.
+pub const SORTS: &[&str] = &["newest", "oldest", "likes"];
+
+pub fn routes() -> Router<AppState> {
+    Router::new().route("/api/me/preferences", get(get_preferences).put(save_preferences))
+}
+
+/// Body and response of `/api/me/preferences`.
+#[derive(Serialize, Deserialize, Validate, sqlx::FromRow, Debug, PartialEq)]
+pub struct Preferences {
+    /// Timeline opened from the home page instead of all events.
+    pub default_timeline_id: Option<Uuid>,
+    #[validate(custom(function = "valid_theme"))]
+    pub theme: String,
+    #[validate(custom(function = "valid_locale"))]
+    pub locale: String,
+    #[validate(range(min = 1, max = 100))]
+    pub items_per_page: i16,
+    #[validate(custom(function = "valid_sort"))]
+    pub default_sort: String,
+}
+
+impl Default for Preferences {
+    /// Matches the column defaults.
+    fn default() -> Self {
+        Self {
+            default_timeline_id: None,
+            theme: "system".to_string(),
+            locale: email_templates::DEFAULT_LOCALE.to_string(),
+            items_per_page: 20,
+            default_sort: "newest".to_string(),
+        }
+    }
+}
+
+fn valid_theme(theme: &str) -> Result<(), ValidationError> {
+    if THEMES.contains(&theme) {
+        Ok(())
+    } else {
+        Err(ValidationError::new("theme"))
+    }
+}
+
+fn valid_locale(locale: &str) -> Result<(), ValidationError> {
+    if email_templates::valid_locale(locale) {
+        Ok(())
+    } else {
+        Err(ValidationError::new("locale"))
+    }
+}
+
+fn valid_sort(sort: &str) -> Result<(), ValidationError> {
+    if SORTS.contains(&sort) {
+        Ok(())
+    } else {
+        Err(ValidationError::new("sort"))
+    }
+}
+
+/// `user`'s preferences, or the defaults if they never saved any.
+pub async fn find(pool: &PgPool, user: Uuid) -> Result<Preferences, sqlx::Error> {
+    let preferences = sqlx::query_as::<_, Preferences>(
+        r#"
+        SELECT default_timeline_id, theme, locale, items_per_page, default_sort
+        FROM user_preferences WHERE user_id = $1
+        "#,
+    )
+    .bind(user)
+    .fetch_optional(pool)
+    .await?;
+    Ok(preferences.unwrap_or_default())
+}
+
+async fn get_preferences(Reader(pool): Reader, user: AuthUser) -> Result<Json<Preferences>, StatusCode> {
+    find(&pool, user.id)
+        .await
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// Replaces the caller's preferences. The default timeline has to be one
+/// they can see.
+async fn save_preferences(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Json(payload): Json<Preferences>,
+) -> Result<Json<Preferences>, Response> {
+    payload.validate().map_err(validation_error)?;
+    if let Some(id) = payload.default_timeline_id {
+        timelines::find_visible(&pool, id, Some(&user)).await?;
+    }
+
+    sqlx::query_as::<_, Preferences>(
+        r#"
+        INSERT INTO user_preferences (user_id, default_timeline_id, theme, locale, items_per_page, default_sort)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (user_id) DO UPDATE SET
+            default_timeline_id = EXCLUDED.default_timeline_id,
+            theme = EXCLUDED.theme,
+            locale = EXCLUDED.locale,
+            items_per_page = EXCLUDED.items_per_page,
+            default_sort = EXCLUDED.default_sort,
+            updated_at = NOW()
+        RETURNING default_timeline_id, theme, locale, items_per_page, default_sort
+        "#,
+    )
+    .bind(user.id)
+    .bind(payload.default_timeline_id)
+    .bind(&payload.theme)
+    .bind(&payload.locale)
+    .bind(payload.items_per_page)
+    .bind(&payload.default_sort)
+    .fetch_one(&pool)
+    .await
+    .map(Json)
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defaults_are_valid() {
+        assert!(Preferences::default().validate().is_ok());
+    }
+
+    #[test]
+    fn rejects_unknown_choices() {
+        let theme = Preferences { theme: "neon".to_string(), ..Preferences::default() };
+        assert!(theme.validate().is_err());
+        let sort = Preferences { default_sort: "random".to_string(), ..Preferences::default() };
+        assert!(sort.validate().is_err());
+        let page = Preferences { items_per_page: 500, ..Preferences::default() };
+        assert!(page.validate().is_err());
+    }
+}
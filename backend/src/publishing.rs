@@ -0,0 +1,118 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post, put},
+    Json, Router,
+};
+use chrono::NaiveDateTime;
+use serde::Deserialize;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    auth::AuthUser,
+    db::events::{EventRepository, Events},
+    timelines, AppState, Event,
+};
+
+/// Values of `events.status`. Only published events are shown publicly.
+pub const STATUSES: [&str; 3] = ["draft", "published", "archived"];
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/events/:id/publish", post(publish_event))
+        .route("/api/events/:id/status", put(update_status))
+        .route("/api/events/:id/schedule", put(schedule_event))
+        .route("/api/events/scheduled", get(list_scheduled))
+}
+
+#[derive(Deserialize)]
+struct StatusUpdate {
+    status: String,
+}
+
+#[derive(Deserialize)]
+struct Schedule {
+    /// UTC time to publish at; `null` cancels the schedule.
+    publish_at: Option<NaiveDateTime>,
+}
+
+pub fn valid_status(status: &str) -> Result<(), (StatusCode, &'static str)> {
+    if STATUSES.contains(&status) {
+        Ok(())
+    } else {
+        Err((StatusCode::UNPROCESSABLE_ENTITY, "status must be draft, published or archived"))
+    }
+}
+
+async fn set_status(
+    pool: &PgPool,
+    events: &Events,
+    user: &AuthUser,
+    id: Uuid,
+    status: &str,
+) -> Result<Event, Response> {
+    valid_status(status).map_err(IntoResponse::into_response)?;
+    timelines::ensure_event_writable(pool, events, Some(user), id).await?;
+
+    events
+        .set_status(id, status)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?
+        .ok_or_else(|| StatusCode::NOT_FOUND.into_response())
+}
+
+async fn publish_event(
+    State(pool): State<PgPool>,
+    State(events): State<Events>,
+    user: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Event>, Response> {
+    set_status(&pool, &events, &user, id, "published").await.map(Json)
+}
+
+/// Moves an event between draft, published and archived.
+async fn update_status(
+    State(pool): State<PgPool>,
+    State(events): State<Events>,
+    user: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<StatusUpdate>,
+) -> Result<Json<Event>, Response> {
+    set_status(&pool, &events, &user, id, &payload.status).await.map(Json)
+}
+
+/// Sets or clears the time a draft is published automatically.
+async fn schedule_event(
+    State(pool): State<PgPool>,
+    State(events): State<Events>,
+    user: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<Schedule>,
+) -> Result<Json<Event>, Response> {
+    timelines::ensure_event_writable(&pool, &events, Some(&user), id).await?;
+
+    events
+        .schedule(id, payload.publish_at)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?
+        .ok_or_else(|| (StatusCode::CONFLICT, "only drafts can be scheduled").into_response())
+        .map(Json)
+}
+
+/// Drafts waiting to be published that the caller may edit, soonest first.
+async fn list_scheduled(State(events): State<Events>, user: AuthUser) -> Result<Json<Vec<Event>>, StatusCode> {
+    events
+        .scheduled(user.id, user.is_admin())
+        .await
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+/// Publishes drafts whose `publish_at` has passed; the store queues an
+/// `event.published` webhook for each. Returns how many were published.
+pub async fn publish_due(events: &dyn EventRepository) -> Result<u64, sqlx::Error> {
+    let published = events.publish_due().await?;
+    Ok(published.len() as u64)
+}
@@ -0,0 +1,208 @@
+//! Emoji reactions on events and comments, from signed-in users who can
+//! see them. A user gives each emoji once per event or comment; giving it
+//! again takes it back.
+
+use std::collections::HashMap;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+use crate::{auth::AuthUser, db::{events::Events, Reader}, timelines, validation_error, AppState};
+
+/// The emoji people can react with, in the order pickers offer them.
+pub const EMOJI: [&str; 6] = ["👍", "❤️", "😂", "😮", "😢", "🎉"];
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/events/:id/reactions", get(event_reactions).post(react_to_event))
+        .route("/api/comments/:id/reactions", post(react_to_comment))
+}
+
+/// What a reaction is on.
+#[derive(Clone, Copy)]
+enum Target {
+    Event(Uuid),
+    Comment(Uuid),
+}
+
+impl Target {
+    /// The `reactions` column pointing at it.
+    fn column(self) -> &'static str {
+        match self {
+            Target::Event(_) => "event_id",
+            Target::Comment(_) => "comment_id",
+        }
+    }
+
+    fn id(self) -> Uuid {
+        match self {
+            Target::Event(id) | Target::Comment(id) => id,
+        }
+    }
+}
+
+/// How many gave one emoji, in the order it was first given.
+#[derive(Serialize, Clone, sqlx::FromRow)]
+pub struct Count {
+    emoji: String,
+    count: i64,
+    /// Whether the caller gave it.
+    mine: bool,
+}
+
+async fn counts(pool: &PgPool, target: Target, user: Option<Uuid>) -> Result<Vec<Count>, sqlx::Error> {
+    sqlx::query_as::<_, Count>(&format!(
+        "SELECT emoji, COUNT(*) AS count, COALESCE(BOOL_OR(user_id = $2), FALSE) AS mine \
+         FROM reactions WHERE {} = $1 GROUP BY emoji ORDER BY MIN(created_at)",
+        target.column()
+    ))
+    .bind(target.id())
+    .bind(user)
+    .fetch_all(pool)
+    .await
+}
+
+/// The counts on each of `comments` that has any, as `user` sees them.
+pub async fn for_comments(
+    pool: &PgPool,
+    comments: &[Uuid],
+    user: Option<Uuid>,
+) -> Result<HashMap<Uuid, Vec<Count>>, sqlx::Error> {
+    #[derive(sqlx::FromRow)]
+    struct Row {
+        comment_id: Uuid,
+        #[sqlx(flatten)]
+        count: Count,
+    }
+
+    let rows = sqlx::query_as::<_, Row>(
+        r#"
+        SELECT comment_id, emoji, COUNT(*) AS count, COALESCE(BOOL_OR(user_id = $2), FALSE) AS mine
+        FROM reactions WHERE comment_id = ANY($1)
+        GROUP BY comment_id, emoji ORDER BY MIN(created_at)
+        "#,
+    )
+    .bind(comments)
+    .bind(user)
+    .fetch_all(pool)
+    .await?;
+    let mut counts: HashMap<Uuid, Vec<Count>> = HashMap::new();
+    for row in rows {
+        counts.entry(row.comment_id).or_default().push(row.count);
+    }
+    Ok(counts)
+}
+
+fn valid_emoji(emoji: &str) -> Result<(), ValidationError> {
+    if EMOJI.contains(&emoji) {
+        Ok(())
+    } else {
+        Err(ValidationError::new("emoji"))
+    }
+}
+
+#[derive(Deserialize, Validate)]
+struct React {
+    #[validate(custom(function = "valid_emoji"))]
+    emoji: String,
+}
+
+/// Gives `emoji` on `target` as `user`, or takes it back, and returns the
+/// counts after.
+async fn toggle(pool: &PgPool, target: Target, user: Uuid, emoji: &str) -> Result<Vec<Count>, sqlx::Error> {
+    let removed = sqlx::query(&format!(
+        "DELETE FROM reactions WHERE {} = $1 AND user_id = $2 AND emoji = $3",
+        target.column()
+    ))
+    .bind(target.id())
+    .bind(user)
+    .bind(emoji)
+    .execute(pool)
+    .await?
+    .rows_affected();
+    if removed == 0 {
+        // A concurrent reaction from the same user makes this a no-op.
+        sqlx::query(&format!(
+            "INSERT INTO reactions ({}, user_id, emoji) VALUES ($1, $2, $3) ON CONFLICT DO NOTHING",
+            target.column()
+        ))
+        .bind(target.id())
+        .bind(user)
+        .bind(emoji)
+        .execute(pool)
+        .await?;
+    }
+    counts(pool, target, Some(user)).await
+}
+
+async fn event_reactions(
+    Reader(pool): Reader,
+    State(events): State<Events>,
+    user: Option<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<Count>>, Response> {
+    timelines::ensure_event_visible(&pool, &events, user.as_ref(), id).await?;
+    let counts = counts(&pool, Target::Event(id), user.map(|user| user.id))
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
+
+    Ok(Json(counts))
+}
+
+async fn react_to_event(
+    State(pool): State<PgPool>,
+    State(events): State<Events>,
+    user: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<React>,
+) -> Result<Json<Vec<Count>>, Response> {
+    payload.validate().map_err(validation_error)?;
+    timelines::ensure_event_visible(&pool, &events, Some(&user), id).await?;
+    let counts = toggle(&pool, Target::Event(id), user.id, &payload.emoji)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
+
+    Ok(Json(counts))
+}
+
+async fn react_to_comment(
+    State(pool): State<PgPool>,
+    State(events): State<Events>,
+    user: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<React>,
+) -> Result<Json<Vec<Count>>, Response> {
+    payload.validate().map_err(validation_error)?;
+    let internal = |_| StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    let event_id = sqlx::query_scalar::<_, Uuid>("SELECT event_id FROM comments WHERE id = $1")
+        .bind(id)
+        .fetch_optional(&pool)
+        .await
+        .map_err(internal)?
+        .ok_or_else(|| StatusCode::NOT_FOUND.into_response())?;
+    timelines::ensure_event_visible(&pool, &events, Some(&user), event_id).await?;
+    let counts = toggle(&pool, Target::Comment(id), user.id, &payload.emoji).await.map_err(internal)?;
+
+    Ok(Json(counts))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_offered_emoji_are_accepted() {
+        assert!(React { emoji: "🎉".to_string() }.validate().is_ok());
+        assert!(React { emoji: "🦀".to_string() }.validate().is_err());
+        assert!(React { emoji: "<b>".to_string() }.validate().is_err());
+    }
+}
@@ -0,0 +1,140 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    routing::{get, put},
+    Json, Router,
+};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{auth::AuthUser, db::Reader, timelines, AppState, Event};
+
+/// Favorites count for more than a plain read when scoring tag affinity.
+const READ_WEIGHT: f64 = 1.0;
+const FAVORITE_WEIGHT: f64 = 3.0;
+const PER_USER: i64 = 20;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/me/recommendations", get(get_recommendations))
+        .route("/api/events/:id/favorite", put(add_favorite).delete(remove_favorite))
+}
+
+/// Remembers that `user_id` opened `event_id`; feeds the affinity scores.
+pub async fn record_read(pool: &PgPool, user_id: Uuid, event_id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO event_reads (user_id, event_id) VALUES ($1, $2)
+        ON CONFLICT (user_id, event_id) DO UPDATE SET viewed_at = NOW()
+        "#,
+    )
+    .bind(user_id)
+    .bind(event_id)
+    .execute(pool)
+    .await
+    .map(|_| ())
+}
+
+/// Rebuilds every user's recommendations by tag affinity: each tag on an
+/// event a user read or favorited earns weight, and unseen events are
+/// scored by the summed weight of their tags. Returns rows written.
+pub async fn refresh(pool: &PgPool) -> Result<u64, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("DELETE FROM recommendations")
+        .execute(&mut *tx)
+        .await?;
+    let written = sqlx::query(
+        r#"
+        WITH interactions AS (
+            SELECT user_id, event_id, $1::float8 AS weight FROM event_reads
+            UNION ALL
+            SELECT user_id, event_id, $2::float8 FROM favorites
+        ), affinity AS (
+            SELECT i.user_id, et.tag_id, SUM(i.weight) AS weight
+            FROM interactions i
+            JOIN event_tags et ON et.event_id = i.event_id
+            GROUP BY i.user_id, et.tag_id
+        ), scored AS (
+            SELECT a.user_id, et.event_id, SUM(a.weight) AS score
+            FROM affinity a
+            JOIN event_tags et ON et.tag_id = a.tag_id
+            WHERE NOT EXISTS (
+                SELECT 1 FROM interactions i
+                WHERE i.user_id = a.user_id AND i.event_id = et.event_id
+            )
+            GROUP BY a.user_id, et.event_id
+        )
+        INSERT INTO recommendations (user_id, event_id, score)
+        SELECT user_id, event_id, score FROM (
+            SELECT *, row_number() OVER (PARTITION BY user_id ORDER BY score DESC) AS rank
+            FROM scored
+        ) ranked
+        WHERE rank <= $3
+        "#,
+    )
+    .bind(READ_WEIGHT)
+    .bind(FAVORITE_WEIGHT)
+    .bind(PER_USER)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+
+    tx.commit().await?;
+    Ok(written)
+}
+
+async fn get_recommendations(
+    Reader(pool): Reader,
+    user: AuthUser,
+) -> Result<Json<Vec<Event>>, StatusCode> {
+    let query = format!(
+        r#"
+        SELECT e.* FROM recommendations r
+        JOIN events e ON e.id = r.event_id
+        WHERE r.user_id = $1 AND {}
+        ORDER BY r.score DESC
+        "#,
+        timelines::PUBLIC_EVENT
+    );
+    let events = sqlx::query_as::<_, Event>(&query)
+    .bind(user.id)
+    .fetch_all(&pool)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(events))
+}
+
+async fn add_favorite(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    sqlx::query("INSERT INTO favorites (user_id, event_id) VALUES ($1, $2) ON CONFLICT DO NOTHING")
+        .bind(user.id)
+        .bind(id)
+        .execute(&pool)
+        .await
+        .map_err(|err| match err {
+            sqlx::Error::Database(db) if db.is_foreign_key_violation() => StatusCode::NOT_FOUND,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        })?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn remove_favorite(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    sqlx::query("DELETE FROM favorites WHERE user_id = $1 AND event_id = $2")
+        .bind(user.id)
+        .bind(id)
+        .execute(&pool)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
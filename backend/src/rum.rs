@@ -0,0 +1,132 @@
+use axum::{
+    body::Bytes,
+    extract::{DefaultBodyLimit, Query, State},
+    http::StatusCode,
+    routing::{get, post},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+
+use crate::{db::Reader, AppState};
+
+/// Metrics the frontend is allowed to report; anything else is dropped.
+const METRICS: &[&str] = &[
+    "ttfb",
+    "dom_content_loaded",
+    "wasm_init",
+    "timeline_first_render",
+    "interaction_latency",
+];
+
+const MAX_SAMPLES_PER_BEACON: usize = 20;
+/// Beacons are a handful of numbers; anything bigger is not from our client.
+const MAX_BEACON_BYTES: usize = 8 * 1024;
+/// Ten minutes; larger values are clock glitches or backgrounded tabs.
+const MAX_VALUE_MS: f64 = 600_000.0;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/rum", post(record_beacon).layer(DefaultBodyLimit::max(MAX_BEACON_BYTES)))
+        .route("/api/rum/trends", get(get_trends))
+}
+
+#[derive(Deserialize)]
+struct Beacon {
+    /// Route pattern with ids stripped by the client, e.g. `/events/:id`.
+    path: String,
+    samples: Vec<Sample>,
+}
+
+#[derive(Deserialize)]
+struct Sample {
+    metric: String,
+    value: f64,
+}
+
+#[derive(Deserialize)]
+struct TrendsQuery {
+    days: Option<i32>,
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+struct Trend {
+    day: chrono::NaiveDateTime,
+    metric: String,
+    p50: f64,
+    p75: f64,
+    p95: f64,
+    samples: i64,
+}
+
+/// Accepts a `navigator.sendBeacon` payload.
+///
+/// Beacons are sent as `text/plain` to avoid a CORS preflight, so the body is
+/// parsed by hand rather than through the `Json` extractor.
+async fn record_beacon(State(pool): State<PgPool>, body: Bytes) -> StatusCode {
+    let Ok(beacon) = serde_json::from_slice::<Beacon>(&body) else {
+        return StatusCode::BAD_REQUEST;
+    };
+    if beacon.path.len() > 200 {
+        return StatusCode::BAD_REQUEST;
+    }
+
+    let (metrics, values): (Vec<String>, Vec<f64>) = beacon
+        .samples
+        .into_iter()
+        .take(MAX_SAMPLES_PER_BEACON)
+        .filter(|sample| METRICS.contains(&sample.metric.as_str()))
+        .filter(|sample| sample.value.is_finite() && (0.0..=MAX_VALUE_MS).contains(&sample.value))
+        .map(|sample| (sample.metric, sample.value))
+        .unzip();
+
+    if metrics.is_empty() {
+        return StatusCode::NO_CONTENT;
+    }
+
+    let result = sqlx::query(
+        r#"
+        INSERT INTO rum_samples (metric, value, path)
+        SELECT metric, value, $3 FROM UNNEST($1::varchar[], $2::float8[]) AS s(metric, value)
+        "#,
+    )
+    .bind(&metrics)
+    .bind(&values)
+    .bind(&beacon.path)
+    .execute(&pool)
+    .await;
+
+    match result {
+        Ok(_) => StatusCode::NO_CONTENT,
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+/// Daily percentiles per metric for the performance panel.
+async fn get_trends(
+    Reader(pool): Reader,
+    Query(query): Query<TrendsQuery>,
+) -> Result<Json<Vec<Trend>>, StatusCode> {
+    let days = query.days.unwrap_or(30).clamp(1, 365);
+
+    let trends = sqlx::query_as::<_, Trend>(
+        r#"
+        SELECT date_trunc('day', recorded_at) AS day,
+               metric,
+               percentile_cont(0.5) WITHIN GROUP (ORDER BY value) AS p50,
+               percentile_cont(0.75) WITHIN GROUP (ORDER BY value) AS p75,
+               percentile_cont(0.95) WITHIN GROUP (ORDER BY value) AS p95,
+               COUNT(*) AS samples
+        FROM rum_samples
+        WHERE recorded_at >= NOW() - make_interval(days => $1)
+        GROUP BY 1, 2
+        ORDER BY 1, 2
+        "#,
+    )
+    .bind(days)
+    .fetch_all(&pool)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(trends))
+}
@@ -0,0 +1,193 @@
+//! Cleans user-supplied text before it is stored, and again before it is
+//! written into server-rendered HTML.
+//!
+//! Event, person and source text is plain text, not HTML, so markup is
+//! removed rather than escaped: the frontend and every export escape on
+//! output, and stored tags would only show up as literal angle brackets.
+
+use validator::ValidationError;
+
+/// Elements removed together with everything inside them.
+const DROPPED: [&str; 10] = [
+    "script", "style", "iframe", "object", "embed", "template", "noscript", "svg", "math", "textarea",
+];
+
+/// `input` without tags, comments and the contents of `DROPPED` elements,
+/// and without control characters other than line breaks and tabs. A `<`
+/// that does not start markup, as in "1 < 2", is kept.
+pub fn text(input: &str) -> String {
+    // Stripping can join the pieces around a removed tag into a new one,
+    // as in `<<script></script>script>`, so repeat until nothing changes.
+    let mut text = strip_markup(input);
+    loop {
+        let next = strip_markup(&text);
+        if next == text {
+            break;
+        }
+        text = next;
+    }
+    text.chars()
+        .filter(|c| !c.is_control() || matches!(c, '\n' | '\r' | '\t'))
+        .collect()
+}
+
+/// `text` for an optional field.
+pub fn optional(field: &mut Option<String>) {
+    if let Some(value) = field {
+        *value = text(value);
+    }
+}
+
+fn strip_markup(input: &str) -> String {
+    let mut output = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(start) = rest.find('<') {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        if !after.starts_with(|c: char| c.is_ascii_alphabetic() || matches!(c, '/' | '!' | '?')) {
+            output.push('<');
+            rest = after;
+            continue;
+        }
+        if let Some(comment) = after.strip_prefix("!--") {
+            match comment.find("-->") {
+                Some(end) => {
+                    rest = &comment[end + "-->".len()..];
+                    continue;
+                }
+                None => return output,
+            }
+        }
+        // An unterminated tag swallows the rest, as it would in a browser.
+        let Some(end) = after.find('>') else {
+            return output;
+        };
+        let tag = &after[..end];
+        rest = &after[end + 1..];
+
+        let name: String = tag
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric())
+            .collect::<String>()
+            .to_ascii_lowercase();
+        if DROPPED.contains(&name.as_str()) && !tag.ends_with('/') {
+            let closing = format!("</{}", name);
+            match rest.to_ascii_lowercase().find(&closing) {
+                Some(index) => {
+                    rest = &rest[index + closing.len()..];
+                    rest = rest.find('>').map_or("", |end| &rest[end + 1..]);
+                }
+                None => return output,
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+/// Accepts web links and paths on this site, which browsers cannot be
+/// talked into running: `javascript:`, `data:` and the like are refused,
+/// however they are spelled.
+pub fn safe_url(url: &str) -> Result<(), ValidationError> {
+    if url.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return Err(ValidationError::new("url"));
+    }
+    let lower = url.to_ascii_lowercase();
+    let web = ["https://", "http://"]
+        .iter()
+        .any(|scheme| lower.strip_prefix(scheme).is_some_and(|host| !host.is_empty()));
+    let local = url.starts_with('/') && !url.starts_with("//");
+    if web || local {
+        Ok(())
+    } else {
+        Err(ValidationError::new("url"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EventCreate, EventPatch};
+    use validator::Validate;
+
+    #[test]
+    fn strips_common_xss_payloads() {
+        let payloads = [
+            ("<script>alert(1)</script>Battle", "Battle"),
+            ("<SCRIPT SRC=//evil.example/x.js></SCRIPT>Battle", "Battle"),
+            ("<img src=x onerror=alert(1)>Battle", "Battle"),
+            ("<svg onload=alert(1)><circle/></svg>Battle", "Battle"),
+            ("<a href=\"javascript:alert(1)\">Battle</a>", "Battle"),
+            ("<iframe src=\"data:text/html,<script>alert(1)</script>\"></iframe>Battle", "Battle"),
+            ("<body onload=alert(1)>Battle", "Battle"),
+            ("<!-- <script>alert(1)</script> -->Battle", "Battle"),
+            ("<style>body { background: url(javascript:alert(1)) }</style>Battle", "Battle"),
+            // The first pass leaves `<script>alert(1)Battle`, an unclosed
+            // script element, which the second drops entirely.
+            ("<<script>alert(1)</script>script>alert(1)</script>Battle", ""),
+            ("Battle<script>alert(1)", "Battle"),
+        ];
+        for (payload, expected) in payloads {
+            assert_eq!(text(payload), expected, "{}", payload);
+        }
+    }
+
+    #[test]
+    fn keeps_plain_text() {
+        assert_eq!(text("1 < 2 & 3 > 2"), "1 < 2 & 3 > 2");
+        assert_eq!(text("Treaty of Paris\n(1783)"), "Treaty of Paris\n(1783)");
+        assert_eq!(text("Fall of <b>Rome</b>"), "Fall of Rome");
+        assert_eq!(text("Null\u{0}byte"), "Nullbyte");
+    }
+
+    #[test]
+    fn refuses_dangerous_urls() {
+        for url in [
+            "javascript:alert(1)",
+            "JaVaScRiPt:alert(1)",
+            " javascript:alert(1)",
+            "java\tscript:alert(1)",
+            "data:image/svg+xml,<svg onload=alert(1)>",
+            "vbscript:msgbox(1)",
+            "//evil.example/x.png",
+            "https://",
+        ] {
+            assert!(safe_url(url).is_err(), "{}", url);
+        }
+        for url in ["https://example.org/a.png", "http://example.org/a.png", "/uploads/a.png"] {
+            assert!(safe_url(url).is_ok(), "{}", url);
+        }
+    }
+
+    #[test]
+    fn events_are_cleaned_before_validation() {
+        let mut event: EventCreate = serde_json::from_value(serde_json::json!({
+            "title": "<script>alert(1)</script>Moon landing",
+            "description": "<img src=x onerror=alert(1)>One small step",
+            "start_date": "1969-07-20T20:17:00",
+        }))
+        .unwrap();
+        event.sanitize();
+        assert_eq!(event.title, "Moon landing");
+        assert_eq!(event.description.as_deref(), Some("One small step"));
+        assert!(event.validate().is_ok());
+
+        // A title that was nothing but markup is left empty and rejected.
+        let mut event: EventCreate = serde_json::from_value(serde_json::json!({
+            "title": "<script>alert(1)</script>",
+            "start_date": "1969-07-20T20:17:00",
+        }))
+        .unwrap();
+        event.sanitize();
+        assert!(event.validate().is_err());
+    }
+
+    #[test]
+    fn patches_reject_script_image_urls() {
+        let patch: EventPatch =
+            serde_json::from_value(serde_json::json!({ "image_url": "javascript:alert(1)" })).unwrap();
+        assert!(patch.validate().is_err());
+        let patch: EventPatch = serde_json::from_value(serde_json::json!({ "image_url": null })).unwrap();
+        assert!(patch.validate().is_ok());
+    }
+}
@@ -0,0 +1,355 @@
+use std::collections::HashMap;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, put},
+    Json, Router,
+};
+use chrono::NaiveDateTime;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, QueryBuilder};
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+use crate::{
+    auth::AuthUser,
+    config::Config,
+    db::{
+        events::{push_filter, EventFilter},
+        Reader,
+    },
+    email_templates, outbox, preferences, sanitize, validation_error, AppState, Event,
+};
+
+const MAX_SAVED_SEARCHES: i64 = 50;
+/// Most new events listed in one alert; the link shows the rest.
+const MAX_ALERT_EVENTS: i64 = 20;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/saved-searches", get(list_saved_searches).post(create_saved_search))
+        .route("/api/saved-searches/:id", put(update_saved_search).delete(delete_saved_search))
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+struct SavedSearch {
+    id: Uuid,
+    #[serde(skip)]
+    user_id: Uuid,
+    name: String,
+    search: Option<String>,
+    categories: Vec<String>,
+    start_date: Option<NaiveDateTime>,
+    end_date: Option<NaiveDateTime>,
+    /// Email the owner when new events match.
+    notify: bool,
+    last_notified_at: NaiveDateTime,
+    created_at: NaiveDateTime,
+}
+
+impl SavedSearch {
+    /// The published, public events the search matches.
+    fn filter(&self) -> EventFilter {
+        EventFilter {
+            search: self.search.clone(),
+            start_date: self.start_date,
+            end_date: self.end_date,
+            categories: self.categories.clone(),
+            status: "published".to_string(),
+            ..EventFilter::default()
+        }
+    }
+
+    /// The Events page showing this search's results.
+    fn url(&self, public_url: &str) -> String {
+        let base = format!("{}/events", public_url.trim_end_matches('/'));
+        let Ok(mut url) = Url::parse(&base) else {
+            return base;
+        };
+        if let Some(search) = &self.search {
+            url.query_pairs_mut().append_pair("search", search);
+        }
+        if !self.categories.is_empty() {
+            url.query_pairs_mut().append_pair("categories", &self.categories.join(","));
+        }
+        url.to_string()
+    }
+}
+
+/// Body of `POST /api/saved-searches` and `PUT /api/saved-searches/:id`.
+#[derive(Deserialize, Validate)]
+#[validate(schema(function = "date_range"))]
+struct SavedSearchInput {
+    #[validate(length(min = 1, max = 100))]
+    name: String,
+    #[validate(length(max = 255))]
+    search: Option<String>,
+    #[serde(default)]
+    #[validate(length(max = 20))]
+    categories: Vec<String>,
+    start_date: Option<NaiveDateTime>,
+    end_date: Option<NaiveDateTime>,
+    #[serde(default)]
+    notify: bool,
+}
+
+impl SavedSearchInput {
+    fn sanitize(&mut self) {
+        self.name = sanitize::text(&self.name).trim().to_string();
+        sanitize::optional(&mut self.search);
+        if self.search.as_deref().is_some_and(|search| search.trim().is_empty()) {
+            self.search = None;
+        }
+        self.categories = self
+            .categories
+            .iter()
+            .map(|name| sanitize::text(name).trim().to_string())
+            .filter(|name| !name.is_empty() && name.chars().count() <= 100)
+            .collect();
+    }
+}
+
+fn date_range(input: &SavedSearchInput) -> Result<(), ValidationError> {
+    match (input.start_date, input.end_date) {
+        (Some(start), Some(end)) if end < start => Err(ValidationError::new("end_before_start")),
+        _ => Ok(()),
+    }
+}
+
+/// A unique violation means the user already has a search by that name.
+fn save_error(error: sqlx::Error) -> Response {
+    match error {
+        sqlx::Error::Database(error) if error.is_unique_violation() => {
+            (StatusCode::CONFLICT, "you already have a saved search with that name").into_response()
+        }
+        _ => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+/// The caller's saved searches by name.
+async fn list_saved_searches(Reader(pool): Reader, user: AuthUser) -> Result<Json<Vec<SavedSearch>>, StatusCode> {
+    sqlx::query_as::<_, SavedSearch>("SELECT * FROM saved_searches WHERE user_id = $1 ORDER BY lower(name)")
+        .bind(user.id)
+        .fetch_all(&pool)
+        .await
+        .map(Json)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
+
+async fn create_saved_search(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Json(mut payload): Json<SavedSearchInput>,
+) -> Result<Json<SavedSearch>, Response> {
+    payload.sanitize();
+    payload.validate().map_err(validation_error)?;
+    let count = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM saved_searches WHERE user_id = $1")
+        .bind(user.id)
+        .fetch_one(&pool)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
+    if count >= MAX_SAVED_SEARCHES {
+        let message = format!("at most {} saved searches", MAX_SAVED_SEARCHES);
+        return Err((StatusCode::UNPROCESSABLE_ENTITY, message).into_response());
+    }
+
+    sqlx::query_as::<_, SavedSearch>(
+        r#"
+        INSERT INTO saved_searches (user_id, name, search, categories, start_date, end_date, notify)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING *
+        "#,
+    )
+    .bind(user.id)
+    .bind(payload.name)
+    .bind(payload.search)
+    .bind(payload.categories)
+    .bind(payload.start_date)
+    .bind(payload.end_date)
+    .bind(payload.notify)
+    .fetch_one(&pool)
+    .await
+    .map(Json)
+    .map_err(save_error)
+}
+
+/// Replaces a saved search. Turning alerts on starts them from now, so
+/// older matches are not sent all at once.
+async fn update_saved_search(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(mut payload): Json<SavedSearchInput>,
+) -> Result<Json<SavedSearch>, Response> {
+    payload.sanitize();
+    payload.validate().map_err(validation_error)?;
+
+    sqlx::query_as::<_, SavedSearch>(
+        r#"
+        UPDATE saved_searches SET
+            name = $3, search = $4, categories = $5, start_date = $6, end_date = $7,
+            last_notified_at = CASE WHEN $8 AND NOT notify THEN NOW() ELSE last_notified_at END,
+            notify = $8
+        WHERE id = $1 AND user_id = $2
+        RETURNING *
+        "#,
+    )
+    .bind(id)
+    .bind(user.id)
+    .bind(payload.name)
+    .bind(payload.search)
+    .bind(payload.categories)
+    .bind(payload.start_date)
+    .bind(payload.end_date)
+    .bind(payload.notify)
+    .fetch_optional(&pool)
+    .await
+    .map_err(save_error)?
+    .map(Json)
+    .ok_or_else(|| StatusCode::NOT_FOUND.into_response())
+}
+
+async fn delete_saved_search(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    let deleted = sqlx::query("DELETE FROM saved_searches WHERE id = $1 AND user_id = $2")
+        .bind(id)
+        .bind(user.id)
+        .execute(&pool)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .rows_affected();
+
+    match deleted {
+        0 => Err(StatusCode::NOT_FOUND),
+        _ => Ok(StatusCode::NO_CONTENT),
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct Recipient {
+    username: String,
+    email: String,
+}
+
+/// One line per event, as in the digest: `- 1969-07-20 Apollo 11 lands`.
+fn event_lines(events: &[Event]) -> String {
+    events
+        .iter()
+        .map(|event| format!("- {} {}", event.start_date.format("%Y-%m-%d"), event.title))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Emails the owner of each alerting saved search the events created since
+/// its last alert, using the `saved_search` template in their locale. The
+/// email is queued in the outbox along with the new `last_notified_at`, so
+/// no alert is lost or queued twice. Returns how many were queued.
+pub async fn send_alerts(pool: &PgPool, config: &Config) -> Result<u64, sqlx::Error> {
+    let searches = sqlx::query_as::<_, SavedSearch>("SELECT * FROM saved_searches WHERE notify")
+        .fetch_all(pool)
+        .await?;
+    let mut sent = 0;
+    for search in searches {
+        let checked_at = chrono::Utc::now().naive_utc();
+        let mut query = QueryBuilder::new("SELECT e.* FROM events e");
+        push_filter(&mut query, &search.filter());
+        query
+            .push(" AND e.created_at > ")
+            .push_bind(search.last_notified_at)
+            .push(" ORDER BY e.created_at LIMIT ")
+            .push_bind(MAX_ALERT_EVENTS);
+        let events = query.build_query_as::<Event>().fetch_all(pool).await?;
+        if events.is_empty() {
+            continue;
+        }
+
+        let Some(recipient) = sqlx::query_as::<_, Recipient>("SELECT username, email FROM users WHERE id = $1")
+            .bind(search.user_id)
+            .fetch_optional(pool)
+            .await?
+        else {
+            continue;
+        };
+        let locale = preferences::find(pool, search.user_id).await?.locale;
+        let Some(template) = email_templates::find(pool, "saved_search", &locale).await? else {
+            continue;
+        };
+        let values = HashMap::from([
+            ("username".to_string(), recipient.username),
+            ("search_name".to_string(), search.name.clone()),
+            ("events".to_string(), event_lines(&events)),
+            ("search_url".to_string(), search.url(&config.public_url)),
+        ]);
+        let (Ok(subject), Ok(body)) = (
+            email_templates::render(&template.subject, &values),
+            email_templates::render(&template.body, &values),
+        ) else {
+            tracing::warn!(search_id = %search.id, "saved search alert template does not render");
+            continue;
+        };
+
+        let mut tx = pool.begin().await?;
+        outbox::queue_mail(&mut tx, &recipient.email, &subject, &body).await?;
+        sqlx::query("UPDATE saved_searches SET last_notified_at = $2 WHERE id = $1")
+            .bind(search.id)
+            .bind(checked_at)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+        sent += 1;
+    }
+
+    Ok(sent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn saved(search: Option<&str>, categories: &[&str]) -> SavedSearch {
+        let now = chrono::Utc::now().naive_utc();
+        SavedSearch {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            name: "Wars".to_string(),
+            search: search.map(str::to_string),
+            categories: categories.iter().map(|name| name.to_string()).collect(),
+            start_date: None,
+            end_date: None,
+            notify: true,
+            last_notified_at: now,
+            created_at: now,
+        }
+    }
+
+    #[test]
+    fn links_to_the_events_page_with_the_filters() {
+        assert_eq!(saved(None, &[]).url("https://example.org"), "https://example.org/events");
+        assert_eq!(
+            saved(Some("world war"), &["War", "Politics"]).url("https://example.org"),
+            "https://example.org/events?search=world+war&categories=War%2CPolitics"
+        );
+    }
+
+    #[test]
+    fn rejects_ranges_that_end_before_they_start() {
+        let date = |year| chrono::NaiveDate::from_ymd_opt(year, 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap();
+        let mut input = SavedSearchInput {
+            name: "Modern".to_string(),
+            search: None,
+            categories: Vec::new(),
+            start_date: Some(date(1950)),
+            end_date: Some(date(1900)),
+            notify: false,
+        };
+        assert!(input.validate().is_err());
+        input.end_date = Some(date(2000));
+        assert!(input.validate().is_ok());
+    }
+}
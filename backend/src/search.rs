@@ -0,0 +1,576 @@
+//! Search: highlights for the event listing's `search` filter, and
+//! `GET /api/search`, which looks through events, timelines, people and
+//! tags at once.
+
+use axum::{extract::Query, http::StatusCode, routing::get, Json, Router};
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{auth::AuthUser, db::Reader, timelines, AppState, Event};
+
+/// Longest query accepted, in characters.
+const MAX_QUERY_CHARS: usize = 200;
+/// Results per group when all groups are shown.
+const PREVIEW_LIMIT: i64 = 5;
+/// Results per page when one group is shown.
+const PAGE_LIMIT: i64 = 20;
+
+pub fn routes() -> Router<AppState> {
+    Router::new().route("/api/search", get(search))
+}
+
+/// Where a search term matched, as `[start, end)` character offsets (not
+/// bytes) into each field.
+#[derive(Serialize, Default)]
+pub struct Highlights {
+    pub title: Vec<(usize, usize)>,
+    pub description: Vec<(usize, usize)>,
+}
+
+/// An event in search results.
+#[derive(Serialize)]
+pub struct SearchHit {
+    #[serde(flatten)]
+    pub event: Event,
+    /// Present only when the listing was filtered by a search term.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub highlights: Option<Highlights>,
+}
+
+impl SearchHit {
+    pub fn new(event: Event, term: Option<&str>) -> Self {
+        let highlights = term.filter(|term| !term.is_empty()).map(|term| Highlights {
+            title: matches(&event.title, term),
+            description: event.description.as_deref().map(|text| matches(text, term)).unwrap_or_default(),
+        });
+        Self { event, highlights }
+    }
+}
+
+fn same_letter(a: char, b: char) -> bool {
+    a == b || a.to_lowercase().eq(b.to_lowercase())
+}
+
+/// Non-overlapping, case-insensitive occurrences of `term` in `text`,
+/// mirroring the `ILIKE '%term%'` filter that selected the event.
+fn matches(text: &str, term: &str) -> Vec<(usize, usize)> {
+    let text: Vec<char> = text.chars().collect();
+    let term: Vec<char> = term.chars().collect();
+    let mut found = Vec::new();
+    let mut start = 0;
+    while !term.is_empty() && start + term.len() <= text.len() {
+        if text[start..start + term.len()]
+            .iter()
+            .zip(&term)
+            .all(|(&a, &b)| same_letter(a, b))
+        {
+            found.push((start, start + term.len()));
+            start += term.len();
+        } else {
+            start += 1;
+        }
+    }
+    found
+}
+
+/// The full-text query for `text`: every word must appear, each possibly
+/// as the start of a longer one, so results show up while typing.
+/// Punctuation is dropped; `None` when no words are left.
+fn prefix_query(text: &str) -> Option<String> {
+    let words: Vec<String> = words(text).into_iter().map(|word| format!("{}:*", word)).collect();
+    (!words.is_empty()).then(|| words.join(" & "))
+}
+
+/// Lowercase words of `text`, without punctuation.
+fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// The kinds of result, as `type` names them.
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum Kind {
+    Events,
+    Timelines,
+    People,
+    Tags,
+}
+
+#[derive(Deserialize)]
+struct SearchQuery {
+    q: String,
+    /// Only this group gets results, a page at a time; the others still
+    /// report their totals.
+    #[serde(rename = "type")]
+    kind: Option<Kind>,
+    page: Option<i64>,
+}
+
+/// Matches of one kind, best first.
+#[derive(Serialize)]
+struct Group<T> {
+    total: i64,
+    items: Vec<T>,
+}
+
+impl<T> Default for Group<T> {
+    fn default() -> Self {
+        Group { total: 0, items: Vec::new() }
+    }
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+struct EventResult {
+    id: Uuid,
+    title: String,
+    /// The start of the description.
+    snippet: Option<String>,
+    start_date: NaiveDateTime,
+    timeline_id: Option<Uuid>,
+    timeline_title: Option<String>,
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+struct TimelineResult {
+    id: Uuid,
+    title: String,
+    description: Option<String>,
+    owner: String,
+    event_count: i64,
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+struct PersonResult {
+    id: Uuid,
+    name: String,
+    birth_date: Option<NaiveDate>,
+    death_date: Option<NaiveDate>,
+    image_url: Option<String>,
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+struct TagResult {
+    id: i64,
+    name: String,
+    /// Published events with the tag.
+    event_count: i64,
+}
+
+#[derive(Serialize)]
+struct SearchResults {
+    query: String,
+    /// Nothing contained the query's words, so these results only
+    /// resemble it.
+    fuzzy: bool,
+    /// A corrected query, offered along with fuzzy results.
+    suggestion: Option<String>,
+    events: Group<EventResult>,
+    timelines: Group<TimelineResult>,
+    people: Group<PersonResult>,
+    tags: Group<TagResult>,
+}
+
+/// Whether results must contain the query's words, or may only resemble
+/// them. Fuzzy matching is the fallback when nothing contains them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Matching {
+    Text,
+    Fuzzy,
+}
+
+/// How close a title must come to the query to match fuzzily, as pg_trgm's
+/// `word_similarity`.
+const FUZZY_SIMILARITY: f64 = 0.5;
+
+/// SQL condition: `document` matches the full-text query bound as `query`
+/// or `title` contains the `ILIKE` pattern bound as `pattern`. Fuzzily,
+/// `pattern` holds the bare text and `title` need only resemble it.
+fn text_matches(matching: Matching, document: &str, title: &str, query: &str, pattern: &str) -> String {
+    match matching {
+        Matching::Text => format!(
+            "(to_tsvector('simple', {}) @@ to_tsquery('simple', {}) OR {} ILIKE {})",
+            document, query, title, pattern
+        ),
+        Matching::Fuzzy => format!("word_similarity({}, {}) >= {}", pattern, title, FUZZY_SIMILARITY),
+    }
+}
+
+/// SQL expression ranking a row matched by `text_matches`, higher first.
+fn text_rank(matching: Matching, document: &str, title: &str, query: &str, pattern: &str) -> String {
+    match matching {
+        Matching::Text => format!("ts_rank(to_tsvector('simple', {}), to_tsquery('simple', {}))", document, query),
+        Matching::Fuzzy => format!("word_similarity({}, {})", pattern, title),
+    }
+}
+
+const EVENT_DOCUMENT: &str = "e.title || ' ' || COALESCE(e.description, '')";
+const TIMELINE_DOCUMENT: &str = "t.title || ' ' || COALESCE(t.description, '')";
+const PERSON_DOCUMENT: &str = "p.name || ' ' || COALESCE(p.bio, '')";
+
+/// Published events the user bound as `$1` (`$2` whether an admin) may
+/// see whose text matches the query `$3`, or whose title or one of whose
+/// tags contains `$4`.
+fn event_matches(matching: Matching) -> String {
+    format!(
+        "FROM events e LEFT JOIN timelines t ON t.id = e.timeline_id \
+         WHERE e.status = 'published' AND (e.timeline_id IS NULL OR {}) \
+         AND ({} OR EXISTS (SELECT 1 FROM event_tags et JOIN tags g ON g.id = et.tag_id \
+             WHERE et.event_id = e.id AND {}))",
+        timelines::VISIBLE_TIMELINE,
+        text_matches(matching, EVENT_DOCUMENT, "e.title", "$3", "$4"),
+        text_matches(matching, "g.name", "g.name", "$3", "$4"),
+    )
+}
+
+/// Timelines `$1`/`$2` may see matching `$3` or with `$4` in the title.
+fn timeline_matches(matching: Matching) -> String {
+    format!(
+        "FROM timelines t WHERE {} AND {}",
+        timelines::VISIBLE_TIMELINE,
+        text_matches(matching, TIMELINE_DOCUMENT, "t.title", "$3", "$4")
+    )
+}
+
+/// People matching `$1` or with `$2` in their name; everyone may see them.
+fn person_matches(matching: Matching) -> String {
+    format!("FROM people p WHERE {}", text_matches(matching, PERSON_DOCUMENT, "p.name", "$1", "$2"))
+}
+
+fn tag_matches(matching: Matching) -> String {
+    format!("FROM tags g WHERE {}", text_matches(matching, "g.name", "g.name", "$1", "$2"))
+}
+
+/// Ranked by how well the text matches, with exact titles first and more
+/// important events ahead of minor ones.
+async fn find_events(
+    pool: &PgPool,
+    user: Option<&AuthUser>,
+    terms: &Terms,
+    limit: i64,
+    offset: i64,
+) -> Result<Group<EventResult>, sqlx::Error> {
+    let matches = event_matches(terms.matching);
+    let total = sqlx::query_scalar::<_, i64>(&format!("SELECT COUNT(*) {}", matches))
+        .bind(user.map(|user| user.id))
+        .bind(user.is_some_and(AuthUser::is_admin))
+        .bind(&terms.query)
+        .bind(&terms.pattern)
+        .fetch_one(pool)
+        .await?;
+    let items = sqlx::query_as::<_, EventResult>(&format!(
+        r#"
+        SELECT e.id, e.title, LEFT(e.description, 200) AS snippet, e.start_date, e.timeline_id,
+            t.title AS timeline_title
+        {}
+        ORDER BY (lower(e.title) = lower($6)) DESC,
+            {} + e.importance * 0.01 DESC,
+            e.start_date
+        LIMIT $5 OFFSET $7
+        "#,
+        matches,
+        text_rank(terms.matching, EVENT_DOCUMENT, "e.title", "$3", "$4")
+    ))
+    .bind(user.map(|user| user.id))
+    .bind(user.is_some_and(AuthUser::is_admin))
+    .bind(&terms.query)
+    .bind(&terms.pattern)
+    .bind(limit)
+    .bind(&terms.text)
+    .bind(offset)
+    .fetch_all(pool)
+    .await?;
+    Ok(Group { total, items })
+}
+
+async fn find_timelines(
+    pool: &PgPool,
+    user: Option<&AuthUser>,
+    terms: &Terms,
+    limit: i64,
+    offset: i64,
+) -> Result<Group<TimelineResult>, sqlx::Error> {
+    let matches = timeline_matches(terms.matching);
+    let total = sqlx::query_scalar::<_, i64>(&format!("SELECT COUNT(*) {}", matches))
+        .bind(user.map(|user| user.id))
+        .bind(user.is_some_and(AuthUser::is_admin))
+        .bind(&terms.query)
+        .bind(&terms.pattern)
+        .fetch_one(pool)
+        .await?;
+    let items = sqlx::query_as::<_, TimelineResult>(&format!(
+        r#"
+        SELECT t.id, t.title, t.description,
+            (SELECT username FROM users WHERE id = t.owner_id) AS owner,
+            (SELECT COUNT(*) FROM events e WHERE e.timeline_id = t.id AND e.status = 'published') AS event_count
+        {}
+        ORDER BY (lower(t.title) = lower($6)) DESC, {} DESC, t.updated_at DESC
+        LIMIT $5 OFFSET $7
+        "#,
+        matches,
+        text_rank(terms.matching, TIMELINE_DOCUMENT, "t.title", "$3", "$4")
+    ))
+    .bind(user.map(|user| user.id))
+    .bind(user.is_some_and(AuthUser::is_admin))
+    .bind(&terms.query)
+    .bind(&terms.pattern)
+    .bind(limit)
+    .bind(&terms.text)
+    .bind(offset)
+    .fetch_all(pool)
+    .await?;
+    Ok(Group { total, items })
+}
+
+async fn find_people(pool: &PgPool, terms: &Terms, limit: i64, offset: i64) -> Result<Group<PersonResult>, sqlx::Error> {
+    let matches = person_matches(terms.matching);
+    let total = sqlx::query_scalar::<_, i64>(&format!("SELECT COUNT(*) {}", matches))
+        .bind(&terms.query)
+        .bind(&terms.pattern)
+        .fetch_one(pool)
+        .await?;
+    let items = sqlx::query_as::<_, PersonResult>(&format!(
+        r#"
+        SELECT p.id, p.name, p.birth_date, p.death_date, p.image_url
+        {}
+        ORDER BY (lower(p.name) = lower($3)) DESC, {} DESC, lower(p.name)
+        LIMIT $4 OFFSET $5
+        "#,
+        matches,
+        text_rank(terms.matching, PERSON_DOCUMENT, "p.name", "$1", "$2")
+    ))
+    .bind(&terms.query)
+    .bind(&terms.pattern)
+    .bind(&terms.text)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(pool)
+    .await?;
+    Ok(Group { total, items })
+}
+
+/// Exact names first, then the most used.
+async fn find_tags(pool: &PgPool, terms: &Terms, limit: i64, offset: i64) -> Result<Group<TagResult>, sqlx::Error> {
+    let matches = tag_matches(terms.matching);
+    let total = sqlx::query_scalar::<_, i64>(&format!("SELECT COUNT(*) {}", matches))
+        .bind(&terms.query)
+        .bind(&terms.pattern)
+        .fetch_one(pool)
+        .await?;
+    let items = sqlx::query_as::<_, TagResult>(&format!(
+        r#"
+        SELECT g.id, g.name, (
+            SELECT COUNT(*) FROM event_tags et JOIN events e ON e.id = et.event_id
+            WHERE et.tag_id = g.id AND {}
+        ) AS event_count
+        {}
+        ORDER BY (lower(g.name) = lower($3)) DESC, event_count DESC, g.name
+        LIMIT $4 OFFSET $5
+        "#,
+        timelines::PUBLIC_EVENT,
+        matches
+    ))
+    .bind(&terms.query)
+    .bind(&terms.pattern)
+    .bind(&terms.text)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(pool)
+    .await?;
+    Ok(Group { total, items })
+}
+
+/// What the finders bind for one way of matching.
+struct Terms {
+    matching: Matching,
+    /// The text as typed, trimmed.
+    text: String,
+    /// The full-text query.
+    query: String,
+    /// An `ILIKE` pattern, or the bare text when matching fuzzily.
+    pattern: String,
+}
+
+impl Terms {
+    fn new(matching: Matching, text: &str, query: String) -> Self {
+        let pattern = match matching {
+            Matching::Text => format!("%{}%", text),
+            Matching::Fuzzy => text.to_string(),
+        };
+        Self { matching, text: text.to_string(), query, pattern }
+    }
+}
+
+/// Every group for `terms`; groups other than `kind`, when given, only
+/// need their totals.
+async fn find_all(
+    pool: &PgPool,
+    user: Option<&AuthUser>,
+    terms: &Terms,
+    kind: Option<Kind>,
+    page: i64,
+) -> Result<(Group<EventResult>, Group<TimelineResult>, Group<PersonResult>, Group<TagResult>), sqlx::Error> {
+    let window = |group: Kind| match kind {
+        None => (PREVIEW_LIMIT, 0),
+        Some(wanted) if wanted == group => (PAGE_LIMIT, (page - 1) * PAGE_LIMIT),
+        Some(_) => (0, 0),
+    };
+    let (limit, offset) = window(Kind::Events);
+    let events = find_events(pool, user, terms, limit, offset).await?;
+    let (limit, offset) = window(Kind::Timelines);
+    let timelines = find_timelines(pool, user, terms, limit, offset).await?;
+    let (limit, offset) = window(Kind::People);
+    let people = find_people(pool, terms, limit, offset).await?;
+    let (limit, offset) = window(Kind::Tags);
+    let tags = find_tags(pool, terms, limit, offset).await?;
+    Ok((events, timelines, people, tags))
+}
+
+/// "Did you mean…?": the query with each word replaced by its closest
+/// known word, if any was replaced. `closest` pairs every word with the
+/// best candidate found, which is the word itself when it is known.
+fn suggestion(closest: &[(String, Option<String>)]) -> Option<String> {
+    let corrected: Vec<&str> = closest
+        .iter()
+        .map(|(word, best)| best.as_deref().unwrap_or(word))
+        .collect();
+    let changed = closest.iter().any(|(word, best)| best.as_deref().is_some_and(|best| best != word));
+    changed.then(|| corrected.join(" "))
+}
+
+/// The closest word in `search_words` to each of `words`, in order, by
+/// pg_trgm similarity.
+async fn closest_words(pool: &PgPool, words: &[String]) -> Result<Vec<(String, Option<String>)>, sqlx::Error> {
+    sqlx::query_as::<_, (String, Option<String>)>(
+        r#"
+        SELECT w.word, (
+            SELECT s.word FROM search_words s
+            WHERE s.word % w.word
+            ORDER BY s.word = w.word DESC, similarity(s.word, w.word) DESC, s.word
+            LIMIT 1
+        )
+        FROM unnest($1::TEXT[]) WITH ORDINALITY AS w(word, n)
+        ORDER BY w.n
+        "#,
+    )
+    .bind(words)
+    .fetch_all(pool)
+    .await
+}
+
+/// Rebuilds `search_words`, the vocabulary suggestions are drawn from: the
+/// words of public event and timeline titles, people's names and the tags
+/// of public events. Nothing private goes in, since anyone may be offered
+/// a suggestion.
+pub async fn refresh_words(pool: &PgPool) -> Result<u64, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM search_words").execute(&mut *tx).await?;
+    let written = sqlx::query(&format!(
+        r#"
+        INSERT INTO search_words (word)
+        SELECT DISTINCT word FROM (
+            SELECT regexp_split_to_table(lower(e.title), '[^[:alnum:]]+') AS word FROM events e WHERE {0}
+            UNION ALL
+            SELECT regexp_split_to_table(lower(title), '[^[:alnum:]]+') FROM timelines WHERE NOT is_private
+            UNION ALL
+            SELECT regexp_split_to_table(lower(name), '[^[:alnum:]]+') FROM people
+            UNION ALL
+            SELECT regexp_split_to_table(lower(g.name), '[^[:alnum:]]+')
+            FROM tags g JOIN event_tags et ON et.tag_id = g.id JOIN events e ON e.id = et.event_id
+            WHERE {0}
+        ) words
+        WHERE length(word) >= 3
+        "#,
+        timelines::PUBLIC_EVENT
+    ))
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+    tx.commit().await?;
+    Ok(written)
+}
+
+/// Events, timelines, people and tags matching `q`, grouped by kind. Only
+/// published events show up, even for their editors. When nothing contains
+/// the query's words, results that merely resemble it are returned instead,
+/// marked `fuzzy`, along with a corrected query to suggest.
+async fn search(
+    Reader(pool): Reader,
+    user: Option<AuthUser>,
+    Query(params): Query<SearchQuery>,
+) -> Result<Json<SearchResults>, StatusCode> {
+    let text = params.q.trim();
+    if text.chars().count() > MAX_QUERY_CHARS {
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+    let Some(query) = prefix_query(text) else {
+        return Ok(Json(SearchResults {
+            query: text.to_string(),
+            fuzzy: false,
+            suggestion: None,
+            events: Group::default(),
+            timelines: Group::default(),
+            people: Group::default(),
+            tags: Group::default(),
+        }));
+    };
+
+    let page = params.page.unwrap_or(1).max(1);
+    let internal = |_| StatusCode::INTERNAL_SERVER_ERROR;
+    let terms = Terms::new(Matching::Text, text, query.clone());
+    let mut found = find_all(&pool, user.as_ref(), &terms, params.kind, page).await.map_err(internal)?;
+    let mut fuzzy = false;
+    let mut suggested = None;
+    if found.0.total + found.1.total + found.2.total + found.3.total == 0 {
+        let terms = Terms::new(Matching::Fuzzy, text, query);
+        found = find_all(&pool, user.as_ref(), &terms, params.kind, page).await.map_err(internal)?;
+        fuzzy = true;
+        suggested = suggestion(&closest_words(&pool, &words(text)).await.map_err(internal)?);
+    }
+
+    let (events, timelines, people, tags) = found;
+    Ok(Json(SearchResults {
+        query: text.to_string(),
+        fuzzy,
+        suggestion: suggested,
+        events,
+        timelines,
+        people,
+        tags,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn queries_match_word_prefixes() {
+        assert_eq!(prefix_query("Battle of Hast").as_deref(), Some("battle:* & of:* & hast:*"));
+        assert_eq!(prefix_query("  Jeanne d'Arc!").as_deref(), Some("jeanne:* & d:* & arc:*"));
+        assert_eq!(prefix_query("Zürich").as_deref(), Some("zürich:*"));
+    }
+
+    #[test]
+    fn punctuation_alone_is_no_query() {
+        assert_eq!(prefix_query(""), None);
+        assert_eq!(prefix_query("&|!():*"), None);
+    }
+
+    #[test]
+    fn suggestions_replace_unknown_words() {
+        let closest = |pairs: &[(&str, Option<&str>)]| -> Vec<(String, Option<String>)> {
+            pairs.iter().map(|(word, best)| (word.to_string(), best.map(str::to_string))).collect()
+        };
+        assert_eq!(
+            suggestion(&closest(&[("napolean", Some("napoleon")), ("bonaparte", Some("bonaparte"))])).as_deref(),
+            Some("napoleon bonaparte")
+        );
+        assert_eq!(suggestion(&closest(&[("qwzx", None), ("battle", Some("battle"))])), None);
+        assert_eq!(suggestion(&closest(&[])), None);
+    }
+}
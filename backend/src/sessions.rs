@@ -0,0 +1,293 @@
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use axum::{
+    extract::{Path, State},
+    http::{header, HeaderMap, StatusCode},
+    routing::{delete, get, post},
+    Json, Router,
+};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    auth::{self, AuthUser, TokenResponse},
+    db::Reader,
+    AppState,
+};
+
+/// How long a device stays logged in without refreshing.
+const SESSION_TTL_DAYS: i32 = 30;
+/// A replaced refresh token used again this soon after is taken for two
+/// tabs refreshing at once; any later, for a stolen token.
+const REUSE_GRACE_SECONDS: f64 = 60.0;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/auth/refresh", post(refresh))
+        .route("/api/auth/logout", post(logout))
+        .route("/api/me/sessions", get(list_sessions))
+        .route("/api/me/sessions/:id", delete(revoke_session))
+}
+
+fn new_refresh_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn token_hash(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+/// The requesting browser's `User-Agent`, to tell sessions apart.
+pub fn user_agent(headers: &HeaderMap) -> Option<String> {
+    let agent = headers.get(header::USER_AGENT)?.to_str().ok()?;
+    Some(agent.chars().take(255).collect())
+}
+
+/// Starts a session for `user`, returning its id and refresh token.
+pub async fn create(pool: &PgPool, user: Uuid, user_agent: Option<String>) -> Result<(Uuid, String), sqlx::Error> {
+    let token = new_refresh_token();
+    let id = sqlx::query_scalar::<_, Uuid>(
+        r#"
+        INSERT INTO sessions (user_id, refresh_token_hash, user_agent, expires_at)
+        VALUES ($1, $2, $3, NOW() + make_interval(days => $4))
+        RETURNING id
+        "#,
+    )
+    .bind(user)
+    .bind(token_hash(&token))
+    .bind(user_agent)
+    .bind(SESSION_TTL_DAYS)
+    .fetch_one(pool)
+    .await?;
+    Ok((id, token))
+}
+
+/// Whether the session can still be used: neither revoked nor expired.
+pub async fn is_active(pool: &PgPool, id: Uuid) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar::<_, bool>(
+        "SELECT EXISTS (SELECT 1 FROM sessions WHERE id = $1 AND revoked_at IS NULL AND expires_at > NOW())",
+    )
+    .bind(id)
+    .fetch_one(pool)
+    .await
+}
+
+#[derive(Deserialize)]
+struct RefreshRequest {
+    refresh_token: String,
+}
+
+/// Swaps a refresh token for a new access token and a new refresh token.
+///
+/// A token that was already swapped means two copies exist. Shortly after
+/// the swap that is a race between tabs and only this request fails;
+/// later, someone else has the token, so the session is revoked.
+async fn refresh(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<RefreshRequest>,
+) -> Result<Json<TokenResponse>, StatusCode> {
+    let hash = token_hash(&payload.refresh_token);
+    let mut tx = state.db.writer().begin().await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let current = sqlx::query_as::<_, (Uuid, Uuid, String)>(
+        r#"
+        SELECT s.id, s.user_id, u.role FROM sessions s JOIN users u ON u.id = s.user_id
+        WHERE s.refresh_token_hash = $1 AND s.revoked_at IS NULL AND s.expires_at > NOW()
+            AND u.banned_at IS NULL
+        FOR UPDATE OF s
+        "#,
+    )
+    .bind(&hash)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    if let Some((session, user, role)) = current {
+        let token = new_refresh_token();
+        sqlx::query(
+            r#"
+            UPDATE sessions SET
+                previous_token_hash = refresh_token_hash,
+                refresh_token_hash = $2,
+                rotated_at = NOW(),
+                last_seen_at = NOW(),
+                expires_at = NOW() + make_interval(days => $3),
+                user_agent = COALESCE($4, user_agent)
+            WHERE id = $1
+            "#,
+        )
+        .bind(session)
+        .bind(token_hash(&token))
+        .bind(SESSION_TTL_DAYS)
+        .bind(user_agent(&headers))
+        .execute(&mut *tx)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        tx.commit().await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+        return Ok(Json(TokenResponse {
+            token: auth::issue_token(&state.config, user, &role, session)?,
+            refresh_token: token,
+        }));
+    }
+
+    let replaced = sqlx::query_as::<_, (Uuid, bool)>(
+        r#"
+        SELECT id, rotated_at > NOW() - make_interval(secs => $2) FROM sessions
+        WHERE previous_token_hash = $1 AND revoked_at IS NULL
+        "#,
+    )
+    .bind(&hash)
+    .bind(REUSE_GRACE_SECONDS)
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    match replaced {
+        Some((_, true)) => Err(StatusCode::CONFLICT),
+        Some((session, false)) => {
+            tracing::warn!(%session, "replaced refresh token reused; revoking session");
+            sqlx::query("UPDATE sessions SET revoked_at = NOW() WHERE id = $1")
+                .bind(session)
+                .execute(&mut *tx)
+                .await
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+            tx.commit().await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+            Err(StatusCode::UNAUTHORIZED)
+        }
+        None => Err(StatusCode::UNAUTHORIZED),
+    }
+}
+
+/// Ends the caller's own session.
+async fn logout(State(pool): State<PgPool>, user: AuthUser) -> Result<StatusCode, StatusCode> {
+    if let Some(session) = user.session_id {
+        revoke(&pool, user.id, session).await?;
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn revoke(pool: &PgPool, user: Uuid, session: Uuid) -> Result<(), StatusCode> {
+    let revoked = sqlx::query(
+        "UPDATE sessions SET revoked_at = NOW() WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL",
+    )
+    .bind(session)
+    .bind(user)
+    .execute(pool)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+    .rows_affected();
+
+    match revoked {
+        0 => Err(StatusCode::NOT_FOUND),
+        _ => Ok(()),
+    }
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+struct Session {
+    id: Uuid,
+    #[serde(skip)]
+    user_agent: Option<String>,
+    /// e.g. "Firefox on Windows".
+    #[sqlx(skip)]
+    device: String,
+    /// Whether this is the session making the request.
+    current: bool,
+    created_at: NaiveDateTime,
+    last_seen_at: NaiveDateTime,
+}
+
+/// The caller's sessions that can still refresh, most recently used first.
+async fn list_sessions(Reader(pool): Reader, user: AuthUser) -> Result<Json<Vec<Session>>, StatusCode> {
+    let mut sessions = sqlx::query_as::<_, Session>(
+        r#"
+        SELECT id, user_agent, id = $2 AS current, created_at, last_seen_at FROM sessions
+        WHERE user_id = $1 AND revoked_at IS NULL AND expires_at > NOW()
+        ORDER BY last_seen_at DESC
+        "#,
+    )
+    .bind(user.id)
+    .bind(user.session_id)
+    .fetch_all(&pool)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    for session in &mut sessions {
+        session.device = device(session.user_agent.as_deref());
+    }
+    Ok(Json(sessions))
+}
+
+/// Logs one of the caller's devices out. Its current access token still
+/// works until it expires, within `auth::ACCESS_TOKEN_TTL_SECONDS`.
+async fn revoke_session(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, StatusCode> {
+    revoke(&pool, user.id, id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Browser and operating system named in a `User-Agent`.
+fn device(user_agent: Option<&str>) -> String {
+    let Some(agent) = user_agent else {
+        return "Unknown device".to_string();
+    };
+    // Order matters: Edge mentions Chrome, and Chrome mentions Safari.
+    let browser = [("Edg/", "Edge"), ("Firefox/", "Firefox"), ("Chrome/", "Chrome"), ("Safari/", "Safari")]
+        .into_iter()
+        .find(|(marker, _)| agent.contains(marker))
+        .map_or("Unknown browser", |(_, name)| name);
+    // iPhones and Android phones also say "like Mac OS X" and "Linux".
+    let os = [
+        ("iPhone", "iOS"),
+        ("iPad", "iPadOS"),
+        ("Android", "Android"),
+        ("Windows", "Windows"),
+        ("Mac OS X", "macOS"),
+        ("Linux", "Linux"),
+    ]
+    .into_iter()
+    .find(|(marker, _)| agent.contains(marker))
+    .map(|(_, name)| name);
+
+    match os {
+        Some(os) => format!("{} on {}", browser, os),
+        None => browser.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_devices_from_user_agents() {
+        let firefox = "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:120.0) Gecko/20100101 Firefox/120.0";
+        assert_eq!(device(Some(firefox)), "Firefox on Windows");
+        let iphone = "Mozilla/5.0 (iPhone; CPU iPhone OS 17_0 like Mac OS X) AppleWebKit/605.1.15 \
+                      (KHTML, like Gecko) Version/17.0 Mobile/15E148 Safari/604.1";
+        assert_eq!(device(Some(iphone)), "Safari on iOS");
+        let edge = "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 \
+                    (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36 Edg/120.0.0.0";
+        assert_eq!(device(Some(edge)), "Edge on macOS");
+        assert_eq!(device(Some("curl/8.4.0")), "Unknown browser");
+        assert_eq!(device(None), "Unknown device");
+    }
+
+    #[test]
+    fn refresh_tokens_are_random_and_stored_hashed() {
+        let token = new_refresh_token();
+        assert_eq!(token.len(), 64);
+        assert_ne!(token, new_refresh_token());
+        assert_eq!(token_hash(&token), token_hash(&token));
+        assert_ne!(token_hash(&token), token);
+    }
+}
@@ -0,0 +1,258 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, put},
+    Json, Router,
+};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+use crate::{
+    auth::AuthUser,
+    db::{events::Events, Reader},
+    sanitize, timelines, validation_error, AppState,
+};
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/sources", get(list_sources).post(create_source))
+        .route("/api/sources/:id", get(get_source))
+        .route("/api/events/:id/sources", get(get_event_sources).post(cite_new_source))
+        .route(
+            "/api/events/:id/sources/:source_id",
+            put(attach_source).delete(detach_source),
+        )
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+struct Source {
+    id: Uuid,
+    title: String,
+    url: Option<String>,
+    author: Option<String>,
+    citation: Option<String>,
+    created_at: NaiveDateTime,
+}
+
+#[derive(Deserialize, Validate)]
+struct SourceInput {
+    #[validate(length(min = 1, max = 255))]
+    title: String,
+    #[validate(length(max = 2048), custom(function = "web_url"))]
+    url: Option<String>,
+    #[validate(length(max = 255))]
+    author: Option<String>,
+    #[validate(length(max = 10_000))]
+    citation: Option<String>,
+}
+
+impl SourceInput {
+    fn sanitize(&mut self) {
+        self.title = sanitize::text(&self.title);
+        sanitize::optional(&mut self.author);
+        sanitize::optional(&mut self.citation);
+    }
+}
+
+/// Source links are shown to readers, so only web pages are accepted.
+fn web_url(url: &str) -> Result<(), ValidationError> {
+    let rest = url.strip_prefix("https://").or_else(|| url.strip_prefix("http://"));
+    match rest {
+        Some(rest) if !rest.is_empty() && !rest.contains(char::is_whitespace) => Ok(()),
+        _ => Err(ValidationError::new("url")),
+    }
+}
+
+#[derive(Deserialize)]
+struct ListQuery {
+    /// Case-insensitive substring of the title or author.
+    search: Option<String>,
+}
+
+async fn list_sources(Reader(pool): Reader, Query(query): Query<ListQuery>) -> Result<Json<Vec<Source>>, StatusCode> {
+    let pattern = format!("%{}%", query.search.unwrap_or_default());
+    let sources = sqlx::query_as::<_, Source>(
+        "SELECT * FROM sources WHERE title ILIKE $1 OR author ILIKE $1 ORDER BY lower(title)",
+    )
+    .bind(pattern)
+    .fetch_all(&pool)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(sources))
+}
+
+async fn get_source(Reader(pool): Reader, Path(id): Path<Uuid>) -> Result<Json<Source>, StatusCode> {
+    sqlx::query_as::<_, Source>("SELECT * FROM sources WHERE id = $1")
+        .bind(id)
+        .fetch_optional(&pool)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .map(Json)
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+async fn create_source(
+    State(pool): State<PgPool>,
+    _user: AuthUser,
+    Json(mut payload): Json<SourceInput>,
+) -> Result<Json<Source>, Response> {
+    payload.sanitize();
+    payload.validate().map_err(validation_error)?;
+    let source = insert(&pool, payload)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
+
+    Ok(Json(source))
+}
+
+async fn insert(pool: &PgPool, source: SourceInput) -> Result<Source, sqlx::Error> {
+    sqlx::query_as::<_, Source>(
+        "INSERT INTO sources (title, url, author, citation) VALUES ($1, $2, $3, $4) RETURNING *",
+    )
+    .bind(source.title.trim())
+    .bind(source.url)
+    .bind(source.author)
+    .bind(source.citation)
+    .fetch_one(pool)
+    .await
+}
+
+/// The event's sources in footnote order.
+async fn get_event_sources(
+    Reader(pool): Reader,
+    State(events): State<Events>,
+    user: Option<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<Source>>, Response> {
+    timelines::ensure_event_visible(&pool, &events, user.as_ref(), id).await?;
+    let sources = event_sources(&pool, id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
+
+    Ok(Json(sources))
+}
+
+/// Adds a new source and cites it on the event.
+async fn cite_new_source(
+    State(pool): State<PgPool>,
+    State(events): State<Events>,
+    user: Option<AuthUser>,
+    Path(id): Path<Uuid>,
+    Json(mut payload): Json<SourceInput>,
+) -> Result<Json<Vec<Source>>, Response> {
+    payload.sanitize();
+    payload.validate().map_err(validation_error)?;
+    ensure_event_exists(&pool, &events, user.as_ref(), id).await?;
+    let error = |_| StatusCode::INTERNAL_SERVER_ERROR.into_response();
+
+    let source = insert(&pool, payload).await.map_err(error)?;
+    link(&pool, id, source.id).await.map_err(error)?;
+
+    let sources = event_sources(&pool, id).await.map_err(error)?;
+    Ok(Json(sources))
+}
+
+/// Cites an existing source on the event; citing it again changes nothing.
+async fn attach_source(
+    State(pool): State<PgPool>,
+    State(events): State<Events>,
+    user: Option<AuthUser>,
+    Path((id, source_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<Vec<Source>>, Response> {
+    ensure_event_exists(&pool, &events, user.as_ref(), id).await?;
+    let error = |_| StatusCode::INTERNAL_SERVER_ERROR.into_response();
+
+    let known = sqlx::query_scalar::<_, bool>("SELECT EXISTS (SELECT 1 FROM sources WHERE id = $1)")
+        .bind(source_id)
+        .fetch_one(&pool)
+        .await
+        .map_err(error)?;
+    if !known {
+        return Err(StatusCode::NOT_FOUND.into_response());
+    }
+    link(&pool, id, source_id).await.map_err(error)?;
+
+    let sources = event_sources(&pool, id).await.map_err(error)?;
+    Ok(Json(sources))
+}
+
+/// Removes the citation; the source itself is kept for other events.
+async fn detach_source(
+    State(pool): State<PgPool>,
+    State(events): State<Events>,
+    user: Option<AuthUser>,
+    Path((id, source_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<Vec<Source>>, Response> {
+    ensure_event_exists(&pool, &events, user.as_ref(), id).await?;
+    let error = |_| StatusCode::INTERNAL_SERVER_ERROR.into_response();
+
+    sqlx::query("DELETE FROM event_sources WHERE event_id = $1 AND source_id = $2")
+        .bind(id)
+        .bind(source_id)
+        .execute(&pool)
+        .await
+        .map_err(error)?;
+
+    let sources = event_sources(&pool, id).await.map_err(error)?;
+    Ok(Json(sources))
+}
+
+/// 404 for a missing event, then the usual write checks.
+async fn ensure_event_exists(
+    pool: &PgPool,
+    events: &Events,
+    user: Option<&AuthUser>,
+    id: Uuid,
+) -> Result<(), Response> {
+    let event = events
+        .find(id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
+    if event.is_none() {
+        return Err(StatusCode::NOT_FOUND.into_response());
+    }
+    timelines::ensure_event_writable(pool, events, user, id).await
+}
+
+async fn link(pool: &PgPool, event_id: Uuid, source_id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query("INSERT INTO event_sources (event_id, source_id) VALUES ($1, $2) ON CONFLICT DO NOTHING")
+        .bind(event_id)
+        .bind(source_id)
+        .execute(pool)
+        .await
+        .map(|_| ())
+}
+
+async fn event_sources(pool: &PgPool, event_id: Uuid) -> Result<Vec<Source>, sqlx::Error> {
+    sqlx::query_as::<_, Source>(
+        r#"
+        SELECT s.* FROM sources s
+        JOIN event_sources es ON es.source_id = s.id
+        WHERE es.event_id = $1
+        ORDER BY es.attached_at, s.id
+        "#,
+    )
+    .bind(event_id)
+    .fetch_all(pool)
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn urls_must_be_web_pages() {
+        assert!(web_url("https://example.org/book").is_ok());
+        assert!(web_url("http://example.org").is_ok());
+        assert!(web_url("javascript:alert(1)").is_err());
+        assert!(web_url("ftp://example.org").is_err());
+        assert!(web_url("https://").is_err());
+        assert!(web_url("https://example.org/a b").is_err());
+    }
+}
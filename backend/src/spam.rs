@@ -0,0 +1,247 @@
+//! Protection for what signed-out visitors write: comments on and reports
+//! of public events, when the instance takes them at all. Each such write
+//! must leave the honeypot empty, stay under its address's hourly limit
+//! and pass the configured challenge, in that order, so bots caught by the
+//! cheap checks never cost a call to the provider.
+
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::{
+    async_trait,
+    extract::{ConnectInfo, FromRef, FromRequestParts, State},
+    http::{request::Parts, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use serde::Deserialize;
+
+use crate::{
+    config::{Challenge, ChallengeProvider, Config},
+    AppState,
+};
+
+/// Span the per-address limit counts over.
+const WINDOW: Duration = Duration::from_secs(60 * 60);
+/// Addresses remembered at most: quiet ones are forgotten first, then the
+/// longest quiet of the rest.
+const MAX_TRACKED: usize = 10_000;
+/// How long a provider gets to check a token.
+const VERIFY_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub fn routes() -> Router<AppState> {
+    Router::new().route("/api/anonymous", get(policy))
+}
+
+/// What signed-out visitors must do to write here, so clients know
+/// whether to offer them a form and which widget to put in it.
+async fn policy(State(config): State<Arc<Config>>) -> Json<serde_json::Value> {
+    Json(serde_json::json!({
+        "enabled": config.anonymous_writes,
+        "challenge": config.challenge.as_ref().map(|challenge| serde_json::json!({
+            "provider": challenge.provider.name(),
+            "site_key": challenge.site_key,
+        })),
+    }))
+}
+
+/// The fields an anonymous write carries besides its own; flattened into
+/// its body.
+#[derive(Deserialize, Default)]
+pub struct Proof {
+    /// The honeypot: hidden from people, so only bots fill it in.
+    #[serde(default)]
+    website: String,
+    /// What the challenge widget handed the visitor.
+    challenge_token: Option<String>,
+}
+
+/// The caller's address: the hop of `X-Forwarded-For` that the outermost
+/// of the configured trusted proxies added, or the peer the server sees.
+pub struct ClientAddress(pub IpAddr);
+
+#[async_trait]
+impl<S> FromRequestParts<S> for ClientAddress
+where
+    Arc<Config>: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = StatusCode;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let config = Arc::<Config>::from_ref(state);
+        let hops: Vec<&str> = parts
+            .headers
+            .get_all("x-forwarded-for")
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .collect();
+        let peer = parts.extensions.get::<ConnectInfo<SocketAddr>>().map(|ConnectInfo(peer)| peer.ip());
+        forwarded(&hops, config.trusted_proxies).or(peer).map(ClientAddress).ok_or(StatusCode::BAD_REQUEST)
+    }
+}
+
+/// The address the outermost of `trusted` proxies saw, each having added
+/// the one it saw to the end of `hops`. Anything before that was written
+/// by the client, so can't be believed; a header shorter than the chain
+/// didn't come through it.
+fn forwarded(hops: &[&str], trusted: usize) -> Option<IpAddr> {
+    let index = hops.len().checked_sub(trusted).filter(|_| trusted > 0)?;
+    hops[index].trim().parse().ok()
+}
+
+/// Recent anonymous writes by address, and the client that asks the
+/// challenge provider; shared through `AppState`.
+#[derive(Clone)]
+pub struct Guard {
+    client: reqwest::Client,
+    recent: Arc<Mutex<HashMap<IpAddr, Vec<Instant>>>>,
+}
+
+impl Default for Guard {
+    fn default() -> Self {
+        Self {
+            client: reqwest::Client::builder()
+                .timeout(VERIFY_TIMEOUT)
+                .build()
+                .expect("failed to build the challenge client"),
+            recent: Arc::default(),
+        }
+    }
+}
+
+fn refuse(status: StatusCode, message: &str) -> Response {
+    (status, Json(serde_json::json!({ "error": message }))).into_response()
+}
+
+impl Guard {
+    /// Lets a signed-out visitor's write through, or says why not: 401 when
+    /// the instance takes none, 422 for a filled honeypot or a failed
+    /// challenge, 429 past the hourly limit.
+    pub async fn check(&self, config: &Config, address: IpAddr, proof: &Proof) -> Result<(), Response> {
+        if !config.anonymous_writes {
+            return Err(StatusCode::UNAUTHORIZED.into_response());
+        }
+        if !proof.website.is_empty() {
+            tracing::info!(%address, "anonymous write filled in the honeypot");
+            return Err(refuse(StatusCode::UNPROCESSABLE_ENTITY, "Rejected as spam."));
+        }
+        if !self.admit(address, config.anonymous_writes_per_hour, Instant::now()) {
+            return Err(refuse(StatusCode::TOO_MANY_REQUESTS, "Too many posts from your address; try again later."));
+        }
+        let Some(challenge) = &config.challenge else {
+            return Ok(());
+        };
+        let Some(token) = proof.challenge_token.as_deref().filter(|token| !token.is_empty()) else {
+            return Err(refuse(StatusCode::UNPROCESSABLE_ENTITY, "Please complete the challenge."));
+        };
+        match self.verify(challenge, token, address).await {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(refuse(StatusCode::UNPROCESSABLE_ENTITY, "The challenge failed; please try again.")),
+            Err(err) => {
+                tracing::warn!(provider = challenge.provider.name(), error = %err, "challenge verification failed");
+                Err(refuse(StatusCode::SERVICE_UNAVAILABLE, "Couldn't check the challenge; please try again."))
+            }
+        }
+    }
+
+    /// Whether `address` may write once more at `now`, counting the write
+    /// if so.
+    fn admit(&self, address: IpAddr, limit: u32, now: Instant) -> bool {
+        let Ok(mut recent) = self.recent.lock() else {
+            return true;
+        };
+        if recent.len() >= MAX_TRACKED {
+            recent.retain(|_, times| times.last().is_some_and(|last| now.duration_since(*last) < WINDOW));
+        }
+        while recent.len() >= MAX_TRACKED && !recent.contains_key(&address) {
+            let Some(oldest) = recent.iter().min_by_key(|(_, times)| times.last().copied()).map(|(oldest, _)| *oldest)
+            else {
+                break;
+            };
+            recent.remove(&oldest);
+        }
+        let times = recent.entry(address).or_default();
+        times.retain(|time| now.duration_since(*time) < WINDOW);
+        if times.len() >= limit as usize {
+            return false;
+        }
+        times.push(now);
+        true
+    }
+
+    /// Asks the provider whether the visitor at `address` solved the
+    /// challenge that handed them `token`.
+    async fn verify(&self, challenge: &Challenge, token: &str, address: IpAddr) -> Result<bool, reqwest::Error> {
+        #[derive(Deserialize)]
+        struct Verdict {
+            success: bool,
+        }
+
+        let url = match challenge.provider {
+            ChallengeProvider::HCaptcha => "https://api.hcaptcha.com/siteverify",
+            ChallengeProvider::Turnstile => "https://challenges.cloudflare.com/turnstile/v0/siteverify",
+        };
+        let address = address.to_string();
+        let verdict = self
+            .client
+            .post(url)
+            .form(&[("secret", challenge.secret.as_str()), ("response", token), ("remoteip", &address)])
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<Verdict>()
+            .await?;
+        Ok(verdict.success)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn believes_only_the_trusted_hops() {
+        let hops = ["198.51.100.7", "203.0.113.1", "10.0.0.2"];
+
+        assert_eq!(forwarded(&hops, 0), None);
+        assert_eq!(forwarded(&hops, 1), "10.0.0.2".parse().ok());
+        assert_eq!(forwarded(&hops, 2), "203.0.113.1".parse().ok());
+        assert_eq!(forwarded(&hops, 4), None);
+        assert_eq!(forwarded(&["not an address"], 1), None);
+    }
+
+    #[test]
+    fn limits_each_address_per_hour() {
+        let guard = Guard::default();
+        let (one, other) = ("203.0.113.1".parse().unwrap(), "203.0.113.2".parse().unwrap());
+        let start = Instant::now();
+
+        assert!(guard.admit(one, 2, start));
+        assert!(guard.admit(one, 2, start + Duration::from_secs(60)));
+        assert!(!guard.admit(one, 2, start + Duration::from_secs(120)));
+        assert!(guard.admit(other, 2, start + Duration::from_secs(120)));
+        // The first write has aged out of the window.
+        assert!(guard.admit(one, 2, start + WINDOW + Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn forgets_the_oldest_address_when_full() {
+        let guard = Guard::default();
+        let start = Instant::now();
+        let address = |n: usize| IpAddr::from(std::net::Ipv4Addr::from(n as u32));
+        for n in 0..MAX_TRACKED {
+            assert!(guard.admit(address(n), 1, start + Duration::from_millis(n as u64)));
+        }
+
+        assert!(guard.admit(address(MAX_TRACKED), 1, start + WINDOW / 2));
+        let recent = guard.recent.lock().unwrap();
+        assert_eq!(recent.len(), MAX_TRACKED);
+        assert!(!recent.contains_key(&address(0)));
+        assert!(recent.contains_key(&address(1)));
+    }
+}
@@ -0,0 +1,146 @@
+use std::path::Path as FsPath;
+
+use axum::{
+    extract::{Path, Request, State},
+    http::{header, HeaderValue, StatusCode},
+    middleware::{self, Next},
+    response::{Html, IntoResponse, Response},
+    routing::{get, MethodRouter},
+    Router,
+};
+use tower_http::services::ServeDir;
+
+use crate::AppState;
+
+const FALLBACK_INDEX_HTML: &str = include_str!("../../public/index.html");
+const MANIFEST: &str = include_str!("../../public/manifest.webmanifest");
+const SERVICE_WORKER: &str = include_str!("../../public/sw.js");
+
+const ICONS: &[(&str, &[u8])] = &[
+    ("icon-192.png", include_bytes!("../../public/icons/icon-192.png")),
+    ("icon-512.png", include_bytes!("../../public/icons/icon-512.png")),
+    ("icon-maskable-512.png", include_bytes!("../../public/icons/icon-maskable-512.png")),
+    ("apple-touch-icon.png", include_bytes!("../../public/icons/apple-touch-icon.png")),
+];
+
+/// Routes for the installable web app: the manifest, service worker and icons.
+pub fn routes<S>() -> Router<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    Router::new()
+        .route("/manifest.webmanifest", get(manifest))
+        .route("/sw.js", get(service_worker))
+        .route("/icons/:name", get(icon))
+}
+
+async fn manifest() -> impl IntoResponse {
+    (
+        [
+            (header::CONTENT_TYPE, "application/manifest+json"),
+            (header::CACHE_CONTROL, "public, max-age=86400"),
+        ],
+        MANIFEST,
+    )
+}
+
+async fn service_worker() -> impl IntoResponse {
+    // The worker must be revalidated on every load or clients keep a stale shell.
+    (
+        [
+            (header::CONTENT_TYPE, "application/javascript"),
+            (header::CACHE_CONTROL, "no-cache"),
+        ],
+        SERVICE_WORKER,
+    )
+}
+
+async fn icon(Path(name): Path<String>) -> Result<impl IntoResponse, StatusCode> {
+    let (_, bytes) = ICONS
+        .iter()
+        .find(|(file, _)| *file == name)
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "image/png"),
+            (header::CACHE_CONTROL, "public, max-age=604800"),
+        ],
+        *bytes,
+    ))
+}
+
+/// Reads the built `index.html`, falling back to the source template when the
+/// frontend hasn't been built (e.g. running the backend alone in development).
+pub fn load_index(asset_dir: &FsPath) -> String {
+    match std::fs::read_to_string(asset_dir.join("index.html")) {
+        Ok(html) => html,
+        Err(err) => {
+            tracing::warn!(
+                "no index.html in {}: {}; using the unbuilt template",
+                asset_dir.display(),
+                err
+            );
+            FALLBACK_INDEX_HTML.to_string()
+        }
+    }
+}
+
+/// Serves the built frontend from the asset directory, answering any other
+/// non-API path with `index.html` so client-side routes survive a refresh.
+pub fn spa_service(state: &AppState) -> MethodRouter {
+    let files = ServeDir::new(&state.config.asset_dir)
+        .fallback(get(spa_index).with_state(state.clone()));
+
+    MethodRouter::new()
+        .fallback_service(files)
+        .layer(middleware::from_fn(cache_control))
+}
+
+async fn spa_index(State(state): State<AppState>, request: Request) -> Response {
+    if request.uri().path().starts_with("/api/") {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+
+    Html(state.index_html.to_string()).into_response()
+}
+
+/// Hashed build outputs never change, so they can be cached forever; the HTML
+/// entry point must always be revalidated so new hashes are picked up.
+async fn cache_control(request: Request, next: Next) -> Response {
+    let path = request.uri().path().to_string();
+    let mut response = next.run(request).await;
+
+    if !response.status().is_success() || response.headers().contains_key(header::CACHE_CONTROL) {
+        return response;
+    }
+
+    let is_html = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("text/html"));
+
+    let value = if is_html {
+        "no-cache"
+    } else if is_hashed(&path) {
+        "public, max-age=31536000, immutable"
+    } else {
+        "public, max-age=3600"
+    };
+    response
+        .headers_mut()
+        .insert(header::CACHE_CONTROL, HeaderValue::from_static(value));
+
+    response
+}
+
+/// Whether a file name carries a content hash, e.g. `timeline_frontend-3f9a1c2b7e4d_bg.wasm`.
+fn is_hashed(path: &str) -> bool {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    let stem = name.split('.').next().unwrap_or(name);
+
+    stem.split(['-', '_']).any(|part| {
+        part.len() >= 8 && part.chars().all(|c| c.is_ascii_hexdigit())
+    })
+}
@@ -0,0 +1,317 @@
+//! Stories: a curator's ordered walk through some of a timeline's events,
+//! with narration for each step. Anyone who can see the timeline can read
+//! its stories; its editors write them. Steps on events the reader cannot
+//! see yet (drafts) are left out for them.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    auth::AuthUser,
+    db::Reader,
+    sanitize,
+    timelines::{self, Access},
+    validation_error, AppState,
+};
+
+/// Most steps one story may have.
+const MAX_STEPS: u64 = 100;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/timelines/:id/stories", get(list_stories).post(create_story))
+        .route("/api/stories/:id", get(get_story).put(update_story).delete(delete_story))
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+struct StorySummary {
+    id: Uuid,
+    title: String,
+    description: Option<String>,
+    step_count: i64,
+    updated_at: NaiveDateTime,
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+struct Story {
+    id: Uuid,
+    timeline_id: Uuid,
+    title: String,
+    description: Option<String>,
+    created_at: NaiveDateTime,
+    updated_at: NaiveDateTime,
+}
+
+/// A step with enough of its event to show it without another request.
+#[derive(Serialize, sqlx::FromRow)]
+struct StoryStep {
+    id: Uuid,
+    position: i32,
+    event_id: Uuid,
+    narration: Option<String>,
+    title: String,
+    start_date: NaiveDateTime,
+    end_date: Option<NaiveDateTime>,
+    image_url: Option<String>,
+}
+
+#[derive(Serialize)]
+struct StoryDetail {
+    #[serde(flatten)]
+    story: Story,
+    timeline_title: String,
+    steps: Vec<StoryStep>,
+    /// Whether the caller may change or delete the story.
+    can_edit: bool,
+}
+
+#[derive(Serialize, Deserialize, Validate)]
+struct StepInput {
+    event_id: Uuid,
+    #[validate(length(max = 5000))]
+    narration: Option<String>,
+}
+
+/// Body of both POST and PUT; a PUT replaces every step.
+#[derive(Deserialize, Validate)]
+struct StoryInput {
+    #[validate(length(min = 1, max = 255))]
+    title: String,
+    #[validate(length(max = 2000))]
+    description: Option<String>,
+    #[validate(length(min = 1, max = MAX_STEPS), nested)]
+    steps: Vec<StepInput>,
+}
+
+impl StoryInput {
+    fn sanitize(&mut self) {
+        self.title = sanitize::text(&self.title);
+        sanitize::optional(&mut self.description);
+        for step in &mut self.steps {
+            sanitize::optional(&mut step.narration);
+        }
+    }
+}
+
+/// Fails with 422 unless every step's event is on `timeline_id`.
+async fn ensure_on_timeline(pool: &PgPool, timeline_id: Uuid, steps: &[StepInput]) -> Result<(), Response> {
+    let mut ids: Vec<Uuid> = steps.iter().map(|step| step.event_id).collect();
+    ids.sort();
+    ids.dedup();
+    let found = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM events WHERE id = ANY($1) AND timeline_id = $2")
+        .bind(&ids)
+        .bind(timeline_id)
+        .fetch_one(pool)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
+    if found != ids.len() as i64 {
+        return Err((StatusCode::UNPROCESSABLE_ENTITY, "every step must be an event on the story's timeline").into_response());
+    }
+    Ok(())
+}
+
+/// Replaces the story's steps with `steps`, numbered from 0.
+async fn write_steps(conn: &mut PgConnection, story_id: Uuid, steps: Vec<StepInput>) -> Result<(), sqlx::Error> {
+    let (events, narrations): (Vec<Uuid>, Vec<Option<String>>) =
+        steps.into_iter().map(|step| (step.event_id, step.narration)).unzip();
+    sqlx::query("DELETE FROM story_steps WHERE story_id = $1")
+        .bind(story_id)
+        .execute(&mut *conn)
+        .await?;
+    sqlx::query(
+        r#"
+        INSERT INTO story_steps (story_id, position, event_id, narration)
+        SELECT $1, s.ord - 1, s.event_id, s.narration
+        FROM UNNEST($2::UUID[], $3::TEXT[]) WITH ORDINALITY AS s(event_id, narration, ord)
+        "#,
+    )
+    .bind(story_id)
+    .bind(events)
+    .bind(narrations)
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
+async fn find_story(pool: &PgPool, id: Uuid) -> Result<Story, Response> {
+    sqlx::query_as::<_, Story>(
+        "SELECT id, timeline_id, title, description, created_at, updated_at FROM stories WHERE id = $1",
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?
+    .ok_or_else(|| StatusCode::NOT_FOUND.into_response())
+}
+
+/// The story with its steps as `user` may see them.
+async fn detail(pool: &PgPool, story: Story, user: Option<&AuthUser>) -> Result<StoryDetail, Response> {
+    let timeline = timelines::find(pool, story.timeline_id).await?;
+    let access = timelines::access(pool, &timeline, user).await?;
+    if access == Access::None {
+        return Err(StatusCode::NOT_FOUND.into_response());
+    }
+    let can_edit = access >= Access::Edit;
+    let steps = sqlx::query_as::<_, StoryStep>(
+        r#"
+        SELECT s.id, s.position, s.event_id, s.narration, e.title, e.start_date, e.end_date, e.image_url
+        FROM story_steps s JOIN events e ON e.id = s.event_id
+        WHERE s.story_id = $1 AND ($2 OR e.status = 'published')
+        ORDER BY s.position
+        "#,
+    )
+    .bind(story.id)
+    .bind(can_edit)
+    .fetch_all(pool)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
+    let can_edit = can_edit && timeline.archived_at.is_none();
+
+    Ok(StoryDetail { story, timeline_title: timeline.title, steps, can_edit })
+}
+
+async fn list_stories(
+    Reader(pool): Reader,
+    user: Option<AuthUser>,
+    Path(timeline_id): Path<Uuid>,
+) -> Result<Json<Vec<StorySummary>>, Response> {
+    timelines::find_visible(&pool, timeline_id, user.as_ref()).await?;
+    let stories = sqlx::query_as::<_, StorySummary>(
+        r#"
+        SELECT s.id, s.title, s.description, s.updated_at,
+            (SELECT COUNT(*) FROM story_steps WHERE story_id = s.id) AS step_count
+        FROM stories s
+        WHERE s.timeline_id = $1
+        ORDER BY lower(s.title)
+        "#,
+    )
+    .bind(timeline_id)
+    .fetch_all(&pool)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
+
+    Ok(Json(stories))
+}
+
+async fn get_story(
+    Reader(pool): Reader,
+    user: Option<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<StoryDetail>, Response> {
+    let story = find_story(&pool, id).await?;
+    detail(&pool, story, user.as_ref()).await.map(Json)
+}
+
+async fn create_story(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Path(timeline_id): Path<Uuid>,
+    Json(mut payload): Json<StoryInput>,
+) -> Result<(StatusCode, Json<StoryDetail>), Response> {
+    payload.sanitize();
+    payload.validate().map_err(validation_error)?;
+    timelines::ensure_timeline_writable(&pool, Some(&user), Some(timeline_id)).await?;
+    ensure_on_timeline(&pool, timeline_id, &payload.steps).await?;
+    let internal = |_| StatusCode::INTERNAL_SERVER_ERROR.into_response();
+
+    let mut tx = pool.begin().await.map_err(internal)?;
+    let story = sqlx::query_as::<_, Story>(
+        r#"
+        INSERT INTO stories (timeline_id, title, description, created_by)
+        VALUES ($1, $2, $3, $4)
+        RETURNING id, timeline_id, title, description, created_at, updated_at
+        "#,
+    )
+    .bind(timeline_id)
+    .bind(payload.title.trim())
+    .bind(&payload.description)
+    .bind(user.id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(internal)?;
+    write_steps(&mut tx, story.id, payload.steps).await.map_err(internal)?;
+    tx.commit().await.map_err(internal)?;
+
+    Ok((StatusCode::CREATED, Json(detail(&pool, story, Some(&user)).await?)))
+}
+
+async fn update_story(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(mut payload): Json<StoryInput>,
+) -> Result<Json<StoryDetail>, Response> {
+    payload.sanitize();
+    payload.validate().map_err(validation_error)?;
+    let story = find_story(&pool, id).await?;
+    timelines::ensure_timeline_writable(&pool, Some(&user), Some(story.timeline_id)).await?;
+    ensure_on_timeline(&pool, story.timeline_id, &payload.steps).await?;
+    let internal = |_| StatusCode::INTERNAL_SERVER_ERROR.into_response();
+
+    let mut tx = pool.begin().await.map_err(internal)?;
+    let story = sqlx::query_as::<_, Story>(
+        r#"
+        UPDATE stories SET title = $2, description = $3, updated_at = NOW()
+        WHERE id = $1
+        RETURNING id, timeline_id, title, description, created_at, updated_at
+        "#,
+    )
+    .bind(id)
+    .bind(payload.title.trim())
+    .bind(&payload.description)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(internal)?;
+    write_steps(&mut tx, id, payload.steps).await.map_err(internal)?;
+    tx.commit().await.map_err(internal)?;
+
+    detail(&pool, story, Some(&user)).await.map(Json)
+}
+
+async fn delete_story(State(pool): State<PgPool>, user: AuthUser, Path(id): Path<Uuid>) -> Result<StatusCode, Response> {
+    let story = find_story(&pool, id).await?;
+    timelines::ensure_timeline_writable(&pool, Some(&user), Some(story.timeline_id)).await?;
+    sqlx::query("DELETE FROM stories WHERE id = $1")
+        .bind(id)
+        .execute(&pool)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn story(steps: usize, narration: &str) -> StoryInput {
+        StoryInput {
+            title: "The road to 1789".to_string(),
+            description: None,
+            steps: (0..steps)
+                .map(|_| StepInput { event_id: Uuid::new_v4(), narration: Some(narration.to_string()) })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn stories_need_between_one_and_max_steps() {
+        assert!(story(1, "First").validate().is_ok());
+        assert!(story(0, "").validate().is_err());
+        assert!(story(MAX_STEPS as usize + 1, "").validate().is_err());
+    }
+
+    #[test]
+    fn narration_length_is_checked_per_step() {
+        assert!(story(2, &"a".repeat(5001)).validate().is_err());
+    }
+}
@@ -0,0 +1,80 @@
+//! Swimlanes: the timeline's alternative layout with one horizontal lane per
+//! category. The lanes come from here rather than from whatever events the
+//! page happens to have loaded, so their order and counts cover every event.
+
+use axum::{
+    extract::Query,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::QueryBuilder;
+use uuid::Uuid;
+
+use crate::{
+    auth::AuthUser,
+    db::{events::UNCATEGORIZED, Reader},
+    timelines::{self, Access},
+    AppState,
+};
+
+pub fn routes() -> Router<AppState> {
+    Router::new().route("/api/events/lanes", get(get_lanes))
+}
+
+#[derive(Deserialize)]
+struct LanesQuery {
+    /// Lanes of one timeline's events rather than all public ones.
+    timeline_id: Option<Uuid>,
+}
+
+/// One category's lane.
+#[derive(Serialize, sqlx::FromRow)]
+struct Lane {
+    /// The category, or `UNCATEGORIZED` for events without one.
+    category: String,
+    count: i64,
+    first_date: NaiveDateTime,
+    last_date: NaiveDateTime,
+}
+
+/// A lane per category, in the order their first events happened, so the
+/// lanes read top to bottom roughly as the story unfolds.
+async fn get_lanes(
+    Reader(pool): Reader,
+    user: Option<AuthUser>,
+    Query(query): Query<LanesQuery>,
+) -> Result<Json<Vec<Lane>>, Response> {
+    let mut builder = QueryBuilder::new("SELECT COALESCE(e.category, ");
+    builder.push_bind(UNCATEGORIZED).push(
+        ") AS category, COUNT(*) AS count, MIN(e.start_date) AS first_date, \
+         MAX(COALESCE(e.end_date, e.start_date)) AS last_date FROM events e WHERE ",
+    );
+    match query.timeline_id {
+        Some(id) => {
+            let timeline = timelines::find_visible(&pool, id, user.as_ref()).await?;
+            // Drafts get a lane for the people who can see them on the timeline.
+            let editor = timelines::access(&pool, &timeline, user.as_ref()).await? >= Access::Edit;
+            builder
+                .push("e.timeline_id = ")
+                .push_bind(id)
+                .push(" AND (")
+                .push_bind(editor)
+                .push(" OR e.status = 'published')");
+        }
+        None => {
+            builder.push(timelines::PUBLIC_EVENT);
+        }
+    }
+    builder.push(" GROUP BY 1 ORDER BY 3, 1");
+
+    let lanes = builder
+        .build_query_as::<Lane>()
+        .fetch_all(&pool)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
+    Ok(Json(lanes))
+}
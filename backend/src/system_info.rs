@@ -0,0 +1,115 @@
+use std::time::Instant;
+
+use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::{auth::AuthUser, cache, db, jobs, AppState};
+
+pub fn routes() -> Router<AppState> {
+    Router::new().route("/api/admin/systeminfo", get(get_system_info))
+}
+
+#[derive(Serialize)]
+struct SystemInfo {
+    build: Build,
+    config: serde_json::Value,
+    features: Features,
+    migrations: Migrations,
+    database: Database,
+    assets: Assets,
+    jobs: Vec<jobs::JobStatus>,
+    /// This instance's hits and misses on public reads.
+    cache: cache::Stats,
+}
+
+#[derive(Serialize)]
+struct Build {
+    version: &'static str,
+    /// Commit the binary was built from, when `GIT_SHA` was set at build time.
+    git_sha: Option<&'static str>,
+    profile: &'static str,
+    started_at: DateTime<Utc>,
+    uptime_seconds: i64,
+}
+
+#[derive(Serialize)]
+struct Features {
+    compression: bool,
+    email_delivery: bool,
+}
+
+#[derive(Serialize)]
+struct Migrations {
+    applied: Option<i64>,
+    latest: Option<i64>,
+    pending: bool,
+}
+
+#[derive(Serialize)]
+struct Database {
+    ok: bool,
+    latency_ms: Option<f64>,
+    error: Option<String>,
+    pool: db::PoolStats,
+    replicas: Vec<db::PoolStats>,
+}
+
+#[derive(Serialize)]
+struct Assets {
+    dir_exists: bool,
+    index_html_exists: bool,
+}
+
+async fn get_system_info(
+    State(state): State<AppState>,
+    user: AuthUser,
+) -> Result<Json<SystemInfo>, StatusCode> {
+    user.require_admin()?;
+
+    let started = Instant::now();
+    let ping = sqlx::query("SELECT 1").execute(state.db.writer()).await;
+    let database = Database {
+        ok: ping.is_ok(),
+        latency_ms: ping.as_ref().ok().map(|_| started.elapsed().as_secs_f64() * 1000.0),
+        error: ping.err().map(|err| err.to_string()),
+        pool: db::stats(state.db.writer()),
+        replicas: state.db.replicas().iter().map(db::stats).collect(),
+    };
+
+    let applied = sqlx::query_scalar::<_, Option<i64>>("SELECT MAX(version) FROM _sqlx_migrations WHERE success")
+        .fetch_one(state.db.writer())
+        .await
+        .ok()
+        .flatten();
+    let latest = sqlx::migrate!("./migrations").iter().map(|migration| migration.version).max();
+
+    let asset_dir = &state.config.asset_dir;
+
+    Ok(Json(SystemInfo {
+        build: Build {
+            version: env!("CARGO_PKG_VERSION"),
+            git_sha: option_env!("GIT_SHA"),
+            profile: if cfg!(debug_assertions) { "debug" } else { "release" },
+            started_at: state.started_at,
+            uptime_seconds: (Utc::now() - state.started_at).num_seconds(),
+        },
+        config: state.config.redacted(),
+        features: Features {
+            compression: state.config.compression,
+            email_delivery: state.config.smtp_url.is_some(),
+        },
+        migrations: Migrations {
+            applied,
+            latest,
+            pending: applied < latest,
+        },
+        database,
+        assets: Assets {
+            dir_exists: asset_dir.is_dir(),
+            index_html_exists: asset_dir.join("index.html").is_file(),
+        },
+        jobs: jobs::status(),
+        cache: state.cache.stats(),
+    }))
+}
@@ -0,0 +1,133 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use serde::Serialize;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{auth::AuthUser, db::{events::Events, Reader}, timelines, AppState};
+
+pub const MAX_TAGS_PER_EVENT: usize = 20;
+const MAX_TAG_LENGTH: usize = 50;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/tags", get(list_tags))
+        .route("/api/events/:id/tags", get(get_event_tags).put(set_event_tags))
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+struct TagCount {
+    name: String,
+    count: i64,
+}
+
+/// Lowercases, trims and de-duplicates tag names, dropping empty ones.
+pub fn normalize(names: Vec<String>) -> Result<Vec<String>, StatusCode> {
+    let mut tags: Vec<String> = names
+        .into_iter()
+        .map(|name| name.trim().to_lowercase())
+        .filter(|name| !name.is_empty())
+        .collect();
+    tags.sort();
+    tags.dedup();
+
+    if tags.len() > MAX_TAGS_PER_EVENT || tags.iter().any(|tag| tag.chars().count() > MAX_TAG_LENGTH) {
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+    Ok(tags)
+}
+
+async fn list_tags(Reader(pool): Reader) -> Result<Json<Vec<TagCount>>, StatusCode> {
+    let tags = sqlx::query_as::<_, TagCount>(
+        r#"
+        SELECT t.name, COUNT(et.event_id) AS count
+        FROM tags t
+        LEFT JOIN event_tags et ON et.tag_id = t.id
+        GROUP BY t.name
+        ORDER BY count DESC, t.name
+        "#,
+    )
+    .fetch_all(&pool)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(tags))
+}
+
+async fn get_event_tags(
+    Reader(pool): Reader,
+    State(events): State<Events>,
+    user: Option<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<String>>, Response> {
+    timelines::ensure_event_visible(&pool, &events, user.as_ref(), id).await?;
+    let tags = sqlx::query_scalar::<_, String>(
+        r#"
+        SELECT t.name FROM tags t
+        JOIN event_tags et ON et.tag_id = t.id
+        WHERE et.event_id = $1
+        ORDER BY t.name
+        "#,
+    )
+    .bind(id)
+    .fetch_all(&pool)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
+
+    Ok(Json(tags))
+}
+
+/// Replaces the event's tags with the given names, creating unknown tags.
+async fn set_event_tags(
+    State(pool): State<PgPool>,
+    State(events): State<Events>,
+    user: Option<AuthUser>,
+    Path(id): Path<Uuid>,
+    Json(names): Json<Vec<String>>,
+) -> Result<Json<Vec<String>>, Response> {
+    let tags = normalize(names).map_err(IntoResponse::into_response)?;
+    timelines::ensure_event_writable(&pool, &events, user.as_ref(), id).await?;
+    let error = |_| StatusCode::INTERNAL_SERVER_ERROR.into_response();
+
+    let mut tx = pool
+        .begin()
+        .await
+        .map_err(error)?;
+
+    let exists = sqlx::query_scalar::<_, bool>("SELECT EXISTS (SELECT 1 FROM events WHERE id = $1)")
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(error)?;
+    if !exists {
+        return Err(StatusCode::NOT_FOUND.into_response());
+    }
+
+    sqlx::query("INSERT INTO tags (name) SELECT UNNEST($1::varchar[]) ON CONFLICT (name) DO NOTHING")
+        .bind(&tags)
+        .execute(&mut *tx)
+        .await
+        .map_err(error)?;
+    sqlx::query("DELETE FROM event_tags WHERE event_id = $1")
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(error)?;
+    sqlx::query("INSERT INTO event_tags (event_id, tag_id) SELECT $1, id FROM tags WHERE name = ANY($2)")
+        .bind(id)
+        .bind(&tags)
+        .execute(&mut *tx)
+        .await
+        .map_err(error)?;
+
+    tx.commit()
+        .await
+        .map_err(error)?;
+
+    Ok(Json(tags))
+}
@@ -0,0 +1,218 @@
+//! Timeline templates: a gallery of timelines offered for copying, and
+//! `POST /api/timelines/:id/clone`, which copies any timeline the caller
+//! can see.
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    auth::AuthUser,
+    db::Reader,
+    organizations,
+    timelines::{self, Access, Timeline},
+    validation_error, AppState,
+};
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/templates", get(list_templates))
+        .route("/api/timelines/:id/clone", post(clone_timeline))
+}
+
+/// A gallery entry.
+#[derive(Serialize, sqlx::FromRow)]
+struct Template {
+    id: Uuid,
+    title: String,
+    description: Option<String>,
+    owner: String,
+    event_count: i64,
+    updated_at: NaiveDateTime,
+}
+
+/// Templates the caller can see, most used first.
+async fn list_templates(Reader(pool): Reader, user: Option<AuthUser>) -> Result<Json<Vec<Template>>, StatusCode> {
+    let templates = sqlx::query_as::<_, Template>(&format!(
+        r#"
+        SELECT t.id, t.title, t.description, u.username AS owner, t.updated_at,
+            (SELECT COUNT(*) FROM events e WHERE e.timeline_id = t.id AND e.status = 'published') AS event_count
+        FROM timelines t JOIN users u ON u.id = t.owner_id
+        WHERE t.is_template AND {}
+        ORDER BY (SELECT COUNT(*) FROM timelines copy WHERE copy.cloned_from = t.id) DESC, t.updated_at DESC
+        "#,
+        timelines::VISIBLE_TIMELINE
+    ))
+    .bind(user.as_ref().map(|user| user.id))
+    .bind(user.as_ref().is_some_and(AuthUser::is_admin))
+    .fetch_all(&pool)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(templates))
+}
+
+#[derive(Deserialize, Validate)]
+struct CloneRequest {
+    /// Defaults to the original's title.
+    #[validate(length(min = 1, max = 255))]
+    title: Option<String>,
+    /// Copies are private unless asked otherwise.
+    #[serde(default = "private")]
+    is_private: bool,
+    /// Put the copy in one of the caller's organizations.
+    organization_id: Option<Uuid>,
+    /// Also copy event images, cited sources and embedded players.
+    #[serde(default)]
+    attachments: bool,
+}
+
+fn private() -> bool {
+    true
+}
+
+/// Copies a timeline for the caller, with its periods, its events and their
+/// categories, tags and people, and optionally their images and sources.
+/// Viewers copy the published events; people who can edit the original also
+/// copy its drafts. Copies never inherit a publishing schedule, views or
+/// featuring.
+async fn clone_timeline(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<CloneRequest>,
+) -> Result<Json<Timeline>, Response> {
+    payload.validate().map_err(validation_error)?;
+    let original = timelines::find_visible(&pool, id, Some(&user)).await?;
+    let editor = timelines::access(&pool, &original, Some(&user)).await? >= Access::Edit;
+    if let Some(organization) = payload.organization_id {
+        organizations::ensure_role(&pool, organization, &user, organizations::Role::Member).await?;
+    }
+    let title = payload.title.as_deref().map(str::trim).unwrap_or(&original.title);
+    if title.is_empty() {
+        return Err(StatusCode::UNPROCESSABLE_ENTITY.into_response());
+    }
+
+    let internal = |_| StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    let mut tx = pool.begin().await.map_err(internal)?;
+    let copy = sqlx::query_as::<_, Timeline>(
+        r#"
+        INSERT INTO timelines (owner_id, title, description, is_private, organization_id, cloned_from)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING *
+        "#,
+    )
+    .bind(user.id)
+    .bind(title)
+    .bind(&original.description)
+    .bind(payload.is_private)
+    .bind(payload.organization_id)
+    .bind(original.id)
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(internal)?;
+
+    let originals = sqlx::query_scalar::<_, Uuid>(
+        "SELECT id FROM events WHERE timeline_id = $1 AND ($2 OR status = 'published')",
+    )
+    .bind(original.id)
+    .bind(editor)
+    .fetch_all(&mut *tx)
+    .await
+    .map_err(internal)?;
+    let copies: Vec<Uuid> = originals.iter().map(|_| Uuid::new_v4()).collect();
+
+    // Each statement joins the originals to their copies' new ids.
+    let mapping = "UNNEST($1::uuid[], $2::uuid[]) AS m(old_id, new_id)";
+    sqlx::query(&format!(
+        r#"
+        INSERT INTO events (
+            id, timeline_id, title, description, start_date, end_date, start_date_min, start_date_max, location,
+            image_url, image_alt, category, importance, color, icon, status, custom_fields
+        )
+        SELECT m.new_id, $3, e.title, e.description, e.start_date, e.end_date, e.start_date_min, e.start_date_max,
+            e.location,
+            CASE WHEN $4 THEN e.image_url END, CASE WHEN $4 THEN e.image_alt END,
+            e.category, e.importance, e.color, e.icon, e.status, e.custom_fields
+        FROM events e JOIN {} ON m.old_id = e.id
+        "#,
+        mapping
+    ))
+    .bind(&originals)
+    .bind(&copies)
+    .bind(copy.id)
+    .bind(payload.attachments)
+    .execute(&mut *tx)
+    .await
+    .map_err(internal)?;
+
+    // Sources keep when they were attached, which numbers their footnotes.
+    let mut links = vec![("event_tags", "tag_id"), ("event_people", "person_id")];
+    if payload.attachments {
+        links.push(("event_sources", "source_id, attached_at"));
+        links.push(("event_embeds", "url, provider, player_url, title, author_name, thumbnail_url"));
+        links.push(("event_images", "url, alt, caption, position, crop"));
+    }
+    for (table, columns) in links {
+        sqlx::query(&format!(
+            "INSERT INTO {0} (event_id, {1}) SELECT m.new_id, {1} FROM {0} l JOIN {2} ON m.old_id = l.event_id",
+            table, columns, mapping
+        ))
+        .bind(&originals)
+        .bind(&copies)
+        .execute(&mut *tx)
+        .await
+        .map_err(internal)?;
+    }
+
+    // The copied events' custom field values need their fields.
+    sqlx::query(
+        r#"
+        INSERT INTO timeline_fields (timeline_id, name, kind, required, position)
+        SELECT $1, name, kind, required, position FROM timeline_fields WHERE timeline_id = $2
+        "#,
+    )
+    .bind(copy.id)
+    .bind(original.id)
+    .execute(&mut *tx)
+    .await
+    .map_err(internal)?;
+
+    // Periods frame the events, so they come along with them.
+    sqlx::query(
+        r#"
+        INSERT INTO periods (timeline_id, name, start_date, end_date, color)
+        SELECT $1, name, start_date, end_date, color FROM periods WHERE timeline_id = $2
+        "#,
+    )
+    .bind(copy.id)
+    .bind(original.id)
+    .execute(&mut *tx)
+    .await
+    .map_err(internal)?;
+
+    tx.commit().await.map_err(internal)?;
+    Ok(Json(copy))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn copies_are_private_and_bare_by_default() {
+        let request: CloneRequest = serde_json::from_str("{}").unwrap();
+        assert!(request.is_private);
+        assert!(!request.attachments);
+        assert!(request.title.is_none());
+    }
+}
@@ -0,0 +1,598 @@
+use std::collections::HashMap;
+
+use axum::{
+    extract::{Path, Query, RawQuery, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::{Database, Encode, PgPool, QueryBuilder, Type};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    auth::AuthUser,
+    cache, custom_fields,
+    db::{
+        events::{Events, TimelineFilter},
+        Reader,
+    },
+    organizations, validation_error, AppState, Event,
+};
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/timelines", get(list_timelines).post(create_timeline))
+        .route(
+            "/api/timelines/:id",
+            get(get_timeline).put(update_timeline).delete(delete_timeline),
+        )
+        .route("/api/timelines/:id/events", get(get_timeline_events))
+        .route(
+            "/api/timelines/:id/archive",
+            post(archive_timeline).delete(unarchive_timeline),
+        )
+        .route("/api/events/:id/move", post(move_event))
+        .route("/api/events/move", post(move_events))
+}
+
+/// Most events one bulk move may take.
+const MAX_MOVE: usize = 500;
+
+#[derive(Serialize, Clone, sqlx::FromRow)]
+pub struct Timeline {
+    pub id: Uuid,
+    pub owner_id: Uuid,
+    pub title: String,
+    pub description: Option<String>,
+    /// Private timelines are only visible to their owner and members.
+    pub is_private: bool,
+    /// Organization the timeline belongs to; its members can see it and
+    /// its owners and admins manage it.
+    pub organization_id: Option<Uuid>,
+    /// Offered in the template gallery for anyone who can see it to copy.
+    pub is_template: bool,
+    /// The timeline this one was cloned from, if it still exists.
+    pub cloned_from: Option<Uuid>,
+    pub archived_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Deserialize, Validate)]
+struct TimelineCreate {
+    #[validate(length(max = 255))]
+    title: String,
+    #[validate(length(max = 2000))]
+    description: Option<String>,
+    #[serde(default)]
+    is_private: bool,
+    /// Must be an organization the caller belongs to.
+    organization_id: Option<Uuid>,
+    #[serde(default)]
+    is_template: bool,
+}
+
+#[derive(Deserialize, Validate)]
+struct TimelineUpdate {
+    #[validate(length(min = 1, max = 255))]
+    title: Option<String>,
+    #[validate(length(max = 2000))]
+    description: Option<String>,
+    is_private: Option<bool>,
+    is_template: Option<bool>,
+}
+
+/// What a caller may do with a timeline, from least to most.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Access {
+    None,
+    View,
+    Edit,
+    Own,
+}
+
+/// SQL condition (on an `events` row aliased `e`) that is true for events
+/// anyone may see: published events that are loose or on public timelines.
+pub const PUBLIC_EVENT: &str = "(e.status = 'published' \
+    AND (e.timeline_id IS NULL OR e.timeline_id IN (SELECT id FROM timelines WHERE NOT is_private)))";
+
+/// SQL condition (on a `timelines` row aliased `t`) that is true for
+/// timelines the user bound as `$1` may see, `$2` being whether they are an
+/// admin: public ones, their own, and those they are a member of directly
+/// or through the timeline's organization.
+pub const VISIBLE_TIMELINE: &str = "(NOT t.is_private \
+    OR t.owner_id = $1 \
+    OR $2 \
+    OR EXISTS (SELECT 1 FROM timeline_members m WHERE m.timeline_id = t.id AND m.user_id = $1) \
+    OR EXISTS (SELECT 1 FROM organization_members om \
+        WHERE om.organization_id = t.organization_id AND om.user_id = $1))";
+
+/// Appends an SQL condition (on `e`) that is true for events a signed-in
+/// user may edit.
+pub fn push_editable_event<'a, DB>(builder: &mut QueryBuilder<'a, DB>, user: Uuid, admin: bool)
+where
+    DB: Database,
+    Uuid: Encode<'a, DB> + Type<DB>,
+    bool: Encode<'a, DB> + Type<DB>,
+{
+    builder
+        .push("(e.timeline_id IS NULL OR ")
+        .push_bind(admin)
+        .push(" OR e.timeline_id IN (SELECT id FROM timelines WHERE owner_id = ")
+        .push_bind(user)
+        .push(") OR e.timeline_id IN (SELECT timeline_id FROM timeline_members WHERE role = 'editor' AND user_id = ")
+        .push_bind(user)
+        .push(
+            ") OR e.timeline_id IN (SELECT t.id FROM timelines t JOIN organization_members om \
+            ON om.organization_id = t.organization_id WHERE om.role IN ('owner', 'admin') AND om.user_id = ",
+        )
+        .push_bind(user)
+        .push("))");
+}
+
+/// Appends an SQL condition (on `e`) that is true for events on timelines
+/// of organizations the user belongs to.
+pub fn push_organization_event<'a, DB>(builder: &mut QueryBuilder<'a, DB>, user: Uuid)
+where
+    DB: Database,
+    Uuid: Encode<'a, DB> + Type<DB>,
+{
+    builder
+        .push(
+            "e.timeline_id IN (SELECT t.id FROM timelines t JOIN organization_members om \
+            ON om.organization_id = t.organization_id WHERE om.user_id = ",
+        )
+        .push_bind(user)
+        .push(")");
+}
+
+/// Rejection for any change to an archived timeline or its events.
+pub struct Archived;
+
+impl IntoResponse for Archived {
+    fn into_response(self) -> Response {
+        (
+            StatusCode::CONFLICT,
+            Json(serde_json::json!({
+                "error": "timeline is archived and read-only; unarchive it to make changes"
+            })),
+        )
+            .into_response()
+    }
+}
+
+pub async fn find(pool: &PgPool, id: Uuid) -> Result<Timeline, Response> {
+    sqlx::query_as::<_, Timeline>("SELECT * FROM timelines WHERE id = $1")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?
+        .ok_or_else(|| StatusCode::NOT_FOUND.into_response())
+}
+
+/// The caller's access to `timeline`: owners and admins own it, members
+/// get their role, and everyone can view public timelines. Owners and
+/// admins of the timeline's organization own it too, and its other members
+/// can view it.
+pub async fn access(pool: &PgPool, timeline: &Timeline, user: Option<&AuthUser>) -> Result<Access, Response> {
+    let public = if timeline.is_private { Access::None } else { Access::View };
+    let Some(user) = user else {
+        return Ok(public);
+    };
+    if timeline.owner_id == user.id || user.is_admin() {
+        return Ok(Access::Own);
+    }
+
+    let role = sqlx::query_scalar::<_, String>(
+        "SELECT role FROM timeline_members WHERE timeline_id = $1 AND user_id = $2",
+    )
+    .bind(timeline.id)
+    .bind(user.id)
+    .fetch_optional(pool)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
+
+    let member = match role.as_deref() {
+        Some("editor") => Access::Edit,
+        Some("viewer") => Access::View,
+        _ => public,
+    };
+    let org = match timeline.organization_id {
+        Some(organization) => match organizations::role(pool, organization, user.id).await? {
+            Some(organizations::Role::Owner | organizations::Role::Admin) => Access::Own,
+            Some(organizations::Role::Member) => Access::View,
+            None => Access::None,
+        },
+        None => Access::None,
+    };
+    Ok(member.max(org))
+}
+
+/// Loads a timeline the caller may see. Private timelines they cannot see
+/// are reported as missing rather than forbidden.
+pub async fn find_visible(pool: &PgPool, id: Uuid, user: Option<&AuthUser>) -> Result<Timeline, Response> {
+    let timeline = find(pool, id).await?;
+    if access(pool, &timeline, user).await? == Access::None {
+        return Err(StatusCode::NOT_FOUND.into_response());
+    }
+    Ok(timeline)
+}
+
+/// Loads a timeline the caller owns (admins own everything, organization
+/// owners and admins their organization's timelines).
+pub async fn find_owned(pool: &PgPool, id: Uuid, user: &AuthUser) -> Result<Timeline, Response> {
+    let timeline = find(pool, id).await?;
+    if access(pool, &timeline, Some(user)).await? != Access::Own {
+        return Err(StatusCode::FORBIDDEN.into_response());
+    }
+    Ok(timeline)
+}
+
+/// Fails unless the caller may change events on `timeline_id`: they need
+/// edit access, and the timeline must not be archived. Events outside any
+/// timeline are not restricted.
+pub async fn ensure_timeline_writable(
+    pool: &PgPool,
+    user: Option<&AuthUser>,
+    timeline_id: Option<Uuid>,
+) -> Result<(), Response> {
+    let Some(timeline_id) = timeline_id else {
+        return Ok(());
+    };
+    let timeline = find(pool, timeline_id).await?;
+    match access(pool, &timeline, user).await? {
+        Access::None => return Err(StatusCode::NOT_FOUND.into_response()),
+        Access::View if user.is_none() => return Err(StatusCode::UNAUTHORIZED.into_response()),
+        Access::View => return Err(StatusCode::FORBIDDEN.into_response()),
+        Access::Edit | Access::Own => {}
+    }
+    match timeline.archived_at {
+        Some(_) => Err(Archived.into_response()),
+        None => Ok(()),
+    }
+}
+
+async fn find_event(events: &Events, event_id: Uuid) -> Result<Option<Event>, Response> {
+    events
+        .find(event_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())
+}
+
+/// `ensure_timeline_writable` for the timeline the event belongs to.
+pub async fn ensure_event_writable(
+    pool: &PgPool,
+    events: &Events,
+    user: Option<&AuthUser>,
+    event_id: Uuid,
+) -> Result<(), Response> {
+    let timeline_id = find_event(events, event_id).await?.and_then(|event| event.timeline_id);
+    ensure_timeline_writable(pool, user, timeline_id).await
+}
+
+/// Fails with 404 unless the caller may see the event: it must not be on a
+/// private timeline they cannot see, and unpublished events additionally
+/// need edit access. Signed-in users may edit loose events.
+pub async fn ensure_event_visible(
+    pool: &PgPool,
+    events: &Events,
+    user: Option<&AuthUser>,
+    event_id: Uuid,
+) -> Result<(), Response> {
+    let Event { timeline_id, status, .. } =
+        find_event(events, event_id).await?.ok_or_else(|| StatusCode::NOT_FOUND.into_response())?;
+
+    let access = match timeline_id {
+        Some(timeline_id) => access(pool, &find(pool, timeline_id).await?, user).await?,
+        None if user.is_some() => Access::Edit,
+        None => Access::View,
+    };
+    let needed = if status == "published" { Access::View } else { Access::Edit };
+    if access < needed {
+        return Err(StatusCode::NOT_FOUND.into_response());
+    }
+    Ok(())
+}
+
+#[derive(Deserialize)]
+struct TimelineListQuery {
+    /// Only timelines the caller may add events to: owned or edited by
+    /// them, and not archived.
+    #[serde(default)]
+    editable: bool,
+    /// Only timelines in this organization.
+    organization_id: Option<Uuid>,
+}
+
+/// Public timelines plus the private ones the caller owns or is a member
+/// of, directly or through their organization.
+async fn list_timelines(
+    Reader(pool): Reader,
+    user: Option<AuthUser>,
+    Query(query): Query<TimelineListQuery>,
+) -> Result<Json<Vec<Timeline>>, StatusCode> {
+    let timelines = sqlx::query_as::<_, Timeline>(&format!(
+        r#"
+        SELECT t.* FROM timelines t
+        WHERE ($4::uuid IS NULL OR t.organization_id = $4)
+        AND CASE WHEN $3 THEN
+                t.archived_at IS NULL AND (t.owner_id = $1 OR $2 OR EXISTS (
+                    SELECT 1 FROM timeline_members m
+                    WHERE m.timeline_id = t.id AND m.user_id = $1 AND m.role = 'editor'
+                ) OR EXISTS (
+                    SELECT 1 FROM organization_members om
+                    WHERE om.organization_id = t.organization_id AND om.user_id = $1 AND om.role IN ('owner', 'admin')
+                ))
+            ELSE {}
+            END
+        ORDER BY t.updated_at DESC
+        "#,
+        VISIBLE_TIMELINE
+    ))
+    .bind(user.as_ref().map(|user| user.id))
+    .bind(user.as_ref().is_some_and(AuthUser::is_admin))
+    .bind(query.editable)
+    .bind(query.organization_id)
+    .fetch_all(&pool)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(timelines))
+}
+
+/// A timeline along with what the caller may do with it.
+#[derive(Serialize)]
+struct TimelineView {
+    #[serde(flatten)]
+    timeline: Timeline,
+    access: Access,
+}
+
+async fn get_timeline(
+    Reader(pool): Reader,
+    user: Option<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<TimelineView>, Response> {
+    let timeline = find_visible(&pool, id, user.as_ref()).await?;
+    let access = access(&pool, &timeline, user.as_ref()).await?;
+    Ok(Json(TimelineView { timeline, access }))
+}
+
+#[derive(Deserialize)]
+struct TimelineEventsQuery {
+    /// Only events at least this important.
+    min_importance: Option<i16>,
+}
+
+/// Also takes `field.<name>` filters on the timeline's custom fields; see
+/// `custom_fields::filters`.
+async fn get_timeline_events(
+    Reader(pool): Reader,
+    State(state): State<AppState>,
+    user: Option<AuthUser>,
+    Path(id): Path<Uuid>,
+    RawQuery(raw): RawQuery,
+    Query(query): Query<TimelineEventsQuery>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Result<Response, Response> {
+    // Signed-out visitors all see the same events, so theirs are cached.
+    if user.is_some() {
+        return timeline_events(&pool, &state.events, user, id, query, params)
+            .await
+            .map(|events| Json(events).into_response());
+    }
+    state
+        .cache
+        .read(cache::Read::List, &[cache::timeline_scope(id)], raw.as_deref().unwrap_or_default(), || {
+            timeline_events(&pool, &state.events, None, id, query, params)
+        })
+        .await
+}
+
+async fn timeline_events(
+    pool: &PgPool,
+    events: &Events,
+    user: Option<AuthUser>,
+    id: Uuid,
+    query: TimelineEventsQuery,
+    params: HashMap<String, String>,
+) -> Result<Vec<Event>, Response> {
+    let timeline = find_visible(pool, id, user.as_ref()).await?;
+    // Drafts and archived events are only shown to people who can edit them.
+    let editor = access(pool, &timeline, user.as_ref()).await? >= Access::Edit;
+    let fields = custom_fields::definitions(pool, Some(id)).await?;
+
+    let filter = TimelineFilter {
+        unpublished: editor,
+        min_importance: query.min_importance.unwrap_or(1),
+        fields: custom_fields::filters(&fields, &params).map_err(custom_fields::invalid)?,
+    };
+    events
+        .on_timeline(id, &filter)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())
+}
+
+async fn create_timeline(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Json(payload): Json<TimelineCreate>,
+) -> Result<Json<Timeline>, Response> {
+    if payload.title.trim().is_empty() {
+        return Err(StatusCode::UNPROCESSABLE_ENTITY.into_response());
+    }
+    payload.validate().map_err(validation_error)?;
+    if let Some(organization) = payload.organization_id {
+        organizations::ensure_role(&pool, organization, &user, organizations::Role::Member).await?;
+    }
+
+    let timeline = sqlx::query_as::<_, Timeline>(
+        r#"
+        INSERT INTO timelines (owner_id, title, description, is_private, organization_id, is_template)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING *
+        "#,
+    )
+    .bind(user.id)
+    .bind(payload.title.trim())
+    .bind(payload.description)
+    .bind(payload.is_private)
+    .bind(payload.organization_id)
+    .bind(payload.is_template)
+    .fetch_one(&pool)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
+
+    Ok(Json(timeline))
+}
+
+async fn update_timeline(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<TimelineUpdate>,
+) -> Result<Json<Timeline>, Response> {
+    payload.validate().map_err(validation_error)?;
+    if find_owned(&pool, id, &user).await?.archived_at.is_some() {
+        return Err(Archived.into_response());
+    }
+
+    let timeline = sqlx::query_as::<_, Timeline>(
+        r#"
+        UPDATE timelines SET
+            title = COALESCE($2, title),
+            description = COALESCE($3, description),
+            is_private = COALESCE($4, is_private),
+            is_template = COALESCE($5, is_template),
+            updated_at = NOW()
+        WHERE id = $1
+        RETURNING *
+        "#,
+    )
+    .bind(id)
+    .bind(payload.title)
+    .bind(payload.description)
+    .bind(payload.is_private)
+    .bind(payload.is_template)
+    .fetch_one(&pool)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
+
+    Ok(Json(timeline))
+}
+
+async fn delete_timeline(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, Response> {
+    if find_owned(&pool, id, &user).await?.archived_at.is_some() {
+        return Err(Archived.into_response());
+    }
+
+    sqlx::query("DELETE FROM timelines WHERE id = $1")
+        .bind(id)
+        .execute(&pool)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn archive_timeline(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Timeline>, Response> {
+    set_archived(&pool, id, &user, true).await.map(Json)
+}
+
+async fn unarchive_timeline(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Timeline>, Response> {
+    set_archived(&pool, id, &user, false).await.map(Json)
+}
+
+async fn set_archived(pool: &PgPool, id: Uuid, user: &AuthUser, archived: bool) -> Result<Timeline, Response> {
+    find_owned(pool, id, user).await?;
+
+    sqlx::query_as::<_, Timeline>(
+        r#"
+        UPDATE timelines
+        SET archived_at = CASE WHEN $2 THEN COALESCE(archived_at, NOW()) END, updated_at = NOW()
+        WHERE id = $1
+        RETURNING *
+        "#,
+    )
+    .bind(id)
+    .bind(archived)
+    .fetch_one(pool)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())
+}
+
+#[derive(Deserialize)]
+struct MoveTarget {
+    timeline_id: Uuid,
+}
+
+#[derive(Deserialize)]
+struct BulkMove {
+    event_ids: Vec<Uuid>,
+    timeline_id: Uuid,
+}
+
+/// Moves an event onto another timeline. The caller needs to be able to
+/// edit both the event and the target timeline.
+async fn move_event(
+    State(pool): State<PgPool>,
+    State(events): State<Events>,
+    user: Option<AuthUser>,
+    Path(id): Path<Uuid>,
+    Json(target): Json<MoveTarget>,
+) -> Result<Json<Event>, Response> {
+    let mut moved = move_to(&pool, &events, user.as_ref(), vec![id], target.timeline_id).await?;
+    Ok(Json(moved.remove(0)))
+}
+
+/// `move_event` for up to `MAX_MOVE` events: either all of them move or,
+/// if any is missing or not editable, none do.
+async fn move_events(
+    State(pool): State<PgPool>,
+    State(events): State<Events>,
+    user: Option<AuthUser>,
+    Json(payload): Json<BulkMove>,
+) -> Result<Json<Vec<Event>>, Response> {
+    if payload.event_ids.is_empty() || payload.event_ids.len() > MAX_MOVE {
+        let message = format!("move between 1 and {} events at a time", MAX_MOVE);
+        return Err((StatusCode::UNPROCESSABLE_ENTITY, message).into_response());
+    }
+    move_to(&pool, &events, user.as_ref(), payload.event_ids, payload.timeline_id).await.map(Json)
+}
+
+async fn move_to(
+    pool: &PgPool,
+    events: &Events,
+    user: Option<&AuthUser>,
+    mut event_ids: Vec<Uuid>,
+    timeline_id: Uuid,
+) -> Result<Vec<Event>, Response> {
+    ensure_timeline_writable(pool, user, Some(timeline_id)).await?;
+    event_ids.sort();
+    event_ids.dedup();
+    for id in &event_ids {
+        ensure_event_writable(pool, events, user, *id).await?;
+    }
+    events
+        .move_to(&event_ids, timeline_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?
+        .ok_or_else(|| StatusCode::NOT_FOUND.into_response())
+}
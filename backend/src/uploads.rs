@@ -0,0 +1,387 @@
+//! Images uploaded rather than linked to, kept in the database and served
+//! from `/uploads/:id`, plus `GET /api/me/usage`. Uploads count against a
+//! storage quota: the organization's when made for one, otherwise the
+//! uploader's own (see `Config`).
+
+use std::sync::Arc;
+
+use axum::{
+    body::{to_bytes, Body, Bytes},
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{delete, get, post},
+    Json, Router,
+};
+use chrono::NaiveDateTime;
+use image::ImageFormat;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::{
+    auth::AuthUser,
+    config::Config,
+    db::Reader,
+    images,
+    organizations::{self, Role},
+    AppState,
+};
+
+/// Uploads never change, so browsers may keep them for a year.
+const UPLOAD_MAX_AGE: u32 = 365 * 24 * 60 * 60;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/uploads", post(upload))
+        .route("/api/uploads/:id", delete(remove_upload))
+        .route("/api/me/usage", get(usage))
+        .route("/uploads/:id", get(serve_upload))
+}
+
+/// Whose quota an upload counts against.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Owner {
+    User(Uuid),
+    Organization(Uuid),
+}
+
+impl Owner {
+    fn quota(self, config: &Config) -> i64 {
+        match self {
+            Owner::User(_) => config.user_storage_quota,
+            Owner::Organization(_) => config.organization_storage_quota,
+        }
+    }
+
+    /// Bytes its uploads take up, leaving out those only shown on archived
+    /// timelines, which are frozen. Inside a transaction, the owner's row is locked
+    /// first so concurrent uploads cannot both squeeze under the quota.
+    async fn used(self, conn: &mut PgConnection, lock: bool) -> Result<i64, sqlx::Error> {
+        let (table, column, id) = match self {
+            Owner::User(id) => ("users", "user_id", id),
+            Owner::Organization(id) => ("organizations", "organization_id", id),
+        };
+        if lock {
+            sqlx::query(&format!("SELECT 1 FROM {} WHERE id = $1 FOR UPDATE", table))
+                .bind(id)
+                .execute(&mut *conn)
+                .await?;
+        }
+        sqlx::query_scalar::<_, i64>(&format!(
+            r#"
+            WITH shown AS (
+                SELECT e.image_url AS url, t.archived_at IS NOT NULL AS archived
+                FROM events e LEFT JOIN timelines t ON t.id = e.timeline_id
+                WHERE e.image_url LIKE '/uploads/%'
+                UNION ALL
+                SELECT i.url, t.archived_at IS NOT NULL
+                FROM event_images i JOIN events e ON e.id = i.event_id LEFT JOIN timelines t ON t.id = e.timeline_id
+                WHERE i.url LIKE '/uploads/%'
+            )
+            SELECT COALESCE(SUM(size), 0)::BIGINT FROM uploads
+            WHERE {} = $1
+                AND '/uploads/' || id NOT IN (SELECT url FROM shown GROUP BY url HAVING bool_and(archived))
+            "#,
+            column
+        ))
+        .bind(id)
+        .fetch_one(&mut *conn)
+        .await
+    }
+}
+
+/// The content type of a PNG, JPEG, GIF or WebP image, judged by its
+/// bytes rather than what the client claims.
+fn image_type(body: &[u8]) -> Option<&'static str> {
+    match image::guess_format(body).ok()? {
+        ImageFormat::Png => Some("image/png"),
+        ImageFormat::Jpeg => Some("image/jpeg"),
+        ImageFormat::Gif => Some("image/gif"),
+        ImageFormat::WebP => Some("image/webp"),
+        _ => None,
+    }
+}
+
+/// `bytes` for people, e.g. "4.5 MB".
+fn readable_size(bytes: i64) -> String {
+    const UNITS: [&str; 4] = ["bytes", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+#[derive(Deserialize)]
+struct UploadQuery {
+    /// Upload for this organization, using its quota.
+    organization_id: Option<Uuid>,
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+struct Upload {
+    id: Uuid,
+    content_type: String,
+    size: i64,
+    created_at: NaiveDateTime,
+}
+
+#[derive(Serialize)]
+struct Uploaded {
+    #[serde(flatten)]
+    upload: Upload,
+    /// Where the image is served; usable wherever an image URL is.
+    url: String,
+}
+
+/// Stores the request body, which must be an image, and answers with its
+/// URL. Too large a file is refused with 413, and one that would take its
+/// owner over quota with 402.
+async fn upload(
+    State(pool): State<PgPool>,
+    State(config): State<Arc<Config>>,
+    user: AuthUser,
+    Query(query): Query<UploadQuery>,
+    body: Body,
+) -> Result<(StatusCode, Json<Uploaded>), Response> {
+    let owner = match query.organization_id {
+        Some(organization) => {
+            organizations::ensure_role(&pool, organization, &user, Role::Member).await?;
+            Owner::Organization(organization)
+        }
+        None => Owner::User(user.id),
+    };
+    let body = to_bytes(body, config.max_upload_bytes).await.map_err(|_| {
+        let limit = readable_size(config.max_upload_bytes as i64);
+        (StatusCode::PAYLOAD_TOO_LARGE, format!("uploads can be at most {}", limit)).into_response()
+    })?;
+    let content_type = image_type(&body).ok_or_else(|| {
+        (StatusCode::UNSUPPORTED_MEDIA_TYPE, "only PNG, JPEG, GIF and WebP images can be uploaded").into_response()
+    })?;
+
+    let internal = |_| StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    let mut tx = pool.begin().await.map_err(internal)?;
+    let used = owner.used(&mut tx, true).await.map_err(internal)?;
+    let quota = owner.quota(&config);
+    if used + body.len() as i64 > quota {
+        let message = format!(
+            "storage is full: {} of {} used, and this file needs {}",
+            readable_size(used),
+            readable_size(quota),
+            readable_size(body.len() as i64)
+        );
+        return Err((StatusCode::PAYMENT_REQUIRED, message).into_response());
+    }
+    let (user_id, organization_id) = match owner {
+        Owner::User(id) => (Some(id), None),
+        Owner::Organization(id) => (None, Some(id)),
+    };
+    let upload = sqlx::query_as::<_, Upload>(
+        r#"
+        INSERT INTO uploads (user_id, organization_id, uploaded_by, content_type, size, body)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING id, content_type, size, created_at
+        "#,
+    )
+    .bind(user_id)
+    .bind(organization_id)
+    .bind(user.id)
+    .bind(content_type)
+    .bind(body.len() as i64)
+    .bind(body.as_ref())
+    .fetch_one(&mut *tx)
+    .await
+    .map_err(internal)?;
+    tx.commit().await.map_err(internal)?;
+
+    let url = format!("/uploads/{}", upload.id);
+    Ok((StatusCode::CREATED, Json(Uploaded { upload, url })))
+}
+
+/// Frees an upload's space. Events still showing it lose their image.
+/// Allowed to the uploader, the organization's admins and instance admins.
+async fn remove_upload(
+    State(pool): State<PgPool>,
+    user: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, Response> {
+    let internal = |_| StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    let (uploaded_by, user_id, organization_id) =
+        sqlx::query_as::<_, (Option<Uuid>, Option<Uuid>, Option<Uuid>)>(
+            "SELECT uploaded_by, user_id, organization_id FROM uploads WHERE id = $1",
+        )
+        .bind(id)
+        .fetch_optional(&pool)
+        .await
+        .map_err(internal)?
+        .ok_or_else(|| StatusCode::NOT_FOUND.into_response())?;
+    let own = uploaded_by == Some(user.id) || user_id == Some(user.id);
+    if !own && !user.is_admin() {
+        match organization_id {
+            Some(organization) => {
+                organizations::ensure_role(&pool, organization, &user, Role::Admin).await?;
+            }
+            None => return Err(StatusCode::NOT_FOUND.into_response()),
+        }
+    }
+
+    sqlx::query("DELETE FROM uploads WHERE id = $1")
+        .bind(id)
+        .execute(&pool)
+        .await
+        .map_err(internal)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Storage one owner has used out of its quota, in bytes.
+#[derive(Serialize)]
+struct Usage {
+    used: i64,
+    quota: i64,
+}
+
+#[derive(Serialize)]
+struct OrganizationUsage {
+    id: Uuid,
+    name: String,
+    #[serde(flatten)]
+    usage: Usage,
+}
+
+#[derive(Serialize)]
+struct UsageReport {
+    #[serde(flatten)]
+    usage: Usage,
+    /// Organizations the user belongs to, which have quotas of their own.
+    organizations: Vec<OrganizationUsage>,
+}
+
+async fn usage(
+    Reader(pool): Reader,
+    State(config): State<Arc<Config>>,
+    user: AuthUser,
+) -> Result<Json<UsageReport>, StatusCode> {
+    let internal = |_| StatusCode::INTERNAL_SERVER_ERROR;
+    let mut conn = pool.acquire().await.map_err(internal)?;
+    let used = Owner::User(user.id).used(&mut conn, false).await.map_err(internal)?;
+    let organizations = sqlx::query_as::<_, (Uuid, String, i64)>(
+        r#"
+        SELECT o.id, o.name, COALESCE((SELECT SUM(size) FROM uploads u WHERE u.organization_id = o.id), 0)::BIGINT
+        FROM organizations o
+        JOIN organization_members m ON m.organization_id = o.id
+        WHERE m.user_id = $1
+        ORDER BY o.name
+        "#,
+    )
+    .bind(user.id)
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(internal)?;
+
+    Ok(Json(UsageReport {
+        usage: Usage { used, quota: config.user_storage_quota },
+        organizations: organizations
+            .into_iter()
+            .map(|(id, name, used)| OrganizationUsage {
+                id,
+                name,
+                usage: Usage { used, quota: config.organization_storage_quota },
+            })
+            .collect(),
+    }))
+}
+
+async fn serve_upload(Reader(pool): Reader, Path(id): Path<Uuid>) -> Result<Response, StatusCode> {
+    let (content_type, body) =
+        sqlx::query_as::<_, (String, Vec<u8>)>("SELECT content_type, body FROM uploads WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&pool)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+            .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(images::image_response(content_type, Bytes::from(body), UPLOAD_MAX_AGE))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_raster_images_are_accepted() {
+        let mut png = std::io::Cursor::new(Vec::new());
+        image::DynamicImage::new_rgb8(1, 1).write_to(&mut png, image::ImageOutputFormat::Png).unwrap();
+        assert_eq!(image_type(png.get_ref()), Some("image/png"));
+        assert_eq!(image_type(b"<svg xmlns=\"http://www.w3.org/2000/svg\"/>"), None);
+        assert_eq!(image_type(b"%PDF-1.7"), None);
+    }
+
+    /// Runs against a scratch database:
+    /// `TEST_DATABASE_URL=postgres://... cargo test -- --ignored`.
+    #[tokio::test]
+    #[ignore = "needs TEST_DATABASE_URL"]
+    async fn uploads_on_archived_timelines_are_free() {
+        let url = std::env::var("TEST_DATABASE_URL").expect("TEST_DATABASE_URL must point at a scratch database");
+        let pool = PgPool::connect(&url).await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        let user = sqlx::query_scalar::<_, Uuid>(
+            "INSERT INTO users (username, email, password_hash) VALUES ($1, $1 || '@example.com', '') RETURNING id",
+        )
+        .bind(Uuid::new_v4().to_string())
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        let mut uploads = Vec::new();
+        for size in [100, 20, 3] {
+            let id = sqlx::query_scalar::<_, Uuid>(
+                "INSERT INTO uploads (user_id, content_type, size, body) VALUES ($1, 'image/png', $2, '') RETURNING id",
+            )
+            .bind(user)
+            .bind(size as i64)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+            uploads.push(format!("/uploads/{}", id));
+        }
+        // The first is only on an archived timeline, the second on that and
+        // a live one too; the third is on nothing.
+        for (archived, images) in [(true, &uploads[..2]), (false, &uploads[1..2])] {
+            let timeline = sqlx::query_scalar::<_, Uuid>(
+                "INSERT INTO timelines (owner_id, title, archived_at) \
+                 VALUES ($1, 'Quota', CASE WHEN $2 THEN NOW() END) RETURNING id",
+            )
+            .bind(user)
+            .bind(archived)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+            for image in images {
+                sqlx::query(
+                    "INSERT INTO events (title, start_date, timeline_id, image_url) VALUES ('Quota', NOW(), $1, $2)",
+                )
+                .bind(timeline)
+                .bind(image)
+                .execute(&pool)
+                .await
+                .unwrap();
+            }
+        }
+
+        let mut conn = pool.acquire().await.unwrap();
+        assert_eq!(Owner::User(user).used(&mut conn, false).await.unwrap(), 23);
+    }
+
+    #[test]
+    fn sizes_read_naturally() {
+        assert_eq!(readable_size(512), "512 bytes");
+        assert_eq!(readable_size(1536), "1.5 KB");
+        assert_eq!(readable_size(100 * 1024 * 1024), "100.0 MB");
+    }
+}
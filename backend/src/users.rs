@@ -0,0 +1,377 @@
+//! `/api/admin/users`: admins finding accounts and looking after them by
+//! disabling them, changing their role or forcing a password reset. A
+//! forced reset mails the user a link to `POST /api/auth/password-reset`.
+
+use std::collections::HashMap;
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post, put},
+    Json, Router,
+};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    auth::{self, AuthUser, TokenResponse},
+    db::Reader,
+    email_templates, moderation, preferences, AppState, PaginatedResponse,
+};
+
+/// Values of `users.role`.
+const ROLES: [&str; 2] = ["user", "admin"];
+/// Values of `status` in listings.
+const STATUSES: [&str; 3] = ["active", "disabled", "deleted"];
+/// How long a reset link works.
+const RESET_TTL_HOURS: i32 = 24;
+
+/// An account's `status`.
+const STATUS: &str = r#"
+    CASE WHEN u.deleted_at IS NOT NULL THEN 'deleted' WHEN u.banned_at IS NOT NULL THEN 'disabled' ELSE 'active' END
+"#;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/api/admin/users", get(list_users))
+        .route("/api/admin/users/:id", get(get_user))
+        .route("/api/admin/users/:id/disable", post(disable_user))
+        .route("/api/admin/users/:id/enable", post(enable_user))
+        .route("/api/admin/users/:id/role", put(set_role))
+        .route("/api/admin/users/:id/password-reset", post(force_password_reset))
+        .route("/api/auth/password-reset", post(reset_password))
+}
+
+#[derive(Deserialize)]
+struct UserQuery {
+    page: Option<i32>,
+    limit: Option<i32>,
+    /// Case-insensitive substring of the username or email.
+    search: Option<String>,
+    /// One of `ROLES`.
+    role: Option<String>,
+    /// One of `STATUSES`.
+    status: Option<String>,
+}
+
+/// An account as admins see it, with how much its owner has contributed.
+#[derive(Serialize, sqlx::FromRow)]
+struct ManagedUser {
+    id: Uuid,
+    username: String,
+    email: String,
+    role: String,
+    /// One of `STATUSES`.
+    status: String,
+    /// False for accounts that only log in through OAuth, and after a
+    /// forced reset until the user picks a new password.
+    has_password: bool,
+    created_at: NaiveDateTime,
+    timelines: i64,
+    /// Events on the timelines they own.
+    events: i64,
+    comments: i64,
+}
+
+/// Users with their contributions; filters on `WHERE` conditions added
+/// after it.
+fn select_users() -> String {
+    format!(
+        r#"
+        SELECT u.id, u.username, u.email, u.role, {} AS status, u.password_hash IS NOT NULL AS has_password,
+            u.created_at,
+            (SELECT COUNT(*) FROM timelines t WHERE t.owner_id = u.id) AS timelines,
+            (SELECT COUNT(*) FROM events e JOIN timelines t ON t.id = e.timeline_id WHERE t.owner_id = u.id) AS events,
+            (SELECT COUNT(*) FROM comments c WHERE c.author_id = u.id) AS comments
+        FROM users u
+        "#,
+        STATUS
+    )
+}
+
+/// Matches `UserQuery`'s filters, bound as `$1` to `$3`.
+fn filters() -> String {
+    format!(
+        r#"
+        WHERE ($1::text IS NULL OR u.username ILIKE $1 OR u.email ILIKE $1)
+        AND ($2::text IS NULL OR u.role = $2)
+        AND ($3::text IS NULL OR {} = $3)
+        "#,
+        STATUS
+    )
+}
+
+/// A page of accounts, newest first. Admins only.
+async fn list_users(
+    Reader(pool): Reader,
+    user: AuthUser,
+    Query(query): Query<UserQuery>,
+) -> Result<Json<PaginatedResponse<ManagedUser>>, StatusCode> {
+    user.require_admin()?;
+    let page = query.page.unwrap_or(1).max(1);
+    let limit = query.limit.unwrap_or(50).clamp(1, 200);
+    let role = query.role.filter(|role| !role.is_empty());
+    let status = query.status.filter(|status| !status.is_empty());
+    if role.as_deref().is_some_and(|role| !ROLES.contains(&role))
+        || status.as_deref().is_some_and(|status| !STATUSES.contains(&status))
+    {
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+    let pattern = query
+        .search
+        .map(|search| search.trim().to_string())
+        .filter(|search| !search.is_empty())
+        .map(|search| format!("%{}%", search));
+
+    let total = sqlx::query_scalar::<_, i64>(&format!("SELECT COUNT(*) FROM users u {}", filters()))
+        .bind(&pattern)
+        .bind(&role)
+        .bind(&status)
+        .fetch_one(&pool)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let users = sqlx::query_as::<_, ManagedUser>(&format!(
+        "{} {} ORDER BY u.created_at DESC LIMIT $4 OFFSET $5",
+        select_users(),
+        filters()
+    ))
+    .bind(&pattern)
+    .bind(&role)
+    .bind(&status)
+    .bind(limit as i64)
+    .bind(((page - 1) * limit) as i64)
+    .fetch_all(&pool)
+    .await
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(PaginatedResponse {
+        data: users,
+        total,
+        page,
+        limit,
+        pages: (total as f64 / limit as f64).ceil() as i32,
+    }))
+}
+
+async fn find(pool: &PgPool, id: Uuid) -> Result<ManagedUser, StatusCode> {
+    sqlx::query_as::<_, ManagedUser>(&format!("{} WHERE u.id = $1", select_users()))
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+async fn get_user(Reader(pool): Reader, user: AuthUser, Path(id): Path<Uuid>) -> Result<Json<ManagedUser>, StatusCode> {
+    user.require_admin()?;
+    find(&pool, id).await.map(Json)
+}
+
+/// Refuses actions admins must not take on their own account.
+fn not_self(user: &AuthUser, id: Uuid, message: &'static str) -> Result<(), (StatusCode, &'static str)> {
+    match user.id == id {
+        true => Err((StatusCode::CONFLICT, message)),
+        false => Ok(()),
+    }
+}
+
+/// Disables an account the same way a moderation ban does: no more logins,
+/// and its sessions end.
+async fn disable_user(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ManagedUser>, Response> {
+    user.require_admin().map_err(IntoResponse::into_response)?;
+    not_self(&user, id, "admins cannot disable their own account").map_err(IntoResponse::into_response)?;
+    let pool = state.db.writer();
+    find(pool, id).await.map_err(IntoResponse::into_response)?;
+    moderation::ban(pool, id).await?;
+    find(pool, id).await.map(Json).map_err(IntoResponse::into_response)
+}
+
+async fn enable_user(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ManagedUser>, StatusCode> {
+    user.require_admin()?;
+    let pool = state.db.writer();
+    sqlx::query("UPDATE users SET banned_at = NULL WHERE id = $1")
+        .bind(id)
+        .execute(pool)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    find(pool, id).await.map(Json)
+}
+
+#[derive(Deserialize)]
+struct RoleChange {
+    /// One of `ROLES`.
+    role: String,
+}
+
+/// Changes an account's role. The user's sessions pick it up when their
+/// access token is next renewed.
+async fn set_role(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<RoleChange>,
+) -> Result<Json<ManagedUser>, Response> {
+    user.require_admin().map_err(IntoResponse::into_response)?;
+    not_self(&user, id, "admins cannot change their own role").map_err(IntoResponse::into_response)?;
+    if !ROLES.contains(&payload.role.as_str()) {
+        return Err(StatusCode::UNPROCESSABLE_ENTITY.into_response());
+    }
+    let pool = state.db.writer();
+    let updated = sqlx::query("UPDATE users SET role = $2 WHERE id = $1 AND deleted_at IS NULL")
+        .bind(id)
+        .bind(&payload.role)
+        .execute(pool)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())?
+        .rows_affected();
+    if updated == 0 {
+        return Err(StatusCode::NOT_FOUND.into_response());
+    }
+    find(pool, id).await.map(Json).map_err(IntoResponse::into_response)
+}
+
+fn new_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn token_hash(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+/// Makes the user choose a new password: the current one stops working,
+/// their sessions end, and they are mailed a link to pick another. Links
+/// sent before stop working too.
+async fn force_password_reset(
+    State(state): State<AppState>,
+    user: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, Response> {
+    user.require_admin().map_err(IntoResponse::into_response)?;
+    let pool = state.db.writer();
+    let internal = |_| StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    let (username, email) = sqlx::query_as::<_, (String, String)>(
+        "SELECT username, email FROM users WHERE id = $1 AND deleted_at IS NULL",
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+    .map_err(internal)?
+    .ok_or_else(|| StatusCode::NOT_FOUND.into_response())?;
+    let locale = preferences::find(pool, id).await.map_err(internal)?.locale;
+
+    let token = new_token();
+    let mut tx = pool.begin().await.map_err(internal)?;
+    sqlx::query("UPDATE users SET password_hash = NULL WHERE id = $1")
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(internal)?;
+    sqlx::query("UPDATE sessions SET revoked_at = NOW() WHERE user_id = $1 AND revoked_at IS NULL")
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(internal)?;
+    sqlx::query("DELETE FROM password_resets WHERE user_id = $1 AND used_at IS NULL")
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .map_err(internal)?;
+    sqlx::query(
+        r#"
+        INSERT INTO password_resets (user_id, token_hash, created_by, expires_at)
+        VALUES ($1, $2, $3, NOW() + make_interval(hours => $4))
+        "#,
+    )
+    .bind(id)
+    .bind(token_hash(&token))
+    .bind(user.id)
+    .bind(RESET_TTL_HOURS)
+    .execute(&mut *tx)
+    .await
+    .map_err(internal)?;
+    // Queued with the reset, so the user is never locked out without a link.
+    let values = HashMap::from([
+        ("username".to_string(), username),
+        ("reset_url".to_string(), format!("{}/reset-password?token={}", state.config.public_url, token)),
+    ]);
+    email_templates::queue(&state, &mut tx, "password_reset", &locale, &email, &values).await?;
+    tx.commit().await.map_err(internal)?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Deserialize)]
+struct PasswordReset {
+    token: String,
+    password: String,
+}
+
+/// Sets the password a reset link was sent for, and logs the user in.
+async fn reset_password(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(payload): Json<PasswordReset>,
+) -> Result<Json<TokenResponse>, StatusCode> {
+    if payload.password.len() < 8 {
+        return Err(StatusCode::UNPROCESSABLE_ENTITY);
+    }
+    let password_hash = auth::hash_password(&payload.password)?;
+    let internal = |_| StatusCode::INTERNAL_SERVER_ERROR;
+    let mut tx = state.db.writer().begin().await.map_err(internal)?;
+
+    let (id, role, banned) = sqlx::query_as::<_, (Uuid, String, bool)>(
+        r#"
+        UPDATE password_resets r SET used_at = NOW()
+        FROM users u
+        WHERE r.token_hash = $1 AND r.used_at IS NULL AND r.expires_at > NOW()
+            AND u.id = r.user_id AND u.deleted_at IS NULL
+        RETURNING u.id, u.role, u.banned_at IS NOT NULL
+        "#,
+    )
+    .bind(token_hash(&payload.token))
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(internal)?
+    .ok_or(StatusCode::NOT_FOUND)?;
+    if banned {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    sqlx::query("UPDATE users SET password_hash = $2 WHERE id = $1")
+        .bind(id)
+        .bind(password_hash)
+        .execute(&mut *tx)
+        .await
+        .map_err(internal)?;
+    tx.commit().await.map_err(internal)?;
+
+    auth::start_session(&state, id, &role, &headers).await.map(Json)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reset_tokens_are_random_and_stored_hashed() {
+        let token = new_token();
+        assert_eq!(token.len(), 64);
+        assert_ne!(token, new_token());
+        assert_eq!(token_hash(&token).len(), 64);
+        assert_ne!(token_hash(&token), token);
+    }
+}
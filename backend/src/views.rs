@@ -0,0 +1,191 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use axum::{extract::Query, http::StatusCode, routing::get, Json, Router};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{db::Reader, timelines, AppState, Event};
+
+/// Longest trending window, in days.
+const MAX_WINDOW_DAYS: i32 = 90;
+/// How many views a like is worth when ranking trending events.
+const LIKE_WEIGHT: i64 = 10;
+
+pub fn routes() -> Router<AppState> {
+    Router::new().route("/api/events/trending", get(get_trending))
+}
+
+/// Views counted since the last flush, shared through `AppState`. Detail
+/// pages only bump a counter here; the `event_views` job writes the totals.
+#[derive(Clone, Default)]
+pub struct ViewCounter {
+    pending: Arc<Mutex<HashMap<Uuid, i64>>>,
+}
+
+impl ViewCounter {
+    pub fn record(&self, event_id: Uuid) {
+        if let Ok(mut pending) = self.pending.lock() {
+            *pending.entry(event_id).or_default() += 1;
+        }
+    }
+
+    fn take(&self) -> HashMap<Uuid, i64> {
+        self.pending.lock().map(|mut pending| std::mem::take(&mut *pending)).unwrap_or_default()
+    }
+
+    /// Puts counts back after a failed flush so the next one retries them.
+    fn restore(&self, counts: HashMap<Uuid, i64>) {
+        if let Ok(mut pending) = self.pending.lock() {
+            for (event_id, views) in counts {
+                *pending.entry(event_id).or_default() += views;
+            }
+        }
+    }
+}
+
+/// Adds the pending views to the events and today's bucket in one
+/// statement each, however many views were counted.
+pub async fn flush(pool: &PgPool, counter: &ViewCounter) -> Result<u64, sqlx::Error> {
+    let counts = counter.take();
+    if counts.is_empty() {
+        return Ok(0);
+    }
+    match write(pool, &counts).await {
+        Ok(rows) => Ok(rows),
+        Err(err) => {
+            counter.restore(counts);
+            Err(err)
+        }
+    }
+}
+
+async fn write(pool: &PgPool, counts: &HashMap<Uuid, i64>) -> Result<u64, sqlx::Error> {
+    let (ids, views): (Vec<Uuid>, Vec<i64>) = counts.iter().map(|(id, views)| (*id, *views)).unzip();
+    let mut tx = pool.begin().await?;
+
+    // Events deleted since they were viewed simply drop out of the join.
+    let updated = sqlx::query(
+        r#"
+        UPDATE events e SET views = e.views + v.views
+        FROM UNNEST($1::uuid[], $2::bigint[]) AS v(id, views)
+        WHERE e.id = v.id
+        "#,
+    )
+    .bind(&ids)
+    .bind(&views)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+    sqlx::query(
+        r#"
+        INSERT INTO event_views (event_id, day, views)
+        SELECT v.id, CURRENT_DATE, v.views
+        FROM UNNEST($1::uuid[], $2::bigint[]) AS v(id, views)
+        JOIN events e ON e.id = v.id
+        ON CONFLICT (event_id, day) DO UPDATE SET views = event_views.views + EXCLUDED.views
+        "#,
+    )
+    .bind(&ids)
+    .bind(&views)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(updated)
+}
+
+#[derive(Deserialize)]
+struct TrendingQuery {
+    /// A number of days such as `7d`; defaults to a week.
+    window: Option<String>,
+    limit: Option<i64>,
+}
+
+#[derive(Serialize, sqlx::FromRow)]
+struct TrendingEvent {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    event: Event,
+    /// Views within the window; `views` on the event is the all-time total.
+    recent_views: i64,
+    /// Likes given within the window.
+    recent_likes: i64,
+}
+
+/// Parses a window like `7d` into days.
+fn window_days(window: &str) -> Option<i32> {
+    let days = window.strip_suffix('d')?.parse().ok()?;
+    (1..=MAX_WINDOW_DAYS).contains(&days).then_some(days)
+}
+
+/// The public events with the most views and likes within the window,
+/// today included; each like counts as `LIKE_WEIGHT` views.
+async fn get_trending(
+    Reader(pool): Reader,
+    Query(query): Query<TrendingQuery>,
+) -> Result<Json<Vec<TrendingEvent>>, StatusCode> {
+    let days = match query.window.as_deref() {
+        Some(window) => window_days(window).ok_or(StatusCode::BAD_REQUEST)?,
+        None => 7,
+    };
+    let limit = query.limit.unwrap_or(6).clamp(1, 50);
+
+    let sql = format!(
+        r#"
+        WITH activity AS (
+            SELECT event_id, views, 0 AS likes FROM event_views WHERE day > CURRENT_DATE - $1
+            UNION ALL
+            SELECT event_id, 0, 1 FROM event_likes WHERE created_at::date > CURRENT_DATE - $1
+        )
+        SELECT e.*, SUM(a.views)::bigint AS recent_views, SUM(a.likes)::bigint AS recent_likes
+        FROM activity a
+        JOIN events e ON e.id = a.event_id
+        WHERE {}
+        GROUP BY e.id
+        ORDER BY SUM(a.views) + $3 * SUM(a.likes) DESC, e.id
+        LIMIT $2
+        "#,
+        timelines::PUBLIC_EVENT
+    );
+    let events = sqlx::query_as::<_, TrendingEvent>(&sql)
+        .bind(days)
+        .bind(limit)
+        .bind(LIKE_WEIGHT)
+        .fetch_all(&pool)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(events))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_windows_in_days() {
+        assert_eq!(window_days("7d"), Some(7));
+        assert_eq!(window_days("90d"), Some(90));
+        assert_eq!(window_days("0d"), None);
+        assert_eq!(window_days("91d"), None);
+        assert_eq!(window_days("7"), None);
+        assert_eq!(window_days("1w"), None);
+    }
+
+    #[test]
+    fn restores_counts_after_a_failed_flush() {
+        let counter = ViewCounter::default();
+        let id = Uuid::new_v4();
+        counter.record(id);
+        counter.record(id);
+
+        let taken = counter.take();
+        assert_eq!(taken[&id], 2);
+        counter.record(id);
+        counter.restore(taken);
+        assert_eq!(counter.take()[&id], 3);
+        assert!(counter.take().is_empty());
+    }
+}
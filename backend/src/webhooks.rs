@@ -0,0 +1,39 @@
+use serde::Serialize;
+
+use crate::config::Config;
+use crate::flags::{Flag, Flags};
+
+#[derive(Serialize)]
+struct Notification<'a, T> {
+    #[serde(rename = "type")]
+    kind: &'a str,
+    sent_at: chrono::DateTime<chrono::Utc>,
+    data: T,
+}
+
+/// POSTs `{"type": kind, "sent_at": .., "data": data}` to `WEBHOOK_URL`.
+/// Without one configured, or with the `webhooks` flag off, the
+/// notification is only logged.
+pub async fn notify<T: Serialize>(config: &Config, flags: &Flags, kind: &str, data: T) -> Result<(), reqwest::Error> {
+    if !flags.enabled(Flag::Webhooks) {
+        tracing::info!(kind, "webhooks are switched off; notification not sent");
+        return Ok(());
+    }
+    let Some(url) = &config.webhook_url else {
+        tracing::info!(kind, "WEBHOOK_URL not set; notification not sent");
+        return Ok(());
+    };
+
+    reqwest::Client::new()
+        .post(url)
+        .json(&Notification {
+            kind,
+            sent_at: chrono::Utc::now(),
+            data,
+        })
+        .timeout(std::time::Duration::from_secs(10))
+        .send()
+        .await?
+        .error_for_status()
+        .map(|_| ())
+}
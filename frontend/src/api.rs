@@ -0,0 +1,395 @@
+//! Typed wrappers around API calls. Failures come back as a `FetchError`
+//! rather than a panic, so a page can show what went wrong and offer a
+//! retry.
+//!
+//! Pages build a `Request` and send it with the `ApiClient` that
+//! `ApiProvider` offers as context: the browser's `fetch`, or in tests a
+//! scripted client. Nothing else talks to `gloo_net`. Builds
+//! with the `mock-api` feature answer from the bundled demo dataset
+//! instead of the network.
+
+// Those builds never reach the network, so the fetch client and what
+// only it reads go unused there.
+#![cfg_attr(feature = "mock-api", allow(dead_code))]
+
+use std::cell::Cell;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use wasm_bindgen::JsValue;
+
+use crate::auth;
+
+/// Tries made for a GET before giving up, including the first.
+const MAX_ATTEMPTS: u32 = 4;
+/// Wait before the first retry; it doubles for each one after.
+const BASE_DELAY_MS: f64 = 300.0;
+/// Session tokens closer than this to expiring are renewed before use.
+const REFRESH_MARGIN_SECONDS: f64 = 60.0;
+
+thread_local! {
+    /// Set while a refresh is in flight, so concurrent requests wait for it
+    /// instead of spending the same refresh token twice.
+    static REFRESHING: Cell<bool> = const { Cell::new(false) };
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Method {
+    Get,
+    Post,
+    Put,
+    Patch,
+    Delete,
+}
+
+/// A request to the API, not yet sent.
+#[derive(Clone, Debug)]
+pub struct Request {
+    pub method: Method,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<Body>,
+}
+
+#[derive(Clone, Debug)]
+pub enum Body {
+    /// Serialized JSON.
+    Json(String),
+    /// Sent as it is, e.g. a `File`.
+    Raw(JsValue),
+}
+
+impl Request {
+    fn new(method: Method, url: &str) -> Self {
+        Self { method, url: url.to_string(), headers: Vec::new(), body: None }
+    }
+
+    pub fn get(url: &str) -> Self {
+        Self::new(Method::Get, url)
+    }
+
+    pub fn post(url: &str) -> Self {
+        Self::new(Method::Post, url)
+    }
+
+    pub fn put(url: &str) -> Self {
+        Self::new(Method::Put, url)
+    }
+
+    pub fn patch(url: &str) -> Self {
+        Self::new(Method::Patch, url)
+    }
+
+    pub fn delete(url: &str) -> Self {
+        Self::new(Method::Delete, url)
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    fn json(self, body: &impl Serialize) -> Result<Self, FetchError> {
+        let json = serde_json::to_string(body).map_err(|_| FetchError::Decode)?;
+        Ok(Self { body: Some(Body::Json(json)), ..self })
+    }
+}
+
+/// A response's status and body, whatever the status.
+#[derive(Clone, Debug)]
+pub struct Response {
+    pub status: u16,
+    pub body: String,
+}
+
+impl Response {
+    fn ok(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
+/// A response on its way.
+pub type Pending = Pin<Box<dyn Future<Output = Result<Response, FetchError>>>>;
+
+/// Sends requests. Error statuses are responses; `Err` means none came
+/// back at all.
+pub trait HttpClient {
+    fn fetch(&self, request: Request) -> Pending;
+}
+
+/// The browser's `fetch`.
+#[derive(Default)]
+pub struct Fetch;
+
+#[cfg(not(feature = "mock-api"))]
+type DefaultClient = Fetch;
+#[cfg(feature = "mock-api")]
+type DefaultClient = crate::demo::DemoClient;
+
+impl HttpClient for Fetch {
+    fn fetch(&self, request: Request) -> Pending {
+        use gloo_net::http::Request as Outgoing;
+
+        Box::pin(async move {
+            let mut builder = match request.method {
+                Method::Get => Outgoing::get(&request.url),
+                Method::Post => Outgoing::post(&request.url),
+                Method::Put => Outgoing::put(&request.url),
+                Method::Patch => Outgoing::patch(&request.url),
+                Method::Delete => Outgoing::delete(&request.url),
+            };
+            for (name, value) in &request.headers {
+                builder = builder.header(name, value);
+            }
+            let outgoing = match request.body {
+                Some(Body::Json(json)) => builder.header("Content-Type", "application/json").body(json),
+                Some(Body::Raw(raw)) => builder.body(raw),
+                None => builder.build(),
+            };
+            let outgoing = outgoing.map_err(|_| FetchError::Network)?;
+            let response = outgoing.send().await.map_err(|_| FetchError::Network)?;
+            let status = response.status();
+            let body = response.text().await.map_err(|_| FetchError::Network)?;
+            Ok(Response { status, body })
+        })
+    }
+}
+
+/// An `HttpClient` to share, as `ApiProvider` does through context;
+/// components get it with `use_api`.
+#[derive(Clone)]
+pub struct ApiClient(pub Rc<dyn HttpClient>);
+
+impl Default for ApiClient {
+    fn default() -> Self {
+        Self(Rc::new(DefaultClient::default()))
+    }
+}
+
+impl PartialEq for ApiClient {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub enum FetchError {
+    /// No response at all: offline, DNS failure, aborted request.
+    Network,
+    /// The API answered with an error status. `message` is the error the
+    /// body describes, if any.
+    Status { status: u16, message: Option<String> },
+    /// The response body was not what the page expected.
+    Decode,
+}
+
+impl FetchError {
+    /// Whether the same request may well succeed a moment later.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            FetchError::Network => true,
+            FetchError::Status { status, .. } => matches!(status, 408 | 429) || *status >= 500,
+            FetchError::Decode => false,
+        }
+    }
+
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            FetchError::Status { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FetchError::Network => f.write_str("Could not reach the server. Check your connection."),
+            FetchError::Status { message: Some(message), .. } => f.write_str(message),
+            FetchError::Status { status: 401, .. } => f.write_str("You need to log in to see this."),
+            FetchError::Status { status: 403, .. } => f.write_str("You don't have access to this."),
+            FetchError::Status { status: 404, .. } => f.write_str("Not found."),
+            FetchError::Status { status, .. } if *status >= 500 => {
+                f.write_str("Something went wrong on our side. Please try again.")
+            }
+            FetchError::Status { status, .. } => write!(f, "Request failed ({})", status),
+            FetchError::Decode => f.write_str("The server sent an unexpected response."),
+        }
+    }
+}
+
+impl ApiClient {
+    /// GETs `url` as the logged-in user and decodes the JSON body. Transient
+    /// failures are retried with exponential backoff before giving up.
+    pub async fn get<T: DeserializeOwned>(&self, url: &str) -> Result<T, FetchError> {
+        self.refresh_session().await;
+        let mut attempt = 0;
+        loop {
+            let result = self.0.fetch(authorized(Request::get(url))).await.and_then(decode);
+            match result {
+                Err(error) if error.is_transient() && attempt + 1 < MAX_ATTEMPTS => {
+                    sleep(backoff(attempt)).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Sends `request` as the logged-in user and decodes the JSON body; use
+    /// `IgnoredAny` when it doesn't matter. Not retried, since the request
+    /// may not be safe to repeat.
+    pub async fn send<T: DeserializeOwned>(&self, request: Request) -> Result<T, FetchError> {
+        self.refresh_session().await;
+        self.0.fetch(authorized(request)).await.and_then(decode)
+    }
+
+    /// Like `send`, with `body` as the JSON request body.
+    pub async fn send_json<T: DeserializeOwned>(
+        &self,
+        request: Request,
+        body: &impl Serialize,
+    ) -> Result<T, FetchError> {
+        self.refresh_session().await;
+        let request = authorized(request).json(body)?;
+        self.0.fetch(request).await.and_then(decode)
+    }
+
+    /// Like `send`, with `body`, such as a `File`, as the raw request body.
+    pub async fn send_body<T: DeserializeOwned>(
+        &self,
+        request: Request,
+        body: impl Into<JsValue>,
+    ) -> Result<T, FetchError> {
+        self.refresh_session().await;
+        let request = Request { body: Some(Body::Raw(body.into())), ..authorized(request) };
+        self.0.fetch(request).await.and_then(decode)
+    }
+
+    /// Renews the session token if it is about to expire. The refresh token
+    /// changes each time; if another tab already swapped it, that tab's new
+    /// one is in storage and is used instead. A session that was revoked or
+    /// has expired is logged out.
+    async fn refresh_session(&self) {
+        while REFRESHING.with(Cell::get) {
+            sleep(50).await;
+        }
+        let Some(refresh_token) = auth::refresh_token() else {
+            return;
+        };
+        if !auth::expires_within(REFRESH_MARGIN_SECONDS) {
+            return;
+        }
+
+        REFRESHING.with(|refreshing| refreshing.set(true));
+        let body = serde_json::json!({ "refresh_token": refresh_token });
+        let response = match Request::post("/api/auth/refresh").json(&body) {
+            Ok(request) => self.0.fetch(request).await.ok(),
+            Err(_) => None,
+        };
+        match response {
+            Some(response) if response.ok() => {
+                if let Ok(tokens) = serde_json::from_str::<Tokens>(&response.body) {
+                    auth::set_tokens(&tokens.token, &tokens.refresh_token);
+                }
+            }
+            Some(response) if response.status == 401 && auth::refresh_token().as_ref() == Some(&refresh_token) => {
+                auth::clear_token();
+            }
+            // Offline, or another tab got there first: requests go out with
+            // whatever token storage holds.
+            _ => {}
+        }
+        REFRESHING.with(|refreshing| refreshing.set(false));
+    }
+}
+
+/// Where an `<img>` should load `url` from: remote images go through the
+/// API's image proxy, so the viewer's browser only talks to this site.
+/// A demo build has no proxy and loads them directly.
+pub fn image_src(url: &str) -> String {
+    if !cfg!(feature = "mock-api") && (url.starts_with("http://") || url.starts_with("https://")) {
+        format!("/api/proxy/image?url={}", js_sys::encode_uri_component(url))
+    } else {
+        url.to_string()
+    }
+}
+
+/// The event's image framed as its cover and `width` pixels wide, for
+/// cards and previews.
+#[cfg(not(feature = "mock-api"))]
+pub fn thumbnail_src(event_id: &str, width: u32) -> String {
+    format!("/api/events/{}/thumbnail?width={}", event_id, width)
+}
+
+/// A demo build has nothing to frame thumbnails with, so they are the
+/// event's image as it is.
+#[cfg(feature = "mock-api")]
+pub fn thumbnail_src(event_id: &str, _width: u32) -> String {
+    crate::demo::image_url(event_id).map(|url| image_src(&url)).unwrap_or_default()
+}
+
+/// The WebSocket address of the API's `path`, as the logged-in user. The
+/// token goes in the query, since browsers can't set headers on a
+/// WebSocket.
+pub fn socket_url(path: &str) -> String {
+    let location = gloo_utils::window().location();
+    let scheme = if location.protocol().ok().as_deref() == Some("https:") { "wss" } else { "ws" };
+    let host = location.host().unwrap_or_default();
+    let token = auth::token().unwrap_or_default();
+    format!("{}://{}{}?token={}", scheme, host, path, js_sys::encode_uri_component(&token))
+}
+
+#[derive(Deserialize)]
+struct Tokens {
+    token: String,
+    refresh_token: String,
+}
+
+fn authorized(request: Request) -> Request {
+    match auth::bearer() {
+        Some(bearer) => request.header("Authorization", &bearer),
+        None => request,
+    }
+}
+
+fn decode<T: DeserializeOwned>(response: Response) -> Result<T, FetchError> {
+    #[derive(Deserialize)]
+    struct ApiError {
+        error: String,
+    }
+
+    let ok = response.ok();
+    let Response { status, body: text } = response;
+    if !ok {
+        // Either `{"error": ...}` or, from simpler handlers, plain text.
+        let message = match serde_json::from_str::<ApiError>(&text) {
+            Ok(body) => Some(body.error),
+            Err(_) => Some(text.trim().to_string())
+                .filter(|text| !text.is_empty() && !text.starts_with(['{', '<'])),
+        };
+        return Err(FetchError::Status { status, message });
+    }
+    // An empty body (204 No Content) decodes as `null`.
+    let text = if text.is_empty() { "null" } else { &text };
+    serde_json::from_str(text).map_err(|_| FetchError::Decode)
+}
+
+/// Milliseconds to wait before retry number `attempt + 1`: exponential,
+/// with jitter so clients that failed together don't retry together.
+fn backoff(attempt: u32) -> i32 {
+    let delay = BASE_DELAY_MS * 2f64.powi(attempt as i32);
+    (delay * (0.5 + js_sys::Math::random() / 2.0)) as i32
+}
+
+async fn sleep(ms: i32) {
+    let promise = js_sys::Promise::new(&mut |resolve, _| {
+        gloo_utils::window()
+            .set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, ms)
+            .ok();
+    });
+    wasm_bindgen_futures::JsFuture::from(promise).await.ok();
+}
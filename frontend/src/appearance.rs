@@ -0,0 +1,44 @@
+//! How an event looks: its colour, from the category unless an editor
+//! picked one, and an optional icon.
+
+/// Category colours; the server-side SVG export uses the same palette.
+const PALETTE: [&str; 8] = ["#3b82f6", "#ef4444", "#10b981", "#f59e0b", "#8b5cf6", "#ec4899", "#14b8a6", "#f97316"];
+const UNCATEGORIZED_COLOR: &str = "#6b7280";
+
+/// Icons events may use, as `(name, glyph, label)`. Names match the API's
+/// `appearance::ICONS`.
+pub const ICONS: [(&str, &str, &str); 12] = [
+    ("star", "★", "Star"),
+    ("flag", "⚑", "Flag"),
+    ("crown", "♛", "Crown"),
+    ("swords", "⚔", "Swords"),
+    ("book", "📖", "Book"),
+    ("flask", "⚗", "Flask"),
+    ("building", "🏛", "Building"),
+    ("music", "♫", "Music"),
+    ("palette", "🎨", "Palette"),
+    ("globe", "🌍", "Globe"),
+    ("heart", "♥", "Heart"),
+    ("rocket", "🚀", "Rocket"),
+];
+
+fn category_color(category: Option<&str>) -> &'static str {
+    match category {
+        Some(category) => {
+            let hash = category.bytes().fold(0u32, |hash, b| hash.wrapping_mul(31).wrapping_add(b as u32));
+            PALETTE[hash as usize % PALETTE.len()]
+        }
+        None => UNCATEGORIZED_COLOR,
+    }
+}
+
+/// The event's own colour, or its category's.
+pub fn event_color<'a>(color: Option<&'a str>, category: Option<&str>) -> &'a str {
+    color.unwrap_or_else(|| category_color(category))
+}
+
+/// Glyph of a named icon; unknown names show nothing.
+pub fn icon_glyph(icon: Option<&str>) -> Option<&'static str> {
+    let icon = icon?;
+    ICONS.iter().find(|(name, _, _)| *name == icon).map(|(_, glyph, _)| *glyph)
+}
@@ -0,0 +1,108 @@
+use web_sys::Storage;
+
+const TOKEN_KEY: &str = "token";
+const REFRESH_TOKEN_KEY: &str = "refresh_token";
+/// The admin's own tokens, put aside while they impersonate someone.
+const ADMIN_TOKEN_KEY: &str = "admin_token";
+const ADMIN_REFRESH_TOKEN_KEY: &str = "admin_refresh_token";
+/// Who the admin is impersonating, as JSON.
+const IMPERSONATING_KEY: &str = "impersonating";
+
+fn storage() -> Option<Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+/// The session token saved at login, if any.
+pub fn token() -> Option<String> {
+    storage()?.get_item(TOKEN_KEY).ok()?
+}
+
+/// The token that gets a new session token once this one expires.
+pub fn refresh_token() -> Option<String> {
+    storage()?.get_item(REFRESH_TOKEN_KEY).ok()?
+}
+
+pub fn set_tokens(token: &str, refresh_token: &str) {
+    if let Some(storage) = storage() {
+        storage.set_item(TOKEN_KEY, token).ok();
+        storage.set_item(REFRESH_TOKEN_KEY, refresh_token).ok();
+    }
+}
+
+pub fn clear_token() {
+    if let Some(storage) = storage() {
+        for key in [TOKEN_KEY, REFRESH_TOKEN_KEY, ADMIN_TOKEN_KEY, ADMIN_REFRESH_TOKEN_KEY, IMPERSONATING_KEY] {
+            storage.remove_item(key).ok();
+        }
+    }
+}
+
+/// The user an admin is using the app as, and until when.
+#[derive(serde::Serialize, serde::Deserialize, Clone, PartialEq)]
+pub struct Impersonating {
+    pub username: String,
+    pub expires_at: String,
+}
+
+/// Switches to an impersonation `token`, keeping the admin's own tokens to
+/// go back to. The token can't be refreshed, so it simply lapses.
+pub fn impersonate(token: &str, user: &Impersonating) {
+    let (Some(storage), Some(admin_token), Some(admin_refresh_token)) = (storage(), self::token(), refresh_token()) else {
+        return;
+    };
+    storage.set_item(ADMIN_TOKEN_KEY, &admin_token).ok();
+    storage.set_item(ADMIN_REFRESH_TOKEN_KEY, &admin_refresh_token).ok();
+    storage.set_item(IMPERSONATING_KEY, &serde_json::to_string(user).unwrap_or_default()).ok();
+    storage.set_item(TOKEN_KEY, token).ok();
+    storage.remove_item(REFRESH_TOKEN_KEY).ok();
+}
+
+/// Who the admin is impersonating, if anyone.
+pub fn impersonating() -> Option<Impersonating> {
+    serde_json::from_str(&storage()?.get_item(IMPERSONATING_KEY).ok()??).ok()
+}
+
+/// Goes back to the admin's own tokens.
+pub fn end_impersonation() {
+    let Some(storage) = storage() else {
+        return;
+    };
+    let admin_token = storage.get_item(ADMIN_TOKEN_KEY).ok().flatten();
+    let admin_refresh_token = storage.get_item(ADMIN_REFRESH_TOKEN_KEY).ok().flatten();
+    for key in [ADMIN_TOKEN_KEY, ADMIN_REFRESH_TOKEN_KEY, IMPERSONATING_KEY] {
+        storage.remove_item(key).ok();
+    }
+    match (admin_token, admin_refresh_token) {
+        (Some(token), Some(refresh_token)) => set_tokens(&token, &refresh_token),
+        _ => clear_token(),
+    }
+}
+
+/// Whether the session token expires within `seconds`, going by its `exp`
+/// claim. A token that can't be read counts as expiring.
+pub fn expires_within(seconds: f64) -> bool {
+    #[derive(serde::Deserialize)]
+    struct Claims {
+        exp: f64,
+    }
+
+    let Some(token) = token() else {
+        return false;
+    };
+    // The payload is the JWT's middle part, in unpadded base64url.
+    let payload = token.split('.').nth(1).unwrap_or_default().replace('-', "+").replace('_', "/");
+    let padded = format!("{}{}", payload, "=".repeat((4 - payload.len() % 4) % 4));
+    let claims = gloo_utils::window()
+        .atob(&padded)
+        .ok()
+        .and_then(|json| serde_json::from_str::<Claims>(&json).ok());
+    match claims {
+        Some(claims) => claims.exp - js_sys::Date::now() / 1000.0 < seconds,
+        None => true,
+    }
+}
+
+/// `Authorization` header value for API calls made as the logged-in user.
+pub fn bearer() -> Option<String> {
+    token().map(|token| format!("Bearer {}", token))
+}
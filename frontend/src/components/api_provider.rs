@@ -0,0 +1,29 @@
+use yew::{function_component, hook, html, use_context, Children, ContextProvider, Html, Properties};
+
+use crate::api::ApiClient;
+
+#[derive(Properties, PartialEq)]
+pub struct ApiProviderProps {
+    /// The network, or the demo dataset in a `mock-api` build, unless a
+    /// test scripts the answers.
+    #[prop_or_default]
+    pub client: ApiClient,
+    pub children: Children,
+}
+
+/// Offers `client` as context to everything inside, which sends its API
+/// calls through it.
+#[function_component(ApiProvider)]
+pub fn api_provider(props: &ApiProviderProps) -> Html {
+    html! {
+        <ContextProvider<ApiClient> context={props.client.clone()}>
+            {props.children.clone()}
+        </ContextProvider<ApiClient>>
+    }
+}
+
+/// The client of the `ApiProvider` above, or the default one outside any.
+#[hook]
+pub fn use_api() -> ApiClient {
+    use_context::<ApiClient>().unwrap_or_default()
+}
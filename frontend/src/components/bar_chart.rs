@@ -0,0 +1,67 @@
+use yew::{function_component, html, Children, Html, Properties};
+
+const WIDTH: f64 = 600.0;
+const HEIGHT: f64 = 160.0;
+const PADDING: f64 = 24.0;
+
+#[derive(Properties, PartialEq)]
+pub struct BarChartProps {
+    pub title: String,
+    /// Labels and values, left to right.
+    pub bars: Vec<(String, i64)>,
+    /// Controls shown beside the title.
+    #[prop_or_default]
+    pub children: Children,
+}
+
+/// Column chart of counts, labelled at the first and last bar and on hover.
+#[function_component(BarChart)]
+pub fn bar_chart(props: &BarChartProps) -> Html {
+    let max = props.bars.iter().map(|(_, value)| *value).max().unwrap_or(0).max(1) as f64;
+    let step = (WIDTH - 2.0 * PADDING) / props.bars.len().max(1) as f64;
+
+    html! {
+        <div class="card bg-base-100 shadow">
+            <div class="card-body">
+                <div class="flex items-center justify-between gap-2">
+                    <h3 class="card-title text-base">{&props.title}</h3>
+                    {props.children.clone()}
+                </div>
+                if props.bars.iter().all(|(_, value)| *value == 0) {
+                    <p class="text-sm opacity-70">{"No data yet"}</p>
+                } else {
+                    <svg viewBox={format!("0 0 {} {}", WIDTH, HEIGHT)} class="w-full h-40">
+                        <text x="2" y="14" class="text-xs fill-current">{max.to_string()}</text>
+                        {props.bars.iter().enumerate().map(|(i, (label, value))| {
+                            let height = *value as f64 / max * (HEIGHT - 2.0 * PADDING);
+                            html! {
+                                <rect
+                                    x={format!("{:.1}", PADDING + i as f64 * step)}
+                                    y={format!("{:.1}", HEIGHT - PADDING - height)}
+                                    width={format!("{:.1}", (step - 1.0).max(1.0))}
+                                    height={format!("{:.1}", height)}
+                                    fill="#3b82f6"
+                                >
+                                    <title>{format!("{}: {}", label, value)}</title>
+                                </rect>
+                            }
+                        }).collect::<Html>()}
+                        if let (Some((first, _)), Some((last, _))) = (props.bars.first(), props.bars.last()) {
+                            <text x={PADDING.to_string()} y={(HEIGHT - 6.0).to_string()} class="text-xs fill-current">
+                                {first}
+                            </text>
+                            <text
+                                x={(WIDTH - PADDING).to_string()}
+                                y={(HEIGHT - 6.0).to_string()}
+                                text-anchor="end"
+                                class="text-xs fill-current"
+                            >
+                                {last}
+                            </text>
+                        }
+                    </svg>
+                }
+            </div>
+        </div>
+    }
+}
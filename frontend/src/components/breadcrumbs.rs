@@ -0,0 +1,138 @@
+use wasm_bindgen::JsValue;
+use web_sys::Storage;
+use yew::{function_component, html, Html, Properties};
+
+use crate::Route;
+
+/// Session storage key prefix for the last URL of each list page.
+const LIST_KEY_PREFIX: &str = "list:";
+
+#[derive(Properties, PartialEq)]
+pub struct BreadcrumbsProps {
+    pub route: Route,
+    /// The timeline's title, for pages under a timeline.
+    #[prop_or_default]
+    pub timeline: Option<String>,
+    /// The event's title, for event pages.
+    #[prop_or_default]
+    pub event: Option<String>,
+    /// The person's name, for person pages.
+    #[prop_or_default]
+    pub person: Option<String>,
+    /// The story's title, for story pages.
+    #[prop_or_default]
+    pub story: Option<String>,
+}
+
+/// The trail from Home to the current page, derived from its route. Titles
+/// the page has not loaded yet fall back to generic labels.
+#[function_component(Breadcrumbs)]
+pub fn breadcrumbs(props: &BreadcrumbsProps) -> Html {
+    let crumbs = trail(
+        &props.route,
+        props.timeline.as_deref(),
+        props.event.as_deref(),
+        props.person.as_deref(),
+        props.story.as_deref(),
+    );
+    let last = crumbs.len() - 1;
+
+    html! {
+        <div class="text-sm breadcrumbs">
+            <ul>
+                {crumbs.into_iter().enumerate().map(|(index, (label, href))| html! {
+                    <li>
+                        if index == last {
+                            <span>{label}</span>
+                        } else {
+                            <a {href}>{label}</a>
+                        }
+                    </li>
+                }).collect::<Html>()}
+            </ul>
+        </div>
+    }
+}
+
+/// `(label, href)` for each step; the last one is the current page.
+fn trail(
+    route: &Route,
+    timeline: Option<&str>,
+    event: Option<&str>,
+    person: Option<&str>,
+    story: Option<&str>,
+) -> Vec<(String, String)> {
+    let crumb = |label: &str, href: String| (label.to_string(), href);
+    let home = crumb("Home", "/".to_string());
+    let events = |path: String| crumb("Events", list_url(&path));
+    let timeline = |id: &str| crumb(timeline.unwrap_or("Timeline"), format!("/timelines/{}", id));
+    let event = |href: String| crumb(event.unwrap_or("Event"), href);
+    let admin = || crumb("Admin", "/admin".to_string());
+
+    match route {
+        Route::Home => vec![home],
+        Route::Events => vec![home, events("/events".to_string())],
+        Route::EventDetail { id } => vec![home, events("/events".to_string()), event(format!("/events/{}", id))],
+        Route::MergeEvents { id, other_id } => vec![
+            home,
+            events("/events".to_string()),
+            event(format!("/events/{}", id)),
+            crumb("Merge", format!("/events/{}/merge/{}", id, other_id)),
+        ],
+        Route::PersonDetail { id } => vec![home, crumb(person.unwrap_or("Person"), format!("/people/{}", id))],
+        Route::TimelineDetail { id } => vec![home, timeline(id)],
+        Route::TimelineEvents { id } => vec![home, timeline(id), events(format!("/timelines/{}/events", id))],
+        Route::TimelineEvent { id, event_id } => vec![
+            home,
+            timeline(id),
+            events(format!("/timelines/{}/events", id)),
+            event(format!("/timelines/{}/events/{}", id, event_id)),
+        ],
+        Route::TimelineStory { id, story_id } => vec![
+            home,
+            timeline(id),
+            crumb(story.unwrap_or("Story"), format!("/timelines/{}/stories/{}", id, story_id)),
+        ],
+        Route::Timeline => vec![home, crumb("Timeline", "/timeline".to_string())],
+        Route::About => vec![home, crumb("About", "/about".to_string())],
+        Route::Login => vec![home, crumb("Log in", "/login".to_string())],
+        Route::ResetPassword => vec![home, crumb("Reset password", "/reset-password".to_string())],
+        Route::Stats => vec![home, crumb("Stats", "/stats".to_string())],
+        Route::Explore => vec![home, crumb("Explore", "/explore".to_string())],
+        Route::Search => vec![home, crumb("Search", "/search".to_string())],
+        Route::Calendar => vec![home, crumb("Calendar", "/calendar".to_string())],
+        Route::Settings => vec![home, crumb("Settings", "/settings".to_string())],
+        Route::Templates => vec![home, crumb("Templates", "/templates".to_string())],
+        Route::AdminDashboard => vec![home, admin()],
+        Route::AdminPerformance => vec![home, admin(), crumb("Performance", "/admin/performance".to_string())],
+        Route::AdminModeration => vec![home, admin(), crumb("Moderation", "/admin/moderation".to_string())],
+        Route::AdminUsers => vec![home, admin(), crumb("Users", "/admin/users".to_string())],
+        Route::AdminEmailTemplates => {
+            vec![home, admin(), crumb("Email templates", "/admin/email-templates".to_string())]
+        }
+    }
+}
+
+fn session() -> Option<Storage> {
+    web_sys::window()?.session_storage().ok()?
+}
+
+/// Shows a list page's filters in the address bar (without a new history
+/// entry) and remembers them, so crumbs leading back to the list restore
+/// the same view.
+pub fn keep_list_url(url: &str) {
+    let path = url.split('?').next().unwrap_or(url);
+    if let Some(storage) = session() {
+        storage.set_item(&format!("{}{}", LIST_KEY_PREFIX, path), url).ok();
+    }
+    if let Ok(history) = gloo_utils::window().history() {
+        history.replace_state_with_url(&JsValue::NULL, "", Some(url)).ok();
+    }
+}
+
+/// The list at `path` as the user last left it.
+fn list_url(path: &str) -> String {
+    session()
+        .and_then(|storage| storage.get_item(&format!("{}{}", LIST_KEY_PREFIX, path)).ok().flatten())
+        .unwrap_or_else(|| path.to_string())
+}
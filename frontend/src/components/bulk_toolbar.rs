@@ -0,0 +1,222 @@
+use wasm_bindgen::JsCast;
+use web_sys::HtmlElement;
+use yew::{function_component, html, use_node_ref, use_state, Callback, Html, Properties};
+
+use crate::api::Request;
+use crate::components::api_provider::use_api;
+use crate::components::error_boundary::use_error_reporter;
+use crate::components::modal::{ConfirmDialog, Confirmation, Modal};
+use crate::components::move_dialog::MoveDialog;
+use crate::components::notifications::use_notify;
+
+/// Bulk actions that take a value first.
+#[derive(Clone, Copy, PartialEq)]
+enum Prompt {
+    Category,
+    Tags,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct BulkToolbarProps {
+    /// Ids of the selected events.
+    pub selected: Vec<String>,
+    pub on_clear: Callback<()>,
+    /// Called after an action has changed the events, to reload them.
+    pub on_done: Callback<()>,
+}
+
+/// Has the browser save `json` as a file.
+fn download(name: &str, json: &serde_json::Value) {
+    let Ok(link) = gloo_utils::document().create_element("a") else {
+        return;
+    };
+    let href = format!("data:application/json;charset=utf-8,{}", js_sys::encode_uri_component(&json.to_string()));
+    link.set_attribute("href", &href).ok();
+    link.set_attribute("download", name).ok();
+    if let Ok(link) = link.dyn_into::<HtmlElement>() {
+        link.click();
+    }
+}
+
+/// Toolbar floating over the page while events are selected, running
+/// `POST /api/events/bulk` actions on all of them at once.
+#[function_component(BulkToolbar)]
+pub fn bulk_toolbar(props: &BulkToolbarProps) -> Html {
+    let prompt = use_state(|| Option::<Prompt>::None);
+    let confirming = use_state(|| Option::<Confirmation>::None);
+    let moving = use_state(|| false);
+    let prompt_input = use_node_ref();
+    let errors = use_error_reporter();
+    let client = use_api();
+    let notify = use_notify();
+
+    let count = props.selected.len();
+    let noun = if count == 1 { "1 event".to_string() } else { format!("{} events", count) };
+
+    // Sends an action and reports `done` once it has been applied.
+    let run = {
+        let selected = props.selected.clone();
+        let errors = errors.clone();
+        let notify = notify.clone();
+        let on_done = props.on_done.clone();
+        let client = client.clone();
+        Callback::from(move |(mut body, done): (serde_json::Value, String)| {
+            body["event_ids"] = serde_json::json!(selected);
+            let errors = errors.clone();
+            let notify = notify.clone();
+            let on_done = on_done.clone();
+            let client = client.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match client.send_json::<serde::de::IgnoredAny>(Request::post("/api/events/bulk"), &body).await {
+                    Ok(_) => {
+                        notify.success(done);
+                        on_done.emit(());
+                    }
+                    Err(error) => errors.report(error),
+                }
+            });
+        })
+    };
+
+    let publish = {
+        let noun = noun.clone();
+        run.reform(move |_| (serde_json::json!({ "action": "publish" }), format!("Published {}", noun)))
+    };
+    let delete = {
+        let delete = {
+            let noun = noun.clone();
+            run.reform(move |_| (serde_json::json!({ "action": "delete" }), format!("Deleted {}", noun)))
+        };
+        let confirmation = Confirmation::new(
+            format!("Delete {}?", noun),
+            "Deleted events can't be restored.",
+            "Delete",
+            delete,
+        );
+        let confirming = confirming.clone();
+        Callback::from(move |_| confirming.set(Some(confirmation.clone())))
+    };
+    let export = {
+        let selected = props.selected.clone();
+        let errors = errors.clone();
+        let client = client.clone();
+        Callback::from(move |_| {
+            let body = serde_json::json!({ "action": "export", "event_ids": selected });
+            let errors = errors.clone();
+            let client = client.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match client.send_json::<serde_json::Value>(Request::post("/api/events/bulk"), &body).await {
+                    Ok(events) => download("events.json", &events),
+                    Err(error) => errors.report(error),
+                }
+            });
+        })
+    };
+
+    let ask = |kind: Prompt| {
+        let prompt = prompt.clone();
+        Callback::from(move |_| prompt.set(Some(kind)))
+    };
+    let close_prompt = {
+        let prompt = prompt.clone();
+        Callback::from(move |_| prompt.set(None))
+    };
+    let submit_prompt = {
+        let prompt = prompt.clone();
+        let prompt_input = prompt_input.clone();
+        let noun = noun.clone();
+        let run = run.clone();
+        Callback::from(move |e: yew::SubmitEvent| {
+            e.prevent_default();
+            let (Some(kind), Some(input)) = (*prompt, prompt_input.cast::<web_sys::HtmlInputElement>()) else {
+                return;
+            };
+            let value = input.value().trim().to_string();
+            let action = match kind {
+                // An empty category clears it.
+                Prompt::Category => (
+                    serde_json::json!({ "action": "set_category", "category": (!value.is_empty()).then_some(&value) }),
+                    format!("Updated the category of {}", noun),
+                ),
+                Prompt::Tags => {
+                    let tags: Vec<&str> = value.split(',').map(str::trim).filter(|tag| !tag.is_empty()).collect();
+                    if tags.is_empty() {
+                        return;
+                    }
+                    (serde_json::json!({ "action": "add_tags", "tags": tags }), format!("Tagged {}", noun))
+                }
+            };
+            prompt.set(None);
+            run.emit(action);
+        })
+    };
+
+    let open_move = {
+        let moving = moving.clone();
+        Callback::from(move |_| moving.set(true))
+    };
+    let close_move = {
+        let moving = moving.clone();
+        Callback::from(move |_| moving.set(false))
+    };
+    let close_confirmation = {
+        let confirming = confirming.clone();
+        Callback::from(move |_| confirming.set(None))
+    };
+    let clear = props.on_clear.reform(|_| ());
+
+    html! {
+        <>
+            <div class="fixed bottom-4 inset-x-0 z-40 flex justify-center pointer-events-none">
+                <div
+                    class="pointer-events-auto flex flex-wrap items-center gap-2 bg-base-100 shadow-xl rounded-box px-4 py-2"
+                    role="toolbar"
+                    aria-label="Bulk actions"
+                >
+                    <span class="font-semibold mr-2">{format!("{} selected", count)}</span>
+                    <button class="btn btn-sm" onclick={publish}>{"Publish"}</button>
+                    <button class="btn btn-sm" onclick={ask(Prompt::Category)}>{"Category…"}</button>
+                    <button class="btn btn-sm" onclick={ask(Prompt::Tags)}>{"Tags…"}</button>
+                    <button class="btn btn-sm" onclick={open_move}>{"Move to…"}</button>
+                    <button class="btn btn-sm" onclick={export}>{"Export"}</button>
+                    <button class="btn btn-sm btn-error btn-outline" onclick={delete}>{"Delete"}</button>
+                    <button class="btn btn-sm btn-ghost" onclick={clear}>{"Clear"}</button>
+                </div>
+            </div>
+            if let Some(kind) = *prompt {
+                <Modal
+                    title={match kind {
+                        Prompt::Category => format!("Set the category of {}", noun),
+                        Prompt::Tags => format!("Add tags to {}", noun),
+                    }}
+                    on_close={close_prompt.clone()}
+                    actions={html! {
+                        <>
+                            <button class="btn" onclick={close_prompt.reform(|_| ())}>{"Cancel"}</button>
+                            <button class="btn btn-primary" type="submit" form="bulk-prompt">{"Apply"}</button>
+                        </>
+                    }}
+                >
+                    <form id="bulk-prompt" class="py-4" onsubmit={submit_prompt}>
+                        <input
+                            ref={prompt_input}
+                            class="input input-bordered w-full"
+                            aria-label={match kind { Prompt::Category => "Category", Prompt::Tags => "Tags" }}
+                            placeholder={match kind { Prompt::Category => "Category", Prompt::Tags => "war, europe" }}
+                        />
+                        <p class="text-sm opacity-70 mt-2">
+                            {match kind {
+                                Prompt::Category => "Leave empty to remove the category.",
+                                Prompt::Tags => "Separate tags with commas. Existing tags are kept.",
+                            }}
+                        </p>
+                    </form>
+                </Modal>
+            }
+            if *moving {
+                <MoveDialog event_ids={props.selected.clone()} on_close={close_move} on_moved={props.on_done.reform(|_| ())} />
+            }
+            <ConfirmDialog confirmation={(*confirming).clone()} on_close={close_confirmation} />
+        </>
+    }
+}
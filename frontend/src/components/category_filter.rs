@@ -0,0 +1,76 @@
+use yew::{function_component, html, Callback, Html, Properties};
+
+/// Name shown, and sent to the API, for events without a category.
+pub const UNCATEGORIZED: &str = "Uncategorized";
+
+#[derive(Properties, PartialEq)]
+pub struct CategoryFilterProps {
+    /// Categories and their event counts, in the order to list them.
+    pub counts: Vec<(String, i64)>,
+    /// Checked categories; none checked shows every category.
+    pub selected: Vec<String>,
+    pub on_change: Callback<Vec<String>>,
+}
+
+/// Sidebar of category checkboxes with event counts. Names compare
+/// case-insensitively, matching the API.
+#[function_component(CategoryFilter)]
+pub fn category_filter(props: &CategoryFilterProps) -> Html {
+    let is_selected = |name: &str| props.selected.iter().any(|selected| selected.eq_ignore_ascii_case(name));
+
+    // A checked category with nothing left in view stays listed, so it can
+    // be unchecked.
+    let mut counts = props.counts.clone();
+    for name in &props.selected {
+        if !counts.iter().any(|(category, _)| category.eq_ignore_ascii_case(name)) {
+            counts.push((name.clone(), 0));
+        }
+    }
+
+    let toggle = |name: &str| {
+        let selected = props.selected.clone();
+        let on_change = props.on_change.clone();
+        let name = name.to_string();
+        Callback::from(move |_| {
+            let mut next = selected.clone();
+            match next.iter().position(|selected| selected.eq_ignore_ascii_case(&name)) {
+                Some(index) => {
+                    next.remove(index);
+                }
+                None => next.push(name.clone()),
+            }
+            on_change.emit(next);
+        })
+    };
+    let clear = props.on_change.reform(|_| Vec::new());
+
+    html! {
+        <aside class="w-full md:w-56 shrink-0" aria-label="Filter by category">
+            <div class="card card-compact bg-base-100 shadow">
+                <div class="card-body">
+                    <div class="flex items-center justify-between">
+                        <h2 class="font-semibold">{"Categories"}</h2>
+                        if !props.selected.is_empty() {
+                            <button class="btn btn-ghost btn-xs" onclick={clear}>{"Clear"}</button>
+                        }
+                    </div>
+                    if counts.is_empty() {
+                        <p class="text-sm opacity-70">{"No events"}</p>
+                    }
+                    {counts.iter().map(|(name, count)| html! {
+                        <label key={name.clone()} class="label cursor-pointer justify-start gap-2 py-1">
+                            <input
+                                type="checkbox"
+                                class="checkbox checkbox-sm"
+                                checked={is_selected(name)}
+                                onchange={toggle(name)}
+                            />
+                            <span class="label-text flex-1 truncate">{name}</span>
+                            <span class="badge badge-ghost badge-sm">{count}</span>
+                        </label>
+                    }).collect::<Html>()}
+                </div>
+            </div>
+        </aside>
+    }
+}
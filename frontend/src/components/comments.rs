@@ -0,0 +1,446 @@
+use std::collections::HashSet;
+
+use serde::Deserialize;
+use wasm_bindgen::JsCast;
+use yew::{function_component, html, use_effect_with, use_node_ref, use_state, Callback, Html, NodeRef, Properties};
+
+use crate::api::Request;
+use crate::auth;
+use crate::components::api_provider::use_api;
+use crate::components::error_boundary::use_error_reporter;
+use crate::components::guest::{self, use_guest_policy, GuestFields};
+use crate::components::modal::{ConfirmDialog, Confirmation};
+use crate::components::reactions::{self, Reactions};
+
+/// How deep replies go, as the API allows; top-level comments are at 0.
+const MAX_DEPTH: i16 = 4;
+
+#[derive(Deserialize, Clone, PartialEq)]
+struct Comment {
+    id: String,
+    depth: i16,
+    author: String,
+    /// Written signed out.
+    #[serde(default)]
+    guest: bool,
+    body: String,
+    created_at: String,
+    /// Whether the viewer wrote it.
+    mine: bool,
+    #[serde(default)]
+    reactions: Vec<reactions::Count>,
+    #[serde(default)]
+    replies: Vec<Comment>,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct CommentsProps {
+    pub event_id: String,
+}
+
+/// `body` with its `@mentions` picked out.
+fn body_html(body: &str) -> Html {
+    body.split_inclusive(char::is_whitespace)
+        .map(|word| match word.strip_prefix('@') {
+            Some(name) if !name.trim().is_empty() => html! {
+                <span class="font-semibold text-primary">{word}</span>
+            },
+            _ => html! { {word} },
+        })
+        .collect()
+}
+
+/// How many comments `comments` holds, replies included.
+fn total(comments: &[Comment]) -> usize {
+    comments.iter().map(|comment| 1 + total(&comment.replies)).sum()
+}
+
+fn find<'a>(comments: &'a mut [Comment], id: &str) -> Option<&'a mut Comment> {
+    for comment in comments {
+        if comment.id == id {
+            return Some(comment);
+        }
+        if let Some(found) = find(&mut comment.replies, id) {
+            return Some(found);
+        }
+    }
+    None
+}
+
+/// Adds `comment` at the end of `parent`'s replies, or of the top level.
+fn insert(comments: &mut Vec<Comment>, parent: Option<&str>, comment: Comment) {
+    match parent {
+        Some(parent) => {
+            if let Some(parent) = find(comments, parent) {
+                parent.replies.push(comment);
+            }
+        }
+        None => comments.push(comment),
+    }
+}
+
+/// Drops the comment `id` and, as the API does, its replies.
+fn remove(comments: &mut Vec<Comment>, id: &str) {
+    comments.retain(|comment| comment.id != id);
+    for comment in comments {
+        remove(&mut comment.replies, id);
+    }
+}
+
+/// What drawing a thread needs from the component.
+struct Thread<'a> {
+    collapsed: &'a HashSet<String>,
+    replying_to: Option<&'a str>,
+    signed_in: bool,
+    on_toggle: &'a Callback<String>,
+    on_reply: &'a Callback<Option<String>>,
+    on_remove: &'a Callback<String>,
+    /// Shown under the comment being replied to.
+    reply_form: &'a Html,
+}
+
+fn thread_html(comments: &[Comment], thread: &Thread) -> Html {
+    comments
+        .iter()
+        .map(|comment| {
+            let id = comment.id.clone();
+            let collapsed = thread.collapsed.contains(&comment.id);
+            let replies = total(&comment.replies);
+            let replying = thread.replying_to == Some(comment.id.as_str());
+            html! {
+                <li key={comment.id.clone()} id={format!("comment-{}", comment.id)}>
+                    <div class="flex items-center gap-2 text-sm">
+                        <span class="font-semibold">{&comment.author}</span>
+                        if comment.guest {
+                            <span class="badge badge-ghost badge-sm">{"guest"}</span>
+                        }
+                        <span class="opacity-60">
+                            {comment.created_at.get(..16).unwrap_or(&comment.created_at).replace('T', " ")}
+                        </span>
+                        if comment.mine {
+                            <button
+                                class="btn btn-ghost btn-xs"
+                                aria-label="Delete comment"
+                                onclick={thread.on_remove.reform({
+                                    let id = id.clone();
+                                    move |_| id.clone()
+                                })}
+                            >
+                                {"✕"}
+                            </button>
+                        }
+                    </div>
+                    <p class="whitespace-pre-line">{body_html(&comment.body)}</p>
+                    <div class="flex flex-wrap items-center gap-2">
+                        <Reactions
+                            url={format!("/api/comments/{}/reactions", comment.id)}
+                            counts={Some(comment.reactions.clone())}
+                        />
+                        if thread.signed_in && comment.depth < MAX_DEPTH {
+                            <button
+                                class="btn btn-ghost btn-xs"
+                                onclick={thread.on_reply.reform({
+                                    let id = id.clone();
+                                    move |_| (!replying).then(|| id.clone())
+                                })}
+                            >
+                                {if replying { "Cancel" } else { "Reply" }}
+                            </button>
+                        }
+                        if replies > 0 {
+                            <button
+                                class="btn btn-ghost btn-xs"
+                                aria-expanded={(!collapsed).to_string()}
+                                onclick={thread.on_toggle.reform(move |_| id.clone())}
+                            >
+                                {match (collapsed, replies) {
+                                    (true, 1) => "Show 1 reply".to_string(),
+                                    (true, n) => format!("Show {} replies", n),
+                                    (false, 1) => "Hide 1 reply".to_string(),
+                                    (false, n) => format!("Hide {} replies", n),
+                                }}
+                            </button>
+                        }
+                    </div>
+                    if replying {
+                        {thread.reply_form.clone()}
+                    }
+                    if !collapsed && !comment.replies.is_empty() {
+                        <ul class="flex flex-col gap-4 mt-3 pl-4 border-l-2 border-base-300">
+                            {thread_html(&comment.replies, thread)}
+                        </ul>
+                    }
+                </li>
+            }
+        })
+        .collect()
+}
+
+/// The discussion under an event, in threads oldest first. Signed-in
+/// viewers can join in or reply, `@mention` people, and delete what they
+/// wrote; threads fold away to their first comment. Where the instance
+/// allows it, signed-out visitors can comment under a name.
+#[function_component(Comments)]
+pub fn comments(props: &CommentsProps) -> Html {
+    let comments = use_state(|| Option::<Vec<Comment>>::None);
+    let confirming = use_state(|| Option::<Confirmation>::None);
+    let sending = use_state(|| false);
+    // The comment whose reply form is open.
+    let replying_to = use_state(|| Option::<String>::None);
+    let collapsed = use_state(HashSet::<String>::new);
+    let body_input = use_node_ref();
+    let reply_input = use_node_ref();
+    let errors = use_error_reporter();
+    let client = use_api();
+    let policy = use_guest_policy();
+    let url = format!("/api/events/{}/comments", props.event_id);
+
+    {
+        let comments = comments.clone();
+        let client = client.clone();
+        use_effect_with(url.clone(), move |url: &String| {
+            let url = format!("{}?threaded=true", url);
+            // The event shows without its comments if they fail to load.
+            wasm_bindgen_futures::spawn_local(async move {
+                comments.set(client.get::<Vec<Comment>>(&url).await.ok());
+            });
+        });
+    }
+
+    // Posts what `input` holds, as a reply to `parent` when set.
+    let submit = |parent: Option<String>, input: NodeRef| {
+        let client = client.clone();
+        let comments = comments.clone();
+        let sending = sending.clone();
+        let replying_to = replying_to.clone();
+        let errors = errors.clone();
+        let url = url.clone();
+        Callback::from(move |e: yew::SubmitEvent| {
+            e.prevent_default();
+            let Some(input) = input.cast::<web_sys::HtmlTextAreaElement>() else {
+                return;
+            };
+            let body = input.value().trim().to_string();
+            if body.is_empty() {
+                return;
+            }
+            let mut payload = serde_json::json!({ "body": body, "parent_comment_id": parent });
+            let form = e.target().and_then(|target| target.dyn_into::<web_sys::Element>().ok());
+            if let (None, Some(form)) = (auth::token(), form) {
+                let name = form
+                    .query_selector("[name=name]")
+                    .ok()
+                    .flatten()
+                    .and_then(|field| field.dyn_into::<web_sys::HtmlInputElement>().ok())
+                    .map(|field| field.value().trim().to_string());
+                payload["name"] = name.into();
+                if let (Some(payload), serde_json::Value::Object(proof)) = (payload.as_object_mut(), guest::proof(&form)) {
+                    payload.extend(proof);
+                }
+            }
+            let comments = comments.clone();
+            let sending = sending.clone();
+            let replying_to = replying_to.clone();
+            let errors = errors.clone();
+            let url = url.clone();
+            let parent = parent.clone();
+            sending.set(true);
+            let client = client.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match client.send_json::<Comment>(Request::post(&url), &payload).await {
+                    Ok(created) => {
+                        input.set_value("");
+                        let mut list = (*comments).clone().unwrap_or_default();
+                        insert(&mut list, parent.as_deref(), created);
+                        comments.set(Some(list));
+                        replying_to.set(None);
+                    }
+                    Err(error) => errors.report(error),
+                }
+                sending.set(false);
+            });
+        })
+    };
+
+    let on_remove = {
+        let comments = comments.clone();
+        let confirming = confirming.clone();
+        let errors = errors.clone();
+        let client = client.clone();
+        let client = client.clone();
+        Callback::from(move |id: String| {
+            let comments = comments.clone();
+            let errors = errors.clone();
+            let client = client.clone();
+            let on_confirm = Callback::from(move |_| {
+                let comments = comments.clone();
+                let errors = errors.clone();
+                let id = id.clone();
+                let client = client.clone();
+                wasm_bindgen_futures::spawn_local(async move {
+                    let url = format!("/api/comments/{}", id);
+                    match client.send::<serde::de::IgnoredAny>(Request::delete(&url)).await {
+                        Ok(_) => {
+                            let mut list = (*comments).clone().unwrap_or_default();
+                            remove(&mut list, &id);
+                            comments.set(Some(list));
+                        }
+                        Err(error) => errors.report(error),
+                    }
+                });
+            });
+            confirming.set(Some(Confirmation::new(
+                "Delete comment?",
+                "It can't be brought back, and neither can its replies.",
+                "Delete",
+                on_confirm,
+            )));
+        })
+    };
+    let on_toggle = {
+        let collapsed = collapsed.clone();
+        Callback::from(move |id: String| {
+            let mut ids = (*collapsed).clone();
+            if !ids.remove(&id) {
+                ids.insert(id);
+            }
+            collapsed.set(ids);
+        })
+    };
+    let on_reply = {
+        let replying_to = replying_to.clone();
+        Callback::from(move |id: Option<String>| replying_to.set(id))
+    };
+    let close_confirmation = {
+        let confirming = confirming.clone();
+        Callback::from(move |_| confirming.set(None))
+    };
+
+    let Some(list) = &*comments else {
+        return html! {};
+    };
+
+    let signed_in = auth::token().is_some();
+    let reply_form = html! {
+        <form class="flex flex-col gap-2 mt-2" onsubmit={submit((*replying_to).clone(), reply_input.clone())}>
+            <textarea
+                ref={reply_input.clone()}
+                class="textarea textarea-bordered textarea-sm"
+                rows="2"
+                maxlength="5000"
+                aria-label="Reply"
+                placeholder="Write a reply"
+                required=true
+            />
+            <button class="btn btn-xs self-end" type="submit" disabled={*sending}>{"Reply"}</button>
+        </form>
+    };
+    let thread = Thread {
+        collapsed: &collapsed,
+        replying_to: replying_to.as_deref(),
+        signed_in,
+        on_toggle: &on_toggle,
+        on_reply: &on_reply,
+        on_remove: &on_remove,
+        reply_form: &reply_form,
+    };
+
+    html! {
+        <section class="card bg-base-100 shadow-xl mt-6" aria-labelledby="comments-heading">
+            <div class="card-body">
+                <h3 id="comments-heading" class="card-title">{format!("Comments ({})", total(list))}</h3>
+                if list.is_empty() {
+                    <p class="opacity-70">{"No comments yet."}</p>
+                }
+                <ul class="flex flex-col gap-4">
+                    {thread_html(list, &thread)}
+                </ul>
+                if signed_in {
+                    <form class="flex flex-col gap-2 mt-2" onsubmit={submit(None, body_input.clone())}>
+                        <textarea
+                            ref={body_input.clone()}
+                            class="textarea textarea-bordered"
+                            rows="3"
+                            maxlength="5000"
+                            aria-label="Comment"
+                            placeholder="Add a comment. Mention someone with @username."
+                            required=true
+                        />
+                        <button class="btn btn-sm self-end" type="submit" disabled={*sending}>{"Comment"}</button>
+                    </form>
+                } else if let Some(policy) = policy.filter(|policy| policy.enabled) {
+                    <form class="flex flex-col gap-2 mt-2" onsubmit={submit(None, body_input.clone())}>
+                        <input
+                            name="name"
+                            class="input input-bordered input-sm"
+                            maxlength="50"
+                            aria-label="Your name"
+                            placeholder="Your name"
+                            required=true
+                        />
+                        <textarea
+                            ref={body_input.clone()}
+                            class="textarea textarea-bordered"
+                            rows="3"
+                            maxlength="5000"
+                            aria-label="Comment"
+                            placeholder="Add a comment"
+                            required=true
+                        />
+                        <GuestFields challenge={policy.challenge} />
+                        <p class="text-sm">
+                            {"Commenting as a guest. "}<a href="/login" class="link">{"Log in"}</a>{" to reply and get notified."}
+                        </p>
+                        <button class="btn btn-sm self-end" type="submit" disabled={*sending}>{"Comment"}</button>
+                    </form>
+                } else {
+                    <p class="text-sm"><a href="/login" class="link">{"Log in"}</a>{" to join the discussion."}</p>
+                }
+            </div>
+            <ConfirmDialog confirmation={(*confirming).clone()} on_close={close_confirmation} />
+        </section>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
+
+    use super::*;
+    use crate::api::Method;
+    use crate::testing::{self, MockClient};
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    fn comment(id: &str, depth: i16, body: &str, replies: serde_json::Value) -> serde_json::Value {
+        serde_json::json!({
+            "id": id, "depth": depth, "author": "ada", "body": body,
+            "created_at": "2026-10-16T09:30:00", "mine": false, "reactions": [], "replies": replies,
+        })
+    }
+
+    #[wasm_bindgen_test]
+    async fn threads_fold_away() {
+        let threads = serde_json::json!([
+            comment("c1", 0, "First", serde_json::json!([
+                comment("c2", 1, "A reply", serde_json::json!([comment("c3", 2, "And another", serde_json::json!([]))])),
+            ])),
+            comment("c4", 0, "Second", serde_json::json!([])),
+        ]);
+        let client = Rc::new(MockClient::default().reply(Method::Get, "/api/events/e1/comments", 200, threads));
+        let root = testing::mount(client.clone(), html! { <Comments event_id="e1" /> }).await;
+        assert!(client.requests().contains(&(Method::Get, "/api/events/e1/comments?threaded=true".to_string())));
+        assert_eq!(testing::texts(&root, "h3"), ["Comments (4)"]);
+        assert_eq!(testing::texts(&root, "li p"), ["First", "A reply", "And another", "Second"]);
+
+        let fold = root.query_selector("[aria-expanded=true]").unwrap().unwrap();
+        assert_eq!(fold.text_content().as_deref(), Some("Hide 2 replies"));
+        fold.dyn_into::<web_sys::HtmlElement>().unwrap().click();
+        testing::settle().await;
+
+        assert_eq!(testing::texts(&root, "li p"), ["First", "Second"]);
+        assert_eq!(testing::texts(&root, "[aria-expanded=false]"), ["Show 2 replies"]);
+    }
+}
@@ -0,0 +1,119 @@
+use yew::{function_component, html, use_node_ref, use_state, Callback, Html, NodeRef, Properties};
+
+use crate::api;
+use crate::components::modal::Modal;
+
+/// Selections smaller than this share of either side count as a click.
+const MIN_SIDE: f64 = 0.02;
+
+#[derive(Properties, PartialEq)]
+pub struct CoverCropProps {
+    pub url: String,
+    /// `[x, y, width, height]` as fractions of the image's size.
+    #[prop_or_default]
+    pub crop: Option<Vec<f64>>,
+    /// Called with the chosen crop, or `None` for the whole image.
+    pub on_save: Callback<Option<Vec<f64>>>,
+    pub on_close: Callback<()>,
+}
+
+/// Where the pointer is over `area`, as fractions of its size.
+fn point(area: &NodeRef, e: &yew::PointerEvent) -> Option<(f64, f64)> {
+    let rect = area.cast::<web_sys::Element>()?.get_bounding_client_rect();
+    if rect.width() <= 0.0 || rect.height() <= 0.0 {
+        return None;
+    }
+    let x = (f64::from(e.client_x()) - rect.left()) / rect.width();
+    let y = (f64::from(e.client_y()) - rect.top()) / rect.height();
+    Some((x.clamp(0.0, 1.0), y.clamp(0.0, 1.0)))
+}
+
+/// Dialog for framing an event's cover: drag over the image to choose the
+/// part cards and thumbnails show.
+#[function_component(CoverCrop)]
+pub fn cover_crop(props: &CoverCropProps) -> Html {
+    let selection = use_state(|| props.crop.clone());
+    let anchor = use_state(|| Option::<(f64, f64)>::None);
+    let area = use_node_ref();
+
+    let start = {
+        let selection = selection.clone();
+        let anchor = anchor.clone();
+        let area = area.clone();
+        Callback::from(move |e: yew::PointerEvent| {
+            e.prevent_default();
+            if let Some((x, y)) = point(&area, &e) {
+                anchor.set(Some((x, y)));
+                selection.set(Some(vec![x, y, 0.0, 0.0]));
+            }
+        })
+    };
+    let drag = {
+        let selection = selection.clone();
+        let anchor = anchor.clone();
+        let area = area.clone();
+        Callback::from(move |e: yew::PointerEvent| {
+            let (Some((from_x, from_y)), Some((x, y))) = (*anchor, point(&area, &e)) else {
+                return;
+            };
+            selection.set(Some(vec![from_x.min(x), from_y.min(y), (x - from_x).abs(), (y - from_y).abs()]));
+        })
+    };
+    let finish = {
+        let selection = selection.clone();
+        let anchor = anchor.clone();
+        Callback::from(move |_: yew::PointerEvent| {
+            if anchor.is_none() {
+                return;
+            }
+            anchor.set(None);
+            if matches!(selection.as_deref(), Some(&[_, _, width, height]) if width < MIN_SIDE || height < MIN_SIDE) {
+                selection.set(None);
+            }
+        })
+    };
+    let save = {
+        let selection = selection.clone();
+        let on_save = props.on_save.clone();
+        Callback::from(move |_: yew::MouseEvent| on_save.emit((*selection).clone()))
+    };
+    let whole = {
+        let on_save = props.on_save.clone();
+        Callback::from(move |_: yew::MouseEvent| on_save.emit(None))
+    };
+
+    html! {
+        <Modal
+            title="Frame the cover"
+            on_close={props.on_close.clone()}
+            actions={html! {
+                <>
+                    <button class="btn btn-ghost" onclick={whole}>{"Show whole image"}</button>
+                    <button class="btn" onclick={props.on_close.reform(|_| ())}>{"Cancel"}</button>
+                    <button class="btn btn-primary" onclick={save}>{"Use as cover"}</button>
+                </>
+            }}
+        >
+            <p class="text-sm opacity-70 mb-2">{"Drag over the image to choose what cards show."}</p>
+            <div
+                ref={area}
+                class="relative overflow-hidden rounded-lg cursor-crosshair select-none touch-none"
+                onpointerdown={start}
+                onpointermove={drag}
+                onpointerup={finish.clone()}
+                onpointerleave={finish}
+            >
+                <img src={api::image_src(&props.url)} alt="" class="w-full pointer-events-none" draggable="false" />
+                if let Some(&[x, y, width, height]) = selection.as_deref() {
+                    <div
+                        class="absolute border-2 border-primary"
+                        style={format!(
+                            "left: {}%; top: {}%; width: {}%; height: {}%; box-shadow: 0 0 0 9999px rgb(0 0 0 / 0.5);",
+                            x * 100.0, y * 100.0, width * 100.0, height * 100.0,
+                        )}
+                    />
+                }
+            </div>
+        </Modal>
+    }
+}
@@ -0,0 +1,489 @@
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use yew::{
+    function_component, hook, html, use_effect_with, use_state, Callback, Html, Properties, TargetCast,
+    UseStateHandle,
+};
+
+use crate::api::Request;
+use crate::components::api_provider::use_api;
+use crate::components::error_boundary::use_error_reporter;
+use crate::components::modal::{ConfirmDialog, Confirmation};
+use crate::components::notifications::use_notify;
+
+/// The kinds a field can have, with their labels.
+const KINDS: [(&str, &str); 4] = [("text", "Text"), ("number", "Number"), ("date", "Date"), ("boolean", "Yes/no")];
+
+/// A custom field defined on a timeline.
+#[derive(Deserialize, Clone, PartialEq)]
+pub struct FieldDefinition {
+    id: String,
+    pub name: String,
+    /// "text", "number", "date" or "boolean".
+    pub kind: String,
+    pub required: bool,
+    position: i32,
+}
+
+impl FieldDefinition {
+    /// "battle_deaths" shown as "Battle deaths".
+    pub fn label(&self) -> String {
+        let name = self.name.replace('_', " ");
+        let mut chars = name.chars();
+        match chars.next() {
+            Some(first) => first.to_uppercase().chain(chars).collect(),
+            None => name,
+        }
+    }
+}
+
+/// The timeline's field definitions, reloaded when `revision` changes.
+#[hook]
+fn use_definitions(timeline_id: String, revision: u32) -> UseStateHandle<Vec<FieldDefinition>> {
+    let client = use_api();
+    let definitions = use_state(Vec::<FieldDefinition>::new);
+    {
+        let definitions = definitions.clone();
+        use_effect_with((timeline_id, revision), move |(timeline_id, _): &(String, u32)| {
+            let url = format!("/api/timelines/{}/fields", timeline_id);
+            wasm_bindgen_futures::spawn_local(async move {
+                if let Ok(list) = client.get::<Vec<FieldDefinition>>(&url).await {
+                    definitions.set(list);
+                }
+            });
+        });
+    }
+    definitions
+}
+
+/// A value as shown to readers.
+fn display(value: &Value) -> String {
+    match value {
+        Value::Bool(true) => "Yes".to_string(),
+        Value::Bool(false) => "No".to_string(),
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// The `field.*` filters in the address bar, as parameter and value.
+pub fn filters_from_url() -> Vec<(String, String)> {
+    let search = gloo_utils::window().location().search().unwrap_or_default();
+    search
+        .trim_start_matches('?')
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .filter(|(key, _)| key.starts_with("field."))
+        .filter_map(|(key, value)| {
+            let value = js_sys::decode_uri_component(&value.replace('+', " ")).ok()?.as_string()?;
+            Some((key.to_string(), value))
+        })
+        .collect()
+}
+
+#[derive(Properties, PartialEq)]
+pub struct CustomFieldsProps {
+    pub timeline_id: String,
+}
+
+/// Editors' list of a timeline's custom fields, with a form that adds one
+/// or, after "Edit", renames it or changes whether it is required. A
+/// field's kind can't change once it exists.
+#[function_component(CustomFields)]
+pub fn custom_fields(props: &CustomFieldsProps) -> Html {
+    let revision = use_state(|| 0u32);
+    let definitions = use_definitions(props.timeline_id.clone(), *revision);
+    let editing = use_state(|| Option::<FieldDefinition>::None);
+    let name = use_state(String::new);
+    let kind = use_state(|| "text".to_string());
+    let required = use_state(|| false);
+    let confirming = use_state(|| Option::<Confirmation>::None);
+    let errors = use_error_reporter();
+    let client = use_api();
+    let notify = use_notify();
+
+    let url = format!("/api/timelines/{}/fields", props.timeline_id);
+    let reload = {
+        let revision = revision.clone();
+        Callback::from(move |_: ()| revision.set(*revision + 1))
+    };
+
+    let reset = {
+        let editing = editing.clone();
+        let name = name.clone();
+        let kind = kind.clone();
+        let required = required.clone();
+        Callback::from(move |_: ()| {
+            editing.set(None);
+            name.set(String::new());
+            kind.set("text".to_string());
+            required.set(false);
+        })
+    };
+
+    let edit = |field: &FieldDefinition| {
+        let field = field.clone();
+        let editing = editing.clone();
+        let name = name.clone();
+        let kind = kind.clone();
+        let required = required.clone();
+        Callback::from(move |_| {
+            name.set(field.name.clone());
+            kind.set(field.kind.clone());
+            required.set(field.required);
+            editing.set(Some(field.clone()));
+        })
+    };
+
+    let submit = {
+        let url = url.clone();
+        let editing = editing.clone();
+        let body = match &*editing {
+            Some(field) => serde_json::json!({
+                "name": *name,
+                "required": *required,
+                "position": field.position,
+            }),
+            None => serde_json::json!({ "name": *name, "kind": *kind, "required": *required }),
+        };
+        let reload = reload.clone();
+        let reset = reset.clone();
+        let errors = errors.clone();
+        let notify = notify.clone();
+        let client = client.clone();
+        Callback::from(move |e: yew::SubmitEvent| {
+            e.prevent_default();
+            let request = match &*editing {
+                Some(field) => Request::put(&format!("/api/fields/{}", field.id)),
+                None => Request::post(&url),
+            };
+            let saved = if editing.is_some() { "Field saved" } else { "Field added" };
+            let body = body.clone();
+            let reload = reload.clone();
+            let reset = reset.clone();
+            let errors = errors.clone();
+            let notify = notify.clone();
+            let client = client.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match client.send_json::<serde::de::IgnoredAny>(request, &body).await {
+                    Ok(_) => {
+                        notify.success(saved);
+                        reset.emit(());
+                        reload.emit(());
+                    }
+                    Err(error) => errors.report(error),
+                }
+            });
+        })
+    };
+
+    let remove = |field: &FieldDefinition| {
+        let client = client.clone();
+        let url = format!("/api/fields/{}", field.id);
+        let removed = format!("Deleted {}", field.label());
+        let reload = reload.clone();
+        let errors = errors.clone();
+        let notify = notify.clone();
+        let remove = Callback::from(move |_| {
+            let url = url.clone();
+            let removed = removed.clone();
+            let reload = reload.clone();
+            let errors = errors.clone();
+            let notify = notify.clone();
+            let client = client.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match client.send::<serde::de::IgnoredAny>(Request::delete(&url)).await {
+                    Ok(_) => notify.success(removed),
+                    Err(error) => errors.report(error),
+                }
+                reload.emit(());
+            });
+        });
+        let confirmation = Confirmation::new(
+            format!("Delete {}?", field.label()),
+            "Every event on the timeline loses its value for this field.",
+            "Delete",
+            remove,
+        );
+        let confirming = confirming.clone();
+        Callback::from(move |_| confirming.set(Some(confirmation.clone())))
+    };
+    let close_confirmation = {
+        let confirming = confirming.clone();
+        Callback::from(move |_| confirming.set(None))
+    };
+
+    let on_name = {
+        let name = name.clone();
+        Callback::from(move |e: yew::InputEvent| {
+            let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+            name.set(input.value());
+        })
+    };
+    let on_kind = {
+        let kind = kind.clone();
+        Callback::from(move |e: yew::Event| {
+            let select: web_sys::HtmlSelectElement = e.target_unchecked_into();
+            kind.set(select.value());
+        })
+    };
+    let on_required = {
+        let required = required.clone();
+        Callback::from(move |e: yew::Event| {
+            let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+            required.set(input.checked());
+        })
+    };
+    let kind_label = |kind: &str| KINDS.iter().find(|(name, _)| *name == kind).map_or("", |(_, label)| label);
+
+    html! {
+        <div class="card bg-base-100 shadow mt-6">
+            <div class="card-body">
+                <h2 class="card-title">{"Custom fields"}</h2>
+                if definitions.is_empty() {
+                    <p class="opacity-70">{"Record details such as \"casualties\" or \"dynasty\" on every event."}</p>
+                }
+                <ul class="divide-y">
+                    {definitions.iter().map(|field| html! {
+                        <li key={field.id.clone()} class="flex items-center justify-between gap-2 py-2">
+                            <span class="flex items-center gap-2">
+                                {field.label()}
+                                <span class="badge badge-ghost">{kind_label(&field.kind)}</span>
+                                if field.required {
+                                    <span class="badge badge-outline">{"Required"}</span>
+                                }
+                            </span>
+                            <div class="flex gap-2">
+                                <button class="btn btn-ghost btn-sm" onclick={edit(field)}>{"Edit"}</button>
+                                <button class="btn btn-ghost btn-sm" onclick={remove(field)}>{"Delete"}</button>
+                            </div>
+                        </li>
+                    }).collect::<Html>()}
+                </ul>
+                <form class="flex flex-wrap items-center gap-2 mt-2" onsubmit={submit}>
+                    <input class="input input-bordered input-sm flex-1" placeholder="Name" maxlength="50"
+                        aria-label="Field name" value={(*name).clone()} oninput={on_name} />
+                    <select class="select select-bordered select-sm" aria-label="Kind"
+                        disabled={editing.is_some()} onchange={on_kind}>
+                        {KINDS.iter().map(|(value, label)| html! {
+                            <option value={*value} selected={*kind == *value}>{*label}</option>
+                        }).collect::<Html>()}
+                    </select>
+                    <label class="label cursor-pointer gap-2">
+                        <input type="checkbox" class="checkbox checkbox-sm" checked={*required} onchange={on_required} />
+                        <span class="label-text">{"Required"}</span>
+                    </label>
+                    <button class="btn btn-primary btn-sm" type="submit" disabled={name.trim().is_empty()}>
+                        {if editing.is_some() { "Save" } else { "Add field" }}
+                    </button>
+                    if editing.is_some() {
+                        <button class="btn btn-ghost btn-sm" type="button" onclick={reset.reform(|_| ())}>{"Cancel"}</button>
+                    }
+                </form>
+                <ConfirmDialog confirmation={(*confirming).clone()} on_close={close_confirmation} />
+            </div>
+        </div>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct CustomFieldValuesProps {
+    pub timeline_id: String,
+    /// The event's `custom_fields`.
+    pub values: Value,
+    pub can_edit: bool,
+    /// Called with the changed values, `null` for cleared ones, to be sent
+    /// as the event's `custom_fields` patch.
+    pub on_save: Callback<Value>,
+}
+
+/// An event's custom field values: listed for readers, and a form with an
+/// input of the right type for each field for editors.
+#[function_component(CustomFieldValues)]
+pub fn custom_field_values(props: &CustomFieldValuesProps) -> Html {
+    let definitions = use_definitions(props.timeline_id.clone(), 0);
+    let draft = use_state(Map::<String, Value>::new);
+
+    {
+        let draft = draft.clone();
+        use_effect_with(props.values.clone(), move |values: &Value| {
+            draft.set(values.as_object().cloned().unwrap_or_default())
+        });
+    }
+
+    if definitions.is_empty() {
+        return html! {};
+    }
+
+    if !props.can_edit {
+        let values = props.values.as_object();
+        return html! {
+            <>
+                {definitions.iter().filter_map(|field| {
+                    let value = values?.get(&field.name)?;
+                    Some(html! { <p><strong>{format!("{}:", field.label())}</strong>{" "}{display(value)}</p> })
+                }).collect::<Html>()}
+            </>
+        };
+    }
+
+    let set = |field: &FieldDefinition| {
+        let draft = draft.clone();
+        let name = field.name.clone();
+        let kind = field.kind.clone();
+        Callback::from(move |e: yew::Event| {
+            let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+            let text = input.value();
+            let value = match kind.as_str() {
+                "boolean" => Value::Bool(input.checked()),
+                _ if text.trim().is_empty() => Value::Null,
+                "number" => text.parse::<f64>().ok().map_or(Value::Null, Value::from),
+                _ => Value::String(text),
+            };
+            let mut next = (*draft).clone();
+            next.insert(name.clone(), value);
+            draft.set(next);
+        })
+    };
+
+    let submit = {
+        let draft = draft.clone();
+        let definitions = definitions.clone();
+        let on_save = props.on_save.clone();
+        Callback::from(move |e: yew::SubmitEvent| {
+            e.prevent_default();
+            let mut values = (*draft).clone();
+            // An unticked box means no rather than unset.
+            for field in definitions.iter().filter(|field| field.kind == "boolean") {
+                values.entry(field.name.clone()).or_insert(Value::Bool(false));
+            }
+            on_save.emit(Value::Object(values));
+        })
+    };
+
+    html! {
+        <form class="mt-2 flex flex-col gap-2" onsubmit={submit} aria-label="Custom fields">
+            {definitions.iter().map(|field| {
+                let value = draft.get(&field.name);
+                let text = match value {
+                    Some(Value::String(text)) => text.clone(),
+                    Some(Value::Number(number)) => number.to_string(),
+                    _ => String::new(),
+                };
+                let input_type = match field.kind.as_str() {
+                    "number" => "number",
+                    "date" => "date",
+                    _ => "text",
+                };
+                html! {
+                    <label key={field.name.clone()} class="flex items-center gap-2">
+                        <strong>{format!("{}{}:", field.label(), if field.required { " *" } else { "" })}</strong>
+                        if field.kind == "boolean" {
+                            <input type="checkbox" class="checkbox checkbox-sm"
+                                checked={matches!(value, Some(Value::Bool(true)))} onchange={set(field)} />
+                        } else {
+                            <input type={input_type} class="input input-bordered input-sm" step="any"
+                                required={field.required} value={text} onchange={set(field)} />
+                        }
+                    </label>
+                }
+            }).collect::<Html>()}
+            <div>
+                <button class="btn btn-sm" type="submit">{"Save fields"}</button>
+            </div>
+        </form>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct CustomFieldFilterProps {
+    pub timeline_id: String,
+    /// Applied `field.*` parameters and their values.
+    pub selected: Vec<(String, String)>,
+    pub on_change: Callback<Vec<(String, String)>>,
+}
+
+/// Sidebar filters on a timeline's custom fields: text and yes/no fields
+/// match a value, numbers and dates a range.
+#[function_component(CustomFieldFilter)]
+pub fn custom_field_filter(props: &CustomFieldFilterProps) -> Html {
+    let definitions = use_definitions(props.timeline_id.clone(), 0);
+    if definitions.is_empty() {
+        return html! {};
+    }
+
+    let current = |key: &str| {
+        props.selected.iter().find(|(selected, _)| selected == key).map(|(_, value)| value.clone()).unwrap_or_default()
+    };
+    // An empty value removes the filter.
+    let apply = {
+        let selected = props.selected.clone();
+        let on_change = props.on_change.clone();
+        move |key: &str, value: String| {
+            let mut next: Vec<(String, String)> = selected.iter().filter(|(name, _)| name != key).cloned().collect();
+            if !value.is_empty() {
+                next.push((key.to_string(), value));
+            }
+            on_change.emit(next);
+        }
+    };
+    let set = |key: String| {
+        let apply = apply.clone();
+        Callback::from(move |e: yew::Event| {
+            let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+            apply(&key, input.value());
+        })
+    };
+    let choose = |key: String| {
+        let apply = apply.clone();
+        Callback::from(move |e: yew::Event| {
+            let select: web_sys::HtmlSelectElement = e.target_unchecked_into();
+            apply(&key, select.value());
+        })
+    };
+
+    html! {
+        <section aria-labelledby="custom-field-filter-heading">
+            <h3 id="custom-field-filter-heading" class="font-semibold mb-2">{"Fields"}</h3>
+            <div class="flex flex-col gap-2">
+                {definitions.iter().map(|field| {
+                    let key = format!("field.{}", field.name);
+                    let control = match field.kind.as_str() {
+                        "boolean" => html! {
+                            <select class="select select-bordered select-sm" aria-label={field.label()}
+                                onchange={choose(key.clone())}>
+                                <option value="" selected={current(&key).is_empty()}>{"Any"}</option>
+                                <option value="true" selected={current(&key) == "true"}>{"Yes"}</option>
+                                <option value="false" selected={current(&key) == "false"}>{"No"}</option>
+                            </select>
+                        },
+                        "number" | "date" => {
+                            let input_type = if field.kind == "number" { "number" } else { "date" };
+                            let (min, max) = (format!("{}.min", key), format!("{}.max", key));
+                            html! {
+                                <div class="flex gap-1">
+                                    <input type={input_type} step="any" class="input input-bordered input-sm w-28"
+                                        aria-label={format!("{} from", field.label())} placeholder="From"
+                                        value={current(&min)} onchange={set(min.clone())} />
+                                    <input type={input_type} step="any" class="input input-bordered input-sm w-28"
+                                        aria-label={format!("{} to", field.label())} placeholder="To"
+                                        value={current(&max)} onchange={set(max.clone())} />
+                                </div>
+                            }
+                        }
+                        _ => html! {
+                            <input class="input input-bordered input-sm" aria-label={field.label()}
+                                value={current(&key)} onchange={set(key.clone())} />
+                        },
+                    };
+                    html! {
+                        <div key={field.name.clone()}>
+                            <span class="label-text">{field.label()}</span>
+                            {control}
+                        </div>
+                    }
+                }).collect::<Html>()}
+            </div>
+        </section>
+    }
+}
@@ -0,0 +1,134 @@
+use serde::Deserialize;
+use wasm_bindgen::{closure::Closure, JsCast};
+use yew::{function_component, html, use_effect_with, use_state, Callback, Html};
+
+use crate::api::Request;
+use crate::components::api_provider::use_api;
+use crate::components::error_boundary::use_error_reporter;
+use crate::components::notifications::use_notify;
+
+/// How often a queued or running export is checked on.
+const POLL_MS: i32 = 5_000;
+
+#[derive(Deserialize, Clone, PartialEq)]
+struct Export {
+    /// `queued`, `running`, `ready` or `failed`.
+    status: String,
+    created_at: String,
+    expires_at: Option<String>,
+    /// Set while a finished archive can be downloaded.
+    download_url: Option<String>,
+}
+
+impl Export {
+    fn pending(&self) -> bool {
+        self.status == "queued" || self.status == "running"
+    }
+}
+
+/// `2024-05-01T09:30:12.345` as `2024-05-01 09:30 UTC`.
+fn timestamp(value: &str) -> String {
+    format!("{} UTC", value.replace('T', " ").chars().take(16).collect::<String>())
+}
+
+/// Requests a zip of everything the user has stored and offers it for
+/// download once the server has built it.
+#[function_component(DataExport)]
+pub fn data_export() -> Html {
+    // `None` until loaded; then the latest export, if any.
+    let export = use_state(|| Option::<Option<Export>>::None);
+    // Bumped to check on a pending export again.
+    let poll = use_state(|| 0u32);
+    let errors = use_error_reporter();
+    let client = use_api();
+    let notify = use_notify();
+
+    {
+        let export = export.clone();
+        let next = poll.clone();
+        let errors = errors.clone();
+        let client = client.clone();
+        use_effect_with(*poll, move |round: &u32| {
+            let round = *round;
+            wasm_bindgen_futures::spawn_local(async move {
+                match client.get::<Option<Export>>("/api/me/export").await {
+                    Ok(latest) => {
+                        if latest.as_ref().is_some_and(Export::pending) {
+                            let again = Closure::once_into_js(move || next.set(round + 1));
+                            gloo_utils::window()
+                                .set_timeout_with_callback_and_timeout_and_arguments_0(again.unchecked_ref(), POLL_MS)
+                                .ok();
+                        }
+                        export.set(Some(latest));
+                    }
+                    Err(error) => errors.report(error),
+                }
+            });
+        });
+    }
+
+    let request = {
+        let export = export.clone();
+        let poll = poll.clone();
+        let client = client.clone();
+        Callback::from(move |_| {
+            let export = export.clone();
+            let poll = poll.clone();
+            let errors = errors.clone();
+            let notify = notify.clone();
+            let client = client.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match client.send::<Export>(Request::post("/api/me/export")).await {
+                    Ok(queued) => {
+                        notify.info("Preparing your data. This can take a few minutes.");
+                        export.set(Some(Some(queued)));
+                        poll.set(*poll + 1);
+                    }
+                    Err(error) => errors.report(error),
+                }
+            });
+        })
+    };
+
+    let latest = export.as_ref().and_then(Option::as_ref);
+    let pending = latest.is_some_and(Export::pending);
+
+    html! {
+        <section class="card bg-base-100 shadow max-w-lg" aria-labelledby="data-export-heading">
+            <div class="card-body">
+                <h2 id="data-export-heading" class="card-title">{"Your data"}</h2>
+                <p class="opacity-70">
+                    {"Download a zip of your profile, settings, timelines and their events and images, favorites and history."}
+                </p>
+                <div aria-live="polite">
+                    {match latest {
+                        None => html! {},
+                        Some(export) if export.pending() => html! {
+                            <p class="flex items-center gap-2">
+                                <span class="loading loading-spinner loading-sm"></span>
+                                {format!("Preparing the export you requested {}…", timestamp(&export.created_at))}
+                            </p>
+                        },
+                        Some(Export { download_url: Some(url), expires_at, .. }) => html! {
+                            <p>
+                                <a href={url.clone()} class="link link-primary" download="">{"Download your data"}</a>
+                                if let Some(expires_at) = expires_at {
+                                    <span class="text-sm opacity-70">{format!(" · available until {}", timestamp(expires_at))}</span>
+                                }
+                            </p>
+                        },
+                        Some(export) if export.status == "failed" => html! {
+                            <p class="text-error">{"The last export failed. Please try again."}</p>
+                        },
+                        Some(_) => html! { <p class="opacity-70">{"Your last export has expired."}</p> },
+                    }}
+                </div>
+                <div class="card-actions justify-end">
+                    <button class="btn btn-primary" disabled={pending || export.is_none()} onclick={request}>
+                        {"Export my data"}
+                    </button>
+                </div>
+            </div>
+        </section>
+    }
+}
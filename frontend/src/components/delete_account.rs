@@ -0,0 +1,136 @@
+use serde::Deserialize;
+use yew::{function_component, html, use_effect_with, use_node_ref, use_state, Callback, Html};
+
+use crate::api::Request;
+use crate::auth;
+use crate::components::api_provider::use_api;
+use crate::components::error_boundary::use_error_reporter;
+use crate::components::modal::Modal;
+use crate::preferences;
+
+#[derive(Deserialize, Clone, PartialEq)]
+struct Me {
+    username: String,
+    /// False for accounts that only log in through Google or GitHub.
+    has_password: bool,
+}
+
+/// Deletes the signed-in account after the user re-enters their password,
+/// or their username if they have none.
+#[function_component(DeleteAccount)]
+pub fn delete_account() -> Html {
+    let me = use_state(|| Option::<Me>::None);
+    let open = use_state(|| false);
+    let deleting = use_state(|| false);
+    let input = use_node_ref();
+    let errors = use_error_reporter();
+    let client = use_api();
+
+    {
+        let me = me.clone();
+        let errors = errors.clone();
+        let client = client.clone();
+        use_effect_with((), move |_| {
+            wasm_bindgen_futures::spawn_local(async move {
+                match client.get::<Me>("/api/me").await {
+                    Ok(account) => me.set(Some(account)),
+                    Err(error) => errors.report(error),
+                }
+            });
+        });
+    }
+
+    let Some(account) = (*me).clone() else {
+        return html! {};
+    };
+
+    let show = {
+        let open = open.clone();
+        Callback::from(move |_| open.set(true))
+    };
+    let close = {
+        let open = open.clone();
+        Callback::from(move |_| open.set(false))
+    };
+    let submit = {
+        let input = input.clone();
+        let deleting = deleting.clone();
+        let has_password = account.has_password;
+        let client = client.clone();
+        Callback::from(move |e: yew::SubmitEvent| {
+            e.prevent_default();
+            let Some(field) = input.cast::<web_sys::HtmlInputElement>() else {
+                return;
+            };
+            let body = match has_password {
+                true => serde_json::json!({ "password": field.value() }),
+                false => serde_json::json!({ "username": field.value() }),
+            };
+            let deleting = deleting.clone();
+            let errors = errors.clone();
+            deleting.set(true);
+            let client = client.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match client.send_json::<serde::de::IgnoredAny>(Request::delete("/api/me"), &body).await {
+                    Ok(_) => {
+                        auth::clear_token();
+                        preferences::clear();
+                        gloo_utils::window().location().set_href("/").ok();
+                    }
+                    Err(error) => {
+                        deleting.set(false);
+                        errors.report(error);
+                    }
+                }
+            });
+        })
+    };
+
+    let (label, input_type) = match account.has_password {
+        true => ("Your password", "password"),
+        false => ("Your username", "text"),
+    };
+
+    html! {
+        <section class="card bg-base-100 shadow max-w-lg border border-error" aria-labelledby="delete-account-heading">
+            <div class="card-body">
+                <h2 id="delete-account-heading" class="card-title">{"Delete account"}</h2>
+                <p class="opacity-70">
+                    {"Your personal data is removed and you are logged out everywhere. This cannot be undone."}
+                </p>
+                <div class="card-actions justify-end">
+                    <button class="btn btn-error btn-outline" onclick={show}>{"Delete my account"}</button>
+                </div>
+            </div>
+            if *open {
+                <Modal
+                    title="Delete your account?"
+                    on_close={close.clone()}
+                    actions={html! {
+                        <>
+                            <button class="btn" onclick={close.reform(|_| ())}>{"Cancel"}</button>
+                            <button class="btn btn-error" type="submit" form="delete-account-form" disabled={*deleting}>
+                                {"Delete account"}
+                            </button>
+                        </>
+                    }}
+                >
+                    <form id="delete-account-form" class="flex flex-col gap-2 py-4" onsubmit={submit}>
+                        <p>{format!("To confirm, enter {}.", label.to_lowercase())}</p>
+                        if !account.has_password {
+                            <p class="text-sm opacity-70">{format!("It is {}.", account.username)}</p>
+                        }
+                        <input
+                            ref={input}
+                            type={input_type}
+                            class="input input-bordered"
+                            aria-label={label}
+                            autocomplete={if account.has_password { "current-password" } else { "off" }}
+                            required=true
+                        />
+                    </form>
+                </Modal>
+            }
+        </section>
+    }
+}
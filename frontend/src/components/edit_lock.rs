@@ -0,0 +1,241 @@
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use serde::Deserialize;
+use wasm_bindgen::{closure::Closure, JsCast};
+use web_sys::{MessageEvent, WebSocket};
+use yew::{function_component, html, use_effect_with, use_state, Callback, Html, Properties};
+
+use crate::api::{self, ApiClient, FetchError, Request};
+use crate::components::api_provider::use_api;
+
+/// How often a held lock is renewed; well within the API's 30 second TTL.
+const HEARTBEAT_MS: i32 = 10_000;
+const HEARTBEAT: &str = "heartbeat";
+const LAPSED: &str = "Your edit lock lapsed and someone else may be editing.";
+
+#[derive(Deserialize, Clone, PartialEq)]
+struct Lock {
+    username: String,
+    /// Whether the viewer holds it.
+    mine: bool,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct EditLockProps {
+    pub event_id: String,
+    /// Told `true` once the viewer holds the lock and `false` while
+    /// someone else does.
+    pub on_change: Callback<bool>,
+}
+
+fn lock_url(event_id: &str) -> String {
+    format!("/api/events/{}/lock", event_id)
+}
+
+/// Takes or renews the lock; a 409 carries who holds it instead.
+async fn acquire(client: &ApiClient, event_id: &str, takeover: bool) -> Result<Lock, FetchError> {
+    let body = serde_json::json!({ "takeover": takeover });
+    client.send_json::<Lock>(Request::post(&lock_url(event_id)), &body).await
+}
+
+/// Gives the lock up, if the viewer still holds it.
+async fn release(client: &ApiClient, event_id: &str) {
+    client.send::<serde::de::IgnoredAny>(Request::delete(&lock_url(event_id))).await.ok();
+}
+
+/// Keeps a held lock alive until dropped, which releases it. Renews over
+/// a WebSocket, or through the API when the socket isn't open, and calls
+/// `on_lost` once if someone takes the lock over.
+struct Heartbeat {
+    client: ApiClient,
+    event_id: String,
+    socket: Option<WebSocket>,
+    interval: i32,
+    lost: Rc<Cell<bool>>,
+    _on_message: Closure<dyn Fn(MessageEvent)>,
+    _tick: Closure<dyn Fn()>,
+}
+
+impl Heartbeat {
+    fn start(client: ApiClient, event_id: &str, on_lost: Callback<String>) -> Self {
+        let lost = Rc::new(Cell::new(false));
+        let lose = {
+            let lost = lost.clone();
+            Callback::from(move |message: String| {
+                if !lost.replace(true) {
+                    on_lost.emit(message);
+                }
+            })
+        };
+
+        let socket = WebSocket::new(&api::socket_url(&format!("{}/ws", lock_url(event_id)))).ok();
+        let on_message = {
+            let lose = lose.clone();
+            Closure::<dyn Fn(MessageEvent)>::new(move |e: MessageEvent| {
+                let Some(text) = e.data().as_string() else {
+                    return;
+                };
+                match serde_json::from_str::<Option<Lock>>(&text) {
+                    Ok(Some(lock)) if lock.mine => {}
+                    Ok(Some(lock)) => lose.emit(format!("{} took over editing this event.", lock.username)),
+                    Ok(None) => lose.emit(LAPSED.to_string()),
+                    Err(_) => {}
+                }
+            })
+        };
+        if let Some(socket) = &socket {
+            socket.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+        }
+
+        let tick = {
+            let client = client.clone();
+            let socket = socket.clone();
+            let event_id = event_id.to_string();
+            Closure::<dyn Fn()>::new(move || match &socket {
+                Some(socket) if socket.ready_state() == WebSocket::OPEN => {
+                    socket.send_with_str(HEARTBEAT).ok();
+                }
+                _ => {
+                    let client = client.clone();
+                    let event_id = event_id.clone();
+                    let lose = lose.clone();
+                    wasm_bindgen_futures::spawn_local(async move {
+                        let renewed = acquire(&client, &event_id, false).await;
+                        if let Err(FetchError::Status { status: 409, message }) = renewed {
+                            lose.emit(message.unwrap_or_else(|| LAPSED.to_string()));
+                        }
+                    });
+                }
+            })
+        };
+        let interval = gloo_utils::window()
+            .set_interval_with_callback_and_timeout_and_arguments_0(tick.as_ref().unchecked_ref(), HEARTBEAT_MS)
+            .unwrap_or_default();
+
+        Self { client, event_id: event_id.to_string(), socket, interval, lost, _on_message: on_message, _tick: tick }
+    }
+}
+
+impl Drop for Heartbeat {
+    fn drop(&mut self) {
+        gloo_utils::window().clear_interval_with_handle(self.interval);
+        if let Some(socket) = &self.socket {
+            socket.close().ok();
+        }
+        // Closing the socket releases the lock too; this covers a socket
+        // that never opened. A lock that was taken over isn't ours to
+        // release, and a takeover of our own may be racing this.
+        if !self.lost.get() {
+            let client = self.client.clone();
+            let event_id = self.event_id.clone();
+            wasm_bindgen_futures::spawn_local(async move { release(&client, &event_id).await });
+        }
+    }
+}
+
+/// Holds the event's edit lock while an editor has its page open. When
+/// someone else has it, says who and offers to take over.
+#[function_component(EditLock)]
+pub fn edit_lock(props: &EditLockProps) -> Html {
+    // What to tell the viewer while someone else holds the lock.
+    let held_elsewhere = use_state(|| Option::<String>::None);
+    let takeovers = use_state(|| 0u32);
+    let client = use_api();
+
+    {
+        let held_elsewhere = held_elsewhere.clone();
+        let on_change = props.on_change.clone();
+        use_effect_with((props.event_id.clone(), *takeovers), move |(event_id, takeovers): &(String, u32)| {
+            let heartbeat = Rc::new(RefCell::new(Option::<Heartbeat>::None));
+            // Set on cleanup, so a lock that arrives after the page moved
+            // on is handed straight back.
+            let gone = Rc::new(Cell::new(false));
+            let lose = {
+                let held_elsewhere = held_elsewhere.clone();
+                let on_change = on_change.clone();
+                Callback::from(move |message: String| {
+                    held_elsewhere.set(Some(message));
+                    on_change.emit(false);
+                })
+            };
+            {
+                let event_id = event_id.clone();
+                let takeover = *takeovers > 0;
+                let heartbeat = heartbeat.clone();
+                let gone = gone.clone();
+                wasm_bindgen_futures::spawn_local(async move {
+                    match acquire(&client, &event_id, takeover).await {
+                        Ok(_) if gone.get() => release(&client, &event_id).await,
+                        Ok(_) => {
+                            held_elsewhere.set(None);
+                            on_change.emit(true);
+                            *heartbeat.borrow_mut() = Some(Heartbeat::start(client, &event_id, lose));
+                        }
+                        Err(FetchError::Status { status: 409, message }) => {
+                            lose.emit(message.unwrap_or_else(|| "Someone else is editing this event.".to_string()));
+                        }
+                        // Editing works without the lock; it only warns others.
+                        _ => {}
+                    }
+                });
+            }
+            move || {
+                gone.set(true);
+                heartbeat.borrow_mut().take();
+            }
+        });
+    }
+
+    let take_over = {
+        let takeovers = takeovers.clone();
+        Callback::from(move |_| takeovers.set(*takeovers + 1))
+    };
+
+    html! {
+        if let Some(message) = &*held_elsewhere {
+            <div role="alert" class="alert alert-warning mb-4">
+                <span>{message}</span>
+                <button class="btn btn-sm" onclick={take_over}>{"Take over"}</button>
+            </div>
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
+
+    use super::*;
+    use crate::api::Method;
+    use crate::testing::{self, MockClient};
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[wasm_bindgen_test]
+    async fn says_who_holds_the_lock_and_offers_to_take_over() {
+        let client = Rc::new(MockClient::default().reply(
+            Method::Post,
+            "/api/events/e1/lock",
+            409,
+            serde_json::json!({ "error": "Bob is editing this event." }),
+        ));
+        let states = Rc::new(RefCell::new(Vec::new()));
+        let on_change = {
+            let states = states.clone();
+            Callback::from(move |holding: bool| states.borrow_mut().push(holding))
+        };
+        let root = testing::mount(client.clone(), html! { <EditLock event_id="e1" {on_change} /> }).await;
+
+        assert_eq!(testing::texts(&root, "[role=alert] span"), ["Bob is editing this event."]);
+        assert_eq!(*states.borrow(), [false]);
+
+        let button = root.query_selector("[role=alert] button").unwrap().unwrap();
+        button.dyn_into::<web_sys::HtmlElement>().unwrap().click();
+        testing::settle().await;
+        let attempts = client.requests().into_iter().filter(|(method, _)| *method == Method::Post).count();
+        assert_eq!(attempts, 2);
+    }
+}
@@ -0,0 +1,69 @@
+use yew::{
+    function_component, hook, html, use_context, use_effect_with, Callback, Children, ContextProvider, Html,
+    Properties,
+};
+
+use crate::components::notifications::use_notify;
+
+/// Page shown if the app panics. A panic leaves the Wasm module unusable,
+/// so it is written straight into the DOM instead of rendered by Yew.
+const CRASH_PAGE: &str = r#"<div class="min-h-screen bg-base-200 flex items-center justify-center">
+    <div class="card bg-base-100 shadow-xl max-w-md">
+        <div class="card-body">
+            <h1 class="card-title">{"Something went wrong"}</h1>
+            <p>{"The page stopped working. Reloading usually fixes it."}</p>
+            <div class="card-actions justify-end">
+                <button class="btn btn-primary" onclick="location.reload()">{"Reload"}</button>
+            </div>
+        </div>
+    </div>
+</div>"#;
+
+/// Shows an error that isn't part of any page's content, such as a save
+/// that failed, as an error toast.
+#[derive(Clone, PartialEq)]
+pub struct ErrorReporter(Callback<String>);
+
+impl ErrorReporter {
+    pub fn report(&self, message: impl ToString) {
+        self.0.emit(message.to_string());
+    }
+}
+
+/// The reporter of the enclosing `ErrorBoundary`; errors are dropped
+/// outside of one.
+#[hook]
+pub fn use_error_reporter() -> ErrorReporter {
+    use_context::<ErrorReporter>().unwrap_or_else(|| ErrorReporter(Callback::noop()))
+}
+
+#[derive(Properties, PartialEq)]
+pub struct ErrorBoundaryProps {
+    pub children: Children,
+}
+
+/// Catches what pages don't handle themselves: errors reported through
+/// `use_error_reporter`, shown as toasts of the enclosing `Notifications`,
+/// and panics, which replace the app with a page offering a reload.
+#[function_component(ErrorBoundary)]
+pub fn error_boundary(props: &ErrorBoundaryProps) -> Html {
+    let notify = use_notify();
+
+    use_effect_with((), |_| {
+        let default_hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            default_hook(info);
+            if let Some(root) = gloo_utils::document().get_element_by_id("app") {
+                root.set_inner_html(CRASH_PAGE);
+            }
+        }));
+    });
+
+    let reporter = ErrorReporter(Callback::from(move |message: String| notify.error(message)));
+
+    html! {
+        <ContextProvider<ErrorReporter> context={reporter}>
+            {props.children.clone()}
+        </ContextProvider<ErrorReporter>>
+    }
+}
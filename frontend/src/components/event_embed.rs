@@ -0,0 +1,145 @@
+use serde::Deserialize;
+use yew::{function_component, html, use_effect_with, use_node_ref, use_state, Callback, Html, Properties};
+
+use crate::api::Request;
+use crate::components::api_provider::use_api;
+use crate::components::error_boundary::use_error_reporter;
+
+#[derive(Deserialize, Clone, PartialEq)]
+struct Embed {
+    url: String,
+    /// "youtube", "vimeo" or "soundcloud".
+    provider: String,
+    player_url: String,
+    title: Option<String>,
+    author_name: Option<String>,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct EventEmbedProps {
+    pub event_id: String,
+    /// Editors can set, replace and remove the player.
+    pub can_edit: bool,
+}
+
+/// The event's video or audio player, sized to the width of the page.
+#[function_component(EventEmbed)]
+pub fn event_embed(props: &EventEmbedProps) -> Html {
+    let embed = use_state(|| Option::<Embed>::None);
+    let saving = use_state(|| false);
+    let input = use_node_ref();
+    let errors = use_error_reporter();
+    let client = use_api();
+    let url = format!("/api/events/{}/embed", props.event_id);
+
+    {
+        let embed = embed.clone();
+        let client = client.clone();
+        use_effect_with(url.clone(), move |url: &String| {
+            let url = url.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                // A page without its player is still useful.
+                if let Ok(found) = client.get::<Option<Embed>>(&url).await {
+                    embed.set(found);
+                }
+            });
+        });
+    }
+
+    let save = {
+        let embed = embed.clone();
+        let saving = saving.clone();
+        let input = input.clone();
+        let errors = errors.clone();
+        let url = url.clone();
+        let client = client.clone();
+        Callback::from(move |e: yew::SubmitEvent| {
+            e.prevent_default();
+            let Some(field) = input.cast::<web_sys::HtmlInputElement>() else {
+                return;
+            };
+            let body = serde_json::json!({ "url": field.value().trim() });
+            let embed = embed.clone();
+            let saving = saving.clone();
+            let errors = errors.clone();
+            let url = url.clone();
+            saving.set(true);
+            let client = client.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match client.send_json::<Embed>(Request::put(&url), &body).await {
+                    Ok(saved) => {
+                        field.set_value("");
+                        embed.set(Some(saved));
+                    }
+                    Err(error) => errors.report(error),
+                }
+                saving.set(false);
+            });
+        })
+    };
+    let remove = {
+        let embed = embed.clone();
+        let client = client.clone();
+        Callback::from(move |_| {
+            let embed = embed.clone();
+            let errors = errors.clone();
+            let url = url.clone();
+            let client = client.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match client.send::<serde::de::IgnoredAny>(Request::delete(&url)).await {
+                    Ok(_) => embed.set(None),
+                    Err(error) => errors.report(error),
+                }
+            });
+        })
+    };
+
+    html! {
+        <>
+            if let Some(current) = &*embed {
+                <figure class="mt-4">
+                    if current.provider == "soundcloud" {
+                        <iframe
+                            src={current.player_url.clone()}
+                            title={current.title.clone().unwrap_or_else(|| "Audio player".to_string())}
+                            class="w-full h-40 rounded-lg"
+                            loading="lazy"
+                        />
+                    } else {
+                        <iframe
+                            src={current.player_url.clone()}
+                            title={current.title.clone().unwrap_or_else(|| "Video player".to_string())}
+                            class="w-full aspect-video rounded-lg"
+                            allow="fullscreen; picture-in-picture"
+                            loading="lazy"
+                        />
+                    }
+                    <figcaption class="text-sm opacity-70 mt-1">
+                        <a class="link" href={current.url.clone()} target="_blank" rel="noopener noreferrer">
+                            {current.title.clone().unwrap_or_else(|| current.url.clone())}
+                        </a>
+                        if let Some(author) = &current.author_name {
+                            {format!(" · {}", author)}
+                        }
+                        if props.can_edit {
+                            <button class="btn btn-ghost btn-xs ml-2" onclick={remove}>{"Remove"}</button>
+                        }
+                    </figcaption>
+                </figure>
+            }
+            if props.can_edit {
+                <form class="flex gap-2 mt-2" onsubmit={save}>
+                    <input
+                        ref={input}
+                        type="url"
+                        class="input input-bordered input-sm flex-1"
+                        aria-label="Video or audio link"
+                        placeholder={if embed.is_some() { "Replace with another YouTube, Vimeo or SoundCloud link" } else { "Add a YouTube, Vimeo or SoundCloud link" }}
+                        required=true
+                    />
+                    <button class="btn btn-sm" type="submit" disabled={*saving}>{"Embed"}</button>
+                </form>
+            }
+        </>
+    }
+}
@@ -0,0 +1,70 @@
+use yew::{function_component, html, Callback, Html, Properties};
+
+/// One value of a facet: what it filters by, how it reads, and how many
+/// events have it.
+#[derive(Clone, PartialEq)]
+pub struct Facet {
+    pub value: String,
+    pub label: String,
+    pub count: i64,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct FacetListProps {
+    pub title: String,
+    /// In the order to list them.
+    pub facets: Vec<Facet>,
+    /// The chosen value, if any.
+    pub selected: Option<String>,
+    /// Called with the value picked, or `None` when the choice is cleared.
+    pub on_change: Callback<Option<String>>,
+}
+
+/// Sidebar list of one facet's values with event counts, where one value
+/// at a time can be picked. Hidden while there is nothing to list.
+#[function_component(FacetList)]
+pub fn facet_list(props: &FacetListProps) -> Html {
+    if props.facets.is_empty() && props.selected.is_none() {
+        return html! {};
+    }
+
+    let pick = |value: &str| {
+        let on_change = props.on_change.clone();
+        let value = value.to_string();
+        let picked = props.selected.as_deref() == Some(value.as_str());
+        Callback::from(move |_| on_change.emit((!picked).then(|| value.clone())))
+    };
+    let clear = props.on_change.reform(|_| None);
+
+    html! {
+        <aside class="w-full md:w-56 shrink-0" aria-label={format!("Filter by {}", props.title.to_lowercase())}>
+            <div class="card card-compact bg-base-100 shadow">
+                <div class="card-body">
+                    <div class="flex items-center justify-between">
+                        <h2 class="font-semibold">{&props.title}</h2>
+                        if props.selected.is_some() {
+                            <button class="btn btn-ghost btn-xs" onclick={clear}>{"Clear"}</button>
+                        }
+                    </div>
+                    <ul class="menu menu-sm p-0">
+                        {props.facets.iter().map(|facet| {
+                            let picked = props.selected.as_deref() == Some(facet.value.as_str());
+                            html! {
+                                <li key={facet.value.clone()}>
+                                    <button
+                                        class={if picked { "active flex" } else { "flex" }}
+                                        aria-pressed={picked.to_string()}
+                                        onclick={pick(&facet.value)}
+                                    >
+                                        <span class="flex-1 truncate text-left">{&facet.label}</span>
+                                        <span class="badge badge-ghost badge-sm">{facet.count}</span>
+                                    </button>
+                                </li>
+                            }
+                        }).collect::<Html>()}
+                    </ul>
+                </div>
+            </div>
+        </aside>
+    }
+}
@@ -0,0 +1,417 @@
+use serde::Deserialize;
+use yew::{function_component, html, use_effect_with, use_node_ref, use_state, Callback, Html, Properties, TargetCast};
+
+use crate::api::{self, Request};
+use crate::components::api_provider::use_api;
+use crate::components::cover_crop::CoverCrop;
+use crate::components::error_boundary::use_error_reporter;
+
+#[derive(Deserialize, Clone, PartialEq)]
+struct EventImage {
+    id: String,
+    url: String,
+    alt: Option<String>,
+    caption: Option<String>,
+    /// How the image is framed as the cover.
+    #[serde(default)]
+    crop: Option<Vec<f64>>,
+}
+
+/// Answer to `POST /api/uploads`.
+#[derive(Deserialize)]
+struct Uploaded {
+    url: String,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct GalleryProps {
+    pub event_id: String,
+    /// Editors can add, caption, reorder and remove images.
+    pub can_edit: bool,
+    /// The event's `image_url`; the image with it is the cover.
+    #[prop_or_default]
+    pub cover: Option<String>,
+    /// Shown instead while the gallery is empty, such as the event's single
+    /// image from before it had one.
+    #[prop_or_default]
+    pub fallback: Html,
+}
+
+/// The event's images, one at a time. The strip scrolls sideways with snap
+/// points, so it swipes on touch screens; the arrows do the same elsewhere.
+#[function_component(Gallery)]
+pub fn gallery(props: &GalleryProps) -> Html {
+    let images = use_state(Vec::<EventImage>::new);
+    let current = use_state(|| 0usize);
+    let saving = use_state(|| false);
+    let cover = use_state(|| props.cover.clone());
+    // Id of the image whose framing is being chosen.
+    let framing = use_state(|| Option::<String>::None);
+    let strip = use_node_ref();
+    let url_input = use_node_ref();
+    let file_input = use_node_ref();
+    let alt_input = use_node_ref();
+    let caption_input = use_node_ref();
+    let errors = use_error_reporter();
+    let client = use_api();
+    let url = format!("/api/events/{}/images", props.event_id);
+
+    {
+        let images = images.clone();
+        let client = client.clone();
+        use_effect_with(url.clone(), move |url: &String| {
+            let url = url.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                // Without the gallery the page falls back to the event's image.
+                if let Ok(found) = client.get::<Vec<EventImage>>(&url).await {
+                    images.set(found);
+                }
+            });
+        });
+    }
+
+    // Every change answers with the whole gallery in its new order.
+    let apply = {
+        let images = images.clone();
+        let errors = errors.clone();
+        let client = client.clone();
+        move |request: Request, body: Option<serde_json::Value>| {
+            let images = images.clone();
+            let errors = errors.clone();
+            let client = client.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                let result = match body {
+                    Some(body) => client.send_json::<Vec<EventImage>>(request, &body).await,
+                    None => client.send::<Vec<EventImage>>(request).await,
+                };
+                match result {
+                    Ok(updated) => images.set(updated),
+                    Err(error) => errors.report(error),
+                }
+            });
+        }
+    };
+
+    let go_to = {
+        let strip = strip.clone();
+        Callback::from(move |index: usize| {
+            if let Some(strip) = strip.cast::<web_sys::Element>() {
+                strip.set_scroll_left(index as i32 * strip.client_width());
+            }
+        })
+    };
+    let on_scroll = {
+        let strip = strip.clone();
+        let current = current.clone();
+        Callback::from(move |_: yew::Event| {
+            if let Some(strip) = strip.cast::<web_sys::Element>() {
+                let width = strip.client_width().max(1);
+                current.set(((strip.scroll_left() + width / 2) / width) as usize);
+            }
+        })
+    };
+
+    let add = {
+        let images = images.clone();
+        let cover = cover.clone();
+        let saving = saving.clone();
+        let url_input = url_input.clone();
+        let file_input = file_input.clone();
+        let alt_input = alt_input.clone();
+        let caption_input = caption_input.clone();
+        let errors = errors.clone();
+        let url = url.clone();
+        let client = client.clone();
+        Callback::from(move |e: yew::SubmitEvent| {
+            e.prevent_default();
+            let (Some(link), Some(picker), Some(alt), Some(caption)) = (
+                url_input.cast::<web_sys::HtmlInputElement>(),
+                file_input.cast::<web_sys::HtmlInputElement>(),
+                alt_input.cast::<web_sys::HtmlInputElement>(),
+                caption_input.cast::<web_sys::HtmlInputElement>(),
+            ) else {
+                return;
+            };
+            let file = picker.files().and_then(|files| files.get(0));
+            let linked = link.value().trim().to_string();
+            if file.is_none() && linked.is_empty() {
+                return;
+            }
+            let images = images.clone();
+            let cover = cover.clone();
+            let saving = saving.clone();
+            let errors = errors.clone();
+            let url = url.clone();
+            saving.set(true);
+            let client = client.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                // A chosen file is uploaded first and used instead of the link.
+                let source = match file {
+                    Some(file) => {
+                        let request = Request::post("/api/uploads").header("Content-Type", &file.type_());
+                        client.send_body::<Uploaded>(request, file).await.map(|uploaded| uploaded.url)
+                    }
+                    None => Ok(linked),
+                };
+                let added = match source {
+                    Ok(source) => {
+                        let body = serde_json::json!({ "url": source, "alt": alt.value(), "caption": caption.value() });
+                        client.send_json::<Vec<EventImage>>(Request::post(&url), &body).await
+                    }
+                    Err(error) => Err(error),
+                };
+                match added {
+                    Ok(updated) => {
+                        // An event's first image becomes its cover.
+                        if cover.is_none() {
+                            cover.set(updated.first().map(|image| image.url.clone()));
+                        }
+                        for field in [&link, &picker, &alt, &caption] {
+                            field.set_value("");
+                        }
+                        images.set(updated);
+                    }
+                    Err(error) => errors.report(error),
+                }
+                saving.set(false);
+            });
+        })
+    };
+    let move_image = {
+        let images = images.clone();
+        let apply = apply.clone();
+        let go_to = go_to.clone();
+        let url = url.clone();
+        move |from: usize, to: usize| {
+            let images = images.clone();
+            let apply = apply.clone();
+            let go_to = go_to.clone();
+            let url = url.clone();
+            Callback::from(move |_: yew::MouseEvent| {
+                let mut ids: Vec<String> = images.iter().map(|image| image.id.clone()).collect();
+                ids.swap(from, to);
+                apply(Request::put(&format!("{}/order", url)), Some(serde_json::json!({ "ids": ids })));
+                go_to.emit(to);
+            })
+        }
+    };
+    let edit_text = {
+        let apply = apply.clone();
+        let url = url.clone();
+        move |id: &str, field: &'static str| {
+            let apply = apply.clone();
+            let image_url = format!("{}/{}", url, id);
+            Callback::from(move |e: yew::Event| {
+                let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+                let mut body = serde_json::Map::new();
+                body.insert(field.to_string(), input.value().into());
+                apply(Request::patch(&image_url), Some(serde_json::Value::Object(body)));
+            })
+        }
+    };
+    let frame = {
+        let framing = framing.clone();
+        move |id: &str| {
+            let framing = framing.clone();
+            let id = id.to_string();
+            Callback::from(move |_: yew::MouseEvent| framing.set(Some(id.clone())))
+        }
+    };
+    let close_framing = {
+        let framing = framing.clone();
+        Callback::from(move |_| framing.set(None))
+    };
+    let set_cover = {
+        let apply = apply.clone();
+        let cover = cover.clone();
+        let framing = framing.clone();
+        let url = format!("/api/events/{}/cover", props.event_id);
+        move |image: &EventImage| {
+            let apply = apply.clone();
+            let cover = cover.clone();
+            let framing = framing.clone();
+            let url = url.clone();
+            let (id, image_url) = (image.id.clone(), image.url.clone());
+            Callback::from(move |crop: Option<Vec<f64>>| {
+                apply(Request::put(&url), Some(serde_json::json!({ "image_id": id, "crop": crop })));
+                cover.set(Some(image_url.clone()));
+                framing.set(None);
+            })
+        }
+    };
+    let remove = {
+        let images = images.clone();
+        let cover = cover.clone();
+        let url = url.clone();
+        move |id: &str| {
+            let apply = apply.clone();
+            let images = images.clone();
+            let cover = cover.clone();
+            let id = id.to_string();
+            let image_url = format!("{}/{}", url, id);
+            Callback::from(move |_: yew::MouseEvent| {
+                // The server hands the cover on to the first image left.
+                let removed = images.iter().find(|image| image.id == id).map(|image| image.url.clone());
+                if removed.is_some() && *cover == removed {
+                    cover.set(images.iter().find(|image| image.id != id).map(|image| image.url.clone()));
+                }
+                apply(Request::delete(&image_url), None)
+            })
+        }
+    };
+
+    let count = images.len();
+    let shown = (*current).min(count.saturating_sub(1));
+
+    html! {
+        <>
+            if count == 0 {
+                {props.fallback.clone()}
+            } else {
+                <section class="mt-4" aria-roledescription="carousel" aria-label="Images">
+                    <div class="relative">
+                        <div
+                            ref={strip}
+                            class="carousel w-full rounded-lg scroll-smooth"
+                            onscroll={on_scroll}
+                        >
+                            {images.iter().enumerate().map(|(index, image)| html! {
+                                <figure
+                                    key={image.id.clone()}
+                                    class="carousel-item w-full flex-col"
+                                    aria-roledescription="slide"
+                                    aria-label={format!("{} of {}", index + 1, count)}
+                                >
+                                    <img
+                                        src={api::image_src(&image.url)}
+                                        alt={image.alt.clone().unwrap_or_default()}
+                                        class="w-full object-contain max-h-[32rem]"
+                                        loading="lazy"
+                                    />
+                                    if let Some(caption) = &image.caption {
+                                        <figcaption class="text-sm opacity-70 mt-1">{caption}</figcaption>
+                                    }
+                                </figure>
+                            }).collect::<Html>()}
+                        </div>
+                        if count > 1 {
+                            <div class="absolute inset-x-2 top-1/2 -translate-y-1/2 flex justify-between pointer-events-none">
+                                <button
+                                    class="btn btn-circle btn-sm pointer-events-auto"
+                                    aria-label="Previous image"
+                                    disabled={shown == 0}
+                                    onclick={go_to.reform(move |_: yew::MouseEvent| shown.saturating_sub(1))}
+                                >
+                                    {"❮"}
+                                </button>
+                                <button
+                                    class="btn btn-circle btn-sm pointer-events-auto"
+                                    aria-label="Next image"
+                                    disabled={shown + 1 == count}
+                                    onclick={go_to.reform(move |_: yew::MouseEvent| shown + 1)}
+                                >
+                                    {"❯"}
+                                </button>
+                            </div>
+                        }
+                    </div>
+                    if count > 1 {
+                        <p class="text-xs text-center opacity-70 mt-1" aria-live="polite">
+                            {format!("{} / {}", shown + 1, count)}
+                        </p>
+                    }
+                    if props.can_edit {
+                        <ul class="mt-2 space-y-2">
+                            {images.iter().enumerate().map(|(index, image)| html! {
+                                <li key={image.id.clone()} class="flex flex-wrap items-center gap-2">
+                                    <img src={api::image_src(&image.url)} alt="" class="w-12 h-12 object-cover rounded" />
+                                    <input
+                                        class="input input-bordered input-xs flex-1"
+                                        aria-label="Caption"
+                                        placeholder="Caption"
+                                        maxlength="500"
+                                        value={image.caption.clone().unwrap_or_default()}
+                                        onchange={edit_text(&image.id, "caption")}
+                                    />
+                                    <input
+                                        class={if image.alt.is_none() { "input input-bordered input-warning input-xs flex-1" } else { "input input-bordered input-xs flex-1" }}
+                                        aria-label="Image description"
+                                        placeholder="Describe it for people who can't see it"
+                                        maxlength="255"
+                                        value={image.alt.clone().unwrap_or_default()}
+                                        onchange={edit_text(&image.id, "alt")}
+                                    />
+                                    <button
+                                        class="btn btn-ghost btn-xs"
+                                        aria-label="Move earlier"
+                                        disabled={index == 0}
+                                        onclick={move_image(index, index.saturating_sub(1))}
+                                    >
+                                        {"←"}
+                                    </button>
+                                    <button
+                                        class="btn btn-ghost btn-xs"
+                                        aria-label="Move later"
+                                        disabled={index + 1 == count}
+                                        onclick={move_image(index, (index + 1).min(count - 1))}
+                                    >
+                                        {"→"}
+                                    </button>
+                                    if cover.as_deref() == Some(image.url.as_str()) {
+                                        <span class="badge badge-primary badge-sm">{"Cover"}</span>
+                                        <button class="btn btn-ghost btn-xs" onclick={frame(&image.id)}>{"Frame"}</button>
+                                    } else {
+                                        <button class="btn btn-ghost btn-xs" onclick={frame(&image.id)}>{"Make cover"}</button>
+                                    }
+                                    <button class="btn btn-ghost btn-xs" onclick={remove(&image.id)}>{"Remove"}</button>
+                                </li>
+                            }).collect::<Html>()}
+                        </ul>
+                    }
+                </section>
+            }
+            if props.can_edit {
+                <form class="flex flex-wrap gap-2 mt-2" onsubmit={add}>
+                    <input
+                        ref={url_input}
+                        type="url"
+                        class="input input-bordered input-sm flex-1"
+                        aria-label="Image link"
+                        placeholder="Add an image link"
+                        maxlength="512"
+                    />
+                    <input
+                        ref={file_input}
+                        type="file"
+                        accept="image/png, image/jpeg, image/gif, image/webp"
+                        class="file-input file-input-bordered file-input-sm flex-1"
+                        aria-label="Or upload an image"
+                    />
+                    <input
+                        ref={alt_input}
+                        class="input input-bordered input-sm flex-1"
+                        aria-label="Image description"
+                        placeholder="Description"
+                        maxlength="255"
+                    />
+                    <input
+                        ref={caption_input}
+                        class="input input-bordered input-sm flex-1"
+                        aria-label="Caption"
+                        placeholder="Caption"
+                        maxlength="500"
+                    />
+                    <button class="btn btn-sm" type="submit" disabled={*saving}>{"Add image"}</button>
+                </form>
+            }
+            if let Some(image) = framing.as_ref().and_then(|id| images.iter().find(|image| &image.id == id)) {
+                <CoverCrop
+                    url={image.url.clone()}
+                    crop={image.crop.clone()}
+                    on_save={set_cover(image)}
+                    on_close={close_framing}
+                />
+            }
+        </>
+    }
+}
@@ -0,0 +1,166 @@
+use serde::Deserialize;
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use yew::{function_component, hook, html, use_effect_with, use_node_ref, use_state, Html, Properties};
+
+use crate::auth;
+use crate::components::api_provider::use_api;
+
+/// Name of the honeypot field, which only bots fill in.
+const HONEYPOT: &str = "website";
+/// Id of the provider's script once it is on the page.
+const SCRIPT_ID: &str = "challenge-script";
+
+/// The widget signed-out visitors solve, as `/api/anonymous` names it.
+#[derive(Deserialize, Clone, PartialEq)]
+pub struct Challenge {
+    provider: String,
+    site_key: String,
+}
+
+/// What signed-out visitors must do to comment or report here.
+#[derive(Deserialize, Clone, PartialEq)]
+pub struct Policy {
+    /// Whether they may at all.
+    pub enabled: bool,
+    pub challenge: Option<Challenge>,
+}
+
+/// The instance's policy for signed-out visitors; `None` while it loads,
+/// when it fails to, and for signed-in viewers.
+#[hook]
+pub fn use_guest_policy() -> Option<Policy> {
+    let client = use_api();
+    let policy = use_state(|| Option::<Policy>::None);
+    {
+        let policy = policy.clone();
+        use_effect_with((), move |_| {
+            if auth::token().is_none() {
+                wasm_bindgen_futures::spawn_local(async move {
+                    policy.set(client.get::<Policy>("/api/anonymous").await.ok());
+                });
+            }
+        });
+    }
+    (*policy).clone()
+}
+
+/// The provider's script, and the global it defines.
+fn script(provider: &str) -> Option<(&'static str, &'static str)> {
+    match provider {
+        "hcaptcha" => Some(("https://js.hcaptcha.com/1/api.js?render=explicit", "hcaptcha")),
+        "turnstile" => Some(("https://challenges.cloudflare.com/turnstile/v0/api.js?render=explicit", "turnstile")),
+        _ => None,
+    }
+}
+
+/// Puts the provider's widget in `container`, loading its script first
+/// when no other form has.
+fn render_widget(container: web_sys::Element, challenge: &Challenge) -> Option<()> {
+    let (src, global) = script(&challenge.provider)?;
+    let window = web_sys::window()?;
+    let document = window.document()?;
+    let site_key = challenge.site_key.clone();
+    let render = move || -> Option<()> {
+        let provider = js_sys::Reflect::get(&window, &JsValue::from_str(global)).ok().filter(JsValue::is_object)?;
+        let render: js_sys::Function = js_sys::Reflect::get(&provider, &JsValue::from_str("render")).ok()?.dyn_into().ok()?;
+        let params = js_sys::Object::new();
+        js_sys::Reflect::set(&params, &JsValue::from_str("sitekey"), &JsValue::from_str(&site_key)).ok()?;
+        render.call2(&provider, &container, &params).ok()?;
+        Some(())
+    };
+    if render().is_some() {
+        return Some(());
+    }
+
+    let script = match document.get_element_by_id(SCRIPT_ID) {
+        Some(script) => script,
+        None => {
+            let script = document.create_element("script").ok()?;
+            script.set_id(SCRIPT_ID);
+            script.set_attribute("src", src).ok()?;
+            script.set_attribute("async", "").ok()?;
+            document.body()?.append_child(&script).ok()?;
+            script
+        }
+    };
+    let on_load = Closure::once_into_js(move || {
+        render();
+    });
+    script.add_event_listener_with_callback("load", on_load.unchecked_ref()).ok()
+}
+
+#[derive(Properties, PartialEq)]
+pub struct GuestFieldsProps {
+    pub challenge: Option<Challenge>,
+}
+
+/// The honeypot and the challenge widget, for a form signed-out visitors
+/// send; `proof` reads them back.
+#[function_component(GuestFields)]
+pub fn guest_fields(props: &GuestFieldsProps) -> Html {
+    let widget = use_node_ref();
+    {
+        let widget = widget.clone();
+        use_effect_with(props.challenge.clone(), move |challenge: &Option<Challenge>| {
+            if let (Some(challenge), Some(container)) = (challenge, widget.cast::<web_sys::Element>()) {
+                render_widget(container, challenge);
+            }
+        });
+    }
+
+    html! {
+        <>
+            // Off screen rather than hidden, since some bots skip hidden fields.
+            <label class="absolute -left-[9999px]" aria-hidden="true">
+                {"Leave this empty"}
+                <input type="text" name={HONEYPOT} tabindex="-1" autocomplete="off" />
+            </label>
+            if props.challenge.is_some() {
+                <div ref={widget} />
+            }
+        </>
+    }
+}
+
+/// What `GuestFields` in `form` hold, as the fields the API expects next
+/// to the form's own.
+pub fn proof(form: &web_sys::Element) -> serde_json::Value {
+    let value = |selector: &str| {
+        let field = form.query_selector(selector).ok().flatten()?;
+        match field.dyn_into::<web_sys::HtmlInputElement>() {
+            Ok(input) => Some(input.value()),
+            Err(field) => field.dyn_into::<web_sys::HtmlTextAreaElement>().ok().map(|area| area.value()),
+        }
+    };
+    serde_json::json!({
+        "website": value(&format!("[name={}]", HONEYPOT)).unwrap_or_default(),
+        // Each provider's widget adds its token as a hidden field.
+        "challenge_token": value("[name=h-captcha-response], [name=cf-turnstile-response]"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
+
+    use super::*;
+    use crate::testing::{self, MockClient};
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[wasm_bindgen_test]
+    async fn proof_carries_the_honeypot() {
+        let root = testing::mount(Rc::new(MockClient::default()), html! {
+            <form><GuestFields challenge={None::<Challenge>} /></form>
+        })
+        .await;
+        let form = root.query_selector("form").unwrap().unwrap();
+        assert_eq!(proof(&form), serde_json::json!({ "website": "", "challenge_token": null }));
+
+        let honeypot = root.query_selector("[name=website]").unwrap().unwrap();
+        honeypot.dyn_into::<web_sys::HtmlInputElement>().unwrap().set_value("http://spam.example");
+        assert_eq!(proof(&form)["website"], "http://spam.example");
+    }
+}
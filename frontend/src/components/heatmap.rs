@@ -0,0 +1,55 @@
+use yew::{function_component, html, Html, Properties};
+
+#[derive(Properties, PartialEq)]
+pub struct HeatmapProps {
+    pub title: String,
+    pub rows: Vec<String>,
+    pub columns: Vec<String>,
+    /// `values[row][column]`.
+    pub values: Vec<Vec<i64>>,
+}
+
+/// Grid of counts shaded by their share of the largest value.
+#[function_component(Heatmap)]
+pub fn heatmap(props: &HeatmapProps) -> Html {
+    let max = props.values.iter().flatten().copied().max().unwrap_or(0).max(1);
+
+    html! {
+        <div class="card bg-base-100 shadow">
+            <div class="card-body overflow-x-auto">
+                <h3 class="card-title text-base">{&props.title}</h3>
+                if props.rows.is_empty() {
+                    <p class="text-sm opacity-70">{"No data yet"}</p>
+                } else {
+                    <table class="text-xs">
+                        <thead>
+                            <tr>
+                                <th></th>
+                                {props.columns.iter().map(|column| html! {
+                                    <th class="px-1 font-normal [writing-mode:vertical-rl] rotate-180">{column}</th>
+                                }).collect::<Html>()}
+                            </tr>
+                        </thead>
+                        <tbody>
+                            {props.rows.iter().zip(&props.values).map(|(row, values)| html! {
+                                <tr>
+                                    <th class="pr-2 text-right font-normal whitespace-nowrap">{row}</th>
+                                    {values.iter().map(|value| {
+                                        let alpha = *value as f64 / max as f64;
+                                        html! {
+                                            <td class="w-6 h-6 text-center"
+                                                style={format!("background-color: rgba(59, 130, 246, {:.2})", alpha)}
+                                                title={value.to_string()}>
+                                                {if *value > 0 { value.to_string() } else { String::new() }}
+                                            </td>
+                                        }
+                                    }).collect::<Html>()}
+                                </tr>
+                            }).collect::<Html>()}
+                        </tbody>
+                    </table>
+                }
+            </div>
+        </div>
+    }
+}
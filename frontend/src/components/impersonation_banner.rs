@@ -0,0 +1,45 @@
+use wasm_bindgen::JsValue;
+use yew::{function_component, html, Callback, Html};
+
+use crate::api::Request;
+use crate::auth;
+use crate::components::api_provider::use_api;
+
+/// One of the API's UTC times as the viewer's clock shows it.
+fn local_time(utc: &str) -> String {
+    let date = js_sys::Date::new(&JsValue::from_str(&format!("{}Z", utc.trim_end_matches('Z'))));
+    date.to_locale_time_string("default").into()
+}
+
+/// Shown on every page while an admin is impersonating someone, with the
+/// way back to their own account.
+#[function_component(ImpersonationBanner)]
+pub fn impersonation_banner() -> Html {
+    let client = use_api();
+    let Some(impersonating) = auth::impersonating() else {
+        return html! {};
+    };
+
+    let onclick = Callback::from(move |_| {
+        let client = client.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            // Fails once the impersonation has lapsed, which ends it just the same.
+            client.send::<serde::de::IgnoredAny>(Request::post("/api/impersonation/end")).await.ok();
+            auth::end_impersonation();
+            gloo_utils::window().location().set_href("/admin/users").ok();
+        });
+    });
+
+    html! {
+        <div class="alert alert-warning rounded-none justify-center" role="status">
+            <span>
+                {format!(
+                    "You are viewing the app as {} until {}. Changes are turned off.",
+                    impersonating.username,
+                    local_time(&impersonating.expires_at)
+                )}
+            </span>
+            <button class="btn btn-sm" {onclick}>{"End impersonation"}</button>
+        </div>
+    }
+}
@@ -0,0 +1,83 @@
+use js_sys::{Function, Reflect};
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use web_sys::Event;
+use yew::{function_component, html, use_effect_with, use_state, Callback, Html};
+
+/// Shows an "Install app" button once the browser fires `beforeinstallprompt`.
+///
+/// The deferred event is kept around so the native install dialog can be
+/// triggered from a user gesture, which browsers require.
+#[function_component(InstallPrompt)]
+pub fn install_prompt() -> Html {
+    let deferred = use_state(|| Option::<Event>::None);
+
+    {
+        let deferred = deferred.clone();
+        use_effect_with((), move |_| {
+            let window = web_sys::window().expect("no window");
+
+            let on_prompt = {
+                let deferred = deferred.clone();
+                Closure::<dyn Fn(Event)>::new(move |event: Event| {
+                    // Suppress the mini-infobar; we show our own button instead.
+                    event.prevent_default();
+                    deferred.set(Some(event));
+                })
+            };
+            let on_installed = {
+                let deferred = deferred.clone();
+                Closure::<dyn Fn(Event)>::new(move |_: Event| deferred.set(None))
+            };
+
+            window
+                .add_event_listener_with_callback(
+                    "beforeinstallprompt",
+                    on_prompt.as_ref().unchecked_ref(),
+                )
+                .ok();
+            window
+                .add_event_listener_with_callback(
+                    "appinstalled",
+                    on_installed.as_ref().unchecked_ref(),
+                )
+                .ok();
+
+            move || {
+                window
+                    .remove_event_listener_with_callback(
+                        "beforeinstallprompt",
+                        on_prompt.as_ref().unchecked_ref(),
+                    )
+                    .ok();
+                window
+                    .remove_event_listener_with_callback(
+                        "appinstalled",
+                        on_installed.as_ref().unchecked_ref(),
+                    )
+                    .ok();
+            }
+        });
+    }
+
+    let Some(event) = (*deferred).clone() else {
+        return html! {};
+    };
+
+    let onclick = {
+        let deferred = deferred.clone();
+        Callback::from(move |_| {
+            // `BeforeInstallPromptEvent` isn't in web-sys, so call `prompt()` by name.
+            if let Ok(prompt) = Reflect::get(&event, &JsValue::from_str("prompt")) {
+                if let Some(prompt) = prompt.dyn_ref::<Function>() {
+                    prompt.call0(&event).ok();
+                }
+            }
+            // The event can only be used once.
+            deferred.set(None);
+        })
+    };
+
+    html! {
+        <button class="btn btn-outline btn-sm" {onclick}>{"Install app"}</button>
+    }
+}
@@ -0,0 +1,103 @@
+use serde::Deserialize;
+use web_sys::Storage;
+use yew::{function_component, html, use_effect_with, use_state, Callback, Html, Properties};
+
+use crate::api::Request;
+use crate::auth;
+use crate::components::api_provider::use_api;
+use crate::components::error_boundary::use_error_reporter;
+
+const VISITOR_KEY: &str = "visitor_id";
+
+#[derive(Deserialize, Clone, PartialEq)]
+struct LikeState {
+    liked: bool,
+    likes: i64,
+}
+
+fn storage() -> Option<Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+/// The random id that lets anonymous visitors like events, made on first
+/// use and kept on this device.
+fn visitor_id() -> Option<String> {
+    let storage = storage()?;
+    if let Ok(Some(id)) = storage.get_item(VISITOR_KEY) {
+        return Some(id);
+    }
+    let id: String = (0..32)
+        .map(|_| char::from_digit((js_sys::Math::random() * 16.0) as u32, 16).unwrap_or('0'))
+        .collect();
+    storage.set_item(VISITOR_KEY, &id).ok()?;
+    Some(id)
+}
+
+/// Signed-in users like as themselves; anyone else as this device.
+fn as_liker(request: Request) -> Request {
+    match (auth::token(), visitor_id()) {
+        (None, Some(id)) => request.header("X-Visitor-Id", &id),
+        _ => request,
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct LikeButtonProps {
+    pub event_id: String,
+}
+
+/// Heart toggle with the like count. Renders nothing for events that
+/// cannot be liked, such as drafts and events on private timelines.
+#[function_component(LikeButton)]
+pub fn like_button(props: &LikeButtonProps) -> Html {
+    let state = use_state(|| Option::<LikeState>::None);
+    let errors = use_error_reporter();
+    let client = use_api();
+    let url = format!("/api/events/{}/like", props.event_id);
+
+    {
+        let state = state.clone();
+        let client = client.clone();
+        use_effect_with(url.clone(), move |url: &String| {
+            let url = url.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                // Fetched with the visitor header, so `liked` is about this device.
+                let request = as_liker(Request::get(&url));
+                state.set(client.send::<LikeState>(request).await.ok());
+            });
+        });
+    }
+
+    let Some(current) = (*state).clone() else {
+        return html! {};
+    };
+
+    let toggle = {
+        let state = state.clone();
+        let client = client.clone();
+        Callback::from(move |_| {
+            let state = state.clone();
+            let errors = errors.clone();
+            let url = url.clone();
+            let client = client.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match client.send::<LikeState>(as_liker(Request::post(&url))).await {
+                    Ok(next) => state.set(Some(next)),
+                    Err(error) => errors.report(error),
+                }
+            });
+        })
+    };
+
+    html! {
+        <button
+            class={if current.liked { "btn btn-sm btn-secondary" } else { "btn btn-sm btn-outline" }}
+            aria-pressed={current.liked.to_string()}
+            aria-label={if current.liked { "Unlike" } else { "Like" }}
+            onclick={toggle}
+        >
+            {if current.liked { "♥" } else { "♡" }}
+            <span>{current.likes}</span>
+        </button>
+    }
+}
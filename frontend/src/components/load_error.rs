@@ -0,0 +1,33 @@
+use yew::{function_component, hook, html, use_state, Callback, Html, Properties};
+
+use crate::api::FetchError;
+
+#[derive(Properties, PartialEq)]
+pub struct LoadErrorProps {
+    pub error: FetchError,
+    pub onretry: Callback<()>,
+}
+
+/// Shown in place of content that failed to load.
+#[function_component(LoadError)]
+pub fn load_error(props: &LoadErrorProps) -> Html {
+    let onclick = props.onretry.reform(|_| ());
+    html! {
+        <div class="alert alert-error">
+            <span>{props.error.to_string()}</span>
+            <button class="btn btn-sm" {onclick}>{"Retry"}</button>
+        </div>
+    }
+}
+
+/// A counter to put in a fetch effect's dependencies, and a callback that
+/// bumps it to run the fetch again.
+#[hook]
+pub fn use_retry() -> (u32, Callback<()>) {
+    let attempt = use_state(|| 0u32);
+    let retry = {
+        let attempt = attempt.clone();
+        Callback::from(move |_| attempt.set(*attempt + 1))
+    };
+    (*attempt, retry)
+}
@@ -0,0 +1,217 @@
+use yew::{function_component, html, use_effect_with, use_state, Callback, Html, Properties, TargetCast};
+use serde::Deserialize;
+
+use crate::api::{FetchError, Request};
+use crate::components::api_provider::use_api;
+use crate::components::error_boundary::use_error_reporter;
+use crate::components::modal::{ConfirmDialog, Confirmation};
+use crate::components::notifications::use_notify;
+
+#[derive(Deserialize, Clone, PartialEq)]
+struct Member {
+    user_id: String,
+    username: String,
+    role: String,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct MembersProps {
+    pub timeline_id: String,
+    /// Show invite, role and remove controls (owners only).
+    pub can_manage: bool,
+}
+
+const ROLES: [(&str, &str); 2] = [("viewer", "Can view"), ("editor", "Can edit")];
+
+/// Collaborators of a timeline and, for its owner, controls to manage them.
+#[function_component(Members)]
+pub fn members(props: &MembersProps) -> Html {
+    let members = use_state(Vec::<Member>::new);
+    let invitee = use_state(String::new);
+    let role = use_state(|| "viewer".to_string());
+    let error = use_state(|| Option::<String>::None);
+    let errors = use_error_reporter();
+    let client = use_api();
+    let notify = use_notify();
+    let confirming = use_state(|| Option::<Confirmation>::None);
+
+    let url = format!("/api/timelines/{}/members", props.timeline_id);
+
+    let reload = {
+        let members = members.clone();
+        let url = url.clone();
+        let client = client.clone();
+        Callback::from(move |_: ()| {
+            let members = members.clone();
+            let url = url.clone();
+            let client = client.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                if let Ok(members_data) = client.get::<Vec<Member>>(&url).await {
+                    members.set(members_data);
+                }
+            });
+        })
+    };
+
+    {
+        let reload = reload.clone();
+        use_effect_with(props.timeline_id.clone(), move |_| reload.emit(()));
+    }
+
+    let oninput = {
+        let invitee = invitee.clone();
+        Callback::from(move |e: yew::InputEvent| {
+            let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+            invitee.set(input.value());
+        })
+    };
+
+    let onrole = {
+        let role = role.clone();
+        Callback::from(move |e: yew::Event| {
+            let select: web_sys::HtmlSelectElement = e.target_unchecked_into();
+            role.set(select.value());
+        })
+    };
+
+    let invite = {
+        let url = url.clone();
+        let invitee = invitee.clone();
+        let role = role.clone();
+        let error = error.clone();
+        let notify = notify.clone();
+        let reload = reload.clone();
+        let client = client.clone();
+        Callback::from(move |e: yew::SubmitEvent| {
+            e.prevent_default();
+            let url = url.clone();
+            let body = serde_json::json!({ "user": *invitee, "role": *role });
+            let invitee = invitee.clone();
+            let error = error.clone();
+            let notify = notify.clone();
+            let reload = reload.clone();
+            let client = client.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match client.send_json::<serde::de::IgnoredAny>(Request::post(&url), &body).await {
+                    Ok(_) => {
+                        notify.success(format!("Invited {}", *invitee));
+                        invitee.set(String::new());
+                        error.set(None);
+                        reload.emit(());
+                    }
+                    Err(FetchError::Status { message: None, .. }) => {
+                        error.set(Some("Could not invite that user".to_string()));
+                    }
+                    Err(other) => error.set(Some(other.to_string())),
+                }
+            });
+        })
+    };
+
+    let change_role = |member: &Member| {
+        let url = format!("{}/{}", url, member.user_id);
+        let reload = reload.clone();
+        let errors = errors.clone();
+        let client = client.clone();
+        Callback::from(move |e: yew::Event| {
+            let select: web_sys::HtmlSelectElement = e.target_unchecked_into();
+            let url = url.clone();
+            let body = serde_json::json!({ "role": select.value() });
+            let reload = reload.clone();
+            let errors = errors.clone();
+            let client = client.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                if let Err(error) = client.send_json::<serde::de::IgnoredAny>(Request::put(&url), &body).await {
+                    errors.report(error);
+                }
+                reload.emit(());
+            });
+        })
+    };
+
+    let remove = |member: &Member| {
+        let client = client.clone();
+        let url = format!("{}/{}", url, member.user_id);
+        let removed = format!("Removed {}", member.username);
+        let reload = reload.clone();
+        let errors = errors.clone();
+        let notify = notify.clone();
+        let remove = Callback::from(move |_| {
+            let url = url.clone();
+            let removed = removed.clone();
+            let reload = reload.clone();
+            let errors = errors.clone();
+            let notify = notify.clone();
+            let client = client.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match client.send::<serde::de::IgnoredAny>(Request::delete(&url)).await {
+                    Ok(_) => notify.success(removed),
+                    Err(error) => errors.report(error),
+                }
+                reload.emit(());
+            });
+        });
+        let confirmation = Confirmation::new(
+            format!("Remove {}?", member.username),
+            "They will lose access to this timeline unless invited again.",
+            "Remove",
+            remove,
+        );
+        let confirming = confirming.clone();
+        Callback::from(move |_| confirming.set(Some(confirmation.clone())))
+    };
+    let close_confirmation = {
+        let confirming = confirming.clone();
+        Callback::from(move |_| confirming.set(None))
+    };
+
+    let role_label = |role: &str| {
+        ROLES.iter().find(|(value, _)| *value == role).map_or(role.to_string(), |(_, label)| label.to_string())
+    };
+
+    html! {
+        <div class="card bg-base-100 shadow mt-6">
+            <div class="card-body">
+                <h2 class="card-title">{"Members"}</h2>
+                if members.is_empty() {
+                    <p class="opacity-70">{"No collaborators yet."}</p>
+                }
+                <ul class="divide-y">
+                    {members.iter().map(|member| html! {
+                        <li class="flex items-center justify-between py-2">
+                            <span>{&member.username}</span>
+                            if props.can_manage {
+                                <div class="flex gap-2">
+                                    <select class="select select-bordered select-sm" onchange={change_role(member)}>
+                                        {ROLES.iter().map(|(value, label)| html! {
+                                            <option value={*value} selected={member.role == *value}>{*label}</option>
+                                        }).collect::<Html>()}
+                                    </select>
+                                    <button class="btn btn-ghost btn-sm" onclick={remove(member)}>{"Remove"}</button>
+                                </div>
+                            } else {
+                                <span class="badge">{role_label(&member.role)}</span>
+                            }
+                        </li>
+                    }).collect::<Html>()}
+                </ul>
+                if props.can_manage {
+                    <form class="flex flex-wrap gap-2 mt-2" onsubmit={invite}>
+                        <input class="input input-bordered input-sm flex-1" placeholder="Username or email"
+                            value={(*invitee).clone()} {oninput} />
+                        <select class="select select-bordered select-sm" onchange={onrole}>
+                            {ROLES.iter().map(|(value, label)| html! {
+                                <option value={*value} selected={*role == *value}>{*label}</option>
+                            }).collect::<Html>()}
+                        </select>
+                        <button class="btn btn-primary btn-sm" type="submit">{"Invite"}</button>
+                    </form>
+                    if let Some(message) = &*error {
+                        <div class="alert alert-error mt-2">{message}</div>
+                    }
+                }
+                <ConfirmDialog confirmation={(*confirming).clone()} on_close={close_confirmation} />
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,145 @@
+use yew::{
+    function_component, html, use_effect_with, use_node_ref, use_state, Callback, Html, PointerEvent, Properties,
+};
+use web_sys::HtmlElement;
+
+use crate::components::api_provider::use_api;
+use crate::histogram::{self, Bucket, Granularity};
+use crate::time_scale::{days_from_civil, TimeScale};
+
+const HEIGHT: f64 = 48.0;
+/// Most bars drawn; the histogram's granularity is picked to stay under it.
+const MAX_BARS: f64 = 150.0;
+
+#[derive(Properties, PartialEq)]
+pub struct MinimapProps {
+    /// Start dates of all events, in days; they set the minimap's extent.
+    pub days: Vec<f64>,
+    /// Count this timeline's events; all public events' when unset.
+    #[prop_or_default]
+    pub timeline_id: Option<String>,
+    /// The main timeline's current view.
+    pub view: TimeScale,
+    /// Called with the new view when the viewport window is dragged.
+    pub on_change: Callback<TimeScale>,
+}
+
+/// Density histogram of the whole timeline with a draggable window showing
+/// (and moving) the main view.
+#[function_component(Minimap)]
+pub fn minimap(props: &MinimapProps) -> Html {
+    let strip = use_node_ref();
+    // Pointer offset from the window's left edge while dragging it.
+    let grab = use_state(|| Option::<f64>::None);
+    let buckets = use_state(|| (Granularity::Year, Vec::<Bucket>::new()));
+    let client = use_api();
+
+    let width = props.view.width;
+    let full = TimeScale::fit(props.days.iter().copied(), width);
+    let granularity = Granularity::fitting(full.span(), MAX_BARS);
+
+    {
+        let buckets = buckets.clone();
+        let client = client.clone();
+        // Whole days, so the fetch isn't repeated for rounding noise.
+        let extent = (full.start.floor() as i64, full.end.ceil() as i64);
+        use_effect_with((props.timeline_id.clone(), granularity, extent), move |(timeline_id, granularity, (from, to))| {
+            let (timeline_id, granularity, range) = (timeline_id.clone(), *granularity, (*from as f64, *to as f64));
+            wasm_bindgen_futures::spawn_local(async move {
+                // Without counts the minimap still works as a scrollbar.
+                if let Ok(fetched) = histogram::fetch(&client, granularity, timeline_id.as_deref(), Some(range)).await {
+                    buckets.set((granularity, fetched));
+                }
+            });
+        });
+    }
+
+    let (bucket_granularity, bucket_counts) = &*buckets;
+    let max = bucket_counts.iter().map(|bucket| bucket.count).max().unwrap_or(0).max(1) as f64;
+    let bars = bucket_counts.iter().filter(|bucket| bucket.count > 0).map(|bucket| {
+        let left = full.x(days_from_civil(bucket.start, 1, 1) as f64);
+        let right = full.x(days_from_civil(bucket.start + bucket_granularity.years(), 1, 1) as f64);
+        let height = bucket.count as f64 / max * (HEIGHT - 4.0);
+        html! {
+            <rect
+                x={format!("{:.1}", left)}
+                y={format!("{:.1}", HEIGHT - height)}
+                width={format!("{:.1}", (right - left - 1.0).max(1.0))}
+                height={format!("{:.1}", height)}
+                fill="#93c5fd"
+            />
+        }
+    }).collect::<Html>();
+
+    let window_left = full.x(props.view.start).clamp(0.0, width);
+    let window_right = full.x(props.view.end).clamp(0.0, width);
+
+    let strip_x = {
+        let strip = strip.clone();
+        move |client_x: i32| {
+            strip
+                .cast::<HtmlElement>()
+                .map(|el| client_x as f64 - el.get_bounding_client_rect().left())
+                .unwrap_or(0.0)
+        }
+    };
+
+    // Keeps the view's span and moves its start so the window's left edge
+    // sits at `left`.
+    let move_to = {
+        let view = props.view;
+        let on_change = props.on_change.clone();
+        move |left: f64| {
+            let start = full.day_at(left);
+            on_change.emit(TimeScale { start, end: start + view.span(), width: view.width });
+        }
+    };
+
+    let onpointerdown = {
+        let grab = grab.clone();
+        let strip = strip.clone();
+        let strip_x = strip_x.clone();
+        let move_to = move_to.clone();
+        let window_width = full.x(props.view.end) - full.x(props.view.start);
+        let window_start = full.x(props.view.start);
+        Callback::from(move |e: PointerEvent| {
+            if let Some(el) = strip.cast::<HtmlElement>() {
+                el.set_pointer_capture(e.pointer_id()).ok();
+            }
+            let x = strip_x(e.client_x());
+            if x >= window_start && x <= window_start + window_width {
+                grab.set(Some(x - window_start));
+            } else {
+                // Clicking outside the window centres the view there.
+                move_to(x - window_width / 2.0);
+                grab.set(Some(window_width / 2.0));
+            }
+        })
+    };
+
+    let onpointermove = {
+        let grab = grab.clone();
+        Callback::from(move |e: PointerEvent| {
+            if let Some(offset) = *grab {
+                move_to(strip_x(e.client_x()) - offset);
+            }
+        })
+    };
+
+    let onpointerup = {
+        let grab = grab.clone();
+        Callback::from(move |_: PointerEvent| grab.set(None))
+    };
+
+    html! {
+        <div class="timeline-minimap" ref={strip} {onpointerdown} {onpointermove} {onpointerup}>
+            <svg viewBox={format!("0 0 {:.0} {}", width, HEIGHT)} preserveAspectRatio="none" class="w-full h-12">
+                {bars}
+            </svg>
+            <div
+                class="timeline-minimap-window"
+                style={format!("left: {:.1}px; width: {:.1}px", window_left, (window_right - window_left).max(4.0))}
+            ></div>
+        </div>
+    }
+}
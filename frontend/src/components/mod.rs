@@ -1,3 +1,44 @@
 pub mod timeline;
-pub mod event_card;
-pub mod event_detail;
+pub mod api_provider;
+pub mod bar_chart;
+pub mod breadcrumbs;
+pub mod bulk_toolbar;
+pub mod category_filter;
+pub mod comments;
+pub mod cover_crop;
+pub mod custom_fields;
+pub mod data_export;
+pub mod delete_account;
+pub mod edit_lock;
+pub mod error_boundary;
+pub mod event_embed;
+pub mod facet_list;
+pub mod gallery;
+pub mod guest;
+pub mod heatmap;
+pub mod impersonation_banner;
+pub mod install_prompt;
+pub mod like_button;
+pub mod load_error;
+pub mod members;
+pub mod minimap;
+pub mod modal;
+pub mod move_dialog;
+pub mod notification_bell;
+pub mod notifications;
+pub mod org_switcher;
+pub mod period_rail;
+pub mod periods;
+pub mod popover;
+pub mod reactions;
+pub mod report_event;
+pub mod saved_searches;
+pub mod sessions;
+pub mod skeleton;
+pub mod skip_link;
+pub mod storage_usage;
+pub mod stories;
+pub mod story_editor;
+pub mod trend_chart;
+pub mod use_template;
+pub mod virtual_grid;
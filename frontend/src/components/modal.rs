@@ -0,0 +1,186 @@
+use wasm_bindgen::{closure::Closure, JsCast};
+use web_sys::HtmlElement;
+use yew::{
+    create_portal, function_component, hook, html, use_effect_with, use_node_ref, Callback, Children, Html,
+    KeyboardEvent, NodeRef, Properties,
+};
+
+/// What Tab moves between inside a dialog.
+const FOCUSABLE: &str = "a[href], button:not([disabled]), input:not([disabled]), select:not([disabled]), \
+                         textarea:not([disabled]), [tabindex]:not([tabindex='-1'])";
+
+fn focusable(dialog: &NodeRef) -> Vec<HtmlElement> {
+    let Some(nodes) = dialog.cast::<web_sys::Element>().and_then(|dialog| dialog.query_selector_all(FOCUSABLE).ok())
+    else {
+        return Vec::new();
+    };
+    (0..nodes.length())
+        .filter_map(|index| nodes.item(index))
+        .filter_map(|node| node.dyn_into::<HtmlElement>().ok())
+        .collect()
+}
+
+#[derive(Properties, PartialEq)]
+pub struct ModalProps {
+    pub title: String,
+    /// Called on Escape and on clicks outside the dialog.
+    pub on_close: Callback<()>,
+    pub children: Children,
+    /// Buttons along the bottom of the dialog.
+    #[prop_or_default]
+    pub actions: Html,
+}
+
+/// Dialog drawn over the page, rendered into `<body>` so no container
+/// clips it. Render it only while it is open: opening moves focus into
+/// it, Tab and Shift+Tab cycle within it, and closing puts focus back
+/// where it was.
+#[function_component(Modal)]
+pub fn modal(props: &ModalProps) -> Html {
+    let dialog = use_node_ref();
+
+    {
+        let dialog = dialog.clone();
+        use_effect_with((), move |_| {
+            let previous = gloo_utils::document()
+                .active_element()
+                .and_then(|element| element.dyn_into::<HtmlElement>().ok());
+            if let Some(first) = focusable(&dialog).first() {
+                first.focus().ok();
+            }
+            move || {
+                if let Some(previous) = previous {
+                    previous.focus().ok();
+                }
+            }
+        });
+    }
+
+    let onkeydown = {
+        let dialog = dialog.clone();
+        let on_close = props.on_close.clone();
+        Callback::from(move |e: KeyboardEvent| match e.key().as_str() {
+            "Escape" => {
+                e.prevent_default();
+                on_close.emit(());
+            }
+            "Tab" => {
+                let items = focusable(&dialog);
+                let (Some(first), Some(last)) = (items.first(), items.last()) else {
+                    e.prevent_default();
+                    return;
+                };
+                let active = gloo_utils::document().active_element();
+                let wrap_to = if e.shift_key() && active.as_ref() == Some(&**first) {
+                    Some(last)
+                } else if !e.shift_key() && active.as_ref() == Some(&**last) {
+                    Some(first)
+                } else {
+                    None
+                };
+                if let Some(target) = wrap_to {
+                    e.prevent_default();
+                    target.focus().ok();
+                }
+            }
+            _ => {}
+        })
+    };
+    let close = props.on_close.reform(|_| ());
+
+    create_portal(
+        html! {
+            <div class="modal modal-open">
+                <div ref={dialog} class="modal-box" role="dialog" aria-modal="true" aria-labelledby="modal-title" {onkeydown}>
+                    <h3 id="modal-title" class="font-bold text-lg">{&props.title}</h3>
+                    {props.children.clone()}
+                    <div class="modal-action">{props.actions.clone()}</div>
+                </div>
+                <div class="modal-backdrop" onclick={close}></div>
+            </div>
+        },
+        gloo_utils::body().into(),
+    )
+}
+
+/// A question put to the user before something that can't be undone.
+#[derive(Clone, PartialEq)]
+pub struct Confirmation {
+    pub title: String,
+    pub message: String,
+    /// Label of the button that goes ahead, e.g. "Delete".
+    pub confirm_label: String,
+    pub on_confirm: Callback<()>,
+}
+
+impl Confirmation {
+    pub fn new(title: impl ToString, message: impl ToString, confirm_label: impl ToString, on_confirm: Callback<()>) -> Self {
+        Self {
+            title: title.to_string(),
+            message: message.to_string(),
+            confirm_label: confirm_label.to_string(),
+            on_confirm,
+        }
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct ConfirmDialogProps {
+    /// The question being asked, if any; nothing is shown without one.
+    pub confirmation: Option<Confirmation>,
+    /// Called once the user has answered either way.
+    pub on_close: Callback<()>,
+}
+
+/// Asks a `Confirmation`, running its action only if the user agrees.
+/// Cancel has focus first, so a stray Enter changes nothing.
+#[function_component(ConfirmDialog)]
+pub fn confirm_dialog(props: &ConfirmDialogProps) -> Html {
+    let Some(confirmation) = &props.confirmation else {
+        return html! {};
+    };
+    let confirm = {
+        let on_confirm = confirmation.on_confirm.clone();
+        let on_close = props.on_close.clone();
+        Callback::from(move |_| {
+            on_close.emit(());
+            on_confirm.emit(());
+        })
+    };
+    let cancel = props.on_close.reform(|_| ());
+    let actions = html! {
+        <>
+            <button class="btn" onclick={cancel}>{"Cancel"}</button>
+            <button class="btn btn-error" onclick={confirm}>{&confirmation.confirm_label}</button>
+        </>
+    };
+
+    html! {
+        <Modal title={confirmation.title.clone()} on_close={props.on_close.clone()} {actions}>
+            <p class="py-4">{&confirmation.message}</p>
+        </Modal>
+    }
+}
+
+/// Has the browser ask before leaving or reloading the page while `dirty`.
+/// Links in the app are ordinary page loads, so this covers them too.
+#[hook]
+pub fn use_leave_warning(dirty: bool) {
+    use_effect_with(dirty, |dirty: &bool| {
+        let window = gloo_utils::window();
+        let listener = dirty.then(|| {
+            let listener = Closure::<dyn Fn(web_sys::Event)>::new(|e: web_sys::Event| e.prevent_default());
+            window
+                .add_event_listener_with_callback("beforeunload", listener.as_ref().unchecked_ref())
+                .ok();
+            listener
+        });
+        move || {
+            if let Some(listener) = listener {
+                window
+                    .remove_event_listener_with_callback("beforeunload", listener.as_ref().unchecked_ref())
+                    .ok();
+            }
+        }
+    });
+}
@@ -0,0 +1,134 @@
+use serde::Deserialize;
+use yew::{function_component, html, use_effect_with, use_state, Callback, Html, Properties, TargetCast};
+
+use crate::api::Request;
+use crate::components::api_provider::use_api;
+use crate::components::error_boundary::use_error_reporter;
+use crate::components::modal::Modal;
+use crate::components::notifications::use_notify;
+
+#[derive(Deserialize, Clone, PartialEq)]
+struct TimelineOption {
+    id: String,
+    title: String,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct MoveDialogProps {
+    /// Events to move; they all go to the same timeline.
+    pub event_ids: Vec<String>,
+    pub on_close: Callback<()>,
+    /// Called with the target timeline's id once the events have moved.
+    pub on_moved: Callback<String>,
+}
+
+/// Picks one of the timelines the user can edit and moves the events
+/// there. The server moves all of them or none.
+#[function_component(MoveDialog)]
+pub fn move_dialog(props: &MoveDialogProps) -> Html {
+    let timelines = use_state(|| Option::<Vec<TimelineOption>>::None);
+    let target = use_state(String::new);
+    let saving = use_state(|| false);
+    let errors = use_error_reporter();
+    let client = use_api();
+    let notify = use_notify();
+
+    {
+        let timelines = timelines.clone();
+        let target = target.clone();
+        let errors = errors.clone();
+        let client = client.clone();
+        use_effect_with((), move |_| {
+            wasm_bindgen_futures::spawn_local(async move {
+                match client.get::<Vec<TimelineOption>>("/api/timelines?editable=true").await {
+                    Ok(options) => {
+                        if let Some(first) = options.first() {
+                            target.set(first.id.clone());
+                        }
+                        timelines.set(Some(options));
+                    }
+                    Err(error) => errors.report(error),
+                }
+            });
+        });
+    }
+
+    let onchange = {
+        let target = target.clone();
+        Callback::from(move |e: yew::Event| {
+            let select: web_sys::HtmlSelectElement = e.target_unchecked_into();
+            target.set(select.value());
+        })
+    };
+
+    let submit = {
+        let event_ids = props.event_ids.clone();
+        let target = target.clone();
+        let saving = saving.clone();
+        let title = timelines
+            .iter()
+            .flatten()
+            .find(|timeline| timeline.id == *target)
+            .map(|timeline| timeline.title.clone())
+            .unwrap_or_default();
+        let on_moved = props.on_moved.clone();
+        let on_close = props.on_close.clone();
+        let client = client.clone();
+        Callback::from(move |_| {
+            let body = serde_json::json!({ "event_ids": event_ids, "timeline_id": *target });
+            let count = event_ids.len();
+            let timeline_id = (*target).clone();
+            let title = title.clone();
+            let saving = saving.clone();
+            let errors = errors.clone();
+            let notify = notify.clone();
+            let on_moved = on_moved.clone();
+            let on_close = on_close.clone();
+            saving.set(true);
+            let client = client.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match client.send_json::<serde::de::IgnoredAny>(Request::post("/api/events/move"), &body).await {
+                    Ok(_) => {
+                        let events = if count == 1 { "event".to_string() } else { format!("{} events", count) };
+                        notify.success(format!("Moved {} to {}", events, title));
+                        on_moved.emit(timeline_id);
+                        on_close.emit(());
+                    }
+                    Err(error) => {
+                        saving.set(false);
+                        errors.report(error);
+                    }
+                }
+            });
+        })
+    };
+
+    let cancel = props.on_close.reform(|_| ());
+    let actions = html! {
+        <>
+            <button class="btn" onclick={cancel}>{"Cancel"}</button>
+            <button class="btn btn-primary" disabled={*saving || target.is_empty()} onclick={submit}>{"Move"}</button>
+        </>
+    };
+
+    html! {
+        <Modal title="Move to timeline" on_close={props.on_close.clone()} {actions}>
+            {match &*timelines {
+                None => html! { <p class="py-4 opacity-70">{"Loading timelines…"}</p> },
+                Some(options) if options.is_empty() => html! {
+                    <p class="py-4">{"You can't add events to any timeline yet."}</p>
+                },
+                Some(options) => html! {
+                    <label class="form-control py-4">
+                        <span class="label-text mb-1">{"Timeline"}</span>
+                        <select class="select select-bordered" {onchange}>
+                            {options.iter().map(|option| html! {
+                                <option value={option.id.clone()} selected={option.id == *target}>{&option.title}</option>
+                            }).collect::<Html>()}
+                        </select>
+                    </label>
+                },
+            }}
+        </Modal>
+    }
+}
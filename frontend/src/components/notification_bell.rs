@@ -0,0 +1,249 @@
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use serde::Deserialize;
+use wasm_bindgen::{closure::Closure, JsCast};
+use web_sys::{MessageEvent, WebSocket};
+use yew::{
+    function_component, html, use_effect_with, use_reducer, use_state, Callback, Html, Reducible,
+    UseReducerDispatcher,
+};
+
+use crate::api::{self, Request};
+use crate::components::api_provider::use_api;
+
+const NOTIFICATIONS_URL: &str = "/api/me/notifications";
+
+#[derive(Deserialize, Clone, PartialEq)]
+struct Notification {
+    id: String,
+    /// `mention` or `reply`.
+    kind: String,
+    actor: Option<String>,
+    event_id: Option<String>,
+    event_title: Option<String>,
+    comment_id: Option<String>,
+    /// The start of the comment.
+    excerpt: Option<String>,
+    read: bool,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Default)]
+struct Inbox {
+    unread: i64,
+    notifications: Vec<Notification>,
+}
+
+enum Action {
+    Loaded(Inbox),
+    /// Pushed over the socket.
+    Arrived(Notification),
+    ReadAll,
+}
+
+impl Reducible for Inbox {
+    type Action = Action;
+
+    fn reduce(self: Rc<Self>, action: Action) -> Rc<Self> {
+        let mut inbox = (*self).clone();
+        match action {
+            Action::Loaded(loaded) => inbox = loaded,
+            Action::Arrived(notification) => {
+                // Written just before the list loaded, so already in it.
+                if inbox.notifications.iter().any(|other| other.id == notification.id) {
+                    return self;
+                }
+                if !notification.read {
+                    inbox.unread += 1;
+                }
+                inbox.notifications.insert(0, notification);
+            }
+            Action::ReadAll => {
+                inbox.unread = 0;
+                inbox.notifications.iter_mut().for_each(|notification| notification.read = true);
+            }
+        }
+        Rc::new(inbox)
+    }
+}
+
+/// What happened, in a sentence.
+fn summary(notification: &Notification) -> String {
+    let actor = notification.actor.as_deref().unwrap_or("Someone");
+    let event = notification.event_title.as_deref().unwrap_or("an event");
+    match notification.kind.as_str() {
+        "mention" => format!("{} mentioned you on {}", actor, event),
+        "reply" => format!("{} replied to you on {}", actor, event),
+        _ => format!("News about {}", event),
+    }
+}
+
+fn link(notification: &Notification) -> Option<String> {
+    let event_id = notification.event_id.as_ref()?;
+    Some(match &notification.comment_id {
+        Some(comment_id) => format!("/events/{}#comment-{}", event_id, comment_id),
+        None => format!("/events/{}", event_id),
+    })
+}
+
+/// Receives the viewer's new notifications until dropped.
+struct Push {
+    socket: WebSocket,
+    _on_message: Closure<dyn Fn(MessageEvent)>,
+}
+
+impl Push {
+    fn open(dispatcher: UseReducerDispatcher<Inbox>) -> Option<Self> {
+        let socket = WebSocket::new(&api::socket_url(&format!("{}/ws", NOTIFICATIONS_URL))).ok()?;
+        let on_message = Closure::<dyn Fn(MessageEvent)>::new(move |e: MessageEvent| {
+            let notification = e.data().as_string().and_then(|text| serde_json::from_str(&text).ok());
+            if let Some(notification) = notification {
+                dispatcher.dispatch(Action::Arrived(notification));
+            }
+        });
+        socket.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+        Some(Self { socket, _on_message: on_message })
+    }
+}
+
+impl Drop for Push {
+    fn drop(&mut self) {
+        self.socket.close().ok();
+    }
+}
+
+/// The header's bell: how many notifications are unread, and a dropdown
+/// listing the latest. New ones arrive as they happen; opening the list
+/// marks them all read.
+#[function_component(NotificationBell)]
+pub fn notification_bell() -> Html {
+    let client = use_api();
+    let inbox = use_reducer(Inbox::default);
+    let open = use_state(|| false);
+
+    {
+        let dispatcher = inbox.dispatcher();
+        let client = client.clone();
+        use_effect_with((), move |_| {
+            let push = Rc::new(RefCell::new(Option::<Push>::None));
+            let gone = Rc::new(Cell::new(false));
+            {
+                let push = push.clone();
+                let gone = gone.clone();
+                wasm_bindgen_futures::spawn_local(async move {
+                    // Loading first also renews the session the socket signs in with.
+                    if let Ok(loaded) = client.get::<Inbox>(NOTIFICATIONS_URL).await {
+                        dispatcher.dispatch(Action::Loaded(loaded));
+                    }
+                    if !gone.get() {
+                        *push.borrow_mut() = Push::open(dispatcher);
+                    }
+                });
+            }
+            move || {
+                gone.set(true);
+                push.borrow_mut().take();
+            }
+        });
+    }
+
+    let toggle = {
+        let open = open.clone();
+        let dispatcher = inbox.dispatcher();
+        let unread = inbox.unread;
+        let client = client.clone();
+        Callback::from(move |_| {
+            if *open {
+                dispatcher.dispatch(Action::ReadAll);
+            } else if unread > 0 {
+                // Still shown as new until the list closes.
+                let client = client.clone();
+                wasm_bindgen_futures::spawn_local(async move {
+                    let request = Request::post(&format!("{}/read", NOTIFICATIONS_URL));
+                    client.send_json::<serde::de::IgnoredAny>(request, &serde_json::json!({})).await.ok();
+                });
+            }
+            open.set(!*open);
+        })
+    };
+
+    let label = match inbox.unread {
+        0 => "Notifications".to_string(),
+        1 => "Notifications, 1 unread".to_string(),
+        unread => format!("Notifications, {} unread", unread),
+    };
+
+    html! {
+        <div class={if *open { "dropdown dropdown-end dropdown-open" } else { "dropdown dropdown-end" }}>
+            <button class="btn btn-ghost btn-sm indicator" aria-label={label} aria-expanded={open.to_string()} onclick={toggle}>
+                {"🔔"}
+                if inbox.unread > 0 {
+                    <span class="indicator-item badge badge-primary badge-xs">{inbox.unread}</span>
+                }
+            </button>
+            if *open {
+                <ul class="dropdown-content menu z-50 mt-2 w-80 rounded-box bg-base-100 p-2 shadow">
+                    if inbox.notifications.is_empty() {
+                        <li class="p-2 opacity-70">{"Nothing yet."}</li>
+                    }
+                    {inbox.notifications.iter().map(|notification| html! {
+                        <li key={notification.id.clone()}>
+                            <a href={link(notification)} class={if notification.read { "" } else { "font-semibold" }}>
+                                <div class="flex flex-col items-start">
+                                    <span>{summary(notification)}</span>
+                                    if let Some(excerpt) = &notification.excerpt {
+                                        <span class="text-sm opacity-70 line-clamp-2">{excerpt}</span>
+                                    }
+                                </div>
+                            </a>
+                        </li>
+                    }).collect::<Html>()}
+                </ul>
+            }
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
+
+    use super::*;
+    use crate::api::Method;
+    use crate::testing::{self, MockClient};
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[wasm_bindgen_test]
+    async fn lists_mentions_and_marks_them_read_when_opened() {
+        let client = Rc::new(
+            MockClient::default()
+                .reply(
+                    Method::Get,
+                    NOTIFICATIONS_URL,
+                    200,
+                    serde_json::json!({
+                        "unread": 1,
+                        "notifications": [{
+                            "id": "n1", "kind": "mention", "actor": "ada", "event_id": "e1",
+                            "event_title": "Apollo 11", "comment_id": "c1", "excerpt": "@grace look at this",
+                            "read": false, "created_at": "2026-10-16T09:30:00",
+                        }],
+                    }),
+                )
+                .reply(Method::Post, "/api/me/notifications/read", 204, serde_json::Value::Null),
+        );
+        let root = testing::mount(client.clone(), html! { <NotificationBell /> }).await;
+        assert_eq!(testing::texts(&root, ".badge"), ["1"]);
+
+        let button = root.query_selector("button").unwrap().unwrap();
+        button.dyn_into::<web_sys::HtmlElement>().unwrap().click();
+        testing::settle().await;
+        assert_eq!(testing::texts(&root, "li a span:first-child"), ["ada mentioned you on Apollo 11"]);
+        let link = root.query_selector("li a").unwrap().unwrap();
+        assert_eq!(link.get_attribute("href").as_deref(), Some("/events/e1#comment-c1"));
+        assert!(client.requests().contains(&(Method::Post, "/api/me/notifications/read".to_string())));
+    }
+}
@@ -0,0 +1,195 @@
+use std::rc::Rc;
+
+use wasm_bindgen::{closure::Closure, JsCast};
+use yew::{
+    function_component, hook, html, use_context, use_mut_ref, use_reducer, Callback, Children, ContextProvider, Html,
+    Properties, Reducible, UseReducerDispatcher,
+};
+
+/// Toasts shown at once; older ones make way for new ones.
+const MAX_SHOWN: usize = 5;
+/// How long success and info toasts stay up. Errors stay until dismissed.
+const DISMISS_MS: i32 = 5_000;
+/// Toasts with an action stay longer, so there is time to use it.
+const ACTION_DISMISS_MS: i32 = 10_000;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Kind {
+    Success,
+    Error,
+    Info,
+}
+
+impl Kind {
+    fn alert_class(self) -> &'static str {
+        match self {
+            Kind::Success => "alert alert-success",
+            Kind::Error => "alert alert-error",
+            Kind::Info => "alert alert-info",
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub struct Notification {
+    pub kind: Kind,
+    pub message: String,
+    /// Button label and what it does, e.g. "Undo"; using it dismisses the
+    /// toast.
+    pub action: Option<(String, Callback<()>)>,
+}
+
+impl Notification {
+    pub fn success(message: impl ToString) -> Self {
+        Self { kind: Kind::Success, message: message.to_string(), action: None }
+    }
+
+    pub fn error(message: impl ToString) -> Self {
+        Self { kind: Kind::Error, message: message.to_string(), action: None }
+    }
+
+    pub fn info(message: impl ToString) -> Self {
+        Self { kind: Kind::Info, message: message.to_string(), action: None }
+    }
+
+    pub fn with_action(mut self, label: impl ToString, action: Callback<()>) -> Self {
+        self.action = Some((label.to_string(), action));
+        self
+    }
+
+    fn dismiss_after(&self) -> Option<i32> {
+        match (self.kind, &self.action) {
+            (Kind::Error, _) => None,
+            (_, Some(_)) => Some(ACTION_DISMISS_MS),
+            (_, None) => Some(DISMISS_MS),
+        }
+    }
+}
+
+/// Shows toasts in the enclosing `Notifications`.
+#[derive(Clone, PartialEq)]
+pub struct Notifier(Callback<Notification>);
+
+impl Notifier {
+    pub fn notify(&self, notification: Notification) {
+        self.0.emit(notification);
+    }
+
+    pub fn success(&self, message: impl ToString) {
+        self.notify(Notification::success(message));
+    }
+
+    pub fn error(&self, message: impl ToString) {
+        self.notify(Notification::error(message));
+    }
+
+    pub fn info(&self, message: impl ToString) {
+        self.notify(Notification::info(message));
+    }
+}
+
+/// The notifier of the enclosing `Notifications`; toasts are dropped
+/// outside of one.
+#[hook]
+pub fn use_notify() -> Notifier {
+    use_context::<Notifier>().unwrap_or_else(|| Notifier(Callback::noop()))
+}
+
+#[derive(Default, PartialEq)]
+struct Shown(Vec<(u32, Notification)>);
+
+enum Action {
+    Show(u32, Notification),
+    Dismiss(u32),
+}
+
+impl Reducible for Shown {
+    type Action = Action;
+
+    fn reduce(self: Rc<Self>, action: Action) -> Rc<Self> {
+        let mut shown = self.0.clone();
+        match action {
+            Action::Show(id, notification) => {
+                // The same message repeated (e.g. several saves in a row) is shown once.
+                if shown.iter().any(|(_, other)| other.kind == notification.kind && other.message == notification.message) {
+                    return self;
+                }
+                shown.push((id, notification));
+                if shown.len() > MAX_SHOWN {
+                    shown.remove(0);
+                }
+            }
+            Action::Dismiss(id) => shown.retain(|(other, _)| *other != id),
+        }
+        Rc::new(Shown(shown))
+    }
+}
+
+fn dismiss_later(dispatcher: UseReducerDispatcher<Shown>, id: u32, ms: i32) {
+    let dismiss = Closure::once_into_js(move || dispatcher.dispatch(Action::Dismiss(id)));
+    gloo_utils::window()
+        .set_timeout_with_callback_and_timeout_and_arguments_0(dismiss.unchecked_ref(), ms)
+        .ok();
+}
+
+#[derive(Properties, PartialEq)]
+pub struct NotificationsProps {
+    pub children: Children,
+}
+
+/// Provides `use_notify` to everything inside it and shows the toasts,
+/// newest at the bottom of the stack.
+#[function_component(Notifications)]
+pub fn notifications(props: &NotificationsProps) -> Html {
+    let shown = use_reducer(Shown::default);
+    let next_id = use_mut_ref(|| 0u32);
+
+    let notifier = {
+        let dispatcher = shown.dispatcher();
+        Notifier(Callback::from(move |notification: Notification| {
+            let id = {
+                let mut next_id = next_id.borrow_mut();
+                *next_id += 1;
+                *next_id
+            };
+            if let Some(ms) = notification.dismiss_after() {
+                dismiss_later(dispatcher.clone(), id, ms);
+            }
+            dispatcher.dispatch(Action::Show(id, notification));
+        }))
+    };
+
+    let dismiss = |id: u32| {
+        let dispatcher = shown.dispatcher();
+        Callback::from(move |_| dispatcher.dispatch(Action::Dismiss(id)))
+    };
+    let act = |id: u32, action: &Callback<()>| {
+        let dispatcher = shown.dispatcher();
+        let action = action.clone();
+        Callback::from(move |_| {
+            action.emit(());
+            dispatcher.dispatch(Action::Dismiss(id));
+        })
+    };
+
+    html! {
+        <ContextProvider<Notifier> context={notifier}>
+            {props.children.clone()}
+            if !shown.0.is_empty() {
+                <div class="toast toast-end z-50" role="status" aria-live="polite">
+                    {shown.0.iter().map(|(id, notification)| html! {
+                        <div key={*id} class={notification.kind.alert_class()}>
+                            <span>{&notification.message}</span>
+                            <div class="flex gap-1">
+                                if let Some((label, action)) = &notification.action {
+                                    <button class="btn btn-sm" onclick={act(*id, action)}>{label}</button>
+                                }
+                                <button class="btn btn-ghost btn-sm" aria-label="Dismiss" onclick={dismiss(*id)}>{"✕"}</button>
+                            </div>
+                        </div>
+                    }).collect::<Html>()}
+                </div>
+            }
+        </ContextProvider<Notifier>>
+    }
+}
@@ -0,0 +1,89 @@
+use serde::Deserialize;
+use web_sys::Storage;
+use yew::{function_component, html, use_effect_with, use_state, Callback, Html, Properties, TargetCast};
+
+use crate::components::api_provider::use_api;
+use crate::components::error_boundary::use_error_reporter;
+
+const ORGANIZATION_KEY: &str = "organization";
+
+#[derive(Deserialize, Clone, PartialEq)]
+struct Organization {
+    id: String,
+    name: String,
+}
+
+fn storage() -> Option<Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+/// The organization last picked on this device, if any.
+pub fn current() -> Option<String> {
+    storage()?.get_item(ORGANIZATION_KEY).ok()?
+}
+
+/// Remembers `organization` on this device; `None` means all of them.
+pub fn store(organization: Option<&str>) {
+    let Some(storage) = storage() else {
+        return;
+    };
+    match organization {
+        Some(id) => storage.set_item(ORGANIZATION_KEY, id).ok(),
+        None => storage.remove_item(ORGANIZATION_KEY).ok(),
+    };
+}
+
+#[derive(Properties, PartialEq)]
+pub struct OrgSwitcherProps {
+    pub selected: Option<String>,
+    /// Called with the picked organization's id, or `None` for all.
+    pub on_change: Callback<Option<String>>,
+}
+
+/// Picks the organization whose events are listed. Hidden for users who
+/// belong to none.
+#[function_component(OrgSwitcher)]
+pub fn org_switcher(props: &OrgSwitcherProps) -> Html {
+    let organizations = use_state(Vec::<Organization>::new);
+    let errors = use_error_reporter();
+    let client = use_api();
+
+    {
+        let organizations = organizations.clone();
+        use_effect_with((), move |_| {
+            wasm_bindgen_futures::spawn_local(async move {
+                match client.get::<Vec<Organization>>("/api/organizations").await {
+                    Ok(list) => organizations.set(list),
+                    Err(error) => errors.report(error),
+                }
+            });
+        });
+    }
+
+    if organizations.is_empty() {
+        return html! {};
+    }
+
+    let onchange = props.on_change.reform(|e: yew::Event| {
+        let select: web_sys::HtmlSelectElement = e.target_unchecked_into();
+        let value = select.value();
+        store((!value.is_empty()).then_some(value.as_str()));
+        (!value.is_empty()).then_some(value)
+    });
+    let selected = props.selected.clone().unwrap_or_default();
+
+    html! {
+        <select class="select select-bordered select-sm" aria-label="Organization" {onchange}>
+            <option value="" selected={selected.is_empty()}>{"All organizations"}</option>
+            {organizations.iter().map(|organization| html! {
+                <option
+                    key={organization.id.clone()}
+                    value={organization.id.clone()}
+                    selected={organization.id == selected}
+                >
+                    {&organization.name}
+                </option>
+            }).collect::<Html>()}
+        </select>
+    }
+}
@@ -0,0 +1,89 @@
+use yew::{function_component, html, use_effect_with, use_state, Callback, Html, Properties};
+
+use crate::components::api_provider::use_api;
+use crate::histogram::{self, period_label, Bucket, Granularity};
+use crate::time_scale::days_from_civil;
+
+#[derive(Properties, PartialEq)]
+pub struct PeriodRailProps {
+    /// List this timeline's periods; all public events' when unset.
+    #[prop_or_default]
+    pub timeline_id: Option<String>,
+    /// Called with the `(start, end)` days of the period picked.
+    pub on_select: Callback<(f64, f64)>,
+}
+
+/// Centuries and decades that have events, each zooming the timeline to
+/// that period when clicked.
+#[function_component(PeriodRail)]
+pub fn period_rail(props: &PeriodRailProps) -> Html {
+    let decades = use_state(Vec::<Bucket>::new);
+    let client = use_api();
+
+    {
+        let decades = decades.clone();
+        let client = client.clone();
+        use_effect_with(props.timeline_id.clone(), move |timeline_id: &Option<String>| {
+            let timeline_id = timeline_id.clone();
+            // The rail is a shortcut; the timeline works without it.
+            wasm_bindgen_futures::spawn_local(async move {
+                let fetched = histogram::fetch(&client, Granularity::Decade, timeline_id.as_deref(), None).await;
+                if let Ok(buckets) = fetched {
+                    decades.set(buckets);
+                }
+            });
+        });
+    }
+
+    if decades.iter().all(|decade| decade.count == 0) {
+        return html! {};
+    }
+
+    let select = |start: i64, granularity: Granularity| {
+        let on_select = props.on_select.clone();
+        Callback::from(move |_| {
+            let from = days_from_civil(start, 1, 1) as f64;
+            let to = days_from_civil(start + granularity.years(), 1, 1) as f64;
+            on_select.emit((from, to));
+        })
+    };
+
+    // Decades arrive sorted, so each century's decades are consecutive.
+    // Empty ones between them are left out.
+    let mut centuries: Vec<(i64, i64, Vec<&Bucket>)> = Vec::new();
+    for decade in decades.iter().filter(|decade| decade.count > 0) {
+        let century = decade.start.div_euclid(Granularity::Century.years()) * Granularity::Century.years();
+        match centuries.last_mut() {
+            Some((start, count, members)) if *start == century => {
+                *count += decade.count;
+                members.push(decade);
+            }
+            _ => centuries.push((century, decade.count, vec![decade])),
+        }
+    }
+
+    html! {
+        <nav class="md:w-40 shrink-0 md:max-h-96 overflow-y-auto" aria-label="Jump to a period">
+            <ul class="menu menu-sm bg-base-100 rounded-box shadow">
+                {centuries.into_iter().map(|(century, count, members)| html! {
+                    <li key={century}>
+                        <button class="font-semibold" onclick={select(century, Granularity::Century)}>
+                            {period_label(century, Granularity::Century)}
+                            <span class="badge badge-ghost badge-sm">{count}</span>
+                        </button>
+                        <ul>
+                            {members.into_iter().map(|decade| html! {
+                                <li key={decade.start}>
+                                    <button onclick={select(decade.start, Granularity::Decade)}>
+                                        {period_label(decade.start, Granularity::Decade)}
+                                        <span class="badge badge-ghost badge-sm">{decade.count}</span>
+                                    </button>
+                                </li>
+                            }).collect::<Html>()}
+                        </ul>
+                    </li>
+                }).collect::<Html>()}
+            </ul>
+        </nav>
+    }
+}
@@ -0,0 +1,240 @@
+use serde::Deserialize;
+use yew::{function_component, html, use_effect_with, use_state, Callback, Html, Properties, TargetCast};
+
+use crate::api::Request;
+use crate::components::api_provider::use_api;
+use crate::components::error_boundary::use_error_reporter;
+use crate::components::modal::{ConfirmDialog, Confirmation};
+use crate::components::notifications::use_notify;
+
+/// Colour a new period starts with.
+const DEFAULT_COLOR: &str = "#f59e0b";
+
+#[derive(Deserialize, Clone, PartialEq)]
+struct Period {
+    id: String,
+    name: String,
+    start_date: String,
+    end_date: String,
+    color: String,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct PeriodsProps {
+    pub timeline_id: String,
+    /// Called after a period is added, changed or deleted.
+    pub on_change: Callback<()>,
+}
+
+/// Editors' list of a timeline's periods, with a form that adds one or,
+/// after "Edit", changes it. Readers only see them as bands on the timeline.
+#[function_component(Periods)]
+pub fn periods(props: &PeriodsProps) -> Html {
+    let periods = use_state(Vec::<Period>::new);
+    let editing = use_state(|| Option::<String>::None);
+    let name = use_state(String::new);
+    let start = use_state(String::new);
+    let end = use_state(String::new);
+    let color = use_state(|| DEFAULT_COLOR.to_string());
+    let confirming = use_state(|| Option::<Confirmation>::None);
+    let errors = use_error_reporter();
+    let client = use_api();
+    let notify = use_notify();
+
+    let url = format!("/api/timelines/{}/periods", props.timeline_id);
+
+    let reload = {
+        let periods = periods.clone();
+        let url = url.clone();
+        let client = client.clone();
+        Callback::from(move |_: ()| {
+            let periods = periods.clone();
+            let url = url.clone();
+            let client = client.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                if let Ok(list) = client.get::<Vec<Period>>(&url).await {
+                    periods.set(list);
+                }
+            });
+        })
+    };
+
+    {
+        let reload = reload.clone();
+        use_effect_with(props.timeline_id.clone(), move |_| reload.emit(()));
+    }
+
+    let field = |state: &yew::UseStateHandle<String>| {
+        let state = state.clone();
+        Callback::from(move |e: yew::InputEvent| {
+            let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+            state.set(input.value());
+        })
+    };
+
+    let reset = {
+        let editing = editing.clone();
+        let name = name.clone();
+        let start = start.clone();
+        let end = end.clone();
+        let color = color.clone();
+        Callback::from(move |_: ()| {
+            editing.set(None);
+            name.set(String::new());
+            start.set(String::new());
+            end.set(String::new());
+            color.set(DEFAULT_COLOR.to_string());
+        })
+    };
+
+    let edit = |period: &Period| {
+        let period = period.clone();
+        let editing = editing.clone();
+        let name = name.clone();
+        let start = start.clone();
+        let end = end.clone();
+        let color = color.clone();
+        Callback::from(move |_| {
+            editing.set(Some(period.id.clone()));
+            name.set(period.name.clone());
+            start.set(period.start_date.clone());
+            end.set(period.end_date.clone());
+            color.set(period.color.clone());
+        })
+    };
+
+    let submit = {
+        let url = url.clone();
+        let editing = editing.clone();
+        let body = serde_json::json!({
+            "name": *name,
+            "start_date": *start,
+            "end_date": *end,
+            "color": *color,
+        });
+        let reload = reload.clone();
+        let reset = reset.clone();
+        let on_change = props.on_change.clone();
+        let errors = errors.clone();
+        let notify = notify.clone();
+        let client = client.clone();
+        Callback::from(move |e: yew::SubmitEvent| {
+            e.prevent_default();
+            let request = match &*editing {
+                Some(id) => Request::put(&format!("/api/periods/{}", id)),
+                None => Request::post(&url),
+            };
+            let saved = if editing.is_some() { "Period saved" } else { "Period added" };
+            let body = body.clone();
+            let reload = reload.clone();
+            let reset = reset.clone();
+            let on_change = on_change.clone();
+            let errors = errors.clone();
+            let notify = notify.clone();
+            let client = client.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match client.send_json::<serde::de::IgnoredAny>(request, &body).await {
+                    Ok(_) => {
+                        notify.success(saved);
+                        reset.emit(());
+                        reload.emit(());
+                        on_change.emit(());
+                    }
+                    Err(error) => errors.report(error),
+                }
+            });
+        })
+    };
+
+    let remove = |period: &Period| {
+        let url = format!("/api/periods/{}", period.id);
+        let removed = format!("Deleted {}", period.name);
+        let reload = reload.clone();
+        let on_change = props.on_change.clone();
+        let errors = errors.clone();
+        let notify = notify.clone();
+        let client = client.clone();
+        let remove = Callback::from(move |_| {
+            let url = url.clone();
+            let removed = removed.clone();
+            let reload = reload.clone();
+            let on_change = on_change.clone();
+            let errors = errors.clone();
+            let notify = notify.clone();
+            let client = client.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match client.send::<serde::de::IgnoredAny>(Request::delete(&url)).await {
+                    Ok(_) => notify.success(removed),
+                    Err(error) => errors.report(error),
+                }
+                reload.emit(());
+                on_change.emit(());
+            });
+        });
+        let confirmation = Confirmation::new(
+            format!("Delete {}?", period.name),
+            "The band disappears from the timeline; its events stay.",
+            "Delete",
+            remove,
+        );
+        let confirming = confirming.clone();
+        Callback::from(move |_| confirming.set(Some(confirmation.clone())))
+    };
+    let close_confirmation = {
+        let confirming = confirming.clone();
+        Callback::from(move |_| confirming.set(None))
+    };
+
+    let on_color = {
+        let color = color.clone();
+        Callback::from(move |e: yew::Event| {
+            let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+            color.set(input.value());
+        })
+    };
+    // Date inputs give `YYYY-MM-DD`, which sorts as text.
+    let ready = !name.trim().is_empty() && !start.is_empty() && !end.is_empty() && *end >= *start;
+
+    html! {
+        <div class="card bg-base-100 shadow mt-6">
+            <div class="card-body">
+                <h2 class="card-title">{"Periods"}</h2>
+                if periods.is_empty() {
+                    <p class="opacity-70">{"Mark eras such as \"Renaissance\" as bands behind the events."}</p>
+                }
+                <ul class="divide-y">
+                    {periods.iter().map(|period| html! {
+                        <li class="flex items-center justify-between gap-2 py-2">
+                            <span class="flex items-center gap-2">
+                                <span class="w-3 h-3 rounded-full shrink-0" style={format!("background: {}", period.color)}></span>
+                                {&period.name}
+                                <span class="opacity-70 text-sm">{format!("{} – {}", period.start_date, period.end_date)}</span>
+                            </span>
+                            <div class="flex gap-2">
+                                <button class="btn btn-ghost btn-sm" onclick={edit(period)}>{"Edit"}</button>
+                                <button class="btn btn-ghost btn-sm" onclick={remove(period)}>{"Delete"}</button>
+                            </div>
+                        </li>
+                    }).collect::<Html>()}
+                </ul>
+                <form class="flex flex-wrap items-center gap-2 mt-2" onsubmit={submit}>
+                    <input class="input input-bordered input-sm flex-1" placeholder="Name" maxlength="255"
+                        value={(*name).clone()} oninput={field(&name)} />
+                    <input type="date" class="input input-bordered input-sm" aria-label="Start date"
+                        value={(*start).clone()} oninput={field(&start)} />
+                    <input type="date" class="input input-bordered input-sm" aria-label="End date"
+                        value={(*end).clone()} oninput={field(&end)} />
+                    <input type="color" class="w-8 h-8 cursor-pointer" aria-label="Colour"
+                        value={(*color).clone()} onchange={on_color} />
+                    <button class="btn btn-primary btn-sm" type="submit" disabled={!ready}>
+                        {if editing.is_some() { "Save" } else { "Add period" }}
+                    </button>
+                    if editing.is_some() {
+                        <button class="btn btn-ghost btn-sm" type="button" onclick={reset.reform(|_| ())}>{"Cancel"}</button>
+                    }
+                </form>
+                <ConfirmDialog confirmation={(*confirming).clone()} on_close={close_confirmation} />
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,90 @@
+use web_sys::{Element, HtmlElement};
+use yew::{
+    create_portal, function_component, html, use_effect_with, use_node_ref, use_state, Callback, Children, Html,
+    PointerEvent, Properties,
+};
+
+/// Gap between a popover and its anchor, and the least it keeps from the
+/// edges of the viewport.
+const MARGIN: f64 = 8.0;
+
+/// What a popover points at, in viewport pixels.
+#[derive(Clone, Copy, PartialEq)]
+pub struct Anchor {
+    pub left: f64,
+    pub top: f64,
+    pub right: f64,
+    pub bottom: f64,
+}
+
+impl Anchor {
+    pub fn of(element: &Element) -> Self {
+        let rect = element.get_bounding_client_rect();
+        Self { left: rect.left(), top: rect.top(), right: rect.right(), bottom: rect.bottom() }
+    }
+}
+
+#[derive(Properties, PartialEq)]
+pub struct PopoverProps {
+    pub anchor: Anchor,
+    #[prop_or_default]
+    pub onpointerenter: Callback<PointerEvent>,
+    #[prop_or_default]
+    pub onpointerleave: Callback<PointerEvent>,
+    pub children: Children,
+}
+
+/// Floating content beside an anchor: below it if there is room, otherwise
+/// above, and shifted sideways to stay on screen. Rendered into `<body>`
+/// so that containers clipping their overflow, like the timeline track,
+/// don't cut it off.
+#[function_component(Popover)]
+pub fn popover(props: &PopoverProps) -> Html {
+    let node = use_node_ref();
+    // Where the popover goes for which anchor. Its size is only known once
+    // it has rendered, so until then it is laid out out of sight.
+    let placed = use_state(|| Option::<(Anchor, (f64, f64))>::None);
+
+    {
+        let node = node.clone();
+        let placed = placed.clone();
+        use_effect_with(props.anchor, move |anchor: &Anchor| {
+            let root = gloo_utils::document().document_element();
+            if let (Some(el), Some(root)) = (node.cast::<HtmlElement>(), root) {
+                let size = (el.offset_width() as f64, el.offset_height() as f64);
+                let viewport = (root.client_width() as f64, root.client_height() as f64);
+                placed.set(Some((*anchor, place(*anchor, size, viewport))));
+            }
+        });
+    }
+
+    let style = match *placed {
+        Some((anchor, (left, top))) if anchor == props.anchor => format!("left: {:.0}px; top: {:.0}px", left, top),
+        _ => "left: 0; top: 0; visibility: hidden".to_string(),
+    };
+    let popover = html! {
+        <div
+            ref={node}
+            class="fixed z-50 w-64"
+            {style}
+            onpointerenter={props.onpointerenter.clone()}
+            onpointerleave={props.onpointerleave.clone()}
+        >
+            {props.children.clone()}
+        </div>
+    };
+    match gloo_utils::document().body() {
+        Some(body) => create_portal(popover, body.into()),
+        None => popover,
+    }
+}
+
+/// Top-left corner for a popover of `size` beside `anchor`.
+fn place(anchor: Anchor, (width, height): (f64, f64), (viewport_width, viewport_height): (f64, f64)) -> (f64, f64) {
+    let below = anchor.bottom + MARGIN;
+    let above = anchor.top - MARGIN - height;
+    let top = if below + height <= viewport_height - MARGIN || above < MARGIN { below } else { above };
+    let center = (anchor.left + anchor.right) / 2.0;
+    let left = (center - width / 2.0).clamp(MARGIN, (viewport_width - width - MARGIN).max(MARGIN));
+    (left, top)
+}
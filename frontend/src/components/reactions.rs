@@ -0,0 +1,161 @@
+use serde::Deserialize;
+use yew::{function_component, html, use_effect_with, use_state, Callback, Html, Properties};
+
+use crate::api::Request;
+use crate::auth;
+use crate::components::api_provider::use_api;
+use crate::components::error_boundary::use_error_reporter;
+
+/// The emoji the API accepts, in picker order.
+const EMOJI: [&str; 6] = ["👍", "❤️", "😂", "😮", "😢", "🎉"];
+
+/// How many gave one emoji.
+#[derive(Deserialize, Clone, PartialEq)]
+pub struct Count {
+    emoji: String,
+    count: i64,
+    /// Whether the viewer gave it.
+    mine: bool,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct ReactionsProps {
+    /// The event's or comment's reactions endpoint.
+    pub url: String,
+    /// Counts that came with the comment; fetched from `url` when unset.
+    #[prop_or_default]
+    pub counts: Option<Vec<Count>>,
+}
+
+/// The emoji given so far with their counts, and a picker to add one.
+/// Signed-in viewers toggle their own by clicking; others only see them.
+#[function_component(Reactions)]
+pub fn reactions(props: &ReactionsProps) -> Html {
+    let counts = use_state(|| props.counts.clone().unwrap_or_default());
+    let picking = use_state(|| false);
+    let errors = use_error_reporter();
+    let client = use_api();
+    let signed_in = auth::token().is_some();
+
+    {
+        let counts = counts.clone();
+        let known = props.counts.is_some();
+        let client = client.clone();
+        use_effect_with(props.url.clone(), move |url: &String| {
+            if !known {
+                let url = url.clone();
+                wasm_bindgen_futures::spawn_local(async move {
+                    if let Ok(loaded) = client.get::<Vec<Count>>(&url).await {
+                        counts.set(loaded);
+                    }
+                });
+            }
+        });
+    }
+
+    let react = {
+        let counts = counts.clone();
+        let picking = picking.clone();
+        let url = props.url.clone();
+        let client = client.clone();
+        Callback::from(move |emoji: &'static str| {
+            let counts = counts.clone();
+            let errors = errors.clone();
+            let url = url.clone();
+            picking.set(false);
+            let client = client.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                let body = serde_json::json!({ "emoji": emoji });
+                match client.send_json::<Vec<Count>>(Request::post(&url), &body).await {
+                    Ok(updated) => counts.set(updated),
+                    Err(error) => errors.report(error),
+                }
+            });
+        })
+    };
+    let toggle_picker = {
+        let picking = picking.clone();
+        Callback::from(move |_| picking.set(!*picking))
+    };
+
+    html! {
+        <div class="flex flex-wrap items-center gap-1">
+            {counts.iter().map(|count| {
+                // Only the picker's emoji can be sent back.
+                let emoji = EMOJI.iter().copied().find(|emoji| *emoji == count.emoji);
+                let onclick = react.reform(move |_| emoji.unwrap_or_default());
+                html! {
+                    <button
+                        key={count.emoji.clone()}
+                        class={if count.mine { "btn btn-xs btn-primary btn-outline" } else { "btn btn-xs btn-ghost" }}
+                        aria-pressed={count.mine.to_string()}
+                        aria-label={format!("{} {}", count.emoji, count.count)}
+                        disabled={!signed_in || emoji.is_none()}
+                        {onclick}
+                    >
+                        {format!("{} {}", count.emoji, count.count)}
+                    </button>
+                }
+            }).collect::<Html>()}
+            if signed_in {
+                <div class="relative">
+                    <button class="btn btn-xs btn-ghost" aria-label="Add reaction" aria-expanded={picking.to_string()}
+                        onclick={toggle_picker}>
+                        {"☺︎+"}
+                    </button>
+                    if *picking {
+                        <div class="absolute z-10 mt-1 flex gap-1 rounded-box bg-base-100 p-1 shadow" role="menu">
+                            {EMOJI.iter().map(|emoji| html! {
+                                <button class="btn btn-xs btn-ghost" role="menuitem" onclick={react.reform(move |_| *emoji)}>
+                                    {*emoji}
+                                </button>
+                            }).collect::<Html>()}
+                        </div>
+                    }
+                </div>
+            }
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use wasm_bindgen::JsCast;
+    use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
+
+    use super::*;
+    use crate::api::Method;
+    use crate::testing::{self, MockClient};
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[wasm_bindgen_test]
+    async fn picking_an_emoji_shows_the_new_counts() {
+        auth::set_tokens("token", "refresh");
+        let client = Rc::new(
+            MockClient::default()
+                .reply(Method::Get, "/api/events/e1/reactions", 200, serde_json::json!([
+                    { "emoji": "👍", "count": 2, "mine": false },
+                ]))
+                .reply(Method::Post, "/api/events/e1/reactions", 200, serde_json::json!([
+                    { "emoji": "👍", "count": 2, "mine": false },
+                    { "emoji": "🎉", "count": 1, "mine": true },
+                ])),
+        );
+        let root = testing::mount(client, html! { <Reactions url="/api/events/e1/reactions" /> }).await;
+        assert_eq!(testing::texts(&root, "button[aria-pressed]"), ["👍 2"]);
+
+        let picker = root.query_selector("[aria-label='Add reaction']").unwrap().unwrap();
+        picker.dyn_into::<web_sys::HtmlElement>().unwrap().click();
+        testing::settle().await;
+        let party = root.query_selector_all("[role=menuitem]").unwrap().item(5).unwrap();
+        party.dyn_into::<web_sys::HtmlElement>().unwrap().click();
+        testing::settle().await;
+
+        assert_eq!(testing::texts(&root, "button[aria-pressed]"), ["👍 2", "🎉 1"]);
+        assert_eq!(testing::texts(&root, "button[aria-pressed=true]"), ["🎉 1"]);
+        auth::clear_token();
+    }
+}
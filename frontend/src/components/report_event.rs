@@ -0,0 +1,133 @@
+use wasm_bindgen::JsCast;
+use yew::{function_component, html, use_node_ref, use_state, Callback, Html, Properties};
+
+use crate::api::Request;
+use crate::auth;
+use crate::components::api_provider::use_api;
+use crate::components::error_boundary::use_error_reporter;
+use crate::components::guest::{self, use_guest_policy, GuestFields};
+use crate::components::modal::Modal;
+use crate::components::notifications::use_notify;
+
+/// Reasons a report can give, as the API names them.
+const REASONS: &[(&str, &str)] = &[
+    ("spam", "Spam or advertising"),
+    ("abuse", "Harassment or hate"),
+    ("inappropriate", "Inappropriate content"),
+    ("copyright", "Copyright infringement"),
+    ("other", "Something else"),
+];
+
+#[derive(Properties, PartialEq)]
+pub struct ReportEventProps {
+    pub event_id: String,
+}
+
+/// "Report" link that asks why and sends the event to the admins'
+/// moderation queue. Shown to signed-out visitors only where the instance
+/// takes their reports.
+#[function_component(ReportEvent)]
+pub fn report_event(props: &ReportEventProps) -> Html {
+    let open = use_state(|| false);
+    let sending = use_state(|| false);
+    let reason_input = use_node_ref();
+    let details_input = use_node_ref();
+    let errors = use_error_reporter();
+    let client = use_api();
+    let notify = use_notify();
+    let policy = use_guest_policy();
+    let signed_in = auth::token().is_some();
+
+    let show = {
+        let open = open.clone();
+        Callback::from(move |_| open.set(true))
+    };
+    let close = {
+        let open = open.clone();
+        Callback::from(move |_| open.set(false))
+    };
+    let submit = {
+        let open = open.clone();
+        let sending = sending.clone();
+        let reason_input = reason_input.clone();
+        let details_input = details_input.clone();
+        let url = format!("/api/events/{}/report", props.event_id);
+        Callback::from(move |e: yew::SubmitEvent| {
+            e.prevent_default();
+            let (Some(reason), Some(details)) = (
+                reason_input.cast::<web_sys::HtmlSelectElement>(),
+                details_input.cast::<web_sys::HtmlTextAreaElement>(),
+            ) else {
+                return;
+            };
+            let mut body = serde_json::json!({ "reason": reason.value(), "details": details.value() });
+            let form = e.target().and_then(|target| target.dyn_into::<web_sys::Element>().ok());
+            if let (None, Some(form)) = (auth::token(), form) {
+                if let (Some(body), serde_json::Value::Object(proof)) = (body.as_object_mut(), guest::proof(&form)) {
+                    body.extend(proof);
+                }
+            }
+            let url = url.clone();
+            let open = open.clone();
+            let sending = sending.clone();
+            let errors = errors.clone();
+            let notify = notify.clone();
+            sending.set(true);
+            let client = client.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match client.send_json::<serde::de::IgnoredAny>(Request::post(&url), &body).await {
+                    Ok(_) => {
+                        open.set(false);
+                        notify.success("Thanks, the admins will take a look.");
+                    }
+                    Err(error) => errors.report(error),
+                }
+                sending.set(false);
+            });
+        })
+    };
+
+    let guest = match policy {
+        _ if signed_in => None,
+        Some(policy) if policy.enabled => Some(policy),
+        _ => return html! {},
+    };
+
+    html! {
+        <>
+            <button class="btn btn-ghost btn-sm" onclick={show}>{"Report"}</button>
+            if *open {
+                <Modal
+                    title="Report this event"
+                    on_close={close.clone()}
+                    actions={html! {
+                        <>
+                            <button class="btn" onclick={close.reform(|_| ())}>{"Cancel"}</button>
+                            <button class="btn btn-warning" type="submit" form="report-event" disabled={*sending}>
+                                {"Send report"}
+                            </button>
+                        </>
+                    }}
+                >
+                    <form id="report-event" class="flex flex-col gap-4 py-4" onsubmit={submit}>
+                        <label class="form-control">
+                            <span class="label-text mb-1">{"What is wrong with it?"}</span>
+                            <select ref={reason_input} class="select select-bordered">
+                                {REASONS.iter().map(|(value, label)| html! {
+                                    <option value={*value}>{*label}</option>
+                                }).collect::<Html>()}
+                            </select>
+                        </label>
+                        <label class="form-control">
+                            <span class="label-text mb-1">{"Details (optional)"}</span>
+                            <textarea ref={details_input} class="textarea textarea-bordered" maxlength="2000" rows="3" />
+                        </label>
+                        if let Some(guest) = guest {
+                            <GuestFields challenge={guest.challenge} />
+                        }
+                    </form>
+                </Modal>
+            }
+        </>
+    }
+}
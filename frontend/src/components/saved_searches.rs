@@ -0,0 +1,238 @@
+use serde::{Deserialize, Serialize};
+use yew::{function_component, html, use_effect_with, use_node_ref, use_state, Callback, Html, Properties};
+
+use crate::api::Request;
+use crate::components::api_provider::use_api;
+use crate::components::error_boundary::use_error_reporter;
+use crate::components::modal::{ConfirmDialog, Confirmation};
+use crate::components::notifications::use_notify;
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+struct SavedSearch {
+    #[serde(skip_serializing)]
+    id: String,
+    name: String,
+    search: Option<String>,
+    categories: Vec<String>,
+    /// Kept as the API sends them, so updates send them back unchanged.
+    start_date: Option<String>,
+    end_date: Option<String>,
+    notify: bool,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct SavedSearchesProps {
+    /// The filter in use, saved by "Save".
+    pub search: String,
+    pub categories: Vec<String>,
+    /// Called with a saved search's text and categories when it is picked.
+    pub on_apply: Callback<(String, Vec<String>)>,
+}
+
+/// The signed-in user's saved searches. Picking one applies it to the
+/// list; the bell turns on emails about new events that match it.
+#[function_component(SavedSearches)]
+pub fn saved_searches(props: &SavedSearchesProps) -> Html {
+    let saved = use_state(|| Option::<Vec<SavedSearch>>::None);
+    let confirming = use_state(|| Option::<Confirmation>::None);
+    let name_input = use_node_ref();
+    let errors = use_error_reporter();
+    let client = use_api();
+    let notify = use_notify();
+
+    {
+        let saved = saved.clone();
+        let errors = errors.clone();
+        let client = client.clone();
+        use_effect_with((), move |_| {
+            wasm_bindgen_futures::spawn_local(async move {
+                match client.get::<Vec<SavedSearch>>("/api/saved-searches").await {
+                    Ok(list) => saved.set(Some(list)),
+                    Err(error) => errors.report(error),
+                }
+            });
+        });
+    }
+
+    let save = {
+        let saved = saved.clone();
+        let name_input = name_input.clone();
+        let errors = errors.clone();
+        let notify = notify.clone();
+        let search = props.search.clone();
+        let categories = props.categories.clone();
+        let client = client.clone();
+        Callback::from(move |e: yew::SubmitEvent| {
+            e.prevent_default();
+            let Some(input) = name_input.cast::<web_sys::HtmlInputElement>() else {
+                return;
+            };
+            let name = input.value().trim().to_string();
+            if name.is_empty() {
+                return;
+            }
+            let body = serde_json::json!({
+                "name": name,
+                "search": (!search.is_empty()).then(|| search.clone()),
+                "categories": categories,
+            });
+            let saved = saved.clone();
+            let errors = errors.clone();
+            let notify = notify.clone();
+            let client = client.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match client.send_json::<SavedSearch>(Request::post("/api/saved-searches"), &body).await {
+                    Ok(created) => {
+                        input.set_value("");
+                        notify.success(format!("Saved \"{}\"", created.name));
+                        let mut list = (*saved).clone().unwrap_or_default();
+                        list.push(created);
+                        list.sort_by_key(|search| search.name.to_lowercase());
+                        saved.set(Some(list));
+                    }
+                    Err(error) => errors.report(error),
+                }
+            });
+        })
+    };
+
+    let toggle_notify = {
+        let saved = saved.clone();
+        let errors = errors.clone();
+        let notify = notify.clone();
+        let client = client.clone();
+        Callback::from(move |search: SavedSearch| {
+            let saved = saved.clone();
+            let errors = errors.clone();
+            let notify = notify.clone();
+            let url = format!("/api/saved-searches/{}", search.id);
+            let body = SavedSearch { notify: !search.notify, ..search };
+            let client = client.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match client.send_json::<SavedSearch>(Request::put(&url), &body).await {
+                    Ok(updated) => {
+                        notify.info(match updated.notify {
+                            true => format!("You'll get an email when new events match \"{}\"", updated.name),
+                            false => format!("No more emails for \"{}\"", updated.name),
+                        });
+                        let list = (*saved)
+                            .iter()
+                            .flatten()
+                            .map(|other| if other.id == updated.id { updated.clone() } else { other.clone() })
+                            .collect();
+                        saved.set(Some(list));
+                    }
+                    Err(error) => errors.report(error),
+                }
+            });
+        })
+    };
+
+    let remove = {
+        let saved = saved.clone();
+        let confirming = confirming.clone();
+        let client = client.clone();
+        let client = client.clone();
+        Callback::from(move |search: SavedSearch| {
+            let saved = saved.clone();
+            let errors = errors.clone();
+            let client = client.clone();
+            let on_confirm = Callback::from(move |_| {
+                let saved = saved.clone();
+                let errors = errors.clone();
+                let id = search.id.clone();
+                let client = client.clone();
+                wasm_bindgen_futures::spawn_local(async move {
+                    let url = format!("/api/saved-searches/{}", id);
+                    match client.send::<serde::de::IgnoredAny>(Request::delete(&url)).await {
+                        Ok(_) => {
+                            let list = (*saved).iter().flatten().filter(|other| other.id != id).cloned().collect();
+                            saved.set(Some(list));
+                        }
+                        Err(error) => errors.report(error),
+                    }
+                });
+            });
+            confirming.set(Some(Confirmation::new(
+                "Delete saved search?",
+                format!("\"{}\" will no longer be listed or send emails.", search.name),
+                "Delete",
+                on_confirm,
+            )));
+        })
+    };
+    let close_confirmation = {
+        let confirming = confirming.clone();
+        Callback::from(move |_| confirming.set(None))
+    };
+
+    let filtered = !props.search.is_empty() || !props.categories.is_empty();
+
+    html! {
+        <aside class="w-full md:w-56 shrink-0" aria-label="Saved searches">
+            <div class="card card-compact bg-base-100 shadow">
+                <div class="card-body">
+                    <h2 class="font-semibold">{"Saved searches"}</h2>
+                    {match &*saved {
+                        None => html! { <p class="text-sm opacity-70">{"Loading…"}</p> },
+                        Some(list) if list.is_empty() => html! {
+                            <p class="text-sm opacity-70">{"Search or pick categories, then save them here."}</p>
+                        },
+                        Some(list) => html! {
+                            <ul class="menu menu-sm p-0">
+                                {list.iter().map(|search| {
+                                    let apply = props.on_apply.reform({
+                                        let search = search.clone();
+                                        move |_| (search.search.clone().unwrap_or_default(), search.categories.clone())
+                                    });
+                                    let bell = if search.notify { "Stop emails" } else { "Email me about new matches" };
+                                    html! {
+                                        <li key={search.id.clone()} class="flex flex-row items-center">
+                                            <button class="flex-1 min-w-0 truncate" onclick={apply}>{&search.name}</button>
+                                            <button
+                                                class={if search.notify { "btn btn-ghost btn-xs" } else { "btn btn-ghost btn-xs opacity-40" }}
+                                                title={bell}
+                                                aria-label={format!("{}: {}", bell, search.name)}
+                                                aria-pressed={search.notify.to_string()}
+                                                onclick={toggle_notify.reform({
+                                                    let search = search.clone();
+                                                    move |_| search.clone()
+                                                })}
+                                            >
+                                                {"🔔"}
+                                            </button>
+                                            <button
+                                                class="btn btn-ghost btn-xs"
+                                                aria-label={format!("Delete {}", search.name)}
+                                                onclick={remove.reform({
+                                                    let search = search.clone();
+                                                    move |_| search.clone()
+                                                })}
+                                            >
+                                                {"✕"}
+                                            </button>
+                                        </li>
+                                    }
+                                }).collect::<Html>()}
+                            </ul>
+                        },
+                    }}
+                    if filtered {
+                        <form class="join w-full" onsubmit={save}>
+                            <input
+                                ref={name_input}
+                                class="input input-bordered input-sm join-item flex-1 min-w-0"
+                                placeholder="Name this search"
+                                aria-label="Saved search name"
+                                maxlength="100"
+                                required=true
+                            />
+                            <button class="btn btn-sm join-item" type="submit">{"Save"}</button>
+                        </form>
+                    }
+                </div>
+            </div>
+            <ConfirmDialog confirmation={(*confirming).clone()} on_close={close_confirmation} />
+        </aside>
+    }
+}
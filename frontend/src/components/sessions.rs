@@ -0,0 +1,136 @@
+use serde::Deserialize;
+use yew::{function_component, html, use_effect_with, use_state, Callback, Html};
+
+use crate::api::Request;
+use crate::auth;
+use crate::components::api_provider::use_api;
+use crate::components::error_boundary::use_error_reporter;
+use crate::components::modal::{ConfirmDialog, Confirmation};
+use crate::components::notifications::use_notify;
+
+#[derive(Deserialize, Clone, PartialEq)]
+struct Session {
+    id: String,
+    /// e.g. "Firefox on Windows".
+    device: String,
+    /// Whether this is the browser looking at the list.
+    current: bool,
+    created_at: String,
+    last_seen_at: String,
+}
+
+/// `2024-05-01T09:30:12.345` as `2024-05-01 09:30 UTC`.
+fn timestamp(value: &str) -> String {
+    format!("{} UTC", value.replace('T', " ").chars().take(16).collect::<String>())
+}
+
+/// Devices the user is logged in on, each of which can be logged out.
+/// Logging out this one goes back to the login page.
+#[function_component(Sessions)]
+pub fn sessions() -> Html {
+    let sessions = use_state(|| Option::<Vec<Session>>::None);
+    let confirming = use_state(|| Option::<Confirmation>::None);
+    let errors = use_error_reporter();
+    let client = use_api();
+    let notify = use_notify();
+
+    {
+        let sessions = sessions.clone();
+        let errors = errors.clone();
+        let client = client.clone();
+        use_effect_with((), move |_| {
+            wasm_bindgen_futures::spawn_local(async move {
+                match client.get::<Vec<Session>>("/api/me/sessions").await {
+                    Ok(list) => sessions.set(Some(list)),
+                    Err(error) => errors.report(error),
+                }
+            });
+        });
+    }
+
+    let revoke = {
+        let sessions = sessions.clone();
+        let confirming = confirming.clone();
+        let client = client.clone();
+        let client = client.clone();
+        Callback::from(move |session: Session| {
+            let sessions = sessions.clone();
+            let errors = errors.clone();
+            let notify = notify.clone();
+            let client = client.clone();
+            let message = match session.current {
+                true => "You will need to log in again on this device.".to_string(),
+                false => format!("{} will need to log in again within 15 minutes.", session.device),
+            };
+            let on_confirm = Callback::from(move |_| {
+                let sessions = sessions.clone();
+                let errors = errors.clone();
+                let notify = notify.clone();
+                let session = session.clone();
+                let client = client.clone();
+                wasm_bindgen_futures::spawn_local(async move {
+                    let url = format!("/api/me/sessions/{}", session.id);
+                    match client.send::<serde::de::IgnoredAny>(Request::delete(&url)).await {
+                        Ok(_) if session.current => {
+                            auth::clear_token();
+                            gloo_utils::window().location().set_href("/login").ok();
+                        }
+                        Ok(_) => {
+                            notify.success(format!("Logged out {}", session.device));
+                            let list = (*sessions).iter().flatten().filter(|other| other.id != session.id).cloned().collect();
+                            sessions.set(Some(list));
+                        }
+                        Err(error) => errors.report(error),
+                    }
+                });
+            });
+            confirming.set(Some(Confirmation::new("Log out this device?", message, "Log out", on_confirm)));
+        })
+    };
+    let close_confirmation = {
+        let confirming = confirming.clone();
+        Callback::from(move |_| confirming.set(None))
+    };
+
+    html! {
+        <section class="card bg-base-100 shadow max-w-lg" aria-labelledby="sessions-heading">
+            <div class="card-body">
+                <h2 id="sessions-heading" class="card-title">{"Sessions"}</h2>
+                {match &*sessions {
+                    None => html! { <p class="opacity-70">{"Loading…"}</p> },
+                    Some(list) if list.is_empty() => html! { <p class="opacity-70">{"No other sessions."}</p> },
+                    Some(list) => html! {
+                        <ul class="divide-y divide-base-200">
+                            {list.iter().map(|session| html! {
+                                <li key={session.id.clone()} class="flex items-center gap-2 py-2">
+                                    <div class="flex-1 min-w-0">
+                                        <p class="font-medium">
+                                            {&session.device}
+                                            if session.current {
+                                                <span class="badge badge-primary badge-sm ml-2">{"This device"}</span>
+                                            }
+                                        </p>
+                                        <p class="text-sm opacity-70">
+                                            {format!(
+                                                "Last active {} · signed in {}",
+                                                timestamp(&session.last_seen_at),
+                                                timestamp(&session.created_at)
+                                            )}
+                                        </p>
+                                    </div>
+                                    <button class="btn btn-ghost btn-sm" onclick={revoke.reform({
+                                        let session = session.clone();
+                                        move |_| session.clone()
+                                    })}>
+                                        {"Log out"}
+                                    </button>
+                                </li>
+                            }).collect::<Html>()}
+                        </ul>
+                    },
+                }}
+            </div>
+            <ConfirmDialog confirmation={(*confirming).clone()} on_close={close_confirmation} />
+        </section>
+    }
+}
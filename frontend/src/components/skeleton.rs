@@ -0,0 +1,97 @@
+use yew::{function_component, html, Html, Properties};
+
+/// What a `Skeleton` stands in for.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Shape {
+    /// An event card in the events grid.
+    Card,
+    /// The card on an event's detail page.
+    Detail,
+    /// The timeline's toolbar and track.
+    Timeline,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct SkeletonProps {
+    pub shape: Shape,
+    /// Number of cards, laid out in the same grid as the real ones. Other
+    /// shapes are shown once.
+    #[prop_or(6)]
+    pub count: usize,
+}
+
+/// A placeholder in the shape of content that is still loading, so the
+/// page keeps its layout when the content arrives.
+#[function_component(Skeleton)]
+pub fn skeleton(props: &SkeletonProps) -> Html {
+    let placeholder = match props.shape {
+        Shape::Card => html! {
+            <div class="grid grid-cols-1 md:grid-cols-2 lg:grid-cols-3 gap-6">
+                {(0..props.count).map(|_| card()).collect::<Html>()}
+            </div>
+        },
+        Shape::Detail => detail(),
+        Shape::Timeline => timeline(),
+    };
+    html! {
+        <div aria-busy="true" aria-label="Loading">{placeholder}</div>
+    }
+}
+
+fn card() -> Html {
+    html! {
+        <div class="card bg-base-100 shadow-xl">
+            <div class="card-body gap-3">
+                <div class="skeleton h-6 w-3/4"></div>
+                <div class="skeleton h-4 w-full"></div>
+                <div class="skeleton h-4 w-5/6"></div>
+                <div class="card-actions justify-end">
+                    <div class="skeleton h-12 w-28"></div>
+                </div>
+            </div>
+        </div>
+    }
+}
+
+fn detail() -> Html {
+    html! {
+        <div class="card bg-base-100 shadow-xl">
+            <div class="card-body gap-3">
+                <div class="skeleton h-8 w-1/2"></div>
+                <div class="skeleton h-4 w-full"></div>
+                <div class="skeleton h-4 w-full"></div>
+                <div class="skeleton h-4 w-2/3"></div>
+                <div class="mt-4 flex flex-col gap-2">
+                    <div class="skeleton h-4 w-48"></div>
+                    <div class="skeleton h-4 w-40"></div>
+                    <div class="skeleton h-4 w-36"></div>
+                </div>
+                <div class="skeleton mt-4 h-64 w-full rounded-lg"></div>
+            </div>
+        </div>
+    }
+}
+
+fn timeline() -> Html {
+    // Markers staggered over a few lanes, at made-up but stable positions.
+    let markers = [(8, 0), (22, 1), (35, 0), (51, 2), (64, 1), (80, 0)];
+    html! {
+        <div class="timeline-container">
+            <div class="flex gap-2 mb-2">
+                <div class="skeleton h-8 w-8"></div>
+                <div class="skeleton h-8 w-8"></div>
+                <div class="skeleton h-8 w-12"></div>
+                <div class="skeleton h-8 w-40 ml-auto"></div>
+            </div>
+            <div class="timeline-track relative" style="height: 152px">
+                {markers.iter().map(|(left, lane)| html! {
+                    <div
+                        class="skeleton absolute h-4 w-24"
+                        style={format!("left: {}%; top: {}px", left, 8 + lane * 32)}
+                    ></div>
+                }).collect::<Html>()}
+                <div class="skeleton absolute bottom-8 left-0 h-1 w-full"></div>
+            </div>
+        </div>
+    }
+}
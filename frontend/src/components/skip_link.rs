@@ -0,0 +1,32 @@
+use wasm_bindgen::JsCast;
+use web_sys::HtmlElement;
+use yew::{function_component, html, Callback, Html, MouseEvent};
+
+/// "Skip to main content", the first stop for keyboard users. Hidden until
+/// focused; moves focus to the page's `<main>`, whichever page is showing.
+#[function_component(SkipLink)]
+pub fn skip_link() -> Html {
+    let onclick = Callback::from(|e: MouseEvent| {
+        e.prevent_default();
+        let main = gloo_utils::document()
+            .query_selector("main")
+            .ok()
+            .flatten()
+            .and_then(|main| main.dyn_into::<HtmlElement>().ok());
+        if let Some(main) = main {
+            // Focusable from script only, so it isn't added to the tab order.
+            main.set_tab_index(-1);
+            main.focus().ok();
+        }
+    });
+
+    html! {
+        <a
+            href="#main"
+            class="sr-only focus:not-sr-only focus:fixed focus:top-2 focus:left-2 focus:z-50 btn btn-primary btn-sm"
+            {onclick}
+        >
+            {"Skip to main content"}
+        </a>
+    }
+}
@@ -0,0 +1,97 @@
+use serde::Deserialize;
+use yew::{function_component, html, use_effect_with, use_state, Html};
+
+use crate::components::api_provider::use_api;
+use crate::components::error_boundary::use_error_reporter;
+
+#[derive(Deserialize, Clone, PartialEq)]
+struct OrganizationUsage {
+    id: String,
+    name: String,
+    used: i64,
+    quota: i64,
+}
+
+#[derive(Deserialize, Clone, PartialEq)]
+struct Usage {
+    used: i64,
+    quota: i64,
+    organizations: Vec<OrganizationUsage>,
+}
+
+/// `bytes` for people, e.g. "4.5 MB".
+fn readable_size(bytes: i64) -> String {
+    const UNITS: [&str; 4] = ["bytes", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+fn usage_bar(label: &str, used: i64, quota: i64) -> Html {
+    let share = if quota > 0 { used as f64 / quota as f64 } else { 1.0 };
+    let class = match share {
+        share if share >= 0.9 => "progress progress-error w-full",
+        share if share >= 0.75 => "progress progress-warning w-full",
+        _ => "progress progress-primary w-full",
+    };
+    html! {
+        <div>
+            <div class="flex justify-between text-sm">
+                <span>{label}</span>
+                <span class="opacity-70">{format!("{} of {}", readable_size(used), readable_size(quota))}</span>
+            </div>
+            <progress class={class} value={used.min(quota).to_string()} max={quota.to_string()} aria-label={label.to_string()} />
+        </div>
+    }
+}
+
+/// How much of their upload storage the user, and each organization they
+/// belong to, has used.
+#[function_component(StorageUsage)]
+pub fn storage_usage() -> Html {
+    let usage = use_state(|| Option::<Usage>::None);
+    let errors = use_error_reporter();
+    let client = use_api();
+
+    {
+        let usage = usage.clone();
+        use_effect_with((), move |_| {
+            wasm_bindgen_futures::spawn_local(async move {
+                match client.get::<Usage>("/api/me/usage").await {
+                    Ok(found) => usage.set(Some(found)),
+                    Err(error) => errors.report(error),
+                }
+            });
+        });
+    }
+
+    html! {
+        <section class="card bg-base-100 shadow max-w-lg" aria-labelledby="storage-heading">
+            <div class="card-body gap-4">
+                <h2 id="storage-heading" class="card-title">{"Storage"}</h2>
+                {match &*usage {
+                    None => html! { <p class="opacity-70">{"Loading…"}</p> },
+                    Some(usage) => html! {
+                        <>
+                            {usage_bar("Your uploads", usage.used, usage.quota)}
+                            {usage.organizations.iter().map(|organization| html! {
+                                <div key={organization.id.clone()}>
+                                    {usage_bar(&organization.name, organization.used, organization.quota)}
+                                </div>
+                            }).collect::<Html>()}
+                            <p class="text-sm opacity-70">{"Linked images don't count; only images you upload do."}</p>
+                        </>
+                    },
+                }}
+            </div>
+        </section>
+    }
+}
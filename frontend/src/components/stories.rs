@@ -0,0 +1,157 @@
+use serde::Deserialize;
+use yew::{function_component, html, use_effect_with, use_state, Callback, Html, Properties};
+
+use crate::api::Request;
+use crate::components::api_provider::use_api;
+use crate::components::error_boundary::use_error_reporter;
+use crate::components::modal::{ConfirmDialog, Confirmation};
+use crate::components::notifications::use_notify;
+use crate::components::story_editor::StoryEditor;
+
+#[derive(Deserialize, Clone, PartialEq)]
+struct StorySummary {
+    id: String,
+    title: String,
+    description: Option<String>,
+    step_count: i64,
+}
+
+/// Which story the editor is open on.
+#[derive(Clone, PartialEq)]
+enum Editing {
+    New,
+    Story(String),
+}
+
+#[derive(Properties, PartialEq)]
+pub struct StoriesProps {
+    pub timeline_id: String,
+    /// Show new, edit and delete controls.
+    pub can_edit: bool,
+}
+
+/// A timeline's stories, each opening the story viewer. Hidden for
+/// readers when there are none.
+#[function_component(Stories)]
+pub fn stories(props: &StoriesProps) -> Html {
+    let stories = use_state(Vec::<StorySummary>::new);
+    let editing = use_state(|| Option::<Editing>::None);
+    let confirming = use_state(|| Option::<Confirmation>::None);
+    let errors = use_error_reporter();
+    let client = use_api();
+    let notify = use_notify();
+
+    let reload = {
+        let stories = stories.clone();
+        let url = format!("/api/timelines/{}/stories", props.timeline_id);
+        let client = client.clone();
+        Callback::from(move |_: ()| {
+            let stories = stories.clone();
+            let url = url.clone();
+            let client = client.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                if let Ok(list) = client.get::<Vec<StorySummary>>(&url).await {
+                    stories.set(list);
+                }
+            });
+        })
+    };
+
+    {
+        let reload = reload.clone();
+        use_effect_with(props.timeline_id.clone(), move |_| reload.emit(()));
+    }
+
+    let open = |target: Editing| {
+        let editing = editing.clone();
+        Callback::from(move |_| editing.set(Some(target.clone())))
+    };
+    let close = {
+        let editing = editing.clone();
+        Callback::from(move |_| editing.set(None))
+    };
+
+    let remove = |story: &StorySummary| {
+        let client = client.clone();
+        let url = format!("/api/stories/{}", story.id);
+        let removed = format!("Deleted {}", story.title);
+        let reload = reload.clone();
+        let errors = errors.clone();
+        let notify = notify.clone();
+        let remove = Callback::from(move |_| {
+            let url = url.clone();
+            let removed = removed.clone();
+            let reload = reload.clone();
+            let errors = errors.clone();
+            let notify = notify.clone();
+            let client = client.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match client.send::<serde::de::IgnoredAny>(Request::delete(&url)).await {
+                    Ok(_) => notify.success(removed),
+                    Err(error) => errors.report(error),
+                }
+                reload.emit(());
+            });
+        });
+        let confirmation =
+            Confirmation::new(format!("Delete {}?", story.title), "Its steps and narration are lost.", "Delete", remove);
+        let confirming = confirming.clone();
+        Callback::from(move |_| confirming.set(Some(confirmation.clone())))
+    };
+    let close_confirmation = {
+        let confirming = confirming.clone();
+        Callback::from(move |_| confirming.set(None))
+    };
+
+    if stories.is_empty() && !props.can_edit {
+        return html! {};
+    }
+
+    let steps = |count: i64| if count == 1 { "1 step".to_string() } else { format!("{} steps", count) };
+
+    html! {
+        <div class="card bg-base-100 shadow mt-6">
+            <div class="card-body">
+                <div class="flex items-center justify-between">
+                    <h2 class="card-title">{"Stories"}</h2>
+                    if props.can_edit {
+                        <button class="btn btn-primary btn-sm" onclick={open(Editing::New)}>{"New story"}</button>
+                    }
+                </div>
+                if stories.is_empty() {
+                    <p class="opacity-70">{"Walk readers through this timeline's events in your own order."}</p>
+                }
+                <ul class="divide-y">
+                    {stories.iter().map(|story| html! {
+                        <li class="flex items-center justify-between gap-2 py-2">
+                            <a class="link link-hover" href={format!("/timelines/{}/stories/{}", props.timeline_id, story.id)}>
+                                <span class="font-semibold">{&story.title}</span>
+                                <span class="opacity-70 text-sm ml-2">{steps(story.step_count)}</span>
+                                if let Some(description) = &story.description {
+                                    <p class="opacity-70 text-sm">{description}</p>
+                                }
+                            </a>
+                            if props.can_edit {
+                                <div class="flex gap-2">
+                                    <button class="btn btn-ghost btn-sm" onclick={open(Editing::Story(story.id.clone()))}>
+                                        {"Edit"}
+                                    </button>
+                                    <button class="btn btn-ghost btn-sm" onclick={remove(story)}>{"Delete"}</button>
+                                </div>
+                            }
+                        </li>
+                    }).collect::<Html>()}
+                </ul>
+                if let Some(target) = &*editing {
+                    <StoryEditor
+                        timeline_id={props.timeline_id.clone()}
+                        story_id={match target { Editing::New => None, Editing::Story(id) => Some(id.clone()) }}
+                        on_close={close}
+                        on_saved={reload}
+                    />
+                }
+                <ConfirmDialog confirmation={(*confirming).clone()} on_close={close_confirmation} />
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,248 @@
+use serde::{Deserialize, Serialize};
+use yew::{function_component, html, use_effect_with, use_state, Callback, Html, Properties, TargetCast};
+
+use crate::api::Request;
+use crate::components::api_provider::use_api;
+use crate::components::error_boundary::use_error_reporter;
+use crate::components::modal::Modal;
+use crate::components::notifications::use_notify;
+
+#[derive(Deserialize, Clone, PartialEq)]
+struct EventOption {
+    id: String,
+    title: String,
+    start_date: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+struct Step {
+    event_id: String,
+    narration: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct StoryData {
+    title: String,
+    description: Option<String>,
+    steps: Vec<Step>,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct StoryEditorProps {
+    pub timeline_id: String,
+    /// The story to edit; a new one when unset.
+    #[prop_or_default]
+    pub story_id: Option<String>,
+    pub on_close: Callback<()>,
+    /// Called once the story has been saved.
+    pub on_saved: Callback<()>,
+}
+
+/// Writes a story: its title, and which of the timeline's events it
+/// visits in what order, with narration for each. Saving replaces every
+/// step.
+#[function_component(StoryEditor)]
+pub fn story_editor(props: &StoryEditorProps) -> Html {
+    let events = use_state(|| Option::<Vec<EventOption>>::None);
+    let title = use_state(String::new);
+    let description = use_state(String::new);
+    let steps = use_state(Vec::<Step>::new);
+    let saving = use_state(|| false);
+    let errors = use_error_reporter();
+    let client = use_api();
+    let notify = use_notify();
+
+    {
+        let events = events.clone();
+        let title = title.clone();
+        let description = description.clone();
+        let steps = steps.clone();
+        let errors = errors.clone();
+        let client = client.clone();
+        use_effect_with((props.timeline_id.clone(), props.story_id.clone()), move |(timeline_id, story_id)| {
+            let events_url = format!("/api/timelines/{}/events", timeline_id);
+            let story_url = story_id.as_ref().map(|id| format!("/api/stories/{}", id));
+            wasm_bindgen_futures::spawn_local(async move {
+                if let Some(url) = story_url {
+                    match client.get::<StoryData>(&url).await {
+                        Ok(story) => {
+                            title.set(story.title);
+                            description.set(story.description.unwrap_or_default());
+                            steps.set(story.steps);
+                        }
+                        Err(error) => errors.report(error),
+                    }
+                }
+                match client.get::<Vec<EventOption>>(&events_url).await {
+                    Ok(mut options) => {
+                        options.sort_by(|a, b| a.start_date.cmp(&b.start_date));
+                        events.set(Some(options));
+                    }
+                    Err(error) => errors.report(error),
+                }
+            });
+        });
+    }
+
+    let on_title = {
+        let title = title.clone();
+        Callback::from(move |e: yew::InputEvent| {
+            let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+            title.set(input.value());
+        })
+    };
+    let on_description = {
+        let description = description.clone();
+        Callback::from(move |e: yew::InputEvent| {
+            let input: web_sys::HtmlTextAreaElement = e.target_unchecked_into();
+            description.set(input.value());
+        })
+    };
+
+    let add_step = {
+        let steps = steps.clone();
+        let first = events.iter().flatten().next().map(|event| event.id.clone()).unwrap_or_default();
+        Callback::from(move |_| {
+            let mut list = (*steps).clone();
+            list.push(Step { event_id: first.clone(), narration: None });
+            steps.set(list);
+        })
+    };
+    let on_event = |index: usize| {
+        let steps = steps.clone();
+        Callback::from(move |e: yew::Event| {
+            let select: web_sys::HtmlSelectElement = e.target_unchecked_into();
+            let mut list = (*steps).clone();
+            list[index].event_id = select.value();
+            steps.set(list);
+        })
+    };
+    let on_narration = |index: usize| {
+        let steps = steps.clone();
+        Callback::from(move |e: yew::InputEvent| {
+            let input: web_sys::HtmlTextAreaElement = e.target_unchecked_into();
+            let mut list = (*steps).clone();
+            list[index].narration = Some(input.value()).filter(|text| !text.trim().is_empty());
+            steps.set(list);
+        })
+    };
+    // Move up, move down and remove: `change` rearranges a copy of the list.
+    let rearrange = |index: usize, change: fn(&mut Vec<Step>, usize)| {
+        let steps = steps.clone();
+        Callback::from(move |_| {
+            let mut list = (*steps).clone();
+            change(&mut list, index);
+            steps.set(list);
+        })
+    };
+
+    let submit = {
+        let url = match &props.story_id {
+            Some(id) => format!("/api/stories/{}", id),
+            None => format!("/api/timelines/{}/stories", props.timeline_id),
+        };
+        let editing = props.story_id.is_some();
+        let title = title.clone();
+        let description = description.clone();
+        let steps = steps.clone();
+        let saving = saving.clone();
+        let on_saved = props.on_saved.clone();
+        let on_close = props.on_close.clone();
+        let client = client.clone();
+        Callback::from(move |_| {
+            let body = serde_json::json!({
+                "title": *title,
+                "description": Some(description.trim()).filter(|text| !text.is_empty()),
+                "steps": *steps,
+            });
+            let request = if editing { Request::put(&url) } else { Request::post(&url) };
+            let saving = saving.clone();
+            let errors = errors.clone();
+            let notify = notify.clone();
+            let on_saved = on_saved.clone();
+            let on_close = on_close.clone();
+            saving.set(true);
+            let client = client.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match client.send_json::<serde::de::IgnoredAny>(request, &body).await {
+                    Ok(_) => {
+                        notify.success(if editing { "Story saved" } else { "Story created" });
+                        on_saved.emit(());
+                        on_close.emit(());
+                    }
+                    Err(error) => {
+                        saving.set(false);
+                        errors.report(error);
+                    }
+                }
+            });
+        })
+    };
+
+    let cancel = props.on_close.reform(|_| ());
+    let ready = !title.trim().is_empty() && !steps.is_empty() && steps.iter().all(|step| !step.event_id.is_empty());
+    let actions = html! {
+        <>
+            <button class="btn" onclick={cancel}>{"Cancel"}</button>
+            <button class="btn btn-primary" disabled={*saving || !ready} onclick={submit}>{"Save"}</button>
+        </>
+    };
+    let heading = if props.story_id.is_some() { "Edit story" } else { "New story" };
+    let options = events.as_deref().unwrap_or_default();
+
+    html! {
+        <Modal title={heading} on_close={props.on_close.clone()} {actions}>
+            <div class="flex flex-col gap-3 py-4">
+                <label class="form-control">
+                    <span class="label-text mb-1">{"Title"}</span>
+                    <input class="input input-bordered" maxlength="255" value={(*title).clone()} oninput={on_title} />
+                </label>
+                <label class="form-control">
+                    <span class="label-text mb-1">{"Introduction"}</span>
+                    <textarea class="textarea textarea-bordered" rows="2" maxlength="2000"
+                        value={(*description).clone()} oninput={on_description} />
+                </label>
+                if events.is_some() && options.is_empty() {
+                    <p class="opacity-70">{"Add events to this timeline before telling a story with them."}</p>
+                }
+                <ol class="flex flex-col gap-3">
+                    {steps.iter().enumerate().map(|(index, step)| {
+                        let up = rearrange(index, |list, index| list.swap(index - 1, index));
+                        let down = rearrange(index, |list, index| list.swap(index, index + 1));
+                        let remove = rearrange(index, |list, index| {
+                            list.remove(index);
+                        });
+                        html! {
+                            <li class="border rounded-box p-3 flex flex-col gap-2">
+                                <div class="flex items-center gap-2">
+                                    <span class="font-semibold">{index + 1}</span>
+                                    <select class="select select-bordered select-sm flex-1" aria-label="Event"
+                                        onchange={on_event(index)}>
+                                        {options.iter().map(|event| html! {
+                                            <option value={event.id.clone()} selected={event.id == step.event_id}>
+                                                {format!("{} · {}", event.start_date.split('T').next().unwrap_or_default(), event.title)}
+                                            </option>
+                                        }).collect::<Html>()}
+                                    </select>
+                                    <button class="btn btn-ghost btn-xs" aria-label="Move up" disabled={index == 0}
+                                        onclick={up}>{"↑"}</button>
+                                    <button class="btn btn-ghost btn-xs" aria-label="Move down"
+                                        disabled={index + 1 == steps.len()} onclick={down}>{"↓"}</button>
+                                    <button class="btn btn-ghost btn-xs" aria-label="Remove step" onclick={remove}>
+                                        {"✕"}
+                                    </button>
+                                </div>
+                                <textarea class="textarea textarea-bordered textarea-sm" rows="2" maxlength="5000"
+                                    placeholder="What happens at this step"
+                                    value={step.narration.clone().unwrap_or_default()} oninput={on_narration(index)} />
+                            </li>
+                        }
+                    }).collect::<Html>()}
+                </ol>
+                <button class="btn btn-outline btn-sm self-start" disabled={options.is_empty()} onclick={add_step}>
+                    {"Add step"}
+                </button>
+            </div>
+        </Modal>
+    }
+}
@@ -1,6 +1,60 @@
-use yew::{function_component, html, use_state, Html, Callback};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use yew::{
+    classes, function_component, html, use_mut_ref, use_node_ref, use_state, Html, Callback, KeyboardEvent,
+    Properties, PointerEvent, TargetCast, UseStateHandle, WheelEvent,
+};
 use serde::{Deserialize, Serialize};
-use gloo_net::http::Request;
+use wasm_bindgen::{closure::Closure, JsCast};
+use web_sys::HtmlElement;
+
+use crate::api::{self, ApiClient, FetchError, Request};
+use crate::appearance;
+use crate::components::api_provider::use_api;
+use crate::components::category_filter::{CategoryFilter, UNCATEGORIZED};
+use crate::components::error_boundary::{use_error_reporter, ErrorReporter};
+use crate::components::load_error::{use_retry, LoadError};
+use crate::components::minimap::Minimap;
+use crate::components::period_rail::PeriodRail;
+use crate::components::popover::{Anchor, Popover};
+use crate::components::skeleton::{Shape, Skeleton};
+use crate::lanes::{self, Extent};
+use crate::rum;
+use crate::time_scale::{self, TimeScale, Unit};
+use crate::timeline_url::ViewState;
+
+/// Pixels a press must travel before it counts as a drag rather than a click.
+const DRAG_THRESHOLD: f64 = 4.0;
+/// Zoom factor of the toolbar buttons.
+const ZOOM_STEP: f64 = 1.5;
+/// Markers this far outside the view are not rendered.
+const OVERSCAN: f64 = 200.0;
+const LANE_HEIGHT: f64 = 32.0;
+/// Room below the lanes for the axis and its tick labels.
+const AXIS_HEIGHT: f64 = 56.0;
+/// Width of a point event's dot, and the minimum width of a range bar.
+const MARKER_SIZE: f64 = 12.0;
+const MAX_LABEL_WIDTH: f64 = 160.0;
+/// Vertical spacing of the names of overlapping periods.
+const PERIOD_LABEL_HEIGHT: f64 = 16.0;
+/// Days shown around a focused date.
+const FOCUS_SPAN_DAYS: f64 = 2.0 * 365.25;
+/// Swipe speed, in px/ms, a released pan needs to keep coasting.
+const FLING_SPEED: f64 = 0.3;
+/// A coasting pan stops once it slows below this, in px/ms.
+const STOP_SPEED: f64 = 0.02;
+/// Share of a coasting pan's speed kept per 16ms frame.
+const FRICTION: f64 = 0.95;
+/// How long a hover popover outlives the pointer leaving it, so the
+/// pointer can cross the gap from the marker into it.
+const HIDE_DELAY_MS: i32 = 150;
+/// Least importance shown once the view spans more than so many years;
+/// narrower views show every event.
+const LEVELS_OF_DETAIL: [(f64, i16); 4] = [(500.0, 5), (100.0, 4), (25.0, 3), (5.0, 2)];
+/// Seconds each event stays up during playback, as offered to choose from.
+const STEP_SECONDS: [u32; 4] = [3, 5, 10, 20];
+const DEFAULT_STEP_SECONDS: u32 = 5;
 
 #[derive(Serialize, Deserialize, Clone)]
 struct TimelineEvent {
@@ -9,56 +63,1533 @@ struct TimelineEvent {
     description: Option<String>,
     start_date: String,
     end_date: Option<String>,
+    /// Bounds of an uncertain start, drawn as a whisker around the marker.
+    #[serde(default)]
+    start_date_min: Option<String>,
+    #[serde(default)]
+    start_date_max: Option<String>,
     location: Option<String>,
     image_url: Option<String>,
     category: Option<String>,
+    /// 1 (minor) to 5 (major).
+    importance: i16,
+    /// Overrides the category colour.
+    color: Option<String>,
+    icon: Option<String>,
+}
+
+/// A named era drawn as a band behind the events. Both dates are whole
+/// days and the end day is included.
+#[derive(Deserialize, Clone)]
+struct Period {
+    name: String,
+    start_date: String,
+    end_date: String,
+    color: String,
+}
+
+/// One category's swimlane, with how many events it holds in all.
+#[derive(Deserialize, Clone)]
+struct Lane {
+    category: String,
+    count: i64,
+}
+
+#[derive(Deserialize)]
+struct Page<T> {
+    data: Vec<T>,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct TimelineProps {
+    /// Show only this timeline's events; all events when unset.
+    #[prop_or_default]
+    pub timeline_id: Option<String>,
+    /// Let the viewer drag markers to change event dates.
+    #[prop_or_default]
+    pub editable: bool,
+    /// Open centered on this date (`YYYY-MM-DD`) instead of fitting all
+    /// events. A range in the URL takes precedence.
+    #[prop_or_default]
+    pub focus: Option<String>,
+    /// Center on this event and mark it, as a story does for its current
+    /// step. Changing it moves the view to the new event.
+    #[prop_or_default]
+    pub highlight: Option<String>,
+    /// Bumped by the page after it changes the timeline's periods, so the
+    /// bands are fetched again.
+    #[prop_or_default]
+    pub periods_revision: u32,
+    /// Render the same pixels for the same events every time, for
+    /// screenshots and layout tests.
+    #[prop_or_default]
+    pub deterministic: Option<Deterministic>,
+}
+
+/// A fixed rendering: `width` instead of the track's measured width, no
+/// transitions or coasting pans, the URL neither read nor written, and
+/// events starting together laid out in an order picked by `seed` rather
+/// than the order the API listed them in.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Deterministic {
+    pub width: f64,
+    pub seed: u64,
+}
+
+/// What a held pointer on the track is doing.
+#[derive(Clone)]
+enum Drag {
+    Pan { last_x: f64 },
+    /// A press on a marker. It stays a click until it passes
+    /// `DRAG_THRESHOLD`; read-only views turn it into a pan instead.
+    Marker { id: String, origin_x: f64, moved: bool, preview: f64, touch: bool },
+    /// Two fingers on the track, `span` apart around `mid`.
+    Pinch { span: f64, mid: f64 },
+}
+
+/// Pointer bookkeeping that has to see every event, not only the last one
+/// before a render: where each finger is, and how fast the view is moving.
+#[derive(Default)]
+struct Gesture {
+    /// Fingers on the track, by pointer id, at their latest x.
+    touches: Vec<(i32, f64)>,
+    /// Pan speed in px/ms, smoothed over the last few moves.
+    speed: f64,
+    sample_x: f64,
+    sampled_at: f64,
+    /// Bumped when the track is touched, stopping any coasting pan.
+    generation: u32,
+}
+
+impl Gesture {
+    /// Span and midpoint of the first two fingers, when there are two.
+    fn pinch(&self) -> Option<(f64, f64)> {
+        match self.touches[..] {
+            [(_, a), (_, b), ..] => Some(((a - b).abs(), (a + b) / 2.0)),
+            _ => None,
+        }
+    }
+
+    /// Starts measuring pan speed afresh from `x`.
+    fn resample(&mut self, x: f64) {
+        self.speed = 0.0;
+        self.sample_x = x;
+        self.sampled_at = rum::now();
+    }
+}
+
+/// Dates of one event before and after a drag, kept for undo.
+#[derive(Clone)]
+struct Move {
+    id: String,
+    title: String,
+    from: (String, Option<String>),
+    to: (String, Option<String>),
 }
 
 #[function_component(Timeline)]
-pub fn timeline() -> Html {
-    let events = use_state(|| Vec::<TimelineEvent>::new());
+pub fn timeline(props: &TimelineProps) -> Html {
+    let client = use_api();
+    let events = use_state(Vec::<TimelineEvent>::new);
     let loading = use_state(|| true);
-    
+    let scale = use_state(|| Option::<TimeScale>::None);
+    let drag = use_state(|| Option::<Drag>::None);
+    let last_move = use_state(|| Option::<Move>::None);
+    let gesture = use_mut_ref(Gesture::default);
+    let coasting = use_state(|| false);
+    // Event tapped on a touch screen, shown in a popover rather than opened.
+    let selected = use_state(|| Option::<String>::None);
+    // Event under the mouse, and the marker its popover points at.
+    let hovered = use_state(|| Option::<(String, Anchor)>::None);
+    // Bumped whenever the pointer enters a marker or its popover; a pending
+    // hide only goes ahead if it hasn't been since.
+    let hover_generation = use_mut_ref(|| 0u32);
+    let restored = use_state(|| match props.deterministic {
+        Some(_) => ViewState::default(),
+        None => ViewState::from_url(),
+    });
+    let group_by_category = use_state(|| restored.grouped);
+    let categories = use_state(|| restored.categories.clone());
+    let collapsed = use_state(|| restored.collapsed.clone());
+    let swimlanes = use_state(Vec::<Lane>::new);
+    let error = use_state(|| Option::<FetchError>::None);
+    let (attempt, retry) = use_retry();
+    let errors = use_error_reporter();
+    let track = use_node_ref();
+    // Playback: the position in `playlist` being presented, whether it is
+    // paused there, and how long each event stays up.
+    let playing = use_state(|| Option::<usize>::None);
+    let paused = use_state(|| false);
+    let step_seconds = use_state(|| DEFAULT_STEP_SECONDS);
+    let container = use_node_ref();
+    let periods = use_state(Vec::<Period>::new);
+
     {
         let events = events.clone();
         let loading = loading.clone();
-        yew::use_effect_with_deps(
-            move |_| {
-                let fetch_events = async move {
-                    let response = Request::get("/api/events")
-                        .send()
-                        .await
-                        .unwrap();
-                    let events_data: Vec<TimelineEvent> = response.json().await.unwrap();
-                    events.set(events_data);
-                    loading.set(false);
+        let error = error.clone();
+        let seed = props.deterministic.map(|mode| mode.seed);
+        let client = client.clone();
+        yew::use_effect_with((props.timeline_id.clone(), attempt), move |(timeline_id, _): &(Option<String>, u32)| {
+            let timeline_id = timeline_id.clone();
+            error.set(None);
+            let fetch_events = async move {
+                let fetched = match timeline_id {
+                    Some(id) => client.get::<Vec<TimelineEvent>>(&format!("/api/timelines/{}/events", id)).await,
+                    None => client.get::<Page<TimelineEvent>>("/api/events").await.map(|page| page.data),
                 };
-                wasm_bindgen_futures::spawn_local(fetch_events);
+                match fetched {
+                    Ok(mut events_data) => {
+                        if let Some(seed) = seed {
+                            events_data.sort_by_cached_key(|event| {
+                                let day = time_scale::parse_date(&event.start_date).unwrap_or(0.0);
+                                (day.floor() as i64, seeded(seed, &event.id))
+                            });
+                        }
+                        events.set(events_data);
+                        loading.set(false);
+                    }
+                    Err(fetch_error) => error.set(Some(fetch_error)),
+                }
+            };
+            wasm_bindgen_futures::spawn_local(fetch_events);
+        });
+    }
+
+    // Periods only exist on timelines, and the events read fine without
+    // their bands, so a failed fetch leaves them out.
+    {
+        let periods = periods.clone();
+        let client = client.clone();
+        yew::use_effect_with((props.timeline_id.clone(), props.periods_revision), move |(timeline_id, _)| {
+            let url = timeline_id.as_ref().map(|id| format!("/api/timelines/{}/periods", id));
+            wasm_bindgen_futures::spawn_local(async move {
+                let fetched = match url {
+                    Some(url) => client.get::<Vec<Period>>(&url).await.unwrap_or_default(),
+                    None => Vec::new(),
+                };
+                periods.set(fetched);
+            });
+        });
+    }
+
+    // Swimlanes are ordered and counted over every event, not just the
+    // loaded ones. Without them the lanes fall back to the loaded events.
+    {
+        let swimlanes = swimlanes.clone();
+        let client = client.clone();
+        yew::use_effect_with(
+            (props.timeline_id.clone(), *group_by_category, events.len()),
+            move |(timeline_id, grouped, _)| {
+                if *grouped {
+                    let url = match timeline_id {
+                        Some(id) => format!("/api/events/lanes?timeline_id={}", id),
+                        None => "/api/events/lanes".to_string(),
+                    };
+                    wasm_bindgen_futures::spawn_local(async move {
+                        swimlanes.set(client.get::<Vec<Lane>>(&url).await.unwrap_or_default());
+                    });
+                }
             },
-            vec![],
         );
     }
 
+    yew::use_effect_with(*loading, |loading| {
+        if !*loading {
+            rum::record_once("timeline_first_render", rum::now());
+        }
+    });
+
+    // Restore the view from the URL, or else center it on the focused date
+    // or fit it to the events, once the track is on screen; and keep the
+    // scale's width in step with the track as the window resizes.
+    {
+        let scale = scale.clone();
+        let events = events.clone();
+        let track = track.clone();
+        let range = restored.range;
+        let focus = props.focus.as_deref().and_then(time_scale::parse_date);
+        let fixed_width = props.deterministic.map(|mode| mode.width);
+        yew::use_effect_with(*loading, move |loading| {
+            let width = move || {
+                fixed_width.or_else(|| track.cast::<HtmlElement>().map(|el| el.client_width() as f64))
+            };
+            if !*loading {
+                if let Some(width) = width() {
+                    scale.set(Some(match (range, focus) {
+                        (Some((start, end)), _) => TimeScale { start, end, width },
+                        (None, Some(day)) => TimeScale::centered(day, FOCUS_SPAN_DAYS, width),
+                        (None, None) => TimeScale::fit(start_days(&events), width),
+                    }));
+                }
+            }
+
+            let on_resize = {
+                let scale = scale.clone();
+                Closure::<dyn Fn()>::new(move || {
+                    if let (Some(current), Some(width)) = (*scale, width()) {
+                        scale.set(Some(current.with_width(width)));
+                    }
+                })
+            };
+            let window = gloo_utils::window();
+            window
+                .add_event_listener_with_callback("resize", on_resize.as_ref().unchecked_ref())
+                .ok();
+            move || {
+                window
+                    .remove_event_listener_with_callback("resize", on_resize.as_ref().unchecked_ref())
+                    .ok();
+            }
+        });
+    }
+
+    // Mirror the view into the URL, leaving it alone mid-drag and while
+    // a swipe coasts to a stop.
+    {
+        let view = scale.filter(|_| props.deterministic.is_none()).map(|current| ViewState {
+            range: Some((current.start, current.end)),
+            categories: (*categories).clone(),
+            grouped: *group_by_category,
+            collapsed: (*collapsed).clone(),
+        });
+        yew::use_effect_with((view, drag.is_some() || *coasting), |(view, dragging)| {
+            if let (Some(view), false) = (view, dragging) {
+                view.write_to_url();
+            }
+        });
+    }
+
+    // During playback, center the view on each event in turn...
+    let list = playlist(&events, &categories);
+    {
+        let scale = scale.clone();
+        let day = playing.and_then(|index| list.get(index)).map(|&(_, day)| day);
+        yew::use_effect_with(day, move |day: &Option<f64>| {
+            if let (Some(day), Some(current)) = (*day, *scale) {
+                scale.set(Some(TimeScale::centered(day, FOCUS_SPAN_DAYS, current.width)));
+            }
+        });
+    }
+
+    // ...and move on to the next once its time is up, pausing on the last.
+    {
+        let playing = playing.clone();
+        let paused = paused.clone();
+        yew::use_effect_with((*playing, *paused, *step_seconds, list.len()), move |&(index, stopped, seconds, count)| {
+            let window = gloo_utils::window();
+            let timer = match (index, stopped) {
+                (Some(index), false) => {
+                    let tick = Closure::<dyn Fn()>::new(move || match index + 1 < count {
+                        true => playing.set(Some(index + 1)),
+                        false => paused.set(true),
+                    });
+                    let handle = window
+                        .set_timeout_with_callback_and_timeout_and_arguments_0(
+                            tick.as_ref().unchecked_ref(),
+                            seconds as i32 * 1000,
+                        )
+                        .ok();
+                    Some((tick, handle))
+                }
+                _ => None,
+            };
+            move || {
+                if let Some((_tick, Some(handle))) = timer {
+                    window.clear_timeout_with_handle(handle);
+                }
+            }
+        });
+    }
+
+    // Center on the highlighted event once the track is measured and
+    // whenever the highlight moves.
+    let highlighted = props.highlight.as_ref().and_then(|id| events.iter().find(|event| &event.id == id));
+    {
+        let scale = scale.clone();
+        let day = highlighted.and_then(|event| time_scale::parse_date(&event.start_date));
+        yew::use_effect_with((day, scale.is_some()), move |&(day, ready): &(Option<f64>, bool)| {
+            if let (Some(day), true, Some(current)) = (day, ready, *scale) {
+                scale.set(Some(TimeScale::centered(day, FOCUS_SPAN_DAYS, current.width)));
+            }
+        });
+    }
+
+    if let Some(fetch_error) = &*error {
+        return html! { <LoadError error={fetch_error.clone()} onretry={retry} /> };
+    }
     if *loading {
-        return html! { <div class="text-center">Loading timeline...</div> };
+        return html! { <Skeleton shape={Shape::Timeline} /> };
     }
 
+    // Pointer position relative to the track's left edge.
+    let track_x = {
+        let track = track.clone();
+        move |client_x: i32| {
+            track
+                .cast::<HtmlElement>()
+                .map(|el| client_x as f64 - el.get_bounding_client_rect().left())
+                .unwrap_or(0.0)
+        }
+    };
+
+    // Capturing on the track keeps moves and the release coming to it even
+    // when the pointer leaves the element mid-drag.
+    let capture = {
+        let track = track.clone();
+        move |e: &PointerEvent| {
+            if let Some(el) = track.cast::<HtmlElement>() {
+                el.set_pointer_capture(e.pointer_id()).ok();
+            }
+        }
+    };
+
+    // Notes a new press, stopping any coasting pan. Returns the pinch it
+    // starts if it is a second finger.
+    let press = {
+        let gesture = gesture.clone();
+        let coasting = coasting.clone();
+        let selected = selected.clone();
+        let hovered = hovered.clone();
+        move |e: &PointerEvent, x: f64| {
+            let mut gesture = gesture.borrow_mut();
+            gesture.generation += 1;
+            gesture.resample(x);
+            coasting.set(false);
+            selected.set(None);
+            hovered.set(None);
+            if e.pointer_type() == "touch" {
+                gesture.touches.push((e.pointer_id(), x));
+            }
+            gesture.pinch().map(|(span, mid)| Drag::Pinch { span, mid })
+        }
+    };
+
+    let onpointerdown = {
+        let drag = drag.clone();
+        let track_x = track_x.clone();
+        let capture = capture.clone();
+        let press = press.clone();
+        Callback::from(move |e: PointerEvent| {
+            capture(&e);
+            let x = track_x(e.client_x());
+            drag.set(Some(press(&e, x).unwrap_or(Drag::Pan { last_x: x })));
+        })
+    };
+
+    let onpointermove = {
+        let drag = drag.clone();
+        let scale = scale.clone();
+        let gesture = gesture.clone();
+        let editable = props.editable;
+        let track_x = track_x.clone();
+        Callback::from(move |e: PointerEvent| {
+            let (Some(current_drag), Some(current)) = ((*drag).clone(), *scale) else {
+                return;
+            };
+            let x = track_x(e.client_x());
+            let mut gesture = gesture.borrow_mut();
+            if let Some(touch) = gesture.touches.iter_mut().find(|(id, _)| *id == e.pointer_id()) {
+                touch.1 = x;
+            }
+            match current_drag {
+                Drag::Pan { last_x } => {
+                    let now = rum::now();
+                    let elapsed = now - gesture.sampled_at;
+                    if elapsed > 0.0 {
+                        gesture.speed = 0.8 * (x - gesture.sample_x) / elapsed + 0.2 * gesture.speed;
+                        gesture.sample_x = x;
+                        gesture.sampled_at = now;
+                    }
+                    scale.set(Some(current.pan(x - last_x)));
+                    drag.set(Some(Drag::Pan { last_x: x }));
+                }
+                // Pan with the fingers' midpoint and zoom around it by how
+                // far they spread.
+                Drag::Pinch { span, mid } => {
+                    let Some((new_span, new_mid)) = gesture.pinch() else {
+                        return;
+                    };
+                    if span > 0.0 && new_span > 0.0 {
+                        scale.set(Some(current.pan(new_mid - mid).zoom(new_span / span, new_mid)));
+                    }
+                    drag.set(Some(Drag::Pinch { span: new_span, mid: new_mid }));
+                }
+                Drag::Marker { origin_x, moved: false, .. } if (x - origin_x).abs() <= DRAG_THRESHOLD => {}
+                Drag::Marker { .. } if !editable => {
+                    gesture.resample(x);
+                    drag.set(Some(Drag::Pan { last_x: x }));
+                }
+                Drag::Marker { id, origin_x, touch, .. } => {
+                    let preview = current.snap(current.day_at(x));
+                    drag.set(Some(Drag::Marker { id, origin_x, moved: true, preview, touch }));
+                }
+            }
+        })
+    };
+
+    let onpointerup = {
+        let drag = drag.clone();
+        let scale = scale.clone();
+        let gesture = gesture.clone();
+        let coasting = coasting.clone();
+        let selected = selected.clone();
+        let events = events.clone();
+        let last_move = last_move.clone();
+        let errors = errors.clone();
+        let timeline_id = props.timeline_id.clone();
+        let still = props.deterministic.is_some();
+        let client = client.clone();
+        Callback::from(move |e: PointerEvent| {
+            let finished = (*drag).clone();
+            let remaining = {
+                let mut gesture = gesture.borrow_mut();
+                gesture.touches.retain(|(id, _)| *id != e.pointer_id());
+                gesture.touches.first().map(|(_, x)| *x)
+            };
+
+            match finished {
+                // Lifting one finger of a pinch carries on as a pan with
+                // the other.
+                Some(Drag::Pinch { .. }) => {
+                    drag.set(remaining.map(|x| {
+                        gesture.borrow_mut().resample(x);
+                        Drag::Pan { last_x: x }
+                    }));
+                    return;
+                }
+                Some(Drag::Pan { .. }) if e.pointer_type() == "touch" && !still => {
+                    if let Some(current) = *scale {
+                        coast(scale.clone(), coasting.clone(), gesture.clone(), current);
+                    }
+                }
+                _ => {}
+            }
+            drag.set(None);
+
+            let Some(Drag::Marker { id, moved, preview, touch, .. }) = finished else {
+                return;
+            };
+            // A tap on a touch screen, with no hover to preview the event,
+            // shows it in a popover first.
+            if !moved && touch {
+                selected.set(Some(id));
+                return;
+            }
+            if !moved {
+                let href = event_href(timeline_id.as_deref(), &id);
+                gloo_utils::window().location().set_href(&href).ok();
+                return;
+            }
+            let Some(event) = events.iter().find(|event| event.id == id) else {
+                return;
+            };
+            let Some(old_start) = time_scale::parse_date(&event.start_date) else {
+                return;
+            };
+
+            // An event with an end date moves as a whole, keeping its length.
+            let shift = preview - old_start;
+            let new_end = event
+                .end_date
+                .as_deref()
+                .and_then(time_scale::parse_date)
+                .map(|end| time_scale::format_date(end + shift));
+            let change = Move {
+                id: event.id.clone(),
+                title: event.title.clone(),
+                from: (event.start_date.clone(), event.end_date.clone()),
+                to: (time_scale::format_date(preview), new_end),
+            };
+            reschedule(client.clone(), events.clone(), errors.clone(), change.id.clone(), change.to.clone());
+            last_move.set(Some(change));
+        })
+    };
+
+    let onwheel = {
+        let scale = scale.clone();
+        let hovered = hovered.clone();
+        let track_x = track_x.clone();
+        Callback::from(move |e: WheelEvent| {
+            e.prevent_default();
+            hovered.set(None);
+            if let Some(current) = *scale {
+                let factor = 1.0015_f64.powf(-e.delta_y());
+                scale.set(Some(current.zoom(factor, track_x(e.client_x()))));
+            }
+        })
+    };
+
+    // Arrow keys pan by a tenth of the view, +/- zoom and Home fits.
+    // During playback the keys step through events instead.
+    let onkeydown = {
+        let scale = scale.clone();
+        let events = events.clone();
+        let presenting = playing.is_some();
+        Callback::from(move |e: KeyboardEvent| {
+            let (Some(current), false) = (*scale, presenting) else {
+                return;
+            };
+            let next = match e.key().as_str() {
+                "ArrowLeft" => current.pan(current.width / 10.0),
+                "ArrowRight" => current.pan(-current.width / 10.0),
+                "+" | "=" => current.zoom(ZOOM_STEP, current.width / 2.0),
+                "-" => current.zoom(1.0 / ZOOM_STEP, current.width / 2.0),
+                "Home" => TimeScale::fit(start_days(&events), current.width),
+                _ => return,
+            };
+            e.prevent_default();
+            scale.set(Some(next));
+        })
+    };
+
+    let zoom_by = |factor: f64| {
+        let scale = scale.clone();
+        Callback::from(move |_| {
+            if let Some(current) = *scale {
+                scale.set(Some(current.zoom(factor, current.width / 2.0)));
+            }
+        })
+    };
+
+    let fit = {
+        let scale = scale.clone();
+        let events = events.clone();
+        Callback::from(move |_| {
+            if let Some(current) = *scale {
+                scale.set(Some(TimeScale::fit(start_days(&events), current.width)));
+            }
+        })
+    };
+
+    let undo = {
+        let events = events.clone();
+        let last_move = last_move.clone();
+        let errors = errors.clone();
+        let client = client.clone();
+        Callback::from(move |_| {
+            if let Some(change) = (*last_move).clone() {
+                reschedule(client.clone(), events.clone(), errors.clone(), change.id, change.from);
+                last_move.set(None);
+            }
+        })
+    };
+
+    let keep_hover = {
+        let hover_generation = hover_generation.clone();
+        Callback::from(move |_: PointerEvent| *hover_generation.borrow_mut() += 1)
+    };
+    let hide_hover = {
+        let hovered = hovered.clone();
+        let hover_generation = hover_generation.clone();
+        Callback::from(move |_: PointerEvent| {
+            let generation = *hover_generation.borrow();
+            let hovered = hovered.clone();
+            let hover_generation = hover_generation.clone();
+            let hide = Closure::once_into_js(move || {
+                if *hover_generation.borrow() == generation {
+                    hovered.set(None);
+                }
+            });
+            gloo_utils::window()
+                .set_timeout_with_callback_and_timeout_and_arguments_0(hide.unchecked_ref(), HIDE_DELAY_MS)
+                .ok();
+        })
+    };
+
+    let close_popover = {
+        let selected = selected.clone();
+        Callback::from(move |_| selected.set(None))
+    };
+
+    let on_view_change = {
+        let scale = scale.clone();
+        Callback::from(move |view: TimeScale| scale.set(Some(view)))
+    };
+
+    let on_period = {
+        let scale = scale.clone();
+        Callback::from(move |(start, end): (f64, f64)| {
+            if let Some(current) = *scale {
+                scale.set(Some(TimeScale { start, end, width: current.width }));
+            }
+        })
+    };
+
+    // Playback starts from the first event in view, or else the first.
+    let play = {
+        let playing = playing.clone();
+        let paused = paused.clone();
+        let days: Vec<f64> = list.iter().map(|&(_, day)| day).collect();
+        let from = scale.map(|current| current.start);
+        Callback::from(move |_| {
+            let first = from.and_then(|start| days.iter().position(|&day| day >= start)).unwrap_or(0);
+            paused.set(false);
+            playing.set(Some(first));
+        })
+    };
+    let step = |by: isize| {
+        let playing = playing.clone();
+        let count = list.len();
+        Callback::from(move |_: ()| {
+            if let Some(index) = *playing {
+                let next = (index as isize + by).clamp(0, count as isize - 1);
+                playing.set(Some(next as usize));
+            }
+        })
+    };
+    let (previous, next) = (step(-1), step(1));
+    let toggle_pause = {
+        let paused = paused.clone();
+        Callback::from(move |_: ()| paused.set(!*paused))
+    };
+    let stop = {
+        let playing = playing.clone();
+        Callback::from(move |_: ()| {
+            let document = gloo_utils::document();
+            if document.fullscreen_element().is_some() {
+                document.exit_fullscreen();
+            }
+            playing.set(None);
+        })
+    };
+    let toggle_fullscreen = {
+        let container = container.clone();
+        Callback::from(move |_| {
+            let document = gloo_utils::document();
+            if document.fullscreen_element().is_some() {
+                document.exit_fullscreen();
+            } else if let Some(el) = container.cast::<web_sys::Element>() {
+                el.request_fullscreen().ok();
+            }
+        })
+    };
+    let on_step_seconds = {
+        let step_seconds = step_seconds.clone();
+        Callback::from(move |e: yew::Event| {
+            let select: web_sys::HtmlSelectElement = e.target_unchecked_into();
+            if let Ok(seconds) = select.value().parse() {
+                step_seconds.set(seconds);
+            }
+        })
+    };
+    // Space pauses, arrows step and Escape ends playback.
+    let playback_keys = {
+        let (previous, next, toggle_pause, stop) = (previous.clone(), next.clone(), toggle_pause.clone(), stop.clone());
+        let presenting = playing.is_some();
+        Callback::from(move |e: KeyboardEvent| {
+            if !presenting {
+                return;
+            }
+            let action = match e.key().as_str() {
+                " " => &toggle_pause,
+                "ArrowLeft" => &previous,
+                "ArrowRight" => &next,
+                "Escape" => &stop,
+                _ => return,
+            };
+            e.prevent_default();
+            action.emit(());
+        })
+    };
+    let presented = playing.and_then(|index| list.get(index)).map(|&(event, _)| event).or(highlighted);
+
+    let layout = |grouped: bool| {
+        let group_by_category = group_by_category.clone();
+        Callback::from(move |_| group_by_category.set(grouped))
+    };
+
+    let mut available: Vec<&str> = events.iter().map(category_name).collect();
+    available.sort_unstable();
+    available.dedup();
+    let shown_category =
+        |category: &str| categories.is_empty() || categories.iter().any(|name| name.eq_ignore_ascii_case(category));
+    let shown = |event: &TimelineEvent| shown_category(category_name(event));
+    // Counts cover the dates in view, so they change as the view moves.
+    let in_view = |event: &TimelineEvent| match (*scale, time_scale::parse_date(&event.start_date)) {
+        (Some(current), Some(day)) => day >= current.start && day <= current.end,
+        _ => true,
+    };
+    let category_counts: Vec<(String, i64)> = available
+        .iter()
+        .map(|name| {
+            let count = events.iter().filter(|event| category_name(event) == *name && in_view(event)).count();
+            (name.to_string(), count as i64)
+        })
+        .collect();
+    let on_categories = {
+        let categories = categories.clone();
+        Callback::from(move |selected: Vec<String>| categories.set(selected))
+    };
+
+    // Zoomed out, minor events give way to major ones, but never so far
+    // that a view with events in it shows none.
+    let threshold = scale.map_or(1, |current| {
+        let most = events.iter().filter(|event| shown(event) && in_view(event)).map(|event| event.importance).max();
+        min_importance(current.span()).min(most.unwrap_or(1))
+    });
+    let hidden = events
+        .iter()
+        .filter(|event| shown(event) && in_view(event) && event.importance < threshold)
+        .count();
+
+    let mut lane_count = 0;
+    let (ticks, bands, markers) = match *scale {
+        Some(current) => {
+            let label_unit = match current.tick_unit() {
+                Unit::Day => Unit::Day,
+                Unit::Month => Unit::Month,
+                _ => Unit::Year,
+            };
+            let ticks = current.ticks().into_iter().map(|tick| html! {
+                <div class="timeline-tick" style={format!("left: {:.1}px", current.x(tick))}>
+                    <span>{time_scale::label(tick, label_unit)}</span>
+                </div>
+            }).collect::<Html>();
+
+            // Lay out from the saved dates so a marker being dragged keeps
+            // its lane instead of jumping between lanes under the pointer.
+            let visible: Vec<(&TimelineEvent, f64, Extent)> = events
+                .iter()
+                .filter(|event| {
+                    shown(event) && (event.importance >= threshold || presented.is_some_and(|on| on.id == event.id))
+                })
+                .filter_map(|event| {
+                    let start = current.x(time_scale::parse_date(&event.start_date)?);
+                    let bar = event
+                        .end_date
+                        .as_deref()
+                        .and_then(time_scale::parse_date)
+                        .map_or(MARKER_SIZE, |end| (current.x(end) - start).max(MARKER_SIZE));
+                    let extent = Extent { start, end: start + bar + label_width(&event.title) };
+                    (extent.end > -OVERSCAN && extent.start < current.width + OVERSCAN).then_some((event, bar, extent))
+                })
+                .collect();
+            // A collapsed swimlane keeps one row with a tick per event.
+            let is_folded = |event: &TimelineEvent| {
+                *group_by_category
+                    && collapsed.iter().any(|name| name == category_name(event))
+                    && presented.is_none_or(|on| on.id != event.id)
+            };
+            let (folded, visible): (Vec<_>, Vec<_>) = visible.into_iter().partition(|(event, _, _)| is_folded(event));
+            let extents: Vec<Extent> = visible.iter().map(|(_, _, extent)| *extent).collect();
+
+            let (lanes, bands) = if *group_by_category {
+                let groups: Vec<&str> = visible.iter().map(|(event, _, _)| category_name(event)).collect();
+                let mut keys: Vec<&str> = Vec::new();
+                let listed = swimlanes.iter().map(|lane| lane.category.as_str());
+                for name in listed.chain(collapsed.iter().map(String::as_str)) {
+                    if shown_category(name) && !keys.contains(&name) {
+                        keys.push(name);
+                    }
+                }
+                lanes::assign_grouped(&extents, &groups, &keys)
+            } else {
+                (lanes::assign(&extents).0, Vec::new())
+            };
+            lane_count = bands
+                .last()
+                .map(|(_, first, count)| first + count)
+                .unwrap_or_else(|| lanes.iter().map(|lane| lane + 1).max().unwrap_or(0));
+
+            let bands = bands.into_iter().enumerate().map(|(index, (name, first, count))| {
+                let folded_here = collapsed.iter().any(|other| other == name);
+                let toggle = {
+                    let collapsed = collapsed.clone();
+                    let name = name.to_string();
+                    Callback::from(move |_: yew::MouseEvent| {
+                        let mut list = (*collapsed).clone();
+                        match list.iter().position(|other| *other == name) {
+                            Some(index) => {
+                                list.remove(index);
+                            }
+                            None => list.push(name.clone()),
+                        }
+                        collapsed.set(list);
+                    })
+                };
+                // In view, and in all when the view leaves some out.
+                let in_view = category_counts.iter().find(|(other, _)| other == name).map_or(0, |(_, count)| *count);
+                let count_label = match swimlanes.iter().find(|lane| lane.category == name) {
+                    Some(lane) if lane.count != in_view => format!("{} of {}", in_view, lane.count),
+                    _ => in_view.to_string(),
+                };
+                html! {
+                    <div
+                        class={if index % 2 == 0 { "timeline-band" } else { "timeline-band odd" }}
+                        style={format!("top: {:.0}px; height: {:.0}px", first as f64 * LANE_HEIGHT, count as f64 * LANE_HEIGHT)}
+                    >
+                        if folded_here {
+                            {folded.iter().filter(|(event, _, _)| category_name(event) == name).map(|(event, _, extent)| html! {
+                                <div
+                                    class="timeline-band-tick"
+                                    aria-hidden="true"
+                                    style={format!("left: {:.1}px; background: {}", extent.start, event_color(event))}
+                                ></div>
+                            }).collect::<Html>()}
+                        }
+                        <button
+                            type="button"
+                            aria-expanded={(!folded_here).to_string()}
+                            title={if folded_here { "Expand lane" } else { "Collapse lane" }}
+                            onpointerdown={Callback::from(|e: PointerEvent| e.stop_propagation())}
+                            onclick={toggle}
+                        >
+                            {if folded_here { "▸ " } else { "▾ " }}
+                            {name}
+                            <span class="timeline-band-count">{count_label}</span>
+                        </button>
+                    </div>
+                }
+            }).collect::<Html>();
+
+            let markers = visible.iter().zip(lanes).map(|((event, bar, extent), lane)| {
+                let preview = match &*drag {
+                    Some(Drag::Marker { id, moved: true, preview, .. }) if *id == event.id => Some(*preview),
+                    _ => None,
+                };
+                let x = preview.map_or(extent.start, |day| current.x(day));
+                let day = current.day_at(extent.start);
+
+                // Enter opens the event; left and right move between
+                // events, panning once past the last one on screen.
+                let onkeydown = {
+                    let href = event_href(props.timeline_id.as_deref(), &event.id);
+                    Callback::from(move |e: KeyboardEvent| match e.key().as_str() {
+                        "Enter" | " " => {
+                            e.prevent_default();
+                            gloo_utils::window().location().set_href(&href).ok();
+                        }
+                        key @ ("ArrowLeft" | "ArrowRight") => {
+                            let marker = e
+                                .target()
+                                .and_then(|target| target.dyn_into::<web_sys::Element>().ok())
+                                .and_then(|label| label.parent_element());
+                            let sibling = marker.and_then(|marker| match key {
+                                "ArrowLeft" => marker.previous_element_sibling(),
+                                _ => marker.next_element_sibling(),
+                            });
+                            let label = sibling
+                                .and_then(|sibling| sibling.query_selector(".event-label").ok().flatten())
+                                .and_then(|label| label.dyn_into::<HtmlElement>().ok());
+                            if let Some(label) = label {
+                                e.prevent_default();
+                                e.stop_propagation();
+                                label.focus().ok();
+                            }
+                        }
+                        _ => {}
+                    })
+                };
+
+                // Touch screens have no hover; a tap shows the compact popover instead.
+                let onpointerenter = {
+                    let hovered = hovered.clone();
+                    let keep_hover = keep_hover.clone();
+                    let id = event.id.clone();
+                    Callback::from(move |e: PointerEvent| {
+                        if e.pointer_type() == "touch" {
+                            return;
+                        }
+                        keep_hover.emit(e.clone());
+                        let marker = e.target().and_then(|target| target.dyn_into::<web_sys::Element>().ok());
+                        if let Some(marker) = marker {
+                            hovered.set(Some((id.clone(), Anchor::of(&marker))));
+                        }
+                    })
+                };
+
+                let onpointerdown = {
+                    let drag = drag.clone();
+                    let id = event.id.clone();
+                    let track_x = track_x.clone();
+                    let capture = capture.clone();
+                    let press = press.clone();
+                    Callback::from(move |e: PointerEvent| {
+                        e.stop_propagation();
+                        capture(&e);
+                        let origin_x = track_x(e.client_x());
+                        let touch = e.pointer_type() == "touch";
+                        drag.set(Some(press(&e, origin_x).unwrap_or(Drag::Marker {
+                            id: id.clone(),
+                            origin_x,
+                            moved: false,
+                            preview: day,
+                            touch,
+                        })));
+                    })
+                };
+
+                let class = match (preview, presented) {
+                    (Some(_), _) => "timeline-marker dragging",
+                    (None, Some(on)) if on.id == event.id => "timeline-marker playing",
+                    _ => "timeline-marker",
+                };
+                html! {
+                    <div
+                        key={event.id.clone()}
+                        {class}
+                        style={format!("left: {:.1}px; top: {:.0}px", x, lane as f64 * LANE_HEIGHT)}
+                        role="listitem"
+                        {onpointerenter}
+                        onpointerleave={hide_hover.clone()}
+                        {onpointerdown}
+                    >
+                        if let Some((left, width)) = uncertainty(event, current) {
+                            <div
+                                class="event-uncertainty"
+                                aria-hidden="true"
+                                style={format!("left: {:.1}px; width: {:.1}px; --event-color: {}", left, width, event_color(event))}
+                            ></div>
+                        }
+                        <div
+                            class="event-marker"
+                            aria-hidden="true"
+                            style={format!("width: {:.1}px; background: {}", bar, event_color(event))}
+                        ></div>
+                        <div class="event-label" role="link" tabindex="0" aria-label={spoken(event)} {onkeydown}>
+                            if let Some(glyph) = appearance::icon_glyph(event.icon.as_deref()) {
+                                <span class="mr-1" aria-hidden="true">{glyph}</span>
+                            }
+                            {&event.title}
+                            if let Some(preview) = preview {
+                                <span class="ml-1 text-xs opacity-70">{time_scale::label(preview, current.snap_unit())}</span>
+                            }
+                        </div>
+                    </div>
+                }
+            }).collect::<Html>();
+
+            (ticks, bands, markers)
+        }
+        None => (html! {}, html! {}, html! {}),
+    };
+    let track_height = lane_count.max(1) as f64 * LANE_HEIGHT + AXIS_HEIGHT;
+
+    // Periods go behind everything else, clipped to the view. A name shows
+    // once its band is wide enough for it; zoomed out, the band's tooltip
+    // still has it.
+    let period_bands = match *scale {
+        Some(current) => {
+            let spans: Vec<(&Period, f64, f64)> = periods
+                .iter()
+                .filter_map(|period| {
+                    let start = current.x(time_scale::parse_date(&period.start_date)?).max(0.0);
+                    let end = current.x(time_scale::parse_date(&period.end_date)? + 1.0).min(current.width);
+                    (end > start).then_some((period, start, end))
+                })
+                .collect();
+            let extents: Vec<Extent> = spans
+                .iter()
+                .map(|(period, start, _)| Extent { start: *start, end: start + label_width(&period.name) })
+                .collect();
+            let (rows, _) = lanes::assign(&extents);
+            spans.iter().zip(rows).map(|((period, start, end), row)| {
+                let labelled = end - start >= label_width(&period.name);
+                html! {
+                    <div
+                        class="timeline-period"
+                        title={format!("{} ({} – {})", period.name, period.start_date, period.end_date)}
+                        style={format!("left: {:.1}px; width: {:.1}px; --period-color: {}", start, end - start, period.color)}
+                    >
+                        if labelled {
+                            <span style={format!("top: {:.0}px", row as f64 * PERIOD_LABEL_HEIGHT)}>{&period.name}</span>
+                        }
+                    </div>
+                }
+            }).collect::<Html>()
+        }
+        None => html! {},
+    };
+    let hovered_event = hovered.as_ref().and_then(|(id, anchor)| {
+        events.iter().find(|event| event.id == *id).map(|event| (event, *anchor))
+    });
+    let view_summary = scale.map(|current| {
+        format!(
+            "Showing {} to {}",
+            time_scale::format_day(current.start),
+            time_scale::format_day(current.end)
+        )
+    });
+
     html! {
-        <div class="timeline-container">
-            <div class="timeline">
-                {events.iter().map(|event| {
-                    html! {
-                        <div class="timeline-event">
-                            <div class="event-marker"></div>
-                            <div class="event-content">
-                                <h3>{&event.title}</h3>
-                                <p>{&event.description.as_ref().unwrap_or(&"No description".to_string())}</p>
-                                <p>{&event.start_date}</p>
+        <div
+            class={classes!(
+                "timeline-container",
+                playing.is_some().then_some("presenting"),
+                props.deterministic.is_some().then_some("deterministic"),
+            )}
+            ref={container}
+            onkeydown={playback_keys}
+        >
+            <div class="flex gap-2 mb-2">
+                <button class="btn btn-sm" title="Zoom out" aria-label="Zoom out" onclick={zoom_by(1.0 / ZOOM_STEP)}>
+                    {"−"}
+                </button>
+                <button class="btn btn-sm" title="Zoom in" aria-label="Zoom in" onclick={zoom_by(ZOOM_STEP)}>
+                    {"+"}
+                </button>
+                <button class="btn btn-sm btn-ghost" onclick={fit}>{"Fit"}</button>
+                if playing.is_none() {
+                    <button class="btn btn-sm btn-ghost" onclick={play} disabled={list.is_empty()}>{"▶ Play"}</button>
+                }
+                if let (Some(id), Some(current)) = (&props.timeline_id, *scale) {
+                    <a
+                        class="btn btn-sm btn-ghost"
+                        download="timeline.svg"
+                        href={format!(
+                            "/api/timelines/{}/render.svg?from={}&to={}&width={:.0}",
+                            id,
+                            time_scale::format_day(current.start),
+                            time_scale::format_day(current.end),
+                            current.width,
+                        )}
+                    >
+                        {"Export image"}
+                    </a>
+                }
+                if hidden > 0 {
+                    <span class="self-center text-xs opacity-70">
+                        {format!("{} minor event{} hidden; zoom in to see more", hidden, if hidden == 1 { "" } else { "s" })}
+                    </span>
+                }
+                <div class="join ml-auto" role="group" aria-label="Layout">
+                    <button
+                        class={if *group_by_category { "join-item btn btn-sm" } else { "join-item btn btn-sm btn-active" }}
+                        aria-pressed={(!*group_by_category).to_string()}
+                        onclick={layout(false)}
+                    >
+                        {"Stacked"}
+                    </button>
+                    <button
+                        class={if *group_by_category { "join-item btn btn-sm btn-active" } else { "join-item btn btn-sm" }}
+                        aria-pressed={group_by_category.to_string()}
+                        onclick={layout(true)}
+                    >
+                        {"Swimlanes"}
+                    </button>
+                </div>
+            </div>
+            <div class="flex flex-col md:flex-row gap-4">
+                if available.len() > 1 {
+                    <CategoryFilter counts={category_counts} selected={(*categories).clone()} on_change={on_categories} />
+                }
+                <div class="flex-1 min-w-0">
+                    <p id="timeline-help" class="sr-only">
+                        {"Use the left and right arrow keys to move through time, plus and minus to zoom, and Home to show \
+                          every event. Tab to an event and press Enter to open it."}
+                    </p>
+                    <div
+                        class="timeline-track"
+                        style={format!("height: {:.0}px", track_height)}
+                        ref={track}
+                        tabindex="0"
+                        role="group"
+                        aria-roledescription="timeline"
+                        aria-label="Timeline"
+                        aria-describedby="timeline-help"
+                        {onpointerdown}
+                        {onpointermove}
+                        onpointerup={onpointerup.clone()}
+                        onpointercancel={onpointerup}
+                        {onwheel}
+                        {onkeydown}
+                    >
+                        <div aria-hidden="true">{period_bands}</div>
+                        {bands}
+                        <div class="timeline-axis" aria-hidden="true"></div>
+                        <div aria-hidden="true">{ticks}</div>
+                        <div role="list" aria-label="Events in view">{markers}</div>
+                    </div>
+                    <p class="sr-only" aria-live="polite">{view_summary}</p>
+                    if let (Some(event), Some(index)) = (presented, *playing) {
+                        <section class="timeline-playback card bg-base-100 shadow-xl mt-2" aria-label="Playback">
+                            if event.image_url.is_some() {
+                                <figure><img src={api::thumbnail_src(&event.id, 640)} alt="" class="max-h-64 w-full object-cover" /></figure>
+                            }
+                            <div class="card-body">
+                                <div class="flex items-center gap-2 text-sm opacity-70">
+                                    <span
+                                        class="w-3 h-3 rounded-full shrink-0"
+                                        style={format!("background: {}", event_color(event))}
+                                    ></span>
+                                    {category_name(event)}
+                                    <span class="ml-auto" aria-live="polite">{format!("{} of {}", index + 1, list.len())}</span>
+                                </div>
+                                <h2 class="card-title text-2xl">
+                                    if let Some(glyph) = appearance::icon_glyph(event.icon.as_deref()) {
+                                        <span class="mr-1" aria-hidden="true">{glyph}</span>
+                                    }
+                                    <a class="link link-hover" href={event_href(props.timeline_id.as_deref(), &event.id)}>{&event.title}</a>
+                                </h2>
+                                <p class="opacity-70">
+                                    {date_range(event)}
+                                    if let Some(location) = &event.location {
+                                        {format!(" · {}", location)}
+                                    }
+                                </p>
+                                if let Some(description) = &event.description {
+                                    <p class="line-clamp-4">{description}</p>
+                                }
+                                <div class="card-actions items-center mt-2">
+                                    <button class="btn btn-sm" aria-label="Previous event" disabled={index == 0}
+                                        onclick={previous.reform(|_: yew::MouseEvent| ())}>{"⏮"}</button>
+                                    <button class="btn btn-sm btn-primary" onclick={toggle_pause.reform(|_: yew::MouseEvent| ())}>
+                                        {if *paused { "▶ Resume" } else { "⏸ Pause" }}
+                                    </button>
+                                    <button class="btn btn-sm" aria-label="Next event" disabled={index + 1 >= list.len()}
+                                        onclick={next.reform(|_: yew::MouseEvent| ())}>{"⏭"}</button>
+                                    <select class="select select-bordered select-sm" aria-label="Seconds per event" onchange={on_step_seconds}>
+                                        {STEP_SECONDS.iter().map(|seconds| html! {
+                                            <option value={seconds.to_string()} selected={*seconds == *step_seconds}>
+                                                {format!("{} s", seconds)}
+                                            </option>
+                                        }).collect::<Html>()}
+                                    </select>
+                                    <button class="btn btn-sm btn-ghost ml-auto" onclick={toggle_fullscreen}>{"Fullscreen"}</button>
+                                    <button class="btn btn-sm btn-ghost" onclick={stop.reform(|_: yew::MouseEvent| ())}>{"Stop"}</button>
+                                </div>
+                            </div>
+                        </section>
+                    }
+                    if let (Some((event, anchor)), None) = (hovered_event, &*drag) {
+                        <Popover anchor={anchor} onpointerenter={keep_hover} onpointerleave={hide_hover}>
+                            <a
+                                class="card card-compact bg-base-100 shadow-xl hover:bg-base-200"
+                                href={event_href(props.timeline_id.as_deref(), &event.id)}
+                            >
+                                if event.image_url.is_some() {
+                                    <figure><img src={api::thumbnail_src(&event.id, 320)} alt="" class="h-28 w-full object-cover" /></figure>
+                                }
+                                <div class="card-body gap-1">
+                                    <div class="flex items-center gap-2 text-xs opacity-70">
+                                        <span
+                                            class="w-3 h-3 rounded-full shrink-0"
+                                            style={format!("background: {}", event_color(event))}
+                                        ></span>
+                                        {category_name(event)}
+                                    </div>
+                                    <h3 class="font-semibold">
+                                        if let Some(glyph) = appearance::icon_glyph(event.icon.as_deref()) {
+                                            <span class="mr-1" aria-hidden="true">{glyph}</span>
+                                        }
+                                        {&event.title}
+                                    </h3>
+                                    <p class="text-sm opacity-70">{date_range(event)}</p>
+                                </div>
+                            </a>
+                        </Popover>
+                    }
+                    if let Some(event) = selected.as_ref().and_then(|id| events.iter().find(|event| event.id == *id)) {
+                        <div class="timeline-popover card card-compact bg-base-100 shadow-lg mt-2">
+                            <div class="card-body flex-row items-center gap-3">
+                                <span
+                                    class="w-3 h-3 rounded-full shrink-0"
+                                    style={format!("background: {}", event_color(event))}
+                                ></span>
+                                <div class="min-w-0 flex-1">
+                                    <p class="font-semibold truncate">{&event.title}</p>
+                                    <p class="text-sm opacity-70">{date_range(event)}</p>
+                                </div>
+                                <a class="btn btn-primary btn-sm" href={event_href(props.timeline_id.as_deref(), &event.id)}>
+                                    {"Open"}
+                                </a>
+                                <button class="btn btn-ghost btn-sm btn-square" aria-label="Close" onclick={close_popover}>
+                                    {"✕"}
+                                </button>
                             </div>
                         </div>
                     }
-                }).collect::<Html>()}
+                    if let Some(current) = *scale {
+                        <Minimap
+                            days={start_days(&events).collect::<Vec<_>>()}
+                            timeline_id={props.timeline_id.clone()}
+                            view={current}
+                            on_change={on_view_change}
+                        />
+                    }
+                    if let Some(change) = &*last_move {
+                        <div class="alert mt-2 flex justify-between">
+                            <span>{format!("Moved \"{}\" to {}", change.title, change.to.0.split('T').next().unwrap_or_default())}</span>
+                            <button class="btn btn-sm" onclick={undo}>{"Undo"}</button>
+                        </div>
+                    }
+                </div>
+                <PeriodRail timeline_id={props.timeline_id.clone()} on_select={on_period} />
             </div>
         </div>
     }
 }
+
+/// Events in the chosen categories, in date order, with their start days:
+/// what playback steps through.
+fn playlist<'a>(events: &'a [TimelineEvent], categories: &[String]) -> Vec<(&'a TimelineEvent, f64)> {
+    let mut list: Vec<(&TimelineEvent, f64)> = events
+        .iter()
+        .filter(|event| categories.is_empty() || categories.iter().any(|name| name.eq_ignore_ascii_case(category_name(event))))
+        .filter_map(|event| Some((event, time_scale::parse_date(&event.start_date)?)))
+        .collect();
+    list.sort_by(|a, b| a.1.total_cmp(&b.1));
+    list
+}
+
+/// The event's detail page, under its timeline when shown on one.
+fn event_href(timeline_id: Option<&str>, id: &str) -> String {
+    match timeline_id {
+        Some(timeline_id) => format!("/timelines/{}/events/{}", timeline_id, id),
+        None => format!("/events/{}", id),
+    }
+}
+
+/// What a screen reader announces for a marker: the title, dates and
+/// category that sighted users get from its position and colour.
+fn spoken(event: &TimelineEvent) -> String {
+    format!("{}, {}, {}", event.title, date_range(event), category_name(event))
+}
+
+/// The event's start day, and end day if it has one, noting how uncertain
+/// the start is.
+fn date_range(event: &TimelineEvent) -> String {
+    let day = |date: &str| date.split('T').next().unwrap_or(date).to_string();
+    let range = match &event.end_date {
+        Some(end_date) => format!("{} to {}", day(&event.start_date), day(end_date)),
+        None => day(&event.start_date),
+    };
+    match time_scale::circa(event.start_date_min.as_deref(), event.start_date_max.as_deref()) {
+        Some(circa) => format!("{} ({})", range, circa),
+        None => range,
+    }
+}
+
+/// Where the whisker of an uncertain start goes, as `(left, width)` in
+/// pixels from the marker's edge. A missing bound ends it at the start.
+fn uncertainty(event: &TimelineEvent, scale: TimeScale) -> Option<(f64, f64)> {
+    if event.start_date_min.is_none() && event.start_date_max.is_none() {
+        return None;
+    }
+    let start = time_scale::parse_date(&event.start_date)?;
+    let bound = |date: &Option<String>| date.as_deref().and_then(time_scale::parse_date).unwrap_or(start);
+    let (min, max) = (scale.x(bound(&event.start_date_min)), scale.x(bound(&event.start_date_max)));
+    Some((min - scale.x(start) + MARKER_SIZE / 2.0, max - min))
+}
+
+/// Least importance shown in a view `span` days wide.
+fn min_importance(span: f64) -> i16 {
+    let years = span / 365.25;
+    LEVELS_OF_DETAIL.iter().find(|(above, _)| years > *above).map_or(1, |(_, importance)| *importance)
+}
+
+fn category_name(event: &TimelineEvent) -> &str {
+    event.category.as_deref().unwrap_or(UNCATEGORIZED)
+}
+
+fn event_color(event: &TimelineEvent) -> &str {
+    appearance::event_color(event.color.as_deref(), event.category.as_deref())
+}
+
+/// Rough rendered width of a marker label, so lanes leave room for it.
+fn label_width(title: &str) -> f64 {
+    (title.chars().count() as f64 * 7.0 + 24.0).min(MAX_LABEL_WIDTH)
+}
+
+/// FNV-1a of `id` starting from `seed`: a stable, seed-dependent order.
+fn seeded(seed: u64, id: &str) -> u64 {
+    id.bytes().fold(seed ^ 0xcbf2_9ce4_8422_2325, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3))
+}
+
+fn start_days(events: &[TimelineEvent]) -> impl Iterator<Item = f64> + '_ {
+    events.iter().filter_map(|event| time_scale::parse_date(&event.start_date))
+}
+
+/// Keeps a released swipe moving, slowing it by `FRICTION` each frame,
+/// until it stops or the track is touched again.
+fn coast(
+    scale: UseStateHandle<Option<TimeScale>>,
+    coasting: UseStateHandle<bool>,
+    gesture: Rc<RefCell<Gesture>>,
+    from: TimeScale,
+) {
+    let (mut speed, generation) = {
+        let gesture = gesture.borrow();
+        (gesture.speed, gesture.generation)
+    };
+    // A finger held still before lifting leaves a stale speed behind.
+    if speed.abs() < FLING_SPEED || rum::now() - gesture.borrow().sampled_at > 100.0 {
+        return;
+    }
+    coasting.set(true);
+    wasm_bindgen_futures::spawn_local(async move {
+        let mut view = from;
+        let mut last = rum::now();
+        while speed.abs() > STOP_SPEED {
+            let now = next_frame().await;
+            if gesture.borrow().generation != generation {
+                return;
+            }
+            let elapsed = now - last;
+            last = now;
+            view = view.pan(speed * elapsed);
+            scale.set(Some(view));
+            speed *= FRICTION.powf(elapsed / 16.0);
+        }
+        coasting.set(false);
+    });
+}
+
+/// Waits for the next animation frame, returning its timestamp.
+async fn next_frame() -> f64 {
+    let promise = js_sys::Promise::new(&mut |resolve, _| {
+        gloo_utils::window().request_animation_frame(&resolve).ok();
+    });
+    wasm_bindgen_futures::JsFuture::from(promise)
+        .await
+        .ok()
+        .and_then(|time| time.as_f64())
+        .unwrap_or_else(rum::now)
+}
+
+/// Shows the new dates straight away and saves them, putting the old ones
+/// back and reporting why if the server refuses (e.g. the timeline was
+/// archived meanwhile). An uncertain start's bounds move along with it.
+fn reschedule(
+    client: ApiClient,
+    events: UseStateHandle<Vec<TimelineEvent>>,
+    errors: ErrorReporter,
+    id: String,
+    (start_date, end_date): (String, Option<String>),
+) {
+    let previous = (*events).clone();
+    let mut updated = previous.clone();
+    let mut bounds = Vec::new();
+    if let Some(event) = updated.iter_mut().find(|event| event.id == id) {
+        let shift = time_scale::parse_date(&start_date)
+            .zip(time_scale::parse_date(&event.start_date))
+            .map_or(0.0, |(to, from)| to - from);
+        let named = [("start_date_min", &mut event.start_date_min), ("start_date_max", &mut event.start_date_max)];
+        for (name, bound) in named {
+            if let Some(day) = bound.as_deref().and_then(time_scale::parse_date) {
+                *bound = Some(time_scale::format_date(day + shift));
+                bounds.push((name, bound.clone()));
+            }
+        }
+        event.start_date = start_date.clone();
+        if end_date.is_some() {
+            event.end_date = end_date.clone();
+        }
+    }
+    events.set(updated);
+
+    wasm_bindgen_futures::spawn_local(async move {
+        let mut body = serde_json::json!({ "start_date": start_date });
+        if let Some(end_date) = end_date {
+            body["end_date"] = end_date.into();
+        }
+        for (name, bound) in bounds {
+            body[name] = bound.into();
+        }
+        let request = Request::patch(&format!("/api/events/{}", id));
+        if let Err(error) = client.send_json::<serde::de::IgnoredAny>(request, &body).await {
+            events.set(previous);
+            errors.report(error);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
+
+    use super::*;
+    use crate::api::Method;
+    use crate::testing::{self, MockClient};
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    const WIDTH: f64 = 800.0;
+
+    fn event(id: &str, start_date: &str) -> TimelineEvent {
+        TimelineEvent {
+            id: id.to_string(),
+            title: id.to_uppercase(),
+            description: None,
+            start_date: format!("{}T00:00:00", start_date),
+            end_date: None,
+            start_date_min: None,
+            start_date_max: None,
+            location: None,
+            image_url: None,
+            category: None,
+            importance: 3,
+            color: None,
+            icon: None,
+        }
+    }
+
+    /// Renders a deterministic timeline of `events` and returns each
+    /// marker's title, left and top once it has loaded.
+    async fn render(events: &[TimelineEvent], seed: u64) -> Vec<(String, String, String)> {
+        let client = MockClient::default().reply(
+            Method::Get,
+            "/api/timelines/fixture/events",
+            200,
+            serde_json::to_value(events).unwrap(),
+        );
+        let props = TimelineProps {
+            timeline_id: Some("fixture".to_string()),
+            editable: false,
+            focus: None,
+            highlight: None,
+            periods_revision: 0,
+            deterministic: Some(Deterministic { width: WIDTH, seed }),
+        };
+        let root = testing::mount(Rc::new(client), html! { <Timeline ..props /> }).await;
+
+        assert!(root.query_selector(".timeline-container.deterministic").unwrap().is_some());
+        let markers = root.query_selector_all(".timeline-marker").unwrap();
+        (0..markers.length())
+            .filter_map(|index| markers.item(index)?.dyn_into::<web_sys::Element>().ok())
+            .map(|marker| {
+                let style = marker.get_attribute("style").unwrap_or_default();
+                let value = |name: &str| {
+                    let rest = style.split(&format!("{}: ", name)).nth(1).unwrap_or_default();
+                    rest.split("px").next().unwrap_or_default().to_string()
+                };
+                (marker.text_content().unwrap_or_default(), value("left"), value("top"))
+            })
+            .collect()
+    }
+
+    #[wasm_bindgen_test]
+    async fn places_markers_on_the_fitted_scale() {
+        let events = [event("a", "1900-01-01"), event("b", "1950-06-15"), event("c", "2000-12-31")];
+        let scale = TimeScale::fit(start_days(&events), WIDTH);
+
+        let markers = render(&events, 0).await;
+
+        let expected: Vec<(String, String, String)> = events
+            .iter()
+            .map(|event| {
+                let x = scale.x(time_scale::parse_date(&event.start_date).unwrap());
+                (event.title.clone(), format!("{:.1}", x), "0".to_string())
+            })
+            .collect();
+        assert_eq!(markers, expected);
+    }
+
+    #[wasm_bindgen_test]
+    async fn events_starting_together_stack_by_seed_not_by_fetch_order() {
+        let together = [event("a", "1914-07-28"), event("b", "1914-07-28"), event("c", "1914-07-28")];
+        let mut reversed = together.clone();
+        reversed.reverse();
+
+        let mut first = render(&together, 7).await;
+        let mut second = render(&reversed, 7).await;
+        first.sort();
+        second.sort();
+        assert_eq!(first, second);
+
+        let mut tops: Vec<&str> = first.iter().map(|(_, _, top)| top.as_str()).collect();
+        tops.sort();
+        assert_eq!(tops, ["0", "32", "64"]);
+    }
+}
@@ -0,0 +1,73 @@
+use yew::{function_component, html, Html, Properties};
+
+const WIDTH: f64 = 600.0;
+const HEIGHT: f64 = 160.0;
+const PADDING: f64 = 24.0;
+
+#[derive(Clone, PartialEq)]
+pub struct TrendPoint {
+    pub label: String,
+    pub p50: f64,
+    pub p95: f64,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct TrendChartProps {
+    pub title: String,
+    pub points: Vec<TrendPoint>,
+}
+
+/// Line chart of daily p50/p95 values, in milliseconds.
+#[function_component(TrendChart)]
+pub fn trend_chart(props: &TrendChartProps) -> Html {
+    let max = props
+        .points
+        .iter()
+        .map(|point| point.p95.max(point.p50))
+        .fold(1.0, f64::max);
+    let step = if props.points.len() > 1 {
+        (WIDTH - 2.0 * PADDING) / (props.points.len() - 1) as f64
+    } else {
+        0.0
+    };
+
+    let line = |value: fn(&TrendPoint) -> f64| {
+        props
+            .points
+            .iter()
+            .enumerate()
+            .map(|(i, point)| {
+                let x = PADDING + i as f64 * step;
+                let y = HEIGHT - PADDING - value(point) / max * (HEIGHT - 2.0 * PADDING);
+                format!("{:.1},{:.1}", x, y)
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+
+    let latest = props.points.last();
+
+    html! {
+        <div class="card bg-base-100 shadow">
+            <div class="card-body">
+                <h3 class="card-title text-base">{&props.title}</h3>
+                {match latest {
+                    Some(point) => html! {
+                        <p class="text-sm opacity-70">
+                            {format!("{}: p50 {:.0} ms, p95 {:.0} ms", point.label, point.p50, point.p95)}
+                        </p>
+                    },
+                    None => html! { <p class="text-sm opacity-70">{"No samples yet"}</p> },
+                }}
+                <svg viewBox={format!("0 0 {} {}", WIDTH, HEIGHT)} class="w-full h-40">
+                    <text x="2" y="14" class="text-xs fill-current">{format!("{:.0} ms", max)}</text>
+                    <line x1={PADDING.to_string()} y1={(HEIGHT - PADDING).to_string()}
+                          x2={(WIDTH - PADDING).to_string()} y2={(HEIGHT - PADDING).to_string()}
+                          stroke="currentColor" stroke-opacity="0.2" />
+                    <polyline points={line(|point| point.p95)} fill="none" stroke="#f97316" stroke-width="2" />
+                    <polyline points={line(|point| point.p50)} fill="none" stroke="#3b82f6" stroke-width="2" />
+                </svg>
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,117 @@
+use serde::Deserialize;
+use yew::{function_component, html, use_node_ref, use_state, Callback, Html, Properties};
+
+use crate::api::Request;
+use crate::components::api_provider::use_api;
+use crate::components::error_boundary::use_error_reporter;
+use crate::components::modal::Modal;
+
+#[derive(Deserialize)]
+struct Copy {
+    id: String,
+}
+
+#[derive(Properties, PartialEq)]
+pub struct UseTemplateProps {
+    pub timeline_id: String,
+    /// The original's title, offered as the copy's.
+    pub title: String,
+    #[prop_or_default]
+    pub class: Option<String>,
+}
+
+/// "Use this template": asks what to call the copy and whether to bring
+/// images and sources along, then copies the timeline and opens the copy.
+#[function_component(UseTemplate)]
+pub fn use_template(props: &UseTemplateProps) -> Html {
+    let open = use_state(|| false);
+    let copying = use_state(|| false);
+    let title_input = use_node_ref();
+    let attachments_input = use_node_ref();
+    let errors = use_error_reporter();
+    let client = use_api();
+
+    let show = {
+        let open = open.clone();
+        Callback::from(move |_| open.set(true))
+    };
+    let close = {
+        let open = open.clone();
+        Callback::from(move |_| open.set(false))
+    };
+    let submit = {
+        let copying = copying.clone();
+        let title_input = title_input.clone();
+        let attachments_input = attachments_input.clone();
+        let url = format!("/api/timelines/{}/clone", props.timeline_id);
+        Callback::from(move |e: yew::SubmitEvent| {
+            e.prevent_default();
+            let (Some(title), Some(attachments)) = (
+                title_input.cast::<web_sys::HtmlInputElement>(),
+                attachments_input.cast::<web_sys::HtmlInputElement>(),
+            ) else {
+                return;
+            };
+            let body = serde_json::json!({
+                "title": title.value().trim(),
+                "attachments": attachments.checked(),
+            });
+            let url = url.clone();
+            let copying = copying.clone();
+            let errors = errors.clone();
+            copying.set(true);
+            let client = client.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match client.send_json::<Copy>(Request::post(&url), &body).await {
+                    Ok(copy) => {
+                        gloo_utils::window().location().set_href(&format!("/timelines/{}", copy.id)).ok();
+                    }
+                    Err(error) => {
+                        copying.set(false);
+                        errors.report(error);
+                    }
+                }
+            });
+        })
+    };
+
+    html! {
+        <>
+            <button class={props.class.clone().unwrap_or_else(|| "btn btn-primary btn-sm".to_string())} onclick={show}>
+                {"Use this template"}
+            </button>
+            if *open {
+                <Modal
+                    title="Use this template"
+                    on_close={close.clone()}
+                    actions={html! {
+                        <>
+                            <button class="btn" onclick={close.reform(|_| ())}>{"Cancel"}</button>
+                            <button class="btn btn-primary" type="submit" form="use-template" disabled={*copying}>
+                                {"Create my copy"}
+                            </button>
+                        </>
+                    }}
+                >
+                    <form id="use-template" class="flex flex-col gap-4 py-4" onsubmit={submit}>
+                        <label class="form-control">
+                            <span class="label-text mb-1">{"Title of your copy"}</span>
+                            <input
+                                ref={title_input}
+                                class="input input-bordered"
+                                value={props.title.clone()}
+                                maxlength="255"
+                                required=true
+                            />
+                        </label>
+                        <label class="label cursor-pointer justify-start gap-2">
+                            <input ref={attachments_input} type="checkbox" class="checkbox checkbox-sm" />
+                            <span class="label-text">{"Include images and sources"}</span>
+                        </label>
+                        <p class="text-sm opacity-70">{"Your copy is private until you share it."}</p>
+                    </form>
+                </Modal>
+            }
+        </>
+    }
+}
@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+
+use yew::{
+    function_component, html, use_effect, use_effect_with, use_force_update, use_mut_ref, use_node_ref,
+    Callback, Html, Properties,
+};
+use wasm_bindgen::{closure::Closure, JsCast};
+use web_sys::HtmlElement;
+
+/// Extra distance above and below the viewport that is kept rendered, so
+/// fast scrolling does not show blank space.
+const BUFFER: f64 = 800.0;
+
+#[derive(Properties, PartialEq)]
+pub struct VirtualGridProps {
+    pub items: usize,
+    /// Renders the item at an index.
+    pub render: Callback<usize, Html>,
+    /// Row height assumed until a row has been measured.
+    #[prop_or(240.0)]
+    pub estimated_row_height: f64,
+    /// Vertical space between rows; matches the grid's `gap-6`.
+    #[prop_or(24.0)]
+    pub gap: f64,
+}
+
+/// Columns of the `grid-cols-1 md:grid-cols-2 lg:grid-cols-3` layout at the
+/// current viewport width.
+fn columns() -> usize {
+    let width = gloo_utils::window()
+        .inner_width()
+        .ok()
+        .and_then(|width| width.as_f64())
+        .unwrap_or(0.0);
+    if width >= 1024.0 {
+        3
+    } else if width >= 768.0 {
+        2
+    } else {
+        1
+    }
+}
+
+/// A card grid that only mounts the rows near the viewport. Rows are placed
+/// absolutely using measured heights (estimated until first rendered), so
+/// the page keeps its full scroll height.
+#[function_component(VirtualGrid)]
+pub fn virtual_grid(props: &VirtualGridProps) -> Html {
+    let container = use_node_ref();
+    let heights = use_mut_ref(HashMap::<usize, f64>::new);
+    let measured_columns = use_mut_ref(|| 0usize);
+    // Re-renders on scroll, resize and new measurements.
+    let refresh = use_force_update();
+
+    {
+        let refresh = refresh.clone();
+        use_effect_with((), move |_| {
+            let on_change = Closure::<dyn Fn()>::new(move || refresh.force_update());
+            let window = gloo_utils::window();
+            for event in ["scroll", "resize"] {
+                window
+                    .add_event_listener_with_callback(event, on_change.as_ref().unchecked_ref())
+                    .ok();
+            }
+            move || {
+                for event in ["scroll", "resize"] {
+                    window
+                        .remove_event_listener_with_callback(event, on_change.as_ref().unchecked_ref())
+                        .ok();
+                }
+            }
+        });
+    }
+
+    // Measure rendered rows; re-render only when a height actually changed.
+    {
+        let container = container.clone();
+        let heights = heights.clone();
+        use_effect(move || {
+            let mut changed = false;
+            if let Some(el) = container.cast::<HtmlElement>() {
+                let rows = el.children();
+                for i in 0..rows.length() {
+                    let Some(row) = rows.item(i).and_then(|row| row.dyn_into::<HtmlElement>().ok()) else {
+                        continue;
+                    };
+                    let Some(index) = row.get_attribute("data-row").and_then(|index| index.parse().ok()) else {
+                        continue;
+                    };
+                    let height = row.offset_height() as f64;
+                    let previous = heights.borrow_mut().insert(index, height);
+                    changed |= previous.is_none_or(|previous| (previous - height).abs() > 0.5);
+                }
+            }
+            if changed {
+                refresh.force_update();
+            }
+            || ()
+        });
+    }
+
+    let columns = columns();
+    // Rows hold different items once the column count changes.
+    if *measured_columns.borrow() != columns {
+        heights.borrow_mut().clear();
+        *measured_columns.borrow_mut() = columns;
+    }
+    let rows = props.items.div_ceil(columns);
+    let heights = heights.borrow();
+    let row_height = |row: usize| heights.get(&row).copied().unwrap_or(props.estimated_row_height);
+
+    // Visible band in container coordinates.
+    let top = container
+        .cast::<HtmlElement>()
+        .map(|el| el.get_bounding_client_rect().top())
+        .unwrap_or(0.0);
+    let viewport = gloo_utils::window()
+        .inner_height()
+        .ok()
+        .and_then(|height| height.as_f64())
+        .unwrap_or(1000.0);
+    let (from, to) = (-top - BUFFER, -top + viewport + BUFFER);
+
+    let mut offset = 0.0;
+    let mut visible = Vec::new();
+    for row in 0..rows {
+        let height = row_height(row);
+        if offset + height >= from && offset <= to {
+            visible.push((row, offset));
+        }
+        offset += height + props.gap;
+    }
+    let total = (offset - props.gap).max(0.0);
+
+    html! {
+        <div ref={container} class="relative" style={format!("height: {:.0}px", total)}>
+            {visible.into_iter().map(|(row, offset)| {
+                let first = row * columns;
+                let last = (first + columns).min(props.items);
+                html! {
+                    <div
+                        key={row}
+                        data-row={row.to_string()}
+                        class="grid grid-cols-1 md:grid-cols-2 lg:grid-cols-3 gap-6 absolute inset-x-0"
+                        style={format!("top: {:.0}px", offset)}
+                    >
+                        {(first..last).map(|index| props.render.emit(index)).collect::<Html>()}
+                    </div>
+                }
+            }).collect::<Html>()}
+        </div>
+    }
+}
@@ -0,0 +1,469 @@
+//! The API of a `mock-api` build (`wasm-pack build --target web --
+//! --features mock-api`), answered from `demo/dataset.json` baked into the
+//! binary, so a static host such as GitHub Pages can show the whole UI
+//! without a backend.
+//!
+//! Only reads are served, shaped like the real API's responses. Anything
+//! that would change data is refused as a read-only demo.
+
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::api::{HttpClient, Method, Pending, Request, Response};
+use crate::components::category_filter::UNCATEGORIZED;
+use crate::time_scale;
+
+const DATASET: &str = include_str!("../demo/dataset.json");
+/// Stamped on records, which the dataset leaves undated.
+const CREATED_AT: &str = "2024-01-01T00:00:00";
+/// Events in the Home page's featured, recent and trending rows.
+const ROW_SIZE: usize = 10;
+
+thread_local! {
+    static DATA: Dataset = Dataset::load();
+}
+
+#[derive(Deserialize)]
+struct Dataset {
+    timelines: Vec<Value>,
+    periods: Vec<Value>,
+    people: Vec<Value>,
+    events: Vec<Value>,
+}
+
+impl Dataset {
+    fn load() -> Self {
+        let mut data: Dataset = serde_json::from_str(DATASET).expect("demo/dataset.json is valid");
+        for event in &mut data.events {
+            let record = event.as_object_mut().expect("events are objects");
+            for field in ["created_at", "updated_at"] {
+                record.entry(field).or_insert_with(|| json!(CREATED_AT));
+            }
+            record.entry("image_url").or_insert(Value::Null);
+            record.entry("status").or_insert_with(|| json!("published"));
+        }
+        for timeline in &mut data.timelines {
+            let record = timeline.as_object_mut().expect("timelines are objects");
+            record.insert("access".to_string(), json!("view"));
+        }
+        data
+    }
+
+    fn event(&self, id: &str) -> Option<&Value> {
+        self.events.iter().find(|event| text(event, "id") == Some(id))
+    }
+
+    fn timeline_events<'a>(&'a self, timeline_id: &'a str) -> impl Iterator<Item = &'a Value> {
+        self.events.iter().filter(move |event| text(event, "timeline_id") == Some(timeline_id))
+    }
+
+    /// Answers a GET of `url`.
+    fn get(&self, url: &str) -> Response {
+        let (path, query) = url.split_once('?').unwrap_or((url, ""));
+        let params = params(query);
+        let param = |name: &str| params.get(name).map(String::as_str);
+        let segments: Vec<&str> = path.trim_start_matches("/api/").split('/').collect();
+
+        let found = match segments[..] {
+            ["events"] => Some(self.list(&params)),
+            ["events", "featured"] => {
+                let featured = self.events.iter().filter(|event| event["featured"] == json!(true));
+                Some(json!(featured.take(ROW_SIZE).collect::<Vec<_>>()))
+            }
+            ["events", "recent"] => Some(json!(ranked(self.events.iter(), |event| -day(event)))),
+            ["events", "trending"] => Some(json!(ranked(self.events.iter(), |event| -number(event, "views")))),
+            ["events", "random"] => {
+                let index = (js_sys::Math::random() * self.events.len() as f64) as usize;
+                self.events.get(index).cloned()
+            }
+            ["events", "on-this-day"] => {
+                let wanted = format!("{:0>2}-{:0>2}", param("month").unwrap_or_default(), param("day").unwrap_or_default());
+                let matching = self.events.iter().filter(|event| month_day(event) == Some(wanted.as_str()));
+                Some(json!(matching.collect::<Vec<_>>()))
+            }
+            ["events", "lanes"] => Some(self.lanes(param("timeline_id"))),
+            ["events", "histogram"] => Some(self.histogram(&params)),
+            ["events", id] => self.event(id).cloned(),
+            ["events", id, "people"] => self.event(id).map(|event| {
+                let linked: Vec<&Value> = self
+                    .people
+                    .iter()
+                    .filter(|person| event["people"].as_array().into_iter().flatten().any(|linked| *linked == person["id"]))
+                    .collect();
+                json!(linked)
+            }),
+            ["events", id, "sources" | "duplicates" | "images" | "comments" | "reactions"] => self.event(id).map(|_| json!([])),
+            ["timelines"] => Some(json!(self.timelines)),
+            ["timelines", "public"] => Some(self.public_timelines()),
+            ["timelines", id] => self.timelines.iter().find(|timeline| text(timeline, "id") == Some(id)).cloned(),
+            ["timelines", id, "events"] => Some(json!(self.timeline_events(id).collect::<Vec<_>>())),
+            ["timelines", id, "periods"] => {
+                let periods = self.periods.iter().filter(|period| text(period, "timeline_id") == Some(id));
+                Some(json!(periods.collect::<Vec<_>>()))
+            }
+            ["timelines", _, "stories" | "fields"] => Some(json!([])),
+            ["people"] => Some(json!(self.people)),
+            ["people", id] => self.people.iter().find(|person| text(person, "id") == Some(id)).cloned(),
+            ["people", id, "events"] => {
+                let events = self
+                    .events
+                    .iter()
+                    .filter(|event| event["people"].as_array().into_iter().flatten().any(|person| person == id));
+                Some(json!(events.collect::<Vec<_>>()))
+            }
+            ["search"] => Some(self.search(param("q").unwrap_or_default())),
+            _ => None,
+        };
+        match found {
+            Some(body) => Response { status: 200, body: body.to_string() },
+            None => Response { status: 404, body: String::new() },
+        }
+    }
+
+    /// `GET /api/events`: the filtered page and its facet counts.
+    fn list(&self, params: &BTreeMap<String, String>) -> Value {
+        let param = |name: &str| params.get(name).map(String::as_str);
+        let search = param("search").map(str::to_lowercase);
+        let categories: Vec<&str> = param("categories").map(|list| list.split(',').collect()).unwrap_or_default();
+        let start = param("start_date").or(param("from")).and_then(time_scale::parse_date);
+        let end = param("end_date").or(param("to")).and_then(time_scale::parse_date);
+
+        let mut matching: Vec<&Value> = self
+            .events
+            .iter()
+            .filter(|event| search.as_deref().is_none_or(|search| mentions(event, search)))
+            .filter(|event| param("tag").is_none_or(|tag| tags(event).any(|name| name == tag)))
+            .filter(|event| start.is_none_or(|start| last_day(event) >= start))
+            .filter(|event| end.is_none_or(|end| day(event) <= end + 1.0))
+            .collect();
+        // Category counts ignore the category filter, so the sidebar still
+        // offers the others.
+        let category_counts = counts(matching.iter().map(|event| category(event)));
+        matching.retain(|event| {
+            categories.is_empty() || categories.iter().any(|name| name.eq_ignore_ascii_case(category(event)))
+        });
+        let facets = facets(category_counts, &matching);
+        match param("sort") {
+            Some("oldest") => matching.sort_by(|a, b| day(a).total_cmp(&day(b))),
+            Some("likes") => matching.sort_by(|a, b| number(b, "likes").total_cmp(&number(a, "likes"))),
+            _ => matching.sort_by(|a, b| day(b).total_cmp(&day(a))),
+        }
+
+        let limit = param("limit").and_then(|limit| limit.parse().ok()).unwrap_or(20usize).clamp(1, 100);
+        let page = param("page").and_then(|page| page.parse().ok()).unwrap_or(1usize).max(1);
+        let total = matching.len();
+        let data: Vec<&Value> = matching.into_iter().skip((page - 1) * limit).take(limit).collect();
+        json!({
+            "data": data,
+            "total": total,
+            "page": page,
+            "limit": limit,
+            "pages": total.div_ceil(limit),
+            "facets": facets,
+        })
+    }
+
+    /// `GET /api/events/lanes`: each category's count and first and last
+    /// start, in the order the categories begin.
+    fn lanes(&self, timeline_id: Option<&str>) -> Value {
+        let mut lanes: BTreeMap<&str, (i64, &Value, &Value)> = BTreeMap::new();
+        let events = self
+            .events
+            .iter()
+            .filter(|event| timeline_id.is_none_or(|id| text(event, "timeline_id") == Some(id)));
+        for event in events {
+            let lane = lanes.entry(category(event)).or_insert((0, event, event));
+            lane.0 += 1;
+            if day(event) < day(lane.1) {
+                lane.1 = event;
+            }
+            if day(event) > day(lane.2) {
+                lane.2 = event;
+            }
+        }
+        let mut lanes: Vec<_> = lanes.into_iter().collect();
+        lanes.sort_by(|(_, (_, a, _)), (_, (_, b, _))| day(a).total_cmp(&day(b)));
+        let lanes: Vec<Value> = lanes
+            .into_iter()
+            .map(|(category, (count, first, last))| {
+                json!({
+                    "category": category,
+                    "count": count,
+                    "first_date": first["start_date"],
+                    "last_date": last["start_date"],
+                })
+            })
+            .collect();
+        json!(lanes)
+    }
+
+    /// `GET /api/events/histogram`: events starting in each period,
+    /// empty ones included.
+    fn histogram(&self, params: &BTreeMap<String, String>) -> Value {
+        let param = |name: &str| params.get(name).map(String::as_str);
+        let years = match param("granularity") {
+            Some("year") => 1,
+            Some("century") => 100,
+            _ => 10,
+        };
+        let from = param("from").and_then(time_scale::parse_date);
+        let to = param("to").and_then(time_scale::parse_date);
+        let mut counts: BTreeMap<i64, i64> = BTreeMap::new();
+        let events = self
+            .events
+            .iter()
+            .filter(|event| param("timeline_id").is_none_or(|id| text(event, "timeline_id") == Some(id)))
+            .filter(|event| from.is_none_or(|from| day(event) >= from))
+            .filter(|event| to.is_none_or(|to| day(event) <= to + 1.0));
+        for event in events {
+            let (year, _, _) = time_scale::civil_from_days(day(event).floor() as i64);
+            *counts.entry(year.div_euclid(years) * years).or_default() += 1;
+        }
+        let (Some(&first), Some(&last)) = (counts.keys().next(), counts.keys().next_back()) else {
+            return json!([]);
+        };
+        let buckets: Vec<Value> = (first..=last)
+            .step_by(years as usize)
+            .map(|start| json!({ "start": start, "count": counts.get(&start).copied().unwrap_or(0) }))
+            .collect();
+        json!(buckets)
+    }
+
+    /// `GET /api/timelines/public`: every timeline, with its extent and
+    /// first event as the cover.
+    fn public_timelines(&self) -> Value {
+        let timelines: Vec<Value> = self
+            .timelines
+            .iter()
+            .filter_map(|timeline| {
+                let id = text(timeline, "id")?;
+                let mut events: Vec<&Value> = self.timeline_events(id).collect();
+                events.sort_by(|a, b| day(a).total_cmp(&day(b)));
+                let (first, last) = (events.first()?, events.last()?);
+                Some(json!({
+                    "id": id,
+                    "title": timeline["title"],
+                    "description": timeline["description"],
+                    "owner": timeline["owner"],
+                    "cover_event_id": first["id"],
+                    "event_count": events.len(),
+                    "starts_at": first["start_date"],
+                    "ends_at": last["start_date"],
+                }))
+            })
+            .collect();
+        json!({ "data": timelines, "total": timelines.len(), "page": 1, "limit": 20, "pages": 1 })
+    }
+
+    /// `GET /api/search`: everything containing `q`, on one page.
+    fn search(&self, q: &str) -> Value {
+        let q = q.trim().to_lowercase();
+        let group = |items: Vec<Value>| json!({ "total": items.len(), "items": items });
+        let events: Vec<Value> = self
+            .events
+            .iter()
+            .filter(|event| mentions(event, &q))
+            .map(|event| {
+                let timeline = self.timelines.iter().find(|timeline| timeline["id"] == event["timeline_id"]);
+                json!({
+                    "id": event["id"],
+                    "title": event["title"],
+                    "snippet": event["description"],
+                    "start_date": event["start_date"],
+                    "timeline_id": event["timeline_id"],
+                    "timeline_title": timeline.map(|timeline| timeline["title"].clone()),
+                })
+            })
+            .collect();
+        let timelines: Vec<Value> = self
+            .timelines
+            .iter()
+            .filter(|timeline| mentions(timeline, &q))
+            .map(|timeline| {
+                let count = text(timeline, "id").map_or(0, |id| self.timeline_events(id).count());
+                json!({
+                    "id": timeline["id"],
+                    "title": timeline["title"],
+                    "description": timeline["description"],
+                    "owner": timeline["owner"],
+                    "event_count": count,
+                })
+            })
+            .collect();
+        let people: Vec<Value> = self
+            .people
+            .iter()
+            .filter(|person| text(person, "name").is_some_and(|name| name.to_lowercase().contains(&q)))
+            .cloned()
+            .collect();
+        let tag_results = counts(self.events.iter().flat_map(tags).filter(|tag| tag.contains(&q)))
+            .into_iter()
+            .map(|(name, count)| json!({ "name": name, "event_count": count }))
+            .collect();
+        json!({
+            "fuzzy": false,
+            "suggestion": null,
+            "events": group(events),
+            "timelines": group(timelines),
+            "people": group(people),
+            "tags": group(tag_results),
+        })
+    }
+}
+
+/// Serves the bundled dataset in place of the network.
+#[derive(Default)]
+pub struct DemoClient;
+
+impl HttpClient for DemoClient {
+    fn fetch(&self, request: Request) -> Pending {
+        let response = match request.method {
+            Method::Get => DATA.with(|data| data.get(&request.url)),
+            _ => Response { status: 403, body: json!({ "error": "This is a read-only demo." }).to_string() },
+        };
+        Box::pin(async move { Ok(response) })
+    }
+}
+
+/// The image of the event `id`, which the demo has no thumbnails for.
+pub fn image_url(id: &str) -> Option<String> {
+    DATA.with(|data| data.event(id).and_then(|event| text(event, "image_url")).map(str::to_string))
+}
+
+fn params(query: &str) -> BTreeMap<String, String> {
+    let decode = |part: &str| {
+        js_sys::decode_uri_component(&part.replace('+', " "))
+            .ok()
+            .and_then(|decoded| decoded.as_string())
+            .unwrap_or_default()
+    };
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(name, value)| (decode(name), decode(value)))
+        .collect()
+}
+
+fn text<'a>(record: &'a Value, field: &str) -> Option<&'a str> {
+    record[field].as_str()
+}
+
+fn number(record: &Value, field: &str) -> f64 {
+    record[field].as_f64().unwrap_or(0.0)
+}
+
+fn category(event: &Value) -> &str {
+    text(event, "category").unwrap_or(UNCATEGORIZED)
+}
+
+fn tags(event: &Value) -> impl Iterator<Item = &str> {
+    event["tags"].as_array().into_iter().flatten().filter_map(Value::as_str)
+}
+
+/// Day of the event's start, as the timeline counts them.
+fn day(event: &Value) -> f64 {
+    text(event, "start_date").and_then(time_scale::parse_date).unwrap_or(0.0)
+}
+
+fn last_day(event: &Value) -> f64 {
+    text(event, "end_date").and_then(time_scale::parse_date).unwrap_or_else(|| day(event))
+}
+
+/// `MM-DD` of the event's start.
+fn month_day(event: &Value) -> Option<&str> {
+    let date = text(event, "start_date")?.trim_start_matches('-');
+    date.get(5..10)
+}
+
+/// Whether the title or description contains `search`, in lowercase.
+fn mentions(record: &Value, search: &str) -> bool {
+    ["title", "description"]
+        .iter()
+        .any(|field| text(record, field).is_some_and(|value| value.to_lowercase().contains(search)))
+}
+
+/// The first events by `key`, lowest first.
+fn ranked<'a>(events: impl Iterator<Item = &'a Value>, key: impl Fn(&Value) -> f64) -> Vec<&'a Value> {
+    let mut events: Vec<&Value> = events.collect();
+    events.sort_by(|a, b| key(a).total_cmp(&key(b)));
+    events.truncate(ROW_SIZE);
+    events
+}
+
+/// How often each key occurs, largest first as the API orders them.
+fn counts<K: Ord>(keys: impl Iterator<Item = K>) -> Vec<(K, i64)> {
+    let mut counts: BTreeMap<K, i64> = BTreeMap::new();
+    for key in keys {
+        *counts.entry(key).or_default() += 1;
+    }
+    let mut counts: Vec<_> = counts.into_iter().collect();
+    counts.sort_by_key(|&(_, count)| Reverse(count));
+    counts
+}
+
+fn facets(categories: Vec<(&str, i64)>, events: &[&Value]) -> Value {
+    let decade = |event: &&Value| {
+        let (year, _, _) = time_scale::civil_from_days(day(event).floor() as i64);
+        year.div_euclid(10) * 10
+    };
+    let mut decades = counts(events.iter().map(decade));
+    decades.sort();
+    let categories: Vec<Value> =
+        categories.into_iter().map(|(category, count)| json!({ "category": category, "count": count })).collect();
+    let decades: Vec<Value> = decades.into_iter().map(|(decade, count)| json!({ "decade": decade, "count": count })).collect();
+    let tags: Vec<Value> = counts(events.iter().flat_map(|event| tags(event)))
+        .into_iter()
+        .map(|(tag, count)| json!({ "tag": tag, "count": count }))
+        .collect();
+    json!({ "categories": categories, "decades": decades, "tags": tags })
+}
+
+#[cfg(test)]
+mod tests {
+    use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
+
+    use super::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    async fn fetch(request: Request) -> (u16, Value) {
+        let response = DemoClient.fetch(request).await.ok().unwrap();
+        (response.status, serde_json::from_str(&response.body).unwrap_or(Value::Null))
+    }
+
+    #[wasm_bindgen_test]
+    async fn lists_a_category_a_page_at_a_time() {
+        let (status, body) = fetch(Request::get("/api/events?categories=Battles&sort=oldest&limit=2&page=2")).await;
+        assert_eq!(status, 200);
+        assert_eq!(body["total"], 3);
+        assert_eq!(body["pages"], 2);
+        let titles: Vec<&str> = body["data"].as_array().unwrap().iter().filter_map(|event| text(event, "title")).collect();
+        assert_eq!(titles, ["Battle of Gaugamela"]);
+        // The sidebar keeps offering the other categories.
+        assert!(body["facets"]["categories"].as_array().unwrap().len() > 1);
+    }
+
+    #[wasm_bindgen_test]
+    async fn counts_events_in_every_period_between_the_first_and_last() {
+        let url = "/api/events/histogram?granularity=century&timeline_id=00000000-0000-4000-8000-000000000002";
+        let (_, body) = fetch(Request::get(url)).await;
+        let buckets: Vec<(i64, i64)> = body
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|bucket| (bucket["start"].as_i64().unwrap(), bucket["count"].as_i64().unwrap()))
+            .collect();
+        assert_eq!(buckets, [(-500, 3), (-400, 2), (-300, 1)]);
+    }
+
+    #[wasm_bindgen_test]
+    async fn refuses_changes_and_unknown_paths() {
+        let (status, body) = fetch(Request::post("/api/auth/login")).await;
+        assert_eq!(status, 403);
+        assert_eq!(body["error"], "This is a read-only demo.");
+        assert_eq!(fetch(Request::get("/api/admin/reports")).await.0, 404);
+    }
+}
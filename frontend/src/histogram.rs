@@ -0,0 +1,86 @@
+//! Event counts per period from `GET /api/events/histogram`, shared by the
+//! minimap, the period rail and the statistics page.
+
+use serde::Deserialize;
+
+use crate::api::{ApiClient, FetchError};
+use crate::time_scale;
+
+#[derive(Deserialize, Clone, PartialEq)]
+pub struct Bucket {
+    /// First year of the period (astronomical).
+    pub start: i64,
+    pub count: i64,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Granularity {
+    Year,
+    Decade,
+    Century,
+}
+
+impl Granularity {
+    pub fn years(self) -> i64 {
+        match self {
+            Granularity::Year => 1,
+            Granularity::Decade => 10,
+            Granularity::Century => 100,
+        }
+    }
+
+    /// The finest granularity splitting `span_days` into at most `max`
+    /// periods (or centuries, however many that makes).
+    pub fn fitting(span_days: f64, max: f64) -> Self {
+        let years = span_days / 365.25;
+        if years <= max {
+            Granularity::Year
+        } else if years <= max * 10.0 {
+            Granularity::Decade
+        } else {
+            Granularity::Century
+        }
+    }
+
+    fn param(self) -> &'static str {
+        match self {
+            Granularity::Year => "year",
+            Granularity::Decade => "decade",
+            Granularity::Century => "century",
+        }
+    }
+}
+
+/// "1910s" for a decade or century in the common era, and a year range
+/// across or before 1 BCE, where "-10s" would mean nothing. Years are
+/// just the year.
+pub fn period_label(start: i64, granularity: Granularity) -> String {
+    match granularity {
+        Granularity::Year => time_scale::year_label(start),
+        _ if start > 0 => format!("{}s", start),
+        _ => format!(
+            "{} – {}",
+            time_scale::year_label(start),
+            time_scale::year_label(start + granularity.years() - 1)
+        ),
+    }
+}
+
+/// Events starting in each period, earliest first, empty periods included.
+/// Counts one timeline's events when `timeline_id` is set, and only those
+/// between the `range` days when that is.
+pub async fn fetch(
+    client: &ApiClient,
+    granularity: Granularity,
+    timeline_id: Option<&str>,
+    range: Option<(f64, f64)>,
+) -> Result<Vec<Bucket>, FetchError> {
+    let mut url = format!("/api/events/histogram?granularity={}", granularity.param());
+    if let Some(id) = timeline_id {
+        url += &format!("&timeline_id={}", id);
+    }
+    if let Some((from, to)) = range {
+        url += &format!("&from={}&to={}", time_scale::format_day(from), time_scale::format_day(to));
+    }
+    client.get(&url).await
+}
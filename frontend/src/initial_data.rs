@@ -0,0 +1,27 @@
+use serde::{de::DeserializeOwned, Deserialize};
+
+const ELEMENT_ID: &str = "initial-data";
+
+#[derive(Deserialize)]
+struct InitialData<T> {
+    path: String,
+    data: T,
+}
+
+/// Takes the data the backend embedded into the page for `path`, if any.
+///
+/// The script element is removed once read so that only the first render
+/// uses it; later navigations fetch from the API as usual.
+pub fn take<T: DeserializeOwned>(path: &str) -> Option<T> {
+    let document = web_sys::window()?.document()?;
+    let element = document.get_element_by_id(ELEMENT_ID)?;
+    let text = element.text_content()?;
+
+    let initial: InitialData<T> = serde_json::from_str(&text).ok()?;
+    if initial.path != path {
+        return None;
+    }
+
+    element.remove();
+    Some(initial.data)
+}
@@ -0,0 +1,70 @@
+//! Lane assignment for timeline items that would otherwise overlap.
+//!
+//! Items are horizontal pixel extents; each is given the lowest lane whose
+//! previous item ends before it starts (first-fit interval scheduling over
+//! items sorted by start). Working in pixels rather than dates means labels
+//! are accounted for and the layout follows the zoom level.
+
+/// Horizontal space an item occupies, in pixels.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Extent {
+    pub start: f64,
+    pub end: f64,
+}
+
+/// Minimum gap kept between neighbours in the same lane.
+const GAP: f64 = 8.0;
+
+/// Lane index for each extent (in input order) and the number of lanes used.
+pub fn assign(extents: &[Extent]) -> (Vec<usize>, usize) {
+    let mut order: Vec<usize> = (0..extents.len()).collect();
+    order.sort_by(|&a, &b| extents[a].start.total_cmp(&extents[b].start));
+
+    let mut lane_ends: Vec<f64> = Vec::new();
+    let mut lanes = vec![0; extents.len()];
+    for index in order {
+        let extent = extents[index];
+        let lane = match lane_ends.iter().position(|&end| end + GAP <= extent.start) {
+            Some(lane) => lane,
+            None => {
+                lane_ends.push(f64::NEG_INFINITY);
+                lane_ends.len() - 1
+            }
+        };
+        lane_ends[lane] = extent.end;
+        lanes[index] = lane;
+    }
+    (lanes, lane_ends.len())
+}
+
+/// Like [`assign`], but items sharing a group key are packed together and
+/// groups are stacked in the order of `keys`, followed by any other keys
+/// in `groups` sorted. Every group keeps at least one lane, so one with
+/// nothing in it still has a band. Returns each item's absolute lane and,
+/// per group, its key, first lane and lane count.
+pub fn assign_grouped<'a>(
+    extents: &[Extent],
+    groups: &[&'a str],
+    keys: &[&'a str],
+) -> (Vec<usize>, Vec<(&'a str, usize, usize)>) {
+    let mut rest: Vec<&'a str> = groups.iter().copied().filter(|group| !keys.contains(group)).collect();
+    rest.sort_unstable();
+    rest.dedup();
+    let keys = keys.iter().copied().chain(rest);
+
+    let mut lanes = vec![0; extents.len()];
+    let mut bands = Vec::new();
+    let mut next_lane = 0;
+    for key in keys {
+        let members: Vec<usize> = (0..extents.len()).filter(|&i| groups[i] == key).collect();
+        let member_extents: Vec<Extent> = members.iter().map(|&i| extents[i]).collect();
+        let (member_lanes, count) = assign(&member_extents);
+        for (&i, lane) in members.iter().zip(member_lanes) {
+            lanes[i] = next_lane + lane;
+        }
+        let count = count.max(1);
+        bands.push((key, next_lane, count));
+        next_lane += count;
+    }
+    (lanes, bands)
+}
@@ -1,9 +1,65 @@
-use yew::{function_component, html, use_state, Html};
-use yew_router::{prelude::*, Switch};
+use yew::{function_component, html, use_node_ref, use_state, Callback, Html, Properties, TargetCast};
+use yew_router::prelude::*;
 use serde::{Deserialize, Serialize};
-use gloo_net::http::Request;
 use wasm_bindgen::prelude::*;
 
+mod api;
+mod appearance;
+mod auth;
+mod components;
+#[cfg(feature = "mock-api")]
+mod demo;
+mod histogram;
+mod initial_data;
+mod lanes;
+mod preferences;
+mod rum;
+#[cfg(test)]
+mod testing;
+mod time_scale;
+mod timeline_url;
+
+use api::{ApiClient, FetchError, Request};
+use histogram::Granularity;
+use components::api_provider::{use_api, ApiProvider};
+use components::bar_chart::BarChart;
+use components::breadcrumbs::{self, Breadcrumbs};
+use components::bulk_toolbar::BulkToolbar;
+use components::category_filter::{CategoryFilter, UNCATEGORIZED};
+use components::comments::Comments;
+use components::custom_fields::{self, CustomFieldFilter, CustomFieldValues, CustomFields};
+use components::data_export::DataExport;
+use components::delete_account::DeleteAccount;
+use components::edit_lock::EditLock;
+use components::error_boundary::{use_error_reporter, ErrorBoundary, ErrorReporter};
+use components::event_embed::EventEmbed;
+use components::facet_list::{Facet, FacetList};
+use components::gallery::Gallery;
+use components::heatmap::Heatmap;
+use components::impersonation_banner::ImpersonationBanner;
+use components::install_prompt::InstallPrompt;
+use components::like_button::LikeButton;
+use components::load_error::{use_retry, LoadError};
+use components::members::Members;
+use components::modal::{use_leave_warning, ConfirmDialog, Confirmation};
+use components::move_dialog::MoveDialog;
+use components::notification_bell::NotificationBell;
+use components::notifications::{use_notify, Notification, Notifications};
+use components::org_switcher::{self, OrgSwitcher};
+use components::periods::Periods;
+use components::reactions::Reactions;
+use components::report_event::ReportEvent;
+use components::saved_searches::SavedSearches;
+use components::sessions::Sessions;
+use components::skeleton::{Shape, Skeleton};
+use components::skip_link::SkipLink;
+use components::storage_usage::StorageUsage;
+use components::stories::Stories;
+use components::timeline::Timeline;
+use components::trend_chart::{TrendChart, TrendPoint};
+use components::use_template::UseTemplate;
+use components::virtual_grid::VirtualGrid;
+
 #[derive(Serialize, Deserialize, Clone)]
 struct Event {
     id: String,
@@ -11,119 +67,1283 @@ struct Event {
     description: Option<String>,
     start_date: String,
     end_date: Option<String>,
+    /// Earliest and latest the event could have started, when its date is
+    /// uncertain.
+    #[serde(default)]
+    start_date_min: Option<String>,
+    #[serde(default)]
+    start_date_max: Option<String>,
     location: Option<String>,
     image_url: Option<String>,
+    /// Describes the image for screen readers.
+    #[serde(default)]
+    image_alt: Option<String>,
     category: Option<String>,
+    /// 1 (minor) to 5 (major); decides how far out the timeline shows it.
+    #[serde(default = "default_importance")]
+    importance: i16,
+    /// `#rrggbb` chosen instead of the category colour.
+    #[serde(default)]
+    color: Option<String>,
+    /// Name of one of `appearance::ICONS`.
+    #[serde(default)]
+    icon: Option<String>,
     created_at: String,
     updated_at: String,
+    timeline_id: Option<String>,
+    /// "draft", "published" or "archived".
+    #[serde(default)]
+    status: String,
+    /// UTC time a draft is scheduled to be published.
+    #[serde(default)]
+    publish_at: Option<String>,
+    /// Search matches, when the event came from a search.
+    #[serde(default)]
+    highlights: Option<Highlights>,
+    /// All-time detail page views.
+    #[serde(default)]
+    views: i64,
+    /// Views within the requested window, for trending events.
+    #[serde(default)]
+    recent_views: Option<i64>,
+    #[serde(default)]
+    likes: i64,
+    /// Shown in the Home page carousel.
+    #[serde(default)]
+    featured: bool,
+    /// Values of the timeline's custom fields, keyed by field name.
+    #[serde(default)]
+    custom_fields: serde_json::Value,
+}
+
+fn default_importance() -> i16 {
+    3
+}
+
+/// `[start, end)` character ranges of search matches in each field.
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct Highlights {
+    title: Vec<(usize, usize)>,
+    description: Vec<(usize, usize)>,
+}
+
+/// Renders `text` with the given character ranges wrapped in `<mark>`.
+/// Everything is emitted as text nodes, so event text is never parsed as HTML.
+fn highlighted(text: &str, ranges: &[(usize, usize)]) -> Html {
+    let chars: Vec<char> = text.chars().collect();
+    let mut parts = Vec::new();
+    let mut position = 0;
+    for &(start, end) in ranges {
+        let (start, end) = (start.clamp(position, chars.len()), end.min(chars.len()));
+        if start >= end {
+            continue;
+        }
+        parts.push(html! { {chars[position..start].iter().collect::<String>()} });
+        parts.push(html! { <mark>{chars[start..end].iter().collect::<String>()}</mark> });
+        position = end;
+    }
+    parts.push(html! { {chars[position..].iter().collect::<String>()} });
+    parts.into_iter().collect()
+}
+
+#[derive(Deserialize, Clone, PartialEq)]
+struct TimelineInfo {
+    id: String,
+    title: String,
+    description: Option<String>,
+    archived_at: Option<String>,
+    #[serde(default)]
+    is_private: bool,
+    #[serde(default)]
+    is_template: bool,
+    /// The viewer's access level: "view", "edit" or "own".
+    #[serde(default)]
+    access: String,
+}
+
+#[derive(Deserialize, Clone)]
+struct Page<T> {
+    data: Vec<T>,
+    #[serde(default)]
+    pages: i32,
+}
+
+#[derive(Deserialize, Clone)]
+struct CategoryCount {
+    category: String,
+    count: i64,
+}
+
+#[derive(Deserialize, Clone)]
+struct DecadeCount {
+    decade: i32,
+    count: i64,
+}
+
+#[derive(Deserialize, Clone)]
+struct TagCount {
+    tag: String,
+    count: i64,
+}
+
+/// Counts for the sidebar's facets, as `GET /api/events` gives them.
+#[derive(Deserialize, Clone, Default)]
+struct Facets {
+    categories: Vec<CategoryCount>,
+    decades: Vec<DecadeCount>,
+    tags: Vec<TagCount>,
+}
+
+/// `GET /api/events`: a page plus facet counts for its filter.
+#[derive(Deserialize)]
+struct EventList {
+    data: Vec<Event>,
+    #[serde(default)]
+    total: i64,
+    #[serde(default)]
+    facets: Facets,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    token: String,
+    refresh_token: String,
+}
+
+#[derive(Deserialize, Clone)]
+struct RumTrend {
+    day: String,
+    metric: String,
+    p50: f64,
+    p95: f64,
+}
+
+#[derive(Deserialize, Clone, Default)]
+struct TagCooccurrence {
+    tags: Vec<String>,
+    matrix: Vec<Vec<i64>>,
 }
 
-#[derive(Switch, Clone)]
+/// Someone events are about, shared across timelines.
+#[derive(Deserialize, Clone, PartialEq)]
+struct Person {
+    id: String,
+    name: String,
+    birth_date: Option<String>,
+    death_date: Option<String>,
+    bio: Option<String>,
+    image_url: Option<String>,
+}
+
+/// A work an event is cited from.
+#[derive(Deserialize, Clone, PartialEq)]
+struct Source {
+    id: String,
+    title: String,
+    url: Option<String>,
+    author: Option<String>,
+    citation: Option<String>,
+}
+
+#[derive(Deserialize, Clone)]
+struct DecadeCategory {
+    decade: i32,
+    category: Option<String>,
+    count: i64,
+}
+
+#[derive(Deserialize, Clone, PartialEq)]
+struct EmailTemplate {
+    locale: String,
+    subject: String,
+    body: String,
+}
+
+#[derive(Deserialize, Clone, PartialEq)]
+struct EmailTemplateKind {
+    key: String,
+    variables: Vec<String>,
+    variants: Vec<EmailTemplate>,
+}
+
+/// The saved text for `locale`, or the default locale's, which a new
+/// locale starts from.
+fn stored_template<'a>(kinds: &'a [EmailTemplateKind], key: &str, locale: &str) -> Option<&'a EmailTemplate> {
+    let variants = &kinds.iter().find(|kind| kind.key == key)?.variants;
+    variants
+        .iter()
+        .find(|t| t.locale == locale)
+        .or_else(|| variants.iter().find(|t| t.locale == "en"))
+}
+
+#[derive(Deserialize, Clone)]
+struct RenderedEmail {
+    subject: String,
+    body: String,
+}
+
+#[derive(Routable, Clone, PartialEq)]
 pub enum Route {
-    #[to = "/events/:id"]
+    #[at("/events/:id/merge/:other_id")]
+    MergeEvents { id: String, other_id: String },
+    #[at("/events/:id")]
     EventDetail { id: String },
-    #[to = "/events"]
+    #[at("/events")]
     Events,
-    #[to = "/"]
+    #[at("/people/:id")]
+    PersonDetail { id: String },
+    #[at("/timelines/:id/events/:event_id")]
+    TimelineEvent { id: String, event_id: String },
+    #[at("/timelines/:id/stories/:story_id")]
+    TimelineStory { id: String, story_id: String },
+    #[at("/timelines/:id/events")]
+    TimelineEvents { id: String },
+    #[at("/timelines/:id")]
+    TimelineDetail { id: String },
+    #[at("/timeline")]
+    Timeline,
+    #[at("/")]
     Home,
-    #[to = "/about"]
+    #[at("/about")]
     About,
+    #[at("/login")]
+    Login,
+    #[at("/reset-password")]
+    ResetPassword,
+    #[at("/admin/performance")]
+    AdminPerformance,
+    #[at("/admin/email-templates")]
+    AdminEmailTemplates,
+    #[at("/admin/moderation")]
+    AdminModeration,
+    #[at("/admin/users")]
+    AdminUsers,
+    #[at("/admin")]
+    AdminDashboard,
+    #[at("/stats")]
+    Stats,
+    #[at("/explore")]
+    Explore,
+    #[at("/search")]
+    Search,
+    #[at("/calendar")]
+    Calendar,
+    #[at("/settings")]
+    Settings,
+    #[at("/templates")]
+    Templates,
+}
+
+#[wasm_bindgen(start)]
+pub fn run_app() {
+    rum::init();
+    // The copy from the last visit, then the saved one in case it changed
+    // on another device.
+    preferences::apply(&preferences::cached());
+    if auth::token().is_some() {
+        // Outside the `ApiProvider`, so through the default client.
+        wasm_bindgen_futures::spawn_local(async {
+            preferences::load(&ApiClient::default()).await.ok();
+        });
+    }
+    let root = gloo_utils::document()
+        .get_element_by_id("app")
+        .expect("missing #app element");
+    yew::Renderer::<App>::with_root(root).render();
 }
 
 #[function_component(App)]
 pub fn app() -> Html {
     html! {
-        <BrowserRouter>
-            <Switch<Route> render={Switch::render(routes)} />
-        </BrowserRouter>
+        <ApiProvider>
+            <Notifications>
+                <ErrorBoundary>
+                    <SkipLink />
+                    <ImpersonationBanner />
+                    <BrowserRouter>
+                        <Switch<Route> render={switch} />
+                    </BrowserRouter>
+                </ErrorBoundary>
+            </Notifications>
+        </ApiProvider>
     }
 }
 
-fn routes(route: &Route) -> Html {
+fn switch(route: Route) -> Html {
     match route {
         Route::Home => html! { <Home /> },
         Route::Events => html! { <Events /> },
-        Route::EventDetail { id } => html! { <EventDetail id={id.clone()} /> },
+        Route::TimelineDetail { id } => html! { <TimelineDetail id={id} /> },
+        Route::TimelineEvents { id } => html! { <Events timeline_id={Some(id)} /> },
+        Route::TimelineStory { id, story_id } => {
+            html! { <StoryViewer timeline_id={id} story_id={story_id} /> }
+        }
+        Route::Timeline => html! { <TimelinePage /> },
+        Route::EventDetail { id } => html! { <EventDetail id={id} /> },
+        Route::TimelineEvent { id, event_id } => {
+            html! { <EventDetail id={event_id} timeline_id={Some(id)} /> }
+        }
+        Route::MergeEvents { id, other_id } => html! { <MergeEvents id={id} other_id={other_id} /> },
+        Route::PersonDetail { id } => html! { <PersonDetail id={id} /> },
         Route::About => html! { <About /> },
+        Route::Login => html! { <Login /> },
+        Route::ResetPassword => html! { <ResetPassword /> },
+        Route::AdminPerformance => html! { <AdminPerformance /> },
+        Route::AdminEmailTemplates => html! { <AdminEmailTemplates /> },
+        Route::AdminModeration => html! { <AdminModeration /> },
+        Route::AdminUsers => html! { <AdminUsers /> },
+        Route::AdminDashboard => html! { <AdminDashboard /> },
+        Route::Stats => html! { <Stats /> },
+        Route::Explore => html! { <Explore /> },
+        Route::Search => html! { <Search /> },
+        Route::Calendar => html! { <Calendar /> },
+        Route::Settings => html! { <Settings /> },
+        Route::Templates => html! { <Templates /> },
     }
 }
 
 #[function_component(Home)]
 fn home() -> Html {
+    let client = use_api();
+    let recommended = use_state(Vec::<Event>::new);
+    let trending = use_state(Vec::<Event>::new);
+    let featured = use_state(Vec::<Event>::new);
+    let recent = use_state(Vec::<Event>::new);
+    let on_this_day = use_state(Vec::<Event>::new);
+    // Bumped by "Shuffle" for a random pick of today's events.
+    let shuffles = use_state(|| 0u32);
+
+    {
+        let featured = featured.clone();
+        let recent = recent.clone();
+        let client = client.clone();
+        yew::use_effect_with((), move |_| {
+            {
+                let client = client.clone();
+                wasm_bindgen_futures::spawn_local(async move {
+                    if let Ok(events) = client.get::<Vec<Event>>("/api/events/featured").await {
+                        featured.set(events);
+                    }
+                });
+            }
+            wasm_bindgen_futures::spawn_local(async move {
+                if let Ok(events) = client.get::<Vec<Event>>("/api/events/recent").await {
+                    recent.set(events);
+                }
+            });
+        });
+    }
+
+    {
+        let on_this_day = on_this_day.clone();
+        let client = client.clone();
+        yew::use_effect_with(*shuffles, move |shuffles: &u32| {
+            // The visitor's own calendar day, not the server's.
+            let today = js_sys::Date::new_0();
+            let mut url = format!("/api/events/on-this-day?month={}&day={}", today.get_month() + 1, today.get_date());
+            if *shuffles > 0 {
+                url.push_str("&shuffle=true");
+            }
+            wasm_bindgen_futures::spawn_local(async move {
+                if let Ok(events) = client.get::<Vec<Event>>(&url).await {
+                    on_this_day.set(events);
+                }
+            });
+        });
+    }
+    let shuffle = {
+        let shuffles = shuffles.clone();
+        Callback::from(move |_: yew::MouseEvent| shuffles.set(*shuffles + 1))
+    };
+    let surprise = {
+        let client = client.clone();
+        Callback::from(move |_: yew::MouseEvent| {
+            let client = client.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                if let Ok(event) = client.get::<Event>("/api/events/random").await {
+                    gloo_utils::window().location().set_href(&timeline_link(&event)).ok();
+                }
+            });
+        })
+    };
+
+    {
+        let trending = trending.clone();
+        let client = client.clone();
+        yew::use_effect_with((), move |_| {
+            wasm_bindgen_futures::spawn_local(async move {
+                if let Ok(events) = client.get::<Vec<Event>>("/api/events/trending?window=7d").await {
+                    trending.set(events);
+                }
+            });
+        });
+    }
+
+    {
+        let recommended = recommended.clone();
+        let client = client.clone();
+        yew::use_effect_with((), move |_| {
+            // Only logged-in users get recommendations.
+            if auth::token().is_some() {
+                let fetch_recommended = async move {
+                    if let Ok(events) = client.get::<Vec<Event>>("/api/me/recommendations").await {
+                        recommended.set(events);
+                    }
+                };
+                wasm_bindgen_futures::spawn_local(fetch_recommended);
+            }
+        });
+    }
+
+    let logout = Callback::from(move |_| {
+        let client = client.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            // Ends the session server-side too, so its refresh token is
+            // useless even if it was copied.
+            client.send::<serde::de::IgnoredAny>(Request::post("/api/auth/logout")).await.ok();
+            auth::clear_token();
+            preferences::clear();
+            org_switcher::store(None);
+            gloo_utils::window().location().reload().ok();
+        });
+    });
+
+    let events_href = match preferences::cached().default_timeline_id {
+        Some(id) if auth::token().is_some() => format!("/timelines/{}/events", id),
+        _ => "/events".to_string(),
+    };
+
     html! {
         <div class="min-h-screen bg-base-200">
             <header class="bg-base-100 shadow">
-                <div class="container mx-auto px-4 py-6">
-                    <h1 class="text-3xl font-bold">Timeline Explorer</h1>
+                <div class="container mx-auto px-4 py-6 flex items-center justify-between">
+                    <h1 class="text-3xl font-bold">{"Timeline Explorer"}</h1>
+                    <div class="flex gap-2">
+                        <InstallPrompt />
+                        <a href="/search" class="btn btn-ghost btn-sm">{"Search"}</a>
+                        <a href="/calendar" class="btn btn-ghost btn-sm">{"Calendar"}</a>
+                        <a href="/explore" class="btn btn-ghost btn-sm">{"Explore"}</a>
+                        <a href="/templates" class="btn btn-ghost btn-sm">{"Templates"}</a>
+                        if auth::token().is_some() {
+                            <NotificationBell />
+                            <a href="/settings" class="btn btn-ghost btn-sm">{"Settings"}</a>
+                            <button class="btn btn-ghost btn-sm" onclick={logout}>{"Log out"}</button>
+                        } else {
+                            <a href="/login" class="btn btn-ghost btn-sm">{"Log in"}</a>
+                        }
+                    </div>
                 </div>
             </header>
             <main class="container mx-auto px-4 py-8">
                 <div class="hero bg-base-200 min-h-screen">
                     <div class="hero-content text-center">
                         <div class="max-w-md">
-                            <h1 class="text-5xl font-bold">Welcome to Timeline Explorer</h1>
-                            <p class="py-6">Explore historical events in an interactive timeline</p>
-                            <a href="/events" class="btn btn-primary">View Events</a>
+                            <h1 class="text-5xl font-bold">{"Welcome to Timeline Explorer"}</h1>
+                            <p class="py-6">{"Explore historical events in an interactive timeline"}</p>
+                            <a href={events_href} class="btn btn-primary">{"View Events"}</a>
                         </div>
                     </div>
                 </div>
+                <section class="mt-8" aria-labelledby="on-this-day-heading">
+                    <div class="flex flex-wrap items-center justify-between gap-2 mb-4">
+                        <h2 id="on-this-day-heading" class="text-2xl font-bold">
+                            {format!("On this day · {}", String::from(js_sys::Date::new_0().to_locale_date_string("default", &month_day_format())))}
+                        </h2>
+                        <div class="flex gap-2">
+                            if on_this_day.len() > 1 {
+                                <button class="btn btn-ghost btn-sm" onclick={shuffle}>{"Shuffle"}</button>
+                            }
+                            <button class="btn btn-ghost btn-sm" onclick={surprise}>{"Random event"}</button>
+                        </div>
+                    </div>
+                    if on_this_day.is_empty() {
+                        <p class="opacity-70">{"Nothing recorded for today yet."}</p>
+                    } else {
+                        <ul class="menu bg-base-100 rounded-box shadow">
+                            {on_this_day.iter().map(|event| html! {
+                                <li key={event.id.clone()}>
+                                    <a href={timeline_link(event)} class="flex justify-between">
+                                        <span class="font-semibold">{event_icon(event)}{&event.title}</span>
+                                        <span class="opacity-70">{year(&event.start_date)}</span>
+                                    </a>
+                                </li>
+                            }).collect::<Html>()}
+                        </ul>
+                    }
+                </section>
+                if !featured.is_empty() {
+                    <section class="mt-8">
+                        <h2 class="text-2xl font-bold mb-4">{"Featured"}</h2>
+                        <div class="carousel w-full gap-4 rounded-box">
+                            {featured.iter().map(|event| html! {
+                                <div class="carousel-item w-full md:w-1/2 lg:w-1/3">
+                                    <div class="card bg-base-100 shadow-xl w-full" style={card_accent(event)}>
+                                        if event.image_url.is_some() {
+                                            <figure>
+                                                // The title is right below, so without a description
+                                                // the image is left out for screen readers.
+                                                <img src={api::thumbnail_src(&event.id, 640)} alt={event.image_alt.clone().unwrap_or_default()}
+                                                    class="h-48 w-full object-cover" />
+                                            </figure>
+                                        }
+                                        <div class="card-body">
+                                            <h3 class="card-title">{event_icon(event)}{&event.title}</h3>
+                                            <p>{event_day(&event.start_date)}</p>
+                                            <div class="card-actions justify-end">
+                                                <a href={timeline_link(event)} class="btn btn-primary btn-sm">{"See on timeline"}</a>
+                                            </div>
+                                        </div>
+                                    </div>
+                                </div>
+                            }).collect::<Html>()}
+                        </div>
+                    </section>
+                }
+                if !recent.is_empty() {
+                    <section class="mt-8">
+                        <h2 class="text-2xl font-bold mb-4">{"Recently added"}</h2>
+                        <ul class="menu bg-base-100 rounded-box shadow">
+                            {recent.iter().map(|event| html! {
+                                <li>
+                                    <a href={timeline_link(event)} class="flex justify-between">
+                                        <span class="font-semibold">{&event.title}</span>
+                                        <span class="opacity-70">{event_day(&event.start_date)}</span>
+                                    </a>
+                                </li>
+                            }).collect::<Html>()}
+                        </ul>
+                    </section>
+                }
+                if !trending.is_empty() {
+                    <section class="mt-8">
+                        <h2 class="text-2xl font-bold mb-4">{"Trending this week"}</h2>
+                        <div class="grid grid-cols-1 md:grid-cols-2 lg:grid-cols-3 gap-6">
+                            {trending.iter().map(|event| html! {
+                                <div class="card bg-base-100 shadow-xl" style={card_accent(event)}>
+                                    <div class="card-body">
+                                        <h3 class="card-title">{event_icon(event)}{&event.title}</h3>
+                                        <p>{&event.start_date}</p>
+                                        <p class="text-sm opacity-70">
+                                            {format!("{} views", event.recent_views.unwrap_or(event.views))}
+                                        </p>
+                                        <div class="card-actions justify-end">
+                                            <a href={format!("/events/{}", event.id)} class="btn btn-primary btn-sm">{"View Details"}</a>
+                                        </div>
+                                    </div>
+                                </div>
+                            }).collect::<Html>()}
+                        </div>
+                    </section>
+                }
+                if !recommended.is_empty() {
+                    <section class="mt-8">
+                        <h2 class="text-2xl font-bold mb-4">{"Recommended for you"}</h2>
+                        <div class="grid grid-cols-1 md:grid-cols-2 lg:grid-cols-3 gap-6">
+                            {recommended.iter().map(|event| html! {
+                                <div class="card bg-base-100 shadow-xl" style={card_accent(event)}>
+                                    <div class="card-body">
+                                        <h3 class="card-title">{event_icon(event)}{&event.title}</h3>
+                                        <p>{&event.start_date}</p>
+                                        <div class="card-actions justify-end">
+                                            <a href={format!("/events/{}", event.id)} class="btn btn-primary btn-sm">{"View Details"}</a>
+                                        </div>
+                                    </div>
+                                </div>
+                            }).collect::<Html>()}
+                        </div>
+                    </section>
+                }
             </main>
         </div>
     }
 }
 
+#[function_component(Login)]
+fn login() -> Html {
+    let client = use_api();
+    let username = use_state(String::new);
+    let password = use_state(String::new);
+    let error = use_state(|| Option::<String>::None);
+    let providers = use_state(Vec::<String>::new);
+
+    {
+        let error = error.clone();
+        let providers = providers.clone();
+        let client = client.clone();
+        yew::use_effect_with((), move |_| {
+            // OAuth logins come back as `#token=...&refresh_token=...`
+            // or `#error=...`.
+            let window = gloo_utils::window();
+            let fragment = window.location().hash().unwrap_or_default();
+            if let Ok(params) = web_sys::UrlSearchParams::new_with_str(fragment.trim_start_matches('#')) {
+                if let (Some(token), Some(refresh_token)) = (params.get("token"), params.get("refresh_token")) {
+                    auth::set_tokens(&token, &refresh_token);
+                    window.location().set_href("/").ok();
+                    return;
+                }
+                if let Some(message) = params.get("error") {
+                    error.set(Some(message));
+                    if let Ok(history) = window.history() {
+                        history.replace_state_with_url(&JsValue::NULL, "", Some("/login")).ok();
+                    }
+                }
+            }
+            wasm_bindgen_futures::spawn_local(async move {
+                if let Ok(names) = client.get::<Vec<String>>("/api/auth/oauth/providers").await {
+                    providers.set(names);
+                }
+            });
+        });
+    }
+
+    let oninput = |field: &yew::UseStateHandle<String>| {
+        let field = field.clone();
+        Callback::from(move |e: yew::InputEvent| {
+            let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+            field.set(input.value());
+        })
+    };
+
+    let onsubmit = {
+        let username = username.clone();
+        let password = password.clone();
+        let error = error.clone();
+        let client = client.clone();
+        Callback::from(move |e: yew::SubmitEvent| {
+            e.prevent_default();
+            let body = serde_json::json!({ "username": *username, "password": *password });
+            let error = error.clone();
+            let client = client.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match client.send_json::<TokenResponse>(Request::post("/api/auth/login"), &body).await {
+                    Ok(session) => {
+                        auth::set_tokens(&session.token, &session.refresh_token);
+                        gloo_utils::window().location().set_href("/").ok();
+                    }
+                    Err(FetchError::Status { status: 401, .. }) => {
+                        error.set(Some("Wrong username or password".to_string()));
+                    }
+                    Err(other) => error.set(Some(other.to_string())),
+                }
+            });
+        })
+    };
+
+    html! {
+        <div class="min-h-screen bg-base-200 flex items-center justify-center">
+            <form class="card bg-base-100 shadow-xl w-full max-w-sm" {onsubmit}>
+                <div class="card-body">
+                    <h1 class="card-title text-2xl">{"Log in"}</h1>
+                    if let Some(message) = &*error {
+                        <div class="alert alert-error">{message}</div>
+                    }
+                    <input class="input input-bordered" placeholder="Username" aria-label="Username"
+                        autocomplete="username" value={(*username).clone()} oninput={oninput(&username)} />
+                    <input class="input input-bordered" type="password" placeholder="Password" aria-label="Password"
+                        autocomplete="current-password" value={(*password).clone()} oninput={oninput(&password)} />
+                    <button class="btn btn-primary" type="submit">{"Log in"}</button>
+                    if !providers.is_empty() {
+                        <div class="divider">{"or"}</div>
+                        {providers.iter().map(|name| html! {
+                            <a key={name.clone()} class="btn btn-outline" href={format!("/api/auth/oauth/{}/start", name)}>
+                                {format!("Continue with {}", provider_label(name))}
+                            </a>
+                        }).collect::<Html>()}
+                    }
+                </div>
+            </form>
+        </div>
+    }
+}
+
+/// Where a forced password reset's email leads: choosing a new password
+/// logs the user straight in.
+#[function_component(ResetPassword)]
+fn reset_password() -> Html {
+    let client = use_api();
+    let token = use_state(|| query_param("token").unwrap_or_default());
+    let password = use_state(String::new);
+    let error = use_state(|| Option::<String>::None);
+
+    let oninput = {
+        let password = password.clone();
+        Callback::from(move |e: yew::InputEvent| {
+            let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+            password.set(input.value());
+        })
+    };
+    let onsubmit = {
+        let token = token.clone();
+        let password = password.clone();
+        let error = error.clone();
+        Callback::from(move |e: yew::SubmitEvent| {
+            e.prevent_default();
+            if password.len() < 8 {
+                error.set(Some("Passwords need at least 8 characters".to_string()));
+                return;
+            }
+            let body = serde_json::json!({ "token": *token, "password": *password });
+            let error = error.clone();
+            let client = client.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match client.send_json::<TokenResponse>(Request::post("/api/auth/password-reset"), &body).await {
+                    Ok(session) => {
+                        auth::set_tokens(&session.token, &session.refresh_token);
+                        gloo_utils::window().location().set_href("/").ok();
+                    }
+                    Err(FetchError::Status { status: 404, .. }) => {
+                        error.set(Some("This link has expired or was already used. Ask an admin for a new one.".to_string()));
+                    }
+                    Err(FetchError::Status { status: 403, .. }) => {
+                        error.set(Some("This account has been disabled".to_string()));
+                    }
+                    Err(other) => error.set(Some(other.to_string())),
+                }
+            });
+        })
+    };
+
+    html! {
+        <div class="min-h-screen bg-base-200 flex items-center justify-center">
+            <form class="card bg-base-100 shadow-xl w-full max-w-sm" {onsubmit}>
+                <div class="card-body">
+                    <h1 class="card-title text-2xl">{"Choose a new password"}</h1>
+                    if token.is_empty() {
+                        <div class="alert alert-error">{"This link is missing its token. Open the one from your email."}</div>
+                    }
+                    if let Some(message) = &*error {
+                        <div class="alert alert-error">{message}</div>
+                    }
+                    <input class="input input-bordered" type="password" placeholder="New password" aria-label="New password"
+                        autocomplete="new-password" minlength="8" value={(*password).clone()} {oninput} />
+                    <button class="btn btn-primary" type="submit" disabled={token.is_empty()}>{"Save and log in"}</button>
+                </div>
+            </form>
+        </div>
+    }
+}
+
+fn provider_label(name: &str) -> &str {
+    match name {
+        "google" => "Google",
+        "github" => "GitHub",
+        other => other,
+    }
+}
+
+#[derive(Properties, PartialEq)]
+struct EventsProps {
+    /// List this timeline's events instead of all public ones.
+    #[prop_or_default]
+    timeline_id: Option<String>,
+}
+
+/// Where an event's detail page lives: under its timeline when the list
+/// was reached through one.
+fn event_path(timeline_id: Option<&str>, event_id: &str) -> String {
+    match timeline_id {
+        Some(timeline_id) => format!("/timelines/{}/events/{}", timeline_id, event_id),
+        None => format!("/events/{}", event_id),
+    }
+}
+
+fn matches_search(event: &Event, search: &str) -> bool {
+    let search = search.to_lowercase();
+    event.title.to_lowercase().contains(&search)
+        || event.description.as_deref().is_some_and(|text| text.to_lowercase().contains(&search))
+}
+
+fn category_name(event: &Event) -> &str {
+    event.category.as_deref().unwrap_or(UNCATEGORIZED)
+}
+
+/// An astronomical year in the API's date format.
+fn iso_year(year: i32) -> String {
+    match year < 0 {
+        true => format!("-{:04}", -year),
+        false => format!("{:04}", year),
+    }
+}
+
+/// The `start_date` and `end_date` of a decade, named by its first year.
+fn decade_window(decade: i32) -> (String, String) {
+    (
+        format!("{}-01-01T00:00:00", iso_year(decade)),
+        format!("{}-12-31T23:59:59", iso_year(decade + 9)),
+    )
+}
+
+/// "1960s", or for early decades the span of years, e.g. "50 BCE – 41 BCE".
+fn decade_label(decade: i32) -> String {
+    match decade > 0 {
+        true => format!("{}s", decade),
+        false => format!(
+            "{} – {}",
+            time_scale::year_label(decade as i64),
+            time_scale::year_label(decade as i64 + 9)
+        ),
+    }
+}
+
+/// Events per category, most first, as `GET /api/categories/counts` gives them.
+fn count_categories(events: &[Event]) -> Vec<(String, i64)> {
+    let mut counts: Vec<(String, i64)> = Vec::new();
+    for event in events {
+        match counts.iter_mut().find(|(name, _)| name == category_name(event)) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((category_name(event).to_string(), 1)),
+        }
+    }
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts
+}
+
 #[function_component(Events)]
-fn events() -> Html {
-    let events = use_state(|| Vec::<Event>::new());
-    let loading = use_state(|| true);
-    
+fn events(props: &EventsProps) -> Html {
+    let client = use_api();
+    let events = use_state(|| match props.timeline_id {
+        Some(_) => None,
+        None => initial_data::take::<Page<Event>>("/events").map(|page| page.data),
+    });
+    let loading = use_state(|| events.is_none());
+    let search = use_state(|| query_param("search").unwrap_or_default());
+    let categories = use_state(|| {
+        query_param("categories")
+            .map(|list| list.split(',').filter(|name| !name.is_empty()).map(str::to_string).collect::<Vec<String>>())
+            .unwrap_or_default()
+    });
+    let category_counts = use_state(Vec::<(String, i64)>::new);
+    let decade = use_state(|| query_param("decade").and_then(|value| value.parse::<i32>().ok()));
+    let tag = use_state(|| query_param("tag").filter(|tag| !tag.is_empty()));
+    let field_filters = use_state(custom_fields::filters_from_url);
+    // Facets of the full listing; a timeline's list has none.
+    let decade_counts = use_state(Vec::<DecadeCount>::new);
+    let tag_counts = use_state(Vec::<TagCount>::new);
+    let organization = use_state(|| match auth::token() {
+        Some(_) => org_switcher::current(),
+        None => None,
+    });
+    let timeline = use_state(|| Option::<TimelineInfo>::None);
+    let error = use_state(|| Option::<FetchError>::None);
+    let (attempt, retry) = use_retry();
+    let errors = use_error_reporter();
+    // Events picked for "Move to…", while its dialog is open.
+    let moving = use_state(|| Option::<Vec<String>>::None);
+    // Events ticked for bulk actions.
+    let selected = use_state(Vec::<String>::new);
+    let search_input = use_node_ref();
+
+    {
+        let timeline = timeline.clone();
+        let client = client.clone();
+        yew::use_effect_with(props.timeline_id.clone(), move |timeline_id: &Option<String>| {
+            if let Some(timeline_id) = timeline_id {
+                let url = format!("/api/timelines/{}", timeline_id);
+                wasm_bindgen_futures::spawn_local(async move {
+                    if let Ok(timeline_data) = client.get::<TimelineInfo>(&url).await {
+                        timeline.set(Some(timeline_data));
+                    }
+                });
+            }
+        });
+    }
+
     {
         let events = events.clone();
+        let category_counts = category_counts.clone();
+        let decade_counts = decade_counts.clone();
+        let tag_counts = tag_counts.clone();
         let loading = loading.clone();
-        yew::use_effect_with_deps(
-            move |_| {
+        let error = error.clone();
+        let client = client.clone();
+        // Runs even when embedded data was rendered, to revalidate it.
+        yew::use_effect_with(
+            (
+                (*search).clone(),
+                (*categories).clone(),
+                *decade,
+                (*tag).clone(),
+                (*field_filters).clone(),
+                (*organization).clone(),
+                props.timeline_id.clone(),
+                attempt,
+            ),
+            move |(search, categories, decade, tag, field_filters, organization, timeline_id, _)| {
+                error.set(None);
+                let mut params = Vec::new();
+                if !search.is_empty() {
+                    params.push(format!("search={}", js_sys::encode_uri_component(search)));
+                }
+                if !categories.is_empty() {
+                    params.push(format!("categories={}", js_sys::encode_uri_component(&categories.join(","))));
+                }
+                if let Some(tag) = tag {
+                    params.push(format!("tag={}", js_sys::encode_uri_component(tag)));
+                }
+                // Custom fields belong to a timeline, so only its list
+                // filters on them, and the API does the filtering.
+                let field_params: Vec<String> = match timeline_id {
+                    Some(_) => field_filters
+                        .iter()
+                        .map(|(key, value)| format!("{}={}", key, js_sys::encode_uri_component(value)))
+                        .collect(),
+                    None => Vec::new(),
+                };
+                params.extend(field_params.iter().cloned());
+                if let Some(decade) = decade {
+                    params.push(format!("decade={}", decade));
+                }
+                let query = if params.is_empty() { String::new() } else { format!("?{}", params.join("&")) };
+                // The API takes the decade as a date range.
+                if let Some(decade) = decade {
+                    params.pop();
+                    let (start, end) = decade_window(*decade);
+                    params.push(format!("start_date={}&end_date={}", start, end));
+                }
+                // The organization is remembered on the device, not linked.
+                if let Some(organization) = organization {
+                    params.push(format!("organization_id={}", organization));
+                }
+                let path = match timeline_id {
+                    Some(id) => format!("/timelines/{}/events", id),
+                    None => "/events".to_string(),
+                };
+                breadcrumbs::keep_list_url(&format!("{}{}", path, query));
+                // Page size and order come from the user's settings rather
+                // than the address bar.
+                let listing = preferences::cached();
+                params.push(format!("limit={}", listing.items_per_page));
+                params.push(format!("sort={}", listing.default_sort));
+                let api_query = format!("?{}", params.join("&"));
+
+                let search = search.clone();
+                let categories = categories.clone();
+                let timeline_id = timeline_id.clone();
                 let fetch_events = async move {
-                    let response = Request::get("/api/events")
-                        .send()
-                        .await
-                        .unwrap();
-                    let events: Vec<Event> = response.json().await.unwrap();
-                    events.set(events);
+                    let fetched = match timeline_id {
+                        // Timeline lists are short; filter and count them here.
+                        Some(id) => client.get::<Vec<Event>>(&format!(
+                            "/api/timelines/{}/events{}",
+                            id,
+                            if field_params.is_empty() { String::new() } else { format!("?{}", field_params.join("&")) }
+                        ))
+                            .await
+                            .map(|events_data| {
+                                let found: Vec<Event> =
+                                    events_data.into_iter().filter(|event| matches_search(event, &search)).collect();
+                                category_counts.set(count_categories(&found));
+                                found
+                                    .into_iter()
+                                    .filter(|event| {
+                                        categories.is_empty()
+                                            || categories.iter().any(|name| name.eq_ignore_ascii_case(category_name(event)))
+                                    })
+                                    .collect()
+                            }),
+                        None => client.get::<EventList>(&format!("/api/events{}", api_query)).await.map(|list| {
+                            let facets = list.facets;
+                            category_counts.set(
+                                facets.categories.into_iter().map(|count| (count.category, count.count)).collect(),
+                            );
+                            decade_counts.set(facets.decades);
+                            tag_counts.set(facets.tags);
+                            list.data
+                        }),
+                    };
+                    match fetched {
+                        Ok(events_data) => events.set(Some(events_data)),
+                        Err(fetch_error) => error.set(Some(fetch_error)),
+                    }
                     loading.set(false);
                 };
                 wasm_bindgen_futures::spawn_local(fetch_events);
             },
-            vec![],
         );
     }
 
-    if *loading {
-        return html! { <div class="text-center">Loading...</div> };
-    }
+    // A new filter starts a new selection, so actions never reach events
+    // that are no longer listed.
+    let on_categories = {
+        let categories = categories.clone();
+        let selected = selected.clone();
+        Callback::from(move |names: Vec<String>| {
+            selected.set(Vec::new());
+            categories.set(names);
+        })
+    };
+    let on_decade = {
+        let decade = decade.clone();
+        let selected = selected.clone();
+        Callback::from(move |value: Option<String>| {
+            selected.set(Vec::new());
+            decade.set(value.and_then(|value| value.parse().ok()));
+        })
+    };
+    let on_tag = {
+        let tag = tag.clone();
+        let selected = selected.clone();
+        Callback::from(move |value: Option<String>| {
+            selected.set(Vec::new());
+            tag.set(value);
+        })
+    };
+    let on_field_filters = {
+        let field_filters = field_filters.clone();
+        let selected = selected.clone();
+        Callback::from(move |filters: Vec<(String, String)>| {
+            selected.set(Vec::new());
+            field_filters.set(filters);
+        })
+    };
+    // Saved searches alert on public events, so they are offered on the
+    // list of all of them rather than on a timeline's.
+    let on_saved_search = {
+        let search = search.clone();
+        let categories = categories.clone();
+        let selected = selected.clone();
+        Callback::from(move |(text, names): (String, Vec<String>)| {
+            selected.set(Vec::new());
+            search.set(text);
+            categories.set(names);
+        })
+    };
+
+    let on_organization = {
+        let organization = organization.clone();
+        let selected = selected.clone();
+        Callback::from(move |id: Option<String>| {
+            selected.set(Vec::new());
+            organization.set(id);
+        })
+    };
+
+    let on_search = {
+        let search = search.clone();
+        let search_input = search_input.clone();
+        let selected = selected.clone();
+        Callback::from(move |e: yew::SubmitEvent| {
+            e.prevent_default();
+            if let Some(input) = search_input.cast::<web_sys::HtmlInputElement>() {
+                selected.set(Vec::new());
+                search.set(input.value().trim().to_string());
+            }
+        })
+    };
+
+    let toggle_selected = {
+        let selected = selected.clone();
+        Callback::from(move |id: String| {
+            let mut next = (*selected).clone();
+            match next.iter().position(|other| *other == id) {
+                Some(index) => {
+                    next.remove(index);
+                }
+                None => next.push(id),
+            }
+            selected.set(next);
+        })
+    };
+    let clear_selection = {
+        let selected = selected.clone();
+        Callback::from(move |_| selected.set(Vec::new()))
+    };
+    let on_bulk_done = {
+        let selected = selected.clone();
+        let retry = retry.clone();
+        Callback::from(move |_| {
+            selected.set(Vec::new());
+            retry.emit(());
+        })
+    };
+
+    // The copy is a draft; its page is where it gets edited.
+    let duplicate = {
+        let errors = errors.clone();
+        let client = client.clone();
+        Callback::from(move |id: String| {
+            let errors = errors.clone();
+            let client = client.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                let url = format!("/api/events/{}/duplicate", id);
+                match client.send::<Event>(Request::post(&url)).await {
+                    Ok(copy) => {
+                        gloo_utils::window()
+                            .location()
+                            .set_href(&event_path(copy.timeline_id.as_deref(), &copy.id))
+                            .ok();
+                    }
+                    Err(error) => errors.report(error),
+                }
+            });
+        })
+    };
+    let signed_in = auth::token().is_some();
+    let move_to = {
+        let moving = moving.clone();
+        Callback::from(move |ids: Vec<String>| moving.set(Some(ids)))
+    };
+    let close_move = {
+        let moving = moving.clone();
+        Callback::from(move |_| moving.set(None))
+    };
+    // Moved events may now belong in this list or out of it.
+    let on_moved = retry.reform(|_: String| ());
+
+    let render_card = {
+        let events = events.clone();
+        let timeline_id = props.timeline_id.clone();
+        let selected = selected.clone();
+        Callback::from(move |index: usize| {
+            let Some(event) = events.as_ref().and_then(|events| events.get(index)) else {
+                return html! {};
+            };
+            let highlights = event.highlights.clone().unwrap_or_default();
+            let checked = selected.contains(&event.id);
+            html! {
+                <div key={event.id.clone()} class="card bg-base-100 shadow-xl" style={card_accent(event)}>
+                    <div class="card-body">
+                        <h2 class="card-title">
+                            if signed_in {
+                                <input
+                                    type="checkbox"
+                                    class="checkbox checkbox-sm"
+                                    aria-label={format!("Select {}", event.title)}
+                                    {checked}
+                                    onchange={toggle_selected.reform({
+                                        let id = event.id.clone();
+                                        move |_| id.clone()
+                                    })}
+                                />
+                            }
+                            {event_icon(event)}{highlighted(&event.title, &highlights.title)}
+                        </h2>
+                        <p>
+                            {match &event.description {
+                                Some(description) => highlighted(description, &highlights.description),
+                                None => html! { "No description" },
+                            }}
+                        </p>
+                        <div class="card-actions justify-end items-center">
+                            if event.likes > 0 {
+                                <span class="text-sm opacity-70 mr-auto" aria-label={format!("{} likes", event.likes)}>
+                                    {format!("♥ {}", event.likes)}
+                                </span>
+                            }
+                            if signed_in {
+                                <div class="dropdown dropdown-end">
+                                    <label tabindex="0" class="btn btn-ghost" aria-label="More actions">{"⋯"}</label>
+                                    <ul tabindex="0" class="dropdown-content menu p-2 shadow bg-base-100 rounded-box w-40 z-10">
+                                        <li>
+                                            <button onclick={duplicate.reform({
+                                                let id = event.id.clone();
+                                                move |_| id.clone()
+                                            })}>{"Duplicate"}</button>
+                                        </li>
+                                        <li>
+                                            <button onclick={move_to.reform({
+                                                let id = event.id.clone();
+                                                move |_| vec![id.clone()]
+                                            })}>{"Move to…"}</button>
+                                        </li>
+                                    </ul>
+                                </div>
+                            }
+                            <a href={event_path(timeline_id.as_deref(), &event.id)} class="btn btn-primary">{"View Details"}</a>
+                        </div>
+                    </div>
+                </div>
+            }
+        })
+    };
+
+    let route = match &props.timeline_id {
+        Some(id) => Route::TimelineEvents { id: id.clone() },
+        None => Route::Events,
+    };
 
     html! {
         <div class="min-h-screen bg-base-200">
             <header class="bg-base-100 shadow">
                 <div class="container mx-auto px-4 py-6">
-                    <h1 class="text-3xl font-bold">Events Timeline</h1>
+                    <Breadcrumbs {route} timeline={timeline.as_ref().map(|timeline| timeline.title.clone())} />
+                    <div class="flex flex-wrap items-center justify-between gap-2">
+                        <h1 class="text-3xl font-bold">{"Events Timeline"}</h1>
+                        if signed_in && props.timeline_id.is_none() {
+                            <OrgSwitcher selected={(*organization).clone()} on_change={on_organization} />
+                        }
+                    </div>
                 </div>
             </header>
             <main class="container mx-auto px-4 py-8">
-                <div class="grid grid-cols-1 md:grid-cols-2 lg:grid-cols-3 gap-6">
-                    {events.iter().map(|event| {
-                        html! {
-                            <div class="card bg-base-100 shadow-xl">
-                                <div class="card-body">
-                                    <h2 class="card-title">{&event.title}</h2>
-                                    <p>{&event.description.as_ref().unwrap_or(&"No description".to_string())}</p>
-                                    <div class="card-actions justify-end">
-                                        <a href={format!("/events/{}", event.id)} class="btn btn-primary">View Details</a>
-                                    </div>
-                                </div>
-                            </div>
+                <form class="flex gap-2 mb-6" onsubmit={on_search}>
+                    <input
+                        ref={search_input}
+                        type="search"
+                        class="input input-bordered flex-1"
+                        placeholder="Search events"
+                        aria-label="Search events"
+                        value={(*search).clone()}
+                    />
+                    <button class="btn btn-primary" type="submit">{"Search"}</button>
+                </form>
+                <div class="flex flex-col md:flex-row gap-6">
+                    <div class="flex flex-col gap-6">
+                        <CategoryFilter
+                            counts={(*category_counts).clone()}
+                            selected={(*categories).clone()}
+                            on_change={on_categories}
+                        />
+                        if let Some(timeline_id) = &props.timeline_id {
+                            <CustomFieldFilter
+                                timeline_id={timeline_id.clone()}
+                                selected={(*field_filters).clone()}
+                                on_change={on_field_filters}
+                            />
                         }
-                    }).collect::<Html>()}
+                        <FacetList
+                            title="Decades"
+                            facets={decade_counts.iter().map(|count| Facet {
+                                value: count.decade.to_string(),
+                                label: decade_label(count.decade),
+                                count: count.count,
+                            }).collect::<Vec<_>>()}
+                            selected={decade.map(|decade| decade.to_string())}
+                            on_change={on_decade}
+                        />
+                        <FacetList
+                            title="Tags"
+                            facets={tag_counts.iter().map(|count| Facet {
+                                value: count.tag.clone(),
+                                label: format!("#{}", count.tag),
+                                count: count.count,
+                            }).collect::<Vec<_>>()}
+                            selected={(*tag).clone()}
+                            on_change={on_tag}
+                        />
+                        if signed_in && props.timeline_id.is_none() {
+                            <SavedSearches
+                                search={(*search).clone()}
+                                categories={(*categories).clone()}
+                                on_apply={on_saved_search}
+                            />
+                        }
+                    </div>
+                    <div class="flex-1 min-w-0">
+                        if let Some(fetch_error) = &*error {
+                            <LoadError error={fetch_error.clone()} onretry={retry} />
+                        } else if *loading {
+                            <Skeleton shape={Shape::Card} />
+                        } else {
+                            <VirtualGrid items={events.iter().flatten().count()} render={render_card} />
+                        }
+                    </div>
                 </div>
+                if let Some(event_ids) = &*moving {
+                    <MoveDialog event_ids={event_ids.clone()} on_close={close_move} {on_moved} />
+                }
+                if !selected.is_empty() {
+                    <BulkToolbar selected={(*selected).clone()} on_clear={clear_selection} on_done={on_bulk_done} />
+                }
             </main>
         </div>
     }
@@ -131,104 +1351,3542 @@ fn events() -> Html {
 
 #[function_component(EventDetail)]
 fn event_detail(props: &EventDetailProps) -> Html {
-    let event = use_state(|| Option::<Event>::None);
-    let loading = use_state(|| true);
-    
+    let event = use_state(|| initial_data::take::<Event>(&format!("/events/{}", props.id)));
+    let duplicates = use_state(Vec::<Event>::new);
+    let timeline = use_state(|| Option::<TimelineInfo>::None);
+    let people = use_state(Vec::<Person>::new);
+    // Everyone who could be linked, for suggestions; loaded for signed-in users.
+    let known_people = use_state(Vec::<Person>::new);
+    let sources = use_state(Vec::<Source>::new);
+    let error = use_state(|| Option::<FetchError>::None);
+    let (attempt, retry) = use_retry();
+    let errors = use_error_reporter();
+    let client = use_api();
+    let notify = use_notify();
+    let alt_input = use_node_ref();
+    let person_input = use_node_ref();
+    let source_form = use_node_ref();
+    // Someone else holds the edit lock, so the controls are hidden.
+    let locked_out = use_state(|| false);
+
     {
         let event = event.clone();
-        let loading = loading.clone();
-        let id = props.id.clone();
-        yew::use_effect_with_deps(
-            move |_| {
-                let fetch_event = async move {
-                    let response = Request::get(&format!("/api/events/{}", id))
-                        .send()
-                        .await
-                        .unwrap();
-                    let event_data: Event = response.json().await.unwrap();
-                    event.set(Some(event_data));
-                    loading.set(false);
+        let duplicates = duplicates.clone();
+        let timeline = timeline.clone();
+        let people = people.clone();
+        let known_people = known_people.clone();
+        let sources = sources.clone();
+        let error = error.clone();
+        let client = client.clone();
+        yew::use_effect_with((props.id.clone(), attempt), move |(id, _): &(String, u32)| {
+            let id = id.clone();
+            error.set(None);
+            let fetch_event = async move {
+                let event_data = match client.get::<Event>(&format!("/api/events/{}", id)).await {
+                    Ok(event_data) => event_data,
+                    Err(fetch_error) => {
+                        error.set(Some(fetch_error));
+                        return;
+                    }
                 };
-                wasm_bindgen_futures::spawn_local(fetch_event);
-            },
-            vec![id],
-        );
-    }
+                let timeline_id = event_data.timeline_id.clone();
+                event.set(Some(event_data));
 
-    if *loading {
-        return html! { <div class="text-center">Loading...</div> };
+                // The page works without these, so failures are left unshown.
+                if let Some(timeline_id) = timeline_id {
+                    let url = format!("/api/timelines/{}", timeline_id);
+                    if let Ok(timeline_data) = client.get::<TimelineInfo>(&url).await {
+                        timeline.set(Some(timeline_data));
+                    }
+                }
+                let url = format!("/api/events/{}/duplicates", id);
+                if let Ok(duplicates_data) = client.get::<Vec<Event>>(&url).await {
+                    duplicates.set(duplicates_data);
+                }
+                let url = format!("/api/events/{}/people", id);
+                if let Ok(people_data) = client.get::<Vec<Person>>(&url).await {
+                    people.set(people_data);
+                }
+                let url = format!("/api/events/{}/sources", id);
+                if let Ok(sources_data) = client.get::<Vec<Source>>(&url).await {
+                    sources.set(sources_data);
+                }
+                if auth::token().is_some() {
+                    if let Ok(people_data) = client.get::<Vec<Person>>("/api/people").await {
+                        known_people.set(people_data);
+                    }
+                }
+            };
+            wasm_bindgen_futures::spawn_local(fetch_event);
+        });
     }
 
-    let event_data = event.as_ref().unwrap();
-    
+    let route = match &props.timeline_id {
+        Some(timeline_id) => Route::TimelineEvent { id: timeline_id.clone(), event_id: props.id.clone() },
+        None => Route::EventDetail { id: props.id.clone() },
+    };
+
+    // A failed revalidation keeps showing the embedded event.
+    let event_data = match (event.as_ref(), &*error) {
+        (Some(event_data), _) => event_data,
+        (None, Some(fetch_error)) => return page_error(fetch_error, retry),
+        (None, None) => {
+            return html! {
+                <div class="min-h-screen bg-base-200">
+                    <header class="bg-base-100 shadow">
+                        <div class="container mx-auto px-4 py-6">
+                            <Breadcrumbs {route} />
+                            <h1 class="text-3xl font-bold">{"Event Details"}</h1>
+                        </div>
+                    </header>
+                    <main class="container mx-auto px-4 py-8">
+                        <Skeleton shape={Shape::Detail} />
+                    </main>
+                </div>
+            }
+        }
+    };
+    let archived = timeline.as_ref().is_some_and(|timeline| timeline.archived_at.is_some());
+    // Loose events can be edited by anyone signed in.
+    let may_edit = !archived
+        && match (&event_data.timeline_id, &*timeline) {
+            (None, _) => auth::token().is_some(),
+            (Some(_), Some(timeline)) => timeline.access == "edit" || timeline.access == "own",
+            (Some(_), None) => false,
+        };
+    let can_edit = may_edit && !*locked_out;
+    let on_lock = {
+        let locked_out = locked_out.clone();
+        Callback::from(move |holding: bool| locked_out.set(!holding))
+    };
+    let published = event_data.status == "published";
+
+    let toggle_published = {
+        let event = event.clone();
+        let errors = errors.clone();
+        let notify = notify.clone();
+        let id = event_data.id.clone();
+        let client = client.clone();
+        Callback::from(move |_| {
+            let event = event.clone();
+            let errors = errors.clone();
+            let notify = notify.clone();
+            let id = id.clone();
+            let client = client.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                let saved = if published {
+                    let body = serde_json::json!({ "status": "draft" });
+                    client.send_json::<Event>(Request::put(&format!("/api/events/{}/status", id)), &body).await
+                } else {
+                    client.send::<Event>(Request::post(&format!("/api/events/{}/publish", id))).await
+                };
+                match saved {
+                    Ok(event_data) => {
+                        notify.success(if published { "Moved back to drafts" } else { "Event published" });
+                        event.set(Some(event_data));
+                    }
+                    Err(error) => errors.report(error),
+                }
+            });
+        })
+    };
+
+    let schedule = {
+        let event = event.clone();
+        let errors = errors.clone();
+        let id = event_data.id.clone();
+        let client = client.clone();
+        Callback::from(move |publish_at: Option<String>| {
+            let event = event.clone();
+            let errors = errors.clone();
+            let url = format!("/api/events/{}/schedule", id);
+            let client = client.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                let body = serde_json::json!({ "publish_at": publish_at });
+                match client.send_json::<Event>(Request::put(&url), &body).await {
+                    Ok(event_data) => event.set(Some(event_data)),
+                    Err(error) => errors.report(error),
+                }
+            });
+        })
+    };
+    let on_schedule = {
+        let schedule = schedule.clone();
+        Callback::from(move |e: yew::Event| {
+            let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+            if !input.value().is_empty() {
+                schedule.emit(Some(local_to_utc(&input.value())));
+            }
+        })
+    };
+    let cancel_schedule = Callback::from(move |_| schedule.emit(None));
+
+    let save_alt = {
+        let event = event.clone();
+        let errors = errors.clone();
+        let notify = notify.clone();
+        let alt_input = alt_input.clone();
+        let url = format!("/api/events/{}", event_data.id);
+        let client = client.clone();
+        Callback::from(move |e: yew::SubmitEvent| {
+            e.prevent_default();
+            let Some(input) = alt_input.cast::<web_sys::HtmlInputElement>() else {
+                return;
+            };
+            let alt = input.value().trim().to_string();
+            // An empty description clears it.
+            let body = serde_json::json!({ "image_alt": (!alt.is_empty()).then_some(alt) });
+            let event = event.clone();
+            let errors = errors.clone();
+            let notify = notify.clone();
+            let url = url.clone();
+            let client = client.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match client.send_json::<Event>(Request::patch(&url), &body).await {
+                    Ok(event_data) => {
+                        notify.success("Image description saved");
+                        event.set(Some(event_data));
+                    }
+                    Err(error) => errors.report(error),
+                }
+            });
+        })
+    };
+
+    // Saves a merge patch of display settings.
+    let patch = {
+        let event = event.clone();
+        let errors = errors.clone();
+        let url = format!("/api/events/{}", event_data.id);
+        let client = client.clone();
+        Callback::from(move |body: serde_json::Value| {
+            let event = event.clone();
+            let errors = errors.clone();
+            let url = url.clone();
+            let client = client.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match client.send_json::<Event>(Request::patch(&url), &body).await {
+                    Ok(event_data) => event.set(Some(event_data)),
+                    Err(error) => errors.report(error),
+                }
+            });
+        })
+    };
+    let set_importance = patch.reform(|e: yew::Event| {
+        let select: web_sys::HtmlSelectElement = e.target_unchecked_into();
+        let importance = select.value().parse::<i16>().unwrap_or(3);
+        serde_json::json!({ "importance": importance })
+    });
+    let set_color = patch.reform(|e: yew::Event| {
+        let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+        serde_json::json!({ "color": input.value() })
+    });
+    let reset_color = patch.reform(|_| serde_json::json!({ "color": null }));
+    let save_custom_fields = patch.reform(|values: serde_json::Value| serde_json::json!({ "custom_fields": values }));
+    // The empty option clears the icon.
+    let set_icon = patch.reform(|e: yew::Event| {
+        let select: web_sys::HtmlSelectElement = e.target_unchecked_into();
+        let icon = select.value();
+        serde_json::json!({ "icon": (!icon.is_empty()).then_some(icon) })
+    });
+    let color = appearance::event_color(event_data.color.as_deref(), event_data.category.as_deref()).to_string();
+
+    let save_people = {
+        let people = people.clone();
+        let errors = errors.clone();
+        let url = format!("/api/events/{}/people", event_data.id);
+        let client = client.clone();
+        Callback::from(move |ids: Vec<String>| {
+            let people = people.clone();
+            let errors = errors.clone();
+            let url = url.clone();
+            let client = client.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match client.send_json::<Vec<Person>>(Request::put(&url), &ids).await {
+                    Ok(people_data) => people.set(people_data),
+                    Err(error) => errors.report(error),
+                }
+            });
+        })
+    };
+    let person_ids: Vec<String> = people.iter().map(|person| person.id.clone()).collect();
+    let remove_person = |id: &str| {
+        let ids: Vec<String> = person_ids.iter().filter(|other| *other != id).cloned().collect();
+        save_people.reform(move |_| ids.clone())
+    };
+    // Links a known person by name, adding them first if nobody has that name.
+    let add_person = {
+        let known_people = known_people.clone();
+        let person_input = person_input.clone();
+        let errors = errors.clone();
+        let person_ids = person_ids.clone();
+        let save_people = save_people.clone();
+        let client = client.clone();
+        Callback::from(move |e: yew::SubmitEvent| {
+            e.prevent_default();
+            let Some(input) = person_input.cast::<web_sys::HtmlInputElement>() else {
+                return;
+            };
+            let name = input.value().trim().to_string();
+            if name.is_empty() {
+                return;
+            }
+            input.set_value("");
+            let known = known_people.iter().find(|person| person.name.eq_ignore_ascii_case(&name)).cloned();
+            let known_people = known_people.clone();
+            let errors = errors.clone();
+            let mut ids = person_ids.clone();
+            let save_people = save_people.clone();
+            let client = client.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                let person = match known {
+                    Some(person) => person,
+                    None => {
+                        let body = serde_json::json!({ "name": name });
+                        match client.send_json::<Person>(Request::post("/api/people"), &body).await {
+                            Ok(person) => {
+                                let mut all = (*known_people).clone();
+                                all.push(person.clone());
+                                known_people.set(all);
+                                person
+                            }
+                            Err(error) => {
+                                errors.report(error);
+                                return;
+                            }
+                        }
+                    }
+                };
+                if !ids.contains(&person.id) {
+                    ids.push(person.id);
+                    save_people.emit(ids);
+                }
+            });
+        })
+    };
+
+    // Detaching can be undone from the toast by citing the source again.
+    let detach_source = |source_id: &str| {
+        let sources = sources.clone();
+        let errors = errors.clone();
+        let notify = notify.clone();
+        let url = format!("/api/events/{}/sources/{}", event_data.id, source_id);
+        let client = client.clone();
+        Callback::from(move |_| {
+            let sources = sources.clone();
+            let errors = errors.clone();
+            let notify = notify.clone();
+            let url = url.clone();
+            let client = client.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match client.send::<Vec<Source>>(Request::delete(&url)).await {
+                    Ok(sources_data) => {
+                        sources.set(sources_data);
+                        let undo = Callback::from(move |_| {
+                            let sources = sources.clone();
+                            let errors = errors.clone();
+                            let url = url.clone();
+                            let client = client.clone();
+                            wasm_bindgen_futures::spawn_local(async move {
+                                match client.send::<Vec<Source>>(Request::put(&url)).await {
+                                    Ok(sources_data) => sources.set(sources_data),
+                                    Err(error) => errors.report(error),
+                                }
+                            });
+                        });
+                        notify.notify(Notification::info("Source removed").with_action("Undo", undo));
+                    }
+                    Err(error) => errors.report(error),
+                }
+            });
+        })
+    };
+    let cite_source = {
+        let sources = sources.clone();
+        let errors = errors.clone();
+        let notify = notify.clone();
+        let source_form = source_form.clone();
+        let url = format!("/api/events/{}/sources", event_data.id);
+        let client = client.clone();
+        Callback::from(move |e: yew::SubmitEvent| {
+            e.prevent_default();
+            let Some(form) = source_form.cast::<web_sys::HtmlFormElement>() else {
+                return;
+            };
+            let field = |name: &str| {
+                form.query_selector(&format!("[name={}]", name))
+                    .ok()
+                    .flatten()
+                    .and_then(|element| element.dyn_into::<web_sys::HtmlInputElement>().ok())
+                    .map(|input| input.value().trim().to_string())
+                    .filter(|value| !value.is_empty())
+            };
+            let Some(title) = field("title") else {
+                return;
+            };
+            let body = serde_json::json!({
+                "title": title,
+                "url": field("url"),
+                "author": field("author"),
+                "citation": field("citation"),
+            });
+            form.reset();
+            let sources = sources.clone();
+            let errors = errors.clone();
+            let notify = notify.clone();
+            let url = url.clone();
+            let client = client.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match client.send_json::<Vec<Source>>(Request::post(&url), &body).await {
+                    Ok(sources_data) => {
+                        notify.success("Source cited");
+                        sources.set(sources_data);
+                    }
+                    Err(error) => errors.report(error),
+                }
+            });
+        })
+    };
+
     html! {
         <div class="min-h-screen bg-base-200">
             <header class="bg-base-100 shadow">
                 <div class="container mx-auto px-4 py-6">
-                    <h1 class="text-3xl font-bold">Event Details</h1>
+                    <Breadcrumbs
+                        {route}
+                        timeline={timeline.as_ref().map(|timeline| timeline.title.clone())}
+                        event={event_data.title.clone()}
+                    />
+                    <h1 class="text-3xl font-bold">{"Event Details"}</h1>
                 </div>
             </header>
             <main class="container mx-auto px-4 py-8">
+                if let Some(timeline) = &*timeline {
+                    <ArchivedBanner timeline={timeline.clone()} />
+                }
+                if may_edit {
+                    <EditLock event_id={event_data.id.clone()} on_change={on_lock} />
+                }
                 <div class="card bg-base-100 shadow-xl">
                     <div class="card-body">
-                        <h2 class="card-title text-2xl">{&event_data.title}</h2>
+                        <div class="flex items-center justify-between gap-4">
+                            <h2 class="card-title text-2xl">
+                                {event_icon(event_data)}
+                                {&event_data.title}
+                                if can_edit {
+                                    <span class={format!("badge {}", status_badge(&event_data.status))}>
+                                        {&event_data.status}
+                                    </span>
+                                }
+                            </h2>
+                            <div class="flex items-center gap-2">
+                                if published {
+                                    <LikeButton event_id={event_data.id.clone()} />
+                                    if !can_edit {
+                                        <ReportEvent event_id={event_data.id.clone()} />
+                                    }
+                                }
+                                if can_edit {
+                                    <button class="btn btn-outline btn-sm" onclick={toggle_published}>
+                                        {if published { "Unpublish" } else { "Publish" }}
+                                    </button>
+                                }
+                            </div>
+                        </div>
+                        if can_edit && event_data.status == "draft" {
+                            <div class="flex items-center gap-2">
+                                if let Some(publish_at) = &event_data.publish_at {
+                                    <span>{format!("Publishes automatically at {} UTC", publish_at.replace('T', " "))}</span>
+                                    <button class="btn btn-ghost btn-xs" onclick={cancel_schedule}>{"Cancel"}</button>
+                                } else {
+                                    <label class="label-text">{"Publish at"}</label>
+                                    <input type="datetime-local" class="input input-bordered input-sm" onchange={on_schedule} />
+                                }
+                            </div>
+                        }
                         <p>{&event_data.description.as_ref().unwrap_or(&"No description".to_string())}</p>
+                        <Reactions url={format!("/api/events/{}/reactions", event_data.id)} />
                         <div class="mt-4">
-                            <p><strong>Start Date:</strong> {&event_data.start_date}</p>
+                            <p>
+                                <strong>{"Start Date:"}</strong> {&event_data.start_date}
+                                if let Some(circa) = time_scale::circa(
+                                    event_data.start_date_min.as_deref(),
+                                    event_data.start_date_max.as_deref(),
+                                ) {
+                                    <span class="opacity-70">{format!(" ({})", circa)}</span>
+                                }
+                            </p>
                             {if let Some(end_date) = &event_data.end_date {
-                                html! { <p><strong>End Date:</strong> {end_date}</p> }
+                                html! { <p><strong>{"End Date:"}</strong> {end_date}</p> }
                             } else {
                                 html! {}
                             }}
                             {if let Some(location) = &event_data.location {
-                                html! { <p><strong>Location:</strong> {location}</p> }
+                                html! { <p><strong>{"Location:"}</strong> {location}</p> }
                             } else {
                                 html! {}
                             }}
                             {if let Some(category) = &event_data.category {
-                                html! { <p><strong>Category:</strong> {category}</p> }
+                                html! { <p><strong>{"Category:"}</strong> {category}</p> }
                             } else {
                                 html! {}
                             }}
+                            if let Some(timeline_id) = &event_data.timeline_id {
+                                <CustomFieldValues
+                                    timeline_id={timeline_id.clone()}
+                                    values={event_data.custom_fields.clone()}
+                                    {can_edit}
+                                    on_save={save_custom_fields}
+                                />
+                            }
+                            if !people.is_empty() || can_edit {
+                                <div class="flex flex-wrap items-center gap-2 mt-2">
+                                    <strong>{"People:"}</strong>
+                                    {people.iter().map(|person| html! {
+                                        <span key={person.id.clone()} class="badge badge-outline gap-1">
+                                            <a href={format!("/people/{}", person.id)} class="link link-hover">{&person.name}</a>
+                                            if can_edit {
+                                                <button
+                                                    class="opacity-60 hover:opacity-100"
+                                                    aria-label={format!("Remove {}", person.name)}
+                                                    onclick={remove_person(&person.id)}
+                                                >
+                                                    {"✕"}
+                                                </button>
+                                            }
+                                        </span>
+                                    }).collect::<Html>()}
+                                    if can_edit {
+                                        <form class="flex gap-1" onsubmit={add_person}>
+                                            <input
+                                                ref={person_input}
+                                                class="input input-bordered input-xs"
+                                                list="known-people"
+                                                aria-label="Add a person"
+                                                placeholder="Add a person"
+                                            />
+                                            <datalist id="known-people">
+                                                {known_people.iter().map(|person| html! {
+                                                    <option value={person.name.clone()} />
+                                                }).collect::<Html>()}
+                                            </datalist>
+                                            <button class="btn btn-xs" type="submit">{"Add"}</button>
+                                        </form>
+                                    }
+                                </div>
+                            }
+                            if can_edit {
+                                <label class="flex items-center gap-2 mt-2">
+                                    <strong>{"Importance:"}</strong>
+                                    <select class="select select-bordered select-sm" onchange={set_importance}>
+                                        {IMPORTANCE_LEVELS.iter().map(|(level, name)| html! {
+                                            <option value={level.to_string()} selected={*level == event_data.importance}>
+                                                {format!("{} – {}", level, name)}
+                                            </option>
+                                        }).collect::<Html>()}
+                                    </select>
+                                </label>
+                                <p class="text-sm opacity-70">{"Zoomed-out timelines show only the more important events."}</p>
+                                <div class="flex flex-wrap items-center gap-2 mt-2">
+                                    <label class="flex items-center gap-2">
+                                        <strong>{"Colour:"}</strong>
+                                        <input type="color" class="w-8 h-8 cursor-pointer" value={color} onchange={set_color} />
+                                    </label>
+                                    if event_data.color.is_some() {
+                                        <button class="btn btn-ghost btn-xs" onclick={reset_color}>{"Use category colour"}</button>
+                                    }
+                                    <label class="flex items-center gap-2 ml-4">
+                                        <strong>{"Icon:"}</strong>
+                                        <select class="select select-bordered select-sm" onchange={set_icon}>
+                                            <option value="" selected={event_data.icon.is_none()}>{"None"}</option>
+                                            {appearance::ICONS.iter().map(|(name, glyph, label)| html! {
+                                                <option value={*name} selected={event_data.icon.as_deref() == Some(*name)}>
+                                                    {format!("{} {}", glyph, label)}
+                                                </option>
+                                            }).collect::<Html>()}
+                                        </select>
+                                    </label>
+                                </div>
+                            }
                         </div>
-                        {if let Some(image_url) = &event_data.image_url {
-                            html! { <img src={image_url.clone()} alt={&event_data.title} class="mt-4 rounded-lg" /> }
-                        } else {
-                            html! {}
-                        }}
+                        <Gallery
+                            event_id={event_data.id.clone()}
+                            {can_edit}
+                            cover={event_data.image_url.clone()}
+                            fallback={html! {
+                                <>
+                                    if let Some(image_url) = &event_data.image_url {
+                                        <img
+                                            src={api::image_src(image_url)}
+                                            alt={event_data.image_alt.clone().unwrap_or_default()}
+                                            class="mt-4 rounded-lg"
+                                        />
+                                        if can_edit {
+                                            <form class="flex gap-2 mt-2" onsubmit={save_alt}>
+                                                <input
+                                                    ref={alt_input}
+                                                    class="input input-bordered input-sm flex-1"
+                                                    aria-label="Image description"
+                                                    placeholder="Describe the image for people who can't see it"
+                                                    value={event_data.image_alt.clone().unwrap_or_default()}
+                                                />
+                                                <button class="btn btn-sm" type="submit">{"Save description"}</button>
+                                            </form>
+                                            if event_data.image_alt.is_none() {
+                                                <p class="text-sm text-warning">{"Without a description, screen readers skip this image."}</p>
+                                            }
+                                        }
+                                    }
+                                </>
+                            }}
+                        />
+                        <EventEmbed event_id={event_data.id.clone()} {can_edit} />
+                        if !sources.is_empty() || can_edit {
+                            <section class="mt-6 border-t border-base-300 pt-4" aria-labelledby="sources-heading">
+                                <h3 id="sources-heading" class="font-semibold mb-2">{"Sources"}</h3>
+                                <ol class="list-decimal list-inside text-sm space-y-1">
+                                    {sources.iter().map(|source| html! {
+                                        <li key={source.id.clone()}>
+                                            if let Some(author) = &source.author {
+                                                {format!("{}, ", author)}
+                                            }
+                                            if let Some(url) = &source.url {
+                                                <a href={url.clone()} class="link" target="_blank" rel="noopener noreferrer">
+                                                    <cite>{&source.title}</cite>{" ↗"}
+                                                </a>
+                                            } else {
+                                                <cite>{&source.title}</cite>
+                                            }
+                                            if let Some(citation) = &source.citation {
+                                                <span class="opacity-70">{format!(". {}", citation)}</span>
+                                            }
+                                            if can_edit {
+                                                <button
+                                                    class="btn btn-ghost btn-xs"
+                                                    aria-label={format!("Remove source {}", source.title)}
+                                                    onclick={detach_source(&source.id)}
+                                                >
+                                                    {"✕"}
+                                                </button>
+                                            }
+                                        </li>
+                                    }).collect::<Html>()}
+                                </ol>
+                                if can_edit {
+                                    <form ref={source_form} class="grid grid-cols-1 md:grid-cols-2 gap-2 mt-3" onsubmit={cite_source}>
+                                        <input name="title" class="input input-bordered input-sm" placeholder="Title" aria-label="Source title" required=true />
+                                        <input name="author" class="input input-bordered input-sm" placeholder="Author" aria-label="Source author" />
+                                        <input name="url" type="url" class="input input-bordered input-sm" placeholder="https://…" aria-label="Source link" />
+                                        <input name="citation" class="input input-bordered input-sm" placeholder="Publisher, year, pages" aria-label="Citation" />
+                                        <button class="btn btn-sm md:col-span-2" type="submit">{"Add source"}</button>
+                                    </form>
+                                }
+                            </section>
+                        }
                     </div>
                 </div>
+                <Comments event_id={event_data.id.clone()} />
+                if !archived && !duplicates.is_empty() {
+                    <div class="alert alert-warning mt-6 flex-col items-start">
+                        <p class="font-bold">{"Possible duplicates"}</p>
+                        <ul>
+                            {duplicates.iter().map(|duplicate| html! {
+                                <li class="flex gap-4 items-center">
+                                    <span>{format!("{} ({})", duplicate.title, duplicate.start_date)}</span>
+                                    <a href={format!("/events/{}/merge/{}", event_data.id, duplicate.id)} class="btn btn-xs">{"Merge"}</a>
+                                </li>
+                            }).collect::<Html>()}
+                        </ul>
+                    </div>
+                }
             </main>
         </div>
     }
 }
 
+/// Importance values and what they mean, least first.
+const IMPORTANCE_LEVELS: [(i16, &str); 5] = [(1, "Minor"), (2, "Low"), (3, "Normal"), (4, "High"), (5, "Major")];
+
 #[derive(Properties, PartialEq)]
 struct EventDetailProps {
     id: String,
+    /// The timeline the page was reached through, for the breadcrumbs.
+    #[prop_or_default]
+    timeline_id: Option<String>,
 }
 
-#[function_component(About)]
-fn about() -> Html {
+#[derive(Properties, PartialEq)]
+struct PersonDetailProps {
+    id: String,
+}
+
+/// A person's details and their personal timeline: the public events they
+/// are linked to, earliest first.
+#[function_component(PersonDetail)]
+fn person_detail(props: &PersonDetailProps) -> Html {
+    let client = use_api();
+    let person = use_state(|| Option::<Person>::None);
+    let events = use_state(Vec::<Event>::new);
+    let error = use_state(|| Option::<FetchError>::None);
+    let (attempt, retry) = use_retry();
+
+    {
+        let person = person.clone();
+        let events = events.clone();
+        let error = error.clone();
+        yew::use_effect_with((props.id.clone(), attempt), move |(id, _): &(String, u32)| {
+            let id = id.clone();
+            error.set(None);
+            wasm_bindgen_futures::spawn_local(async move {
+                match client.get::<Person>(&format!("/api/people/{}", id)).await {
+                    Ok(person_data) => person.set(Some(person_data)),
+                    Err(fetch_error) => {
+                        error.set(Some(fetch_error));
+                        return;
+                    }
+                }
+                match client.get::<Vec<Event>>(&format!("/api/people/{}/events", id)).await {
+                    Ok(events_data) => events.set(events_data),
+                    Err(fetch_error) => error.set(Some(fetch_error)),
+                }
+            });
+        });
+    }
+
+    let route = Route::PersonDetail { id: props.id.clone() };
+    if let Some(fetch_error) = &*error {
+        return page_error(fetch_error, retry);
+    }
+    let Some(person_data) = &*person else {
+        return html! {
+            <div class="min-h-screen bg-base-200">
+                <header class="bg-base-100 shadow">
+                    <div class="container mx-auto px-4 py-6">
+                        <Breadcrumbs {route} />
+                        <h1 class="text-3xl font-bold">{"Person"}</h1>
+                    </div>
+                </header>
+                <main class="container mx-auto px-4 py-8">
+                    <Skeleton shape={Shape::Detail} />
+                </main>
+            </div>
+        };
+    };
+    let lifespan = match (&person_data.birth_date, &person_data.death_date) {
+        (None, None) => None,
+        (birth, death) => Some(format!(
+            "{} – {}",
+            birth.as_deref().unwrap_or("?"),
+            death.as_deref().unwrap_or_default()
+        )),
+    };
+
     html! {
         <div class="min-h-screen bg-base-200">
             <header class="bg-base-100 shadow">
                 <div class="container mx-auto px-4 py-6">
-                    <h1 class="text-3xl font-bold">About Timeline Explorer</h1>
+                    <Breadcrumbs {route} person={person_data.name.clone()} />
+                    <h1 class="text-3xl font-bold">{&person_data.name}</h1>
+                    if let Some(lifespan) = lifespan {
+                        <p class="opacity-70">{lifespan}</p>
+                    }
                 </div>
             </header>
-            <main class="container mx-auto px-4 py-8">
-                <div class="prose max-w-none">
-                    <p>This timeline application allows you to explore historical events in an interactive way.</p>
-                    <p>Features include:</p>
-                    <ul>
-                        <li>Zoomable and pannable timeline</li>
-                        <li>Event details with images</li>
-                        <li>Search and filtering capabilities</li>
-                        <li>Responsive design</li>
-                    </ul>
+            <main class="container mx-auto px-4 py-8 grid grid-cols-1 md:grid-cols-3 gap-6">
+                <div class="card bg-base-100 shadow-xl h-fit">
+                    if let Some(image_url) = &person_data.image_url {
+                        <figure><img src={api::image_src(image_url)} alt={person_data.name.clone()} class="w-full object-cover" /></figure>
+                    }
+                    <div class="card-body">
+                        <p>{person_data.bio.clone().unwrap_or_else(|| "No biography yet".to_string())}</p>
+                    </div>
                 </div>
+                <section class="md:col-span-2">
+                    <h2 class="text-2xl font-bold mb-4">{"Timeline"}</h2>
+                    if events.is_empty() {
+                        <p class="opacity-70">{"No events yet"}</p>
+                    } else {
+                        <ol class="border-l-2 border-primary ml-2">
+                            {events.iter().map(|event| html! {
+                                <li key={event.id.clone()} class="relative pl-6 pb-6">
+                                    <span
+                                        class="absolute -left-[7px] top-1.5 w-3 h-3 rounded-full"
+                                        style={format!(
+                                            "background: {}",
+                                            appearance::event_color(event.color.as_deref(), event.category.as_deref())
+                                        )}
+                                    ></span>
+                                    <p class="text-sm opacity-70">{event_day(&event.start_date)}</p>
+                                    <a href={format!("/events/{}", event.id)} class="font-semibold link link-hover">
+                                        {event_icon(event)}{&event.title}
+                                    </a>
+                                </li>
+                            }).collect::<Html>()}
+                        </ol>
+                    }
+                </section>
             </main>
         </div>
     }
 }
+
+/// Converts a `datetime-local` value (browser time zone) to a UTC timestamp
+/// the API accepts.
+fn local_to_utc(value: &str) -> String {
+    let date = js_sys::Date::new(&JsValue::from_str(value));
+    String::from(date.to_iso_string()).trim_end_matches('Z').to_string()
+}
+
+/// A query string parameter of the current page.
+fn query_param(name: &str) -> Option<String> {
+    let search = gloo_utils::window().location().search().ok()?;
+    web_sys::UrlSearchParams::new_with_str(&search).ok()?.get(name)
+}
+
+/// `toLocaleDateString` options for a month and day, e.g. "October 16".
+fn month_day_format() -> js_sys::Object {
+    let options = js_sys::Object::new();
+    js_sys::Reflect::set(&options, &"month".into(), &"long".into()).ok();
+    js_sys::Reflect::set(&options, &"day".into(), &"numeric".into()).ok();
+    options
+}
+
+/// The date part of an API timestamp.
+fn event_day(start_date: &str) -> &str {
+    start_date.split('T').next().unwrap_or(start_date)
+}
+
+/// The event's timeline (or the timeline of all events), centered on its date.
+fn timeline_link(event: &Event) -> String {
+    let date = event_day(&event.start_date);
+    match &event.timeline_id {
+        Some(timeline_id) => format!("/timelines/{}?date={}", timeline_id, date),
+        None => format!("/timeline?date={}", date),
+    }
+}
+
+/// Card border in the event's colour, as its marker has on the timeline.
+fn card_accent(event: &Event) -> String {
+    let color = appearance::event_color(event.color.as_deref(), event.category.as_deref());
+    format!("border-top: 4px solid {}", color)
+}
+
+/// The event's icon before its title, if it has one.
+fn event_icon(event: &Event) -> Html {
+    match appearance::icon_glyph(event.icon.as_deref()) {
+        Some(glyph) => html! { <span aria-hidden="true">{glyph}</span> },
+        None => html! {},
+    }
+}
+
+fn status_badge(status: &str) -> &'static str {
+    match status {
+        "draft" => "badge-warning",
+        "archived" => "badge-ghost",
+        _ => "badge-success",
+    }
+}
+
+/// Reads one field of an event for the merge screen.
+type FieldGetter = fn(&Event) -> Option<String>;
+
+/// Fields offered in the merge screen, as (API name, label, getter).
+const MERGE_FIELDS: &[(&str, &str, FieldGetter)] = &[
+    ("title", "Title", |event| Some(event.title.clone())),
+    ("description", "Description", |event| event.description.clone()),
+    ("start_date", "Start date", |event| Some(event.start_date.clone())),
+    ("end_date", "End date", |event| event.end_date.clone()),
+    ("location", "Location", |event| event.location.clone()),
+    ("image_url", "Image URL", |event| event.image_url.clone()),
+    ("category", "Category", |event| event.category.clone()),
+];
+
+#[derive(Properties, PartialEq)]
+struct MergeEventsProps {
+    id: String,
+    other_id: String,
+}
+
+/// Side-by-side merge of `other_id` into `id`, choosing each field's winner.
+#[function_component(MergeEvents)]
+fn merge_events(props: &MergeEventsProps) -> Html {
+    let events = use_state(|| Option::<(Event, Event)>::None);
+    let choices = use_state(std::collections::HashMap::<&'static str, &'static str>::new);
+    let saving = use_state(|| false);
+    let confirming = use_state(|| Option::<Confirmation>::None);
+    let error = use_state(|| Option::<FetchError>::None);
+    let (attempt, retry) = use_retry();
+    let errors = use_error_reporter();
+    let client = use_api();
+
+    {
+        let events = events.clone();
+        let choices = choices.clone();
+        let error = error.clone();
+        let ids = (props.id.clone(), props.other_id.clone(), attempt);
+        let client = client.clone();
+        yew::use_effect_with(ids, move |(id, other_id, _): &(String, String, u32)| {
+            let (id, other_id) = (id.clone(), other_id.clone());
+            error.set(None);
+            let fetch_events = async move {
+                let fetched = match client.get::<Event>(&format!("/api/events/{}", id)).await {
+                    Ok(keep) => client.get::<Event>(&format!("/api/events/{}", other_id))
+                        .await
+                        .map(|other| (keep, other)),
+                    Err(fetch_error) => Err(fetch_error),
+                };
+                let (keep, other) = match fetched {
+                    Ok(pair) => pair,
+                    Err(fetch_error) => {
+                        error.set(Some(fetch_error));
+                        return;
+                    }
+                };
+
+                // Start from the server default: keep ours unless it's empty.
+                let defaults = MERGE_FIELDS
+                    .iter()
+                    .map(|(field, _, value)| {
+                        let side = if value(&keep).is_none() && value(&other).is_some() { "other" } else { "keep" };
+                        (*field, side)
+                    })
+                    .collect();
+                choices.set(defaults);
+                events.set(Some((keep, other)));
+            };
+            wasm_bindgen_futures::spawn_local(fetch_events);
+        });
+    }
+
+    let Some((keep, other)) = (*events).clone() else {
+        return match &*error {
+            Some(fetch_error) => page_error(fetch_error, retry),
+            None => html! { <div class="text-center">{"Loading..."}</div> },
+        };
+    };
+
+    let merge = {
+        let choices = choices.clone();
+        let saving = saving.clone();
+        let (id, other_id) = (props.id.clone(), props.other_id.clone());
+        let client = client.clone();
+        Callback::from(move |_| {
+            let body = (*choices).clone();
+            let (id, other_id) = (id.clone(), other_id.clone());
+            let saving = saving.clone();
+            let errors = errors.clone();
+            saving.set(true);
+            let client = client.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                let url = format!("/api/events/{}/merge/{}", id, other_id);
+                match client.send_json::<serde::de::IgnoredAny>(Request::post(&url), &body).await {
+                    Ok(_) => {
+                        gloo_utils::window()
+                            .location()
+                            .set_href(&format!("/events/{}", id))
+                            .ok();
+                    }
+                    Err(error) => {
+                        saving.set(false);
+                        errors.report(error);
+                    }
+                }
+            });
+        })
+    };
+    let onsubmit = {
+        let confirmation = Confirmation::new(
+            "Merge these events?",
+            format!("\"{}\" will be deleted and merged into \"{}\". This can't be undone.", other.title, keep.title),
+            "Merge",
+            merge,
+        );
+        let confirming = confirming.clone();
+        Callback::from(move |_| confirming.set(Some(confirmation.clone())))
+    };
+    let close_confirmation = {
+        let confirming = confirming.clone();
+        Callback::from(move |_| confirming.set(None))
+    };
+
+    let cell = |field: &'static str, side: &'static str, value: Option<String>| {
+        let checked = choices.get(field) == Some(&side);
+        let onchange = {
+            let choices = choices.clone();
+            Callback::from(move |_| {
+                let mut next = (*choices).clone();
+                next.insert(field, side);
+                choices.set(next);
+            })
+        };
+        html! {
+            <td class={if checked { "bg-primary/10" } else { "" }}>
+                <label class="flex gap-2 items-start cursor-pointer">
+                    <input type="radio" class="radio radio-sm" name={field} {checked} {onchange} />
+                    <span>{value.unwrap_or_else(|| "—".to_string())}</span>
+                </label>
+            </td>
+        }
+    };
+
+    html! {
+        <div class="min-h-screen bg-base-200">
+            <header class="bg-base-100 shadow">
+                <div class="container mx-auto px-4 py-6">
+                    <h1 class="text-3xl font-bold">{"Merge Events"}</h1>
+                </div>
+            </header>
+            <main class="container mx-auto px-4 py-8">
+                <p class="mb-4">{"Pick the value to keep for each field. Tags from both events are kept; the right-hand event is deleted."}</p>
+                <table class="table bg-base-100">
+                    <thead>
+                        <tr><th></th><th>{"Keeps"}</th><th>{"Merged away"}</th></tr>
+                    </thead>
+                    <tbody>
+                        {MERGE_FIELDS.iter().map(|(field, label, value)| {
+                            // Each row is one radio group, named after the field.
+                            html! {
+                                <tr>
+                                    <th>{*label}</th>
+                                    {cell(field, "keep", value(&keep))}
+                                    {cell(field, "other", value(&other))}
+                                </tr>
+                            }
+                        }).collect::<Html>()}
+                    </tbody>
+                </table>
+                <div class="mt-6 flex gap-4">
+                    <button class="btn btn-primary" disabled={*saving} onclick={onsubmit}>{"Merge"}</button>
+                    <a href={format!("/events/{}", props.id)} class="btn btn-ghost">{"Cancel"}</a>
+                </div>
+                <ConfirmDialog confirmation={(*confirming).clone()} on_close={close_confirmation} />
+            </main>
+        </div>
+    }
+}
+
+#[function_component(About)]
+fn about() -> Html {
+    html! {
+        <div class="min-h-screen bg-base-200">
+            <header class="bg-base-100 shadow">
+                <div class="container mx-auto px-4 py-6">
+                    <h1 class="text-3xl font-bold">{"About Timeline Explorer"}</h1>
+                </div>
+            </header>
+            <main class="container mx-auto px-4 py-8">
+                <div class="prose max-w-none">
+                    <p>{"This timeline application allows you to explore historical events in an interactive way."}</p>
+                    <p>{"Features include:"}</p>
+                    <ul>
+                        <li>{"Zoomable and pannable timeline"}</li>
+                        <li>{"Event details with images"}</li>
+                        <li>{"Search and filtering capabilities"}</li>
+                        <li>{"Responsive design"}</li>
+                    </ul>
+                </div>
+            </main>
+        </div>
+    }
+}
+
+#[function_component(AdminDashboard)]
+fn admin_dashboard() -> Html {
+    let client = use_api();
+    let info = use_state(|| Option::<serde_json::Value>::None);
+    let error = use_state(|| Option::<FetchError>::None);
+    let (attempt, retry) = use_retry();
+
+    {
+        let info = info.clone();
+        let error = error.clone();
+        yew::use_effect_with(attempt, move |_| {
+            error.set(None);
+            let fetch_info = async move {
+                match client.get::<serde_json::Value>("/api/admin/systeminfo").await {
+                    Ok(info_data) => info.set(Some(info_data)),
+                    Err(fetch_error) => error.set(Some(fetch_error)),
+                }
+            };
+            wasm_bindgen_futures::spawn_local(fetch_info);
+        });
+    }
+
+    let sections = match &*info {
+        Some(serde_json::Value::Object(sections)) => sections
+            .iter()
+            .map(|(name, value)| html! {
+                <div class="card bg-base-100 shadow">
+                    <div class="card-body">
+                        <h3 class="card-title text-base capitalize">{name}</h3>
+                        <table class="table table-sm">
+                            <tbody>{info_rows("", value)}</tbody>
+                        </table>
+                    </div>
+                </div>
+            })
+            .collect::<Html>(),
+        _ => match &*error {
+            Some(fetch_error) if matches!(fetch_error.status(), Some(401 | 403)) => {
+                html! { <div class="alert alert-error">{"System info is only available to admins"}</div> }
+            }
+            Some(fetch_error) => html! { <LoadError error={fetch_error.clone()} onretry={retry} /> },
+            None => html! { <div class="text-center">{"Loading..."}</div> },
+        },
+    };
+
+    html! {
+        <div class="min-h-screen bg-base-200">
+            <header class="bg-base-100 shadow">
+                <div class="container mx-auto px-4 py-6 flex items-center justify-between">
+                    <h1 class="text-3xl font-bold">{"Admin"}</h1>
+                    <div class="flex gap-2">
+                        <a href="/admin/performance" class="btn btn-ghost btn-sm">{"Performance"}</a>
+                        <a href="/admin/email-templates" class="btn btn-ghost btn-sm">{"Email templates"}</a>
+                        <a href="/admin/moderation" class="btn btn-ghost btn-sm">{"Moderation"}</a>
+                        <a href="/admin/users" class="btn btn-ghost btn-sm">{"Users"}</a>
+                    </div>
+                </div>
+            </header>
+            <main class="container mx-auto px-4 py-8">
+                <div class="grid grid-cols-1 lg:grid-cols-2 gap-6">{sections}</div>
+            </main>
+        </div>
+    }
+}
+
+/// Flattens a system info section into `key: value` rows; nested objects and
+/// array items get dotted keys.
+fn info_rows(prefix: &str, value: &serde_json::Value) -> Html {
+    let key = |name: &str| if prefix.is_empty() { name.to_string() } else { format!("{}.{}", prefix, name) };
+    match value {
+        serde_json::Value::Object(fields) => fields.iter().map(|(name, value)| info_rows(&key(name), value)).collect(),
+        serde_json::Value::Array(items) if !items.is_empty() => items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| info_rows(&key(&i.to_string()), item))
+            .collect(),
+        other => {
+            let text = match other {
+                serde_json::Value::String(text) => text.clone(),
+                serde_json::Value::Null => "—".to_string(),
+                other => other.to_string(),
+            };
+            html! {
+                <tr>
+                    <td class="font-mono opacity-70">{prefix}</td>
+                    <td class="font-mono break-all">{text}</td>
+                </tr>
+            }
+        }
+    }
+}
+
+#[function_component(AdminPerformance)]
+fn admin_performance() -> Html {
+    let client = use_api();
+    let trends = use_state(Vec::<RumTrend>::new);
+    let loading = use_state(|| true);
+    let error = use_state(|| Option::<FetchError>::None);
+    let (attempt, retry) = use_retry();
+
+    {
+        let trends = trends.clone();
+        let loading = loading.clone();
+        let error = error.clone();
+        yew::use_effect_with(attempt, move |_| {
+            loading.set(true);
+            error.set(None);
+            let fetch_trends = async move {
+                match client.get::<Vec<RumTrend>>("/api/rum/trends?days=30").await {
+                    Ok(trends_data) => trends.set(trends_data),
+                    Err(fetch_error) => error.set(Some(fetch_error)),
+                }
+                loading.set(false);
+            };
+            wasm_bindgen_futures::spawn_local(fetch_trends);
+        });
+    }
+
+    if *loading {
+        return html! { <div class="text-center">{"Loading..."}</div> };
+    }
+    if let Some(fetch_error) = &*error {
+        return page_error(fetch_error, retry);
+    }
+
+    let charts = [
+        ("ttfb", "Time to first byte"),
+        ("dom_content_loaded", "DOM content loaded"),
+        ("wasm_init", "Wasm initialised"),
+        ("timeline_first_render", "Timeline first render"),
+        ("interaction_latency", "Worst interaction latency"),
+    ];
+
+    html! {
+        <div class="min-h-screen bg-base-200">
+            <header class="bg-base-100 shadow">
+                <div class="container mx-auto px-4 py-6">
+                    <h1 class="text-3xl font-bold">{"Frontend Performance"}</h1>
+                </div>
+            </header>
+            <main class="container mx-auto px-4 py-8">
+                <div class="grid grid-cols-1 lg:grid-cols-2 gap-6">
+                    {charts.iter().map(|(metric, title)| {
+                        let points = trends
+                            .iter()
+                            .filter(|trend| trend.metric == *metric)
+                            .map(|trend| TrendPoint {
+                                label: trend.day.chars().take(10).collect(),
+                                p50: trend.p50,
+                                p95: trend.p95,
+                            })
+                            .collect::<Vec<_>>();
+                        html! { <TrendChart title={title.to_string()} {points} /> }
+                    }).collect::<Html>()}
+                </div>
+            </main>
+        </div>
+    }
+}
+
+#[function_component(AdminEmailTemplates)]
+fn admin_email_templates() -> Html {
+    let client = use_api();
+    let kinds = use_state(Vec::<EmailTemplateKind>::new);
+    let selected = use_state(|| (String::new(), "en".to_string()));
+    let subject = use_state(String::new);
+    let body = use_state(String::new);
+    let new_locale = use_state(String::new);
+    let test_to = use_state(String::new);
+    let preview = use_state(|| Option::<RenderedEmail>::None);
+    let message = use_state(|| Option::<(bool, String)>::None);
+    let error = use_state(|| Option::<FetchError>::None);
+    let confirming = use_state(|| Option::<Confirmation>::None);
+
+    let reload = {
+        let kinds = kinds.clone();
+        let selected = selected.clone();
+        let error = error.clone();
+        let client = client.clone();
+        Callback::from(move |_: ()| {
+            let kinds = kinds.clone();
+            let selected = selected.clone();
+            let error = error.clone();
+            error.set(None);
+            let client = client.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match client.get::<Vec<EmailTemplateKind>>("/api/admin/email-templates").await {
+                    Ok(kinds_data) => {
+                        if selected.0.is_empty() {
+                            if let Some(first) = kinds_data.first() {
+                                selected.set((first.key.clone(), "en".to_string()));
+                            }
+                        }
+                        kinds.set(kinds_data);
+                    }
+                    Err(fetch_error) => error.set(Some(fetch_error)),
+                }
+            });
+        })
+    };
+
+    {
+        let reload = reload.clone();
+        yew::use_effect_with((), move |_| reload.emit(()));
+    }
+
+    // Load the selected variant into the editor; a new locale starts from
+    // the default-locale text.
+    {
+        let subject = subject.clone();
+        let body = body.clone();
+        let preview = preview.clone();
+        let kinds = (*kinds).clone();
+        yew::use_effect_with((*selected).clone(), move |(key, locale): &(String, String)| {
+            if let Some(variant) = stored_template(&kinds, key, locale) {
+                subject.set(variant.subject.clone());
+                body.set(variant.body.clone());
+            }
+            preview.set(None);
+        });
+    }
+
+    let dirty = stored_template(&kinds, &selected.0, &selected.1)
+        .is_some_and(|stored| stored.subject != *subject || stored.body != *body);
+    use_leave_warning(dirty);
+    // Runs an action that replaces the editor's text, asking first if
+    // there are unsaved edits.
+    let unless_dirty = {
+        let confirming = confirming.clone();
+        Callback::from(move |action: Callback<()>| {
+            if dirty {
+                confirming.set(Some(Confirmation::new(
+                    "Discard unsaved changes?",
+                    "Your edits to this template have not been saved.",
+                    "Discard",
+                    action,
+                )));
+            } else {
+                action.emit(());
+            }
+        })
+    };
+    let close_confirmation = {
+        let confirming = confirming.clone();
+        Callback::from(move |_| confirming.set(None))
+    };
+
+    let Some(kind) = kinds.iter().find(|kind| kind.key == selected.0).cloned() else {
+        return match &*error {
+            Some(fetch_error) => page_error(fetch_error, reload),
+            None => html! { <div class="text-center">{"Loading..."}</div> },
+        };
+    };
+    let (key, locale) = (*selected).clone();
+    let url = format!("/api/admin/email-templates/{}/{}", key, locale);
+
+    let oninput = |field: &yew::UseStateHandle<String>| {
+        let field = field.clone();
+        Callback::from(move |e: yew::InputEvent| {
+            let input: web_sys::HtmlInputElement = e.target_unchecked_into();
+            field.set(input.value());
+        })
+    };
+    let onbodyinput = {
+        let body = body.clone();
+        Callback::from(move |e: yew::InputEvent| {
+            let input: web_sys::HtmlTextAreaElement = e.target_unchecked_into();
+            body.set(input.value());
+        })
+    };
+
+    let select = |key: String, locale: String| {
+        let selected = selected.clone();
+        let message = message.clone();
+        let switch = Callback::from(move |_| {
+            message.set(None);
+            selected.set((key.clone(), locale.clone()));
+        });
+        unless_dirty.reform(move |_| switch.clone())
+    };
+
+    let add_locale = {
+        let selected = selected.clone();
+        let new_locale = new_locale.clone();
+        let key = key.clone();
+        let unless_dirty = unless_dirty.clone();
+        Callback::from(move |_| {
+            let locale = new_locale.trim().to_string();
+            if !locale.is_empty() {
+                let selected = selected.clone();
+                let new_locale = new_locale.clone();
+                let key = key.clone();
+                unless_dirty.emit(Callback::from(move |_| {
+                    selected.set((key.clone(), locale.clone()));
+                    new_locale.set(String::new());
+                }));
+            }
+        })
+    };
+
+    let save = {
+        let url = url.clone();
+        let subject = subject.clone();
+        let body = body.clone();
+        let message = message.clone();
+        let reload = reload.clone();
+        let client = client.clone();
+        Callback::from(move |_| {
+            let url = url.clone();
+            let payload = serde_json::json!({ "subject": *subject, "body": *body });
+            let message = message.clone();
+            let reload = reload.clone();
+            let client = client.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match client.send_json::<serde::de::IgnoredAny>(Request::put(&url), &payload).await {
+                    Ok(_) => {
+                        message.set(Some((true, "Template saved".to_string())));
+                        reload.emit(());
+                    }
+                    Err(error) => message.set(Some((false, error.to_string()))),
+                }
+            });
+        })
+    };
+
+    let remove = {
+        let url = url.clone();
+        let selected = selected.clone();
+        let message = message.clone();
+        let reload = reload.clone();
+        let client = client.clone();
+        let remove = Callback::from(move |_| {
+            let url = url.clone();
+            let selected = selected.clone();
+            let message = message.clone();
+            let reload = reload.clone();
+            let client = client.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match client.send::<serde::de::IgnoredAny>(Request::delete(&url)).await {
+                    Ok(_) => {
+                        selected.set((selected.0.clone(), "en".to_string()));
+                        reload.emit(());
+                    }
+                    Err(error) => message.set(Some((false, error.to_string()))),
+                }
+            });
+        });
+        let confirmation = Confirmation::new(
+            format!("Delete the {} version?", locale),
+            "Emails to readers using this locale will be sent in English.",
+            "Delete",
+            remove,
+        );
+        let confirming = confirming.clone();
+        Callback::from(move |_| confirming.set(Some(confirmation.clone())))
+    };
+
+    let render_preview = {
+        let url = url.clone();
+        let subject = subject.clone();
+        let body = body.clone();
+        let preview = preview.clone();
+        let message = message.clone();
+        let client = client.clone();
+        Callback::from(move |_| {
+            let url = format!("{}/preview", url);
+            let payload = serde_json::json!({ "subject": *subject, "body": *body });
+            let preview = preview.clone();
+            let message = message.clone();
+            let client = client.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match client.send_json::<RenderedEmail>(Request::post(&url), &payload).await {
+                    Ok(rendered) => preview.set(Some(rendered)),
+                    Err(error) => message.set(Some((false, error.to_string()))),
+                }
+            });
+        })
+    };
+
+    let send_test = {
+        let url = url.clone();
+        let test_to = test_to.clone();
+        let message = message.clone();
+        let client = client.clone();
+        Callback::from(move |_| {
+            let url = format!("{}/test", url);
+            let payload = serde_json::json!({ "to": *test_to });
+            let message = message.clone();
+            let client = client.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match client.send_json::<serde::de::IgnoredAny>(Request::post(&url), &payload).await {
+                    Ok(_) => message.set(Some((true, "Test email sent (saved version)".to_string()))),
+                    Err(error) => message.set(Some((false, error.to_string()))),
+                }
+            });
+        })
+    };
+
+    let saved_locales: Vec<String> = kind.variants.iter().map(|t| t.locale.clone()).collect();
+    let is_new = !saved_locales.contains(&locale);
+
+    html! {
+        <div class="min-h-screen bg-base-200">
+            <header class="bg-base-100 shadow">
+                <div class="container mx-auto px-4 py-6">
+                    <h1 class="text-3xl font-bold">{"Email Templates"}</h1>
+                </div>
+            </header>
+            <main class="container mx-auto px-4 py-8 grid grid-cols-1 lg:grid-cols-4 gap-6">
+                <ul class="menu bg-base-100 rounded-box">
+                    {kinds.iter().map(|k| html! {
+                        <li>
+                            <a class={if k.key == key { "active" } else { "" }}
+                                onclick={select(k.key.clone(), "en".to_string())}>{&k.key}</a>
+                        </li>
+                    }).collect::<Html>()}
+                </ul>
+                <div class="lg:col-span-3 card bg-base-100 shadow">
+                    <div class="card-body gap-4">
+                        <div class="flex flex-wrap items-center gap-2">
+                            <div class="tabs tabs-boxed">
+                                {saved_locales.iter().map(|l| html! {
+                                    <a class={if *l == locale { "tab tab-active" } else { "tab" }}
+                                        onclick={select(key.clone(), l.clone())}>{l}</a>
+                                }).collect::<Html>()}
+                                if is_new {
+                                    <a class="tab tab-active">{format!("{} (new)", locale)}</a>
+                                }
+                            </div>
+                            <input class="input input-bordered input-sm w-28" placeholder="pt-BR"
+                                value={(*new_locale).clone()} oninput={oninput(&new_locale)} />
+                            <button class="btn btn-sm" onclick={add_locale}>{"Add locale"}</button>
+                        </div>
+                        if let Some((ok, text)) = &*message {
+                            <div class={if *ok { "alert alert-success" } else { "alert alert-error" }}>{text}</div>
+                        }
+                        <p class="text-sm opacity-70">
+                            {"Placeholders: "}
+                            {kind.variables.iter().map(|v| format!("{{{{{}}}}}", v)).collect::<Vec<_>>().join(", ")}
+                        </p>
+                        <input class="input input-bordered" placeholder="Subject"
+                            value={(*subject).clone()} oninput={oninput(&subject)} />
+                        <textarea class="textarea textarea-bordered font-mono h-64"
+                            value={(*body).clone()} oninput={onbodyinput} />
+                        <div class="flex flex-wrap gap-2">
+                            <button class="btn btn-primary" onclick={save}>{"Save"}</button>
+                            <button class="btn" onclick={render_preview}>{"Preview"}</button>
+                            if locale != "en" && !is_new {
+                                <button class="btn btn-error btn-outline" onclick={remove}>{"Delete locale"}</button>
+                            }
+                            <div class="join ml-auto">
+                                <input class="input input-bordered join-item" placeholder="you@example.com"
+                                    value={(*test_to).clone()} oninput={oninput(&test_to)} />
+                                <button class="btn join-item" onclick={send_test}>{"Send test"}</button>
+                            </div>
+                        </div>
+                        if let Some(rendered) = &*preview {
+                            <div class="mockup-window border bg-base-300">
+                                <div class="bg-base-100 px-6 py-4">
+                                    <p class="font-bold">{&rendered.subject}</p>
+                                    <pre class="whitespace-pre-wrap mt-2">{&rendered.body}</pre>
+                                </div>
+                            </div>
+                        }
+                    </div>
+                </div>
+                <ConfirmDialog confirmation={(*confirming).clone()} on_close={close_confirmation} />
+            </main>
+        </div>
+    }
+}
+
+/// An event in the moderation queue, from `/api/admin/reports`.
+#[derive(Deserialize, Clone, PartialEq)]
+struct ReportedEvent {
+    event_id: String,
+    title: String,
+    timeline_id: Option<String>,
+    author: Option<String>,
+    reports: i64,
+    reasons: Vec<String>,
+    details: Vec<String>,
+    first_reported_at: String,
+}
+
+/// Admins' queue of reported events, with what can be done about each.
+#[function_component(AdminModeration)]
+fn admin_moderation() -> Html {
+    let reported = use_state(|| Option::<Vec<ReportedEvent>>::None);
+    let error = use_state(|| Option::<FetchError>::None);
+    let confirming = use_state(|| Option::<Confirmation>::None);
+    let (attempt, retry) = use_retry();
+    let errors = use_error_reporter();
+    let client = use_api();
+
+    {
+        let reported = reported.clone();
+        let error = error.clone();
+        let client = client.clone();
+        yew::use_effect_with(attempt, move |_| {
+            error.set(None);
+            wasm_bindgen_futures::spawn_local(async move {
+                match client.get::<Vec<ReportedEvent>>("/api/admin/reports").await {
+                    Ok(queue) => reported.set(Some(queue)),
+                    Err(fetch_error) => error.set(Some(fetch_error)),
+                }
+            });
+        });
+    }
+
+    let Some(queue) = (*reported).clone() else {
+        return match &*error {
+            Some(fetch_error) if matches!(fetch_error.status(), Some(401 | 403)) => {
+                html! { <div class="alert alert-error">{"Moderation is only available to admins"}</div> }
+            }
+            Some(fetch_error) => page_error(fetch_error, retry),
+            None => html! { <div class="text-center">{"Loading..."}</div> },
+        };
+    };
+
+    // Resolving takes the event out of the queue.
+    let resolve = {
+        let reported = reported.clone();
+        let client = client.clone();
+        Callback::from(move |(event_id, action): (String, &'static str)| {
+            let reported = reported.clone();
+            let errors = errors.clone();
+            let client = client.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                let url = format!("/api/admin/reports/{}", event_id);
+                let body = serde_json::json!({ "action": action });
+                match client.send_json::<serde::de::IgnoredAny>(Request::post(&url), &body).await {
+                    Ok(_) => {
+                        let remaining = reported
+                            .as_ref()
+                            .map(|queue| queue.iter().filter(|item| item.event_id != event_id).cloned().collect());
+                        reported.set(remaining);
+                    }
+                    Err(error) => errors.report(error),
+                }
+            });
+        })
+    };
+    // Everything but dismissing is asked about first.
+    let confirm = {
+        let confirming = confirming.clone();
+        let resolve = resolve.clone();
+        Callback::from(move |(item, action): (ReportedEvent, &'static str)| {
+            let (title, message, label) = match action {
+                "unpublish" => (
+                    "Unpublish this event?",
+                    format!("\"{}\" goes back to being a draft.", item.title),
+                    "Unpublish",
+                ),
+                "delete" => ("Delete this event?", format!("\"{}\" will be deleted for good.", item.title), "Delete"),
+                _ => (
+                    "Ban the author?",
+                    format!(
+                        "{} will be logged out and unable to log in, and \"{}\" will be unpublished.",
+                        item.author.as_deref().unwrap_or("The author"),
+                        item.title
+                    ),
+                    "Ban",
+                ),
+            };
+            let event_id = item.event_id.clone();
+            let on_confirm = resolve.reform(move |_| (event_id.clone(), action));
+            confirming.set(Some(Confirmation::new(title, message, label, on_confirm)));
+        })
+    };
+    let close_confirmation = {
+        let confirming = confirming.clone();
+        Callback::from(move |_| confirming.set(None))
+    };
+
+    html! {
+        <div class="min-h-screen bg-base-200">
+            <header class="bg-base-100 shadow">
+                <div class="container mx-auto px-4 py-6">
+                    <Breadcrumbs route={Route::AdminModeration} />
+                    <h1 class="text-3xl font-bold">{"Moderation"}</h1>
+                </div>
+            </header>
+            <main class="container mx-auto px-4 py-8">
+                if queue.is_empty() {
+                    <p class="opacity-70">{"No open reports."}</p>
+                }
+                <div class="flex flex-col gap-4">
+                    {queue.iter().map(|item| {
+                        let action = |action: &'static str| {
+                            let item = item.clone();
+                            match action {
+                                "dismiss" => resolve.reform(move |_: yew::MouseEvent| (item.event_id.clone(), action)),
+                                _ => confirm.reform(move |_: yew::MouseEvent| (item.clone(), action)),
+                            }
+                        };
+                        html! {
+                            <div class="card bg-base-100 shadow" key={item.event_id.clone()}>
+                                <div class="card-body">
+                                    <h2 class="card-title">
+                                        <a class="link link-hover" href={event_path(item.timeline_id.as_deref(), &item.event_id)}>
+                                            {&item.title}
+                                        </a>
+                                        <span class="badge badge-warning">
+                                            {format!("{} report{}", item.reports, if item.reports == 1 { "" } else { "s" })}
+                                        </span>
+                                    </h2>
+                                    <p class="text-sm opacity-70">
+                                        {format!(
+                                            "By {} · first reported {} · {}",
+                                            item.author.as_deref().unwrap_or("no one"),
+                                            event_day(&item.first_reported_at),
+                                            item.reasons.join(", ")
+                                        )}
+                                    </p>
+                                    if !item.details.is_empty() {
+                                        <ul class="list-disc ml-6">
+                                            {item.details.iter().map(|details| html! { <li>{details}</li> }).collect::<Html>()}
+                                        </ul>
+                                    }
+                                    <div class="card-actions justify-end">
+                                        <button class="btn btn-ghost btn-sm" onclick={action("dismiss")}>{"Dismiss"}</button>
+                                        <button class="btn btn-outline btn-sm" onclick={action("unpublish")}>{"Unpublish"}</button>
+                                        <button class="btn btn-error btn-outline btn-sm" onclick={action("delete")}>{"Delete"}</button>
+                                        if item.author.is_some() {
+                                            <button class="btn btn-error btn-sm" onclick={action("ban")}>{"Ban author"}</button>
+                                        }
+                                    </div>
+                                </div>
+                            </div>
+                        }
+                    }).collect::<Html>()}
+                </div>
+                <ConfirmDialog confirmation={(*confirming).clone()} on_close={close_confirmation} />
+            </main>
+        </div>
+    }
+}
+
+/// An account in `/api/admin/users`.
+#[derive(Deserialize, Clone, PartialEq)]
+struct ManagedUser {
+    id: String,
+    username: String,
+    email: String,
+    role: String,
+    /// `active`, `disabled` or `deleted`.
+    status: String,
+    has_password: bool,
+    created_at: String,
+    timelines: i64,
+    events: i64,
+    comments: i64,
+}
+
+/// What `POST /api/admin/impersonate/:user_id` hands back.
+#[derive(Deserialize)]
+struct ImpersonationStarted {
+    token: String,
+    username: String,
+    expires_at: String,
+}
+
+/// Switches to seeing the app as `user`, until the banner ends it.
+fn impersonate(client: ApiClient, user: &ManagedUser, errors: ErrorReporter) {
+    let url = format!("/api/admin/impersonate/{}", user.id);
+    wasm_bindgen_futures::spawn_local(async move {
+        match client.send::<ImpersonationStarted>(Request::post(&url)).await {
+            Ok(started) => {
+                let impersonating = auth::Impersonating { username: started.username, expires_at: started.expires_at };
+                auth::impersonate(&started.token, &impersonating);
+                gloo_utils::window().location().set_href("/").ok();
+            }
+            Err(error) => errors.report(error),
+        }
+    });
+}
+
+/// Admins' list of accounts, with disabling, role changes, forced
+/// password resets and impersonation.
+#[function_component(AdminUsers)]
+fn admin_users() -> Html {
+    let users = use_state(|| Option::<Page<ManagedUser>>::None);
+    let search = use_state(String::new);
+    let role = use_state(String::new);
+    let status = use_state(String::new);
+    let page = use_state(|| 1);
+    let error = use_state(|| Option::<FetchError>::None);
+    let confirming = use_state(|| Option::<Confirmation>::None);
+    let (attempt, retry) = use_retry();
+    let errors = use_error_reporter();
+    let client = use_api();
+    let search_input = use_node_ref();
+
+    {
+        let users = users.clone();
+        let error = error.clone();
+        let client = client.clone();
+        yew::use_effect_with(
+            ((*search).clone(), (*role).clone(), (*status).clone(), *page, attempt),
+            move |(search, role, status, page, _)| {
+                error.set(None);
+                let mut params = vec![format!("page={}", page)];
+                if !search.is_empty() {
+                    params.push(format!("search={}", js_sys::encode_uri_component(search)));
+                }
+                if !role.is_empty() {
+                    params.push(format!("role={}", role));
+                }
+                if !status.is_empty() {
+                    params.push(format!("status={}", status));
+                }
+                let url = format!("/api/admin/users?{}", params.join("&"));
+                wasm_bindgen_futures::spawn_local(async move {
+                    match client.get::<Page<ManagedUser>>(&url).await {
+                        Ok(found) => users.set(Some(found)),
+                        Err(fetch_error) => error.set(Some(fetch_error)),
+                    }
+                });
+            },
+        );
+    }
+
+    if let Some(fetch_error) = &*error {
+        return match fetch_error.status() {
+            Some(401 | 403) => html! { <div class="alert alert-error">{"User management is only available to admins"}</div> },
+            _ => page_error(fetch_error, retry),
+        };
+    }
+
+    let on_search = {
+        let search = search.clone();
+        let page = page.clone();
+        let search_input = search_input.clone();
+        Callback::from(move |e: yew::SubmitEvent| {
+            e.prevent_default();
+            if let Some(input) = search_input.cast::<web_sys::HtmlInputElement>() {
+                page.set(1);
+                search.set(input.value().trim().to_string());
+            }
+        })
+    };
+    let on_filter = |filter: &yew::UseStateHandle<String>| {
+        let filter = filter.clone();
+        let page = page.clone();
+        Callback::from(move |e: yew::Event| {
+            let select: web_sys::HtmlSelectElement = e.target_unchecked_into();
+            page.set(1);
+            filter.set(select.value());
+        })
+    };
+    let go_to = |target: i32| {
+        let page = page.clone();
+        Callback::from(move |_| page.set(target))
+    };
+
+    // Each action answers with the account as it now is, except a reset,
+    // which only leaves it without a password.
+    let act = {
+        let users = users.clone();
+        let errors = errors.clone();
+        let client = client.clone();
+        Callback::from(move |(user, action): (ManagedUser, &'static str)| {
+            let users = users.clone();
+            let errors = errors.clone();
+            let client = client.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                let base = format!("/api/admin/users/{}", user.id);
+                let result = match action {
+                    "disable" | "enable" => {
+                        client.send::<ManagedUser>(Request::post(&format!("{}/{}", base, action))).await
+                    }
+                    "reset" => client.send::<serde::de::IgnoredAny>(Request::post(&format!("{}/password-reset", base)))
+                        .await
+                        .map(|_| ManagedUser { has_password: false, ..user }),
+                    role => {
+                        let body = serde_json::json!({ "role": role });
+                        client.send_json::<ManagedUser>(Request::put(&format!("{}/role", base)), &body).await
+                    }
+                };
+                match result {
+                    Ok(updated) => {
+                        let page = (*users).clone().map(|mut found| {
+                            for user in found.data.iter_mut().filter(|user| user.id == updated.id) {
+                                *user = updated.clone();
+                            }
+                            found
+                        });
+                        users.set(page);
+                    }
+                    Err(error) => errors.report(error),
+                }
+            });
+        })
+    };
+    // Everything but re-enabling is asked about first.
+    let confirm = {
+        let confirming = confirming.clone();
+        let act = act.clone();
+        Callback::from(move |(user, action): (ManagedUser, &'static str)| {
+            let (title, message, label) = match action {
+                "disable" => (
+                    "Disable this account?",
+                    format!("{} will be logged out and unable to log in until the account is enabled again.", user.username),
+                    "Disable",
+                ),
+                "reset" => (
+                    "Force a password reset?",
+                    format!(
+                        "{}'s password stops working and they are logged out; {} gets a link to choose a new one.",
+                        user.username, user.email
+                    ),
+                    "Reset password",
+                ),
+                "admin" => ("Make this user an admin?", format!("{} will be able to manage the whole site.", user.username), "Make admin"),
+                _ => ("Remove admin rights?", format!("{} will be an ordinary user again.", user.username), "Remove admin"),
+            };
+            let on_confirm = act.reform(move |_| (user.clone(), action));
+            confirming.set(Some(Confirmation::new(title, message, label, on_confirm)));
+        })
+    };
+    let close_confirmation = {
+        let confirming = confirming.clone();
+        Callback::from(move |_| confirming.set(None))
+    };
+    let view_as = |user: &ManagedUser| {
+        let client = client.clone();
+        let user = user.clone();
+        let errors = errors.clone();
+        let client = client.clone();
+        Callback::from(move |_: yew::MouseEvent| impersonate(client.clone(), &user, errors.clone()))
+    };
+
+    let results = match &*users {
+        None => html! { <div class="text-center">{"Loading..."}</div> },
+        Some(found) if found.data.is_empty() => html! { <p class="opacity-70">{"No accounts match."}</p> },
+        Some(found) => html! {
+            <>
+                <div class="overflow-x-auto bg-base-100 rounded-box shadow">
+                    <table class="table">
+                        <thead>
+                            <tr>
+                                <th>{"User"}</th>
+                                <th>{"Role"}</th>
+                                <th>{"Status"}</th>
+                                <th>{"Contributions"}</th>
+                                <th>{"Joined"}</th>
+                                <th><span class="sr-only">{"Actions"}</span></th>
+                            </tr>
+                        </thead>
+                        <tbody>
+                            {found.data.iter().map(|user| {
+                                let action = |action: &'static str| {
+                                    let user = user.clone();
+                                    match action {
+                                        "enable" => act.reform(move |_: yew::MouseEvent| (user.clone(), action)),
+                                        _ => confirm.reform(move |_: yew::MouseEvent| (user.clone(), action)),
+                                    }
+                                };
+                                let badge = match user.status.as_str() {
+                                    "active" => "badge badge-success",
+                                    "disabled" => "badge badge-error",
+                                    _ => "badge badge-ghost",
+                                };
+                                html! {
+                                    <tr key={user.id.clone()}>
+                                        <td>
+                                            <div class="font-bold">{&user.username}</div>
+                                            <div class="text-sm opacity-70">{&user.email}</div>
+                                        </td>
+                                        <td class="capitalize">{&user.role}</td>
+                                        <td>
+                                            <span class={badge}>{&user.status}</span>
+                                            if !user.has_password {
+                                                <span class="badge badge-outline ml-1">{"no password"}</span>
+                                            }
+                                        </td>
+                                        <td class="text-sm">
+                                            {format!("{} timelines · {} events · {} comments", user.timelines, user.events, user.comments)}
+                                        </td>
+                                        <td class="text-sm">{event_day(&user.created_at)}</td>
+                                        <td class="flex flex-wrap gap-1 justify-end">
+                                            if user.status == "active" {
+                                                if user.role == "admin" {
+                                                    <button class="btn btn-ghost btn-xs" onclick={action("user")}>{"Remove admin"}</button>
+                                                } else {
+                                                    <button class="btn btn-ghost btn-xs" onclick={view_as(user)}>{"View as"}</button>
+                                                    <button class="btn btn-ghost btn-xs" onclick={action("admin")}>{"Make admin"}</button>
+                                                    <button class="btn btn-error btn-outline btn-xs" onclick={action("disable")}>{"Disable"}</button>
+                                                }
+                                                <button class="btn btn-outline btn-xs" onclick={action("reset")}>{"Reset password"}</button>
+                                            } else if user.status == "disabled" {
+                                                <button class="btn btn-outline btn-xs" onclick={action("enable")}>{"Enable"}</button>
+                                            }
+                                        </td>
+                                    </tr>
+                                }
+                            }).collect::<Html>()}
+                        </tbody>
+                    </table>
+                </div>
+                if found.pages > 1 {
+                    <div class="join mt-6 flex justify-center">
+                        <button class="join-item btn" disabled={*page <= 1} onclick={go_to(*page - 1)}>{"«"}</button>
+                        <span class="join-item btn btn-disabled">{format!("Page {} of {}", *page, found.pages)}</span>
+                        <button class="join-item btn" disabled={*page >= found.pages} onclick={go_to(*page + 1)}>{"»"}</button>
+                    </div>
+                }
+            </>
+        },
+    };
+
+    html! {
+        <div class="min-h-screen bg-base-200">
+            <header class="bg-base-100 shadow">
+                <div class="container mx-auto px-4 py-6">
+                    <Breadcrumbs route={Route::AdminUsers} />
+                    <h1 class="text-3xl font-bold">{"Users"}</h1>
+                </div>
+            </header>
+            <main class="container mx-auto px-4 py-8">
+                <div class="flex flex-wrap gap-2 mb-6">
+                    <form class="flex gap-2 flex-1" onsubmit={on_search}>
+                        <input
+                            ref={search_input}
+                            type="search"
+                            class="input input-bordered flex-1"
+                            placeholder="Search by username or email"
+                            aria-label="Search users"
+                            value={(*search).clone()}
+                        />
+                        <button class="btn btn-primary" type="submit">{"Search"}</button>
+                    </form>
+                    <select class="select select-bordered" aria-label="Role" onchange={on_filter(&role)}>
+                        <option value="" selected={role.is_empty()}>{"Any role"}</option>
+                        <option value="user" selected={*role == "user"}>{"Users"}</option>
+                        <option value="admin" selected={*role == "admin"}>{"Admins"}</option>
+                    </select>
+                    <select class="select select-bordered" aria-label="Status" onchange={on_filter(&status)}>
+                        <option value="" selected={status.is_empty()}>{"Any status"}</option>
+                        <option value="active" selected={*status == "active"}>{"Active"}</option>
+                        <option value="disabled" selected={*status == "disabled"}>{"Disabled"}</option>
+                        <option value="deleted" selected={*status == "deleted"}>{"Deleted"}</option>
+                    </select>
+                </div>
+                {results}
+                <ConfirmDialog confirmation={(*confirming).clone()} on_close={close_confirmation} />
+            </main>
+        </div>
+    }
+}
+
+/// Stands in for a page whose content failed to load.
+fn page_error(error: &FetchError, onretry: Callback<()>) -> Html {
+    html! {
+        <div class="container mx-auto px-4 py-8">
+            <LoadError error={error.clone()} {onretry} />
+        </div>
+    }
+}
+
+#[function_component(Stats)]
+fn stats() -> Html {
+    let cooccurrence = use_state(TagCooccurrence::default);
+    let decades = use_state(Vec::<DecadeCategory>::new);
+    let granularity = use_state(|| Granularity::Century);
+    let periods = use_state(Vec::<histogram::Bucket>::new);
+    let loading = use_state(|| true);
+    let error = use_state(|| Option::<FetchError>::None);
+    let (attempt, retry) = use_retry();
+    let errors = use_error_reporter();
+    let client = use_api();
+
+    {
+        let periods = periods.clone();
+        let client = client.clone();
+        yew::use_effect_with(*granularity, move |granularity: &Granularity| {
+            let granularity = *granularity;
+            wasm_bindgen_futures::spawn_local(async move {
+                match histogram::fetch(&client, granularity, None, None).await {
+                    Ok(buckets) => periods.set(buckets),
+                    Err(fetch_error) => errors.report(fetch_error),
+                }
+            });
+        });
+    }
+
+    {
+        let cooccurrence = cooccurrence.clone();
+        let decades = decades.clone();
+        let loading = loading.clone();
+        let error = error.clone();
+        yew::use_effect_with(attempt, move |_| {
+            loading.set(true);
+            error.set(None);
+            let fetch_stats = async move {
+                let fetched = match client.get::<TagCooccurrence>("/api/analytics/tag-cooccurrence?limit=20").await {
+                    Ok(cooccurrence_data) => client.get::<Vec<DecadeCategory>>("/api/analytics/decades")
+                        .await
+                        .map(|decades_data| (cooccurrence_data, decades_data)),
+                    Err(fetch_error) => Err(fetch_error),
+                };
+                match fetched {
+                    Ok((cooccurrence_data, decades_data)) => {
+                        cooccurrence.set(cooccurrence_data);
+                        decades.set(decades_data);
+                    }
+                    Err(fetch_error) => error.set(Some(fetch_error)),
+                }
+                loading.set(false);
+            };
+            wasm_bindgen_futures::spawn_local(fetch_stats);
+        });
+    }
+
+    if *loading {
+        return html! { <div class="text-center">{"Loading..."}</div> };
+    }
+    if let Some(fetch_error) = &*error {
+        return page_error(fetch_error, retry);
+    }
+
+    // Pivot the (decade, category, count) rows into a decade x category grid.
+    let category_name = |row: &DecadeCategory| row.category.clone().unwrap_or_else(|| "Uncategorized".to_string());
+    let mut decade_labels: Vec<i32> = decades.iter().map(|row| row.decade).collect();
+    decade_labels.dedup();
+    let mut categories: Vec<String> = decades.iter().map(category_name).collect();
+    categories.sort();
+    categories.dedup();
+    let decade_values = decade_labels
+        .iter()
+        .map(|decade| {
+            categories
+                .iter()
+                .map(|category| {
+                    decades
+                        .iter()
+                        .find(|row| row.decade == *decade && category_name(row) == *category)
+                        .map_or(0, |row| row.count)
+                })
+                .collect()
+        })
+        .collect::<Vec<Vec<i64>>>();
+
+    let on_granularity = {
+        let granularity = granularity.clone();
+        Callback::from(move |e: yew::Event| {
+            let select: web_sys::HtmlSelectElement = e.target_unchecked_into();
+            granularity.set(match select.value().as_str() {
+                "year" => Granularity::Year,
+                "decade" => Granularity::Decade,
+                _ => Granularity::Century,
+            });
+        })
+    };
+    let period_bars = periods
+        .iter()
+        .map(|bucket| (histogram::period_label(bucket.start, *granularity), bucket.count))
+        .collect::<Vec<_>>();
+
+    html! {
+        <div class="min-h-screen bg-base-200">
+            <header class="bg-base-100 shadow">
+                <div class="container mx-auto px-4 py-6">
+                    <h1 class="text-3xl font-bold">{"Statistics"}</h1>
+                </div>
+            </header>
+            <main class="container mx-auto px-4 py-8 grid grid-cols-1 xl:grid-cols-2 gap-6">
+                <Heatmap
+                    title="Tag co-occurrence"
+                    rows={cooccurrence.tags.clone()}
+                    columns={cooccurrence.tags.clone()}
+                    values={cooccurrence.matrix.clone()}
+                />
+                <BarChart title="Events over time" bars={period_bars}>
+                    <select class="select select-bordered select-sm" aria-label="Period" onchange={on_granularity}>
+                        <option value="century" selected={*granularity == Granularity::Century}>{"Centuries"}</option>
+                        <option value="decade" selected={*granularity == Granularity::Decade}>{"Decades"}</option>
+                        <option value="year" selected={*granularity == Granularity::Year}>{"Years"}</option>
+                    </select>
+                </BarChart>
+                <Heatmap
+                    title="Categories per decade"
+                    rows={decade_labels.iter().map(|decade| format!("{}s", decade)).collect::<Vec<_>>()}
+                    columns={categories}
+                    values={decade_values}
+                />
+            </main>
+        </div>
+    }
+}
+
+/// The signed-in user's preferences. Each change takes effect at once and
+/// is saved in the background; a failed save puts the old value back.
+#[function_component(Settings)]
+fn settings() -> Html {
+    let current = use_state(preferences::cached);
+    let timelines = use_state(Vec::<TimelineInfo>::new);
+    let errors = use_error_reporter();
+    let client = use_api();
+    let signed_in = auth::token().is_some();
+
+    {
+        let current = current.clone();
+        let timelines = timelines.clone();
+        let errors = errors.clone();
+        let client = client.clone();
+        yew::use_effect_with((), move |_| {
+            if signed_in {
+                {
+                    let client = client.clone();
+                    wasm_bindgen_futures::spawn_local(async move {
+                        match preferences::load(&client).await {
+                            Ok(saved) => current.set(saved),
+                            Err(error) => errors.report(error),
+                        }
+                    });
+                }
+                wasm_bindgen_futures::spawn_local(async move {
+                    if let Ok(visible) = client.get::<Vec<TimelineInfo>>("/api/timelines").await {
+                        timelines.set(visible);
+                    }
+                });
+            }
+        });
+    }
+
+    let update = {
+        let current = current.clone();
+        move |change: fn(&mut preferences::Preferences, String)| {
+            let current = current.clone();
+            let errors = errors.clone();
+            let client = client.clone();
+            Callback::from(move |e: yew::Event| {
+                let select: web_sys::HtmlSelectElement = e.target_unchecked_into();
+                let previous = (*current).clone();
+                let mut next = previous.clone();
+                change(&mut next, select.value());
+                current.set(next.clone());
+                let current = current.clone();
+                let errors = errors.clone();
+                let client = client.clone();
+                wasm_bindgen_futures::spawn_local(async move {
+                    if let Err(error) = preferences::save(&client, &next).await {
+                        preferences::store(&previous);
+                        current.set(previous);
+                        errors.report(error);
+                    }
+                });
+            })
+        }
+    };
+
+    let options = |choices: &[(&'static str, &'static str)], selected: &str| {
+        choices
+            .iter()
+            .map(|(value, label)| html! { <option value={*value} selected={*value == selected}>{*label}</option> })
+            .collect::<Html>()
+    };
+
+    html! {
+        <div class="min-h-screen bg-base-200">
+            <header class="bg-base-100 shadow">
+                <div class="container mx-auto px-4 py-6">
+                    <Breadcrumbs route={Route::Settings} />
+                    <h1 class="text-3xl font-bold">{"Settings"}</h1>
+                </div>
+            </header>
+            <main class="container mx-auto px-4 py-8">
+                if !signed_in {
+                    <p>{"Log in to change your settings. "}<a href="/login" class="link">{"Log in"}</a></p>
+                } else {
+                    <div class="flex flex-col gap-6">
+                        <div class="card bg-base-100 shadow max-w-lg">
+                            <div class="card-body gap-4">
+                                <label class="form-control">
+                                    <span class="label-text mb-1">{"Theme"}</span>
+                                    <select class="select select-bordered"
+                                        onchange={update(|preferences, value| preferences.theme = value)}>
+                                        {options(preferences::THEMES, &current.theme)}
+                                    </select>
+                                </label>
+                                <label class="form-control">
+                                    <span class="label-text mb-1">{"Language"}</span>
+                                    <select class="select select-bordered"
+                                        onchange={update(|preferences, value| preferences.locale = value)}>
+                                        {options(preferences::LOCALES, &current.locale)}
+                                    </select>
+                                </label>
+                                <label class="form-control">
+                                    <span class="label-text mb-1">{"Events per page"}</span>
+                                    <select class="select select-bordered" onchange={update(|preferences, value| {
+                                        preferences.items_per_page = value.parse().unwrap_or(preferences.items_per_page);
+                                    })}>
+                                        {preferences::PAGE_SIZES.iter().map(|size| html! {
+                                            <option value={size.to_string()} selected={*size == current.items_per_page}>
+                                                {size}
+                                            </option>
+                                        }).collect::<Html>()}
+                                    </select>
+                                </label>
+                                <label class="form-control">
+                                    <span class="label-text mb-1">{"Event order"}</span>
+                                    <select class="select select-bordered"
+                                        onchange={update(|preferences, value| preferences.default_sort = value)}>
+                                        {options(preferences::SORTS, &current.default_sort)}
+                                    </select>
+                                </label>
+                                <label class="form-control">
+                                    <span class="label-text mb-1">{"Home page opens"}</span>
+                                    <select class="select select-bordered" onchange={update(|preferences, value| {
+                                        preferences.default_timeline_id = Some(value).filter(|id| !id.is_empty());
+                                    })}>
+                                        <option value="" selected={current.default_timeline_id.is_none()}>{"All events"}</option>
+                                        {timelines.iter().map(|timeline| html! {
+                                            <option
+                                                value={timeline.id.clone()}
+                                                selected={current.default_timeline_id.as_deref() == Some(timeline.id.as_str())}
+                                            >
+                                                {&timeline.title}
+                                            </option>
+                                        }).collect::<Html>()}
+                                    </select>
+                                </label>
+                            </div>
+                        </div>
+                        <StorageUsage />
+                        <Sessions />
+                        <DataExport />
+                        <DeleteAccount />
+                    </div>
+                }
+            </main>
+        </div>
+    }
+}
+
+/// A card on the Explore page, from `/api/timelines/public`.
+#[derive(Deserialize, Clone, PartialEq)]
+struct PublicTimeline {
+    id: String,
+    title: String,
+    description: Option<String>,
+    owner: String,
+    /// Event whose thumbnail is the cover.
+    cover_event_id: Option<String>,
+    event_count: i64,
+    starts_at: String,
+    ends_at: String,
+}
+
+/// The year of an API timestamp, which may be BCE ("-0490-09-12T…").
+fn year(date: &str) -> &str {
+    let end = date.get(1..).and_then(|rest| rest.find('-')).map_or(date.len(), |index| index + 1);
+    &date[..end]
+}
+
+/// The years a public timeline covers, e.g. "1914–1918".
+fn year_span(timeline: &PublicTimeline) -> String {
+    let (first, last) = (year(&timeline.starts_at), year(&timeline.ends_at));
+    match first == last {
+        true => first.to_string(),
+        false => format!("{}–{}", first, last),
+    }
+}
+
+/// Public timelines to browse, searchable and sorted by popularity or
+/// recent activity.
+#[function_component(Explore)]
+fn explore() -> Html {
+    let client = use_api();
+    let timelines = use_state(|| Option::<Page<PublicTimeline>>::None);
+    let search = use_state(|| query_param("search").unwrap_or_default());
+    let sort = use_state(|| query_param("sort").unwrap_or_else(|| "popular".to_string()));
+    let page = use_state(|| 1);
+    let error = use_state(|| Option::<FetchError>::None);
+    let (attempt, retry) = use_retry();
+    let search_input = use_node_ref();
+
+    {
+        let timelines = timelines.clone();
+        let error = error.clone();
+        yew::use_effect_with(((*search).clone(), (*sort).clone(), *page, attempt), move |(search, sort, page, _)| {
+            error.set(None);
+            let mut params = vec![format!("sort={}", sort), format!("page={}", page)];
+            if !search.is_empty() {
+                params.push(format!("search={}", js_sys::encode_uri_component(search)));
+            }
+            let url = format!("/api/timelines/public?{}", params.join("&"));
+            wasm_bindgen_futures::spawn_local(async move {
+                match client.get::<Page<PublicTimeline>>(&url).await {
+                    Ok(found) => timelines.set(Some(found)),
+                    Err(fetch_error) => error.set(Some(fetch_error)),
+                }
+            });
+        });
+    }
+
+    let on_search = {
+        let search = search.clone();
+        let page = page.clone();
+        let search_input = search_input.clone();
+        Callback::from(move |e: yew::SubmitEvent| {
+            e.prevent_default();
+            if let Some(input) = search_input.cast::<web_sys::HtmlInputElement>() {
+                page.set(1);
+                search.set(input.value().trim().to_string());
+            }
+        })
+    };
+    let on_sort = {
+        let sort = sort.clone();
+        let page = page.clone();
+        Callback::from(move |e: yew::Event| {
+            let select: web_sys::HtmlSelectElement = e.target_unchecked_into();
+            page.set(1);
+            sort.set(select.value());
+        })
+    };
+    let go_to = |target: i32| {
+        let page = page.clone();
+        Callback::from(move |_| page.set(target))
+    };
+
+    let results = match (&*timelines, &*error) {
+        (_, Some(fetch_error)) => page_error(fetch_error, retry),
+        (None, None) => html! { <div class="text-center">{"Loading..."}</div> },
+        (Some(found), None) if found.data.is_empty() => html! {
+            <p class="opacity-70">{"No public timelines match."}</p>
+        },
+        (Some(found), None) => html! {
+            <>
+                <div class="grid gap-4 md:grid-cols-2 lg:grid-cols-3">
+                    {found.data.iter().map(|timeline| html! {
+                        <a class="card bg-base-100 shadow hover:shadow-xl" key={timeline.id.clone()}
+                            href={format!("/timelines/{}", timeline.id)}>
+                            if let Some(cover) = &timeline.cover_event_id {
+                                <figure class="h-40">
+                                    <img src={api::thumbnail_src(cover, 480)} alt="" class="w-full h-full object-cover" loading="lazy" />
+                                </figure>
+                            }
+                            <div class="card-body">
+                                <h2 class="card-title">{&timeline.title}</h2>
+                                if let Some(description) = &timeline.description {
+                                    <p class="opacity-70 line-clamp-3">{description}</p>
+                                }
+                                <p class="text-sm opacity-70">
+                                    {format!("{} · {} events · by {}", year_span(timeline), timeline.event_count, timeline.owner)}
+                                </p>
+                            </div>
+                        </a>
+                    }).collect::<Html>()}
+                </div>
+                if found.pages > 1 {
+                    <div class="join mt-6 flex justify-center">
+                        <button class="join-item btn" disabled={*page <= 1} onclick={go_to(*page - 1)}>{"«"}</button>
+                        <span class="join-item btn btn-disabled">{format!("Page {} of {}", *page, found.pages)}</span>
+                        <button class="join-item btn" disabled={*page >= found.pages} onclick={go_to(*page + 1)}>{"»"}</button>
+                    </div>
+                }
+            </>
+        },
+    };
+
+    html! {
+        <div class="min-h-screen bg-base-200">
+            <header class="bg-base-100 shadow">
+                <div class="container mx-auto px-4 py-6">
+                    <Breadcrumbs route={Route::Explore} />
+                    <h1 class="text-3xl font-bold">{"Explore"}</h1>
+                    <p class="opacity-70">{"Timelines people have shared with everyone."}</p>
+                </div>
+            </header>
+            <main class="container mx-auto px-4 py-8">
+                <div class="flex flex-wrap gap-2 mb-6">
+                    <form class="flex gap-2 flex-1" onsubmit={on_search}>
+                        <input
+                            ref={search_input}
+                            type="search"
+                            class="input input-bordered flex-1"
+                            placeholder="Search timelines"
+                            aria-label="Search timelines"
+                            value={(*search).clone()}
+                        />
+                        <button class="btn btn-primary" type="submit">{"Search"}</button>
+                    </form>
+                    <select class="select select-bordered" aria-label="Sort timelines" onchange={on_sort}>
+                        <option value="popular" selected={*sort == "popular"}>{"Most viewed"}</option>
+                        <option value="recent" selected={*sort == "recent"}>{"Recently updated"}</option>
+                    </select>
+                </div>
+                {results}
+            </main>
+        </div>
+    }
+}
+
+/// An event in `/api/search` results.
+#[derive(Deserialize, Clone, PartialEq)]
+struct EventResult {
+    id: String,
+    title: String,
+    snippet: Option<String>,
+    start_date: String,
+    timeline_id: Option<String>,
+    timeline_title: Option<String>,
+}
+
+#[derive(Deserialize, Clone, PartialEq)]
+struct TimelineResult {
+    id: String,
+    title: String,
+    description: Option<String>,
+    owner: String,
+    event_count: i64,
+}
+
+#[derive(Deserialize, Clone, PartialEq)]
+struct PersonResult {
+    id: String,
+    name: String,
+    birth_date: Option<String>,
+    death_date: Option<String>,
+}
+
+#[derive(Deserialize, Clone, PartialEq)]
+struct TagResult {
+    name: String,
+    event_count: i64,
+}
+
+#[derive(Deserialize, Clone, PartialEq)]
+struct ResultGroup<T> {
+    total: i64,
+    items: Vec<T>,
+}
+
+#[derive(Deserialize, Clone, PartialEq)]
+struct SearchResults {
+    /// The results only resemble the query; nothing contained it.
+    fuzzy: bool,
+    suggestion: Option<String>,
+    events: ResultGroup<EventResult>,
+    timelines: ResultGroup<TimelineResult>,
+    people: ResultGroup<PersonResult>,
+    tags: ResultGroup<TagResult>,
+}
+
+/// Tabs of the search page: the `type` the API takes and its label.
+const SEARCH_TABS: &[(&str, &str)] = &[("", "All"), ("events", "Events"), ("timelines", "Timelines"), ("people", "People"), ("tags", "Tags")];
+/// Results per page on a single tab; matches the API.
+const SEARCH_PAGE_SIZE: i64 = 20;
+
+fn search_href(q: &str, kind: &str) -> String {
+    let mut href = format!("/search?q={}", js_sys::encode_uri_component(q));
+    if !kind.is_empty() {
+        href.push_str(&format!("&type={}", kind));
+    }
+    href
+}
+
+/// Everything matching a query, grouped into events, timelines, people and
+/// tags. "All" shows the best few of each; the other tabs page through one.
+#[function_component(Search)]
+fn search() -> Html {
+    let client = use_api();
+    let q = use_state(|| query_param("q").unwrap_or_default());
+    let kind = use_state(|| query_param("type").unwrap_or_default());
+    let page = use_state(|| 1i64);
+    let results = use_state(|| Option::<SearchResults>::None);
+    let error = use_state(|| Option::<FetchError>::None);
+    let (attempt, retry) = use_retry();
+    let search_input = use_node_ref();
+
+    {
+        let results = results.clone();
+        let error = error.clone();
+        yew::use_effect_with(((*q).clone(), (*kind).clone(), *page, attempt), move |(q, kind, page, _)| {
+            breadcrumbs::keep_list_url(&search_href(q, kind));
+            error.set(None);
+            results.set(None);
+            if !q.is_empty() {
+                let mut url = format!("/api/search?q={}&page={}", js_sys::encode_uri_component(q), page);
+                if !kind.is_empty() {
+                    url.push_str(&format!("&type={}", kind));
+                }
+                wasm_bindgen_futures::spawn_local(async move {
+                    match client.get::<SearchResults>(&url).await {
+                        Ok(found) => results.set(Some(found)),
+                        Err(fetch_error) => error.set(Some(fetch_error)),
+                    }
+                });
+            }
+        });
+    }
+
+    let on_search = {
+        let q = q.clone();
+        let page = page.clone();
+        let search_input = search_input.clone();
+        Callback::from(move |e: yew::SubmitEvent| {
+            e.prevent_default();
+            if let Some(input) = search_input.cast::<web_sys::HtmlInputElement>() {
+                page.set(1);
+                q.set(input.value().trim().to_string());
+            }
+        })
+    };
+    let show = |target: &'static str| {
+        let kind = kind.clone();
+        let page = page.clone();
+        Callback::from(move |_: yew::MouseEvent| {
+            page.set(1);
+            kind.set(target.to_string());
+        })
+    };
+    let go_to = |target: i64| {
+        let page = page.clone();
+        Callback::from(move |_: yew::MouseEvent| page.set(target))
+    };
+    let accept = |suggestion: String| {
+        let q = q.clone();
+        let page = page.clone();
+        Callback::from(move |_: yew::MouseEvent| {
+            page.set(1);
+            q.set(suggestion.clone());
+        })
+    };
+
+    let section = |target: &'static str, title: &str, total: i64, items: Html| {
+        if total == 0 {
+            return html! {};
+        }
+        html! {
+            <section class="mb-8" aria-label={title.to_string()}>
+                if kind.is_empty() {
+                    <div class="flex items-baseline justify-between mb-2">
+                        <h2 class="text-xl font-semibold">{title}</h2>
+                        if total > items_shown(target, &results) {
+                            <button class="btn btn-link btn-sm" onclick={show(target)}>
+                                {format!("See all {} {}", total, title.to_lowercase())}
+                            </button>
+                        }
+                    </div>
+                }
+                <ul class="flex flex-col gap-2">{items}</ul>
+            </section>
+        }
+    };
+
+    let body = match (&*results, &*error) {
+        (_, Some(fetch_error)) => page_error(fetch_error, retry),
+        _ if q.is_empty() => html! { <p class="opacity-70">{"Search events, timelines, people and tags."}</p> },
+        (None, None) => html! { <div class="text-center">{"Searching…"}</div> },
+        (Some(found), None) => {
+            let nothing = found.events.total + found.timelines.total + found.people.total + found.tags.total == 0;
+            let total = match kind.as_str() {
+                "events" => found.events.total,
+                "timelines" => found.timelines.total,
+                "people" => found.people.total,
+                "tags" => found.tags.total,
+                _ => 0,
+            };
+            let pages = (total + SEARCH_PAGE_SIZE - 1) / SEARCH_PAGE_SIZE;
+            html! {
+                <>
+                    if let Some(suggestion) = &found.suggestion {
+                        <p class="mb-2">
+                            {"Did you mean "}
+                            <button class="link link-primary font-semibold" onclick={accept(suggestion.clone())}>
+                                {suggestion}
+                            </button>
+                            {"?"}
+                        </p>
+                    }
+                    if nothing {
+                        <p class="opacity-70">{format!("Nothing matches “{}”.", *q)}</p>
+                    } else if found.fuzzy {
+                        <p class="opacity-70 mb-4">{format!("Nothing contains “{}”; showing similar results.", *q)}</p>
+                    }
+                    {section("events", "Events", found.events.total, found.events.items.iter().map(|event| html! {
+                        <li key={event.id.clone()} class="card card-compact bg-base-100 shadow">
+                            <a class="card-body hover:bg-base-200" href={match &event.timeline_id {
+                                Some(timeline_id) => format!("/timelines/{}/events/{}", timeline_id, event.id),
+                                None => format!("/events/{}", event.id),
+                            }}>
+                                <h3 class="font-semibold">{&event.title}</h3>
+                                <p class="text-sm opacity-70">
+                                    {event_day(&event.start_date)}
+                                    if let Some(timeline) = &event.timeline_title {
+                                        {format!(" · {}", timeline)}
+                                    }
+                                </p>
+                                if let Some(snippet) = &event.snippet {
+                                    <p class="line-clamp-2">{snippet}</p>
+                                }
+                            </a>
+                        </li>
+                    }).collect::<Html>())}
+                    {section("timelines", "Timelines", found.timelines.total, found.timelines.items.iter().map(|timeline| html! {
+                        <li key={timeline.id.clone()} class="card card-compact bg-base-100 shadow">
+                            <a class="card-body hover:bg-base-200" href={format!("/timelines/{}", timeline.id)}>
+                                <h3 class="font-semibold">{&timeline.title}</h3>
+                                <p class="text-sm opacity-70">{format!("{} events · by {}", timeline.event_count, timeline.owner)}</p>
+                                if let Some(description) = &timeline.description {
+                                    <p class="line-clamp-2">{description}</p>
+                                }
+                            </a>
+                        </li>
+                    }).collect::<Html>())}
+                    {section("people", "People", found.people.total, found.people.items.iter().map(|person| html! {
+                        <li key={person.id.clone()} class="card card-compact bg-base-100 shadow">
+                            <a class="card-body hover:bg-base-200" href={format!("/people/{}", person.id)}>
+                                <h3 class="font-semibold">{&person.name}</h3>
+                                if person.birth_date.is_some() || person.death_date.is_some() {
+                                    <p class="text-sm opacity-70">
+                                        {format!(
+                                            "{} – {}",
+                                            person.birth_date.as_deref().map(year).unwrap_or("?"),
+                                            person.death_date.as_deref().map(year).unwrap_or("")
+                                        )}
+                                    </p>
+                                }
+                            </a>
+                        </li>
+                    }).collect::<Html>())}
+                    {section("tags", "Tags", found.tags.total, html! {
+                        <li class="flex flex-wrap gap-2">
+                            {found.tags.items.iter().map(|tag| html! {
+                                <a key={tag.name.clone()} class="badge badge-lg badge-outline gap-1"
+                                    href={search_href(&tag.name, "events")}>
+                                    {format!("#{}", tag.name)}
+                                    <span class="opacity-60">{tag.event_count}</span>
+                                </a>
+                            }).collect::<Html>()}
+                        </li>
+                    })}
+                    if pages > 1 {
+                        <div class="join mt-6 flex justify-center">
+                            <button class="join-item btn" disabled={*page <= 1} onclick={go_to(*page - 1)}>{"«"}</button>
+                            <span class="join-item btn btn-disabled">{format!("Page {} of {}", *page, pages)}</span>
+                            <button class="join-item btn" disabled={*page >= pages} onclick={go_to(*page + 1)}>{"»"}</button>
+                        </div>
+                    }
+                </>
+            }
+        }
+    };
+
+    let total_for = |target: &str| {
+        (*results).as_ref().map(|found| match target {
+            "events" => found.events.total,
+            "timelines" => found.timelines.total,
+            "people" => found.people.total,
+            "tags" => found.tags.total,
+            _ => found.events.total + found.timelines.total + found.people.total + found.tags.total,
+        })
+    };
+
+    html! {
+        <div class="min-h-screen bg-base-200">
+            <header class="bg-base-100 shadow">
+                <div class="container mx-auto px-4 py-6">
+                    <Breadcrumbs route={Route::Search} />
+                    <h1 class="text-3xl font-bold">{"Search"}</h1>
+                </div>
+            </header>
+            <main class="container mx-auto px-4 py-8">
+                <form class="flex gap-2 mb-4" role="search" onsubmit={on_search}>
+                    <input
+                        ref={search_input}
+                        type="search"
+                        class="input input-bordered flex-1"
+                        placeholder="Search everything"
+                        aria-label="Search"
+                        maxlength="200"
+                        value={(*q).clone()}
+                    />
+                    <button class="btn btn-primary" type="submit">{"Search"}</button>
+                </form>
+                <div role="tablist" class="tabs tabs-bordered mb-6">
+                    {SEARCH_TABS.iter().map(|(target, label)| html! {
+                        <button
+                            role="tab"
+                            class={if *kind == *target { "tab tab-active" } else { "tab" }}
+                            aria-selected={(*kind == *target).to_string()}
+                            onclick={show(target)}
+                        >
+                            {*label}
+                            if let Some(total) = total_for(target) {
+                                <span class="badge badge-sm ml-2">{total}</span>
+                            }
+                        </button>
+                    }).collect::<Html>()}
+                </div>
+                {body}
+            </main>
+        </div>
+    }
+}
+
+/// How many results of `target` the "All" tab lists.
+fn items_shown(target: &str, results: &Option<SearchResults>) -> i64 {
+    results.as_ref().map_or(0, |found| {
+        (match target {
+            "events" => found.events.items.len(),
+            "timelines" => found.timelines.items.len(),
+            "people" => found.people.items.len(),
+            _ => found.tags.items.len(),
+        }) as i64
+    })
+}
+
+/// A gallery entry from `/api/templates`.
+#[derive(Deserialize, Clone, PartialEq)]
+struct TemplateInfo {
+    id: String,
+    title: String,
+    description: Option<String>,
+    owner: String,
+    event_count: i64,
+}
+
+/// Timelines offered as templates, most used first.
+#[function_component(Templates)]
+fn templates() -> Html {
+    let client = use_api();
+    let templates = use_state(|| Option::<Vec<TemplateInfo>>::None);
+    let error = use_state(|| Option::<FetchError>::None);
+    let (attempt, retry) = use_retry();
+    let signed_in = auth::token().is_some();
+
+    {
+        let templates = templates.clone();
+        let error = error.clone();
+        yew::use_effect_with(attempt, move |_| {
+            error.set(None);
+            wasm_bindgen_futures::spawn_local(async move {
+                match client.get::<Vec<TemplateInfo>>("/api/templates").await {
+                    Ok(gallery) => templates.set(Some(gallery)),
+                    Err(fetch_error) => error.set(Some(fetch_error)),
+                }
+            });
+        });
+    }
+
+    let Some(gallery) = (*templates).clone() else {
+        return match &*error {
+            Some(fetch_error) => page_error(fetch_error, retry),
+            None => html! { <div class="text-center">{"Loading..."}</div> },
+        };
+    };
+
+    html! {
+        <div class="min-h-screen bg-base-200">
+            <header class="bg-base-100 shadow">
+                <div class="container mx-auto px-4 py-6">
+                    <Breadcrumbs route={Route::Templates} />
+                    <h1 class="text-3xl font-bold">{"Templates"}</h1>
+                    <p class="opacity-70">{"Prepared timelines you can copy and make your own."}</p>
+                </div>
+            </header>
+            <main class="container mx-auto px-4 py-8">
+                if gallery.is_empty() {
+                    <p class="opacity-70">{"No timelines are offered as templates yet."}</p>
+                } else {
+                    <div class="grid gap-4 md:grid-cols-2 lg:grid-cols-3">
+                        {gallery.iter().map(|template| html! {
+                            <div class="card bg-base-100 shadow" key={template.id.clone()}>
+                                <div class="card-body">
+                                    <h2 class="card-title">
+                                        <a class="link link-hover" href={format!("/timelines/{}", template.id)}>
+                                            {&template.title}
+                                        </a>
+                                    </h2>
+                                    if let Some(description) = &template.description {
+                                        <p class="opacity-70">{description}</p>
+                                    }
+                                    <p class="text-sm opacity-70">
+                                        {format!("By {} · {} events", template.owner, template.event_count)}
+                                    </p>
+                                    if signed_in {
+                                        <div class="card-actions justify-end">
+                                            <UseTemplate timeline_id={template.id.clone()} title={template.title.clone()} />
+                                        </div>
+                                    }
+                                </div>
+                            </div>
+                        }).collect::<Html>()}
+                    </div>
+                }
+                if !signed_in {
+                    <p class="mt-6">{"Log in to copy a template. "}<a href="/login" class="link">{"Log in"}</a></p>
+                }
+            </main>
+        </div>
+    }
+}
+
+#[derive(Properties, PartialEq)]
+struct ArchivedBannerProps {
+    timeline: TimelineInfo,
+}
+
+/// Notice shown on pages belonging to a read-only (archived) timeline.
+#[function_component(ArchivedBanner)]
+fn archived_banner(props: &ArchivedBannerProps) -> Html {
+    match &props.timeline.archived_at {
+        Some(archived_at) => html! {
+            <div class="alert alert-info mb-6">
+                {format!(
+                    "\"{}\" was archived on {} and is read-only.",
+                    props.timeline.title,
+                    archived_at.chars().take(10).collect::<String>()
+                )}
+            </div>
+        },
+        None => html! {},
+    }
+}
+
+#[derive(Properties, PartialEq)]
+struct TimelineDetailProps {
+    id: String,
+}
+
+#[function_component(TimelineDetail)]
+fn timeline_detail(props: &TimelineDetailProps) -> Html {
+    let timeline = use_state(|| Option::<TimelineInfo>::None);
+    let error = use_state(|| Option::<FetchError>::None);
+    // Bumped when the Periods panel changes them, to redraw the bands.
+    let periods_revision = use_state(|| 0u32);
+    let (attempt, retry) = use_retry();
+    let errors = use_error_reporter();
+    let client = use_api();
+
+    {
+        let timeline = timeline.clone();
+        let error = error.clone();
+        let client = client.clone();
+        yew::use_effect_with((props.id.clone(), attempt), move |(id, _): &(String, u32)| {
+            let url = format!("/api/timelines/{}", id);
+            error.set(None);
+            let fetch_timeline = async move {
+                match client.get::<TimelineInfo>(&url).await {
+                    Ok(timeline_data) => timeline.set(Some(timeline_data)),
+                    Err(fetch_error) => error.set(Some(fetch_error)),
+                }
+            };
+            wasm_bindgen_futures::spawn_local(fetch_timeline);
+        });
+    }
+
+    let Some(timeline_data) = (*timeline).clone() else {
+        return match &*error {
+            Some(fetch_error) => page_error(fetch_error, retry),
+            None => html! { <div class="text-center">{"Loading..."}</div> },
+        };
+    };
+    let archived = timeline_data.archived_at.is_some();
+    let owner = timeline_data.access == "own";
+    let can_edit = owner || timeline_data.access == "edit";
+
+    let toggle_archive = {
+        let timeline = timeline.clone();
+        let id = props.id.clone();
+        let access = timeline_data.access.clone();
+        let errors = errors.clone();
+        let client = client.clone();
+        Callback::from(move |_| {
+            let timeline = timeline.clone();
+            let errors = errors.clone();
+            let url = format!("/api/timelines/{}/archive", id);
+            let access = access.clone();
+            let client = client.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                let request = if archived { Request::delete(&url) } else { Request::post(&url) };
+                match client.send::<TimelineInfo>(request).await {
+                    // The archive endpoints return the bare timeline, without `access`.
+                    Ok(timeline_data) => timeline.set(Some(TimelineInfo { access, ..timeline_data })),
+                    Err(error) => errors.report(error),
+                }
+            });
+        })
+    };
+
+    let refresh_periods = {
+        let periods_revision = periods_revision.clone();
+        Callback::from(move |_| periods_revision.set(*periods_revision + 1))
+    };
+
+    let toggle_template = {
+        let timeline = timeline.clone();
+        let id = props.id.clone();
+        let access = timeline_data.access.clone();
+        let is_template = timeline_data.is_template;
+        Callback::from(move |_| {
+            let timeline = timeline.clone();
+            let errors = errors.clone();
+            let url = format!("/api/timelines/{}", id);
+            let access = access.clone();
+            let body = serde_json::json!({ "is_template": !is_template });
+            let client = client.clone();
+            wasm_bindgen_futures::spawn_local(async move {
+                match client.send_json::<TimelineInfo>(Request::put(&url), &body).await {
+                    Ok(timeline_data) => timeline.set(Some(TimelineInfo { access, ..timeline_data })),
+                    Err(error) => errors.report(error),
+                }
+            });
+        })
+    };
+
+    html! {
+        <div class="min-h-screen bg-base-200">
+            <header class="bg-base-100 shadow">
+                <div class="container mx-auto px-4 py-6 flex items-center justify-between">
+                    <div>
+                        <Breadcrumbs
+                            route={Route::TimelineDetail { id: timeline_data.id.clone() }}
+                            timeline={timeline_data.title.clone()}
+                        />
+                        <h1 class="text-3xl font-bold">
+                            {&timeline_data.title}
+                            if timeline_data.is_private {
+                                <span class="badge badge-ghost ml-2 align-middle">{"Private"}</span>
+                            }
+                            if timeline_data.is_template {
+                                <span class="badge badge-accent ml-2 align-middle">{"Template"}</span>
+                            }
+                        </h1>
+                        if let Some(description) = &timeline_data.description {
+                            <p class="opacity-70">{description}</p>
+                        }
+                    </div>
+                    <div class="flex gap-2">
+                        <a class="btn btn-ghost btn-sm" href={format!("/timelines/{}/events", timeline_data.id)}>
+                            {"Events"}
+                        </a>
+                        <a class="btn btn-ghost btn-sm" href={format!("/api/timelines/{}/export.pdf", timeline_data.id)}>
+                            {"Download PDF"}
+                        </a>
+                        <a class="btn btn-ghost btn-sm" href={format!("/api/timelines/{}/export.csv", timeline_data.id)}>
+                            {"CSV"}
+                        </a>
+                        <a class="btn btn-ghost btn-sm" href={format!("/api/timelines/{}/export.json", timeline_data.id)}>
+                            {"JSON"}
+                        </a>
+                        if timeline_data.is_template && auth::token().is_some() {
+                            <UseTemplate timeline_id={timeline_data.id.clone()} title={timeline_data.title.clone()} />
+                        }
+                        if owner && !archived {
+                            <button class="btn btn-ghost btn-sm" onclick={toggle_template}>
+                                {if timeline_data.is_template { "Stop offering as template" } else { "Offer as template" }}
+                            </button>
+                        }
+                        if owner {
+                            <button class="btn btn-outline btn-sm" onclick={toggle_archive}>
+                                {if archived { "Unarchive" } else { "Archive" }}
+                            </button>
+                        }
+                    </div>
+                </div>
+            </header>
+            <main class="container mx-auto px-4 py-8">
+                <ArchivedBanner timeline={timeline_data.clone()} />
+                <Timeline
+                    timeline_id={Some(timeline_data.id.clone())}
+                    editable={!archived && can_edit}
+                    focus={query_param("date")}
+                    periods_revision={*periods_revision}
+                />
+                if !archived && can_edit {
+                    <Periods timeline_id={timeline_data.id.clone()} on_change={refresh_periods} />
+                    <CustomFields timeline_id={timeline_data.id.clone()} />
+                }
+                <Stories timeline_id={timeline_data.id.clone()} can_edit={!archived && can_edit} />
+                if auth::token().is_some() {
+                    <Members timeline_id={timeline_data.id.clone()} can_manage={owner} />
+                }
+            </main>
+        </div>
+    }
+}
+
+#[derive(Deserialize, Clone, PartialEq)]
+struct StoryStep {
+    event_id: String,
+    narration: Option<String>,
+    title: String,
+    start_date: String,
+}
+
+#[derive(Deserialize, Clone, PartialEq)]
+struct Story {
+    title: String,
+    description: Option<String>,
+    timeline_title: String,
+    steps: Vec<StoryStep>,
+}
+
+#[derive(Properties, PartialEq)]
+struct StoryViewerProps {
+    timeline_id: String,
+    story_id: String,
+}
+
+/// Steps through a story: the timeline centers on each step's event while
+/// its narration shows above. Left and right arrows move between steps.
+#[function_component(StoryViewer)]
+fn story_viewer(props: &StoryViewerProps) -> Html {
+    let client = use_api();
+    let story = use_state(|| Option::<Story>::None);
+    let step = use_state(|| 0usize);
+    let error = use_state(|| Option::<FetchError>::None);
+    let (attempt, retry) = use_retry();
+
+    {
+        let story = story.clone();
+        let step = step.clone();
+        let error = error.clone();
+        yew::use_effect_with((props.story_id.clone(), attempt), move |(id, _): &(String, u32)| {
+            let url = format!("/api/stories/{}", id);
+            error.set(None);
+            wasm_bindgen_futures::spawn_local(async move {
+                match client.get::<Story>(&url).await {
+                    Ok(story_data) => {
+                        step.set(0);
+                        story.set(Some(story_data));
+                    }
+                    Err(fetch_error) => error.set(Some(fetch_error)),
+                }
+            });
+        });
+    }
+
+    let count = story.as_ref().map_or(0, |story| story.steps.len());
+    let go = |offset: isize| {
+        let step = step.clone();
+        Callback::from(move |_: ()| {
+            let target = *step as isize + offset;
+            if (0..count as isize).contains(&target) {
+                step.set(target as usize);
+            }
+        })
+    };
+    let previous = go(-1);
+    let next = go(1);
+
+    // Arrow keys anywhere on the page, unless something focused (such as
+    // the timeline's track) already used them.
+    {
+        let previous = previous.clone();
+        let next = next.clone();
+        yew::use_effect_with((*step, count), move |_| {
+            let on_key = Closure::<dyn Fn(web_sys::KeyboardEvent)>::new(move |e: web_sys::KeyboardEvent| {
+                if e.default_prevented() {
+                    return;
+                }
+                match e.key().as_str() {
+                    "ArrowLeft" => previous.emit(()),
+                    "ArrowRight" => next.emit(()),
+                    _ => return,
+                }
+                e.prevent_default();
+            });
+            let window = gloo_utils::window();
+            window.add_event_listener_with_callback("keydown", on_key.as_ref().unchecked_ref()).ok();
+            move || {
+                window.remove_event_listener_with_callback("keydown", on_key.as_ref().unchecked_ref()).ok();
+            }
+        });
+    }
+
+    let Some(story_data) = (*story).clone() else {
+        return match &*error {
+            Some(fetch_error) => page_error(fetch_error, retry),
+            None => html! { <div class="text-center">{"Loading..."}</div> },
+        };
+    };
+    let current = story_data.steps.get(*step);
+
+    html! {
+        <div class="min-h-screen bg-base-200">
+            <header class="bg-base-100 shadow">
+                <div class="container mx-auto px-4 py-6">
+                    <Breadcrumbs
+                        route={Route::TimelineStory { id: props.timeline_id.clone(), story_id: props.story_id.clone() }}
+                        timeline={story_data.timeline_title.clone()}
+                        story={story_data.title.clone()}
+                    />
+                    <h1 class="text-3xl font-bold">{&story_data.title}</h1>
+                    if let Some(description) = &story_data.description {
+                        <p class="opacity-70">{description}</p>
+                    }
+                </div>
+            </header>
+            <main class="container mx-auto px-4 py-8">
+                <div class="card bg-base-100 shadow mb-6" aria-live="polite">
+                    <div class="card-body">
+                        if let Some(current) = current {
+                            <p class="text-sm opacity-70">
+                                {format!("Step {} of {} · {}", *step + 1, count, event_day(&current.start_date))}
+                            </p>
+                            <h2 class="card-title">
+                                <a class="link link-hover"
+                                    href={format!("/timelines/{}/events/{}", props.timeline_id, current.event_id)}>
+                                    {&current.title}
+                                </a>
+                            </h2>
+                            if let Some(narration) = &current.narration {
+                                <p class="whitespace-pre-line">{narration}</p>
+                            }
+                        } else {
+                            <p class="opacity-70">{"This story has no steps you can see yet."}</p>
+                        }
+                        <div class="card-actions justify-end">
+                            <button class="btn btn-sm" disabled={*step == 0} onclick={previous.reform(|_| ())}>
+                                {"← Previous"}
+                            </button>
+                            <button class="btn btn-primary btn-sm" disabled={*step + 1 >= count}
+                                onclick={next.reform(|_| ())}>
+                                {"Next →"}
+                            </button>
+                        </div>
+                    </div>
+                </div>
+                <Timeline
+                    timeline_id={Some(props.timeline_id.clone())}
+                    highlight={current.map(|current| current.event_id.clone())}
+                />
+            </main>
+        </div>
+    }
+}
+
+/// Every public event on one timeline; `?date=` centers the view.
+#[function_component(TimelinePage)]
+fn timeline_page() -> Html {
+    let month = query_param("date").and_then(|date| time_scale::parse_date(&date)).map(month_of);
+    html! {
+        <div class="min-h-screen bg-base-200">
+            <header class="bg-base-100 shadow">
+                <div class="container mx-auto px-4 py-6 flex items-center justify-between">
+                    <h1 class="text-3xl font-bold">{"Timeline"}</h1>
+                    {view_switch("timeline", month)}
+                </div>
+            </header>
+            <main class="container mx-auto px-4 py-8">
+                <Timeline focus={query_param("date")} />
+            </main>
+        </div>
+    }
+}
+
+const MONTHS: [&str; 12] = [
+    "January", "February", "March", "April", "May", "June", "July", "August", "September", "October", "November",
+    "December",
+];
+const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+/// Events listed in a calendar cell before "+N more".
+const DAY_EVENTS: usize = 3;
+
+/// The `(year, month)` a day falls in.
+fn month_of(days: f64) -> (i64, u32) {
+    let (year, month, _) = time_scale::civil_from_days(days.floor() as i64);
+    (year, month)
+}
+
+/// `YYYY-MM`, as `?month=` takes it.
+fn month_param((year, month): (i64, u32)) -> String {
+    let first = time_scale::format_day(time_scale::days_from_civil(year, month, 1) as f64);
+    first[..first.len() - 3].to_string()
+}
+
+/// Switches between the timeline and the calendar, keeping roughly the
+/// same moment in view.
+fn view_switch(current: &str, month: Option<(i64, u32)>) -> Html {
+    let timeline = match month {
+        Some((year, month)) => format!(
+            "/timeline?date={}",
+            time_scale::format_day(time_scale::days_from_civil(year, month, 15) as f64)
+        ),
+        None => "/timeline".to_string(),
+    };
+    let calendar = match month {
+        Some(month) => format!("/calendar?month={}", month_param(month)),
+        None => "/calendar".to_string(),
+    };
+    let tab = |name: &str, label: &'static str, href: String| {
+        let active = current == name;
+        html! {
+            <a role="tab" class={if active { "tab tab-active" } else { "tab" }} href={href}
+                aria-selected={active.to_string()}>
+                {label}
+            </a>
+        }
+    };
+    html! {
+        <div role="tablist" class="tabs tabs-boxed">
+            {tab("timeline", "Timeline", timeline)}
+            {tab("calendar", "Calendar", calendar)}
+        </div>
+    }
+}
+
+/// A month of public events laid out on a calendar; `?month=YYYY-MM`
+/// picks the month, this one by default.
+#[function_component(Calendar)]
+fn calendar() -> Html {
+    let client = use_api();
+    let month = use_state(|| {
+        query_param("month")
+            .and_then(|month| time_scale::parse_date(&format!("{}-01", month)))
+            .map(month_of)
+            .unwrap_or_else(|| {
+                let today = js_sys::Date::new_0();
+                (today.get_full_year() as i64, today.get_month() + 1)
+            })
+    });
+    let events = use_state(|| Option::<EventList>::None);
+    let error = use_state(|| Option::<FetchError>::None);
+    let expanded = use_state(|| Option::<i64>::None);
+    let (attempt, retry) = use_retry();
+
+    let (year, number) = *month;
+    let first = time_scale::days_from_civil(year, number, 1);
+    let next = if number == 12 { (year + 1, 1) } else { (year, number + 1) };
+    let previous = if number == 1 { (year - 1, 12) } else { (year, number - 1) };
+    let length = time_scale::days_from_civil(next.0, next.1, 1) - first;
+    let title = format!("{} {}", MONTHS[number as usize - 1], time_scale::year_label(year));
+
+    {
+        let events = events.clone();
+        let error = error.clone();
+        let expanded = expanded.clone();
+        yew::use_effect_with((*month, attempt), move |(month, _): &((i64, u32), u32)| {
+            breadcrumbs::keep_list_url(&format!("/calendar?month={}", month_param(*month)));
+            error.set(None);
+            events.set(None);
+            expanded.set(None);
+            let url = format!(
+                "/api/events?from={}&to={}&sort=oldest&limit=100",
+                time_scale::format_day(first as f64),
+                time_scale::format_day((first + length - 1) as f64)
+            );
+            wasm_bindgen_futures::spawn_local(async move {
+                match client.get::<EventList>(&url).await {
+                    Ok(list) => events.set(Some(list)),
+                    Err(fetch_error) => error.set(Some(fetch_error)),
+                }
+            });
+        });
+    }
+
+    let go_to = |target: (i64, u32)| {
+        let month = month.clone();
+        Callback::from(move |_: yew::MouseEvent| month.set(target))
+    };
+    let today = {
+        let now = js_sys::Date::new_0();
+        (now.get_full_year() as i64, now.get_month() + 1)
+    };
+    let expand = |day: i64| {
+        let expanded = expanded.clone();
+        Callback::from(move |_: yew::MouseEvent| expanded.set(Some(day)))
+    };
+
+    let body = match (&*events, &*error) {
+        (_, Some(fetch_error)) => page_error(fetch_error, retry),
+        (None, None) => html! { <div class="text-center">{"Loading…"}</div> },
+        (Some(list), None) => {
+            // Each event sits on every day of the month it spans.
+            let mut days = vec![Vec::<&Event>::new(); length as usize];
+            for event in &list.data {
+                let Some(start) = time_scale::parse_date(&event.start_date) else {
+                    continue;
+                };
+                let end = event.end_date.as_deref().and_then(time_scale::parse_date).unwrap_or(start);
+                let start = (start.floor() as i64 - first).max(0);
+                let end = (end.floor() as i64 - first).min(length - 1);
+                for day in start..=end {
+                    days[day as usize].push(event);
+                }
+            }
+            // Monday is the first column; 1970-01-01 was a Thursday.
+            let offset = (first + 3).rem_euclid(7);
+            html! {
+                <>
+                    if list.total > list.data.len() as i64 {
+                        <p class="opacity-70 mb-2">
+                            {format!("Showing the first {} of {} events this month.", list.data.len(), list.total)}
+                        </p>
+                    }
+                    <div class="calendar-grid" role="grid" aria-label={title.clone()}>
+                        {WEEKDAYS.iter().map(|name| html! {
+                            <div class="calendar-weekday" role="columnheader">{*name}</div>
+                        }).collect::<Html>()}
+                        {(0..offset).map(|_| html! {
+                            <div class="calendar-day calendar-day-empty"></div>
+                        }).collect::<Html>()}
+                        {days.iter().enumerate().map(|(index, on_day)| {
+                            let day = index as i64;
+                            let shown = if *expanded == Some(day) { on_day.len() } else { DAY_EVENTS };
+                            html! {
+                                <div class="calendar-day" role="gridcell">
+                                    <span class="calendar-date">{index + 1}</span>
+                                    <ul>
+                                        {on_day.iter().take(shown).map(|event| html! {
+                                            <li>
+                                                <a class="calendar-event" title={event.title.clone()}
+                                                    href={event_path(event.timeline_id.as_deref(), &event.id)}>
+                                                    {&event.title}
+                                                </a>
+                                            </li>
+                                        }).collect::<Html>()}
+                                    </ul>
+                                    if on_day.len() > shown {
+                                        <button class="btn btn-link btn-xs px-0" onclick={expand(day)}>
+                                            {format!("+{} more", on_day.len() - shown)}
+                                        </button>
+                                    }
+                                </div>
+                            }
+                        }).collect::<Html>()}
+                    </div>
+                </>
+            }
+        }
+    };
+
+    html! {
+        <div class="min-h-screen bg-base-200">
+            <header class="bg-base-100 shadow">
+                <div class="container mx-auto px-4 py-6">
+                    <Breadcrumbs route={Route::Calendar} />
+                    <div class="flex items-center justify-between">
+                        <h1 class="text-3xl font-bold">{"Calendar"}</h1>
+                        {view_switch("calendar", Some(*month))}
+                    </div>
+                </div>
+            </header>
+            <main class="container mx-auto px-4 py-8">
+                <div class="flex items-center justify-between mb-4">
+                    <h2 class="text-2xl font-semibold">{title}</h2>
+                    <div class="join">
+                        <button class="join-item btn btn-sm" aria-label="Previous month" onclick={go_to(previous)}>
+                            {"«"}
+                        </button>
+                        <button class="join-item btn btn-sm" disabled={*month == today} onclick={go_to(today)}>
+                            {"Today"}
+                        </button>
+                        <button class="join-item btn btn-sm" aria-label="Next month" onclick={go_to(next)}>
+                            {"»"}
+                        </button>
+                    </div>
+                </div>
+                {body}
+            </main>
+        </div>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
+
+    use super::*;
+    use crate::api::Method;
+    use crate::testing::{self, MockClient};
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    fn event(id: &str, title: &str) -> serde_json::Value {
+        serde_json::json!({
+            "id": id,
+            "title": title,
+            "description": "Seen live around the world.",
+            "start_date": "1969-07-20T20:17:00",
+            "end_date": null,
+            "location": null,
+            "image_url": null,
+            "category": "Science",
+            "created_at": "2024-01-01T00:00:00",
+            "updated_at": "2024-01-01T00:00:00",
+            "timeline_id": null,
+            "status": "published",
+        })
+    }
+
+    fn recognize(path: &str) -> Option<Route> {
+        Route::recognize(path)
+    }
+
+    fn click(root: &web_sys::Element, selector: &str) {
+        let element = root.query_selector(selector).unwrap().expect(selector);
+        element.dyn_into::<web_sys::HtmlElement>().unwrap().click();
+    }
+
+    #[wasm_bindgen_test]
+    fn nested_paths_reach_the_most_specific_route() {
+        let event = Route::TimelineEvent { id: "t1".into(), event_id: "e1".into() };
+        assert!(recognize("/timelines/t1/events/e1") == Some(event));
+        assert!(recognize("/timelines/t1/events") == Some(Route::TimelineEvents { id: "t1".into() }));
+        assert!(recognize("/timelines/t1") == Some(Route::TimelineDetail { id: "t1".into() }));
+        assert!(recognize("/events/e1/merge/e2") == Some(Route::MergeEvents { id: "e1".into(), other_id: "e2".into() }));
+        assert!(recognize("/events/e1") == Some(Route::EventDetail { id: "e1".into() }));
+        assert!(recognize("/calendar") == Some(Route::Calendar));
+    }
+
+    #[wasm_bindgen_test]
+    async fn an_event_path_renders_its_detail_page() {
+        let client = MockClient::default().reply(Method::Get, "/api/events/e1", 200, event("e1", "Apollo 11 lands"));
+        let page = switch(recognize("/events/e1").unwrap());
+
+        let root = testing::mount(Rc::new(client), page).await;
+
+        assert_eq!(testing::texts(&root, "h2.card-title"), ["Apollo 11 lands"]);
+    }
+
+    #[wasm_bindgen_test]
+    async fn lists_events_as_cards() {
+        let list = serde_json::json!({
+            "data": [event("e1", "Apollo 11 lands"), event("e2", "Sputnik launches")],
+            "total": 2,
+        });
+        let client = Rc::new(MockClient::default().reply(Method::Get, "/api/events", 200, list));
+
+        let root = testing::mount(client.clone(), html! { <Events /> }).await;
+
+        let titles = testing::texts(&root, ".card-title");
+        assert!(titles.iter().any(|title| title.ends_with("Apollo 11 lands")), "{:?}", titles);
+        assert!(titles.iter().any(|title| title.ends_with("Sputnik launches")), "{:?}", titles);
+        assert!(client.requests().iter().any(|(method, url)| *method == Method::Get && url.starts_with("/api/events?")));
+    }
+
+    #[wasm_bindgen_test]
+    async fn a_failed_list_shows_the_api_error() {
+        let failure = serde_json::json!({ "error": "Unknown sort order" });
+        let client = MockClient::default().reply(Method::Get, "/api/events", 422, failure);
+
+        let root = testing::mount(Rc::new(client), html! { <Events /> }).await;
+
+        assert_eq!(testing::texts(&root, ".alert-error span"), ["Unknown sort order"]);
+        assert!(testing::texts(&root, ".card-title").is_empty());
+    }
+
+    #[wasm_bindgen_test]
+    async fn a_missing_event_offers_a_retry() {
+        let client = Rc::new(MockClient::default());
+        let detail = |client: &MockClient| {
+            client.requests().iter().filter(|(_, url)| url == "/api/events/e1").count()
+        };
+
+        let root = testing::mount(client.clone(), html! { <EventDetail id="e1" /> }).await;
+        assert_eq!(testing::texts(&root, ".alert-error span"), ["Not found."]);
+        assert_eq!(detail(&client), 1);
+
+        click(&root, ".alert-error button");
+        testing::settle().await;
+        assert_eq!(detail(&client), 2);
+    }
+
+    #[wasm_bindgen_test]
+    async fn disabled_accounts_can_only_be_enabled() {
+        let user = |id: &str, status: &str| serde_json::json!({
+            "id": id, "username": id, "email": format!("{}@example.com", id), "role": "user", "status": status,
+            "has_password": true, "created_at": "2024-05-01T12:00:00", "timelines": 2, "events": 9, "comments": 1,
+        });
+        let list = serde_json::json!({ "data": [user("ada", "active"), user("bob", "disabled")], "total": 2, "pages": 1 });
+        let client = MockClient::default().reply(Method::Get, "/api/admin/users", 200, list);
+
+        let root = testing::mount(Rc::new(client), html! { <AdminUsers /> }).await;
+
+        assert_eq!(testing::texts(&root, "tbody tr:first-child button"), ["View as", "Make admin", "Disable", "Reset password"]);
+        assert_eq!(testing::texts(&root, "tbody tr:last-child button"), ["Enable"]);
+    }
+
+    #[wasm_bindgen_test]
+    async fn custom_field_values_show_under_their_labels() {
+        let mut tudor = event("e1", "Battle of Bosworth");
+        tudor["timeline_id"] = serde_json::json!("t1");
+        tudor["custom_fields"] = serde_json::json!({ "battle_deaths": 1000, "decisive": true });
+        let field = |name: &str, kind: &str| serde_json::json!({
+            "id": name, "name": name, "kind": kind, "required": false, "position": 0,
+        });
+        let fields = serde_json::json!([field("dynasty", "text"), field("battle_deaths", "number"), field("decisive", "boolean")]);
+        let client = MockClient::default()
+            .reply(Method::Get, "/api/events/e1", 200, tudor)
+            .reply(Method::Get, "/api/timelines/t1/fields", 200, fields);
+
+        let root = testing::mount(Rc::new(client), html! { <EventDetail id="e1" /> }).await;
+
+        let shown = testing::texts(&root, ".card-body p");
+        assert!(shown.contains(&"Battle deaths: 1000".to_string()));
+        assert!(shown.contains(&"Decisive: Yes".to_string()));
+        assert!(!shown.iter().any(|text| text.starts_with("Dynasty")));
+    }
+}
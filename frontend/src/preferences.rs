@@ -0,0 +1,94 @@
+//! The signed-in user's settings from `/api/me/preferences`. A copy is kept
+//! in local storage, so the theme is right before the API has answered and
+//! pages can read the settings without waiting for it.
+
+use serde::{Deserialize, Serialize};
+use web_sys::Storage;
+
+use crate::api::{ApiClient, FetchError, Request};
+
+const PREFERENCES_KEY: &str = "preferences";
+
+/// Values and labels of the settings' choices; the server accepts the same.
+pub const THEMES: &[(&str, &str)] = &[("system", "Match my device"), ("light", "Light"), ("dark", "Dark")];
+pub const SORTS: &[(&str, &str)] =
+    &[("newest", "Newest first"), ("oldest", "Oldest first"), ("likes", "Most liked")];
+pub const LOCALES: &[(&str, &str)] = &[("en", "English"), ("de", "Deutsch"), ("es", "Español"), ("fr", "Français")];
+pub const PAGE_SIZES: &[i16] = &[10, 20, 50, 100];
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct Preferences {
+    /// Timeline the home page opens instead of all events.
+    pub default_timeline_id: Option<String>,
+    pub theme: String,
+    /// Language of emails and of the page's `lang`.
+    pub locale: String,
+    pub items_per_page: i16,
+    pub default_sort: String,
+}
+
+impl Default for Preferences {
+    fn default() -> Self {
+        Self {
+            default_timeline_id: None,
+            theme: "system".to_string(),
+            locale: "en".to_string(),
+            items_per_page: 20,
+            default_sort: "newest".to_string(),
+        }
+    }
+}
+
+fn storage() -> Option<Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+/// The settings last loaded or saved on this device, or the defaults.
+pub fn cached() -> Preferences {
+    storage()
+        .and_then(|storage| storage.get_item(PREFERENCES_KEY).ok()?)
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+/// Caches and applies `preferences` on this device only.
+pub fn store(preferences: &Preferences) {
+    apply(preferences);
+    if let (Some(storage), Ok(json)) = (storage(), serde_json::to_string(preferences)) {
+        storage.set_item(PREFERENCES_KEY, &json).ok();
+    }
+}
+
+/// Forgets this device's copy, e.g. on logout.
+pub fn clear() {
+    if let Some(storage) = storage() {
+        storage.remove_item(PREFERENCES_KEY).ok();
+    }
+}
+
+/// Sets the daisyUI theme and the page language. Without a theme of its
+/// own, daisyUI follows the browser's light or dark mode.
+pub fn apply(preferences: &Preferences) {
+    let Some(root) = gloo_utils::document().document_element() else {
+        return;
+    };
+    match preferences.theme.as_str() {
+        "system" => root.remove_attribute("data-theme").ok(),
+        theme => root.set_attribute("data-theme", theme).ok(),
+    };
+    root.set_attribute("lang", &preferences.locale).ok();
+}
+
+/// Fetches the saved settings, then caches and applies them.
+pub async fn load(client: &ApiClient) -> Result<Preferences, FetchError> {
+    let preferences = client.get::<Preferences>("/api/me/preferences").await?;
+    store(&preferences);
+    Ok(preferences)
+}
+
+/// Stores `preferences` right away, then saves them. Callers `store` the
+/// previous settings again if saving fails.
+pub async fn save(client: &ApiClient, preferences: &Preferences) -> Result<Preferences, FetchError> {
+    store(preferences);
+    client.send_json::<Preferences>(Request::put("/api/me/preferences"), preferences).await
+}
@@ -0,0 +1,221 @@
+//! Real user monitoring: a handful of timings from a sample of page loads,
+//! posted to `/api/rum` with `navigator.sendBeacon`.
+//!
+//! Beacons carry no identifiers; paths are reduced to route patterns.
+
+use std::cell::{Cell, RefCell};
+
+use js_sys::Reflect;
+use serde::Serialize;
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use web_sys::VisibilityState;
+
+const ENDPOINT: &str = "/api/rum";
+/// Fraction of page loads that report anything at all.
+const SAMPLE_RATE: f64 = 0.1;
+/// Flush early once this many samples are buffered.
+const FLUSH_AT: usize = 10;
+
+#[derive(Serialize)]
+struct Sample {
+    metric: &'static str,
+    value: f64,
+}
+
+#[derive(Serialize)]
+struct Beacon<'a> {
+    path: String,
+    samples: &'a [Sample],
+}
+
+thread_local! {
+    static SAMPLED: bool = js_sys::Math::random() < SAMPLE_RATE;
+    static BUFFER: RefCell<Vec<Sample>> = const { RefCell::new(Vec::new()) };
+    static RECORDED_ONCE: RefCell<Vec<&'static str>> = const { RefCell::new(Vec::new()) };
+    static WORST_INTERACTION: Cell<f64> = const { Cell::new(0.0) };
+}
+
+/// Milliseconds since navigation start.
+pub fn now() -> f64 {
+    web_sys::window()
+        .and_then(|window| window.performance())
+        .map(|performance| performance.now())
+        .unwrap_or(0.0)
+}
+
+/// Starts page-level collection. Called once from the wasm entry point.
+pub fn init() {
+    if !SAMPLED.with(|sampled| *sampled) {
+        return;
+    }
+
+    record("wasm_init", now());
+    listen_for_interactions();
+    flush_when_hidden();
+}
+
+pub fn record(metric: &'static str, value: f64) {
+    if !SAMPLED.with(|sampled| *sampled) {
+        return;
+    }
+
+    let buffered = BUFFER.with(|buffer| {
+        let mut buffer = buffer.borrow_mut();
+        buffer.push(Sample { metric, value });
+        buffer.len()
+    });
+    if buffered >= FLUSH_AT {
+        flush();
+    }
+}
+
+/// Records `metric` only the first time it is reported during this page load.
+pub fn record_once(metric: &'static str, value: f64) {
+    let first = RECORDED_ONCE.with(|recorded| {
+        let mut recorded = recorded.borrow_mut();
+        if recorded.contains(&metric) {
+            false
+        } else {
+            recorded.push(metric);
+            true
+        }
+    });
+    if first {
+        record(metric, value);
+    }
+}
+
+pub fn flush() {
+    let worst = WORST_INTERACTION.with(|worst| worst.replace(0.0));
+    if worst > 0.0 {
+        BUFFER.with(|buffer| {
+            buffer.borrow_mut().push(Sample {
+                metric: "interaction_latency",
+                value: worst,
+            })
+        });
+    }
+
+    let samples = BUFFER.with(|buffer| std::mem::take(&mut *buffer.borrow_mut()));
+    if samples.is_empty() {
+        return;
+    }
+    let Some(window) = web_sys::window() else {
+        return;
+    };
+
+    let path = route_pattern(&window.location().pathname().unwrap_or_default());
+    if let Ok(body) = serde_json::to_string(&Beacon {
+        path,
+        samples: &samples,
+    }) {
+        window
+            .navigator()
+            .send_beacon_with_opt_str(ENDPOINT, Some(&body))
+            .ok();
+    }
+}
+
+/// Navigation timing is only complete after load, so it is read lazily.
+fn record_navigation_timing() {
+    let Some(performance) = web_sys::window().and_then(|window| window.performance()) else {
+        return;
+    };
+    let entry = performance.get_entries_by_type("navigation").get(0);
+    if entry.is_undefined() {
+        return;
+    }
+
+    let field = |name: &str| {
+        Reflect::get(&entry, &JsValue::from_str(name))
+            .ok()
+            .and_then(|value| value.as_f64())
+            .filter(|value| *value > 0.0)
+    };
+    if let Some(value) = field("responseStart") {
+        record_once("ttfb", value);
+    }
+    if let Some(value) = field("domContentLoadedEventEnd") {
+        record_once("dom_content_loaded", value);
+    }
+}
+
+/// Tracks the worst input-to-next-frame delay seen on this page.
+fn listen_for_interactions() {
+    let Some(window) = web_sys::window() else {
+        return;
+    };
+
+    let on_input = Closure::<dyn Fn(web_sys::Event)>::new(|event: web_sys::Event| {
+        let started = event.time_stamp();
+        let after_paint = Closure::once_into_js(move || {
+            let latency = now() - started;
+            WORST_INTERACTION.with(|worst| {
+                if latency > worst.get() {
+                    worst.set(latency);
+                }
+            });
+        });
+        // A timeout scheduled from rAF runs just after the frame is painted.
+        let next_frame = Closure::once_into_js(move || {
+            if let Some(window) = web_sys::window() {
+                window
+                    .set_timeout_with_callback(after_paint.unchecked_ref())
+                    .ok();
+            }
+        });
+        if let Some(window) = web_sys::window() {
+            window
+                .request_animation_frame(next_frame.unchecked_ref())
+                .ok();
+        }
+    });
+
+    for event_type in ["pointerdown", "keydown"] {
+        window
+            .add_event_listener_with_callback_and_bool(
+                event_type,
+                on_input.as_ref().unchecked_ref(),
+                true,
+            )
+            .ok();
+    }
+    // Lives for the whole page.
+    on_input.forget();
+}
+
+fn flush_when_hidden() {
+    let Some(document) = web_sys::window().and_then(|window| window.document()) else {
+        return;
+    };
+
+    let on_change = Closure::<dyn Fn()>::new(|| {
+        let hidden = web_sys::window()
+            .and_then(|window| window.document())
+            .is_some_and(|document| document.visibility_state() == VisibilityState::Hidden);
+        if hidden {
+            record_navigation_timing();
+            flush();
+        }
+    });
+    document
+        .add_event_listener_with_callback("visibilitychange", on_change.as_ref().unchecked_ref())
+        .ok();
+    on_change.forget();
+}
+
+/// Replaces id-like path segments so beacons can't identify what was viewed.
+fn route_pattern(path: &str) -> String {
+    path.split('/')
+        .map(|segment| {
+            let looks_like_id = segment.len() == 36 && segment.matches('-').count() == 4
+                || (!segment.is_empty() && segment.chars().all(|c| c.is_ascii_digit()));
+            if looks_like_id {
+                ":id"
+            } else {
+                segment
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
@@ -0,0 +1,81 @@
+//! Helpers for the browser tests (`wasm-pack test --headless --firefox`):
+//! an `HttpClient` that answers from a script, and mounting a page under
+//! it.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use wasm_bindgen_futures::JsFuture;
+use yew::{Children, Html};
+
+use crate::api::{ApiClient, HttpClient, Method, Pending, Request, Response};
+use crate::components::api_provider::{ApiProvider, ApiProviderProps};
+
+/// Answers scripted requests and records every request made. Anything
+/// not scripted gets a 404, so a test only scripts what it looks at.
+#[derive(Default)]
+pub struct MockClient {
+    replies: Vec<(Method, String, u16, String)>,
+    requests: RefCell<Vec<(Method, String)>>,
+}
+
+impl MockClient {
+    /// Answers `method` on `url` with `status` and `body`. The URL is
+    /// matched with or without its query string.
+    pub fn reply(mut self, method: Method, url: &str, status: u16, body: serde_json::Value) -> Self {
+        self.replies.push((method, url.to_string(), status, body.to_string()));
+        self
+    }
+
+    /// The requests made so far, oldest first.
+    pub fn requests(&self) -> Vec<(Method, String)> {
+        self.requests.borrow().clone()
+    }
+}
+
+impl HttpClient for MockClient {
+    fn fetch(&self, request: Request) -> Pending {
+        self.requests.borrow_mut().push((request.method, request.url.clone()));
+        let path = request.url.split('?').next().unwrap_or_default();
+        let response = self
+            .replies
+            .iter()
+            .find(|(method, url, _, _)| *method == request.method && (*url == request.url || url == path))
+            .map(|(_, _, status, body)| Response { status: *status, body: body.clone() })
+            .unwrap_or(Response { status: 404, body: String::new() });
+        Box::pin(async move { Ok(response) })
+    }
+}
+
+/// Renders `content` into a new element on the page, answering its
+/// requests from `client`, and waits for it to settle.
+pub async fn mount(client: Rc<MockClient>, content: Html) -> web_sys::Element {
+    let document = gloo_utils::document();
+    let root = document.create_element("div").unwrap();
+    document.body().unwrap().append_child(&root).unwrap();
+    let props = ApiProviderProps { client: ApiClient(client), children: Children::new(vec![content]) };
+    yew::Renderer::<ApiProvider>::with_root_and_props(root.clone(), props).render();
+    settle().await;
+    root
+}
+
+/// Lets pending requests answer and the components re-render.
+pub async fn settle() {
+    for _ in 0..5 {
+        let tick = js_sys::Promise::new(&mut |resolve, _| {
+            gloo_utils::window()
+                .set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, 20)
+                .unwrap();
+        });
+        JsFuture::from(tick).await.unwrap();
+    }
+}
+
+/// Text of every element matching `selector` under `root`.
+pub fn texts(root: &web_sys::Element, selector: &str) -> Vec<String> {
+    let found = root.query_selector_all(selector).unwrap();
+    (0..found.length())
+        .filter_map(|index| found.item(index)?.text_content())
+        .map(|text| text.trim().to_string())
+        .collect()
+}
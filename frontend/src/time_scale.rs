@@ -0,0 +1,257 @@
+//! Date arithmetic and the linear date <-> pixel mapping used by the timeline.
+//!
+//! Dates are handled as fractional days since 1970-01-01 (proleptic
+//! Gregorian, astronomical year numbering), which keeps BCE dates and
+//! millennia-wide zoom levels in plain `f64` arithmetic.
+
+/// Days since 1970-01-01 for a civil date.
+pub fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month = month as i64;
+    let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Civil `(year, month, day)` for days since 1970-01-01.
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// Parses the API's `YYYY-MM-DDTHH:MM:SS` (or a bare date, optionally with a
+/// leading `-` for BCE years) into fractional days.
+pub fn parse_date(value: &str) -> Option<f64> {
+    let (negative, value) = match value.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, value),
+    };
+    let (date, time) = value.split_once('T').unwrap_or((value, "00:00:00"));
+
+    let mut parts = date.splitn(3, '-');
+    let year: i64 = parts.next()?.parse().ok()?;
+    let month: u32 = parts.next()?.parse().ok()?;
+    let day: u32 = parts.next()?.parse().ok()?;
+    let year = if negative { -year } else { year };
+
+    let mut clock = time.split(':').map(|part| part.parse::<f64>().unwrap_or(0.0));
+    let hours = clock.next().unwrap_or(0.0);
+    let minutes = clock.next().unwrap_or(0.0);
+
+    Some(days_from_civil(year, month, day) as f64 + (hours + minutes / 60.0) / 24.0)
+}
+
+/// Formats whole days back into the API's `NaiveDateTime` representation.
+pub fn format_date(days: f64) -> String {
+    let (year, month, day) = civil_from_days(days.floor() as i64);
+    if year < 0 {
+        format!("-{:04}-{:02}-{:02}T00:00:00", -year, month, day)
+    } else {
+        format!("{:04}-{:02}-{:02}T00:00:00", year, month, day)
+    }
+}
+
+/// `YYYY-MM-DD` (with a leading `-` for BCE years) for a query parameter.
+pub fn format_day(days: f64) -> String {
+    let date = format_date(days);
+    date.split('T').next().unwrap_or_default().to_string()
+}
+
+/// Short human label for an axis tick or a dragged marker.
+pub fn label(days: f64, unit: Unit) -> String {
+    let (year, month, day) = civil_from_days(days.floor() as i64);
+    let year_label = year_label(year);
+    match unit {
+        Unit::Day => format!("{} {:02}-{:02}", year_label, month, day),
+        Unit::Month => format!("{} {:02}", year_label, month),
+        _ => year_label,
+    }
+}
+
+/// An astronomical year as people write it: `0` is "1 BCE".
+pub fn year_label(year: i64) -> String {
+    if year <= 0 {
+        format!("{} BCE", 1 - year)
+    } else {
+        year.to_string()
+    }
+}
+
+/// How sure an event's start is, from its bounds: "c. 1430–1440" when
+/// both are known, "after 1430" or "before 1440" when one is.
+pub fn circa(min: Option<&str>, max: Option<&str>) -> Option<String> {
+    let year = |date: &str| parse_date(date).map(|days| year_label(civil_from_days(days.floor() as i64).0));
+    match (min.and_then(year), max.and_then(year)) {
+        (Some(first), Some(last)) if first == last => Some(format!("c. {}", first)),
+        (Some(first), Some(last)) => Some(format!("c. {}–{}", first, last)),
+        (Some(first), None) => Some(format!("after {}", first)),
+        (None, Some(last)) => Some(format!("before {}", last)),
+        (None, None) => None,
+    }
+}
+
+/// Granularity that dates snap to and ticks are drawn at.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Unit {
+    Day,
+    Month,
+    Year,
+    Decade,
+    Century,
+}
+
+/// Linear mapping of the visible date range `[start, end)` onto `width` pixels.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TimeScale {
+    pub start: f64,
+    pub end: f64,
+    pub width: f64,
+}
+
+/// Never zoom in past one day across the view, or out past 20,000 years.
+const MIN_SPAN_DAYS: f64 = 1.0;
+const MAX_SPAN_DAYS: f64 = 20_000.0 * 365.25;
+
+impl TimeScale {
+    /// A scale covering `days` with a 5% margin on both sides.
+    pub fn fit(days: impl IntoIterator<Item = f64>, width: f64) -> Self {
+        let (min, max) = days
+            .into_iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), day| (min.min(day), max.max(day)));
+        if !min.is_finite() {
+            let today = js_sys::Date::now() / 86_400_000.0;
+            return Self { start: today - 365.0, end: today + 365.0, width };
+        }
+        let margin = ((max - min) * 0.05).max(30.0);
+        Self { start: min - margin, end: max + margin, width }
+    }
+
+    /// A scale `span` days wide centered on `day`.
+    pub fn centered(day: f64, span: f64, width: f64) -> Self {
+        let span = span.clamp(MIN_SPAN_DAYS, MAX_SPAN_DAYS);
+        Self { start: day - span / 2.0, end: day + span / 2.0, width }
+    }
+
+    pub fn span(&self) -> f64 {
+        self.end - self.start
+    }
+
+    pub fn x(&self, day: f64) -> f64 {
+        (day - self.start) / self.span() * self.width
+    }
+
+    pub fn day_at(&self, x: f64) -> f64 {
+        self.start + x / self.width * self.span()
+    }
+
+    pub fn with_width(self, width: f64) -> Self {
+        Self { width, ..self }
+    }
+
+    /// Zooms by `factor` (> 1 zooms in) keeping the date under `anchor_x` fixed.
+    pub fn zoom(&self, factor: f64, anchor_x: f64) -> Self {
+        let anchor = self.day_at(anchor_x);
+        let span = (self.span() / factor).clamp(MIN_SPAN_DAYS, MAX_SPAN_DAYS);
+        let start = anchor - anchor_x / self.width * span;
+        Self { start, end: start + span, width: self.width }
+    }
+
+    /// Shifts the view by `dx` pixels (positive moves towards earlier dates).
+    pub fn pan(&self, dx: f64) -> Self {
+        let shift = dx / self.width * self.span();
+        Self { start: self.start - shift, end: self.end - shift, width: self.width }
+    }
+
+    /// The unit dates snap to at this zoom: fine enough that one step is a
+    /// few pixels wide, so a drag still feels continuous.
+    pub fn snap_unit(&self) -> Unit {
+        let days_per_px = self.span() / self.width;
+        if days_per_px < 3.0 {
+            Unit::Day
+        } else if days_per_px < 90.0 {
+            Unit::Month
+        } else {
+            Unit::Year
+        }
+    }
+
+    pub fn snap(&self, day: f64) -> f64 {
+        snap_to(day, self.snap_unit())
+    }
+
+    /// Tick unit giving roughly one label per 100px.
+    pub fn tick_unit(&self) -> Unit {
+        let days_per_tick = self.span() / self.width * 100.0;
+        if days_per_tick < 20.0 {
+            Unit::Day
+        } else if days_per_tick < 300.0 {
+            Unit::Month
+        } else if days_per_tick < 3_000.0 {
+            Unit::Year
+        } else if days_per_tick < 30_000.0 {
+            Unit::Decade
+        } else {
+            Unit::Century
+        }
+    }
+
+    /// Tick positions (in days) across the visible range.
+    pub fn ticks(&self) -> Vec<f64> {
+        let unit = self.tick_unit();
+        let mut ticks = Vec::new();
+        let mut tick = snap_down(self.start, unit);
+        while tick < self.end && ticks.len() < 200 {
+            if tick >= self.start {
+                ticks.push(tick);
+            }
+            tick = step(tick, unit);
+        }
+        ticks
+    }
+}
+
+fn snap_down(day: f64, unit: Unit) -> f64 {
+    let (year, month, _) = civil_from_days(day.floor() as i64);
+    let start = match unit {
+        Unit::Day => return day.floor(),
+        Unit::Month => days_from_civil(year, month, 1),
+        Unit::Year => days_from_civil(year, 1, 1),
+        Unit::Decade => days_from_civil(year.div_euclid(10) * 10, 1, 1),
+        Unit::Century => days_from_civil(year.div_euclid(100) * 100, 1, 1),
+    };
+    start as f64
+}
+
+fn step(day: f64, unit: Unit) -> f64 {
+    let (year, month, _) = civil_from_days(day.floor() as i64);
+    let next = match unit {
+        Unit::Day => return day + 1.0,
+        Unit::Month if month == 12 => days_from_civil(year + 1, 1, 1),
+        Unit::Month => days_from_civil(year, month + 1, 1),
+        Unit::Year => days_from_civil(year + 1, 1, 1),
+        Unit::Decade => days_from_civil(year + 10, 1, 1),
+        Unit::Century => days_from_civil(year + 100, 1, 1),
+    };
+    next as f64
+}
+
+/// Rounds to the nearest start of `unit`.
+pub fn snap_to(day: f64, unit: Unit) -> f64 {
+    let down = snap_down(day, unit);
+    let up = step(down, unit);
+    if day - down < up - day {
+        down
+    } else {
+        up
+    }
+}
@@ -0,0 +1,101 @@
+//! The timeline view encoded in the page URL, so it can be bookmarked and
+//! shared, e.g. `/timeline?from=1914-01-01&to=1945-12-31&categories=war`.
+//!
+//! `from`/`to` give the visible range, `categories` a comma-separated list
+//! of categories to show (all when absent) and `group=category` turns on
+//! the swimlane layout, with `collapsed` listing the lanes folded away.
+//! Other parameters on the page are left alone.
+
+use wasm_bindgen::JsValue;
+use web_sys::UrlSearchParams;
+
+use crate::time_scale;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ViewState {
+    /// Visible range in days; the timeline picks one when unset.
+    pub range: Option<(f64, f64)>,
+    /// Categories to show; all when empty.
+    pub categories: Vec<String>,
+    pub grouped: bool,
+    /// Swimlanes shown folded to a single row.
+    pub collapsed: Vec<String>,
+}
+
+impl ViewState {
+    /// The view described by the current URL.
+    pub fn from_url() -> Self {
+        let search = gloo_utils::window().location().search().unwrap_or_default();
+        let Ok(params) = UrlSearchParams::new_with_str(&search) else {
+            return Self::default();
+        };
+
+        let day = |name: &str| params.get(name).as_deref().and_then(time_scale::parse_date);
+        let range = match (day("from"), day("to")) {
+            (Some(from), Some(to)) if from < to => Some((from, to)),
+            _ => None,
+        };
+        let list = |name: &str| {
+            params
+                .get(name)
+                .map(|list| list.split(',').filter(|name| !name.is_empty()).map(str::to_string).collect())
+                .unwrap_or_default()
+        };
+
+        Self {
+            range,
+            categories: list("categories"),
+            grouped: params.get("group").as_deref() == Some("category"),
+            collapsed: list("collapsed"),
+        }
+    }
+
+    /// Puts the view into the URL without adding a history entry. A `date`
+    /// parameter is dropped, as the range supersedes it.
+    pub fn write_to_url(&self) {
+        let window = gloo_utils::window();
+        let location = window.location();
+        let current = location.search().unwrap_or_default();
+        let Ok(params) = UrlSearchParams::new_with_str(&current) else {
+            return;
+        };
+
+        params.delete("date");
+        match self.range {
+            Some((from, to)) => {
+                params.set("from", &time_scale::format_day(from));
+                params.set("to", &time_scale::format_day(to));
+            }
+            None => {
+                params.delete("from");
+                params.delete("to");
+            }
+        }
+        if self.categories.is_empty() {
+            params.delete("categories");
+        } else {
+            params.set("categories", &self.categories.join(","));
+        }
+        if self.grouped {
+            params.set("group", "category");
+        } else {
+            params.delete("group");
+        }
+        if self.grouped && !self.collapsed.is_empty() {
+            params.set("collapsed", &self.collapsed.join(","));
+        } else {
+            params.delete("collapsed");
+        }
+
+        let query = String::from(params.to_string());
+        let search = if query.is_empty() { String::new() } else { format!("?{}", query) };
+        // Panning at day granularity often lands on the same URL.
+        if search == current {
+            return;
+        }
+        let url = format!("{}{}", location.pathname().unwrap_or_default(), search);
+        if let Ok(history) = window.history() {
+            history.replace_state_with_url(&JsValue::NULL, "", Some(&url)).ok();
+        }
+    }
+}